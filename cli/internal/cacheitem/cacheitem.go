@@ -18,8 +18,24 @@ var (
 	errNameMalformed        = errors.New("file name is malformed")
 	errNameWindowsUnsafe    = errors.New("file name is not Windows-safe")
 	errUnsupportedFileType  = errors.New("attempted to restore unsupported file type")
+	errUnsupportedVersion   = errors.New("artifact requires a newer version of turbo")
+	errMetadataMalformed    = errors.New("artifact metadata is malformed")
 )
 
+// artifactMetadataPath is the path of the metadata entry which newer versions of
+// turbo write as the first entry of an artifact. It only describes the artifact,
+// so it's never restored.
+const artifactMetadataPath = ".turbo-artifact.json"
+
+// artifactReaderVersion is the version of the artifact layout this reader restores.
+const artifactReaderVersion = 1
+
+// artifactMetadata is the content of the metadata entry.
+type artifactMetadata struct {
+	SchemaVersion    uint32 `json:"schemaVersion"`
+	MinReaderVersion uint32 `json:"minReaderVersion"`
+}
+
 // CacheItem is a `tar` utility with a little bit extra.
 type CacheItem struct {
 	// Path is the location on disk for the CacheItem.
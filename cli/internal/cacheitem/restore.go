@@ -2,6 +2,7 @@ package cacheitem
 
 import (
 	"archive/tar"
+	"encoding/json"
 	"errors"
 	"io"
 	"os"
@@ -87,6 +88,8 @@ func (ci *CacheItem) Restore(anchor turbopath.AbsoluteSystemPath) ([]turbopath.A
 		anchorAtDepth: []turbopath.AbsoluteSystemPath{anchor},
 	}
 
+	isFirst := true
+	hasMetadata := false
 	for {
 		header, trErr := tr.Next()
 		if trErr == io.EOF {
@@ -106,6 +109,24 @@ func (ci *CacheItem) Restore(anchor turbopath.AbsoluteSystemPath) ([]turbopath.A
 		// The reader will not advance until tr.Next is called.
 		// We can treat this as file metadata + body reader.
 
+		// A leading metadata entry describes the artifact and reserves its path,
+		// so no entry at that path is restored. Artifacts without it are restored
+		// as they are.
+		if header.Name == artifactMetadataPath {
+			if isFirst {
+				isFirst = false
+				hasMetadata = true
+				if metadataErr := checkArtifactMetadata(tr); metadataErr != nil {
+					return restored, metadataErr
+				}
+				continue
+			}
+			if hasMetadata {
+				continue
+			}
+		}
+		isFirst = false
+
 		// Attempt to place the file on disk.
 		file, restoreErr := restoreEntry(dirCache, anchor, header, tr)
 		if restoreErr != nil {
@@ -122,6 +143,18 @@ func (ci *CacheItem) Restore(anchor turbopath.AbsoluteSystemPath) ([]turbopath.A
 	return restored, closeError
 }
 
+// checkArtifactMetadata fails for artifacts which require a newer reader.
+func checkArtifactMetadata(reader io.Reader) error {
+	var metadata artifactMetadata
+	if err := json.NewDecoder(reader).Decode(&metadata); err != nil {
+		return errMetadataMalformed
+	}
+	if metadata.MinReaderVersion > artifactReaderVersion {
+		return errUnsupportedVersion
+	}
+	return nil
+}
+
 // restoreRegular is the entry point for all things read from the tar.
 func restoreEntry(dirCache *cachedDirTree, anchor turbopath.AbsoluteSystemPath, header *tar.Header, reader *tar.Reader) (turbopath.AnchoredSystemPath, error) {
 	// We're permissive on creation, but restrictive on restoration.
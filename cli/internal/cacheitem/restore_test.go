@@ -1454,6 +1454,50 @@ func TestCacheItem_Restore(t *testing.T) {
 			},
 			want: turbopath.AnchoredUnixPathArray{"target", "source", "one", "one/two"}.ToSystemPathArray(),
 		},
+		{
+			name: "artifact metadata",
+			tarFiles: []tarFile{
+				{
+					Header: &tar.Header{
+						Name:     ".turbo-artifact.json",
+						Typeflag: tar.TypeReg,
+						Mode:     0644,
+					},
+					Body: `{"schemaVersion":2,"minReaderVersion":1}`,
+				},
+				{
+					Header: &tar.Header{
+						Name:     "target",
+						Typeflag: tar.TypeReg,
+						Mode:     0644,
+					},
+					Body: "target",
+				},
+			},
+			want: turbopath.AnchoredUnixPathArray{"target"}.ToSystemPathArray(),
+		},
+		{
+			name: "legacy artifact with a file at the metadata path",
+			tarFiles: []tarFile{
+				{
+					Header: &tar.Header{
+						Name:     "target",
+						Typeflag: tar.TypeReg,
+						Mode:     0644,
+					},
+					Body: "target",
+				},
+				{
+					Header: &tar.Header{
+						Name:     ".turbo-artifact.json",
+						Typeflag: tar.TypeReg,
+						Mode:     0644,
+					},
+					Body: "{}",
+				},
+			},
+			want: turbopath.AnchoredUnixPathArray{"target", ".turbo-artifact.json"}.ToSystemPathArray(),
+		},
 	}
 	for _, tt := range tests {
 		getTestFunc := func(compressed bool) func(t *testing.T) {
@@ -1491,3 +1535,36 @@ func TestCacheItem_Restore(t *testing.T) {
 		t.Run(tt.name, getTestFunc(false))
 	}
 }
+
+func TestCacheItem_Restore_UnsupportedVersion(t *testing.T) {
+	archivePath := generateTar(t, []tarFile{
+		{
+			Header: &tar.Header{
+				Name:     ".turbo-artifact.json",
+				Typeflag: tar.TypeReg,
+				Mode:     0644,
+			},
+			Body: `{"schemaVersion":3,"minReaderVersion":2}`,
+		},
+		{
+			Header: &tar.Header{
+				Name:     "target",
+				Typeflag: tar.TypeReg,
+				Mode:     0644,
+			},
+			Body: "target",
+		},
+	})
+	anchor := generateAnchor(t)
+
+	cacheItem, err := Open(archivePath)
+	assert.NilError(t, err, "Open")
+
+	restoreOutput, restoreErr := cacheItem.Restore(anchor)
+	assert.ErrorIs(t, restoreErr, errUnsupportedVersion)
+	assert.Equal(t, len(restoreOutput), 0)
+	assert.NilError(t, cacheItem.Close(), "Close")
+
+	_, statErr := anchor.UntypedJoin("target").Lstat()
+	assert.Assert(t, errors.Is(statErr, os.ErrNotExist))
+}
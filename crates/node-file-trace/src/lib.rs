@@ -1,6 +1,7 @@
 #![feature(min_specialization)]
 
 mod nft_json;
+mod nft_list;
 
 use std::{
     collections::{BTreeSet, HashMap},
@@ -51,7 +52,10 @@ use turbopack_core::{
     resolve::options::{ImportMapping, ResolvedMap},
 };
 
-use crate::nft_json::NftJsonAssetVc;
+use crate::{
+    nft_json::{NftJsonAssetVc, NftJsonOptions},
+    nft_list::NftListAssetVc,
+};
 
 #[cfg(feature = "persistent_cache")]
 #[cfg_attr(feature = "cli", derive(clap::Args))]
@@ -158,6 +162,17 @@ pub enum Args {
         #[cfg_attr(feature = "cli", clap(flatten))]
         #[cfg_attr(feature = "node-api", serde(flatten))]
         common: CommonArgs,
+
+        /// Also list the references which couldn't be resolved while tracing.
+        #[cfg_attr(feature = "cli", clap(long))]
+        #[cfg_attr(feature = "node-api", serde(default))]
+        warnings: bool,
+
+        /// Add a *.nft.txt file instead, which lists the referenced files one
+        /// per line.
+        #[cfg_attr(feature = "cli", clap(long, conflicts_with = "warnings"))]
+        #[cfg_attr(feature = "node-api", serde(default))]
+        list: bool,
     },
 
     // Copy input files and all referenced files to the output directory
@@ -576,7 +591,11 @@ async fn main_operation(
 
             return Ok(StringsVc::cell(result.into_iter().collect::<Vec<_>>()));
         }
-        Args::Annotate { common: _ } => {
+        Args::Annotate {
+            common: _,
+            warnings,
+            list,
+        } => {
             let input = process_input(&dir, &context, input).unwrap();
             let mut output_nft_assets = Vec::new();
             let mut emits = Vec::new();
@@ -592,10 +611,22 @@ async fn main_operation(
             .await?
             .iter()
             {
-                let nft_asset = NftJsonAssetVc::new(*module);
+                let nft_asset = NftJsonAssetVc::new_with_options(
+                    *module,
+                    NftJsonOptions {
+                        warnings,
+                        ..Default::default()
+                    }
+                    .cell(),
+                );
+                let nft_asset: AssetVc = if list {
+                    NftListAssetVc::new(nft_asset).into()
+                } else {
+                    nft_asset.into()
+                };
                 let path = nft_asset.ident().path().await?.path.clone();
                 output_nft_assets.push(path);
-                emits.push(emit_asset(nft_asset.into()));
+                emits.push(emit_asset(nft_asset));
             }
             // Wait for all files to be emitted
             for emit in emits {
@@ -1,23 +1,59 @@
+use std::collections::BTreeSet;
+
 use anyhow::Result;
+use serde::Serialize;
 use serde_json::json;
-use turbo_tasks_fs::{File, FileSystem};
+use turbo_tasks::ValueToString;
+use turbo_tasks_fs::{
+    glob::Glob, File, FileSystem, FileSystemPath, FileSystemPathReadRef, FileSystemPathVc,
+};
 use turbopack_core::{
-    asset::{Asset, AssetContentVc, AssetVc},
+    asset::{Asset, AssetContentVc, AssetVc, AssetsVc},
     ident::AssetIdentVc,
+    issue::{resolve::ResolvingIssueVc, IssueVc},
     output::{OutputAsset, OutputAssetVc},
     reference::all_assets,
+    resolve::parse::Request,
 };
 
+/// Options of an [NftJsonAsset].
+#[turbo_tasks::value(shared)]
+#[derive(Default, Clone)]
+pub struct NftJsonOptions {
+    /// When not empty, only traced files matching one of these globs are
+    /// listed. The globs are matched case-sensitively against the whole path
+    /// relative to the base directory, without a leading `./`.
+    pub include_globs: Vec<String>,
+    /// Traced files matching one of these globs are not listed.
+    pub exclude_globs: Vec<String>,
+    /// Files that are always listed, even when they weren't traced or are
+    /// excluded by the globs.
+    pub force_include: Vec<FileSystemPathVc>,
+    /// The directory the listed files are relative to, e.g. the project root
+    /// for tools that resolve the files from there. Defaults to the directory
+    /// of the entry.
+    pub base: Option<FileSystemPathVc>,
+    /// Whether the references which couldn't be resolved while tracing are
+    /// listed in a `warnings` array.
+    pub warnings: bool,
+}
+
 #[turbo_tasks::value(shared)]
 pub struct NftJsonAsset {
     entry: AssetVc,
+    options: NftJsonOptionsVc,
 }
 
 #[turbo_tasks::value_impl]
 impl NftJsonAssetVc {
     #[turbo_tasks::function]
     pub fn new(entry: AssetVc) -> Self {
-        Self::cell(NftJsonAsset { entry })
+        Self::new_with_options(entry, NftJsonOptions::default().cell())
+    }
+
+    #[turbo_tasks::function]
+    pub fn new_with_options(entry: AssetVc, options: NftJsonOptionsVc) -> Self {
+        Self::cell(NftJsonAsset { entry, options })
     }
 }
 
@@ -36,28 +72,392 @@ impl Asset for NftJsonAsset {
 
     #[turbo_tasks::function]
     async fn content(&self) -> Result<AssetContentVc> {
-        let context = self.entry.ident().path().parent().await?;
+        let context = self.context().await?;
+        let result = self.files(&context).await?;
+        let mut json = json!({
+          "version": 1,
+          "files": result
+        });
+        if self.options.await?.warnings {
+            json["warnings"] = serde_json::to_value(
+                unresolved_references(all_assets(self.entry), &context).await?,
+            )?;
+        }
+
+        Ok(File::from(json.to_string()).into())
+    }
+}
+
+impl NftJsonAsset {
+    pub(crate) fn entry(&self) -> AssetVc {
+        self.entry
+    }
+
+    /// The directory the listed files are relative to.
+    pub(crate) async fn context(&self) -> Result<FileSystemPathReadRef> {
+        Ok(match self.options.await?.base {
+            Some(base) => base.await?,
+            None => self.entry.ident().path().parent().await?,
+        })
+    }
+
+    /// Returns the sorted and deduplicated paths of the traced files, relative
+    /// to `context`. The entry itself isn't listed.
+    pub(crate) async fn files(&self, context: &FileSystemPath) -> Result<Vec<String>> {
+        let options = self.options.await?;
         // For clippy -- This explicit deref is necessary
         let entry_path = &*self.entry.ident().path().await?;
-        let mut result = Vec::new();
-        if let Some(self_path) = context.get_relative_path_to(entry_path) {
-            let set = all_assets(self.entry);
-            for asset in set.await?.iter() {
-                let path = asset.ident().path().await?;
-                if let Some(rel_path) = context.get_relative_path_to(&path) {
-                    if rel_path != self_path {
-                        result.push(rel_path);
-                    }
+        let Some(self_path) = context.get_relative_path_to(entry_path) else {
+            return Ok(Vec::new());
+        };
+        let mut traced = Vec::new();
+        for asset in all_assets(self.entry).await?.iter() {
+            let path = asset.ident().path().await?;
+            if let Some(rel_path) = context.get_relative_path_to(&path) {
+                if rel_path != self_path {
+                    traced.push(rel_path);
                 }
             }
-            result.sort();
-            result.dedup();
         }
-        let json = json!({
-          "version": 1,
-          "files": result
+        let mut forced = Vec::new();
+        for path in options.force_include.iter() {
+            let path = path.await?;
+            if let Some(rel_path) = context.get_relative_path_to(&path) {
+                if rel_path != self_path {
+                    forced.push(rel_path);
+                }
+            }
+        }
+        Ok(FileFilters::new(&options.include_globs, &options.exclude_globs)?.apply(traced, forced))
+    }
+}
+
+/// Why a reference couldn't be resolved, see [TraceWarning].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "kebab-case")]
+enum TraceWarningReason {
+    /// The request is computed at runtime, e.g. `require(variable)`.
+    DynamicExpression,
+    /// The requested file doesn't exist.
+    MissingFile,
+    /// The request is an url whose scheme can't be traced.
+    UnsupportedScheme,
+}
+
+impl TraceWarningReason {
+    fn of(request: &Request) -> Self {
+        match request {
+            Request::Uri { .. } => TraceWarningReason::UnsupportedScheme,
+            Request::Dynamic => TraceWarningReason::DynamicExpression,
+            request if request.request().is_some() => TraceWarningReason::MissingFile,
+            _ => TraceWarningReason::DynamicExpression,
+        }
+    }
+}
+
+/// A reference which couldn't be resolved while tracing, listed in the
+/// `warnings` of an [NftJsonAsset].
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+struct TraceWarning {
+    /// The referencing file, relative like the listed files.
+    file: String,
+    request: String,
+    reason: TraceWarningReason,
+}
+
+/// Collects the references which couldn't be resolved while tracing `assets`
+/// from the resolving issues emitted during the traversal. The warnings are
+/// sorted and deduplicated.
+async fn unresolved_references(
+    assets: AssetsVc,
+    context: &FileSystemPath,
+) -> Result<Vec<TraceWarning>> {
+    let issues = IssueVc::peek_issues_with_path(assets).await?.await?;
+    let mut warnings = BTreeSet::new();
+    for issue in issues.iter() {
+        let Some(issue) = ResolvingIssueVc::resolve_from(issue).await? else {
+            continue;
+        };
+        let issue = issue.await?;
+        let Some(file) = context.get_relative_path_to(&*issue.context.await?) else {
+            continue;
+        };
+        let request = issue.request.await?;
+        let raw_request = match request.request() {
+            Some(raw_request) => raw_request,
+            None => issue.request.to_string().await?.clone_value(),
+        };
+        warnings.insert(TraceWarning {
+            file,
+            request: raw_request,
+            reason: TraceWarningReason::of(&request),
         });
+    }
+    Ok(warnings.into_iter().collect())
+}
 
-        Ok(File::from(json.to_string()).into())
+/// The include and exclude globs of an [NftJsonAsset].
+struct FileFilters {
+    include: Vec<Glob>,
+    exclude: Vec<Glob>,
+}
+
+impl FileFilters {
+    fn new(include_globs: &[String], exclude_globs: &[String]) -> Result<Self> {
+        Ok(FileFilters {
+            include: include_globs
+                .iter()
+                .map(|glob| Glob::parse(glob))
+                .collect::<Result<_>>()?,
+            exclude: exclude_globs
+                .iter()
+                .map(|glob| Glob::parse(glob))
+                .collect::<Result<_>>()?,
+        })
+    }
+
+    /// Whether a traced file is listed. `path` is relative to the base
+    /// directory as listed, i.e. starts with `./` or `../`. The globs are
+    /// matched against the path without the leading `./`, so that
+    /// `node_modules/**` matches `./node_modules/pkg/index.js`.
+    fn is_included(&self, path: &str) -> bool {
+        let path = path.strip_prefix("./").unwrap_or(path);
+        (self.include.is_empty() || self.include.iter().any(|glob| glob.execute(path)))
+            && !self.exclude.iter().any(|glob| glob.execute(path))
+    }
+
+    /// Returns the sorted list of files to list. Force included files are
+    /// always listed, regardless of the globs.
+    fn apply(&self, traced: Vec<String>, forced: Vec<String>) -> Vec<String> {
+        let mut files: Vec<_> = traced
+            .into_iter()
+            .filter(|path| self.is_included(path))
+            .chain(forced)
+            .collect();
+        files.sort();
+        files.dedup();
+        files
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use anyhow::Result;
+    use serde_json::json;
+    use turbo_tasks::{NothingVc, TurboTasks, Value};
+    use turbo_tasks_fs::{DiskFileSystemVc, FileSystem};
+    use turbo_tasks_memory::MemoryBackend;
+    use turbopack::{
+        module_options::ModuleOptionsContext, resolve_options_context::ResolveOptionsContext,
+        transition::TransitionsByNameVc, ModuleAssetContextVc,
+    };
+    use turbopack_core::{
+        asset::{Asset, AssetVc},
+        compile_time_info::CompileTimeInfoVc,
+        context::{AssetContext, AssetContextVc},
+        environment::{EnvironmentVc, ExecutionEnvironment, NodeJsEnvironment},
+        file_source::FileSourceVc,
+        reference_type::ReferenceType,
+        resolve::{parse::Request, pattern::Pattern},
+    };
+
+    use super::{FileFilters, NftJsonAssetVc, NftJsonOptions, TraceWarningReason};
+
+    fn filters(include: &[&str], exclude: &[&str]) -> FileFilters {
+        let to_strings =
+            |globs: &[&str]| -> Vec<String> { globs.iter().map(|glob| glob.to_string()).collect() };
+        FileFilters::new(&to_strings(include), &to_strings(exclude)).unwrap()
+    }
+
+    #[test]
+    fn exclude() {
+        let filters = filters(&[], &["**/*.md", "LICENSE"]);
+        assert!(filters.is_included("./index.js"));
+        assert!(filters.is_included("./node_modules/pkg/LICENSE"));
+        assert!(!filters.is_included("./LICENSE"));
+        assert!(!filters.is_included("./README.md"));
+        assert!(!filters.is_included("./node_modules/pkg/README.md"));
+        assert!(!filters.is_included("../shared/README.md"));
+    }
+
+    #[test]
+    fn include() {
+        let filters = filters(&["node_modules/**"], &["**/test/**"]);
+        assert!(filters.is_included("./node_modules/pkg/index.js"));
+        assert!(!filters.is_included("./index.js"));
+        assert!(!filters.is_included("./node_modules/pkg/test/fixture.js"));
+    }
+
+    #[test]
+    fn outside_of_base() {
+        let filters = filters(&["../shared/**"], &[]);
+        assert!(filters.is_included("../shared/util.js"));
+        assert!(!filters.is_included("./shared/util.js"));
+    }
+
+    #[test]
+    fn force_include_wins() {
+        let filters = filters(&["node_modules/**"], &["**/*.node"]);
+        assert_eq!(
+            filters.apply(
+                vec![
+                    "./node_modules/pkg/index.js".to_string(),
+                    "./node_modules/pkg/binding.node".to_string(),
+                    "./index.js".to_string(),
+                ],
+                vec![
+                    "./node_modules/native/binding.node".to_string(),
+                    "./native.node".to_string(),
+                    "./node_modules/pkg/index.js".to_string(),
+                ],
+            ),
+            vec![
+                "./native.node",
+                "./node_modules/native/binding.node",
+                "./node_modules/pkg/index.js",
+            ]
+        );
+    }
+
+    #[test]
+    fn case_sensitive() {
+        let filters = filters(&[], &["**/*.md"]);
+        assert!(filters.is_included("./README.MD"));
+    }
+
+    #[test]
+    fn trace_warning_reasons() {
+        let reason = |pattern| TraceWarningReason::of(&Request::parse(pattern));
+        assert_eq!(
+            reason(Pattern::Dynamic),
+            TraceWarningReason::DynamicExpression
+        );
+        assert_eq!(
+            reason(Pattern::Concatenation(vec![
+                Pattern::Constant("./locales/".to_string()),
+                Pattern::Dynamic,
+            ])),
+            TraceWarningReason::DynamicExpression
+        );
+        assert_eq!(
+            reason(Pattern::Constant("./missing.js".to_string())),
+            TraceWarningReason::MissingFile
+        );
+        assert_eq!(
+            reason(Pattern::Constant("https://example.com/lib.js".to_string())),
+            TraceWarningReason::UnsupportedScheme
+        );
+    }
+
+    /// Processes `index.js` in the fixture directory `tests/<fixture>`.
+    fn entry(fixture: &str) -> AssetVc {
+        let fs = DiskFileSystemVc::new(
+            "project".to_string(),
+            format!("{}/tests/{fixture}", env!("CARGO_MANIFEST_DIR")),
+        );
+        let env = EnvironmentVc::new(Value::new(ExecutionEnvironment::NodeJsLambda(
+            NodeJsEnvironment::default().into(),
+        )));
+        let context: AssetContextVc = ModuleAssetContextVc::new(
+            TransitionsByNameVc::cell(HashMap::new()),
+            CompileTimeInfoVc::new(env),
+            ModuleOptionsContext::default().cell(),
+            ResolveOptionsContext::default().cell(),
+        )
+        .into();
+        context
+            .process(
+                FileSourceVc::new(fs.root().join("index.js")).into(),
+                Value::new(ReferenceType::Undefined),
+            )
+            .into()
+    }
+
+    async fn json(nft_json: NftJsonAssetVc) -> Result<serde_json::Value> {
+        let content = nft_json.content().file_content().await?;
+        Ok(serde_json::from_str(
+            &content.as_content().unwrap().content().to_str()?,
+        )?)
+    }
+
+    /// Returns the files listed for `entry` with the include and exclude
+    /// globs.
+    async fn files(
+        entry: AssetVc,
+        include: &[&str],
+        exclude: &[&str],
+    ) -> Result<serde_json::Value> {
+        let options = NftJsonOptions {
+            include_globs: include.iter().map(|glob| glob.to_string()).collect(),
+            exclude_globs: exclude.iter().map(|glob| glob.to_string()).collect(),
+            ..Default::default()
+        };
+        let json = json(NftJsonAssetVc::new_with_options(entry, options.cell())).await?;
+        Ok(json["files"].clone())
+    }
+
+    #[tokio::test]
+    async fn filters_traced_files() -> Result<()> {
+        crate::register();
+
+        let tt = TurboTasks::new(MemoryBackend::default());
+        let task = tt.spawn_once_task(async move {
+            let entry = entry("filters");
+            assert_eq!(
+                files(entry, &[], &[]).await?,
+                json!(["./lib/a.js", "./lib/a.test.js", "./vendor/b.js"])
+            );
+            assert_eq!(
+                files(entry, &["lib/**"], &[]).await?,
+                json!(["./lib/a.js", "./lib/a.test.js"])
+            );
+            assert_eq!(
+                files(entry, &[], &["**/*.test.js"]).await?,
+                json!(["./lib/a.js", "./vendor/b.js"])
+            );
+            assert_eq!(
+                files(entry, &["lib/**"], &["**/*.test.js"]).await?,
+                json!(["./lib/a.js"])
+            );
+
+            Ok(NothingVc::new().into())
+        });
+        tt.wait_task_completion(task, true).await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn warnings() -> Result<()> {
+        crate::register();
+
+        let tt = TurboTasks::new(MemoryBackend::default());
+        let task = tt.spawn_once_task(async move {
+            let options = NftJsonOptions {
+                warnings: true,
+                ..Default::default()
+            };
+            let json = json(NftJsonAssetVc::new_with_options(
+                entry("warnings"),
+                options.cell(),
+            ))
+            .await?;
+            assert_eq!(json["files"], json!(["./config.js"]));
+            assert_eq!(
+                json["warnings"],
+                json!([{
+                    "file": "./index.js",
+                    "request": "dynamic",
+                    "reason": "dynamic-expression",
+                }])
+            );
+
+            Ok(NothingVc::new().into())
+        });
+        tt.wait_task_completion(task, true).await?;
+
+        Ok(())
     }
 }
@@ -0,0 +1,157 @@
+use anyhow::Result;
+use turbo_tasks_fs::{File, FileSystem};
+use turbopack_core::{
+    asset::{Asset, AssetContentVc},
+    ident::AssetIdentVc,
+    output::{OutputAsset, OutputAssetVc},
+};
+
+use crate::nft_json::NftJsonAssetVc;
+
+/// Lists the same files as an [NftJsonAsset] as plain text, one per line, for
+/// tools which don't read json, e.g. shell scripts or `tar -T`.
+///
+/// Every line, including the last one, ends with a newline, so lists can be
+/// concatenated.
+///
+/// [NftJsonAsset]: crate::nft_json::NftJsonAsset
+#[turbo_tasks::value(shared)]
+pub struct NftListAsset {
+    nft_json: NftJsonAssetVc,
+}
+
+#[turbo_tasks::value_impl]
+impl NftListAssetVc {
+    /// Creates a list of the files of `nft_json`, which uses its filters and
+    /// base directory. Warnings aren't listed.
+    #[turbo_tasks::function]
+    pub fn new(nft_json: NftJsonAssetVc) -> Self {
+        Self::cell(NftListAsset { nft_json })
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl OutputAsset for NftListAsset {}
+
+#[turbo_tasks::value_impl]
+impl Asset for NftListAsset {
+    #[turbo_tasks::function]
+    async fn ident(&self) -> Result<AssetIdentVc> {
+        let path = self.nft_json.await?.entry().ident().path().await?;
+        Ok(AssetIdentVc::from_path(
+            path.fs.root().join(&format!("{}.nft.txt", path.path)),
+        ))
+    }
+
+    #[turbo_tasks::function]
+    async fn content(&self) -> Result<AssetContentVc> {
+        let nft_json = self.nft_json.await?;
+        let context = nft_json.context().await?;
+        let files = nft_json.files(&context).await?;
+        Ok(File::from(to_list(&files)).into())
+    }
+}
+
+/// Joins the files into lines. Paths of a [FileSystemPath] always use forward
+/// slashes as separators.
+///
+/// [FileSystemPath]: turbo_tasks_fs::FileSystemPath
+fn to_list(files: &[String]) -> String {
+    files.iter().map(|file| format!("{file}\n")).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use anyhow::Result;
+    use turbo_tasks::{NothingVc, TurboTasks, Value};
+    use turbo_tasks_fs::{DiskFileSystemVc, FileSystem};
+    use turbo_tasks_memory::MemoryBackend;
+    use turbopack::{
+        module_options::ModuleOptionsContext, resolve_options_context::ResolveOptionsContext,
+        transition::TransitionsByNameVc, ModuleAssetContextVc,
+    };
+    use turbopack_core::{
+        asset::Asset,
+        compile_time_info::CompileTimeInfoVc,
+        context::{AssetContext, AssetContextVc},
+        environment::{EnvironmentVc, ExecutionEnvironment, NodeJsEnvironment},
+        file_source::FileSourceVc,
+        reference_type::ReferenceType,
+    };
+
+    use super::{to_list, NftListAssetVc};
+    use crate::nft_json::{NftJsonAssetVc, NftJsonOptions};
+
+    #[test]
+    fn trailing_newline() {
+        assert_eq!(to_list(&[]), "");
+        assert_eq!(
+            to_list(&[
+                "./a.js".to_string(),
+                "./node_modules/b/index.js".to_string()
+            ]),
+            "./a.js\n./node_modules/b/index.js\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn lists_files_of_nft_json() -> Result<()> {
+        crate::register();
+
+        let tt = TurboTasks::new(MemoryBackend::default());
+        let task = tt.spawn_once_task(async move {
+            let fs = DiskFileSystemVc::new(
+                "project".to_string(),
+                concat!(env!("CARGO_MANIFEST_DIR"), "/tests/list").to_string(),
+            );
+            let env = EnvironmentVc::new(Value::new(ExecutionEnvironment::NodeJsLambda(
+                NodeJsEnvironment::default().into(),
+            )));
+            let context: AssetContextVc = ModuleAssetContextVc::new(
+                TransitionsByNameVc::cell(HashMap::new()),
+                CompileTimeInfoVc::new(env),
+                ModuleOptionsContext::default().cell(),
+                ResolveOptionsContext::default().cell(),
+            )
+            .into();
+            let entry = context.process(
+                FileSourceVc::new(fs.root().join("index.js")).into(),
+                Value::new(ReferenceType::Undefined),
+            );
+
+            let nft_list = NftListAssetVc::new(NftJsonAssetVc::new(entry.into()));
+            assert_eq!(
+                nft_list.ident().path().await?.path,
+                "index.js.nft.txt".to_string()
+            );
+            let content = nft_list.content().file_content().await?;
+            // `b.js` is referenced twice, but listed once.
+            assert_eq!(
+                content.as_content().unwrap().content().to_str()?,
+                "./b.js\n./lib/a.js\n"
+            );
+
+            // The list uses the globs of the nft.json asset.
+            let nft_list = NftListAssetVc::new(NftJsonAssetVc::new_with_options(
+                entry.into(),
+                NftJsonOptions {
+                    include_globs: vec!["lib/**".to_string()],
+                    ..Default::default()
+                }
+                .cell(),
+            ));
+            let content = nft_list.content().file_content().await?;
+            assert_eq!(
+                content.as_content().unwrap().content().to_str()?,
+                "./lib/a.js\n"
+            );
+
+            Ok(NothingVc::new().into())
+        });
+        tt.wait_task_completion(task, true).await?;
+
+        Ok(())
+    }
+}
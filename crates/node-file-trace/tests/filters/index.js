@@ -0,0 +1,5 @@
+const a = require("./lib/a.js");
+require("./lib/a.test.js");
+const b = require("./vendor/b.js");
+
+module.exports = a + b;
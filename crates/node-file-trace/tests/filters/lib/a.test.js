@@ -0,0 +1,5 @@
+const a = require("./a.js");
+
+if (a !== "a") {
+  throw new Error("unexpected export");
+}
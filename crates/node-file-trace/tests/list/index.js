@@ -0,0 +1,2 @@
+require("./lib/a.js");
+require("./b.js");
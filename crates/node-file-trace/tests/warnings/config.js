@@ -0,0 +1 @@
+exports.plugin = process.env.PLUGIN;
@@ -0,0 +1,4 @@
+const { plugin } = require("./config.js");
+
+// The traced files can't be determined for this require.
+require(plugin);
@@ -0,0 +1,56 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+use event_listener::{Event, EventListener};
+
+/// A signal which aborts a graph traversal which was started with
+/// [`GraphTraversal::visit_with_abort`](super::GraphTraversal::visit_with_abort),
+/// e.g. when the request which needs the traversal was cancelled.
+///
+/// Clones share the same state, so the signal can be aborted from another task
+/// than the one which polls the traversal.
+#[derive(Clone, Default)]
+pub struct AbortSignal {
+    inner: Arc<AbortSignalInner>,
+}
+
+struct AbortSignalInner {
+    aborted: AtomicBool,
+    event: Event,
+}
+
+impl Default for AbortSignalInner {
+    fn default() -> Self {
+        Self {
+            aborted: AtomicBool::new(false),
+            event: Event::new(),
+        }
+    }
+}
+
+impl AbortSignal {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Aborts all traversals which use this signal. They are woken up, and
+    /// resolve with the nodes which were visited so far.
+    pub fn abort(&self) {
+        if !self.inner.aborted.swap(true, Ordering::SeqCst) {
+            self.inner.event.notify(usize::MAX);
+        }
+    }
+
+    pub fn is_aborted(&self) -> bool {
+        self.inner.aborted.load(Ordering::SeqCst)
+    }
+
+    /// Returns a listener which resolves once the signal is aborted. The
+    /// signal must be checked again after the listener was created, since an
+    /// abort before that isn't observed by it.
+    pub(super) fn listen(&self) -> EventListener {
+        self.inner.event.listen()
+    }
+}
@@ -0,0 +1,156 @@
+use std::collections::HashSet;
+
+use super::graph_store::{GraphNode, GraphStore};
+
+/// A [`GraphStore`] wrapper that stops accepting nodes once `limit` nodes
+/// were accepted by the underlying store. Nodes that are rejected are neither
+/// stored nor expanded, which causes the traversal to stop once all pending
+/// edges are visited. The store is only truncated when a node that wasn't
+/// visited before is rejected, edges to visited nodes don't lose any nodes.
+///
+/// When combined with [`SkipDuplicates`](super::SkipDuplicates), the capped
+/// store should wrap the de-duplicating store so that only unique nodes count
+/// towards the limit.
+#[derive(Debug)]
+pub struct CappedStore<StoreImpl>
+where
+    StoreImpl: GraphStore,
+{
+    store: StoreImpl,
+    limit: usize,
+    count: usize,
+    visited: HashSet<StoreImpl::Node>,
+    truncated: bool,
+}
+
+impl<StoreImpl> CappedStore<StoreImpl>
+where
+    StoreImpl: GraphStore,
+{
+    pub fn new(store: StoreImpl, limit: usize) -> Self {
+        Self {
+            store,
+            limit,
+            count: 0,
+            visited: Default::default(),
+            truncated: false,
+        }
+    }
+
+    /// Returns whether any node was dropped because the limit was reached.
+    pub fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+
+    /// Consumes the wrapper and returns the underlying store.
+    pub fn into_inner(self) -> StoreImpl {
+        self.store
+    }
+}
+
+impl<StoreImpl> GraphStore for CappedStore<StoreImpl>
+where
+    StoreImpl: GraphStore,
+    StoreImpl::Node: Eq + std::hash::Hash + Clone,
+{
+    type Node = StoreImpl::Node;
+    type Handle = StoreImpl::Handle;
+
+    fn insert(
+        &mut self,
+        from_handle: Option<Self::Handle>,
+        node: GraphNode<StoreImpl::Node>,
+    ) -> Option<(Self::Handle, &StoreImpl::Node)> {
+        if self.count >= self.limit {
+            if !self.visited.contains(node.node()) {
+                self.truncated = true;
+            }
+            return None;
+        }
+        self.visited.insert(node.node().clone());
+        let inserted = self.store.insert(from_handle, node);
+        if inserted.is_some() {
+            self.count += 1;
+        }
+        inserted
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use super::CappedStore;
+    use crate::graph::{GraphTraversal, NonDeterministic, OrderedNodes};
+
+    /// An infinite binary tree, where node `n` has the children `2n + 1` and
+    /// `2n + 2`.
+    async fn children(node: u64) -> Result<[u64; 2]> {
+        Ok([node * 2 + 1, node * 2 + 2])
+    }
+
+    #[tokio::test]
+    async fn terminates_with_exactly_limit_nodes() -> Result<()> {
+        let store = CappedStore::new(NonDeterministic::new(), 10)
+            .visit([0u64], |&node: &u64| children(node))
+            .await
+            .completed()?;
+        assert!(store.is_truncated());
+        assert_eq!(store.into_inner().into_iter().count(), 10);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn counts_unique_nodes_only() -> Result<()> {
+        // Every node points back to the root as well, which must not count
+        // towards the limit.
+        let store = CappedStore::new(OrderedNodes::new().skip_duplicates(), 5)
+            .visit([0u64], |&node: &u64| async move {
+                Ok([0, node * 2 + 1, node * 2 + 2])
+            })
+            .await
+            .completed()?;
+        assert!(store.is_truncated());
+        let nodes = store.into_inner().into_inner().into_inner();
+        assert_eq!(nodes.len(), 5);
+        assert_eq!(nodes[0], 0);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn not_truncated_at_limit() -> Result<()> {
+        // Exactly `limit` nodes, where the last one points back to the root.
+        let store = CappedStore::new(OrderedNodes::new(), 4)
+            .visit([0u64], |&node: &u64| async move {
+                Ok(if node < 3 { vec![node + 1] } else { vec![0] })
+            })
+            .await
+            .completed()?;
+        assert!(!store.is_truncated());
+        assert_eq!(store.into_inner().into_inner(), [0, 1, 2, 3]);
+
+        // One more node is dropped.
+        let store = CappedStore::new(OrderedNodes::new(), 4)
+            .visit([0u64], |&node: &u64| async move {
+                Ok(if node < 4 { vec![node + 1] } else { vec![0] })
+            })
+            .await
+            .completed()?;
+        assert!(store.is_truncated());
+        assert_eq!(store.into_inner().into_inner(), [0, 1, 2, 3]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn not_truncated_below_limit() -> Result<()> {
+        let store = CappedStore::new(NonDeterministic::new(), 10)
+            .visit([0u64], |&node: &u64| async move {
+                Ok(if node < 3 { vec![node + 1] } else { vec![] })
+            })
+            .await
+            .completed()?;
+        assert!(!store.is_truncated());
+        assert_eq!(store.into_inner().into_iter().count(), 4);
+        Ok(())
+    }
+}
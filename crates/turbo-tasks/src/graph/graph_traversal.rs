@@ -5,8 +5,10 @@ use futures::{stream::FuturesUnordered, Stream};
 
 use super::{
     graph_store::{GraphNode, GraphStore},
+    graph_traversal_stream::GraphTraversalStream,
+    graph_traversal_with_abort::GraphTraversalWithAbortFuture,
     with_future::With,
-    SkipDuplicates, Visit, VisitControlFlow,
+    AbortSignal, SkipDuplicates, Visit, VisitControlFlow,
 };
 
 /// [`GraphTraversal`] is a utility type that can be used to traverse a graph of
@@ -23,6 +25,26 @@ pub trait GraphTraversal: GraphStore + Sized {
         VisitImpl: Visit<Self::Node, Abort, Impl>,
         RootEdgesIt: IntoIterator<Item = VisitImpl::Edge>;
 
+    fn visit_stream<RootEdgesIt, VisitImpl, Impl>(
+        self,
+        root_edges: RootEdgesIt,
+        visit: VisitImpl,
+    ) -> GraphTraversalStream<Self, VisitImpl, Impl>
+    where
+        Self::Node: Eq + std::hash::Hash + Clone,
+        VisitImpl: Visit<Self::Node, !, Impl>,
+        RootEdgesIt: IntoIterator<Item = VisitImpl::Edge>;
+
+    fn visit_with_abort<RootEdgesIt, VisitImpl, Impl>(
+        self,
+        root_edges: RootEdgesIt,
+        visit: VisitImpl,
+        signal: AbortSignal,
+    ) -> GraphTraversalWithAbortFuture<Self, VisitImpl, Impl>
+    where
+        VisitImpl: Visit<Self::Node, !, Impl>,
+        RootEdgesIt: IntoIterator<Item = VisitImpl::Edge>;
+
     fn skip_duplicates(self) -> SkipDuplicates<Self>;
 }
 
@@ -69,6 +91,41 @@ where
         }
     }
 
+    /// Visits the graph starting from the given `roots`, and returns a stream
+    /// of the discovered nodes, which yields every node once, as soon as it is
+    /// discovered. The order is nondeterministic. The store can be obtained
+    /// from the stream once it is exhausted.
+    fn visit_stream<RootEdgesIt, VisitImpl, Impl>(
+        self,
+        root_edges: RootEdgesIt,
+        visit: VisitImpl,
+    ) -> GraphTraversalStream<Self, VisitImpl, Impl>
+    where
+        Self::Node: Eq + std::hash::Hash + Clone,
+        VisitImpl: Visit<Self::Node, !, Impl>,
+        RootEdgesIt: IntoIterator<Item = VisitImpl::Edge>,
+    {
+        GraphTraversalStream::new(self, root_edges, visit)
+    }
+
+    /// Visits the graph starting from the given `roots`, like
+    /// [`GraphTraversal::visit`], but stops as soon as the given `signal` is
+    /// aborted. In that case the future resolves to
+    /// [`GraphTraversalResult::Aborted`] with the partial store, and all
+    /// pending edges futures are dropped.
+    fn visit_with_abort<RootEdgesIt, VisitImpl, Impl>(
+        self,
+        root_edges: RootEdgesIt,
+        visit: VisitImpl,
+        signal: AbortSignal,
+    ) -> GraphTraversalWithAbortFuture<Self, VisitImpl, Impl>
+    where
+        VisitImpl: Visit<Self::Node, !, Impl>,
+        RootEdgesIt: IntoIterator<Item = VisitImpl::Edge>,
+    {
+        GraphTraversalWithAbortFuture::new(self, root_edges, visit, signal)
+    }
+
     fn skip_duplicates(self) -> SkipDuplicates<Self> {
         SkipDuplicates::new(self)
     }
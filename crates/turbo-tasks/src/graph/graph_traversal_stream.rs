@@ -0,0 +1,273 @@
+use std::{
+    collections::{HashSet, VecDeque},
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use anyhow::Result;
+use futures::{stream::FuturesUnordered, Stream};
+
+use super::{
+    graph_store::{GraphNode, GraphStore},
+    with_future::With,
+    Visit, VisitControlFlow,
+};
+
+/// The number of discovered nodes which are buffered for the consumer of a
+/// [`GraphTraversalStream`]. No further edges are expanded while the buffer is
+/// full.
+const DISCOVERED_BUFFER_SIZE: usize = 16;
+
+/// A stream of the nodes discovered by a graph traversal, see
+/// [`GraphTraversal::visit_stream`](super::GraphTraversal::visit_stream).
+///
+/// Every node is yielded once, as soon as the edges future of the node it was
+/// discovered from resolves. Since edges futures are polled concurrently, the
+/// order of the nodes is nondeterministic. When an edges future fails, the
+/// already discovered nodes are yielded before the error, and the stream ends
+/// after it.
+///
+/// The traversal only makes progress while the stream is polled. Dropping the
+/// stream drops all pending edges futures.
+pub struct GraphTraversalStream<Store, VisitImpl, Impl>
+where
+    Store: GraphStore,
+    VisitImpl: Visit<Store::Node, !, Impl>,
+{
+    store: Store,
+    futures: FuturesUnordered<With<VisitImpl::EdgesFuture, Store::Handle>>,
+    visit: VisitImpl,
+    discovered: VecDeque<Store::Node>,
+    yielded: HashSet<Store::Node>,
+    /// The error of a failed edges future, which is yielded after the
+    /// buffered nodes.
+    error: Option<anyhow::Error>,
+    failed: bool,
+}
+
+impl<Store, VisitImpl, Impl> GraphTraversalStream<Store, VisitImpl, Impl>
+where
+    Store: GraphStore,
+    Store::Node: Eq + std::hash::Hash + Clone,
+    VisitImpl: Visit<Store::Node, !, Impl>,
+{
+    pub(super) fn new(
+        store: Store,
+        root_edges: impl IntoIterator<Item = VisitImpl::Edge>,
+        visit: VisitImpl,
+    ) -> Self {
+        let mut stream = Self {
+            store,
+            futures: FuturesUnordered::new(),
+            visit,
+            discovered: VecDeque::with_capacity(DISCOVERED_BUFFER_SIZE),
+            yielded: HashSet::new(),
+            error: None,
+            failed: false,
+        };
+        for edge in root_edges {
+            stream.visit_edge(None, edge);
+        }
+        stream
+    }
+
+    /// Returns the store which was built by the traversal, once the stream
+    /// has been exhausted. Returns `None` when the stream was not exhausted or
+    /// the traversal failed.
+    pub fn into_store(self) -> Option<Store> {
+        if self.failed || !self.futures.is_empty() || !self.discovered.is_empty() {
+            return None;
+        }
+        Some(self.store)
+    }
+
+    fn visit_edge(&mut self, from_handle: Option<Store::Handle>, edge: VisitImpl::Edge) {
+        match self.visit.visit(edge) {
+            VisitControlFlow::Continue(node) => {
+                let span = self.visit.span(&node);
+                self.discover(&node);
+                if let Some((node_handle, node_ref)) =
+                    self.store.insert(from_handle, GraphNode(node))
+                {
+                    self.futures
+                        .push(With::new(self.visit.edges(node_ref), span, node_handle));
+                }
+            }
+            VisitControlFlow::Skip(node) => {
+                self.discover(&node);
+                self.store.insert(from_handle, GraphNode(node));
+            }
+            VisitControlFlow::Abort(abort) => abort,
+        }
+    }
+
+    fn discover(&mut self, node: &Store::Node) {
+        if self.yielded.insert(node.clone()) {
+            self.discovered.push_back(node.clone());
+        }
+    }
+}
+
+impl<Store, VisitImpl, Impl> Stream for GraphTraversalStream<Store, VisitImpl, Impl>
+where
+    Store: GraphStore,
+    Store::Node: Eq + std::hash::Hash + Clone,
+    VisitImpl: Visit<Store::Node, !, Impl>,
+{
+    type Item = Result<Store::Node>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        // SAFETY: Only the edges futures are pinned, and they are owned by
+        // `FuturesUnordered`, which never moves them.
+        let this = unsafe { self.get_unchecked_mut() };
+
+        while !this.failed && this.discovered.len() < DISCOVERED_BUFFER_SIZE {
+            match Pin::new(&mut this.futures).poll_next(cx) {
+                Poll::Ready(Some((node_handle, span, Ok(edges)))) => {
+                    let _guard = span.enter();
+                    for edge in edges {
+                        this.visit_edge(Some(node_handle.clone()), edge);
+                    }
+                }
+                Poll::Ready(Some((_, _, Err(err)))) => {
+                    this.failed = true;
+                    this.futures.clear();
+                    this.error = Some(err);
+                }
+                Poll::Ready(None) | Poll::Pending => break,
+            }
+        }
+
+        match this.discovered.pop_front() {
+            Some(node) => Poll::Ready(Some(Ok(node))),
+            None if this.failed => Poll::Ready(this.error.take().map(Err)),
+            None if this.futures.is_empty() => Poll::Ready(None),
+            None => Poll::Pending,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    use anyhow::{bail, Result};
+    use futures::StreamExt;
+
+    use super::DISCOVERED_BUFFER_SIZE;
+    use crate::graph::{AdjacencyMap, GraphTraversal, NonDeterministic};
+
+    /// Counts the edges futures which are alive.
+    struct Live(Arc<AtomicUsize>);
+
+    impl Live {
+        fn new(count: &Arc<AtomicUsize>) -> Self {
+            count.fetch_add(1, Ordering::SeqCst);
+            Self(count.clone())
+        }
+    }
+
+    impl Drop for Live {
+        fn drop(&mut self) {
+            self.0.fetch_sub(1, Ordering::SeqCst);
+        }
+    }
+
+    #[tokio::test]
+    async fn yields_each_node_once() -> Result<()> {
+        // `a` and `b` both point to `c`, which points back to `a`.
+        let mut stream =
+            AdjacencyMap::new()
+                .skip_duplicates()
+                .visit_stream(['a'], |&node: &char| async move {
+                    Ok(match node {
+                        'a' => vec!['b', 'c'],
+                        'b' => vec!['c'],
+                        'c' => vec!['a'],
+                        _ => vec![],
+                    })
+                });
+        let mut nodes = Vec::new();
+        while let Some(node) = stream.next().await {
+            nodes.push(node?);
+        }
+        nodes.sort();
+        assert_eq!(nodes, ['a', 'b', 'c']);
+
+        let Some(graph) = stream.into_store() else {
+            bail!("expected the store of an exhausted stream");
+        };
+        let graph = graph.into_inner();
+        assert_eq!(graph.get(&'a').unwrap().collect::<Vec<_>>(), [&'b', &'c']);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn pauses_while_buffer_is_full() -> Result<()> {
+        // An infinite binary tree, where node `n` has the children `2n + 1`
+        // and `2n + 2`.
+        let expanded = Arc::new(AtomicUsize::new(0));
+        let mut stream = NonDeterministic::new().visit_stream([0u64], {
+            let expanded = expanded.clone();
+            move |&node: &u64| {
+                expanded.fetch_add(1, Ordering::SeqCst);
+                async move { Ok([node * 2 + 1, node * 2 + 2]) }
+            }
+        });
+        for _ in 0..3 {
+            stream.next().await.transpose()?;
+        }
+        // Every resolved edges future discovers two nodes, and no further
+        // edges futures are polled once the buffer is full.
+        assert!(expanded.load(Ordering::SeqCst) <= DISCOVERED_BUFFER_SIZE + 4);
+        assert!(stream.into_store().is_none());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn dropping_drops_pending_futures() -> Result<()> {
+        let live = Arc::new(AtomicUsize::new(0));
+        let mut stream = NonDeterministic::new().visit_stream([0u64], {
+            let live = live.clone();
+            move |&node: &u64| {
+                let live = Live::new(&live);
+                async move {
+                    tokio::task::yield_now().await;
+                    drop(live);
+                    Ok(if node < 100 {
+                        vec![node + 1, node + 2]
+                    } else {
+                        vec![]
+                    })
+                }
+            }
+        });
+        for _ in 0..5 {
+            stream.next().await.transpose()?;
+        }
+        assert!(live.load(Ordering::SeqCst) > 0);
+
+        drop(stream);
+        assert_eq!(live.load(Ordering::SeqCst), 0);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn ends_after_an_error() -> Result<()> {
+        let mut stream = NonDeterministic::new().visit_stream([0u64], |&node: &u64| async move {
+            if node == 1 {
+                bail!("failed to get the edges of {}", node);
+            }
+            Ok(vec![node + 1])
+        });
+        assert_eq!(stream.next().await.transpose()?, Some(0));
+        assert_eq!(stream.next().await.transpose()?, Some(1));
+        assert!(stream.next().await.unwrap().is_err());
+        assert!(stream.next().await.is_none());
+        assert!(stream.into_store().is_none());
+        Ok(())
+    }
+}
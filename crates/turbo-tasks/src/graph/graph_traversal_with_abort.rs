@@ -0,0 +1,272 @@
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use anyhow::Result;
+use event_listener::EventListener;
+use futures::{stream::FuturesUnordered, Stream};
+
+use super::{
+    abort_signal::AbortSignal,
+    graph_store::{GraphNode, GraphStore},
+    with_future::With,
+    GraphTraversalResult, Visit, VisitControlFlow,
+};
+
+/// A future that resolves to a [`GraphStore`] containing the result of a graph
+/// traversal, which can be aborted with an [`AbortSignal`], see
+/// [`GraphTraversal::visit_with_abort`](super::GraphTraversal::visit_with_abort).
+///
+/// The signal is checked before the edges of every node are expanded. Once it
+/// is aborted, all pending edges futures are dropped and the future resolves to
+/// [`GraphTraversalResult::Aborted`] with the store built so far. Nodes are
+/// only inserted into the store together with the edge they were reached
+/// through, so the partial store has no edges to nodes it doesn't contain. It
+/// just lacks the edges of the nodes which weren't expanded yet.
+pub struct GraphTraversalWithAbortFuture<Store, VisitImpl, Impl>
+where
+    Store: GraphStore,
+    VisitImpl: Visit<Store::Node, !, Impl>,
+{
+    store: Option<Store>,
+    futures: FuturesUnordered<With<VisitImpl::EdgesFuture, Store::Handle>>,
+    visit: VisitImpl,
+    signal: AbortSignal,
+    listener: Option<EventListener>,
+}
+
+impl<Store, VisitImpl, Impl> GraphTraversalWithAbortFuture<Store, VisitImpl, Impl>
+where
+    Store: GraphStore,
+    VisitImpl: Visit<Store::Node, !, Impl>,
+{
+    pub(super) fn new(
+        store: Store,
+        root_edges: impl IntoIterator<Item = VisitImpl::Edge>,
+        visit: VisitImpl,
+        signal: AbortSignal,
+    ) -> Self {
+        let mut future = Self {
+            store: Some(store),
+            futures: FuturesUnordered::new(),
+            visit,
+            signal,
+            listener: None,
+        };
+        if !future.signal.is_aborted() {
+            for edge in root_edges {
+                future.visit_edge(None, edge);
+            }
+        }
+        future
+    }
+
+    fn visit_edge(&mut self, from_handle: Option<Store::Handle>, edge: VisitImpl::Edge) {
+        let store = self.store.as_mut().expect("polled after completion");
+        match self.visit.visit(edge) {
+            VisitControlFlow::Continue(node) => {
+                let span = self.visit.span(&node);
+                if let Some((node_handle, node_ref)) = store.insert(from_handle, GraphNode(node)) {
+                    self.futures
+                        .push(With::new(self.visit.edges(node_ref), span, node_handle));
+                }
+            }
+            VisitControlFlow::Skip(node) => {
+                store.insert(from_handle, GraphNode(node));
+            }
+            VisitControlFlow::Abort(abort) => abort,
+        }
+    }
+
+    fn take_store(&mut self) -> Store {
+        self.store.take().expect("polled after completion")
+    }
+}
+
+impl<Store, VisitImpl, Impl> Future for GraphTraversalWithAbortFuture<Store, VisitImpl, Impl>
+where
+    Store: GraphStore,
+    VisitImpl: Visit<Store::Node, !, Impl>,
+{
+    type Output = GraphTraversalResult<Result<Store>, Store>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // SAFETY: Only the edges futures are pinned, and they are owned by
+        // `FuturesUnordered`, which never moves them.
+        let this = unsafe { self.get_unchecked_mut() };
+
+        loop {
+            if this.signal.is_aborted() {
+                this.futures.clear();
+                this.listener = None;
+                return Poll::Ready(GraphTraversalResult::Aborted(this.take_store()));
+            }
+            match Pin::new(&mut this.futures).poll_next(cx) {
+                Poll::Ready(Some((node_handle, span, Ok(edges)))) => {
+                    let _guard = span.enter();
+                    for edge in edges {
+                        this.visit_edge(Some(node_handle.clone()), edge);
+                    }
+                }
+                Poll::Ready(Some((_, _, Err(err)))) => {
+                    this.futures.clear();
+                    this.listener = None;
+                    this.store = None;
+                    return Poll::Ready(GraphTraversalResult::Completed(Err(err)));
+                }
+                Poll::Ready(None) => {
+                    this.listener = None;
+                    return Poll::Ready(GraphTraversalResult::Completed(Ok(this.take_store())));
+                }
+                Poll::Pending => {
+                    // All edges futures are pending, so the traversal must
+                    // also be woken up when the signal is aborted.
+                    let signal = &this.signal;
+                    let listener = this.listener.get_or_insert_with(|| signal.listen());
+                    if Pin::new(listener).poll(cx).is_ready() {
+                        this.listener = None;
+                        continue;
+                    }
+                    if signal.is_aborted() {
+                        continue;
+                    }
+                    return Poll::Pending;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        time::Duration,
+    };
+
+    use anyhow::{bail, Result};
+
+    use crate::graph::{AbortSignal, AdjacencyMap, GraphTraversal, GraphTraversalResult};
+
+    /// Counts the edges futures which are alive.
+    struct Live(Arc<AtomicUsize>);
+
+    impl Live {
+        fn new(count: &Arc<AtomicUsize>) -> Self {
+            count.fetch_add(1, Ordering::SeqCst);
+            Self(count.clone())
+        }
+    }
+
+    impl Drop for Live {
+        fn drop(&mut self) {
+            self.0.fetch_sub(1, Ordering::SeqCst);
+        }
+    }
+
+    /// Asserts that all children of the nodes in the graph are nodes of the
+    /// graph.
+    fn assert_consistent(graph: &AdjacencyMap<u64>) {
+        for node in graph.iter_nodes() {
+            for child in graph.get(node).into_iter().flatten() {
+                assert!(graph.iter_nodes().any(|n| n == child), "{child} is missing");
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn aborts_after_n_nodes() -> Result<()> {
+        const ABORT_AFTER: usize = 100;
+
+        // A binary tree with a million nodes, where node `n` has the children
+        // `2n + 1` and `2n + 2`.
+        let signal = AbortSignal::new();
+        let expanded = Arc::new(AtomicUsize::new(0));
+        let live = Arc::new(AtomicUsize::new(0));
+        let traversal = AdjacencyMap::new().visit_with_abort(
+            [0u64],
+            {
+                let signal = signal.clone();
+                let expanded = expanded.clone();
+                let live = live.clone();
+                move |&node: &u64| {
+                    if expanded.fetch_add(1, Ordering::SeqCst) + 1 == ABORT_AFTER {
+                        signal.abort();
+                    }
+                    let live = Live::new(&live);
+                    async move {
+                        tokio::task::yield_now().await;
+                        drop(live);
+                        Ok([node * 2 + 1, node * 2 + 2]
+                            .into_iter()
+                            .filter(|&child| child < 1_000_000))
+                    }
+                }
+            },
+            signal.clone(),
+        );
+        let result = tokio::time::timeout(Duration::from_secs(5), traversal).await?;
+
+        let GraphTraversalResult::Aborted(graph) = result else {
+            bail!("expected the traversal to be aborted");
+        };
+        // Nodes discovered by the last expansion are in the store, but their
+        // edges futures were dropped without being polled further.
+        assert!(expanded.load(Ordering::SeqCst) <= ABORT_AFTER + 2);
+        assert_eq!(live.load(Ordering::SeqCst), 0);
+        assert!(graph.node_count() >= ABORT_AFTER);
+        assert!(graph.node_count() < 1_000_000);
+        assert_consistent(&graph);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn abort_wakes_pending_traversal() -> Result<()> {
+        let signal = AbortSignal::new();
+        let traversal = AdjacencyMap::new().visit_with_abort(
+            [0u64],
+            |&node: &u64| async move {
+                if node > 0 {
+                    // Never resolves, so only the signal can end the traversal.
+                    std::future::pending::<()>().await;
+                }
+                Ok([1, 2])
+            },
+            signal.clone(),
+        );
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            signal.abort();
+        });
+        let result = tokio::time::timeout(Duration::from_secs(5), traversal).await?;
+
+        let GraphTraversalResult::Aborted(graph) = result else {
+            bail!("expected the traversal to be aborted");
+        };
+        assert_eq!(graph.get(&0).unwrap().collect::<Vec<_>>(), [&1, &2]);
+        assert_consistent(&graph);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn completes_without_abort() -> Result<()> {
+        let result = AdjacencyMap::new()
+            .visit_with_abort(
+                [0u64],
+                |&node: &u64| async move { Ok(if node < 3 { vec![node + 1] } else { vec![] }) },
+                AbortSignal::new(),
+            )
+            .await;
+
+        let GraphTraversalResult::Completed(graph) = result else {
+            bail!("expected the traversal to complete");
+        };
+        assert_eq!(graph?.node_count(), 4);
+        Ok(())
+    }
+}
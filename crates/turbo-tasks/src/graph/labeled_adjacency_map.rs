@@ -0,0 +1,238 @@
+use std::collections::HashSet;
+
+use indexmap::IndexMap;
+
+use super::{
+    adjacency_map::{ReverseTopologicalFromNodeIter, ReverseTopologicalIter},
+    graph_store::{GraphNode, GraphStore},
+};
+
+/// A graph traversal that builds an adjacency map, keeping the label of every
+/// edge.
+///
+/// The traversal visits `(label, node)` pairs. Root nodes are stored with the
+/// label of their root edge. Every edge is stored, but a node is only expanded
+/// the first time it is reached, whatever the label of the edge, so it doesn't
+/// need to be wrapped in [`SkipDuplicates`](super::SkipDuplicates).
+pub struct LabeledAdjacencyMap<T, E>
+where
+    T: Eq + std::hash::Hash + Clone,
+{
+    adjacency_map: IndexMap<T, Vec<(E, T)>>,
+    roots: Vec<(E, T)>,
+    visited: HashSet<T>,
+}
+
+impl<T, E> Default for LabeledAdjacencyMap<T, E>
+where
+    T: Eq + std::hash::Hash + Clone,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, E> LabeledAdjacencyMap<T, E>
+where
+    T: Eq + std::hash::Hash + Clone,
+{
+    /// Creates a new labeled adjacency map
+    pub fn new() -> Self {
+        Self {
+            adjacency_map: IndexMap::new(),
+            roots: Vec::new(),
+            visited: HashSet::new(),
+        }
+    }
+
+    /// Returns an iterator over the root nodes of the graph
+    pub fn roots(&self) -> impl Iterator<Item = &T> {
+        self.roots.iter().map(|(_, root)| root)
+    }
+
+    /// Returns an iterator over the root nodes of the graph together with the
+    /// labels of their root edges
+    pub fn labeled_roots(&self) -> impl Iterator<Item = &(E, T)> {
+        self.roots.iter()
+    }
+
+    /// Returns the number of root nodes of the graph
+    pub fn root_count(&self) -> usize {
+        self.roots.len()
+    }
+
+    /// Returns true if the graph has neither root nodes nor edges
+    pub fn is_empty(&self) -> bool {
+        self.roots.is_empty() && self.adjacency_map.is_empty()
+    }
+
+    /// Returns an iterator over the children of the given node
+    pub fn get(&self, node: &T) -> Option<impl Iterator<Item = &T>> {
+        self.adjacency_map
+            .get(node)
+            .map(|vec| vec.iter().map(|(_, child)| child))
+    }
+
+    /// Returns an iterator over the children of the given node together with
+    /// the labels of the edges to them
+    pub fn get_labeled(&self, node: &T) -> Option<impl Iterator<Item = &(E, T)>> {
+        self.adjacency_map.get(node).map(|vec| vec.iter())
+    }
+}
+
+impl<T, E> GraphStore for LabeledAdjacencyMap<T, E>
+where
+    T: Eq + std::hash::Hash + Clone,
+{
+    type Node = (E, T);
+    type Handle = T;
+
+    fn insert(
+        &mut self,
+        from_handle: Option<T>,
+        node: GraphNode<(E, T)>,
+    ) -> Option<(Self::Handle, &(E, T))> {
+        let handle = node.node().1.clone();
+        let is_new = self.visited.insert(handle.clone());
+
+        let vec = if let Some(from_handle) = from_handle {
+            self.adjacency_map
+                .entry(from_handle)
+                .or_insert_with(|| Vec::with_capacity(1))
+        } else {
+            &mut self.roots
+        };
+        vec.push(node.into_node());
+
+        if !is_new {
+            return None;
+        }
+        Some((handle, vec.last().unwrap()))
+    }
+}
+
+impl<T, E> LabeledAdjacencyMap<T, E>
+where
+    T: Eq + std::hash::Hash + Clone,
+{
+    /// Returns an iterator over the nodes in reverse topological order,
+    /// starting from the roots. Labels are ignored.
+    pub fn into_reverse_topological(self) -> ReverseTopologicalIter<T, (E, T)> {
+        ReverseTopologicalIter::new(
+            self.adjacency_map,
+            self.roots.into_iter().map(|(_, root)| root),
+            labeled,
+        )
+    }
+
+    /// Returns an iterator over the nodes in reverse topological order,
+    /// starting from the given node. Labels are ignored.
+    pub fn reverse_topological_from_node<'graph>(
+        &'graph self,
+        node: &'graph T,
+    ) -> ReverseTopologicalFromNodeIter<'graph, T, (E, T)> {
+        ReverseTopologicalFromNodeIter::new(&self.adjacency_map, node, labeled)
+    }
+}
+
+fn labeled<E, T>((_, node): &(E, T)) -> &T {
+    node
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use anyhow::Result;
+
+    use super::LabeledAdjacencyMap;
+    use crate::graph::GraphTraversal;
+
+    /// `a` imports `b` and `c`, `b` imports `c`. Edges are labeled with the
+    /// import specifier.
+    fn imports(node: char) -> Vec<(&'static str, char)> {
+        match node {
+            'a' => vec![("./b", 'b'), ("./c.js", 'c')],
+            'b' => vec![("../c", 'c')],
+            _ => vec![],
+        }
+    }
+
+    async fn graph() -> Result<LabeledAdjacencyMap<char, &'static str>> {
+        LabeledAdjacencyMap::new()
+            .visit([("entry", 'a')], |&(_, node): &(&str, char)| async move {
+                Ok(imports(node))
+            })
+            .await
+            .completed()
+    }
+
+    #[tokio::test]
+    async fn stores_edge_labels() -> Result<()> {
+        let graph = graph().await?;
+
+        assert_eq!(graph.roots().collect::<Vec<_>>(), [&'a']);
+        assert_eq!(graph.labeled_roots().collect::<Vec<_>>(), [&("entry", 'a')]);
+        assert_eq!(graph.get(&'a').unwrap().collect::<Vec<_>>(), [&'b', &'c']);
+        assert_eq!(
+            graph.get_labeled(&'a').unwrap().collect::<Vec<_>>(),
+            [&("./b", 'b'), &("./c.js", 'c')]
+        );
+        assert_eq!(
+            graph.get_labeled(&'b').unwrap().collect::<Vec<_>>(),
+            [&("../c", 'c')]
+        );
+        assert!(graph.get_labeled(&'c').is_none());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn expands_nodes_once() -> Result<()> {
+        // `c` is reached through two edges with different labels, and `d`
+        // imports `a` again.
+        let expanded = AtomicUsize::new(0);
+        let graph = LabeledAdjacencyMap::new()
+            .visit([("entry", 'a')], |&(_, node): &(&str, char)| {
+                expanded.fetch_add(1, Ordering::SeqCst);
+                async move {
+                    Ok(match node {
+                        'c' => vec![("./d", 'd')],
+                        'd' => vec![("../a", 'a')],
+                        node => imports(node),
+                    })
+                }
+            })
+            .await
+            .completed()?;
+
+        assert_eq!(expanded.load(Ordering::SeqCst), 4);
+        // All edges are stored, including the ones to nodes which were
+        // already expanded.
+        assert_eq!(
+            graph.get_labeled(&'b').unwrap().collect::<Vec<_>>(),
+            [&("../c", 'c')]
+        );
+        assert_eq!(
+            graph.get_labeled(&'d').unwrap().collect::<Vec<_>>(),
+            [&("../a", 'a')]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn topological_order_ignores_labels() -> Result<()> {
+        let graph = graph().await?;
+
+        assert_eq!(
+            graph
+                .reverse_topological_from_node(&'b')
+                .collect::<Vec<_>>(),
+            [&'c', &'b']
+        );
+        assert_eq!(
+            graph.into_reverse_topological().collect::<Vec<_>>(),
+            ['c', 'b', 'a']
+        );
+        Ok(())
+    }
+}
@@ -1,14 +1,26 @@
+mod abort_signal;
 mod adjacency_map;
+mod capped_store;
 mod control_flow;
 mod graph_store;
 mod graph_traversal;
+mod graph_traversal_stream;
+mod graph_traversal_with_abort;
+mod labeled_adjacency_map;
 mod non_deterministic;
+mod ordered_nodes;
 mod visit;
 mod with_future;
 
+pub use abort_signal::AbortSignal;
 pub use adjacency_map::AdjacencyMap;
+pub use capped_store::CappedStore;
 pub use control_flow::VisitControlFlow;
 pub use graph_store::{GraphStore, SkipDuplicates};
 pub use graph_traversal::{GraphTraversal, GraphTraversalResult};
+pub use graph_traversal_stream::GraphTraversalStream;
+pub use graph_traversal_with_abort::GraphTraversalWithAbortFuture;
+pub use labeled_adjacency_map::LabeledAdjacencyMap;
 pub use non_deterministic::NonDeterministic;
+pub use ordered_nodes::OrderedNodes;
 pub use visit::Visit;
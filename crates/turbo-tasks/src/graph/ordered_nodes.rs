@@ -0,0 +1,102 @@
+use std::collections::HashSet;
+
+use super::graph_store::{GraphNode, GraphStore};
+
+/// A graph store that collects all visited nodes in the order in which they
+/// were first visited. Nodes that were already visited are neither stored nor
+/// expanded again.
+pub struct OrderedNodes<T> {
+    nodes: Vec<T>,
+    visited: HashSet<T>,
+}
+
+impl<T> Default for OrderedNodes<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> OrderedNodes<T> {
+    pub fn new() -> Self {
+        Self {
+            nodes: Vec::new(),
+            visited: HashSet::new(),
+        }
+    }
+
+    /// Consumes the store and returns the visited nodes in first-visit order.
+    pub fn into_inner(self) -> Vec<T> {
+        self.nodes
+    }
+}
+
+impl<T> GraphStore for OrderedNodes<T>
+where
+    T: Eq + std::hash::Hash + Clone,
+{
+    type Node = T;
+    type Handle = ();
+
+    fn insert(
+        &mut self,
+        _from_handle: Option<Self::Handle>,
+        node: GraphNode<T>,
+    ) -> Option<(Self::Handle, &T)> {
+        if !self.visited.insert(node.node().clone()) {
+            return None;
+        }
+        self.nodes.push(node.into_node());
+        Some(((), self.nodes.last().unwrap()))
+    }
+}
+
+impl<T> IntoIterator for OrderedNodes<T> {
+    type Item = T;
+    type IntoIter = <Vec<T> as IntoIterator>::IntoIter;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.nodes.into_iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use super::OrderedNodes;
+    use crate::graph::GraphTraversal;
+
+    #[tokio::test]
+    async fn visits_each_node_once_in_order() -> Result<()> {
+        // 0 -> 1, 2; 1 -> 2; 2 -> 0
+        let nodes = OrderedNodes::new()
+            .visit([0u32], |&node: &u32| async move {
+                Ok(match node {
+                    0 => vec![1, 2],
+                    1 => vec![2],
+                    2 => vec![0],
+                    _ => unreachable!(),
+                })
+            })
+            .await
+            .completed()?
+            .into_inner();
+        assert_eq!(nodes, vec![0, 1, 2]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn works_with_skip_duplicates() -> Result<()> {
+        let nodes = OrderedNodes::new()
+            .skip_duplicates()
+            .visit([0u32, 0], |&node: &u32| async move {
+                Ok(if node < 3 { vec![node + 1] } else { vec![] })
+            })
+            .await
+            .completed()?
+            .into_inner()
+            .into_inner();
+        assert_eq!(nodes, vec![0, 1, 2, 3]);
+        Ok(())
+    }
+}
@@ -26,6 +26,9 @@ pub struct U32(u32);
 #[turbo_tasks::value(transparent)]
 pub struct U64(u64);
 
+#[turbo_tasks::value(transparent)]
+pub struct OptionU64(Option<u64>);
+
 #[turbo_tasks::value_impl]
 impl ValueToString for U64 {
     #[turbo_tasks::function]
@@ -65,6 +68,9 @@ impl StringsVc {
 #[turbo_tasks::value(transparent)]
 pub struct Bool(bool);
 
+#[turbo_tasks::value(transparent)]
+pub struct Bools(Vec<bool>);
+
 #[turbo_tasks::value(transparent)]
 pub struct Usize(usize);
 
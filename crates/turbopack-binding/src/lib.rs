@@ -94,6 +94,8 @@ pub mod turbopack {
     pub use turbopack_test_utils as test_utils;
     #[cfg(feature = "__turbopack_tests")]
     pub use turbopack_tests as tests;
+    #[cfg(feature = "__turbopack_wasm")]
+    pub use turbopack_wasm as wasm;
 }
 
 #[cfg(feature = "__features")]
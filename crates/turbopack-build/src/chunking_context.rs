@@ -2,15 +2,15 @@ use anyhow::{bail, Result};
 use indexmap::IndexSet;
 use turbo_tasks::{
     graph::{AdjacencyMap, GraphTraversal},
-    primitives::{BoolVc, StringVc},
+    primitives::{BoolVc, OptionStringVc, StringVc},
     TryJoinIterExt, Value,
 };
 use turbo_tasks_fs::FileSystemPathVc;
 use turbopack_core::{
     asset::{Asset, AssetVc},
     chunk::{
-        Chunk, ChunkVc, ChunkableModule, ChunkingContext, ChunkingContextVc, ChunksVc,
-        EvaluatableAssetsVc,
+        hashed_file_name, Chunk, ChunkVc, ChunkableModule, ChunkingContext, ChunkingContextVc,
+        ChunksVc, EvaluatableAssetsVc,
     },
     environment::EnvironmentVc,
     ident::AssetIdentVc,
@@ -18,8 +18,9 @@ use turbopack_core::{
 };
 use turbopack_css::chunk::CssChunkVc;
 use turbopack_ecmascript::chunk::{
-    EcmascriptChunkPlaceableVc, EcmascriptChunkVc, EcmascriptChunkingContext,
-    EcmascriptChunkingContextVc,
+    validate_module_ids, EcmascriptChunkPlaceableVc, EcmascriptChunkVc, EcmascriptChunkingContext,
+    EcmascriptChunkingContextVc, ModuleIdMapVc, ModuleIdStrategy, ModuleIdStrategyVc,
+    OptionModuleIdMapVc,
 };
 use turbopack_ecmascript_runtime::RuntimeType;
 
@@ -43,6 +44,22 @@ impl BuildChunkingContextBuilder {
         self
     }
 
+    /// Uses short numeric module ids assigned by the given map instead of
+    /// ids derived from the module path. The map must assign ids to all
+    /// modules, see [ModuleIdMapVc::with_idents]. Persisting the map between
+    /// builds keeps the ids stable.
+    pub fn module_id_map(mut self, module_id_map: ModuleIdMapVc) -> Self {
+        self.context.module_id_map = Some(module_id_map);
+        self
+    }
+
+    /// Sets how module ids are derived from the module idents when no module
+    /// id map is used.
+    pub fn chunk_item_id_strategy(mut self, chunk_item_id_strategy: ModuleIdStrategy) -> Self {
+        self.context.chunk_item_id_strategy = chunk_item_id_strategy;
+        self
+    }
+
     /// Builds the chunking context.
     pub fn build(self) -> BuildChunkingContextVc {
         BuildChunkingContextVc::new(Value::new(self.context))
@@ -64,10 +81,16 @@ pub struct BuildChunkingContext {
     asset_root_path: FileSystemPathVc,
     /// Layer name within this context
     layer: Option<String>,
+    /// The name of the chunks, see [ChunkingContext::with_chunk_name]
+    chunk_name: Option<String>,
     /// The environment chunks will be evaluated in.
     environment: EnvironmentVc,
     /// The kind of runtime to include in the output.
     runtime_type: RuntimeType,
+    /// Assigns numeric module ids when set.
+    module_id_map: Option<ModuleIdMapVc>,
+    /// How module ids are derived from the module idents.
+    chunk_item_id_strategy: ModuleIdStrategy,
 }
 
 impl BuildChunkingContextVc {
@@ -86,8 +109,11 @@ impl BuildChunkingContextVc {
                 chunk_root_path,
                 asset_root_path,
                 layer: None,
+                chunk_name: None,
                 environment,
                 runtime_type: Default::default(),
+                module_id_map: None,
+                chunk_item_id_strategy: Default::default(),
             },
         }
     }
@@ -154,6 +180,15 @@ impl BuildChunkingContextVc {
 }
 
 impl BuildChunkingContextVc {
+    /// Checks that the module ids of a chunk group are unique when they are
+    /// hashed, as short hashes can collide.
+    async fn validate_module_ids(self, chunks: ChunksVc) -> Result<()> {
+        if let ModuleIdStrategy::Hashed { .. } = self.await?.chunk_item_id_strategy {
+            validate_module_ids(chunks).await?;
+        }
+        Ok(())
+    }
+
     async fn get_chunk_assets(
         self,
         entry_chunk: ChunkVc,
@@ -178,6 +213,7 @@ impl BuildChunkingContextVc {
         let chunks = get_parallel_chunks(chunks);
 
         let chunks = get_optimized_chunks(chunks.await?).await?;
+        self.validate_module_ids(chunks).await?;
 
         Ok(chunks
             .await?
@@ -212,7 +248,14 @@ impl ChunkingContext for BuildChunkingContext {
         } else {
             root_path
         };
-        let name = ident.output_name(self.context_path, extension).await?;
+        let name = match self.chunk_name.as_deref() {
+            Some(chunk_name) => {
+                ident
+                    .named_output_name(self.context_path, chunk_name, extension)
+                    .await?
+            }
+            None => ident.output_name(self.context_path, extension).await?,
+        };
         Ok(root_path.join(&name))
     }
 
@@ -242,19 +285,9 @@ impl ChunkingContext for BuildChunkingContext {
         original_asset_ident: AssetIdentVc,
     ) -> Result<FileSystemPathVc> {
         let source_path = original_asset_ident.path().await?;
-        let basename = source_path.file_name();
-        let asset_path = match source_path.extension() {
-            Some(ext) => format!(
-                "{basename}.{content_hash}.{ext}",
-                basename = &basename[..basename.len() - ext.len() - 1],
-                content_hash = &content_hash[..8]
-            ),
-            None => format!(
-                "{basename}.{content_hash}",
-                content_hash = &content_hash[..8]
-            ),
-        };
-        Ok(self.asset_root_path.join(&asset_path))
+        Ok(self
+            .asset_root_path
+            .join(&hashed_file_name(&source_path, content_hash)))
     }
 
     #[turbo_tasks::function]
@@ -269,6 +302,41 @@ impl ChunkingContext for BuildChunkingContext {
         Ok(BuildChunkingContextVc::new(Value::new(context)).into())
     }
 
+    #[turbo_tasks::function]
+    async fn with_environment(
+        self_vc: BuildChunkingContextVc,
+        environment: EnvironmentVc,
+    ) -> Result<ChunkingContextVc> {
+        let mut context = self_vc.await?.clone_value();
+        context.environment = environment;
+        Ok(BuildChunkingContextVc::new(Value::new(context)).into())
+    }
+
+    #[turbo_tasks::function]
+    fn chunk_name(&self) -> OptionStringVc {
+        OptionStringVc::cell(self.chunk_name.clone())
+    }
+
+    #[turbo_tasks::function]
+    async fn with_chunk_name(
+        self_vc: BuildChunkingContextVc,
+        name: &str,
+    ) -> Result<ChunkingContextVc> {
+        let this = self_vc.await?;
+        let chunk_name = (!name.is_empty()).then(|| name.to_string());
+        if this.chunk_name == chunk_name {
+            return Ok(self_vc.into());
+        }
+        let mut context = this.clone_value();
+        context.chunk_name = chunk_name;
+        Ok(BuildChunkingContextVc::new(Value::new(context)).into())
+    }
+
+    #[turbo_tasks::function]
+    fn chunk_output_assets(self_vc: BuildChunkingContextVc, chunk: ChunkVc) -> OutputAssetsVc {
+        OutputAssetsVc::cell(vec![self_vc.generate_chunk(chunk)])
+    }
+
     #[turbo_tasks::function]
     async fn chunk_group(
         self_vc: BuildChunkingContextVc,
@@ -277,6 +345,7 @@ impl ChunkingContext for BuildChunkingContext {
         let parallel_chunks = get_parallel_chunks([entry_chunk]).await?;
 
         let optimized_chunks = get_optimized_chunks(parallel_chunks).await?;
+        self_vc.validate_module_ids(optimized_chunks).await?;
 
         let assets: Vec<OutputAssetVc> = optimized_chunks
             .await?
@@ -300,7 +369,17 @@ impl ChunkingContext for BuildChunkingContext {
 }
 
 #[turbo_tasks::value_impl]
-impl EcmascriptChunkingContext for BuildChunkingContext {}
+impl EcmascriptChunkingContext for BuildChunkingContext {
+    #[turbo_tasks::function]
+    fn module_id_map(&self) -> OptionModuleIdMapVc {
+        OptionModuleIdMapVc::cell(self.module_id_map)
+    }
+
+    #[turbo_tasks::function]
+    fn chunk_item_id_strategy(&self) -> ModuleIdStrategyVc {
+        self.chunk_item_id_strategy.cell()
+    }
+}
 
 async fn get_parallel_chunks<I>(entries: I) -> Result<impl Iterator<Item = ChunkVc>>
 where
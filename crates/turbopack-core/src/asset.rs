@@ -1,10 +1,11 @@
 use anyhow::Result;
 use indexmap::IndexSet;
-use turbo_tasks::CompletionVc;
+use turbo_tasks::{primitives::U64Vc, CompletionVc};
 use turbo_tasks_fs::{
     File, FileContent, FileContentVc, FileJsonContent, FileJsonContentVc, FileLinesContent,
     FileLinesContentVc, FileSystemPathVc, LinkContent, LinkType,
 };
+use turbo_tasks_hash::hash_xxh3_hash64;
 
 use crate::{
     ident::AssetIdentVc,
@@ -49,6 +50,46 @@ pub trait Asset {
     async fn versioned_content(&self) -> Result<VersionedContentVc> {
         Ok(VersionedAssetContentVc::new(self.content()).into())
     }
+
+    /// The coarse category of the [Asset]. By default it's derived from the
+    /// extension of its path.
+    async fn category(&self) -> Result<AssetCategoryVc> {
+        let path = self.ident().path().await?;
+        Ok(AssetCategory::from_extension(path.extension()).cell())
+    }
+}
+
+/// A coarse category of [Asset]s, e. g. to partition the assets of a
+/// deployment.
+#[turbo_tasks::value(serialization = "auto_for_input")]
+#[derive(Debug, Clone, Copy, PartialOrd, Ord, Hash)]
+pub enum AssetCategory {
+    /// JavaScript and TypeScript.
+    Js,
+    Css,
+    /// Images, audio and video.
+    Media,
+    Wasm,
+    Other,
+}
+
+impl AssetCategory {
+    /// The category of a path with the given extension. Unknown extensions
+    /// are [AssetCategory::Other].
+    pub fn from_extension(extension: Option<&str>) -> Self {
+        let Some(extension) = extension else {
+            return AssetCategory::Other;
+        };
+        match extension.to_ascii_lowercase().as_str() {
+            "js" | "mjs" | "cjs" | "jsx" | "ts" | "mts" | "cts" | "tsx" => AssetCategory::Js,
+            "css" => AssetCategory::Css,
+            "apng" | "avif" | "bmp" | "gif" | "ico" | "jpeg" | "jpg" | "png" | "svg" | "webp"
+            | "aac" | "flac" | "mp3" | "oga" | "ogg" | "opus" | "wav" | "m4a" | "mov" | "mp4"
+            | "ogv" | "webm" => AssetCategory::Media,
+            "wasm" => AssetCategory::Wasm,
+            _ => AssetCategory::Other,
+        }
+    }
 }
 
 /// An optional [Asset]
@@ -105,6 +146,17 @@ impl AssetContentVc {
         }
     }
 
+    /// The xxh3 hash of the bytes of the file. Redirects and files which
+    /// don't exist hash to 0.
+    #[turbo_tasks::function]
+    pub async fn hash(self) -> Result<U64Vc> {
+        let content = self.file_content().await?;
+        Ok(U64Vc::cell(match &*content {
+            FileContent::Content(file) => hash_xxh3_hash64(file.content()),
+            FileContent::NotFound => 0,
+        }))
+    }
+
     #[turbo_tasks::function]
     pub async fn lines(self) -> Result<FileLinesContentVc> {
         let this = self.await?;
@@ -140,3 +192,41 @@ impl AssetContentVc {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::AssetCategory;
+
+    #[test]
+    fn category_from_extension() {
+        assert_eq!(
+            AssetCategory::from_extension(Some("mjs")),
+            AssetCategory::Js
+        );
+        assert_eq!(
+            AssetCategory::from_extension(Some("tsx")),
+            AssetCategory::Js
+        );
+        assert_eq!(
+            AssetCategory::from_extension(Some("css")),
+            AssetCategory::Css
+        );
+        assert_eq!(
+            AssetCategory::from_extension(Some("PNG")),
+            AssetCategory::Media
+        );
+        assert_eq!(
+            AssetCategory::from_extension(Some("webm")),
+            AssetCategory::Media
+        );
+        assert_eq!(
+            AssetCategory::from_extension(Some("wasm")),
+            AssetCategory::Wasm
+        );
+        assert_eq!(
+            AssetCategory::from_extension(Some("map")),
+            AssetCategory::Other
+        );
+        assert_eq!(AssetCategory::from_extension(None), AssetCategory::Other);
+    }
+}
@@ -4,14 +4,15 @@ use anyhow::Result;
 use indexmap::IndexSet;
 use turbo_tasks::{
     graph::{AdjacencyMap, GraphTraversal},
-    primitives::{BoolVc, U64Vc},
+    primitives::{BoolVc, BoolsVc, U64Vc},
     TryJoinIterExt, ValueToString,
 };
+use turbo_tasks_fs::FileContent;
 use turbo_tasks_hash::Xxh3Hash64Hasher;
 
 use super::{ChunkableModuleReference, ChunkableModuleReferenceVc, ChunkingType};
 use crate::{
-    asset::{Asset, AssetVc, AssetsSetVc},
+    asset::{Asset, AssetContent, AssetVc, AssetsSetVc, AssetsVc},
     reference::AssetReference,
 };
 
@@ -38,13 +39,11 @@ impl AvailableAssetsVc {
 
     #[turbo_tasks::function]
     pub async fn with_roots(self, roots: Vec<AssetVc>) -> Result<Self> {
+        let included = self.includes_all(roots.clone()).await?;
         let roots = roots
             .into_iter()
-            .map(|root| async move { Ok((self.includes(root).await?, root)) })
-            .try_join()
-            .await?
-            .into_iter()
-            .filter_map(|(included, root)| (!*included).then_some(root))
+            .zip(included.iter())
+            .filter_map(|(root, &included)| (!included).then_some(root))
             .collect();
         Ok(Self::new_normalized(Some(self), roots))
     }
@@ -79,6 +78,140 @@ impl AvailableAssetsVc {
         }
         Ok(BoolVc::cell(false))
     }
+
+    /// Answers [AvailableAssetsVc::includes] for each of the assets, in the
+    /// same order. The parent chain and the assets reachable from each root
+    /// are only walked once for all assets.
+    #[turbo_tasks::function]
+    pub async fn includes_all(self, assets: Vec<AssetVc>) -> Result<BoolsVc> {
+        let assets = assets
+            .into_iter()
+            .map(|asset| asset.resolve())
+            .try_join()
+            .await?;
+        let mut included = vec![false; assets.len()];
+        let mut current = Some(self);
+        while let Some(available_assets) = current {
+            if included.iter().all(|&included| included) {
+                break;
+            }
+            let this = available_assets.await?;
+            for root in this.roots.iter() {
+                let root_assets = chunkable_assets_set(*root).await?;
+                for (included, asset) in included.iter_mut().zip(assets.iter()) {
+                    *included = *included || root_assets.contains(asset);
+                }
+            }
+            current = this.parent;
+        }
+        Ok(BoolsVc::cell(included))
+    }
+
+    /// Returns the roots of this and all parent [AvailableAssets], the roots of
+    /// the parents first. Roots of multiple links are listed once.
+    #[turbo_tasks::function]
+    pub async fn all_roots(self) -> Result<AssetsVc> {
+        let this = self.await?;
+        let mut roots = IndexSet::new();
+        if let Some(parent) = this.parent {
+            roots.extend(parent.all_roots().await?.iter().copied());
+        }
+        for root in this.roots.iter() {
+            roots.insert(root.resolve().await?);
+        }
+        Ok(AssetsVc::cell(roots.into_iter().collect()))
+    }
+
+    /// Returns the total content size in bytes of all chunkable assets that
+    /// are reachable from the roots of this and all parent [AvailableAssets].
+    /// Each asset is counted only once, even when it is reachable from
+    /// multiple roots.
+    #[turbo_tasks::function]
+    pub async fn transitive_size(self) -> Result<U64Vc> {
+        let size = self
+            .assets()
+            .await?
+            .iter()
+            .map(|&asset| async move { Ok(*asset_size(asset).await?) })
+            .try_join()
+            .await?
+            .into_iter()
+            .sum();
+        Ok(U64Vc::cell(size))
+    }
+
+    /// Compares the available assets with the ones of `other`, e.g. to find
+    /// out why the hash of an availability info changed between builds.
+    #[turbo_tasks::function]
+    pub async fn diff(self, other: AvailableAssetsVc) -> Result<AvailableAssetsDiffVc> {
+        let assets = self.assets().await?;
+        let other_assets = other.assets().await?;
+        Ok(AvailableAssetsDiff {
+            removed: sorted_by_ident(
+                assets
+                    .iter()
+                    .filter(|asset| !other_assets.contains(*asset))
+                    .copied(),
+            )
+            .await?,
+            added: sorted_by_ident(
+                other_assets
+                    .iter()
+                    .filter(|asset| !assets.contains(*asset))
+                    .copied(),
+            )
+            .await?,
+        }
+        .cell())
+    }
+
+    /// Returns all chunkable assets that are reachable from the roots of this
+    /// and all parent [AvailableAssets].
+    #[turbo_tasks::function]
+    async fn assets(self) -> Result<AssetsSetVc> {
+        let mut assets = IndexSet::new();
+        let mut current = Some(self);
+        while let Some(available_assets) = current {
+            let this = available_assets.await?;
+            for root in this.roots.iter() {
+                assets.extend(chunkable_assets_set(*root).await?.iter().copied());
+            }
+            current = this.parent;
+        }
+        Ok(AssetsSetVc::cell(assets))
+    }
+}
+
+/// The difference between two [AvailableAssets], see
+/// [AvailableAssetsVc::diff]. Both lists are ordered by the ident of the
+/// assets.
+#[turbo_tasks::value(shared)]
+pub struct AvailableAssetsDiff {
+    /// Assets which are available in `self`, but not in `other`.
+    pub removed: Vec<AssetVc>,
+    /// Assets which are available in `other`, but not in `self`.
+    pub added: Vec<AssetVc>,
+}
+
+async fn sorted_by_ident(assets: impl Iterator<Item = AssetVc>) -> Result<Vec<AssetVc>> {
+    let mut assets = assets
+        .map(|asset| async move { Ok((asset.ident().to_string().await?, asset)) })
+        .try_join()
+        .await?;
+    assets.sort_by(|(a, _), (b, _)| a.cmp(b));
+    Ok(assets.into_iter().map(|(_, asset)| asset).collect())
+}
+
+#[turbo_tasks::function]
+async fn asset_size(asset: AssetVc) -> Result<U64Vc> {
+    let size = match &*asset.content().await? {
+        AssetContent::File(file) => match &*file.await? {
+            FileContent::Content(file) => file.content().len() as u64,
+            FileContent::NotFound => 0,
+        },
+        AssetContent::Redirect { .. } => 0,
+    };
+    Ok(U64Vc::cell(size))
 }
 
 #[turbo_tasks::function]
@@ -1,12 +1,14 @@
 use std::fmt::Debug;
 
-use anyhow::Result;
-use turbo_tasks::primitives::{BoolVc, StringVc};
+use turbo_tasks::primitives::{BoolVc, OptionStringVc, OptionU64Vc, StringVc, UsizeVc};
 use turbo_tasks_fs::FileSystemPathVc;
 
 use super::{ChunkVc, EvaluatableAssetsVc};
 use crate::{
-    asset::AssetVc, environment::EnvironmentVc, ident::AssetIdentVc, output::OutputAssetsVc,
+    asset::AssetVc,
+    environment::{ChunkLoadingVc, EnvironmentVc},
+    ident::AssetIdentVc,
+    output::{OptionOutputAssetVc, OutputAssetVc, OutputAssetsVc},
 };
 
 /// A context for the chunking that influences the way chunks are created
@@ -19,6 +21,23 @@ pub trait ChunkingContext {
     // environment since this can change due to transitions in the module graph
     fn environment(&self) -> EnvironmentVc;
 
+    /// How the runtime loads the chunks of async chunk groups. Defaults to the
+    /// strategy of the [ChunkingContext::environment].
+    fn chunk_loading(self_vc: ChunkingContextVc) -> ChunkLoadingVc {
+        self_vc.environment().chunk_loading()
+    }
+
+    /// A chunking context for chunks which are evaluated in `environment`,
+    /// e.g. the chunks of a web worker. The chunk loading strategy is derived
+    /// from `environment`.
+    fn with_environment(&self, environment: EnvironmentVc) -> ChunkingContextVc;
+
+    /// The base path which is prepended to the paths of chunks relative to the
+    /// [ChunkingContext::output_root] to get the URL they are loaded from.
+    fn chunk_base_path(&self) -> OptionStringVc {
+        OptionStringVc::cell(None)
+    }
+
     // TODO(alexkirsz) Remove this from the chunking context. This should be at the
     // discretion of chunking context implementors. However, we currently use this
     // in a couple of places in `turbopack-css`, so we need to remove that
@@ -47,6 +66,43 @@ pub trait ChunkingContext {
 
     fn with_layer(&self, layer: &str) -> ChunkingContextVc;
 
+    /// The name of the chunks created by this chunking context, see
+    /// [ChunkingContext::with_chunk_name].
+    fn chunk_name(&self) -> OptionStringVc {
+        OptionStringVc::cell(None)
+    }
+
+    /// A chunking context whose chunk paths start with `name`, followed by a
+    /// hash of the chunk, so that chunks with the same name don't collide.
+    /// This is used to name the chunks of async chunk groups, e.g. after a
+    /// `webpackChunkName` magic comment. The name should be sanitized with
+    /// [sanitize_chunk_name]. An empty name removes the name.
+    fn with_chunk_name(&self, name: &str) -> ChunkingContextVc;
+
+    /// Chunks with fewer chunk items than this are merged into neighboring
+    /// chunks of the same chunk group, see
+    /// [crate::chunk::optimize::merge_small_chunks]. The default of 0 disables
+    /// merging.
+    fn min_chunk_size(&self) -> UsizeVc {
+        UsizeVc::cell(0)
+    }
+
+    /// Chunks are not merged beyond this number of chunk items.
+    fn max_chunk_size(&self) -> UsizeVc {
+        UsizeVc::cell(usize::MAX)
+    }
+
+    /// The estimated size in bytes that the content of an emitted chunk should
+    /// stay under. Larger chunks are split into multiple chunks which are
+    /// loaded in parallel. `None` disables splitting.
+    fn chunk_size_budget(&self) -> OptionU64Vc {
+        OptionU64Vc::cell(None)
+    }
+
+    /// The output assets which are emitted for `chunk`. This can be more than
+    /// one asset when the chunk is split.
+    fn chunk_output_assets(&self, chunk: ChunkVc) -> OutputAssetsVc;
+
     fn chunk_group(&self, entry: ChunkVc) -> OutputAssetsVc;
 
     fn evaluated_chunk_group(
@@ -54,4 +110,54 @@ pub trait ChunkingContext {
         entry: ChunkVc,
         evaluatable_assets: EvaluatableAssetsVc,
     ) -> OutputAssetsVc;
+
+    /// The output asset which contains the runtime (module loader) that loads
+    /// and evaluates the evaluated chunk groups of `entries`, separately from
+    /// the chunk groups themselves. Implementors can return a minimal runtime
+    /// here, e.g. for embedded targets.
+    ///
+    /// Returns `None` when the runtime is bundled into the evaluated chunk
+    /// group, see [ChunkingContext::evaluated_chunk_group], which is the
+    /// default.
+    fn runtime_chunk(&self, _entries: EvaluatableAssetsVc) -> OptionOutputAssetVc {
+        OptionOutputAssetVc::cell(None)
+    }
+}
+
+/// Sanitizes a chunk name given by the user, e.g. in a magic comment, for use
+/// in file names. The name is lowercased and other characters than `a-z`,
+/// `0-9`, `-` and `_` are replaced by `-`. Returns `None` when nothing is left
+/// of the name.
+pub fn sanitize_chunk_name(name: &str) -> Option<String> {
+    let mut sanitized = String::with_capacity(name.len());
+    for c in name.trim().chars().flat_map(char::to_lowercase) {
+        match c {
+            'a'..='z' | '0'..='9' | '_' => sanitized.push(c),
+            // Runs of replaced characters are replaced by a single `-`.
+            _ if !sanitized.ends_with('-') => sanitized.push('-'),
+            _ => {}
+        }
+    }
+    let sanitized = sanitized.trim_matches('-');
+    (!sanitized.is_empty()).then(|| sanitized.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::sanitize_chunk_name;
+
+    #[test]
+    fn sanitizes_chunk_names() {
+        assert_eq!(sanitize_chunk_name("settings").as_deref(), Some("settings"));
+        assert_eq!(
+            sanitize_chunk_name("Admin/User Settings").as_deref(),
+            Some("admin-user-settings")
+        );
+        assert_eq!(
+            sanitize_chunk_name(" page_2--[id] ").as_deref(),
+            Some("page_2-id")
+        );
+        assert_eq!(sanitize_chunk_name(""), None);
+        assert_eq!(sanitize_chunk_name("@/ü"), None);
+    }
 }
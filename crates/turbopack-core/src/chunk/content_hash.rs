@@ -0,0 +1,38 @@
+use anyhow::Result;
+use turbo_tasks::primitives::U64Vc;
+use turbo_tasks_fs::FileSystemPath;
+use turbo_tasks_hash::Xxh3Hash64Hasher;
+
+use super::{Chunk, ChunkVc, ChunkingContext};
+use crate::output::OutputAsset;
+
+#[turbo_tasks::value_impl]
+impl ChunkVc {
+    /// A hash of the content which is emitted for the chunk. It combines the
+    /// [OutputAsset::content_hash]es of the output assets of the chunk, so it
+    /// changes exactly when one of the emitted files does.
+    #[turbo_tasks::function]
+    pub async fn content_hash_stable(self) -> Result<U64Vc> {
+        let assets = self.chunking_context().chunk_output_assets(self).await?;
+        let mut hasher = Xxh3Hash64Hasher::new();
+        for asset in assets.iter() {
+            hasher.write_value(*asset.content_hash().await?);
+        }
+        Ok(U64Vc::cell(hasher.finish()))
+    }
+}
+
+/// Returns the file name of `path` with the first 8 characters of the hex
+/// encoded `content_hash` inserted before its extension, e.g.
+/// `index.0123abcd.js`.
+pub fn hashed_file_name(path: &FileSystemPath, content_hash: &str) -> String {
+    let file_name = path.file_name();
+    let content_hash = &content_hash[..8];
+    match path.extension() {
+        Some(ext) => format!(
+            "{basename}.{content_hash}.{ext}",
+            basename = &file_name[..file_name.len() - ext.len() - 1],
+        ),
+        None => format!("{file_name}.{content_hash}"),
+    }
+}
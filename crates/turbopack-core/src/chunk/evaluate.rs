@@ -1,4 +1,5 @@
 use anyhow::{bail, Result};
+use indexmap::IndexSet;
 use turbo_tasks::{Value, ValueToString};
 
 use super::{ChunkableModule, ChunkableModuleVc};
@@ -7,6 +8,7 @@ use crate::{
     context::{AssetContext, AssetContextVc},
     module::{Module, ModuleVc},
     reference_type::{EntryReferenceSubType, ReferenceType},
+    resolve::{origin::ResolveOriginVc, parse::RequestVc},
     source::SourceVc,
 };
 
@@ -65,3 +67,52 @@ impl EvaluatableAssetsVc {
         Ok(EvaluatableAssets(entries).cell())
     }
 }
+
+/// Resolves the runtime entry `requests` from `origin` to the evaluatable
+/// assets for [crate::chunk::ChunkingContext::evaluated_chunk_group].
+///
+/// Only the first primary asset of a request is used when it resolves to
+/// multiple assets. Resolved sources which aren't modules yet are processed
+/// with `context`. Assets which are resolved by multiple requests are only
+/// included once, in the order of their first request.
+#[turbo_tasks::function]
+pub async fn evaluatable_assets_from_requests(
+    origin: ResolveOriginVc,
+    context: AssetContextVc,
+    requests: Vec<RequestVc>,
+) -> Result<EvaluatableAssetsVc> {
+    let reference_type = ReferenceType::Entry(EntryReferenceSubType::Runtime);
+    let mut entries = IndexSet::new();
+    for request in requests {
+        let result = origin.resolve_asset(
+            request,
+            origin.resolve_options(Value::new(reference_type.clone())),
+            Value::new(reference_type.clone()),
+        );
+        let Some(asset) = *result.first_asset().await? else {
+            bail!(
+                "runtime entry {} could not be resolved from {}",
+                request.to_string().await?,
+                origin.origin_path().to_string().await?
+            );
+        };
+        let asset = match (
+            ModuleVc::resolve_from(asset).await?,
+            SourceVc::resolve_from(asset).await?,
+        ) {
+            (None, Some(source)) => context
+                .process(source, Value::new(reference_type.clone()))
+                .into(),
+            _ => asset,
+        };
+        let Some(entry) = EvaluatableAssetVc::resolve_from(asset).await? else {
+            bail!(
+                "runtime entry {} resolved to {}, which cannot be evaluated",
+                request.to_string().await?,
+                asset.ident().to_string().await?
+            );
+        };
+        entries.insert(entry);
+    }
+    Ok(EvaluatableAssetsVc::cell(entries.into_iter().collect()))
+}
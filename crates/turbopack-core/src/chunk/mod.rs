@@ -2,6 +2,7 @@ pub mod availability_info;
 pub mod available_assets;
 pub(crate) mod chunking_context;
 pub(crate) mod containment_tree;
+pub(crate) mod content_hash;
 pub(crate) mod data;
 pub(crate) mod evaluate;
 pub mod optimize;
@@ -21,7 +22,7 @@ use tracing::{info_span, Span};
 use turbo_tasks::{
     debug::ValueDebugFormat,
     graph::{AdjacencyMap, GraphTraversal, GraphTraversalResult, Visit, VisitControlFlow},
-    primitives::{StringReadRef, StringVc},
+    primitives::{OptionStringVc, StringReadRef, StringVc},
     trace::TraceRawVcs,
     TryJoinIterExt, Value, ValueToString, ValueToStringVc,
 };
@@ -30,9 +31,13 @@ use turbo_tasks_hash::DeterministicHash;
 
 use self::availability_info::AvailabilityInfo;
 pub use self::{
-    chunking_context::{ChunkingContext, ChunkingContextVc},
+    chunking_context::{sanitize_chunk_name, ChunkingContext, ChunkingContextVc},
+    content_hash::hashed_file_name,
     data::{ChunkData, ChunkDataOption, ChunkDataOptionVc, ChunkDataVc, ChunksData, ChunksDataVc},
-    evaluate::{EvaluatableAsset, EvaluatableAssetVc, EvaluatableAssets, EvaluatableAssetsVc},
+    evaluate::{
+        evaluatable_assets_from_requests, EvaluatableAsset, EvaluatableAssetVc, EvaluatableAssets,
+        EvaluatableAssetsVc,
+    },
     passthrough_asset::{PassthroughAsset, PassthroughAssetVc},
 };
 use crate::{
@@ -189,6 +194,12 @@ pub trait ChunkableModuleReference: AssetReference + ValueToString {
     fn chunking_type(&self) -> ChunkingTypeOptionVc {
         ChunkingTypeOptionVc::cell(Some(ChunkingType::default()))
     }
+
+    /// The name of the chunks of the chunk group which is created for a
+    /// [ChunkingType::Async] reference, see [ChunkingContext::with_chunk_name].
+    fn chunk_name(&self) -> OptionStringVc {
+        OptionStringVc::cell(None)
+    }
 }
 
 /// A reference to multiple chunks from a [ChunkGroup]
@@ -237,6 +248,8 @@ impl ValueToString for ChunkGroupReference {
 
 pub struct ChunkContentResult<I> {
     pub chunk_items: Vec<I>,
+    /// The assets the `chunk_items` were created from, in the same order.
+    pub chunk_item_assets: Vec<AssetVc>,
     pub chunks: Vec<ChunkVc>,
     pub external_asset_references: Vec<AssetReferenceVc>,
     pub availability_info: AvailabilityInfo,
@@ -283,9 +296,15 @@ where
 enum ChunkContentGraphNode<I> {
     // An asset not placed in the current chunk, but whose references we will
     // follow to find more graph nodes.
-    PassthroughAsset { asset: AssetVc },
+    PassthroughAsset {
+        asset: AssetVc,
+    },
     // Chunk items that are placed into the current chunk
-    ChunkItem { item: I, ident: StringReadRef },
+    ChunkItem {
+        item: I,
+        asset: AssetVc,
+        ident: StringReadRef,
+    },
     // Asset that is already available and doesn't need to be included
     AvailableAsset(AssetVc),
     // Chunks that are loaded in parallel to the current chunk
@@ -370,6 +389,7 @@ where
                         Some((asset, chunking_type)),
                         ChunkContentGraphNode::ChunkItem {
                             item: chunk_item,
+                            asset,
                             ident: asset.ident().to_string().await?,
                         },
                     ));
@@ -411,6 +431,7 @@ where
                             Some((asset, chunking_type)),
                             ChunkContentGraphNode::ChunkItem {
                                 item: chunk_item,
+                                asset,
                                 ident: asset.ident().to_string().await?,
                             },
                         ));
@@ -426,8 +447,17 @@ where
                 ));
             }
             ChunkingType::Async => {
+                // The chunk group is only named by the reference. It isn't named after the
+                // chunk group of the importing chunk.
+                let chunking_context = context.chunking_context.with_chunk_name(
+                    chunkable_asset_reference
+                        .chunk_name()
+                        .await?
+                        .as_deref()
+                        .unwrap_or_default(),
+                );
                 if let Some(manifest_loader_item) = I::from_async_asset(
-                    context.chunking_context,
+                    chunking_context,
                     chunkable_asset,
                     context.availability_info,
                 )
@@ -437,6 +467,7 @@ where
                         Some((asset, chunking_type)),
                         ChunkContentGraphNode::ChunkItem {
                             item: manifest_loader_item,
+                            asset,
                             ident: asset.ident().to_string().await?,
                         },
                     ));
@@ -563,6 +594,7 @@ where
                 Some((entry, ChunkingType::Placed)),
                 ChunkContentGraphNode::ChunkItem {
                     item: I::from_asset(chunking_context, entry).await?.unwrap(),
+                    asset: entry,
                     ident: entry.ident().to_string().await?,
                 },
             ))
@@ -593,6 +625,7 @@ where
     let graph_nodes: Vec<_> = traversal_result?.into_reverse_topological().collect();
 
     let mut chunk_items = Vec::new();
+    let mut chunk_item_assets = Vec::new();
     let mut chunks = Vec::new();
     let mut external_asset_references = Vec::new();
 
@@ -600,8 +633,9 @@ where
         match graph_node {
             ChunkContentGraphNode::AvailableAsset(_)
             | ChunkContentGraphNode::PassthroughAsset { .. } => {}
-            ChunkContentGraphNode::ChunkItem { item, .. } => {
+            ChunkContentGraphNode::ChunkItem { item, asset, .. } => {
                 chunk_items.push(item);
+                chunk_item_assets.push(asset);
             }
             ChunkContentGraphNode::Chunk(chunk) => {
                 chunks.push(chunk);
@@ -614,6 +648,7 @@ where
 
     Ok(Some(ChunkContentResult {
         chunk_items,
+        chunk_item_assets,
         chunks,
         external_asset_references,
         availability_info: availability_info.into_value(),
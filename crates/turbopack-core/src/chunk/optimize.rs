@@ -3,11 +3,16 @@
 //! Usually chunks are optimized by limiting their total count, restricting
 //! their size and eliminating duplicates between them.
 
+use std::{future::Future, ops::Range};
+
 use anyhow::Result;
 use turbo_tasks::TryJoinIterExt;
 use turbo_tasks_fs::{FileSystemPathOptionVc, FileSystemPathVc};
 
-use crate::chunk::containment_tree::{ContainmentTree, ContainmentTreeKey};
+use crate::chunk::{
+    containment_tree::{ContainmentTree, ContainmentTreeKey},
+    ChunkingContext, ChunkingContextVc,
+};
 
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
 struct FileSystemPathKey(FileSystemPathVc);
@@ -73,3 +78,101 @@ where
 
     Ok(optimize_tree(tree, &optimize))
 }
+
+/// Merges chunks that are smaller than the [ChunkingContext::min_chunk_size]
+/// of the chunking context into their neighbors, until they reach the minimum
+/// size. A merged chunk never exceeds [ChunkingContext::max_chunk_size].
+///
+/// Only neighboring chunks are merged and the merged chunks are passed to
+/// `merge` in their original order, so the order of the chunks and their
+/// contents is preserved. All chunks must be mergeable with each other, e.g.
+/// they must share the same availability info, so callers should only pass
+/// chunks of a single chunk group.
+///
+/// The size of a merged chunk is assumed to be the sum of the sizes of its
+/// parts, which is an upper bound when the chunks share some content.
+pub async fn merge_small_chunks<T, Size, SizeFuture, Merge, MergeFuture>(
+    chunking_context: ChunkingContextVc,
+    chunks: Vec<T>,
+    size: Size,
+    merge: Merge,
+) -> Result<Vec<T>>
+where
+    T: Clone,
+    Size: Fn(T) -> SizeFuture,
+    SizeFuture: Future<Output = Result<usize>>,
+    Merge: Fn(Vec<T>) -> MergeFuture,
+    MergeFuture: Future<Output = Result<T>>,
+{
+    let min_size = *chunking_context.min_chunk_size().await?;
+    if min_size == 0 || chunks.len() < 2 {
+        return Ok(chunks);
+    }
+    let max_size = *chunking_context.max_chunk_size().await?;
+
+    let sizes = chunks
+        .iter()
+        .map(|chunk| size(chunk.clone()))
+        .try_join()
+        .await?;
+
+    let mut merged = Vec::new();
+    for range in merge_ranges(&sizes, min_size, max_size) {
+        if range.len() == 1 {
+            merged.push(chunks[range.start].clone());
+        } else {
+            merged.push(merge(chunks[range].to_vec()).await?);
+        }
+    }
+    Ok(merged)
+}
+
+/// Splits chunks of the given sizes into ranges of neighboring chunks that
+/// should be merged. A chunk is added to the range before it when either of
+/// them is smaller than `min_size` and the sum of their sizes doesn't exceed
+/// `max_size`.
+fn merge_ranges(sizes: &[usize], min_size: usize, max_size: usize) -> Vec<Range<usize>> {
+    let mut ranges: Vec<(Range<usize>, usize)> = Vec::new();
+    for (index, &size) in sizes.iter().enumerate() {
+        if let Some((range, total)) = ranges.last_mut() {
+            let merged_size = total.saturating_add(size);
+            if (*total < min_size || size < min_size) && merged_size <= max_size {
+                range.end = index + 1;
+                *total = merged_size;
+                continue;
+            }
+        }
+        ranges.push((index..index + 1, size));
+    }
+    ranges.into_iter().map(|(range, _)| range).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::merge_ranges;
+
+    #[test]
+    fn merges_small_chunks_into_neighbors() {
+        assert_eq!(
+            merge_ranges(&[1, 1, 1, 10, 1, 10], 3, 100),
+            vec![0..3, 3..5, 5..6]
+        );
+    }
+
+    #[test]
+    fn keeps_chunks_at_the_minimum() {
+        assert_eq!(merge_ranges(&[3, 3, 3], 3, 100), vec![0..1, 1..2, 2..3]);
+    }
+
+    #[test]
+    fn never_exceeds_the_maximum() {
+        assert_eq!(merge_ranges(&[1, 9, 1, 9], 5, 10), vec![0..2, 2..4]);
+        assert_eq!(merge_ranges(&[1, 10, 1], 5, 10), vec![0..1, 1..2, 2..3]);
+    }
+
+    #[test]
+    fn merges_into_the_next_chunk() {
+        assert_eq!(merge_ranges(&[10, 2, 10], 5, 12), vec![0..2, 2..3]);
+        assert_eq!(merge_ranges(&[10, 4, 6], 5, 12), vec![0..1, 1..3]);
+    }
+}
@@ -0,0 +1,102 @@
+use anyhow::{bail, Result};
+use turbo_tasks::{primitives::StringVc, CompletionVc, CompletionsVc, Value, ValueToString};
+use turbo_tasks_fs::FileSystemPathVc;
+use turbo_tasks_hash::{encode_hex, Xxh3Hash64Hasher};
+
+use crate::{
+    asset::{Asset, AssetContentVc, AssetVc},
+    ident::AssetIdentVc,
+    output::{OutputAsset, OutputAssetVc},
+};
+
+/// An output asset which consists of multiple files that are emitted as a
+/// unit, e.g. a chunk together with its source map and a manifest entry.
+///
+/// The first file is the primary file: its path is the path of the ident and
+/// its content is the content of the asset. The ident also includes a hash of
+/// the paths of all files.
+///
+/// Emitting the asset with
+/// [write_output_asset](crate::output::write_output_asset) writes all other
+/// files first, and the primary file only once they have been written. So the
+/// primary file never exists without the other files, e.g. a chunk without its
+/// source map. Emit loops which write the content of an asset to its path
+/// directly only write the primary file.
+#[turbo_tasks::value]
+pub struct CompositeOutputAsset {
+    files: Vec<(FileSystemPathVc, AssetContentVc)>,
+}
+
+impl CompositeOutputAssetVc {
+    /// Creates a composite output asset from the paths and contents of its
+    /// files. The first file is the primary file.
+    pub fn new(files: Vec<(FileSystemPathVc, AssetContentVc)>) -> Result<Self> {
+        if files.is_empty() {
+            bail!("a composite output asset needs at least one file");
+        }
+        Ok(CompositeOutputAsset { files }.cell())
+    }
+}
+
+impl CompositeOutputAsset {
+    /// The paths and contents of the files, the primary file first.
+    pub fn files(&self) -> &[(FileSystemPathVc, AssetContentVc)] {
+        &self.files
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl CompositeOutputAssetVc {
+    /// Writes all files, the primary file last. The returned completion
+    /// resolves once all files have been written.
+    #[turbo_tasks::function]
+    pub async fn write(self) -> Result<CompletionVc> {
+        let this = self.await?;
+        let Some(((primary_path, primary_content), others)) = this.files.split_first() else {
+            bail!("a composite output asset needs at least one file");
+        };
+        CompletionsVc::all(
+            others
+                .iter()
+                .map(|(path, content)| content.write(*path))
+                .collect(),
+        )
+        .await?;
+        Ok(primary_content.write(*primary_path))
+    }
+}
+
+#[turbo_tasks::function]
+fn modifier() -> StringVc {
+    StringVc::cell("composite".to_string())
+}
+
+#[turbo_tasks::value_impl]
+impl OutputAsset for CompositeOutputAsset {}
+
+#[turbo_tasks::value_impl]
+impl Asset for CompositeOutputAsset {
+    #[turbo_tasks::function]
+    async fn ident(&self) -> Result<AssetIdentVc> {
+        let Some((primary_path, _)) = self.files.first() else {
+            bail!("a composite output asset needs at least one file");
+        };
+        let mut hasher = Xxh3Hash64Hasher::new();
+        for (path, _) in &self.files {
+            hasher.write_ref(&*path.to_string().await?);
+        }
+
+        let mut ident = AssetIdentVc::from_path(*primary_path).await?.clone_value();
+        ident.add_modifier(modifier());
+        ident.add_modifier(StringVc::cell(encode_hex(hasher.finish())));
+        Ok(AssetIdentVc::new(Value::new(ident)))
+    }
+
+    #[turbo_tasks::function]
+    fn content(&self) -> Result<AssetContentVc> {
+        let Some((_, primary_content)) = self.files.first() else {
+            bail!("a composite output asset needs at least one file");
+        };
+        Ok(*primary_content)
+    }
+}
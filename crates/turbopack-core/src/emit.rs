@@ -0,0 +1,269 @@
+use std::{
+    collections::{hash_map::Entry, BTreeMap, HashMap, HashSet},
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use tokio::fs;
+use turbo_tasks::{trace::TraceRawVcs, ValueToString};
+use turbo_tasks_fs::{
+    rope::Rope, to_sys_path, util::unix_to_sys, FileContent, FileContentVc, FileSystemPathVc,
+};
+use turbo_tasks_hash::{encode_hex, hash_xxh3_hash64};
+
+use crate::{
+    asset::{Asset, AssetContent, AssetContentVc},
+    composite_output::CompositeOutputAssetVc,
+    output::OutputAssetsVc,
+};
+
+/// The name of the manifest in the root of [emit_assets], which lists the
+/// files written by it.
+pub const EMIT_MANIFEST: &str = ".turbopack-emit-manifest.json";
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct EmitOptions {
+    /// Removes the files which were written by a previous call with the same
+    /// root, but aren't emitted anymore.
+    pub clean: bool,
+}
+
+/// The number of files [emit_assets] has written, skipped because they were
+/// unchanged, and removed because they weren't emitted anymore.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, TraceRawVcs)]
+pub struct EmitSummary {
+    pub written: usize,
+    pub skipped: usize,
+    pub removed: usize,
+}
+
+/// The files written by [emit_assets], by their path relative to the root,
+/// with the hashes of their contents.
+#[derive(Default, Serialize, Deserialize)]
+struct EmitManifest {
+    files: BTreeMap<String, String>,
+}
+
+/// The contents of the files to emit, by their path relative to the root.
+#[turbo_tasks::value(transparent)]
+struct EmittedFiles(BTreeMap<String, FileContentVc>);
+
+/// Collects the files of `assets` and checks that they can be emitted to
+/// `root` together.
+#[turbo_tasks::function]
+async fn emitted_files(assets: OutputAssetsVc, root: FileSystemPathVc) -> Result<EmittedFilesVc> {
+    let root_path = root.await?;
+
+    let mut files = Vec::new();
+    for &asset in assets.await?.iter() {
+        if let Some(composite) = CompositeOutputAssetVc::resolve_from(asset).await? {
+            files.extend(composite.await?.files().iter().copied());
+        } else {
+            files.push((asset.ident().path(), asset.content()));
+        }
+    }
+
+    // The relative path of each file, by its lowercase path. The manifest is
+    // included, so that no asset overwrites it.
+    let mut paths = HashMap::from([(EMIT_MANIFEST.to_lowercase(), EMIT_MANIFEST.to_string())]);
+    let mut contents = BTreeMap::new();
+    for (path, content) in files {
+        let path_ref = path.await?;
+        let Some(relative) = root_path.get_path_to(&path_ref) else {
+            bail!(
+                "{} can't be emitted, as it isn't inside of {}",
+                path.to_string().await?,
+                root.to_string().await?
+            );
+        };
+        match paths.entry(relative.to_lowercase()) {
+            Entry::Occupied(entry) if entry.get() != relative => bail!(
+                "{} and {} can't both be emitted to {}, as their paths only differ in case, which \
+                 collides on case-insensitive file systems",
+                entry.get(),
+                relative,
+                root.to_string().await?
+            ),
+            Entry::Occupied(_) => {
+                let bytes = file_content(content).await?.map(|(_, bytes)| bytes);
+                let previous = match contents.get(relative) {
+                    Some(&file) => file_content_bytes(file).await?,
+                    None => None,
+                };
+                if bytes != previous {
+                    bail!(
+                        "{} is emitted twice to {} with different contents",
+                        relative,
+                        root.to_string().await?
+                    );
+                }
+                continue;
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(relative.to_string());
+            }
+        }
+        if let Some((file, _)) = file_content(content).await? {
+            contents.insert(relative.to_string(), file);
+        }
+    }
+
+    Ok(EmittedFilesVc::cell(contents))
+}
+
+/// Writes the contents of `assets` to their paths, which need to be inside
+/// `root` on a disk file system. A
+/// [CompositeOutputAsset](crate::composite_output::CompositeOutputAsset)
+/// writes all of its files.
+///
+/// Each file is written to a temporary file which is renamed to the path, so
+/// the path never contains a partially written file. Files which already have
+/// the content are skipped. The written files are listed in a manifest in the
+/// root, so with [EmitOptions::clean] the files of a previous call which
+/// aren't emitted anymore are removed. Only files from the manifest which
+/// still have the content that was written are removed.
+///
+/// Fails without writing anything when the paths of two files only differ in
+/// case, as they would overwrite each other on a case-insensitive file system.
+///
+/// Only collecting the files is cached. The files on disk are read untracked
+/// and compared on every call, as they are written by this function itself.
+pub async fn emit_assets(
+    assets: OutputAssetsVc,
+    root: FileSystemPathVc,
+    options: EmitOptions,
+) -> Result<EmitSummary> {
+    let files = emitted_files(assets, root).await?;
+    let Some(sys_root) = to_sys_path(root).await? else {
+        bail!(
+            "assets can only be emitted to a disk file system, but {} isn't on one",
+            root.to_string().await?
+        );
+    };
+
+    let manifest_path = sys_root.join(EMIT_MANIFEST);
+    let previous = read_manifest(&manifest_path).await?;
+    let mut manifest = EmitManifest::default();
+    let mut summary = EmitSummary::default();
+
+    let mut emitted = HashSet::from([EMIT_MANIFEST.to_lowercase()]);
+    for (relative, &file) in files.iter() {
+        emitted.insert(relative.to_lowercase());
+        let Some(content) = file_content_bytes(file).await? else {
+            continue;
+        };
+        let content = content.to_bytes()?;
+        let hash = encode_hex(hash_xxh3_hash64(&*content));
+        let path = sys_path(&sys_root, relative);
+        if content_hash(&path).await?.as_ref() == Some(&hash) {
+            summary.skipped += 1;
+        } else {
+            write_atomically(&path, &content).await?;
+            summary.written += 1;
+        }
+        manifest.files.insert(relative.clone(), hash);
+    }
+
+    for (relative, hash) in previous.files {
+        // A file which is emitted again, possibly with a different case,
+        // isn't stale.
+        if emitted.contains(&relative.to_lowercase()) || !is_normalized(&relative) {
+            continue;
+        }
+        let path = sys_path(&sys_root, &relative);
+        if content_hash(&path).await?.as_ref() != Some(&hash) {
+            // The file was changed or removed by someone else since it was
+            // written, so it isn't ours anymore.
+            continue;
+        }
+        if options.clean {
+            fs::remove_file(&path)
+                .await
+                .with_context(|| format!("failed to remove {}", path.display()))?;
+            summary.removed += 1;
+        } else {
+            manifest.files.insert(relative, hash);
+        }
+    }
+
+    write_atomically(&manifest_path, &serde_json::to_vec_pretty(&manifest)?).await?;
+
+    Ok(summary)
+}
+
+/// The file and its content, or `None` if there is no file to write.
+async fn file_content(content: AssetContentVc) -> Result<Option<(FileContentVc, Rope)>> {
+    Ok(match &*content.await? {
+        AssetContent::File(file) => file_content_bytes(*file).await?.map(|bytes| (*file, bytes)),
+        AssetContent::Redirect { target, .. } => {
+            bail!("the link to {target} can't be emitted, only files can")
+        }
+    })
+}
+
+/// The content of the file, or `None` if there is no file.
+async fn file_content_bytes(file: FileContentVc) -> Result<Option<Rope>> {
+    Ok(match &*file.await? {
+        FileContent::Content(file) => Some(file.content().clone()),
+        FileContent::NotFound => None,
+    })
+}
+
+async fn read_manifest(path: &Path) -> Result<EmitManifest> {
+    match fs::read(path).await {
+        // An unreadable manifest is ignored, which only means that the files
+        // it lists aren't removed.
+        Ok(content) => Ok(serde_json::from_slice(&content).unwrap_or_default()),
+        Err(err) if err.kind() == ErrorKind::NotFound => Ok(EmitManifest::default()),
+        Err(err) => Err(err).with_context(|| format!("failed to read {}", path.display())),
+    }
+}
+
+/// The hash of the content of the file at `path`, or `None` if there is no
+/// file.
+async fn content_hash(path: &Path) -> Result<Option<String>> {
+    match fs::read(path).await {
+        Ok(content) => Ok(Some(encode_hex(hash_xxh3_hash64(&*content)))),
+        Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err).with_context(|| format!("failed to read {}", path.display())),
+    }
+}
+
+/// Writes the content to a temporary file next to `path`, and renames it to
+/// `path` once it's complete.
+async fn write_atomically(path: &Path, content: &[u8]) -> Result<()> {
+    let (Some(parent), Some(name)) = (path.parent(), path.file_name()) else {
+        bail!("{} isn't a path to a file", path.display());
+    };
+    fs::create_dir_all(parent)
+        .await
+        .with_context(|| format!("failed to create directory {}", parent.display()))?;
+    let temp_path = parent.join(format!(
+        ".{}.{}.tmp",
+        name.to_string_lossy(),
+        std::process::id()
+    ));
+    fs::write(&temp_path, content)
+        .await
+        .with_context(|| format!("failed to write to {}", temp_path.display()))?;
+    if let Err(err) = fs::rename(&temp_path, path).await {
+        let _ = fs::remove_file(&temp_path).await;
+        return Err(err).with_context(|| format!("failed to write to {}", path.display()));
+    }
+    Ok(())
+}
+
+fn sys_path(root: &Path, relative: &str) -> PathBuf {
+    root.join(&*unix_to_sys(relative))
+}
+
+/// Paths in the manifest are relative paths without `.` or `..` segments, as
+/// long as it wasn't edited.
+fn is_normalized(relative: &str) -> bool {
+    !relative.is_empty()
+        && relative
+            .split('/')
+            .all(|segment| !matches!(segment, "" | "." | ".."))
+}
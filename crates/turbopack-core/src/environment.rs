@@ -144,15 +144,21 @@ impl Rendering {
     }
 }
 
-#[turbo_tasks::value]
-#[derive(Default)]
+/// How the runtime loads the chunks of async chunk groups.
+#[turbo_tasks::value(shared, serialization = "auto_for_input")]
+#[derive(Debug, Default, Clone, Copy, Hash, PartialOrd, Ord)]
 pub enum ChunkLoading {
     #[default]
     None,
-    /// CommonJS in Node.js
-    NodeJs,
+    /// CommonJS `require` in Node.js
+    NodeRequire,
     /// <script> and <link> tags in the browser
-    Dom,
+    DomScript,
+    /// `import()` of the chunk url, e.g. in an Electron renderer with node
+    /// integration
+    DynamicImport,
+    /// `importScripts` in web workers without a DOM
+    ImportScripts,
 }
 
 #[turbo_tasks::value]
@@ -226,6 +232,33 @@ impl EnvironmentVc {
         })
     }
 
+    #[turbo_tasks::function]
+    pub async fn is_browser(self) -> Result<BoolVc> {
+        let this = self.await?;
+        Ok(BoolVc::cell(matches!(
+            this.execution,
+            ExecutionEnvironment::Browser(_)
+        )))
+    }
+
+    /// The result of `typeof name` for the globals which only exist in some
+    /// environments, e.g. `window`. `None` when it's unknown.
+    #[turbo_tasks::function]
+    pub async fn type_of_global(self, name: String) -> Result<OptionStringVc> {
+        let env = self.await?;
+        let type_name = match (env.execution, &*name) {
+            (ExecutionEnvironment::Browser(_), "window" | "document" | "self") => Some("object"),
+            (
+                ExecutionEnvironment::NodeJsBuildTime(..) | ExecutionEnvironment::NodeJsLambda(_),
+                "window" | "document" | "self",
+            ) => Some("undefined"),
+            (ExecutionEnvironment::EdgeWorker(_), "window" | "document") => Some("undefined"),
+            (ExecutionEnvironment::EdgeWorker(_), "self") => Some("object"),
+            _ => None,
+        };
+        Ok(OptionStringVc::cell(type_name.map(|s| s.to_string())))
+    }
+
     #[turbo_tasks::function]
     pub async fn resolve_extensions(self) -> Result<StringsVc> {
         let env = self.await?;
@@ -302,13 +335,68 @@ impl EnvironmentVc {
         let env = self.await?;
         Ok(match env.execution {
             ExecutionEnvironment::NodeJsBuildTime(_) | ExecutionEnvironment::NodeJsLambda(_) => {
-                ChunkLoading::NodeJs.cell()
+                ChunkLoading::NodeRequire.cell()
             }
             ExecutionEnvironment::EdgeWorker(_) => ChunkLoading::None.cell(),
-            ExecutionEnvironment::Browser(_) => ChunkLoading::Dom.cell(),
+            ExecutionEnvironment::Browser(browser_env) => {
+                let browser_env = browser_env.await?;
+                if !browser_env.dom && (browser_env.web_worker || browser_env.service_worker) {
+                    ChunkLoading::ImportScripts.cell()
+                } else {
+                    ChunkLoading::DomScript.cell()
+                }
+            }
             _ => ChunkLoading::None.cell(),
         })
     }
+
+    /// Whether chunks can be loaded with `chunk_loading` in this environment,
+    /// e.g. `importScripts` only exists in web workers and `<script>` tags need
+    /// a DOM.
+    #[turbo_tasks::function]
+    pub async fn supports_chunk_loading(self, chunk_loading: ChunkLoadingVc) -> Result<BoolVc> {
+        let env = self.await?;
+        let browser_env = match env.execution {
+            ExecutionEnvironment::Browser(browser_env) => Some(browser_env.await?),
+            _ => None,
+        };
+        Ok(BoolVc::cell(match *chunk_loading.await? {
+            ChunkLoading::None => true,
+            ChunkLoading::NodeRequire => matches!(
+                env.execution,
+                ExecutionEnvironment::NodeJsBuildTime(_) | ExecutionEnvironment::NodeJsLambda(_)
+            ),
+            ChunkLoading::DomScript | ChunkLoading::DynamicImport => {
+                browser_env.map_or(false, |env| env.dom)
+            }
+            ChunkLoading::ImportScripts => browser_env.map_or(false, |env| {
+                !env.dom && (env.web_worker || env.service_worker)
+            }),
+        }))
+    }
+
+    /// The environment of the web workers started in this environment. They
+    /// have no DOM, but support the same browsers. Other environments are
+    /// returned unchanged.
+    #[turbo_tasks::function]
+    pub async fn web_worker(self) -> Result<EnvironmentVc> {
+        let env = self.await?;
+        let ExecutionEnvironment::Browser(browser_env) = env.execution else {
+            return Ok(self);
+        };
+        let browser_env = browser_env.await?;
+        Ok(EnvironmentVc::new(Value::new(
+            ExecutionEnvironment::Browser(
+                BrowserEnvironment {
+                    dom: false,
+                    web_worker: true,
+                    service_worker: false,
+                    browserslist_query: browser_env.browserslist_query.clone(),
+                }
+                .into(),
+            ),
+        )))
+    }
 }
 
 pub enum NodeEnvironmentType {
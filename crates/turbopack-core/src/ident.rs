@@ -58,6 +58,9 @@ impl ValueToString for AssetIdent {
         for (key, asset) in &self.assets {
             write!(s, "/({})/{}", key.await?, asset.to_string().await?)?;
         }
+        if let Some(part) = self.part {
+            write!(s, " <{}>", part.to_string().await?)?;
+        }
         if !self.modifiers.is_empty() {
             s.push_str(" (");
             for (i, modifier) in self.modifiers.iter().enumerate() {
@@ -244,6 +247,24 @@ impl AssetIdentVc {
         name += expected_extension;
         Ok(StringVc::cell(name))
     }
+
+    /// Like [AssetIdentVc::output_name], but the name is `chunk_name`, followed
+    /// by a hash of the output name, so that assets with the same chunk name
+    /// don't collide, e.g. `settings_3f2a9c1b.js`.
+    #[turbo_tasks::function]
+    pub async fn named_output_name(
+        self,
+        context_path: FileSystemPathVc,
+        chunk_name: &str,
+        expected_extension: &str,
+    ) -> Result<StringVc> {
+        let output_name = self.output_name(context_path, expected_extension).await?;
+        let hash = encode_hex(hash_xxh3_hash64(output_name.as_bytes()));
+        Ok(StringVc::cell(format!(
+            "{chunk_name}_{}{expected_extension}",
+            &hash[..8]
+        )))
+    }
 }
 
 fn clean_separators(s: &str) -> String {
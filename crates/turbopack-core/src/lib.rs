@@ -10,7 +10,9 @@ pub mod changed;
 pub mod chunk;
 pub mod code_builder;
 pub mod compile_time_info;
+pub mod composite_output;
 pub mod context;
+pub mod emit;
 pub mod environment;
 pub mod error;
 pub mod file_source;
@@ -34,6 +36,7 @@ pub mod source_transform;
 pub mod target;
 mod utils;
 pub mod version;
+pub mod virtual_output;
 pub mod virtual_source;
 
 pub mod virtual_fs {
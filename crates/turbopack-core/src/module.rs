@@ -7,3 +7,6 @@ pub trait Module: Asset {}
 
 #[turbo_tasks::value(transparent)]
 pub struct OptionModule(Option<ModuleVc>);
+
+#[turbo_tasks::value(transparent)]
+pub struct Modules(Vec<ModuleVc>);
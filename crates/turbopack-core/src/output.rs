@@ -1,11 +1,24 @@
 use anyhow::{Context, Result};
+use turbo_tasks::{primitives::U64Vc, CompletionVc};
 
-use crate::asset::{Asset, AssetVc};
+use crate::{
+    asset::{Asset, AssetVc},
+    composite_output::CompositeOutputAssetVc,
+};
 
 /// An asset that should be outputted, e. g. written to disk or served from a
 /// server.
 #[turbo_tasks::value_trait]
-pub trait OutputAsset: Asset {}
+pub trait OutputAsset: Asset {
+    /// The xxh3 hash of the emitted bytes, e. g. to generate ETags without
+    /// hashing the file on every request. Assets which aren't files hash to 0.
+    fn content_hash(&self) -> U64Vc {
+        self.content().hash()
+    }
+}
+
+#[turbo_tasks::value(transparent)]
+pub struct OptionOutputAsset(Option<OutputAssetVc>);
 
 #[turbo_tasks::value(transparent)]
 pub struct OutputAssets(Vec<OutputAssetVc>);
@@ -27,3 +40,14 @@ pub async fn asset_to_output_asset(asset: AssetVc) -> Result<OutputAssetVc> {
         .await?
         .context("Asset must be a OutputAsset")
 }
+
+/// Writes an asset to its path. A
+/// [CompositeOutputAsset](crate::composite_output::CompositeOutputAsset) writes
+/// all of its files.
+#[turbo_tasks::function]
+pub async fn write_output_asset(asset: AssetVc) -> Result<CompletionVc> {
+    if let Some(composite) = CompositeOutputAssetVc::resolve_from(asset).await? {
+        return Ok(composite.write());
+    }
+    Ok(asset.content().write(asset.ident().path()))
+}
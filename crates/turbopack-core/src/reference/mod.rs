@@ -1,10 +1,11 @@
-use std::collections::{HashSet, VecDeque};
+use std::collections::{BTreeMap, VecDeque};
 
 use anyhow::Result;
+use indexmap::IndexSet;
 use turbo_tasks::{primitives::StringVc, TryJoinIterExt, ValueToString, ValueToStringVc};
 
 use crate::{
-    asset::{Asset, AssetVc, AssetsVc},
+    asset::{Asset, AssetCategory, AssetVc, AssetsVc},
     issue::IssueContextExt,
     resolve::{PrimaryResolveResult, ResolveResult, ResolveResultVc},
 };
@@ -150,13 +151,13 @@ pub async fn primary_referenced_assets(asset: AssetVc) -> Result<AssetsVc> {
 
 /// Aggregates all [Asset]s referenced by an [Asset] including transitively
 /// referenced [Asset]s. This basically gives all [Asset]s in a subgraph
-/// starting from the passed [Asset].
+/// starting from the passed [Asset], in breadth-first order.
 #[turbo_tasks::function]
 pub async fn all_assets(asset: AssetVc) -> Result<AssetsVc> {
     // TODO need to track import path here
     let mut queue = VecDeque::with_capacity(32);
     queue.push_back((asset, all_referenced_assets(asset)));
-    let mut assets = HashSet::new();
+    let mut assets = IndexSet::new();
     assets.insert(asset);
     while let Some((parent, references)) = queue.pop_front() {
         let references = references
@@ -170,3 +171,31 @@ pub async fn all_assets(asset: AssetVc) -> Result<AssetsVc> {
     }
     Ok(AssetsVc::cell(assets.into_iter().collect()))
 }
+
+/// The [Asset]s of [all_assets] by their [Asset::category]. Only categories
+/// with assets are included.
+#[turbo_tasks::value(transparent)]
+pub struct AssetsByType(BTreeMap<AssetCategory, AssetsVc>);
+
+/// Like [all_assets], but partitions the [Asset]s by their [Asset::category]
+/// in the same traversal. The assets of each category keep the order of
+/// [all_assets].
+#[turbo_tasks::function]
+pub async fn all_assets_by_type(asset: AssetVc) -> Result<AssetsByTypeVc> {
+    let assets = all_assets(asset).await?;
+    let categories = assets
+        .iter()
+        .map(|asset| async move { Ok((*asset, *asset.category().await?)) })
+        .try_join()
+        .await?;
+    let mut by_type = BTreeMap::<_, Vec<_>>::new();
+    for (asset, category) in categories {
+        by_type.entry(category).or_default().push(asset);
+    }
+    Ok(AssetsByTypeVc::cell(
+        by_type
+            .into_iter()
+            .map(|(category, assets)| (category, AssetsVc::cell(assets)))
+            .collect(),
+    ))
+}
@@ -1,9 +1,4 @@
-use std::{
-    borrow::Cow,
-    collections::{BTreeMap, HashMap, HashSet},
-    future::Future,
-    pin::Pin,
-};
+use std::{borrow::Cow, collections::BTreeMap, future::Future, pin::Pin};
 
 use anyhow::{anyhow, bail, Result};
 use serde_json::Value as JsonValue;
@@ -23,7 +18,7 @@ use self::{
     },
     parse::{Request, RequestVc},
     pattern::QueryMapVc,
-    remap::{ExportsField, ImportsField},
+    remap::{lookup_results, ImportsField},
 };
 use crate::{
     asset::{Asset, AssetOptionVc, AssetVc, AssetsVc},
@@ -51,16 +46,27 @@ pub(crate) mod remap;
 pub use alias_map::{
     AliasMap, AliasMapIntoIter, AliasMapLookupIterator, AliasMatch, AliasPattern, AliasTemplate,
 };
-pub use remap::{ResolveAliasMap, ResolveAliasMapVc, SubpathValue};
+pub use remap::{ExportsField, ResolveAliasMap, ResolveAliasMapVc, SubpathValue};
 
 use crate::issue::{IssueSeverity, IssueSeverityVc, OptionIssueSourceVc};
 
+/// How an external module is loaded at runtime.
+#[turbo_tasks::value(serialization = "auto_for_input")]
+#[derive(PartialOrd, Ord, Hash, Debug, Copy, Clone)]
+pub enum ExternalType {
+    /// The module is loaded with `require()`.
+    CommonJs,
+    /// The module is loaded with `import()`, so the modules which import it
+    /// are evaluated asynchronously.
+    EcmaScriptModule,
+}
+
 #[turbo_tasks::value(shared)]
 #[derive(Clone, Debug)]
 pub enum PrimaryResolveResult {
     Asset(AssetVc),
     OriginalReferenceExternal,
-    OriginalReferenceTypeExternal(String),
+    OriginalReferenceTypeExternal(String, ExternalType),
     Ignore,
     Empty,
     Custom(u8),
@@ -420,7 +426,8 @@ enum ImportsFieldResult {
         #[turbo_tasks(debug_ignore, trace_ignore)] ImportsField,
         FileSystemPathVc,
     ),
-    None,
+    /// The references to the package.json files that were consulted.
+    None(Vec<AssetReferenceVc>),
 }
 
 /// Extracts the "imports" field out of the nearest package.json, parsing it
@@ -429,17 +436,19 @@ enum ImportsFieldResult {
 async fn imports_field(context: FileSystemPathVc) -> Result<ImportsFieldResultVc> {
     let package_json_context = find_context_file(context, package_json()).await?;
     let FindContextFileResult::Found(package_json_path, _refs) = &*package_json_context else {
-        return Ok(ImportsFieldResult::None.cell());
+        return Ok(ImportsFieldResult::None(Vec::new()).cell());
     };
+    let affecting_references =
+        vec![AffectingResolvingAssetReferenceVc::new(*package_json_path).into()];
 
     let read = read_package_json(*package_json_path).await?;
     let package_json = match &*read {
         Some(json) => json,
-        None => return Ok(ImportsFieldResult::None.cell()),
+        None => return Ok(ImportsFieldResult::None(affecting_references).cell()),
     };
 
     let Some(imports) = package_json.get("imports") else {
-        return Ok(ImportsFieldResult::None.cell());
+        return Ok(ImportsFieldResult::None(affecting_references).cell());
     };
     match imports.try_into() {
         Ok(imports) => Ok(ImportsFieldResult::Some(imports, *package_json_path).cell()),
@@ -450,7 +459,7 @@ async fn imports_field(context: FileSystemPathVc) -> Result<ImportsFieldResultVc
             }
             .into();
             issue.as_issue().emit();
-            Ok(ImportsFieldResult::None.cell())
+            Ok(ImportsFieldResult::None(affecting_references).cell())
         }
     }
 }
@@ -877,6 +886,7 @@ async fn resolve_internal(
             remainder,
         } => ResolveResult::primary(PrimaryResolveResult::OriginalReferenceTypeExternal(
             format!("{}{}", protocol, remainder),
+            ExternalType::CommonJs,
         ))
         .into(),
         Request::Unknown { path } => {
@@ -917,6 +927,14 @@ async fn resolve_into_folder(
 ) -> Result<ResolveResultVc> {
     let package_json_path = package_path.join("package.json");
     let options_value = options.await?;
+    // Changing a field of the package.json could change the result, even when
+    // looking up that field failed.
+    let affecting_references: Vec<AssetReferenceVc> =
+        if read_package_json(package_json_path).await?.is_some() {
+            vec![AffectingResolvingAssetReferenceVc::new(package_json_path).into()]
+        } else {
+            Vec::new()
+        };
     for resolve_into_package in options_value.into_package.iter() {
         match resolve_into_package {
             ResolveIntoPackage::Default(req) => {
@@ -928,7 +946,8 @@ async fn resolve_into_folder(
                         )
                     })?;
                 let request = RequestVc::parse(Value::new(str.into()));
-                return Ok(resolve_internal(package_path, request, options));
+                return Ok(resolve_internal(package_path, request, options)
+                    .with_references(affecting_references));
             }
             ResolveIntoPackage::MainField(name) => {
                 if let Some(package_json) = &*read_package_json(package_json_path).await? {
@@ -941,9 +960,9 @@ async fn resolve_into_folder(
                         // we continue to try other alternatives
                         if !result.is_unresolveable() {
                             let mut result = result.clone();
-                            result.add_reference(
-                                AffectingResolvingAssetReferenceVc::new(package_json_path).into(),
-                            );
+                            for reference in affecting_references {
+                                result.add_reference(reference);
+                            }
                             return Ok(result.into());
                         }
                     }
@@ -970,7 +989,7 @@ async fn resolve_into_folder(
             }
         }
     }
-    Ok(ResolveResult::unresolveable().into())
+    Ok(ResolveResult::unresolveable_with_references(affecting_references).into())
 }
 
 async fn resolve_module_request(
@@ -981,6 +1000,9 @@ async fn resolve_module_request(
     path: &Pattern,
     _: &QueryMapVc,
 ) -> Result<ResolveResultVc> {
+    // The package.json files which were consulted without affecting the result
+    let mut affecting_references = Vec::new();
+
     // Check alias field for module aliases first
     for in_package in options_value.in_package.iter() {
         match in_package {
@@ -990,6 +1012,10 @@ async fn resolve_module_request(
                 {
                     let read = read_package_json(*package_json_path).await?;
                     if let Some(package_json) = &*read {
+                        affecting_references.extend(refs.iter().copied());
+                        affecting_references.push(
+                            AffectingResolvingAssetReferenceVc::new(*package_json_path).into(),
+                        );
                         if let Some(field_value) = package_json[field].as_object() {
                             let package_path = package_json_path.parent();
                             let full_pattern =
@@ -1027,8 +1053,10 @@ async fn resolve_module_request(
     )
     .await?;
 
+    affecting_references.extend(result.references.iter().copied());
+
     if result.packages.is_empty() {
-        return Ok(ResolveResult::unresolveable_with_references(result.references.clone()).into());
+        return Ok(ResolveResult::unresolveable_with_references(affecting_references).into());
     }
 
     let mut results = vec![];
@@ -1078,6 +1106,11 @@ async fn resolve_module_request(
                             // field exist
                             break;
                         }
+                        if read_package_json(package_json_path).await?.is_some() {
+                            affecting_references.push(
+                                AffectingResolvingAssetReferenceVc::new(package_json_path).into(),
+                            );
+                        }
                     }
                 }
             }
@@ -1088,10 +1121,7 @@ async fn resolve_module_request(
         }
     }
 
-    Ok(merge_results_with_references(
-        results,
-        result.references.clone(),
-    ))
+    Ok(merge_results_with_references(results, affecting_references))
 }
 
 async fn resolve_import_map_result(
@@ -1198,6 +1228,10 @@ async fn resolved(
     options: ResolveOptionsVc,
 ) -> Result<ResolveResultVc> {
     let RealPathResult { path, symlinks } = &*fs_path.realpath_with_links().await?;
+    let mut affecting_references: Vec<AssetReferenceVc> = symlinks
+        .iter()
+        .map(|p| AffectingResolvingAssetReferenceVc::new(*p).into())
+        .collect();
     for in_package in in_package.iter() {
         match in_package {
             ResolveInPackage::AliasField(field) => {
@@ -1206,6 +1240,10 @@ async fn resolved(
                 {
                     let read = read_package_json(*package_json_path).await?;
                     if let Some(package_json) = &*read {
+                        affecting_references.extend(refs.iter().copied());
+                        affecting_references.push(
+                            AffectingResolvingAssetReferenceVc::new(*package_json_path).into(),
+                        );
                         if let Some(field_value) = package_json[field].as_object() {
                             let package_path = package_json_path.parent();
                             if let Some(rel_path) =
@@ -1252,14 +1290,10 @@ async fn resolved(
         }
     }
 
-    Ok(ResolveResult::asset_with_references(
-        FileSourceVc::new(*path).into(),
-        symlinks
-            .iter()
-            .map(|p| AffectingResolvingAssetReferenceVc::new(*p).into())
-            .collect(),
+    Ok(
+        ResolveResult::asset_with_references(FileSourceVc::new(*path).into(), affecting_references)
+            .into(),
     )
-    .into())
 }
 
 fn handle_exports_imports_field(
@@ -1271,29 +1305,15 @@ fn handle_exports_imports_field(
     conditions: &BTreeMap<String, ConditionValue>,
     unspecified_conditions: &ConditionValue,
 ) -> Result<ResolveResultVc> {
-    let mut results = Vec::new();
-    let mut conditions_state = HashMap::new();
-    let values = exports_imports_field
-        .lookup(path)
-        .map(AliasMatch::try_into_self)
-        .collect::<Result<Vec<_>>>()?;
-    for value in values.iter() {
-        if value.add_results(
-            conditions,
-            unspecified_conditions,
-            &mut conditions_state,
-            &mut results,
-        ) {
-            break;
-        }
-    }
-    {
-        let mut duplicates_set = HashSet::new();
-        results.retain(|item| duplicates_set.insert(*item));
-    }
+    let results = lookup_results(
+        exports_imports_field,
+        path,
+        conditions,
+        unspecified_conditions,
+    )?;
     let mut resolved_results = Vec::new();
     for path in results {
-        if let Some(path) = normalize_path(path) {
+        if let Some(path) = normalize_path(&path) {
             let request = RequestVc::relative(Value::new(format!("./{}", path).into()), false);
             resolved_results.push(resolve_internal(package_path, request, options));
         }
@@ -1339,7 +1359,9 @@ async fn resolve_package_internal_with_imports_field(
     let imports_result = imports_field(context).await?;
     let (imports, package_json_path) = match &*imports_result {
         ImportsFieldResult::Some(i, p) => (i, p),
-        ImportsFieldResult::None => return Ok(ResolveResult::unresolveable().into()),
+        ImportsFieldResult::None(references) => {
+            return Ok(ResolveResult::unresolveable_with_references(references.clone()).into())
+        }
     };
 
     handle_exports_imports_field(
@@ -1457,3 +1479,15 @@ impl ModulePartVc {
         ModulePart::Internal(id).cell()
     }
 }
+
+#[turbo_tasks::value_impl]
+impl ValueToString for ModulePart {
+    #[turbo_tasks::function]
+    async fn to_string(&self) -> Result<StringVc> {
+        Ok(StringVc::cell(match self {
+            ModulePart::ModuleEvaluation => "module evaluation".to_string(),
+            ModulePart::Export(export) => format!("export {}", export.await?),
+            ModulePart::Internal(id) => format!("internal part {}", id),
+        }))
+    }
+}
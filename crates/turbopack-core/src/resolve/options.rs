@@ -10,7 +10,7 @@ use turbo_tasks_fs::{glob::GlobVc, FileSystemPathVc};
 
 use super::{
     alias_map::{AliasMap, AliasTemplate},
-    AliasPattern, PrimaryResolveResult, ResolveResult, ResolveResultVc,
+    AliasPattern, ExternalType, PrimaryResolveResult, ResolveResult, ResolveResultVc,
 };
 use crate::resolve::{parse::RequestVc, plugin::ResolvePluginVc};
 
@@ -273,7 +273,12 @@ async fn import_mapping_to_result(
             ResolveResult::primary_with_references(
                 name.as_ref().map_or_else(
                     || PrimaryResolveResult::OriginalReferenceExternal,
-                    |req| PrimaryResolveResult::OriginalReferenceTypeExternal(req.to_string()),
+                    |req| {
+                        PrimaryResolveResult::OriginalReferenceTypeExternal(
+                            req.to_string(),
+                            ExternalType::CommonJs,
+                        )
+                    },
                 ),
                 Vec::new(),
             )
@@ -1,5 +1,5 @@
 use std::{
-    collections::{BTreeMap, HashMap},
+    collections::{BTreeMap, HashMap, HashSet},
     fmt::Display,
     ops::Deref,
 };
@@ -10,7 +10,7 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
 use super::{
-    alias_map::{AliasMap, AliasMapIter, AliasPattern, AliasTemplate},
+    alias_map::{AliasMap, AliasMapIter, AliasMatch, AliasPattern, AliasTemplate},
     options::ConditionValue,
 };
 
@@ -304,6 +304,20 @@ impl TryFrom<&Value> for ExportsField {
     }
 }
 
+impl ExportsField {
+    /// The subpaths `subpath` (`"."` or `"./..."`) is exported as when only
+    /// `conditions` are set, like Node.js resolves it. As in Node.js, the
+    /// order of the conditions in the field decides which one is used, not
+    /// the order of `conditions`, and `"default"` always matches.
+    pub fn resolve(&self, subpath: &str, conditions: &[&str]) -> Result<Vec<String>> {
+        let conditions = conditions
+            .iter()
+            .map(|condition| (condition.to_string(), ConditionValue::Set))
+            .collect();
+        lookup_results(&self.0, subpath, &conditions, &ConditionValue::Unset)
+    }
+}
+
 impl Deref for ExportsField {
     type Target = AliasMap<SubpathValue>;
     fn deref(&self) -> &Self::Target {
@@ -311,6 +325,36 @@ impl Deref for ExportsField {
     }
 }
 
+/// Looks up `path` in an "exports" or "imports" field and returns the
+/// subpaths of the most specific matching entry, without duplicates. An entry
+/// whose conditions don't match falls back to the next matching entry.
+pub fn lookup_results(
+    field: &AliasMap<SubpathValue>,
+    path: &str,
+    conditions: &BTreeMap<String, ConditionValue>,
+    unspecified_conditions: &ConditionValue,
+) -> Result<Vec<String>> {
+    let mut results = Vec::new();
+    let mut conditions_state = HashMap::new();
+    let values = field
+        .lookup(path)
+        .map(AliasMatch::try_into_self)
+        .collect::<Result<Vec<_>>>()?;
+    for value in values.iter() {
+        if value.add_results(
+            conditions,
+            unspecified_conditions,
+            &mut conditions_state,
+            &mut results,
+        ) {
+            break;
+        }
+    }
+    let mut duplicates_set = HashSet::new();
+    results.retain(|item| duplicates_set.insert(*item));
+    Ok(results.into_iter().map(str::to_string).collect())
+}
+
 /// Content of an "imports" field in a package.json
 #[derive(PartialEq, Eq, Serialize, Deserialize)]
 pub struct ImportsField(AliasMap<SubpathValue>);
@@ -424,3 +468,99 @@ impl<'a> IntoIterator for &'a ResolveAliasMap {
         (&self.0).into_iter()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use anyhow::Result;
+    use serde_json::json;
+
+    use super::ExportsField;
+
+    fn resolve(exports: serde_json::Value, subpath: &str, conditions: &[&str]) -> Vec<String> {
+        ExportsField::try_from(&exports)
+            .unwrap()
+            .resolve(subpath, conditions)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_string_shorthand() {
+        assert_eq!(resolve(json!("./index.js"), ".", &[]), ["./index.js"]);
+        assert_eq!(
+            resolve(json!({ ".": "./index.js" }), ".", &[]),
+            ["./index.js"]
+        );
+        assert!(resolve(json!("./index.js"), "./other", &[]).is_empty());
+    }
+
+    #[test]
+    fn test_nested_conditions() {
+        let exports = json!({
+            ".": {
+                "node": {
+                    "import": "./node.mjs",
+                    "require": "./node.cjs"
+                },
+                "browser": "./browser.js",
+                "default": "./index.js"
+            }
+        });
+        assert_eq!(
+            resolve(exports.clone(), ".", &["node", "import"]),
+            ["./node.mjs"]
+        );
+        assert_eq!(
+            resolve(exports.clone(), ".", &["require", "node"]),
+            ["./node.cjs"]
+        );
+        assert_eq!(
+            resolve(exports.clone(), ".", &["browser", "import"]),
+            ["./browser.js"]
+        );
+        // "node" matches, but none of its conditions, so it falls back to
+        // "default".
+        assert_eq!(
+            resolve(exports.clone(), ".", &["node", "worker"]),
+            ["./index.js"]
+        );
+        assert_eq!(resolve(exports, ".", &[]), ["./index.js"]);
+    }
+
+    #[test]
+    fn test_condition_order() {
+        // The order of the field decides, not the order of the conditions.
+        let exports = json!({ "import": "./index.mjs", "node": "./node.js" });
+        assert_eq!(
+            resolve(exports.clone(), ".", &["node", "import"]),
+            ["./index.mjs"]
+        );
+        assert_eq!(resolve(exports.clone(), ".", &["node"]), ["./node.js"]);
+        assert!(resolve(exports, ".", &["browser"]).is_empty());
+    }
+
+    #[test]
+    fn test_wildcards() -> Result<()> {
+        let exports = ExportsField::try_from(&json!({
+            ".": "./index.js",
+            "./features/*": {
+                "import": "./esm/features/*.mjs",
+                "default": "./cjs/features/*.js"
+            },
+            "./features/internal/*": null
+        }))?;
+        assert_eq!(
+            exports.resolve("./features/a", &["import"])?,
+            ["./esm/features/a.mjs"]
+        );
+        assert_eq!(
+            exports.resolve("./features/a/b", &["require"])?,
+            ["./cjs/features/a/b.js"]
+        );
+        // The more specific pattern excludes the subpath.
+        assert!(exports
+            .resolve("./features/internal/a", &["import"])?
+            .is_empty());
+        assert!(exports.resolve("./other", &["import"])?.is_empty());
+        Ok(())
+    }
+}
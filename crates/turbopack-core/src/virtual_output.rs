@@ -0,0 +1,73 @@
+use anyhow::Result;
+use turbo_tasks::primitives::OptionStringVc;
+use turbo_tasks_fs::FileSystemPathVc;
+
+use crate::{
+    asset::{Asset, AssetContentVc, AssetVc},
+    ident::AssetIdentVc,
+    output::{OutputAsset, OutputAssetVc},
+};
+
+/// An [OutputAsset] that is created from some passed content, e.g. a
+/// generated manifest.
+#[turbo_tasks::value]
+pub struct VirtualOutputAsset {
+    pub path: FileSystemPathVc,
+    pub content: AssetContentVc,
+    /// The content type to serve the asset with, when it can't be derived
+    /// from the extension of `path`.
+    pub content_type: Option<String>,
+}
+
+#[turbo_tasks::value_impl]
+impl VirtualOutputAssetVc {
+    #[turbo_tasks::function]
+    pub fn new(path: FileSystemPathVc, content: AssetContentVc) -> Self {
+        Self::cell(VirtualOutputAsset {
+            path,
+            content,
+            content_type: None,
+        })
+    }
+
+    /// Creates a [VirtualOutputAsset] which is served as `content_type`,
+    /// e.g. `text/javascript` for a script at a path without extension.
+    #[turbo_tasks::function]
+    pub fn new_with_content_type(
+        path: FileSystemPathVc,
+        content: AssetContentVc,
+        content_type: String,
+    ) -> Self {
+        Self::cell(VirtualOutputAsset {
+            path,
+            content,
+            content_type: Some(content_type),
+        })
+    }
+
+    /// The content type to serve the asset with. When it's `None`, it is
+    /// derived from the extension of the path as usual.
+    ///
+    /// This is only metadata for serving the asset, the ident stays derived
+    /// from the path.
+    #[turbo_tasks::function]
+    pub async fn content_type(self) -> Result<OptionStringVc> {
+        Ok(OptionStringVc::cell(self.await?.content_type.clone()))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl OutputAsset for VirtualOutputAsset {}
+
+#[turbo_tasks::value_impl]
+impl Asset for VirtualOutputAsset {
+    #[turbo_tasks::function]
+    fn ident(&self) -> AssetIdentVc {
+        AssetIdentVc::from_path(self.path)
+    }
+
+    #[turbo_tasks::function]
+    fn content(&self) -> AssetContentVc {
+        self.content
+    }
+}
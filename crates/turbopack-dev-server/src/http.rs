@@ -128,24 +128,40 @@ pub async fn process_request_with_content_source(
                     );
 
                     should_compress = should_compress_predicate(content_type);
-                } else if let hyper::header::Entry::Vacant(entry) = header_map.entry("content-type")
-                {
-                    let guess = mime_guess::from_path(&original_path).first_or_octet_stream();
-                    should_compress = should_compress_predicate(&guess);
-                    // If a text type, application/javascript, or application/json was
-                    // guessed, use a utf-8 charset as  we most likely generated it as
-                    // such.
-                    entry.insert(hyper::header::HeaderValue::try_from(
-                        if (guess.type_() == mime::TEXT
-                            || guess.subtype() == mime::JAVASCRIPT
-                            || guess.subtype() == mime::JSON)
-                            && guess.get_param("charset").is_none()
-                        {
-                            guess.to_string() + "; charset=utf-8"
-                        } else {
-                            guess.to_string()
-                        },
-                    )?);
+                } else {
+                    match header_map.entry("content-type") {
+                        hyper::header::Entry::Vacant(entry) => {
+                            let guess =
+                                mime_guess::from_path(&original_path).first_or_octet_stream();
+                            should_compress = should_compress_predicate(&guess);
+                            // If a text type, application/javascript, or application/json was
+                            // guessed, use a utf-8 charset as  we most likely generated it as
+                            // such.
+                            entry.insert(hyper::header::HeaderValue::try_from(
+                                if (guess.type_() == mime::TEXT
+                                    || guess.subtype() == mime::JAVASCRIPT
+                                    || guess.subtype() == mime::JSON)
+                                    && guess.get_param("charset").is_none()
+                                {
+                                    guess.to_string() + "; charset=utf-8"
+                                } else {
+                                    guess.to_string()
+                                },
+                            )?);
+                        }
+                        // The content source set the content type, e.g. for an asset whose
+                        // path doesn't have the right extension.
+                        hyper::header::Entry::Occupied(entry) => {
+                            if let Some(content_type) = entry
+                                .get()
+                                .to_str()
+                                .ok()
+                                .and_then(|content_type| content_type.parse::<Mime>().ok())
+                            {
+                                should_compress = should_compress_predicate(&content_type);
+                            }
+                        }
+                    }
                 }
 
                 if !header_map.contains_key("cache-control") {
@@ -233,3 +249,71 @@ async fn http_request_to_source_request(request: Request<hyper::Body>) -> Result
         body: Body::new(bytes),
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use anyhow::Result;
+    use hyper::{Body, Request};
+    use turbo_tasks::{NothingVc, TransientInstance, TurboTasks};
+    use turbo_tasks_fs::{File, FileSystem, VirtualFileSystemVc};
+    use turbo_tasks_memory::MemoryBackend;
+    use turbopack_cli_utils::issue::{ConsoleUiVc, LogOptions};
+    use turbopack_core::{issue::IssueSeverity, virtual_output::VirtualOutputAssetVc};
+
+    use super::process_request_with_content_source;
+    use crate::source::asset_graph::AssetGraphContentSourceVc;
+
+    #[tokio::test]
+    async fn serves_virtual_output_asset_with_content_type() -> Result<()> {
+        crate::register();
+
+        let tt = TurboTasks::new(MemoryBackend::default());
+        let task = tt.spawn_once_task(async move {
+            let root = VirtualFileSystemVc::new().root();
+            let issue_reporter = ConsoleUiVc::new(TransientInstance::new(LogOptions {
+                current_dir: PathBuf::new(),
+                project_dir: PathBuf::new(),
+                show_all: false,
+                log_detail: false,
+                log_level: IssueSeverity::Error,
+            }))
+            .into();
+
+            // The path has no extension, so the content type can't be guessed.
+            let asset = VirtualOutputAssetVc::new_with_content_type(
+                root.join("script"),
+                File::from("console.log(42);").into(),
+                "text/javascript".to_string(),
+            );
+            let source = AssetGraphContentSourceVc::new_eager(root, asset.into());
+            let request = Request::builder().uri("/script").body(Body::empty())?;
+            let (response, _) =
+                process_request_with_content_source(source.into(), request, issue_reporter).await?;
+            assert_eq!(response.status(), 200);
+            assert_eq!(response.headers()["content-type"], "text/javascript");
+            assert_eq!(response.headers()["content-encoding"], "gzip");
+
+            // Without the override, the content type is guessed from the path.
+            let asset = VirtualOutputAssetVc::new(
+                root.join("script"),
+                File::from("console.log(42);").into(),
+            );
+            let source = AssetGraphContentSourceVc::new_eager(root, asset.into());
+            let request = Request::builder().uri("/script").body(Body::empty())?;
+            let (response, _) =
+                process_request_with_content_source(source.into(), request, issue_reporter).await?;
+            assert_eq!(
+                response.headers()["content-type"],
+                "application/octet-stream"
+            );
+            assert!(!response.headers().contains_key("content-encoding"));
+
+            Ok(NothingVc::new().into())
+        });
+        tt.wait_task_completion(task, true).await?;
+
+        Ok(())
+    }
+}
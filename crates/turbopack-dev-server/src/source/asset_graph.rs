@@ -13,12 +13,13 @@ use turbopack_core::{
         asset::IntrospectableAssetVc, Introspectable, IntrospectableChildrenVc, IntrospectableVc,
     },
     reference::all_referenced_assets,
+    virtual_output::VirtualOutputAssetVc,
 };
 
 use super::{
     route_tree::{BaseSegment, RouteTreeVc, RouteTreesVc, RouteType},
     ContentSource, ContentSourceContentVc, ContentSourceData, ContentSourceSideEffect,
-    ContentSourceVc, GetContentSourceContent,
+    ContentSourceVc, GetContentSourceContent, HeaderListVc,
 };
 use crate::source::{ContentSourceSideEffectVc, GetContentSourceContentVc};
 
@@ -218,6 +219,15 @@ impl GetContentSourceContent for AssetGraphGetContentSourceContent {
     ) -> Result<ContentSourceContentVc> {
         let this = self_vc.await?;
         turbo_tasks::emit(self_vc.as_content_source_side_effect());
+        if let Some(asset) = VirtualOutputAssetVc::resolve_from(this.asset).await? {
+            if let Some(content_type) = &*asset.content_type().await? {
+                return Ok(ContentSourceContentVc::static_with_headers(
+                    this.asset.versioned_content(),
+                    200,
+                    HeaderListVc::cell(vec![("content-type".to_string(), content_type.clone())]),
+                ));
+            }
+        }
         Ok(ContentSourceContentVc::static_content(
             this.asset.versioned_content(),
         ))
@@ -2,23 +2,24 @@ use anyhow::{bail, Result};
 use indexmap::IndexSet;
 use turbo_tasks::{
     graph::{AdjacencyMap, GraphTraversal},
-    primitives::{BoolVc, OptionStringVc, StringVc},
+    primitives::{BoolVc, OptionStringVc, OptionU64Vc, StringVc, UsizeVc},
     TryJoinIterExt, Value,
 };
 use turbo_tasks_fs::FileSystemPathVc;
 use turbopack_core::{
     asset::{Asset, AssetVc},
     chunk::{
-        Chunk, ChunkVc, ChunkableModule, ChunkingContext, ChunkingContextVc, ChunksVc,
-        EvaluatableAssetsVc,
+        hashed_file_name, Chunk, ChunkVc, ChunkableModule, ChunkingContext, ChunkingContextVc,
+        ChunksVc, EvaluatableAssetsVc,
     },
-    environment::EnvironmentVc,
+    environment::{ChunkLoading, ChunkLoadingVc, EnvironmentVc},
     ident::AssetIdentVc,
-    output::{OutputAssetVc, OutputAssetsVc},
+    output::{OptionOutputAssetVc, OutputAssetVc, OutputAssetsVc},
 };
 use turbopack_css::chunk::{CssChunkVc, CssChunksVc};
 use turbopack_ecmascript::chunk::{
-    EcmascriptChunkVc, EcmascriptChunkingContext, EcmascriptChunkingContextVc, EcmascriptChunksVc,
+    validate_module_ids, EcmascriptChunkVc, EcmascriptChunkingContext, EcmascriptChunkingContextVc,
+    EcmascriptChunksVc, ModuleIdStrategy, ModuleIdStrategyVc,
 };
 use turbopack_ecmascript_runtime::RuntimeType;
 
@@ -26,9 +27,12 @@ use crate::{
     css::optimize::optimize_css_chunks,
     ecmascript::{
         chunk::EcmascriptDevChunkVc,
-        evaluate::chunk::EcmascriptDevEvaluateChunkVc,
+        evaluate::{chunk::EcmascriptDevEvaluateChunkVc, runtime::EcmascriptDevRuntimeChunkVc},
         list::asset::{EcmascriptDevChunkListSource, EcmascriptDevChunkListVc},
-        optimize::optimize_ecmascript_chunks,
+        optimize::{
+            hoist_common_chunk_items, merge_small_ecmascript_chunks, optimize_ecmascript_chunks,
+        },
+        registry::ChunkRegistryAssetVc,
     },
 };
 
@@ -67,6 +71,56 @@ impl DevChunkingContextBuilder {
         self
     }
 
+    pub fn detect_duplicate_modules(mut self, detect_duplicate_modules: bool) -> Self {
+        self.context.detect_duplicate_modules = detect_duplicate_modules;
+        self
+    }
+
+    pub fn min_chunk_size(mut self, min_chunk_size: usize) -> Self {
+        self.context.min_chunk_size = min_chunk_size;
+        self
+    }
+
+    pub fn max_chunk_size(mut self, max_chunk_size: usize) -> Self {
+        self.context.max_chunk_size = max_chunk_size;
+        self
+    }
+
+    pub fn chunk_size_budget(mut self, chunk_size_budget: u64) -> Self {
+        self.context.chunk_size_budget = Some(chunk_size_budget);
+        self
+    }
+
+    pub fn common_chunk_min_chunks(mut self, common_chunk_min_chunks: usize) -> Self {
+        self.context.common_chunk_min_chunks = common_chunk_min_chunks;
+        self
+    }
+
+    pub fn chunk_registry(mut self, chunk_registry: bool) -> Self {
+        self.context.chunk_registry = chunk_registry;
+        self
+    }
+
+    pub fn chunk_item_id_strategy(mut self, chunk_item_id_strategy: ModuleIdStrategy) -> Self {
+        self.context.chunk_item_id_strategy = chunk_item_id_strategy;
+        self
+    }
+
+    pub fn separate_runtime_chunk(mut self, separate_runtime_chunk: bool) -> Self {
+        self.context.separate_runtime_chunk = separate_runtime_chunk;
+        self
+    }
+
+    pub fn chunk_loading(mut self, chunk_loading: ChunkLoading) -> Self {
+        self.context.chunk_loading = Some(chunk_loading);
+        self
+    }
+
+    pub fn hashed_chunk_filenames(mut self, hashed_chunk_filenames: bool) -> Self {
+        self.context.hashed_chunk_filenames = hashed_chunk_filenames;
+        self
+    }
+
     pub fn build(self) -> DevChunkingContextVc {
         DevChunkingContextVc::new(Value::new(self.context))
     }
@@ -98,15 +152,52 @@ pub struct DevChunkingContext {
     chunk_base_path: OptionStringVc,
     /// Layer name within this context
     layer: Option<String>,
+    /// The name of the chunks, see [ChunkingContext::with_chunk_name]
+    chunk_name: Option<String>,
     /// Enable HMR for this chunking
     enable_hot_module_replacement: bool,
     /// The environment chunks will be evaluated in.
     environment: EnvironmentVc,
+    /// Overrides how async chunks are loaded, which is derived from the
+    /// environment by default
+    chunk_loading: Option<ChunkLoading>,
     /// The kind of runtime to include in the output.
     runtime_type: RuntimeType,
+    /// Report distinct modules with the same ident within a chunk
+    detect_duplicate_modules: bool,
+    /// Chunks with fewer chunk items are merged into neighboring chunks
+    min_chunk_size: usize,
+    /// Chunks are not merged beyond this number of chunk items
+    max_chunk_size: usize,
+    /// Ecmascript chunks with more code are split into parallel chunks
+    chunk_size_budget: Option<u64>,
+    /// Chunk items which are in at least this many ecmascript chunks of a chunk
+    /// group are hoisted into a common chunk. Values below 2 disable hoisting
+    common_chunk_min_chunks: usize,
+    /// Emit a registry of the chunks containing each module with every chunk
+    /// group
+    chunk_registry: bool,
+    /// How module ids are derived from the module idents
+    chunk_item_id_strategy: ModuleIdStrategy,
+    /// Emit the runtime as its own chunk in evaluated chunk groups, see
+    /// [ChunkingContext::runtime_chunk], instead of bundling it into the
+    /// evaluate chunk
+    separate_runtime_chunk: bool,
+    /// Include the content hash of ecmascript chunks in their filenames, see
+    /// [OutputAsset::content_hash](turbopack_core::output::OutputAsset::content_hash)
+    hashed_chunk_filenames: bool,
 }
 
 impl DevChunkingContextVc {
+    /// Checks that the module ids of a chunk group are unique when they are
+    /// hashed, as short hashes can collide.
+    async fn validate_module_ids(self, chunks: ChunksVc) -> Result<()> {
+        if let ModuleIdStrategy::Hashed { .. } = self.await?.chunk_item_id_strategy {
+            validate_module_ids(chunks).await?;
+        }
+        Ok(())
+    }
+
     pub fn builder(
         context_path: FileSystemPathVc,
         output_root: FileSystemPathVc,
@@ -124,9 +215,20 @@ impl DevChunkingContextVc {
                 asset_root_path,
                 chunk_base_path: Default::default(),
                 layer: None,
+                chunk_name: None,
                 enable_hot_module_replacement: false,
                 environment,
+                chunk_loading: None,
                 runtime_type: Default::default(),
+                detect_duplicate_modules: false,
+                min_chunk_size: 0,
+                max_chunk_size: usize::MAX,
+                chunk_size_budget: None,
+                common_chunk_min_chunks: 2,
+                chunk_registry: false,
+                chunk_item_id_strategy: Default::default(),
+                separate_runtime_chunk: false,
+                hashed_chunk_filenames: false,
             },
         }
     }
@@ -141,9 +243,16 @@ impl DevChunkingContext {
         self.runtime_type
     }
 
-    /// Returns the asset base path.
-    pub fn chunk_base_path(&self) -> OptionStringVc {
-        self.chunk_base_path
+    /// Returns whether the runtime is emitted as its own chunk instead of
+    /// being part of the evaluate chunk.
+    pub fn separate_runtime_chunk(&self) -> bool {
+        self.separate_runtime_chunk
+    }
+
+    /// Returns whether the content hash of ecmascript chunks is included in
+    /// their filenames.
+    pub fn hashed_chunk_filenames(&self) -> bool {
+        self.hashed_chunk_filenames
     }
 }
 
@@ -175,17 +284,44 @@ impl DevChunkingContextVc {
         EcmascriptDevChunkListVc::new(self_vc, entry_chunk, other_chunks, source).into()
     }
 
+    /// Generates the chunk registry of a chunk group when enabled. It isn't
+    /// part of the chunk group, but referenced by its chunk list.
     #[turbo_tasks::function]
-    async fn generate_chunk(self, chunk: ChunkVc) -> Result<OutputAssetVc> {
-        Ok(
+    pub(crate) async fn generate_chunk_registry(
+        self,
+        entry_chunk: ChunkVc,
+        chunks: OutputAssetsVc,
+    ) -> Result<OutputAssetsVc> {
+        Ok(OutputAssetsVc::cell(if self.await?.chunk_registry {
+            vec![ChunkRegistryAssetVc::new(self, entry_chunk, chunks).into()]
+        } else {
+            Vec::new()
+        }))
+    }
+
+    /// Generates the output assets for a chunk. Ecmascript chunks which exceed
+    /// the chunk size budget result in multiple assets, which are loaded in
+    /// parallel.
+    #[turbo_tasks::function]
+    async fn generate_chunks(self, chunk: ChunkVc) -> Result<OutputAssetsVc> {
+        Ok(OutputAssetsVc::cell(
             if let Some(ecmascript_chunk) = EcmascriptChunkVc::resolve_from(chunk).await? {
-                EcmascriptDevChunkVc::new(self, ecmascript_chunk).into()
+                let parts = ecmascript_chunk.content_parts().await?;
+                if parts.len() == 1 {
+                    vec![EcmascriptDevChunkVc::new(self, ecmascript_chunk).into()]
+                } else {
+                    (0..parts.len())
+                        .map(|index| {
+                            EcmascriptDevChunkVc::new_part(self, ecmascript_chunk, index).into()
+                        })
+                        .collect()
+                }
             } else if let Some(output_asset) = OutputAssetVc::resolve_from(chunk).await? {
-                output_asset
+                vec![output_asset]
             } else {
                 bail!("Unable to generate output asset for chunk");
             },
-        )
+        ))
     }
 }
 
@@ -206,6 +342,42 @@ impl ChunkingContext for DevChunkingContext {
         self.environment
     }
 
+    #[turbo_tasks::function]
+    async fn with_environment(
+        self_vc: DevChunkingContextVc,
+        environment: EnvironmentVc,
+    ) -> Result<ChunkingContextVc> {
+        let mut context = self_vc.await?.clone_value();
+        context.environment = environment;
+        // The chunk loading of the new environment is used.
+        context.chunk_loading = None;
+        Ok(DevChunkingContextVc::new(Value::new(context)).into())
+    }
+
+    #[turbo_tasks::function]
+    fn chunk_base_path(&self) -> OptionStringVc {
+        self.chunk_base_path
+    }
+
+    #[turbo_tasks::function]
+    async fn chunk_loading(&self) -> Result<ChunkLoadingVc> {
+        let Some(chunk_loading) = self.chunk_loading else {
+            return Ok(self.environment.chunk_loading());
+        };
+        let chunk_loading_vc = chunk_loading.cell();
+        if !*self
+            .environment
+            .supports_chunk_loading(chunk_loading_vc)
+            .await?
+        {
+            bail!(
+                "Chunk loading {:?} is not supported in the environment of the chunking context",
+                chunk_loading
+            );
+        }
+        Ok(chunk_loading_vc)
+    }
+
     #[turbo_tasks::function]
     async fn chunk_path(&self, ident: AssetIdentVc, extension: &str) -> Result<FileSystemPathVc> {
         let root_path = self.chunk_root_path;
@@ -214,7 +386,14 @@ impl ChunkingContext for DevChunkingContext {
         } else {
             root_path
         };
-        let name = ident.output_name(self.context_path, extension).await?;
+        let name = match self.chunk_name.as_deref() {
+            Some(chunk_name) => {
+                ident
+                    .named_output_name(self.context_path, chunk_name, extension)
+                    .await?
+            }
+            None => ident.output_name(self.context_path, extension).await?,
+        };
         Ok(root_path.join(&name))
     }
 
@@ -254,19 +433,9 @@ impl ChunkingContext for DevChunkingContext {
         original_asset_ident: AssetIdentVc,
     ) -> Result<FileSystemPathVc> {
         let source_path = original_asset_ident.path().await?;
-        let basename = source_path.file_name();
-        let asset_path = match source_path.extension() {
-            Some(ext) => format!(
-                "{basename}.{content_hash}.{ext}",
-                basename = &basename[..basename.len() - ext.len() - 1],
-                content_hash = &content_hash[..8]
-            ),
-            None => format!(
-                "{basename}.{content_hash}",
-                content_hash = &content_hash[..8]
-            ),
-        };
-        Ok(self.asset_root_path.join(&asset_path))
+        Ok(self
+            .asset_root_path
+            .join(&hashed_file_name(&source_path, content_hash)))
     }
 
     #[turbo_tasks::function]
@@ -286,6 +455,46 @@ impl ChunkingContext for DevChunkingContext {
         Ok(DevChunkingContextVc::new(Value::new(context)).into())
     }
 
+    #[turbo_tasks::function]
+    fn chunk_name(&self) -> OptionStringVc {
+        OptionStringVc::cell(self.chunk_name.clone())
+    }
+
+    #[turbo_tasks::function]
+    async fn with_chunk_name(
+        self_vc: DevChunkingContextVc,
+        name: &str,
+    ) -> Result<ChunkingContextVc> {
+        let this = self_vc.await?;
+        let chunk_name = (!name.is_empty()).then(|| name.to_string());
+        if this.chunk_name == chunk_name {
+            return Ok(self_vc.into());
+        }
+        let mut context = this.clone_value();
+        context.chunk_name = chunk_name;
+        Ok(DevChunkingContextVc::new(Value::new(context)).into())
+    }
+
+    #[turbo_tasks::function]
+    fn min_chunk_size(&self) -> UsizeVc {
+        UsizeVc::cell(self.min_chunk_size)
+    }
+
+    #[turbo_tasks::function]
+    fn max_chunk_size(&self) -> UsizeVc {
+        UsizeVc::cell(self.max_chunk_size)
+    }
+
+    #[turbo_tasks::function]
+    fn chunk_size_budget(&self) -> OptionU64Vc {
+        OptionU64Vc::cell(self.chunk_size_budget)
+    }
+
+    #[turbo_tasks::function]
+    fn chunk_output_assets(self_vc: DevChunkingContextVc, chunk: ChunkVc) -> OutputAssetsVc {
+        self_vc.generate_chunks(chunk)
+    }
+
     #[turbo_tasks::function]
     async fn chunk_group(
         self_vc: DevChunkingContextVc,
@@ -293,17 +502,24 @@ impl ChunkingContext for DevChunkingContext {
     ) -> Result<OutputAssetsVc> {
         let parallel_chunks = get_parallel_chunks([entry_chunk]).await?;
 
-        let optimized_chunks = get_optimized_chunks(parallel_chunks).await?;
+        let optimized_chunks = get_optimized_chunks(self_vc, parallel_chunks).await?;
+        self_vc.validate_module_ids(optimized_chunks).await?;
 
         let mut assets: Vec<OutputAssetVc> = optimized_chunks
             .await?
             .iter()
-            .map(|chunk| self_vc.generate_chunk(*chunk))
+            .map(|chunk| self_vc.generate_chunks(*chunk))
+            .try_join()
+            .await?
+            .iter()
+            .flat_map(|assets| assets.iter().copied())
             .collect();
 
+        let other_assets = OutputAssetsVc::cell(assets.clone());
+
         assets.push(self_vc.generate_chunk_list_register_chunk(
             entry_chunk,
-            OutputAssetsVc::cell(assets.clone()),
+            other_assets,
             Value::new(EcmascriptDevChunkListSource::Dynamic),
         ));
 
@@ -337,16 +553,27 @@ impl ChunkingContext for DevChunkingContext {
 
         let parallel_chunks = get_parallel_chunks(entry_assets).await?;
 
-        let optimized_chunks = get_optimized_chunks(parallel_chunks).await?;
+        let optimized_chunks = get_optimized_chunks(self_vc, parallel_chunks).await?;
+        self_vc.validate_module_ids(optimized_chunks).await?;
 
         let mut assets: Vec<OutputAssetVc> = optimized_chunks
             .await?
             .iter()
-            .map(|chunk| self_vc.generate_chunk(*chunk))
+            .map(|chunk| self_vc.generate_chunks(*chunk))
+            .try_join()
+            .await?
+            .iter()
+            .flat_map(|assets| assets.iter().copied())
             .collect();
 
         let other_assets = OutputAssetsVc::cell(assets.clone());
 
+        // The runtime has to be loaded by the host, it is not loaded by the
+        // evaluate chunk, so it's not part of the other assets.
+        if let Some(runtime_chunk) = *self_vc.runtime_chunk(evaluatable_assets).await? {
+            assets.insert(0, runtime_chunk);
+        }
+
         assets.push(self_vc.generate_chunk_list_register_chunk(
             entry_chunk,
             other_assets,
@@ -357,6 +584,20 @@ impl ChunkingContext for DevChunkingContext {
 
         Ok(OutputAssetsVc::cell(assets))
     }
+
+    #[turbo_tasks::function]
+    async fn runtime_chunk(
+        self_vc: DevChunkingContextVc,
+        _entries: EvaluatableAssetsVc,
+    ) -> Result<OptionOutputAssetVc> {
+        Ok(OptionOutputAssetVc::cell(
+            if self_vc.await?.separate_runtime_chunk {
+                Some(EcmascriptDevRuntimeChunkVc::new(self_vc).into())
+            } else {
+                None
+            },
+        ))
+    }
 }
 
 #[turbo_tasks::value_impl]
@@ -365,6 +606,16 @@ impl EcmascriptChunkingContext for DevChunkingContext {
     fn has_react_refresh(&self) -> BoolVc {
         BoolVc::cell(true)
     }
+
+    #[turbo_tasks::function]
+    fn detect_duplicate_modules(&self) -> BoolVc {
+        BoolVc::cell(self.detect_duplicate_modules)
+    }
+
+    #[turbo_tasks::function]
+    fn chunk_item_id_strategy(&self) -> ModuleIdStrategyVc {
+        self.chunk_item_id_strategy.cell()
+    }
 }
 
 async fn get_parallel_chunks<I>(entries: I) -> Result<impl Iterator<Item = ChunkVc>>
@@ -388,10 +639,15 @@ where
         .into_reverse_topological())
 }
 
-async fn get_optimized_chunks<I>(chunks: I) -> Result<ChunksVc>
+async fn get_optimized_chunks<I>(
+    chunking_context: DevChunkingContextVc,
+    chunks: I,
+) -> Result<ChunksVc>
 where
     I: IntoIterator<Item = ChunkVc>,
 {
+    let common_chunk_min_chunks = chunking_context.await?.common_chunk_min_chunks;
+    let chunking_context: ChunkingContextVc = chunking_context.into();
     let mut ecmascript_chunks = vec![];
     let mut css_chunks = vec![];
     let mut other_chunks = vec![];
@@ -406,8 +662,14 @@ where
         }
     }
 
-    let ecmascript_chunks =
-        optimize_ecmascript_chunks(EcmascriptChunksVc::cell(ecmascript_chunks)).await?;
+    let ecmascript_chunks = hoist_common_chunk_items(
+        merge_small_ecmascript_chunks(
+            optimize_ecmascript_chunks(EcmascriptChunksVc::cell(ecmascript_chunks)),
+            chunking_context,
+        ),
+        common_chunk_min_chunks,
+    )
+    .await?;
     let css_chunks = optimize_css_chunks(CssChunksVc::cell(css_chunks)).await?;
 
     let chunks = ecmascript_chunks
@@ -420,3 +682,625 @@ where
 
     Ok(ChunksVc::cell(chunks))
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeMap, HashSet};
+
+    use anyhow::{bail, Context, Result};
+    use turbo_tasks::{Value, ValueToString};
+    use turbo_tasks_fs::FileSystemPathVc;
+    use turbopack_core::{
+        asset::{Asset, AssetsVc},
+        chunk::{
+            ChunkItem, ChunkVc, ChunkableModule, ChunkableModuleVc, ChunkingContext,
+            ChunkingContextVc, EvaluatableAssetsVc, ModuleId,
+        },
+        context::AssetContext,
+        environment::{ChunkLoading, EnvironmentVc},
+        file_source::FileSourceVc,
+        output::OutputAssetVc,
+        reference_type::{EntryReferenceSubType, ReferenceType},
+    };
+    use turbopack_ecmascript::{
+        chunk::{module_idents, EcmascriptChunkVc, ModuleIdAssignmentVc, ModuleIdStrategy},
+        EcmascriptModuleAssetVc,
+    };
+
+    use crate::{
+        tests::{
+            browser_env, default_asset_context, dev_chunking_context, node_build_time_env,
+            node_lambda_env, project_root, register, run_once, write_files,
+        },
+        DevChunkingContextVc,
+    };
+
+    /// Chunks `index.js` with the given chunk size budget. Returns the module
+    /// ids and the number of external references of each part of the entry
+    /// chunk, and the number of output assets of the chunk group.
+    async fn split_chunk(
+        root: FileSystemPathVc,
+        budget: Option<u64>,
+    ) -> Result<(Vec<Vec<ModuleId>>, Vec<usize>, usize)> {
+        let env = node_build_time_env();
+        let context = default_asset_context(env);
+        let mut builder = dev_chunking_context(root, env);
+        if let Some(budget) = budget {
+            builder = builder.chunk_size_budget(budget);
+        }
+        let chunking_context: ChunkingContextVc = builder.build().into();
+
+        let module = context.process(
+            FileSourceVc::new(root.join("index.js")).into(),
+            Value::new(ReferenceType::Entry(EntryReferenceSubType::Undefined)),
+        );
+        let module = ChunkableModuleVc::resolve_from(module)
+            .await?
+            .context("expected a chunkable module")?;
+        let chunk = module.as_root_chunk(chunking_context);
+        let ecmascript_chunk = EcmascriptChunkVc::resolve_from(chunk)
+            .await?
+            .context("expected an ecmascript chunk")?;
+
+        let mut parts = Vec::new();
+        let mut references = Vec::new();
+        for part in ecmascript_chunk.content_parts().await?.iter() {
+            let part = part.await?;
+            let mut ids = Vec::new();
+            for item in part.chunk_items.iter() {
+                ids.push(item.id().await?.clone_value());
+            }
+            parts.push(ids);
+            references.push(part.external_asset_references.len());
+        }
+        let outputs = chunking_context.chunk_group(chunk).await?.len();
+
+        Ok((parts, references, outputs))
+    }
+
+    /// Asserts that every module of the unsplit chunk is in exactly one part.
+    fn assert_exactly_once(parts: &[Vec<ModuleId>], all: &[ModuleId]) {
+        let mut ids = parts.iter().flatten().cloned().collect::<Vec<_>>();
+        let mut all = all.to_vec();
+        ids.sort();
+        all.sort();
+        assert_eq!(ids, all);
+    }
+
+    #[tokio::test]
+    async fn chunk_splitting() -> Result<()> {
+        register();
+
+        // A module which is larger than the chunk size budget of the test.
+        let big = format!(
+            "export default [\n{}];\n",
+            (0..100)
+                .map(|i| format!("  \"item {i}\",\n"))
+                .collect::<String>()
+        );
+        let dir = write_files(&[
+            (
+                "index.js",
+                r#"import big from "./lib/big.js";
+import { helper } from "./lib/helper.js";
+import { other } from "./util/other.js";
+import { small } from "./util/small.js";
+
+console.log(big, helper(), other, small);
+"#,
+            ),
+            ("lib/big.js", big.as_str()),
+            ("lib/helper.js", "export const helper = () => \"helper\";\n"),
+            ("util/other.js", "export const other = \"other\";\n"),
+            (
+                "util/small.js",
+                "export const small = require(\"./data.txt\");\n",
+            ),
+            ("util/data.txt", "small\n"),
+        ]);
+        let root = dir.path().to_string_lossy().to_string();
+
+        run_once(async move {
+            let root = project_root(root);
+
+            // Without a budget the chunk is not split. The chunk group contains
+            // the chunk and the chunk list registration. `data.txt` isn't
+            // chunkable and is an external reference.
+            let (parts, references, outputs) = split_chunk(root, None).await?;
+            assert_eq!(parts.len(), 1);
+            assert_eq!(references, [1]);
+            assert_eq!(outputs, 2);
+            let all = parts[0].clone();
+            assert_eq!(all.len(), 5);
+
+            // A budget which fits all modules doesn't split either.
+            let (parts, references, outputs) = split_chunk(root, Some(1_000_000)).await?;
+            assert_eq!(parts.len(), 1);
+            assert_eq!(references, [1]);
+            assert_eq!(outputs, 2);
+            assert_exactly_once(&parts, &all);
+
+            // The chunking context permits modules of a directory to share a
+            // part. `lib/big.js` is larger than the budget and gets a part on
+            // its own, which leaves `lib/helper.js` alone, while both `util`
+            // modules fit into one part. The entry is in another directory
+            // than all of them.
+            let (parts, references, outputs) = split_chunk(root, Some(1000)).await?;
+            assert_eq!(parts.len(), 4);
+            assert_eq!(outputs, 5);
+            assert_eq!(parts.iter().map(Vec::len).collect::<Vec<_>>(), [1, 1, 1, 2]);
+            assert_exactly_once(&parts, &all);
+            // Only the part with `util/small.js` references `data.txt`.
+            assert_eq!(references, [0, 0, 0, 1]);
+
+            Ok(())
+        })
+        .await
+    }
+
+    /// Returns the module of `entry` in `context_path`.
+    async fn entry_module(
+        context_path: FileSystemPathVc,
+        entry: &str,
+    ) -> Result<ChunkableModuleVc> {
+        let context = default_asset_context(node_build_time_env());
+        let module = context.process(
+            FileSourceVc::new(context_path.join(entry)).into(),
+            Value::new(ReferenceType::Entry(EntryReferenceSubType::Undefined)),
+        );
+        ChunkableModuleVc::resolve_from(module)
+            .await?
+            .context("expected a chunkable module")
+    }
+
+    /// Returns the root chunk of `entry` in a chunking context with the context
+    /// path `context_path` and the given module id strategy.
+    async fn root_chunk(
+        context_path: FileSystemPathVc,
+        entry: &str,
+        strategy: ModuleIdStrategy,
+    ) -> Result<(ChunkingContextVc, ChunkVc)> {
+        let chunking_context: ChunkingContextVc = DevChunkingContextVc::builder(
+            context_path,
+            context_path.join("output"),
+            context_path.join("output"),
+            context_path.join("static"),
+            node_build_time_env(),
+        )
+        .chunk_item_id_strategy(strategy)
+        .build()
+        .into();
+
+        let module = entry_module(context_path, entry).await?;
+        Ok((chunking_context, module.as_root_chunk(chunking_context)))
+    }
+
+    /// Returns the module ids of the chunk items of the root chunk of `entry`.
+    async fn module_ids(
+        context_path: FileSystemPathVc,
+        entry: &str,
+        strategy: ModuleIdStrategy,
+    ) -> Result<Vec<String>> {
+        let (_, chunk) = root_chunk(context_path, entry, strategy).await?;
+        let chunk = EcmascriptChunkVc::resolve_from(chunk)
+            .await?
+            .context("expected an ecmascript chunk")?;
+        let mut ids = BTreeMap::new();
+        for chunk_item in chunk.chunk_content().await?.chunk_items.iter() {
+            ids.insert(
+                chunk_item.asset_ident().to_string().await?.clone_value(),
+                chunk_item.id().await?.to_string(),
+            );
+        }
+        Ok(ids.into_values().collect())
+    }
+
+    #[tokio::test]
+    async fn module_id_strategy() -> Result<()> {
+        register();
+
+        let index = "import { lib } from \"./lib.js\";\n\nconsole.log(lib);\n";
+        let lib = "export const lib = \"lib\";\n";
+        // 17 modules, which are bound to collide with a single hex digit.
+        let mut collisions = vec![(
+            "collisions/index.js".to_string(),
+            (0..16)
+                .map(|i| format!("import \"./m{i}.js\";\n"))
+                .collect::<String>(),
+        )];
+        for i in 0..16 {
+            collisions.push((
+                format!("collisions/m{i}.js"),
+                format!("console.log(\"m{i}\");\n"),
+            ));
+        }
+        let mut files = vec![
+            ("a/pkg/src/index.js", index),
+            ("a/pkg/src/lib.js", lib),
+            ("b/pkg/src/index.js", index),
+            ("b/pkg/src/lib.js", lib),
+        ];
+        files.extend(
+            collisions
+                .iter()
+                .map(|(path, content)| (path.as_str(), content.as_str())),
+        );
+        let dir = write_files(&files);
+        let root = dir.path().to_string_lossy().to_string();
+
+        run_once(async move {
+            let root = project_root(root);
+
+            // Named ids are relative to the context path, so they don't change
+            // when the package is moved.
+            let a = module_ids(root.join("a/pkg"), "src/index.js", ModuleIdStrategy::Named).await?;
+            let b = module_ids(root.join("b/pkg"), "src/index.js", ModuleIdStrategy::Named).await?;
+            assert_eq!(
+                a,
+                vec!["src/index.js (ecmascript)", "src/lib.js (ecmascript)"]
+            );
+            assert_eq!(a, b);
+
+            // Full idents include the location of the package.
+            let a = module_ids(root.join("a/pkg"), "src/index.js", ModuleIdStrategy::Ident).await?;
+            assert_eq!(a[0], "[project]/a/pkg/src/index.js (ecmascript)");
+
+            let hashed = module_ids(
+                root.join("a/pkg"),
+                "src/index.js",
+                ModuleIdStrategy::Hashed { length: 6 },
+            )
+            .await?;
+            assert!(hashed.iter().all(|id| id.len() == 6), "{hashed:?}");
+
+            // With a single hex digit, 17 modules are bound to collide.
+            let collisions = root.join("collisions");
+            let (chunking_context, chunk) = root_chunk(
+                collisions,
+                "index.js",
+                ModuleIdStrategy::Hashed { length: 1 },
+            )
+            .await?;
+            let Err(error) = chunking_context.chunk_group(chunk).await else {
+                bail!("expected the colliding module ids to fail the chunk group");
+            };
+            let message = format!("{error:?}");
+            let (_, colliding) = message
+                .split_once("is used by both ")
+                .with_context(|| format!("expected a module id collision: {message}"))?;
+            let (first, second) = colliding
+                .split_once(" and ")
+                .context("expected two colliding modules")?;
+            assert!(first.ends_with(".js (ecmascript)"), "{message}");
+            assert!(second.starts_with("[project]/"), "{message}");
+            assert_ne!(first, second.lines().next().unwrap_or_default());
+
+            // Unique hashed ids are lengthened until they don't collide, so the
+            // chunk group can be generated.
+            let module = entry_module(collisions, "index.js").await?;
+            let assignment =
+                ModuleIdAssignmentVc::new(module_idents(AssetsVc::cell(vec![module.into()])), 1);
+            let strategy = ModuleIdStrategy::UniqueHashed { assignment };
+            let (chunking_context, chunk) = root_chunk(collisions, "index.js", strategy).await?;
+            chunking_context.chunk_group(chunk).await?;
+
+            let ids = module_ids(collisions, "index.js", strategy).await?;
+            let full = module_ids(
+                collisions,
+                "index.js",
+                ModuleIdStrategy::Hashed { length: 16 },
+            )
+            .await?;
+            assert_eq!(ids.len(), 17);
+            assert_eq!(ids.iter().collect::<HashSet<_>>().len(), ids.len());
+            assert!(ids.iter().any(|id| id.len() > 1), "{ids:?}");
+            for (id, full) in ids.iter().zip(full.iter()) {
+                assert!(full.starts_with(id.as_str()), "{id} {full}");
+            }
+
+            Ok(())
+        })
+        .await
+    }
+
+    /// Chunks `app/index.js` with the given minimum number of chunks for
+    /// common chunk items. Returns the code of the javascript output assets of
+    /// the chunk group.
+    async fn chunk_group_code(root: FileSystemPathVc, min_chunks: usize) -> Result<Vec<String>> {
+        let env = node_build_time_env();
+        let context = default_asset_context(env);
+        let chunking_context: ChunkingContextVc = dev_chunking_context(root, env)
+            .common_chunk_min_chunks(min_chunks)
+            .build()
+            .into();
+
+        let module = context.process(
+            FileSourceVc::new(root.join("app/index.js")).into(),
+            Value::new(ReferenceType::Entry(EntryReferenceSubType::Undefined)),
+        );
+        let module = ChunkableModuleVc::resolve_from(module)
+            .await?
+            .context("expected a chunkable module")?;
+        let chunk = module.as_root_chunk(chunking_context);
+
+        let mut code = Vec::new();
+        for asset in chunking_context.chunk_group(chunk).await?.iter() {
+            if !asset.ident().path().await?.path.ends_with(".js") {
+                continue;
+            }
+            let content = asset.content().file_content().await?;
+            let content = content.as_content().context("expected a file")?;
+            code.push(content.content().to_str()?.into_owned());
+        }
+        Ok(code)
+    }
+
+    /// Returns how often the module exporting `"<name> module"` is in the code.
+    fn module_count(code: &[String], name: &str) -> usize {
+        let needle = format!("\"{name} module\"");
+        code.iter().map(|code| code.matches(&needle).count()).sum()
+    }
+
+    #[tokio::test]
+    async fn common_chunk() -> Result<()> {
+        register();
+
+        // `vendor.js` is large and imported by both `a.js` and `b.js`.
+        let vendor = format!(
+            "const words = [\n{}];\n\nexport const spell = (text) => text.split(\"\").map((char) \
+             => words[char.charCodeAt(0) - 97] ?? char);\n\nexport default \"vendor module\";\n",
+            (0..100)
+                .map(|i| format!("  \"word {i}\",\n"))
+                .collect::<String>()
+        );
+        let mut files = vec![
+            (
+                "app/index.js".to_string(),
+                "import a from \"../lib/a.js\";\nimport b from \"../lib/b.js\";\n\nconsole.log(a, \
+                 b);\n"
+                    .to_string(),
+            ),
+            ("lib/vendor.js".to_string(), vendor),
+        ];
+        let mut names = vec!["a".to_string(), "b".to_string(), "vendor".to_string()];
+        for name in ["a", "b"] {
+            let imports = (1..=9)
+                .map(|i| format!("import {name}{i} from \"./{name}{i}.js\";\n"))
+                .collect::<String>();
+            let exports = (1..=9).map(|i| format!(", {name}{i}")).collect::<String>();
+            files.push((
+                format!("lib/{name}.js"),
+                format!(
+                    "{imports}import vendor from \"./vendor.js\";\n\nexport default [\"{name} \
+                     module\", vendor{exports}];\n"
+                ),
+            ));
+            for i in 1..=9 {
+                files.push((
+                    format!("lib/{name}{i}.js"),
+                    format!("export default \"{name}{i} module\";\n"),
+                ));
+                names.push(format!("{name}{i}"));
+            }
+        }
+        let dir = write_files(
+            &files
+                .iter()
+                .map(|(path, content)| (path.as_str(), content.as_str()))
+                .collect::<Vec<_>>(),
+        );
+        let root = dir.path().to_string_lossy().to_string();
+
+        run_once(async move {
+            let root = project_root(root);
+
+            // `a.js` and `b.js` are outside of the directory of the entry and
+            // get a chunk each. Both chunks contain `vendor.js`.
+            let duplicated = chunk_group_code(root, 0).await?;
+            assert_eq!(module_count(&duplicated, "vendor"), 2);
+
+            // With hoisting, `vendor.js` is emitted once in a common chunk.
+            let hoisted = chunk_group_code(root, 2).await?;
+            for name in names {
+                assert_eq!(module_count(&hoisted, &name), 1, "{name}");
+            }
+            assert_eq!(hoisted.len(), duplicated.len() + 1);
+            let size = |code: &[String]| code.iter().map(String::len).sum::<usize>();
+            assert!(size(&hoisted) < size(&duplicated));
+
+            // No chunk item is in three chunks.
+            let code = chunk_group_code(root, 3).await?;
+            assert_eq!(code, duplicated);
+
+            Ok(())
+        })
+        .await
+    }
+
+    /// Whether the code of `asset` contains the dev runtime.
+    async fn contains_runtime(asset: OutputAssetVc) -> Result<bool> {
+        let content = asset.content().file_content().await?;
+        let Some(file) = content.as_content() else {
+            return Ok(false);
+        };
+        Ok(file
+            .content()
+            .to_str()?
+            .contains("function instantiateModule("))
+    }
+
+    /// Returns the evaluated chunk group of `index.js` and the runtime chunk
+    /// of the chunking context, if any.
+    async fn evaluated_chunk_group(
+        root: FileSystemPathVc,
+        separate_runtime_chunk: bool,
+    ) -> Result<(Vec<OutputAssetVc>, Option<OutputAssetVc>)> {
+        let env = browser_env(true);
+        let context = default_asset_context(env);
+        let chunking_context: ChunkingContextVc = dev_chunking_context(root, env)
+            .separate_runtime_chunk(separate_runtime_chunk)
+            .build()
+            .into();
+
+        let module = context.process(
+            FileSourceVc::new(root.join("index.js")).into(),
+            Value::new(ReferenceType::Entry(EntryReferenceSubType::Undefined)),
+        );
+        let module = EcmascriptModuleAssetVc::resolve_from(module)
+            .await?
+            .context("expected an ecmascript module")?;
+        let entries = EvaluatableAssetsVc::empty().with_entry(module.into());
+        let assets = chunking_context
+            .evaluated_chunk_group(module.as_root_chunk(chunking_context), entries)
+            .await?;
+        let runtime_chunk = match *chunking_context.runtime_chunk(entries).await? {
+            Some(runtime_chunk) => Some(runtime_chunk.resolve().await?),
+            None => None,
+        };
+        Ok((assets.clone_value(), runtime_chunk))
+    }
+
+    #[tokio::test]
+    async fn runtime_chunk() -> Result<()> {
+        register();
+
+        let dir = write_files(&[("index.js", "console.log(\"runtime chunk entry\");\n")]);
+        let root = dir.path().to_string_lossy().to_string();
+
+        run_once(async move {
+            let root = project_root(root);
+
+            // By default, the runtime is bundled into the evaluate chunk.
+            let (assets, runtime_chunk) = evaluated_chunk_group(root, false).await?;
+            assert!(runtime_chunk.is_none());
+            let mut runtime_assets = 0;
+            for &asset in &assets {
+                if contains_runtime(asset).await? {
+                    runtime_assets += 1;
+                }
+            }
+            assert_eq!(runtime_assets, 1);
+
+            // Otherwise the group starts with the runtime chunk, which is the
+            // only asset containing the runtime.
+            let (assets, runtime_chunk) = evaluated_chunk_group(root, true).await?;
+            let runtime_chunk = runtime_chunk.context("expected a runtime chunk")?;
+            assert_eq!(assets[0].resolve().await?, runtime_chunk);
+            assert!(contains_runtime(runtime_chunk).await?);
+            assert!(runtime_chunk.ident().path().await?.path.ends_with(".js"));
+            for &asset in &assets[1..] {
+                assert!(!contains_runtime(asset).await?);
+            }
+
+            Ok(())
+        })
+        .await
+    }
+
+    /// Returns a chunking context for `env` which loads chunks with
+    /// `chunk_loading`, or the strategy of `env` when it's `None`.
+    fn loading_chunking_context(
+        root: FileSystemPathVc,
+        env: EnvironmentVc,
+        chunk_loading: Option<ChunkLoading>,
+    ) -> ChunkingContextVc {
+        let mut builder = dev_chunking_context(root, env);
+        if let Some(chunk_loading) = chunk_loading {
+            builder = builder.chunk_loading(chunk_loading);
+        }
+        builder.build().into()
+    }
+
+    /// Returns the part of the dev runtime code generated for
+    /// `chunking_context` which depends on its chunk loading strategy.
+    async fn runtime_backend_code(chunking_context: ChunkingContextVc) -> Result<String> {
+        let code = turbopack_ecmascript_runtime::get_dev_runtime_backend_code(
+            chunking_context.environment(),
+            chunking_context.chunk_loading(),
+        )
+        .await?;
+        Ok(code.source_code().to_str()?.to_string())
+    }
+
+    #[tokio::test]
+    async fn chunk_loading() -> Result<()> {
+        register();
+
+        let dir = tempfile::tempdir()?;
+        let root = dir.path().to_string_lossy().to_string();
+
+        run_once(async move {
+            let root = project_root(root);
+
+            // The strategy is derived from the environment by default.
+            for (env, expected) in [
+                (browser_env(true), ChunkLoading::DomScript),
+                (browser_env(false), ChunkLoading::ImportScripts),
+                (node_lambda_env(), ChunkLoading::NodeRequire),
+            ] {
+                let chunk_loading = loading_chunking_context(root, env, None)
+                    .chunk_loading()
+                    .await?;
+                assert_eq!(*chunk_loading, expected);
+            }
+
+            // Workers started from a page load their chunks with
+            // `importScripts`, even when the page uses another strategy.
+            let page = loading_chunking_context(
+                root,
+                browser_env(true),
+                Some(ChunkLoading::DynamicImport),
+            );
+            let worker = page.with_environment(page.environment().web_worker());
+            assert_eq!(*worker.chunk_loading().await?, ChunkLoading::ImportScripts);
+
+            // The runtime backend of every strategy loads chunks in its own
+            // way, and in no other.
+            let loaders = [
+                (
+                    browser_env(true),
+                    ChunkLoading::DomScript,
+                    "document.createElement(\"script\")",
+                ),
+                (
+                    browser_env(true),
+                    ChunkLoading::DynamicImport,
+                    "import(chunkUrl)",
+                ),
+                (
+                    browser_env(false),
+                    ChunkLoading::ImportScripts,
+                    "importScripts(",
+                ),
+                (
+                    node_lambda_env(),
+                    ChunkLoading::NodeRequire,
+                    "require(resolved)",
+                ),
+            ];
+            for &(env, chunk_loading, _) in &loaders {
+                let chunking_context = loading_chunking_context(root, env, Some(chunk_loading));
+                let code = runtime_backend_code(chunking_context).await?;
+                for &(_, other, other_loader) in &loaders {
+                    assert_eq!(
+                        code.contains(other_loader),
+                        other == chunk_loading,
+                        "{chunk_loading:?} {other_loader}"
+                    );
+                }
+            }
+
+            // `importScripts` doesn't exist in a DOM environment.
+            let invalid = loading_chunking_context(
+                root,
+                browser_env(true),
+                Some(ChunkLoading::ImportScripts),
+            );
+            assert!(invalid.chunk_loading().await.is_err());
+            assert!(runtime_backend_code(invalid).await.is_err());
+
+            Ok(())
+        })
+        .await
+    }
+}
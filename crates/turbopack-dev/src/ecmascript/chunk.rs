@@ -1,11 +1,16 @@
 use anyhow::Result;
 use indexmap::IndexSet;
-use turbo_tasks::{primitives::StringVc, ValueToString, ValueToStringVc};
+use turbo_tasks::{
+    primitives::{StringVc, U64Vc},
+    ValueToString, ValueToStringVc,
+};
+use turbo_tasks_fs::FileSystemPathVc;
+use turbo_tasks_hash::encode_hex;
 use turbopack_core::{
     asset::{Asset, AssetContentVc, AssetVc},
     chunk::{
-        ChunkingContext, OutputChunk, OutputChunkRuntimeInfo, OutputChunkRuntimeInfoVc,
-        OutputChunkVc,
+        hashed_file_name, ChunkingContext, ModuleIdsVc, OutputChunk, OutputChunkRuntimeInfo,
+        OutputChunkRuntimeInfoVc, OutputChunkVc,
     },
     ident::AssetIdentVc,
     introspect::{Introspectable, IntrospectableChildrenVc, IntrospectableVc},
@@ -16,7 +21,9 @@ use turbopack_core::{
     },
     version::{VersionedContent, VersionedContentVc},
 };
-use turbopack_ecmascript::chunk::EcmascriptChunkVc;
+use turbopack_ecmascript::chunk::{
+    EcmascriptChunkContentVc, EcmascriptChunkPlaceable, EcmascriptChunkVc,
+};
 
 use crate::{ecmascript::content::EcmascriptDevChunkContentVc, DevChunkingContextVc};
 
@@ -25,6 +32,9 @@ use crate::{ecmascript::content::EcmascriptDevChunkContentVc, DevChunkingContext
 pub(crate) struct EcmascriptDevChunk {
     chunking_context: DevChunkingContextVc,
     chunk: EcmascriptChunkVc,
+    /// The part of the chunk content when the chunk is split by the chunk
+    /// size budget.
+    part: Option<usize>,
 }
 
 #[turbo_tasks::value_impl]
@@ -35,9 +45,54 @@ impl EcmascriptDevChunkVc {
         EcmascriptDevChunk {
             chunking_context,
             chunk,
+            part: None,
         }
         .cell()
     }
+
+    /// Creates a new [`EcmascriptDevChunkVc`] for a part of a chunk which
+    /// exceeds the chunk size budget.
+    #[turbo_tasks::function]
+    pub fn new_part(
+        chunking_context: DevChunkingContextVc,
+        chunk: EcmascriptChunkVc,
+        part: usize,
+    ) -> Self {
+        EcmascriptDevChunk {
+            chunking_context,
+            chunk,
+            part: Some(part),
+        }
+        .cell()
+    }
+
+    #[turbo_tasks::function]
+    pub(crate) async fn chunk_content(self) -> Result<EcmascriptChunkContentVc> {
+        let this = self.await?;
+        Ok(match this.part {
+            Some(part) => this.chunk.content_part(part),
+            None => this.chunk.chunk_content(),
+        })
+    }
+
+    /// The ids of the chunk's entries which are included in this chunk.
+    #[turbo_tasks::function]
+    async fn included_ids(self) -> Result<ModuleIdsVc> {
+        let this = self.await?;
+        if this.part.is_none() {
+            return Ok(this.chunk.entry_ids());
+        }
+        let content = self.chunk_content().await?;
+        let context = this.chunk.await?.context;
+        let mut included_ids = Vec::new();
+        for &entry in &*this.chunk.main_entries().await? {
+            let item = entry.as_chunk_item(context).resolve().await?;
+            if content.chunk_items.contains(&item) {
+                included_ids.push(item.id());
+            }
+        }
+        Ok(ModuleIdsVc::cell(included_ids))
+    }
 }
 
 #[turbo_tasks::value_impl]
@@ -51,9 +106,9 @@ impl ValueToString for EcmascriptDevChunk {
 #[turbo_tasks::value_impl]
 impl OutputChunk for EcmascriptDevChunk {
     #[turbo_tasks::function]
-    fn runtime_info(&self) -> OutputChunkRuntimeInfoVc {
+    fn runtime_info(self_vc: EcmascriptDevChunkVc) -> OutputChunkRuntimeInfoVc {
         OutputChunkRuntimeInfo {
-            included_ids: Some(self.chunk.entry_ids()),
+            included_ids: Some(self_vc.included_ids()),
             ..Default::default()
         }
         .cell()
@@ -67,13 +122,55 @@ fn modifier() -> StringVc {
 
 #[turbo_tasks::value_impl]
 impl EcmascriptDevChunkVc {
+    /// The path of the chunk without its content hash.
+    #[turbo_tasks::function]
+    async fn unhashed_path(self) -> Result<FileSystemPathVc> {
+        let this = self.await?;
+        let mut ident = this.chunk.ident().with_modifier(modifier());
+        if let Some(part) = this.part {
+            ident = ident.with_modifier(StringVc::cell(format!("part {part}")));
+        }
+        Ok(this.chunking_context.chunk_path(ident, ".js"))
+    }
+
+    /// The hash of the content emitted to the unhashed path. The emitted code
+    /// references the path of the chunk, so this is the hash which is
+    /// included in a hashed path, as it can't depend on itself.
+    #[turbo_tasks::function]
+    async fn unhashed_content_hash(self) -> Result<U64Vc> {
+        let this = self.await?;
+        Ok(EcmascriptDevChunkContentVc::new(
+            this.chunking_context,
+            self.unhashed_path(),
+            self.chunk_content(),
+        )
+        .content()
+        .hash())
+    }
+
+    /// The path of the chunk, which includes its content hash when the
+    /// chunking context requests hashed filenames.
+    #[turbo_tasks::function]
+    async fn path(self) -> Result<FileSystemPathVc> {
+        let this = self.await?;
+        let unhashed_path = self.unhashed_path();
+        if !this.chunking_context.await?.hashed_chunk_filenames() {
+            return Ok(unhashed_path);
+        }
+        let content_hash = encode_hex(*self.unhashed_content_hash().await?);
+        let path = unhashed_path.await?;
+        Ok(unhashed_path
+            .parent()
+            .join(&hashed_file_name(&path, &content_hash)))
+    }
+
     #[turbo_tasks::function]
     async fn own_content(self) -> Result<EcmascriptDevChunkContentVc> {
         let this = self.await?;
         Ok(EcmascriptDevChunkContentVc::new(
             this.chunking_context,
-            self,
-            this.chunk.chunk_content(),
+            self.path(),
+            self.chunk_content(),
         ))
     }
 }
@@ -84,19 +181,25 @@ impl OutputAsset for EcmascriptDevChunk {}
 #[turbo_tasks::value_impl]
 impl Asset for EcmascriptDevChunk {
     #[turbo_tasks::function]
-    fn ident(&self) -> AssetIdentVc {
-        let ident = self.chunk.ident().with_modifier(modifier());
-        AssetIdentVc::from_path(self.chunking_context.chunk_path(ident, ".js"))
+    fn ident(self_vc: EcmascriptDevChunkVc) -> AssetIdentVc {
+        AssetIdentVc::from_path(self_vc.path())
     }
 
     #[turbo_tasks::function]
     async fn references(self_vc: EcmascriptDevChunkVc) -> Result<AssetReferencesVc> {
         let this = self_vc.await?;
-        let chunk_references = this.chunk.references().await?;
+        let chunk_references = match this.part {
+            Some(_) => self_vc
+                .chunk_content()
+                .await?
+                .external_asset_references
+                .clone(),
+            None => this.chunk.references().await?.clone_value(),
+        };
         let mut references = Vec::with_capacity(chunk_references.len() + 1);
 
-        for reference in &*chunk_references {
-            references.push(*reference);
+        for reference in chunk_references {
+            references.push(reference);
         }
 
         if *this
@@ -165,3 +268,254 @@ impl Introspectable for EcmascriptDevChunk {
         Ok(IntrospectableChildrenVc::cell(children))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeMap, iter::once};
+
+    use anyhow::{Context, Result};
+    use turbo_tasks::Value;
+    use turbo_tasks_fs::FileSystemPathVc;
+    use turbopack::{
+        module_options::ModuleOptionsContext, resolve_options_context::ResolveOptionsContext,
+    };
+    use turbopack_core::{
+        asset::Asset,
+        chunk::{
+            Chunk, ChunkVc, ChunkableModule, ChunkableModuleVc, ChunkingContext, ChunkingContextVc,
+        },
+        context::AssetContext,
+        file_source::FileSourceVc,
+        output::OutputAsset,
+        reference_type::{EntryReferenceSubType, ReferenceType},
+    };
+    use turbopack_ecmascript::{chunk::ModuleIdStrategy, CommentsMode};
+
+    use crate::{
+        tests::{
+            asset_context, node_build_time_env, project_root, register, run_once, write_files,
+        },
+        DevChunkingContextVc,
+    };
+
+    /// Returns the root chunk of `index.js` in `context_path`. Its module ids
+    /// are relative to the context path.
+    async fn root_chunk(
+        context_path: FileSystemPathVc,
+        preserve_comments: CommentsMode,
+        hashed_chunk_filenames: bool,
+    ) -> Result<ChunkVc> {
+        let env = node_build_time_env();
+        let context = asset_context(
+            env,
+            ModuleOptionsContext {
+                preserve_comments,
+                ..Default::default()
+            }
+            .cell(),
+            ResolveOptionsContext::default().cell(),
+        );
+        let chunking_context: ChunkingContextVc = DevChunkingContextVc::builder(
+            context_path,
+            context_path.join("output"),
+            context_path.join("output"),
+            context_path.join("static"),
+            env,
+        )
+        .chunk_item_id_strategy(ModuleIdStrategy::Named)
+        .hashed_chunk_filenames(hashed_chunk_filenames)
+        .build()
+        .into();
+
+        let module = context.process(
+            FileSourceVc::new(context_path.join("index.js")).into(),
+            Value::new(ReferenceType::Entry(EntryReferenceSubType::Undefined)),
+        );
+        let module = ChunkableModuleVc::resolve_from(module)
+            .await?
+            .context("expected a chunkable module")?;
+        Ok(module.as_root_chunk(chunking_context))
+    }
+
+    /// Returns the emitted code of `chunk`.
+    async fn emitted_code(chunk: ChunkVc) -> Result<String> {
+        let mut code = String::new();
+        for asset in chunk
+            .chunking_context()
+            .chunk_output_assets(chunk)
+            .await?
+            .iter()
+        {
+            let content = asset.content().file_content().await?;
+            let file = content.as_content().context("expected a file")?;
+            code.push_str(&file.content().to_str()?);
+        }
+        Ok(code)
+    }
+
+    /// Returns the content hashes of the output assets of the root chunk of
+    /// `index.js` in `context_path` and of its parallel chunks, keyed by their
+    /// path in the output directory.
+    async fn output_hashes(
+        context_path: FileSystemPathVc,
+        hashed_chunk_filenames: bool,
+    ) -> Result<BTreeMap<String, u64>> {
+        let chunk = root_chunk(context_path, CommentsMode::All, hashed_chunk_filenames).await?;
+        let output = context_path.join("output").await?;
+        let mut hashes = BTreeMap::new();
+        for chunk in once(chunk).chain(chunk.parallel_chunks().await?.iter().copied()) {
+            for asset in chunk
+                .chunking_context()
+                .chunk_output_assets(chunk)
+                .await?
+                .iter()
+            {
+                let path = asset.ident().path().await?;
+                let name = output
+                    .get_path_to(&path)
+                    .context("expected the chunk to be in the output directory")?
+                    .to_string();
+                hashes.insert(name, *asset.content_hash().await?);
+            }
+        }
+        Ok(hashes)
+    }
+
+    #[tokio::test]
+    async fn content_hash_stable() -> Result<()> {
+        register();
+
+        // `a` and `b` only differ in a comment, `c` in its code. `d` and `e`
+        // only differ in a template literal line which looks like a comment.
+        let dir = write_files(&[
+            ("a/index.js", "// Built from a\nconsole.log(\"hello\");\n"),
+            ("b/index.js", "// Built from b\nconsole.log(\"hello\");\n"),
+            ("c/index.js", "console.log(\"world\");\n"),
+            ("d/index.js", "console.log(`\n// Built from d\n`);\n"),
+            ("e/index.js", "console.log(`\n// Built from e\n`);\n"),
+        ]);
+        let root = dir.path().to_string_lossy().to_string();
+
+        run_once(async move {
+            let root = project_root(root);
+            let chunk = |name: &str, comments| root_chunk(root.join(name), comments, false);
+
+            // The hash is the hash of the emitted bytes, which include the
+            // comments when they are preserved.
+            let a = chunk("a", CommentsMode::All).await?;
+            let b = chunk("b", CommentsMode::All).await?;
+            let code = emitted_code(a).await?;
+            assert!(code.contains("// Built from a"), "{code}");
+            assert!(code.contains("//# sourceMappingURL="), "{code}");
+            assert_ne!(code, emitted_code(b).await?);
+            assert_ne!(
+                *a.content_hash_stable().await?,
+                *b.content_hash_stable().await?
+            );
+
+            // Without comments, `a` and `b` emit the same bytes.
+            let a = chunk("a", CommentsMode::None).await?;
+            let b = chunk("b", CommentsMode::None).await?;
+            let c = chunk("c", CommentsMode::None).await?;
+            assert_eq!(emitted_code(a).await?, emitted_code(b).await?);
+            let hash = *a.content_hash_stable().await?;
+            assert_eq!(hash, *b.content_hash_stable().await?);
+            assert_ne!(hash, *c.content_hash_stable().await?);
+
+            // Template literals are kept as they are, even without comments.
+            let d = chunk("d", CommentsMode::None).await?;
+            let e = chunk("e", CommentsMode::None).await?;
+            assert!(emitted_code(d).await?.contains("// Built from d"));
+            assert_ne!(
+                *d.content_hash_stable().await?,
+                *e.content_hash_stable().await?
+            );
+
+            Ok(())
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn content_hash_of_edited_module() -> Result<()> {
+        register();
+
+        // Only `index.js` differs between `before` and `after`, `dep.js` is in
+        // a parallel chunk since it's in `node_modules`.
+        let dep = "export const dep = \"dep\";\n";
+        let dir = write_files(&[
+            (
+                "before/index.js",
+                "import { dep } from \"./node_modules/dep.js\";\n\nconsole.log(\"before\", dep);\n",
+            ),
+            ("before/node_modules/dep.js", dep),
+            (
+                "after/index.js",
+                "import { dep } from \"./node_modules/dep.js\";\n\nconsole.log(\"after\", dep);\n",
+            ),
+            ("after/node_modules/dep.js", dep),
+        ]);
+        let root = dir.path().to_string_lossy().to_string();
+
+        run_once(async move {
+            let root = project_root(root);
+
+            let before = output_hashes(root.join("before"), false).await?;
+            let after = output_hashes(root.join("after"), false).await?;
+            assert_eq!(before.len(), 2, "{before:?}");
+            assert_eq!(
+                before.keys().collect::<Vec<_>>(),
+                after.keys().collect::<Vec<_>>()
+            );
+            for (name, hash) in before.iter() {
+                if name.contains("node_modules") {
+                    assert_eq!(after[name], *hash, "{name}");
+                } else {
+                    assert_ne!(after[name], *hash, "{name}");
+                }
+            }
+
+            // With hashed filenames, the filename includes a hash, so only the
+            // filename of the edited chunk changes.
+            let before = output_hashes(root.join("before"), true).await?;
+            let after = output_hashes(root.join("after"), true).await?;
+            assert_eq!(before.len(), 2, "{before:?}");
+            for name in before.keys().chain(after.keys()) {
+                let hash = name.rsplitn(3, '.').nth(1).unwrap_or_default();
+                assert!(
+                    hash.len() == 8 && hash.chars().all(|c| c.is_ascii_hexdigit()),
+                    "{name} doesn't include a hash"
+                );
+            }
+            let changed = before
+                .iter()
+                .filter(|(name, hash)| after.get(*name) != Some(hash))
+                .map(|(name, _)| name)
+                .collect::<Vec<_>>();
+            assert_eq!(changed.len(), 1, "{before:?} {after:?}");
+            assert!(!changed[0].contains("node_modules"), "{changed:?}");
+
+            // The content hash is the hash of the emitted bytes, with and
+            // without hashed filenames.
+            for hashed_chunk_filenames in [false, true] {
+                let chunk = root_chunk(
+                    root.join("before"),
+                    CommentsMode::All,
+                    hashed_chunk_filenames,
+                )
+                .await?;
+                for asset in chunk
+                    .chunking_context()
+                    .chunk_output_assets(chunk)
+                    .await?
+                    .iter()
+                {
+                    assert_eq!(*asset.content_hash().await?, *asset.content().hash().await?);
+                }
+            }
+
+            Ok(())
+        })
+        .await
+    }
+}
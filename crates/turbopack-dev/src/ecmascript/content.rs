@@ -2,7 +2,7 @@ use std::io::Write;
 
 use anyhow::{bail, Result};
 use indoc::writedoc;
-use turbo_tasks_fs::File;
+use turbo_tasks_fs::{File, FileSystemPathVc};
 use turbopack_core::{
     asset::{Asset, AssetContentVc},
     chunk::{ChunkingContext, ModuleId},
@@ -16,7 +16,7 @@ use turbopack_core::{
 use turbopack_ecmascript::{chunk::EcmascriptChunkContentVc, utils::StringifyJs};
 
 use super::{
-    chunk::EcmascriptDevChunkVc, content_entry::EcmascriptDevChunkContentEntriesVc,
+    content_entry::EcmascriptDevChunkContentEntriesVc,
     merged::merger::EcmascriptDevChunkContentMergerVc, version::EcmascriptDevChunkVersionVc,
 };
 use crate::DevChunkingContextVc;
@@ -25,7 +25,10 @@ use crate::DevChunkingContextVc;
 pub(super) struct EcmascriptDevChunkContent {
     pub(super) entries: EcmascriptDevChunkContentEntriesVc,
     pub(super) chunking_context: DevChunkingContextVc,
-    pub(super) chunk: EcmascriptDevChunkVc,
+    /// The path the content is emitted to, which is also referenced by the
+    /// code. It differs from the path of the chunk while the hash of the
+    /// content is computed for a hashed filename.
+    pub(super) chunk_path: FileSystemPathVc,
 }
 
 #[turbo_tasks::value_impl]
@@ -33,7 +36,7 @@ impl EcmascriptDevChunkContentVc {
     #[turbo_tasks::function]
     pub(crate) async fn new(
         chunking_context: DevChunkingContextVc,
-        chunk: EcmascriptDevChunkVc,
+        chunk_path: FileSystemPathVc,
         content: EcmascriptChunkContentVc,
     ) -> Result<Self> {
         let entries = EcmascriptDevChunkContentEntriesVc::new(content)
@@ -42,7 +45,7 @@ impl EcmascriptDevChunkContentVc {
         Ok(EcmascriptDevChunkContent {
             entries,
             chunking_context,
-            chunk,
+            chunk_path,
         }
         .cell())
     }
@@ -55,7 +58,7 @@ impl EcmascriptDevChunkContentVc {
         let this = self.await?;
         Ok(EcmascriptDevChunkVersionVc::new(
             this.chunking_context.output_root(),
-            this.chunk.ident().path(),
+            this.chunk_path,
             this.entries,
         ))
     }
@@ -64,7 +67,7 @@ impl EcmascriptDevChunkContentVc {
     async fn code(self) -> Result<CodeVc> {
         let this = self.await?;
         let output_root = this.chunking_context.output_root().await?;
-        let chunk_path = this.chunk.ident().path().await?;
+        let chunk_path = this.chunk_path.await?;
         let chunk_server_path = if let Some(path) = output_root.get_path_to(&chunk_path) {
             path
         } else {
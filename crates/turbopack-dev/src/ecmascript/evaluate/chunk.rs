@@ -22,8 +22,8 @@ use turbopack_ecmascript::{
     chunk::{EcmascriptChunkData, EcmascriptChunkPlaceable, EcmascriptChunkPlaceableVc},
     utils::StringifyJs,
 };
-use turbopack_ecmascript_runtime::RuntimeType;
 
+use super::runtime::push_runtime_code;
 use crate::DevChunkingContextVc;
 
 /// An Ecmascript chunk that:
@@ -69,7 +69,6 @@ impl EcmascriptDevEvaluateChunkVc {
     async fn code(self) -> Result<CodeVc> {
         let this = self.await?;
         let chunking_context = this.chunking_context.await?;
-        let environment = this.chunking_context.environment();
 
         let output_root = this.chunking_context.output_root().await?;
         let chunk_path = self.ident().path().await?;
@@ -139,19 +138,10 @@ impl EcmascriptDevEvaluateChunkVc {
             StringifyJs(&params),
         )?;
 
-        match chunking_context.runtime_type() {
-            RuntimeType::Default => {
-                let runtime_code = turbopack_ecmascript_runtime::get_dev_runtime_code(
-                    environment,
-                    chunking_context.chunk_base_path(),
-                );
-                code.push_code(&*runtime_code.await?);
-            }
-            #[cfg(feature = "test")]
-            RuntimeType::Dummy => {
-                let runtime_code = turbopack_ecmascript_runtime::get_dummy_runtime_code();
-                code.push_code(&runtime_code);
-            }
+        // The runtime is emitted on its own when the chunking context uses a
+        // separate runtime chunk.
+        if !chunking_context.separate_runtime_chunk() {
+            push_runtime_code(&mut code, this.chunking_context).await?;
         }
 
         if code.has_source_map() {
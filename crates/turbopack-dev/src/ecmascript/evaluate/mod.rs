@@ -1 +1,2 @@
 pub(crate) mod chunk;
+pub(crate) mod runtime;
@@ -0,0 +1,143 @@
+use std::io::Write;
+
+use anyhow::Result;
+use turbo_tasks::{primitives::StringVc, Value, ValueToString, ValueToStringVc};
+use turbo_tasks_fs::File;
+use turbopack_core::{
+    asset::{Asset, AssetContentVc},
+    chunk::ChunkingContext,
+    code_builder::{CodeBuilder, CodeVc},
+    ident::{AssetIdent, AssetIdentVc},
+    output::OutputAsset,
+    reference::AssetReferencesVc,
+    source_map::{
+        GenerateSourceMap, GenerateSourceMapVc, OptionSourceMapVc, SourceMapAssetReferenceVc,
+    },
+};
+use turbopack_ecmascript_runtime::RuntimeType;
+
+use crate::DevChunkingContextVc;
+
+/// Appends the Turbopack dev runtime code of `chunking_context` to `code`.
+pub(crate) async fn push_runtime_code(
+    code: &mut CodeBuilder,
+    chunking_context: DevChunkingContextVc,
+) -> Result<()> {
+    let environment = chunking_context.environment();
+    let chunk_loading = chunking_context.chunk_loading();
+    let chunk_base_path = chunking_context.chunk_base_path();
+    let chunking_context = chunking_context.await?;
+    match chunking_context.runtime_type() {
+        RuntimeType::Default => {
+            let runtime_code = turbopack_ecmascript_runtime::get_dev_runtime_code(
+                environment,
+                chunk_loading,
+                chunk_base_path,
+            );
+            code.push_code(&*runtime_code.await?);
+        }
+        #[cfg(feature = "test")]
+        RuntimeType::Dummy => {
+            let runtime_code = turbopack_ecmascript_runtime::get_dummy_runtime_code();
+            code.push_code(&runtime_code);
+        }
+    }
+    Ok(())
+}
+
+/// An Ecmascript chunk which only contains the Turbopack dev runtime code. It
+/// is emitted when the runtime is not bundled into the evaluated chunks, and
+/// picks up the chunks which were registered before it was loaded.
+#[turbo_tasks::value(shared)]
+pub(crate) struct EcmascriptDevRuntimeChunk {
+    chunking_context: DevChunkingContextVc,
+}
+
+#[turbo_tasks::value_impl]
+impl EcmascriptDevRuntimeChunkVc {
+    /// Creates a new [`EcmascriptDevRuntimeChunkVc`].
+    #[turbo_tasks::function]
+    pub fn new(chunking_context: DevChunkingContextVc) -> Self {
+        EcmascriptDevRuntimeChunk { chunking_context }.cell()
+    }
+
+    #[turbo_tasks::function]
+    async fn code(self) -> Result<CodeVc> {
+        let this = self.await?;
+        let chunk_path = self.ident().path().await?;
+
+        let mut code = CodeBuilder::default();
+        push_runtime_code(&mut code, this.chunking_context).await?;
+
+        if code.has_source_map() {
+            let filename = chunk_path.file_name();
+            write!(code, "\n\n//# sourceMappingURL={}.map", filename)?;
+        }
+
+        Ok(CodeVc::cell(code.build()))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ValueToString for EcmascriptDevRuntimeChunk {
+    #[turbo_tasks::function]
+    async fn to_string(&self) -> Result<StringVc> {
+        Ok(StringVc::cell("Ecmascript Dev Runtime Chunk".to_string()))
+    }
+}
+
+#[turbo_tasks::function]
+fn modifier() -> StringVc {
+    StringVc::cell("ecmascript dev runtime chunk".to_string())
+}
+
+#[turbo_tasks::value_impl]
+impl OutputAsset for EcmascriptDevRuntimeChunk {}
+
+#[turbo_tasks::value_impl]
+impl Asset for EcmascriptDevRuntimeChunk {
+    #[turbo_tasks::function]
+    fn ident(&self) -> AssetIdentVc {
+        // The runtime doesn't depend on the entries, so all evaluated chunk
+        // groups of a chunking context share it.
+        let mut ident = AssetIdent::from_path(
+            self.chunking_context
+                .context_path()
+                .join("__turbopack__/runtime"),
+        );
+        ident.add_modifier(modifier());
+
+        let ident = AssetIdentVc::new(Value::new(ident));
+        AssetIdentVc::from_path(self.chunking_context.chunk_path(ident, ".js"))
+    }
+
+    #[turbo_tasks::function]
+    async fn references(self_vc: EcmascriptDevRuntimeChunkVc) -> Result<AssetReferencesVc> {
+        let this = self_vc.await?;
+        let mut references = Vec::new();
+
+        if *this
+            .chunking_context
+            .reference_chunk_source_maps(self_vc.into())
+            .await?
+        {
+            references.push(SourceMapAssetReferenceVc::new(self_vc.into()).into());
+        }
+
+        Ok(AssetReferencesVc::cell(references))
+    }
+
+    #[turbo_tasks::function]
+    async fn content(self_vc: EcmascriptDevRuntimeChunkVc) -> Result<AssetContentVc> {
+        let code = self_vc.code().await?;
+        Ok(File::from(code.source_code().clone()).into())
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl GenerateSourceMap for EcmascriptDevRuntimeChunk {
+    #[turbo_tasks::function]
+    fn generate_source_map(self_vc: EcmascriptDevRuntimeChunkVc) -> OptionSourceMapVc {
+        self_vc.code().generate_source_map()
+    }
+}
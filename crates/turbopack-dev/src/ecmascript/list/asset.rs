@@ -75,6 +75,11 @@ fn chunk_list_chunk_reference_description() -> StringVc {
     StringVc::cell("chunk list chunk".to_string())
 }
 
+#[turbo_tasks::function]
+fn chunk_registry_reference_description() -> StringVc {
+    StringVc::cell("chunk registry".to_string())
+}
+
 #[turbo_tasks::value_impl]
 impl OutputAsset for EcmascriptDevChunkList {}
 
@@ -98,19 +103,34 @@ impl Asset for EcmascriptDevChunkList {
 
     #[turbo_tasks::function]
     async fn references(&self) -> Result<AssetReferencesVc> {
-        Ok(AssetReferencesVc::cell(
-            self.chunks
-                .await?
-                .iter()
-                .map(|&chunk| {
-                    SingleAssetReferenceVc::new(
-                        chunk.into(),
-                        chunk_list_chunk_reference_description(),
-                    )
+        let mut references: Vec<_> = self
+            .chunks
+            .await?
+            .iter()
+            .map(|&chunk| {
+                SingleAssetReferenceVc::new(chunk.into(), chunk_list_chunk_reference_description())
                     .into()
-                })
-                .collect(),
-        ))
+            })
+            .collect();
+
+        // The chunk registry is emitted with the chunk list, but it's not a
+        // chunk, so it's not part of the chunk group.
+        for &registry in self
+            .chunking_context
+            .generate_chunk_registry(self.entry_chunk, self.chunks)
+            .await?
+            .iter()
+        {
+            references.push(
+                SingleAssetReferenceVc::new(
+                    registry.into(),
+                    chunk_registry_reference_description(),
+                )
+                .into(),
+            );
+        }
+
+        Ok(AssetReferencesVc::cell(references))
     }
 
     #[turbo_tasks::function]
@@ -5,5 +5,6 @@ pub(crate) mod evaluate;
 pub(crate) mod list;
 pub(crate) mod merged;
 pub(crate) mod optimize;
+pub(crate) mod registry;
 pub(crate) mod update;
 pub(crate) mod version;
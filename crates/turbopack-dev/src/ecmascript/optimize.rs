@@ -1,14 +1,25 @@
 //! Chunk optimization for Ecmascript chunks.
 
-use std::{cmp::Ordering, collections::HashSet};
+use std::{
+    cmp::Ordering,
+    collections::{HashMap, HashSet},
+};
 
 use anyhow::Result;
 use indexmap::{IndexMap, IndexSet};
-use turbo_tasks::{TryJoinIterExt, Value};
+use turbo_tasks::{TryJoinIterExt, Value, ValueToString};
 use turbo_tasks_fs::FileSystemPathOptionVc;
-use turbopack_core::chunk::optimize::optimize_by_common_parent;
+use turbopack_core::{
+    asset::{Asset, AssetVc},
+    chunk::{
+        availability_info::AvailabilityInfo,
+        optimize::{merge_small_chunks, optimize_by_common_parent},
+        ChunkingContext, ChunkingContextVc,
+    },
+};
 use turbopack_ecmascript::chunk::{
-    EcmascriptChunkPlaceablesVc, EcmascriptChunkVc, EcmascriptChunkingContextVc, EcmascriptChunksVc,
+    EcmascriptChunkItemVc, EcmascriptChunkPlaceableVc, EcmascriptChunkPlaceablesVc,
+    EcmascriptChunkVc, EcmascriptChunkingContextVc, EcmascriptChunksVc,
 };
 
 #[turbo_tasks::function]
@@ -53,6 +64,203 @@ pub async fn optimize_ecmascript_chunks(chunks: EcmascriptChunksVc) -> Result<Ec
     Ok(EcmascriptChunksVc::cell(optimized_chunks))
 }
 
+/// Merges chunks that are smaller than the minimum chunk size of the chunking
+/// context into their neighbors. Only consecutive chunks with the same chunking
+/// context and availability info are merged.
+#[turbo_tasks::function]
+pub async fn merge_small_ecmascript_chunks(
+    chunks: EcmascriptChunksVc,
+    chunking_context: ChunkingContextVc,
+) -> Result<EcmascriptChunksVc> {
+    let mut runs: Vec<Vec<EcmascriptChunkVc>> = Vec::new();
+    let mut previous_key = None;
+    for &chunk in chunks.await?.iter() {
+        let content = chunk.await?;
+        let key = (content.context.resolve().await?, content.availability_info);
+        match runs.last_mut() {
+            Some(run) if previous_key.as_ref() == Some(&key) => run.push(chunk),
+            _ => runs.push(vec![chunk]),
+        }
+        previous_key = Some(key);
+    }
+
+    let mut merged = Vec::new();
+    for run in runs {
+        merged.extend(
+            merge_small_chunks(
+                chunking_context,
+                run,
+                |chunk| async move { Ok(*chunk.chunk_items_count().await?) },
+                |chunks| async move { merge_chunks(chunks[0], &chunks).await },
+            )
+            .await?,
+        );
+    }
+    Ok(EcmascriptChunksVc::cell(merged))
+}
+
+/// Hoists the chunk items which are in at least `min_chunks` of the chunks
+/// into common chunks, so that their code is only emitted once per chunk
+/// group. The common chunks are placed before the other chunks, and the
+/// hoisted chunk items are omitted from the chunks they were in. The chunks
+/// of a chunk group are loaded in parallel before any module is evaluated,
+/// so the hoisted modules are still registered when they are required.
+///
+/// Chunk items which can't be in the same chunk, as decided by the chunking
+/// context, are hoisted into different common chunks. Values of `min_chunks`
+/// below 2 disable hoisting.
+#[turbo_tasks::function]
+pub async fn hoist_common_chunk_items(
+    chunks: EcmascriptChunksVc,
+    min_chunks: usize,
+) -> Result<EcmascriptChunksVc> {
+    let chunks_ref = chunks.await?;
+    if min_chunks < 2 || chunks_ref.len() < min_chunks {
+        return Ok(chunks);
+    }
+
+    // Only chunks with the same chunking context share chunk items, see
+    // `optimize_ecmascript_chunks`.
+    let mut chunks_by_chunking_context: IndexMap<EcmascriptChunkingContextVc, Vec<usize>> =
+        IndexMap::new();
+    for (index, chunk) in chunks_ref.iter().enumerate() {
+        let chunking_context = chunk.await?.context.resolve().await?;
+        chunks_by_chunking_context
+            .entry(chunking_context)
+            .or_default()
+            .push(index);
+    }
+
+    let mut common_chunks = Vec::new();
+    let mut optimized_chunks = chunks_ref.clone_value();
+    for (chunking_context, indices) in chunks_by_chunking_context {
+        if indices.len() < min_chunks {
+            continue;
+        }
+        let chunks = indices
+            .iter()
+            .map(|&index| chunks_ref[index])
+            .collect::<Vec<_>>();
+        let (common, hoisted_chunks) =
+            hoist_common_items(chunking_context, &chunks, min_chunks).await?;
+        common_chunks.extend(common);
+        for (index, chunk) in indices.into_iter().zip(hoisted_chunks) {
+            optimized_chunks[index] = chunk;
+        }
+    }
+
+    common_chunks.extend(optimized_chunks);
+    Ok(EcmascriptChunksVc::cell(common_chunks))
+}
+
+/// Returns the common chunks of the chunks of a single chunking context, and
+/// the chunks with the hoisted chunk items omitted, in their original order.
+async fn hoist_common_items(
+    chunking_context: EcmascriptChunkingContextVc,
+    chunks: &[EcmascriptChunkVc],
+    min_chunks: usize,
+) -> Result<(Vec<EcmascriptChunkVc>, Vec<EcmascriptChunkVc>)> {
+    let contents = chunks
+        .iter()
+        .map(|chunk| chunk.chunk_content())
+        .try_join()
+        .await?;
+
+    let mut counts = IndexMap::<EcmascriptChunkItemVc, (AssetVc, usize)>::new();
+    for content in contents.iter() {
+        let items = content
+            .chunk_items
+            .iter()
+            .copied()
+            .zip(content.chunk_item_assets.iter().copied())
+            .collect::<IndexMap<_, _>>();
+        for (item, asset) in items {
+            counts.entry(item).or_insert((asset, 0)).1 += 1;
+        }
+    }
+
+    let mut candidates = Vec::new();
+    for (item, (asset, count)) in counts {
+        if count < min_chunks {
+            continue;
+        }
+        let Some(placeable) = EcmascriptChunkPlaceableVc::resolve_from(asset).await? else {
+            continue;
+        };
+        candidates.push((asset.ident().to_string().await?, item, asset, placeable));
+    }
+    if candidates.is_empty() {
+        return Ok((Vec::new(), chunks.to_vec()));
+    }
+    // Sorted by ident, so that the common chunks don't depend on the order
+    // the chunk items were found in.
+    candidates.sort_by(|(a, ..), (b, ..)| a.cmp(b));
+
+    // Each group of hoisted chunk items becomes a common chunk. A chunk item
+    // joins the first group whose first chunk item it can be in the same
+    // chunk with.
+    let context: ChunkingContextVc = chunking_context.into();
+    let mut groups: Vec<(AssetVc, Vec<EcmascriptChunkPlaceableVc>)> = Vec::new();
+    let mut hoisted = HashMap::new();
+    for (_, item, asset, placeable) in candidates {
+        let mut group_index = None;
+        for (index, &(first, _)) in groups.iter().enumerate() {
+            if *context.can_be_in_same_chunk(first, asset).await?
+                && *context.can_be_in_same_chunk(asset, first).await?
+            {
+                group_index = Some(index);
+                break;
+            }
+        }
+        match group_index {
+            Some(index) => groups[index].1.push(placeable),
+            None => groups.push((asset, vec![placeable])),
+        }
+        hoisted.insert(item, placeable);
+    }
+
+    // The common chunks don't track availability, so they contain everything
+    // which is omitted from any of the chunks, whatever was available to it.
+    let common_chunks = groups
+        .into_iter()
+        .map(|(_, entries)| {
+            EcmascriptChunkVc::new_normalized(
+                chunking_context,
+                EcmascriptChunkPlaceablesVc::cell(entries),
+                None,
+                Value::new(AvailabilityInfo::Untracked),
+            )
+        })
+        .collect();
+
+    let mut hoisted_chunks = Vec::with_capacity(chunks.len());
+    for (&chunk, content) in chunks.iter().zip(contents.iter()) {
+        let omitted = content
+            .chunk_items
+            .iter()
+            .filter_map(|item| hoisted.get(item).copied())
+            .collect::<Vec<_>>();
+        if omitted.is_empty() {
+            hoisted_chunks.push(chunk);
+            continue;
+        }
+        let chunk = chunk.await?;
+        let mut omit_entries = match chunk.omit_entries {
+            Some(omit_entries) => omit_entries.await?.clone_value(),
+            None => Vec::new(),
+        };
+        omit_entries.extend(omitted);
+        hoisted_chunks.push(EcmascriptChunkVc::new_normalized(
+            chunk.context,
+            chunk.main_entries,
+            Some(EcmascriptChunkPlaceablesVc::cell(omit_entries)),
+            Value::new(chunk.availability_info),
+        ));
+    }
+
+    Ok((common_chunks, hoisted_chunks))
+}
+
 #[turbo_tasks::function]
 async fn get_common_parent(chunk: EcmascriptChunkVc) -> Result<FileSystemPathOptionVc> {
     Ok(chunk.common_parent())
@@ -0,0 +1,323 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use anyhow::Result;
+use serde::Serialize;
+use turbo_tasks::{primitives::StringVc, TryJoinIterExt, Value, ValueToString, ValueToStringVc};
+use turbo_tasks_fs::{File, FileSystemPath};
+use turbopack_core::{
+    asset::{Asset, AssetContentVc, AssetVc},
+    chunk::{ChunkVc, ChunkingContext},
+    ident::AssetIdentVc,
+    output::{OutputAsset, OutputAssetVc, OutputAssetsVc},
+    reference::AssetReferencesVc,
+};
+use turbopack_ecmascript::ManifestLoaderItemVc;
+
+use super::chunk::EcmascriptDevChunkVc;
+use crate::DevChunkingContextVc;
+
+/// An asset that maps the module ids of a chunk group to the paths of the
+/// chunks which contain them, relative to the output root.
+///
+/// The chunk groups of dynamic imports in the chunk group are included one
+/// level deep under `"async"`:
+///
+/// ```json
+/// {
+///   "chunks": { "<module id>": ["<chunk path>"] },
+///   "async": { "<module id>": ["<chunk path>"] }
+/// }
+/// ```
+#[turbo_tasks::value(shared)]
+pub struct ChunkRegistryAsset {
+    chunking_context: DevChunkingContextVc,
+    entry_chunk: ChunkVc,
+    chunks: OutputAssetsVc,
+}
+
+#[turbo_tasks::value_impl]
+impl ChunkRegistryAssetVc {
+    /// Creates a new [`ChunkRegistryAssetVc`] for the `chunks` of the chunk
+    /// group of `entry_chunk`.
+    #[turbo_tasks::function]
+    pub fn new(
+        chunking_context: DevChunkingContextVc,
+        entry_chunk: ChunkVc,
+        chunks: OutputAssetsVc,
+    ) -> Self {
+        ChunkRegistryAsset {
+            chunking_context,
+            entry_chunk,
+            chunks,
+        }
+        .cell()
+    }
+}
+
+#[derive(Serialize)]
+struct ChunkRegistry {
+    chunks: BTreeMap<String, BTreeSet<String>>,
+    #[serde(rename = "async")]
+    async_chunks: BTreeMap<String, BTreeSet<String>>,
+}
+
+/// Adds the module ids of the ecmascript chunks in `chunks` to `registry`,
+/// and the async chunk groups of the dynamic imports to `async_chunk_groups`.
+async fn add_chunks(
+    output_root: &FileSystemPath,
+    chunks: OutputAssetsVc,
+    registry: &mut BTreeMap<String, BTreeSet<String>>,
+    mut async_chunk_groups: Option<&mut Vec<OutputAssetsVc>>,
+) -> Result<()> {
+    for &chunk in chunks.await?.iter() {
+        let Some(dev_chunk) = EcmascriptDevChunkVc::resolve_from(chunk).await? else {
+            continue;
+        };
+        let path = chunk.ident().path().await?;
+        let Some(path) = output_root.get_path_to(&path) else {
+            continue;
+        };
+        let content = dev_chunk.chunk_content().await?;
+        let ids = content
+            .chunk_items
+            .iter()
+            .map(|item| item.id())
+            .try_join()
+            .await?;
+        for id in ids {
+            registry
+                .entry(id.to_string())
+                .or_default()
+                .insert(path.to_string());
+        }
+        if let Some(async_chunk_groups) = async_chunk_groups.as_deref_mut() {
+            for &item in content.chunk_items.iter() {
+                if let Some(loader) = ManifestLoaderItemVc::resolve_from(item).await? {
+                    async_chunk_groups.push(loader.async_chunk_group());
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+#[turbo_tasks::value_impl]
+impl ChunkRegistryAssetVc {
+    #[turbo_tasks::function]
+    async fn code(self) -> Result<AssetContentVc> {
+        let this = self.await?;
+        let output_root = this.chunking_context.output_root().await?;
+
+        let mut registry = ChunkRegistry {
+            chunks: BTreeMap::new(),
+            async_chunks: BTreeMap::new(),
+        };
+        let mut async_chunk_groups = Vec::new();
+        add_chunks(
+            &output_root,
+            this.chunks,
+            &mut registry.chunks,
+            Some(&mut async_chunk_groups),
+        )
+        .await?;
+        for chunks in async_chunk_groups {
+            add_chunks(&output_root, chunks, &mut registry.async_chunks, None).await?;
+        }
+
+        let code = serde_json::to_string_pretty(&registry)?;
+        Ok(File::from(code).into())
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ValueToString for ChunkRegistryAsset {
+    #[turbo_tasks::function]
+    async fn to_string(&self) -> Result<StringVc> {
+        Ok(StringVc::cell("Chunk Registry".to_string()))
+    }
+}
+
+#[turbo_tasks::function]
+fn modifier() -> StringVc {
+    StringVc::cell("chunk registry".to_string())
+}
+
+#[turbo_tasks::value_impl]
+impl OutputAsset for ChunkRegistryAsset {}
+
+#[turbo_tasks::value_impl]
+impl Asset for ChunkRegistryAsset {
+    #[turbo_tasks::function]
+    async fn ident(&self) -> Result<AssetIdentVc> {
+        let mut ident = self.entry_chunk.ident().await?.clone_value();
+
+        ident.add_modifier(modifier());
+
+        let ident = AssetIdentVc::new(Value::new(ident));
+        Ok(AssetIdentVc::from_path(
+            self.chunking_context.chunk_path(ident, ".json"),
+        ))
+    }
+
+    #[turbo_tasks::function]
+    fn references(&self) -> AssetReferencesVc {
+        AssetReferencesVc::empty()
+    }
+
+    #[turbo_tasks::function]
+    fn content(self_vc: ChunkRegistryAssetVc) -> AssetContentVc {
+        self_vc.code()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use anyhow::{bail, Context, Result};
+    use serde::Deserialize;
+    use turbo_tasks::Value;
+    use turbo_tasks_fs::{FileContent, FileSystemPathVc};
+    use turbopack_core::{
+        asset::Asset,
+        chunk::{ChunkableModule, ChunkableModuleVc, ChunkingContext, ChunkingContextVc},
+        context::AssetContext,
+        file_source::FileSourceVc,
+        reference::AssetReference,
+        reference_type::{EntryReferenceSubType, ReferenceType},
+    };
+
+    use super::ChunkRegistryAssetVc;
+    use crate::tests::{
+        default_asset_context, dev_chunking_context, node_build_time_env, project_root, register,
+        run_once, write_files,
+    };
+
+    #[derive(Deserialize)]
+    struct ChunkRegistry {
+        chunks: BTreeMap<String, Vec<String>>,
+        #[serde(rename = "async")]
+        async_chunks: BTreeMap<String, Vec<String>>,
+    }
+
+    /// Returns the chunk paths of the module with the file name `name`, not of
+    /// its manifest chunk items.
+    fn chunk_paths<'a>(
+        registry: &'a BTreeMap<String, Vec<String>>,
+        name: &str,
+    ) -> Option<&'a [String]> {
+        registry
+            .iter()
+            .find(|(id, _)| id.ends_with(&format!("/{name} (ecmascript)")))
+            .map(|(_, paths)| &paths[..])
+    }
+
+    /// Returns the contents of the chunk registries in the chunk group of
+    /// `index.js`.
+    async fn chunk_registries(root: FileSystemPathVc, enabled: bool) -> Result<Vec<String>> {
+        let env = node_build_time_env();
+        let context = default_asset_context(env);
+        let chunking_context: ChunkingContextVc = dev_chunking_context(root, env)
+            .chunk_registry(enabled)
+            .build()
+            .into();
+
+        let module = context.process(
+            FileSourceVc::new(root.join("index.js")).into(),
+            Value::new(ReferenceType::Entry(EntryReferenceSubType::Undefined)),
+        );
+        let module = ChunkableModuleVc::resolve_from(module)
+            .await?
+            .context("expected a chunkable module")?;
+        let chunk = module.as_root_chunk(chunking_context);
+
+        // The registry is not part of the chunk group, it's referenced by the
+        // chunk list of the group.
+        let mut registries = Vec::new();
+        for &asset in chunking_context.chunk_group(chunk).await?.iter() {
+            assert!(ChunkRegistryAssetVc::resolve_from(asset).await?.is_none());
+            for &reference in asset.references().await?.iter() {
+                for &referenced in reference.resolve_reference().primary_assets().await?.iter() {
+                    if let Some(registry) = ChunkRegistryAssetVc::resolve_from(referenced).await? {
+                        registries.push(registry);
+                    }
+                }
+            }
+        }
+
+        let mut contents = Vec::new();
+        for registry in registries {
+            assert!(registry.ident().path().await?.path.ends_with(".json"));
+            let FileContent::Content(file) = &*registry.content().file_content().await? else {
+                bail!("expected the chunk registry to have content");
+            };
+            contents.push(file.content().to_str()?.into_owned());
+        }
+        Ok(contents)
+    }
+
+    #[tokio::test]
+    async fn chunk_registry() -> Result<()> {
+        register();
+
+        let dir = write_files(&[
+            (
+                "index.js",
+                "import { helper } from \
+                 \"./helper.js\";\n\nhelper();\nimport(\"./lib.js\").then(({ lib }) => lib());\n",
+            ),
+            (
+                "helper.js",
+                "export function helper() {\n  console.log(\"helper\");\n}\n",
+            ),
+            (
+                "lib.js",
+                "export function lib() {\n  console.log(\"lib\");\n}\n",
+            ),
+        ]);
+        let root = dir.path().to_string_lossy().to_string();
+
+        run_once(async move {
+            let root = project_root(root);
+
+            // The registry is only emitted when enabled.
+            assert!(chunk_registries(root, false).await?.is_empty());
+
+            let registries = chunk_registries(root, true).await?;
+            let [content] = &registries[..] else {
+                bail!("expected a single chunk registry, got {}", registries.len());
+            };
+            let registry: ChunkRegistry = serde_json::from_str(content)?;
+
+            let entry_chunks = chunk_paths(&registry.chunks, "index.js").context("index.js")?;
+            assert_eq!(
+                chunk_paths(&registry.chunks, "helper.js"),
+                Some(entry_chunks)
+            );
+
+            // The dynamically imported module is only in its async chunk.
+            assert!(
+                chunk_paths(&registry.chunks, "lib.js").is_none(),
+                "{content}"
+            );
+            let async_chunks = chunk_paths(&registry.async_chunks, "lib.js").context("lib.js")?;
+            assert!(!async_chunks.is_empty());
+            for path in async_chunks {
+                assert!(!entry_chunks.contains(path), "{content}");
+            }
+
+            // Source maps are not part of the registry.
+            for path in registry
+                .chunks
+                .values()
+                .chain(registry.async_chunks.values())
+                .flatten()
+            {
+                assert!(path.ends_with(".js"), "{path}");
+            }
+
+            Ok(())
+        })
+        .await
+    }
+}
@@ -6,8 +6,11 @@ pub(crate) mod chunking_context;
 pub(crate) mod css;
 pub(crate) mod ecmascript;
 pub mod react_refresh;
+#[cfg(test)]
+mod tests;
 
 pub use chunking_context::{DevChunkingContext, DevChunkingContextBuilder, DevChunkingContextVc};
+pub use ecmascript::registry::{ChunkRegistryAsset, ChunkRegistryAssetVc};
 
 pub fn register() {
     turbo_tasks::register();
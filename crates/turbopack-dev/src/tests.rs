@@ -0,0 +1,1132 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    future::Future,
+};
+
+use anyhow::{bail, Context, Result};
+use indexmap::IndexSet;
+use tempfile::TempDir;
+use turbo_tasks::{
+    registry, NothingVc, StatsType, TurboTasks, TurboTasksBackendApi, Value, ValueToString,
+};
+use turbo_tasks_fs::{DiskFileSystemVc, FileSystem, FileSystemPathVc};
+use turbo_tasks_memory::{stats::StatsTaskType, MemoryBackend};
+use turbopack::{
+    module_options::{ModuleOptionsContext, ModuleOptionsContextVc},
+    resolve_options_context::{ResolveOptionsContext, ResolveOptionsContextVc},
+    transition::TransitionsByNameVc,
+    ModuleAssetContextVc,
+};
+use turbopack_build::BuildChunkingContextVc;
+use turbopack_core::{
+    asset::{Asset, AssetsSetVc},
+    chunk::{
+        availability_info::AvailabilityInfo, available_assets::AvailableAssetsVc, ChunkItem,
+        ChunkableModule, ChunkableModuleReference, ChunkableModuleReferenceVc, ChunkableModuleVc,
+        ChunkingContext, ChunkingContextVc, EvaluatableAssetsVc,
+    },
+    compile_time_info::CompileTimeInfoVc,
+    context::{AssetContext, AssetContextVc},
+    environment::{BrowserEnvironment, EnvironmentVc, ExecutionEnvironment, NodeJsEnvironment},
+    file_source::FileSourceVc,
+    issue::IssueVc,
+    output::OutputAssetVc,
+    reference::AssetReference,
+    reference_type::{EntryReferenceSubType, ReferenceType},
+    resolve::ModulePartVc,
+    source_map::{GenerateSourceMap, GenerateSourceMapVc, Token},
+};
+use turbopack_ecmascript::{
+    analysis_stats::{ModuleAnalysisStats, ModulesStatsVc},
+    chunk::{
+        EcmascriptChunkItem, EcmascriptChunkPlaceable, EcmascriptChunkPlaceableVc,
+        EcmascriptChunkVc, EcmascriptChunkingContextVc, EcmascriptExports,
+    },
+    tree_shake::asset::EcmascriptModulePartAssetVc,
+    EcmascriptModuleAssetVc,
+};
+
+use crate::{DevChunkingContextBuilder, DevChunkingContextVc};
+
+pub(crate) fn register() {
+    turbopack::register();
+    crate::register();
+}
+
+/// Writes `files`, given as paths and contents, to a new temporary directory.
+pub(crate) fn write_files(files: &[(&str, &str)]) -> TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    for (file, content) in files {
+        let path = dir.path().join(file);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+    dir
+}
+
+/// The root of a disk file system named `project` which is rooted at `root`.
+pub(crate) fn project_root(root: String) -> FileSystemPathVc {
+    DiskFileSystemVc::new("project".to_string(), root).root()
+}
+
+/// Runs `future` in a once task of a new [TurboTasks] instance, and waits until
+/// it's fully settled.
+pub(crate) async fn run_once(
+    future: impl Future<Output = Result<()>> + Send + 'static,
+) -> Result<()> {
+    let tt = TurboTasks::new(MemoryBackend::default());
+    let task = tt.spawn_once_task(async move {
+        future.await?;
+        Ok(NothingVc::new().into())
+    });
+    tt.wait_task_completion(task, true).await
+}
+
+pub(crate) fn node_build_time_env() -> EnvironmentVc {
+    EnvironmentVc::new(Value::new(ExecutionEnvironment::NodeJsBuildTime(
+        NodeJsEnvironment::default().into(),
+    )))
+}
+
+pub(crate) fn node_lambda_env() -> EnvironmentVc {
+    EnvironmentVc::new(Value::new(ExecutionEnvironment::NodeJsLambda(
+        NodeJsEnvironment::default().into(),
+    )))
+}
+
+/// A browser environment of a document when `dom` is set, or of a web worker
+/// otherwise.
+pub(crate) fn browser_env(dom: bool) -> EnvironmentVc {
+    EnvironmentVc::new(Value::new(ExecutionEnvironment::Browser(
+        BrowserEnvironment {
+            dom,
+            web_worker: !dom,
+            service_worker: false,
+            browserslist_query: "Chrome 100".to_string(),
+        }
+        .into(),
+    )))
+}
+
+/// An asset context without transitions.
+pub(crate) fn asset_context(
+    env: EnvironmentVc,
+    module_options_context: ModuleOptionsContextVc,
+    resolve_options_context: ResolveOptionsContextVc,
+) -> AssetContextVc {
+    ModuleAssetContextVc::new(
+        TransitionsByNameVc::cell(HashMap::new()),
+        CompileTimeInfoVc::new(env),
+        module_options_context,
+        resolve_options_context,
+    )
+    .into()
+}
+
+/// An asset context without transitions and with the default options.
+pub(crate) fn default_asset_context(env: EnvironmentVc) -> AssetContextVc {
+    asset_context(
+        env,
+        ModuleOptionsContext::default().cell(),
+        ResolveOptionsContext::default().cell(),
+    )
+}
+
+/// A dev chunking context for the project at `root`, which emits chunks to
+/// `output` and assets to `static` in it.
+pub(crate) fn dev_chunking_context(
+    root: FileSystemPathVc,
+    env: EnvironmentVc,
+) -> DevChunkingContextBuilder {
+    DevChunkingContextVc::builder(
+        root,
+        root.join("output"),
+        root.join("output"),
+        root.join("static"),
+        env,
+    )
+}
+
+/// Processes the file at `path` as an asset which isn't referenced by another
+/// one and expects an ecmascript module.
+pub(crate) async fn ecmascript_module(
+    context: AssetContextVc,
+    path: FileSystemPathVc,
+) -> Result<EcmascriptModuleAssetVc> {
+    let module = context.process(
+        FileSourceVc::new(path).into(),
+        Value::new(ReferenceType::Undefined),
+    );
+    EcmascriptModuleAssetVc::resolve_from(module)
+        .await?
+        .context("expected an ecmascript module")
+}
+
+/// Processes the file at `path` as an entry and expects an ecmascript module.
+pub(crate) async fn entry_module(
+    context: AssetContextVc,
+    path: FileSystemPathVc,
+) -> Result<EcmascriptModuleAssetVc> {
+    let module = context.process(
+        FileSourceVc::new(path).into(),
+        Value::new(ReferenceType::Entry(EntryReferenceSubType::Undefined)),
+    );
+    EcmascriptModuleAssetVc::resolve_from(module)
+        .await?
+        .context("expected an ecmascript module")
+}
+
+#[tokio::test]
+async fn analysis_stats() -> Result<()> {
+    register();
+
+    let dir = write_files(&[
+        (
+            "index.js",
+            r#"import { esm } from "./esm.js";
+
+const cjs = require("./cjs.js");
+
+import("./dynamic.js").then((dynamic) => console.log(dynamic));
+
+console.log(esm, cjs, require.resolve("./missing.js"));
+"#,
+        ),
+        ("esm.js", "export const esm = \"esm\";\n"),
+        ("cjs.js", "module.exports = \"cjs\";\n"),
+        ("dynamic.js", "export default \"dynamic\";\n"),
+    ]);
+    let root = dir.path().to_string_lossy().to_string();
+
+    run_once(async move {
+        let root = project_root(root);
+        let env = node_build_time_env();
+        let context = default_asset_context(env);
+        let chunking_context = dev_chunking_context(root, env).build();
+
+        let module = entry_module(context, root.join("index.js")).await?;
+
+        let stats = *module.analysis_stats(chunking_context.into()).await?;
+        assert_eq!(stats.static_imports, 1);
+        assert_eq!(stats.dynamic_imports, 1);
+        assert_eq!(stats.requires, 1);
+        assert_eq!(stats.unresolved_references, 1);
+        assert!(stats.code_gen_visitors > 0);
+        assert!(stats.code_size > 0);
+
+        let modules_stats = ModulesStatsVc::new(
+            AssetsSetVc::cell(IndexSet::from([module.into()])),
+            chunking_context.into(),
+        )
+        .await?;
+        assert_eq!(modules_stats.modules, 1);
+        assert_eq!(modules_stats.total, stats);
+
+        let empty_stats =
+            ModulesStatsVc::new(AssetsSetVc::cell(IndexSet::new()), chunking_context.into())
+                .await?;
+        assert_eq!(empty_stats.modules, 0);
+        assert_eq!(empty_stats.total, ModuleAnalysisStats::default());
+
+        Ok(())
+    })
+    .await
+}
+
+/// Returns the idents of the chunk items of the chunk.
+async fn chunk_item_idents(chunk: EcmascriptChunkVc) -> Result<Vec<String>> {
+    let mut idents = Vec::new();
+    for part in chunk.content_parts().await?.iter() {
+        for item in part.await?.chunk_items.iter() {
+            idents.push(item.asset_ident().to_string().await?.clone_value());
+        }
+    }
+    Ok(idents)
+}
+
+/// Returns the ident of the part of `lib.js` that contains `part`.
+async fn lib_part_ident(
+    context: AssetContextVc,
+    root: FileSystemPathVc,
+    part: ModulePartVc,
+) -> Result<String> {
+    let module = ecmascript_module(context, root.join("lib.js")).await?;
+    Ok(EcmascriptModulePartAssetVc::select_part(module, part)
+        .ident()
+        .to_string()
+        .await?
+        .clone_value())
+}
+
+async fn chunkable_entry(
+    context: AssetContextVc,
+    path: FileSystemPathVc,
+) -> Result<ChunkableModuleVc> {
+    let module = context.process(
+        FileSourceVc::new(path).into(),
+        Value::new(ReferenceType::Entry(EntryReferenceSubType::Undefined)),
+    );
+    ChunkableModuleVc::resolve_from(module)
+        .await?
+        .context("expected a chunkable module")
+}
+
+#[tokio::test]
+async fn tree_shaking_availability() -> Result<()> {
+    register();
+
+    let dir = write_files(&[
+        (
+            "index.js",
+            "import { a } from \"./lib.js\";\n\nconsole.log(a);\n\nimport(\"./other.js\");\n",
+        ),
+        (
+            "other.js",
+            "import { b } from \"./lib.js\";\n\nconsole.log(b);\n",
+        ),
+        (
+            "lib.js",
+            "console.log(\"lib evaluated\");\n\nexport const a = \"a\";\n\nexport const b = \
+             \"b\";\n",
+        ),
+    ]);
+    let root = dir.path().to_string_lossy().to_string();
+
+    run_once(async move {
+        let root = project_root(root);
+
+        let env = node_build_time_env();
+        let context = asset_context(
+            env,
+            ModuleOptionsContext {
+                enable_tree_shaking: true,
+                ..Default::default()
+            }
+            .cell(),
+            ResolveOptionsContext::default().cell(),
+        );
+        let chunking_context: ChunkingContextVc = dev_chunking_context(root, env).build().into();
+
+        let a = lib_part_ident(context, root, ModulePartVc::export("a".to_string())).await?;
+        let b = lib_part_ident(context, root, ModulePartVc::export("b".to_string())).await?;
+        let evaluation = lib_part_ident(context, root, ModulePartVc::module_evaluation()).await?;
+        // Every part has its own ident.
+        assert_ne!(a, b);
+        assert_ne!(a, evaluation);
+        assert_ne!(b, evaluation);
+
+        // `index.js` is loaded first, its chunk contains the parts it imports.
+        let index = chunkable_entry(context, root.join("index.js")).await?;
+        let index_chunk = EcmascriptChunkVc::resolve_from(index.as_root_chunk(chunking_context))
+            .await?
+            .context("expected an ecmascript chunk")?;
+        let index_idents = chunk_item_idents(index_chunk).await?;
+        assert!(index_idents.contains(&a), "{index_idents:#?}");
+        assert!(index_idents.contains(&evaluation), "{index_idents:#?}");
+        assert!(!index_idents.contains(&b), "{index_idents:#?}");
+
+        // `other.js` is loaded by `index.js`. The module evaluation part is
+        // already available, only the part of `b` is added.
+        let other = chunkable_entry(context, root.join("other.js")).await?;
+        let other_chunk = EcmascriptChunkVc::resolve_from(other.as_chunk(
+            chunking_context,
+            Value::new(AvailabilityInfo::Inner {
+                available_assets: AvailableAssetsVc::new(vec![index.into()]),
+                current_availability_root: other.into(),
+            }),
+        ))
+        .await?
+        .context("expected an ecmascript chunk")?;
+        let other_idents = chunk_item_idents(other_chunk).await?;
+        assert!(other_idents.contains(&b), "{other_idents:#?}");
+        assert!(!other_idents.contains(&a), "{other_idents:#?}");
+        assert!(!other_idents.contains(&evaluation), "{other_idents:#?}");
+
+        Ok(())
+    })
+    .await
+}
+
+/// Returns the code of the module content for three different availability
+/// infos.
+async fn contents(
+    module: EcmascriptModuleAssetVc,
+    chunking_context: EcmascriptChunkingContextVc,
+    other: EcmascriptModuleAssetVc,
+) -> Result<Vec<String>> {
+    let availability_infos = [
+        AvailabilityInfo::Untracked,
+        AvailabilityInfo::Root {
+            current_availability_root: module.into(),
+        },
+        AvailabilityInfo::Inner {
+            available_assets: AvailableAssetsVc::new(vec![other.into()]),
+            current_availability_root: module.into(),
+        },
+    ];
+    let mut contents = Vec::new();
+    for availability_info in availability_infos {
+        let content = module
+            .module_content(chunking_context, Value::new(availability_info))
+            .await?;
+        contents.push(content.inner_code.to_str()?.into_owned());
+    }
+    Ok(contents)
+}
+
+/// Returns the number of tasks of the function `name` and the sum of their
+/// executions.
+fn executions(tt: &TurboTasks<MemoryBackend>, name: &str) -> (usize, u32) {
+    let backend = tt.backend();
+    let mut tasks = 0;
+    let mut executions = 0;
+    backend.with_all_cached_tasks(|id| {
+        backend.with_task(id, |task| {
+            let StatsTaskType::Native(function) = task.get_stats_type() else {
+                return;
+            };
+            if registry::get_function(function).name == name {
+                tasks += 1;
+                executions += task.get_stats_info(backend).executions.unwrap_or(0);
+            }
+        })
+    });
+    (tasks, executions)
+}
+
+#[tokio::test]
+async fn module_content_availability() -> Result<()> {
+    register();
+
+    let dir = write_files(&[
+        (
+            "index.js",
+            "import { a } from \"./lib.js\";\n\nconsole.log(a);\n",
+        ),
+        (
+            "dynamic.js",
+            "import { a } from \
+             \"./lib.js\";\n\nconsole.log(a);\nimport(\"./lib.js\").then(console.log);\n",
+        ),
+        ("lib.js", "export const a = \"a\";\n"),
+    ]);
+    let root = dir.path().to_string_lossy().to_string();
+
+    let tt = TurboTasks::new(MemoryBackend::default());
+    tt.set_stats_type(StatsType::Full);
+    let task = tt.spawn_once_task(async move {
+        let root = project_root(root);
+
+        let env = node_build_time_env();
+        let context = default_asset_context(env);
+        let chunking_context: EcmascriptChunkingContextVc =
+            dev_chunking_context(root, env).build().into();
+
+        let index = ecmascript_module(context, root.join("index.js")).await?;
+        let dynamic = ecmascript_module(context, root.join("dynamic.js")).await?;
+
+        // Without code generation that depends on the availability info, the
+        // content is the same for every availability info.
+        let index_contents = contents(index, chunking_context, dynamic).await?;
+        assert_eq!(index_contents[0], index_contents[1]);
+        assert_eq!(index_contents[0], index_contents[2]);
+
+        // The dynamic import is generated for every availability info, in the
+        // same pass as the rest of the module. `lib.js` is available from
+        // `index.js`.
+        let dynamic_contents = contents(dynamic, chunking_context, index).await?;
+        for content in &dynamic_contents {
+            assert!(content.contains("console.log(__TURBOPACK__imported__module__"));
+        }
+        assert!(dynamic_contents[0].contains("(ecmascript, manifest chunk, loader)"));
+        assert!(dynamic_contents[1].contains("(ecmascript, manifest chunk, loader)"));
+        assert!(
+            dynamic_contents[2].contains("Promise.resolve().then(()=>__turbopack_require__("),
+            "{}",
+            dynamic_contents[2]
+        );
+
+        Ok(NothingVc::new().into())
+    });
+    tt.wait_task_completion(task, true).await?;
+
+    // `index.js`, `dynamic.js` and `lib.js` are parsed once each.
+    assert_eq!(executions(&tt, "parse"), (3, 3));
+    // Only `index.js` is emitted without the code generation that depends on
+    // the availability info, once for all availability infos.
+    assert_eq!(
+        executions(
+            &tt,
+            "EcmascriptModuleContentVc::new_availability_independent"
+        ),
+        (1, 1)
+    );
+
+    Ok(())
+}
+
+/// Returns the original file which the first occurrence of `needle` in the
+/// code of `asset` maps to, or `None` when the code doesn't contain it.
+async fn original_file(asset: OutputAssetVc, needle: &str) -> Result<Option<String>> {
+    let content = asset.content().file_content().await?;
+    let Some(file) = content.as_content() else {
+        return Ok(None);
+    };
+    let code = file.content().to_str()?.into_owned();
+    let Some((line, column)) = code
+        .lines()
+        .enumerate()
+        .find_map(|(line, code)| Some((line, code.find(needle)?)))
+    else {
+        return Ok(None);
+    };
+
+    let map = GenerateSourceMapVc::resolve_from(asset)
+        .await?
+        .context("expected the chunk to generate a source map")?
+        .generate_source_map()
+        .await?
+        .context("expected a source map")?;
+    match &*map.lookup_token(line, column).await? {
+        Some(Token::Original(token)) => Ok(Some(token.original_file.clone())),
+        _ => bail!("expected {needle:?} at {line}:{column} to map to an original file"),
+    }
+}
+
+/// Asserts that the source map of `asset`, and the maps of all of its
+/// sections, list every source only once.
+async fn assert_unique_sources(asset: OutputAssetVc) -> Result<()> {
+    fn assert_map(map: &serde_json::Value) {
+        if let Some(sections) = map["sections"].as_array() {
+            for section in sections {
+                assert_map(&section["map"]);
+            }
+            return;
+        }
+        let sources = map["sources"]
+            .as_array()
+            .expect("expected the source map to have sources")
+            .iter()
+            .map(|source| source.as_str().expect("expected the source to be a string"))
+            .collect::<Vec<_>>();
+        let unique = sources.iter().collect::<HashSet<_>>();
+        assert_eq!(unique.len(), sources.len(), "{sources:?}");
+    }
+
+    let Some(generate) = GenerateSourceMapVc::resolve_from(asset).await? else {
+        return Ok(());
+    };
+    let Some(map) = *generate.generate_source_map().await? else {
+        return Ok(());
+    };
+    let map: serde_json::Value = serde_json::from_str(&map.to_rope().await?.to_str()?)?;
+    assert_map(&map);
+    Ok(())
+}
+
+/// Returns `entry` and the output assets it references, directly or
+/// indirectly, except for source maps.
+async fn output_assets(entry: OutputAssetVc) -> Result<Vec<OutputAssetVc>> {
+    let mut assets = vec![entry.resolve().await?];
+    let mut index = 0;
+    while index < assets.len() {
+        for &reference in assets[index].references().await?.iter() {
+            for &asset in reference.resolve_reference().primary_assets().await?.iter() {
+                let Some(asset) = OutputAssetVc::resolve_from(asset).await? else {
+                    continue;
+                };
+                if asset.ident().path().await?.path.ends_with(".map") {
+                    continue;
+                }
+                if !assets.contains(&asset) {
+                    assets.push(asset);
+                }
+            }
+        }
+        index += 1;
+    }
+    Ok(assets)
+}
+
+/// Asserts that a position inside the runtime maps to `runtime_file`, and a
+/// position inside the second module to `second.js`, and that the source maps
+/// of all `assets` list each source once.
+async fn assert_source_maps(assets: &[OutputAssetVc], runtime_file: &str) -> Result<()> {
+    let mut runtime_files = Vec::new();
+    let mut module_files = Vec::new();
+    for &asset in assets {
+        runtime_files.extend(original_file(asset, "function instantiateModule(").await?);
+        module_files.extend(original_file(asset, "\"second module\"").await?);
+        assert_unique_sources(asset).await?;
+    }
+    assert_eq!(runtime_files.len(), 1, "{runtime_files:?}");
+    assert!(
+        runtime_files[0].ends_with(runtime_file),
+        "{runtime_files:?}"
+    );
+    assert_eq!(module_files.len(), 1, "{module_files:?}");
+    assert!(
+        module_files[0].ends_with("[project]/second.js"),
+        "{module_files:?}"
+    );
+    Ok(())
+}
+
+/// Writes an entry `index.js` which imports `first.js` and `second.js`.
+fn write_source_map_files() -> TempDir {
+    write_files(&[
+        (
+            "index.js",
+            "import { first } from \"./first.js\";\nimport { second } from \
+             \"./second.js\";\n\nconsole.log(first(), second());\n",
+        ),
+        (
+            "first.js",
+            "export function first() {\n  return \"first module\";\n}\n",
+        ),
+        (
+            "second.js",
+            "export function second() {\n  return \"second module\";\n}\n",
+        ),
+    ])
+}
+
+#[tokio::test]
+async fn evaluated_source_map() -> Result<()> {
+    register();
+
+    let dir = write_source_map_files();
+    let root = dir.path().to_string_lossy().to_string();
+
+    run_once(async move {
+        let root = project_root(root);
+
+        let env = browser_env(true);
+        let context = default_asset_context(env);
+        let chunking_context: ChunkingContextVc = dev_chunking_context(root, env).build().into();
+
+        let module = entry_module(context, root.join("index.js")).await?;
+        let assets = chunking_context.evaluated_chunk_group(
+            module.as_root_chunk(chunking_context),
+            EvaluatableAssetsVc::empty().with_entry(module.into()),
+        );
+
+        // The evaluated chunk concatenates the runtime with its prologue, and
+        // the entry chunk the chunk items of all modules.
+        assert_source_maps(&assets.await?, "/dev/runtime/base/runtime-base.ts").await?;
+
+        Ok(())
+    })
+    .await
+}
+
+#[tokio::test]
+async fn build_entry_source_map() -> Result<()> {
+    register();
+    turbopack_build::register();
+
+    let dir = write_source_map_files();
+    let root = dir.path().to_string_lossy().to_string();
+
+    run_once(async move {
+        let root = project_root(root);
+
+        let env = node_lambda_env();
+        let context = default_asset_context(env);
+        let chunking_context = BuildChunkingContextVc::builder(
+            root,
+            root.join("output"),
+            root.join("output"),
+            root.join("static"),
+            env,
+        )
+        .build();
+
+        let module = entry_module(context, root.join("index.js")).await?;
+        let entry = chunking_context.entry_chunk(
+            root.join("output/entry.js"),
+            module.into(),
+            EvaluatableAssetsVc::empty(),
+        );
+
+        // The entry chunk loads the runtime chunk, which concatenates the
+        // runtime code, and a chunk with the chunk items of all modules.
+        assert_source_maps(&output_assets(entry).await?, "/build/runtime.ts").await?;
+
+        Ok(())
+    })
+    .await
+}
+
+/// Both limits of the size limit test, in bytes.
+const MAX_BYTES: usize = 200;
+
+const LARGE: &str = r#"// Generated data. The module is larger than the limits of the test, so it is
+// neither evaluated by the analysis nor parsed for the code generation.
+const dep = require("./dep");
+
+module.exports = {
+  dep,
+  values: [
+    0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20,
+    21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32, 33, 34, 35, 36, 37, 38, 39,
+  ],
+};
+"#;
+
+const LARGE_ESM: &str = r#"// The module is larger than the limits of the test, but its import
+// declaration is still followed.
+import dep from "./dep";
+
+export const values = [
+  0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21,
+  22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32, 33, 34, 35, 36, 37, 38, 39, dep,
+];
+"#;
+
+struct LimitedModule {
+    requires: usize,
+    static_imports: usize,
+    issue_titles: Vec<String>,
+    code: String,
+    free_require: bool,
+    source: String,
+}
+
+/// Analyzes and generates the code of the module at `path`.
+async fn limited_module(
+    context: AssetContextVc,
+    chunking_context: EcmascriptChunkingContextVc,
+    path: FileSystemPathVc,
+) -> Result<LimitedModule> {
+    let source = FileSourceVc::new(path);
+    let module = entry_module(context, path).await?;
+
+    let stats = *module.analysis_stats(chunking_context).await?;
+    let issue_titles = IssueVc::peek_issues_with_path(module.analyze())
+        .await?
+        .await?
+        .get_plain_issues()
+        .await?
+        .iter()
+        .map(|issue| issue.title.clone())
+        .collect();
+    let content = module
+        .module_content(chunking_context, Value::new(AvailabilityInfo::Untracked))
+        .await?;
+    let code = content.inner_code.to_str()?.into_owned();
+    let source = source
+        .content()
+        .file_content()
+        .await?
+        .as_content()
+        .context("expected a file")?
+        .content()
+        .to_str()?
+        .into_owned();
+
+    Ok(LimitedModule {
+        requires: stats.requires,
+        static_imports: stats.static_imports,
+        issue_titles,
+        code,
+        free_require: content.free_require,
+        source,
+    })
+}
+
+#[tokio::test]
+async fn size_limit() -> Result<()> {
+    register();
+
+    let dir = write_files(&[
+        (
+            "small.js",
+            "const dep = require(\"./dep\");\n\nmodule.exports = { dep };\n",
+        ),
+        ("dep.js", "module.exports = \"dep\";\n"),
+        ("large.js", LARGE),
+        ("large_esm.js", LARGE_ESM),
+    ]);
+    let root = dir.path().to_string_lossy().to_string();
+
+    run_once(async move {
+        let root = project_root(root);
+
+        let env = node_build_time_env();
+        let context = asset_context(
+            env,
+            ModuleOptionsContext {
+                ecmascript_max_analysis_bytes: Some(MAX_BYTES),
+                ecmascript_max_codegen_bytes: Some(MAX_BYTES),
+                ..Default::default()
+            }
+            .cell(),
+            ResolveOptionsContext::default().cell(),
+        );
+        let chunking_context: EcmascriptChunkingContextVc =
+            dev_chunking_context(root, env).build().into();
+
+        // A module below the limits is analyzed and generated as usual.
+        let small = limited_module(context, chunking_context, root.join("small.js")).await?;
+        assert!(small.source.len() <= MAX_BYTES);
+        assert_eq!(small.requires, 1);
+        assert!(small.issue_titles.is_empty(), "{:?}", small.issue_titles);
+        assert_ne!(small.code, small.source);
+
+        // The `require` of a large module isn't followed, which is reported,
+        // and its source is emitted as is. Its `require` calls load modules
+        // with the runtime, as the module factory binds `require` to it.
+        let large = limited_module(context, chunking_context, root.join("large.js")).await?;
+        assert!(large.source.len() > MAX_BYTES);
+        assert_eq!(large.requires, 0);
+        assert_eq!(large.issue_titles.len(), 1);
+        assert!(
+            large.issue_titles[0].contains("too large to be analyzed"),
+            "{}",
+            large.issue_titles[0]
+        );
+        assert_eq!(large.code, large.source);
+        assert!(large.free_require);
+
+        // The import declaration of a large ES module is still followed, and
+        // its code is generated as usual.
+        let large_esm =
+            limited_module(context, chunking_context, root.join("large_esm.js")).await?;
+        assert!(large_esm.source.len() > MAX_BYTES);
+        assert_eq!(large_esm.static_imports, 1);
+        assert_eq!(large_esm.issue_titles.len(), 1);
+        assert!(!large_esm.code.contains("import dep"), "{}", large_esm.code);
+
+        Ok(())
+    })
+    .await
+}
+
+/// Returns the code of the module and the requests of its references which
+/// are placed in the chunk.
+async fn module_code(root: FileSystemPathVc, file: &str) -> Result<(String, Vec<String>)> {
+    let env = node_build_time_env();
+    let context = asset_context(
+        env,
+        ModuleOptionsContext {
+            ecmascript_inline_json_bytes: Some(32),
+            ..Default::default()
+        }
+        .cell(),
+        ResolveOptionsContext::default().cell(),
+    );
+    let chunking_context = dev_chunking_context(root, env).build();
+
+    let module = context.process(
+        FileSourceVc::new(root.join(file)).into(),
+        Value::new(ReferenceType::Entry(EntryReferenceSubType::Undefined)),
+    );
+    let module = EcmascriptChunkPlaceableVc::resolve_from(module)
+        .await?
+        .context("expected an ecmascript module")?;
+    let chunk_item = module.as_chunk_item(chunking_context.into());
+
+    let mut chunked = Vec::new();
+    for reference in chunk_item.references().await?.iter() {
+        let Some(reference) = ChunkableModuleReferenceVc::resolve_from(reference).await? else {
+            continue;
+        };
+        if reference.chunking_type().await?.is_some() {
+            chunked.push(reference.to_string().await?.to_string());
+        }
+    }
+
+    let content = chunk_item.content().await?;
+    Ok((content.inner_code.to_str()?.into_owned(), chunked))
+}
+
+#[tokio::test]
+async fn inline_json() -> Result<()> {
+    register();
+
+    let dir = write_files(&[
+        (
+            "index.js",
+            r#"import small from "./small.json";
+import large from "./large.json";
+import { other } from "./other.js";
+
+console.log(small, large, other);
+"#,
+        ),
+        (
+            "other.js",
+            "import small from \"./small.json\";\n\nexport const other = small.enabled;\n",
+        ),
+        ("small.json", "{ \"enabled\": true }\n"),
+        (
+            "large.json",
+            r#"{ "messages": ["one", "two", "three", "four", "five", "six", "seven"] }
+"#,
+        ),
+    ]);
+    let root = dir.path().to_string_lossy().to_string();
+
+    run_once(async move {
+        let root = project_root(root);
+
+        let (code, chunked) = module_code(root, "index.js").await?;
+        // The small file is inlined, the large one is imported.
+        assert!(
+            code.contains(r#"JSON.parse("{\"enabled\":true}")"#),
+            "{code}"
+        );
+        assert!(!code.contains("messages"), "{code}");
+        assert!(
+            chunked.iter().any(|request| request.contains("large.json")),
+            "{chunked:?}"
+        );
+        assert!(
+            !chunked.iter().any(|request| request.contains("small.json")),
+            "{chunked:?}"
+        );
+
+        // Every importer gets its own copy of the value, so the module isn't
+        // placed in the chunk by any of them.
+        let (code, chunked) = module_code(root, "other.js").await?;
+        assert!(
+            code.contains(r#"JSON.parse("{\"enabled\":true}")"#),
+            "{code}"
+        );
+        assert!(chunked.is_empty(), "{chunked:?}");
+
+        Ok(())
+    })
+    .await
+}
+
+#[tokio::test]
+async fn evaluation_order() -> Result<()> {
+    register();
+
+    let dir = write_files(&[
+        (
+            "index.js",
+            r#"import { a } from "./a.js";
+import { b } from "./b.js";
+import { data } from "./tla.js";
+
+console.log(a, b, data);
+"#,
+        ),
+        (
+            "a.js",
+            "import { c } from \"./c.js\";\n\nexport const a = `a${c}`;\n",
+        ),
+        (
+            "b.js",
+            r#"import { a } from "./a.js";
+import { c } from "./c.js";
+import "./index.js";
+
+export const b = `b${a}${c}`;
+"#,
+        ),
+        ("c.js", "export const c = \"c\";\n"),
+        (
+            "tla.js",
+            "export const data = await Promise.resolve(\"data\");\n",
+        ),
+    ]);
+    let root = dir.path().to_string_lossy().to_string();
+
+    run_once(async move {
+        let root = project_root(root);
+
+        let env = node_build_time_env();
+        let context = default_asset_context(env);
+        let chunking_context = dev_chunking_context(root, env).build();
+
+        let module = context.process(
+            FileSourceVc::new(root.join("index.js")).into(),
+            Value::new(ReferenceType::Entry(EntryReferenceSubType::Undefined)),
+        );
+        let module = EcmascriptChunkPlaceableVc::resolve_from(module)
+            .await?
+            .context("expected an ecmascript module")?;
+
+        let chunk = EcmascriptChunkVc::new_root(chunking_context.into(), module);
+        let order = chunk.evaluation_order().await?;
+        let order: Vec<_> = order
+            .iter()
+            .map(|ident| ident.strip_prefix("[project]/").unwrap_or(ident))
+            .collect();
+
+        // `b.js` imports `index.js` while it is being evaluated, and `a.js` and
+        // `c.js` after they were evaluated, so none of them is evaluated again.
+        // The entry is async since it imports a module with top-level await.
+        assert_eq!(
+            order,
+            vec![
+                "c.js (ecmascript)",
+                "a.js (ecmascript)",
+                "b.js (ecmascript)",
+                "tla.js (ecmascript) (async)",
+                "index.js (ecmascript) (async)",
+            ]
+        );
+
+        Ok(())
+    })
+    .await
+}
+
+#[tokio::test]
+async fn empty_module() -> Result<()> {
+    register();
+
+    let dir = write_files(&[("empty.js", ""), ("empty.mjs", ""), ("empty.cjs", "")]);
+    let root = dir.path().to_string_lossy().to_string();
+
+    run_once(async move {
+        let root = project_root(root);
+
+        let env = node_build_time_env();
+        let context = default_asset_context(env);
+        let chunking_context: EcmascriptChunkingContextVc =
+            dev_chunking_context(root, env).build().into();
+
+        // Zero-byte modules are ESM, unless they're specified to be CommonJS.
+        for (file, is_esm) in [
+            ("empty.js", true),
+            ("empty.mjs", true),
+            ("empty.cjs", false),
+        ] {
+            let module = ecmascript_module(context, root.join(file)).await?;
+
+            let content = module
+                .module_content(chunking_context, Value::new(AvailabilityInfo::Untracked))
+                .await?;
+            assert!(content.inner_code.is_empty(), "{file} has content");
+            assert_eq!(content.is_esm, is_esm, "{file}");
+
+            let exports = module.get_exports().await?;
+            if is_esm {
+                assert!(
+                    matches!(*exports, EcmascriptExports::EsmExports(_)),
+                    "{file} should have ESM exports"
+                );
+            } else {
+                assert!(
+                    matches!(*exports, EcmascriptExports::CommonJs),
+                    "{file} should have CommonJS exports"
+                );
+            }
+        }
+
+        Ok(())
+    })
+    .await
+}
+
+/// A WebAssembly module exporting `add(a: i32, b: i32) -> i32`.
+const ADD_WASM: &[u8] = &[
+    0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, 0x01, 0x07, 0x01, 0x60, 0x02, 0x7f, 0x7f, 0x01,
+    0x7f, 0x03, 0x02, 0x01, 0x00, 0x07, 0x07, 0x01, 0x03, 0x61, 0x64, 0x64, 0x00, 0x00, 0x0a, 0x09,
+    0x01, 0x07, 0x00, 0x20, 0x00, 0x20, 0x01, 0x6a, 0x0b,
+];
+
+/// A WebAssembly module importing `log` from `./math.js`.
+const IMPORTS_WASM: &[u8] = &[
+    0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, 0x01, 0x04, 0x01, 0x60, 0x00, 0x00, 0x02, 0x11,
+    0x01, 0x09, 0x2e, 0x2f, 0x6d, 0x61, 0x74, 0x68, 0x2e, 0x6a, 0x73, 0x03, 0x6c, 0x6f, 0x67, 0x00,
+    0x00,
+];
+
+/// Processes the file at `path` as an asset which isn't referenced by another
+/// one and expects an ecmascript chunk placeable.
+async fn placeable(
+    context: AssetContextVc,
+    path: FileSystemPathVc,
+) -> Result<EcmascriptChunkPlaceableVc> {
+    let module = context.process(
+        FileSourceVc::new(path).into(),
+        Value::new(ReferenceType::Undefined),
+    );
+    EcmascriptChunkPlaceableVc::resolve_from(module)
+        .await?
+        .context("expected an ecmascript chunk placeable")
+}
+
+#[tokio::test]
+async fn wasm() -> Result<()> {
+    register();
+
+    let dir = write_files(&[(
+        "index.js",
+        "import { add } from \"./add.wasm\";\n\nconsole.log(add(1, 2));\n",
+    )]);
+    fs::write(dir.path().join("add.wasm"), ADD_WASM)?;
+    fs::write(dir.path().join("imports.wasm"), IMPORTS_WASM)?;
+    let root = dir.path().to_string_lossy().to_string();
+
+    run_once(async move {
+        let root = project_root(root);
+
+        for (env, instantiate) in [
+            (
+                browser_env(true),
+                "WebAssembly.instantiateStreaming(fetch(\"/output/static/add.",
+            ),
+            (
+                node_lambda_env(),
+                "readFile(path.join(path.dirname(__filename), \"./static/add.",
+            ),
+        ] {
+            let context = default_asset_context(env);
+            let chunking_context: EcmascriptChunkingContextVc = DevChunkingContextVc::builder(
+                root,
+                root.join("output"),
+                root.join("output"),
+                root.join("output/static"),
+                env,
+            )
+            .build()
+            .into();
+
+            // The exports are read from the export section of the module.
+            let wasm = placeable(context, root.join("add.wasm")).await?;
+            let EcmascriptExports::EsmExports(exports) = &*wasm.get_exports().await? else {
+                bail!("expected the WebAssembly module to have ESM exports");
+            };
+            let exports = exports.await?;
+            assert_eq!(exports.exports.keys().collect::<Vec<_>>(), vec!["add"]);
+
+            let content = wasm.as_chunk_item(chunking_context).content().await?;
+            let code = content.inner_code.to_str()?;
+            assert!(content.options.async_module);
+            assert!(
+                code.contains("__turbopack_esm__({\n    \"add\": () => wasmExports[\"add\"],\n});"),
+                "{code}"
+            );
+            assert!(code.contains(instantiate), "{code}");
+
+            // Modules importing the WebAssembly module wait for it to be
+            // instantiated.
+            let index = placeable(context, root.join("index.js")).await?;
+            let content = index.as_chunk_item(chunking_context).content().await?;
+            assert!(content.options.async_module);
+
+            // WebAssembly modules which import JavaScript aren't supported.
+            let imports = placeable(context, root.join("imports.wasm")).await?;
+            let Err(error) = imports.as_chunk_item(chunking_context).content().await else {
+                bail!("expected WebAssembly modules importing JavaScript to fail");
+            };
+            assert!(
+                format!("{error:?}")
+                    .contains("WebAssembly modules which import JavaScript are not supported yet"),
+                "{error:?}"
+            );
+        }
+
+        Ok(())
+    })
+    .await
+}
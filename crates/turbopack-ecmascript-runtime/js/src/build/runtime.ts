@@ -29,17 +29,20 @@ interface RequireContextEntry {
 }
 
 type ExternalRequire = (id: ModuleId) => Exports | EsmNamespaceObject;
+type ResolveAbsolutePath = (modulePath?: string) => string;
 
 interface TurbopackNodeBuildContext {
   e: Module["exports"];
   r: CommonJsRequire;
   x: ExternalRequire;
+  P: ResolveAbsolutePath;
   f: RequireContextFactory;
   i: EsmImport;
   s: EsmExport;
   j: typeof dynamicExport;
   v: ExportValue;
   n: typeof exportNamespace;
+  a: AsyncModule;
   m: Module;
   c: ModuleCache;
   l: LoadChunk;
@@ -56,6 +59,18 @@ const path = require("path");
 const relativePathToRuntimeRoot = path.relative(RUNTIME_PUBLIC_PATH, ".");
 const RUNTIME_ROOT = path.resolve(__filename, relativePathToRuntimeRoot);
 
+/**
+ * Returns the absolute path of a path relative to the output root, or of the
+ * output root itself. Used for values which depend on where the output is
+ * located at runtime, e.g. `import.meta.resolve(…)` of emitted assets.
+ */
+function resolveAbsolutePath(modulePath?: string): string {
+  if (modulePath) {
+    return path.join(RUNTIME_ROOT, modulePath);
+  }
+  return RUNTIME_ROOT;
+}
+
 const moduleFactories: ModuleFactories = Object.create(null);
 const moduleCache: ModuleCache = Object.create(null);
 
@@ -179,12 +194,14 @@ function instantiateModule(id: ModuleId, source: SourceInfo): Module {
       e: module.exports,
       r: commonJsRequire.bind(null, module),
       x: externalRequire,
+      P: resolveAbsolutePath,
       f: requireContext.bind(null, module),
       i: esmImport.bind(null, module),
       s: esm.bind(null, module.exports),
       j: dynamicExport.bind(null, module),
       v: exportValue.bind(null, module),
       n: exportNamespace.bind(null, module),
+      a: asyncModule.bind(null, module),
       m: module,
       c: moduleCache,
       l: loadChunkAsync.bind(null, { type: SourceType.Parent, parentId: id }),
@@ -42,6 +42,7 @@ interface TurbopackDevBaseContext {
   j: typeof dynamicExport;
   v: ExportValue;
   n: typeof exportNamespace;
+  a: AsyncModule;
   m: Module;
   c: ModuleCache;
   l: LoadChunk;
@@ -341,6 +342,7 @@ function instantiateModule(id: ModuleId, source: SourceInfo): Module {
           j: dynamicExport.bind(null, module),
           v: exportValue.bind(null, module),
           n: exportNamespace.bind(null, module),
+          a: asyncModule.bind(null, module),
           m: module,
           c: moduleCache,
           l: loadChunk.bind(null, { type: SourceType.Parent, parentId: id }),
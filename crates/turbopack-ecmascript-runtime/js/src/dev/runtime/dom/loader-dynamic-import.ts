@@ -0,0 +1,15 @@
+/**
+ * This file contains the chunk loader of the Turbopack development ECMAScript
+ * DOM runtime which loads JS chunks with `import()`, e.g. in Electron renderers
+ * with node integration.
+ *
+ * It will be appended to the DOM runtime backend code.
+ */
+
+/// <reference path="./runtime-backend-dom.ts" />
+
+loadScriptChunk = (chunkUrl, onError) => {
+  // The chunk registers itself when it's evaluated, the namespace object is
+  // not used.
+  import(chunkUrl).catch(onError);
+};
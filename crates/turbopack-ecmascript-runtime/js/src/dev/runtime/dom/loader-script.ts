@@ -0,0 +1,15 @@
+/**
+ * This file contains the chunk loader of the Turbopack development ECMAScript
+ * DOM runtime which loads JS chunks with `<script>` tags.
+ *
+ * It will be appended to the DOM runtime backend code.
+ */
+
+/// <reference path="./runtime-backend-dom.ts" />
+
+loadScriptChunk = (chunkUrl, onError) => {
+  const script = document.createElement("script");
+  script.src = chunkUrl;
+  script.onerror = onError;
+  document.body.appendChild(script);
+};
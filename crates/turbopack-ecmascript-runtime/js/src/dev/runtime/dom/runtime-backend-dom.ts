@@ -16,6 +16,13 @@ type ChunkResolver = {
 
 let BACKEND: RuntimeBackend;
 
+/**
+ * Loads a JS chunk from the given url. This is assigned by the chunk loader
+ * code (e.g. `loader-script.ts`) which is appended after this file, depending
+ * on the chunk loading strategy of the chunking context.
+ */
+let loadScriptChunk: (chunkUrl: string, onError: () => void) => void;
+
 function augmentContext(context: TurbopackDevBaseContext): TurbopackDevContext {
   return context;
 }
@@ -212,15 +219,12 @@ function commonJsRequireContext(
       };
       document.body.appendChild(link);
     } else if (chunkPath.endsWith(".js")) {
-      const script = document.createElement("script");
-      script.src = chunkUrl;
       // We'll only mark the chunk as loaded once the script has been executed,
       // which happens in `registerChunk`. Hence the absence of `resolve()` in
       // this branch.
-      script.onerror = () => {
+      loadScriptChunk(chunkUrl, () => {
         resolver.reject();
-      };
-      document.body.appendChild(script);
+      });
     } else {
       throw new Error(`can't infer type of chunk from path ${chunkPath}`);
     }
@@ -13,11 +13,19 @@ interface RequireContextEntry {
 }
 
 type ExternalRequire = (id: ModuleId) => Exports | EsmNamespaceObject;
+type ResolveAbsolutePath = (modulePath?: string) => string;
 
 interface TurbopackDevContext {
   x: ExternalRequire;
+  P: ResolveAbsolutePath;
 }
 
+/**
+ * The absolute path of the output root. It's derived from the path of the
+ * first evaluated chunk, which contains this runtime.
+ */
+let OUTPUT_ROOT: string | undefined;
+
 function commonJsRequireContext(
   entry: RequireContextEntry,
   sourceModule: Module
@@ -59,9 +67,25 @@ externalRequire.resolve = (
   return require.resolve(id, options);
 };
 
+/**
+ * Returns the absolute path of a path relative to the output root, or of the
+ * output root itself. Used for values which depend on where the output is
+ * located at runtime, e.g. `import.meta.resolve(…)` of emitted assets.
+ */
+function resolveAbsolutePath(modulePath?: string): string {
+  if (OUTPUT_ROOT == null) {
+    throw new Error("the output root is only known once a chunk is evaluated");
+  }
+  if (modulePath) {
+    return require("path").join(OUTPUT_ROOT, modulePath);
+  }
+  return OUTPUT_ROOT;
+}
+
 function augmentContext(context: TurbopackDevBaseContext): TurbopackDevContext {
   const nodejsContext = context as TurbopackDevContext;
   nodejsContext.x = externalRequire;
+  nodejsContext.P = resolveAbsolutePath;
   return nodejsContext;
 }
 
@@ -74,6 +98,15 @@ let BACKEND: RuntimeBackend;
         return;
       }
 
+      if (OUTPUT_ROOT == null) {
+        // Chunks are loaded relative to this file, see `loadChunk`.
+        const path = require("path");
+        OUTPUT_ROOT = path.resolve(
+          __dirname,
+          path.relative(path.dirname(chunkPath), ".")
+        );
+      }
+
       if (params.runtimeModuleIds.length > 0) {
         for (const otherChunkData of params.otherChunks) {
           loadChunk(getChunkPath(otherChunkData), {
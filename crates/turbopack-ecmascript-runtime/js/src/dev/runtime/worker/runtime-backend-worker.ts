@@ -0,0 +1,78 @@
+/**
+ * This file contains the runtime code specific to the Turbopack development
+ * ECMAScript web worker runtime, which loads chunks with `importScripts`.
+ *
+ * It will be appended to the base development runtime code.
+ */
+
+/// <reference path="../base/runtime-base.ts" />
+
+let BACKEND: RuntimeBackend;
+
+function augmentContext(context: TurbopackDevBaseContext): TurbopackDevContext {
+  return context;
+}
+
+function commonJsRequireContext(
+  entry: RequireContextEntry,
+  sourceModule: Module
+): Exports {
+  return commonJsRequire(sourceModule, entry.id());
+}
+
+(() => {
+  BACKEND = {
+    registerChunk(chunkPath, params) {
+      loadedChunks.add(chunkPath);
+
+      if (params == null) {
+        return;
+      }
+
+      // `importScripts` is synchronous, so the other chunks are registered
+      // once they are loaded.
+      for (const otherChunkData of params.otherChunks) {
+        loadChunk(getChunkPath(otherChunkData));
+      }
+
+      for (const moduleId of params.runtimeModuleIds) {
+        getOrInstantiateRuntimeModule(moduleId, chunkPath);
+      }
+    },
+
+    async loadChunk(chunkPath) {
+      loadChunk(chunkPath);
+    },
+
+    unloadChunk(chunkPath) {
+      loadedChunks.delete(chunkPath);
+    },
+
+    restart: () => {
+      throw new Error("restart is not supported in web workers");
+    },
+  };
+
+  const loadedChunks: Set<ChunkPath> = new Set();
+
+  function loadChunk(chunkPath: ChunkPath) {
+    if (!chunkPath.endsWith(".js")) {
+      // Styles can't be applied without a DOM.
+      // This branch can be hit when trying to load a CSS chunk.
+      return;
+    }
+
+    if (loadedChunks.has(chunkPath)) {
+      return;
+    }
+
+    // The chunk is marked as loaded in `registerChunk` when it's evaluated.
+    importScripts(`/${getChunkRelativeUrl(chunkPath)}`);
+  }
+})();
+
+function _eval({ code, url, map }: EcmascriptModuleEntry): ModuleFactory {
+  code += `\n\n//# sourceURL=${location.origin}/${url}`;
+  if (map) code += `\n//# sourceMappingURL=${map}`;
+  return eval(code);
+}
@@ -28,6 +28,7 @@ type EsmImport = (
 ) => EsmNamespaceObject;
 type EsmExport = (exportGetters: Record<string, () => any>) => void;
 type ExportValue = (value: any) => void;
+type AsyncModule = (body: AsyncModuleBody) => void;
 
 type LoadChunk = (chunkPath: ChunkPath) => Promise<any> | undefined;
 
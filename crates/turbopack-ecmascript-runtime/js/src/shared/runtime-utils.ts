@@ -17,6 +17,7 @@ interface Exports {
 type EsmNamespaceObject = Record<string, any>;
 
 const REEXPORTED_OBJECTS = Symbol("reexported objects");
+const ASYNC_MODULE_STATE = Symbol("async module state");
 
 interface BaseModule {
   exports: Exports;
@@ -27,6 +28,12 @@ interface BaseModule {
   parents: ModuleId[];
   namespaceObject?: EsmNamespaceObject;
   [REEXPORTED_OBJECTS]?: any[];
+  /**
+   * Settles once an async module and the async modules it imports have been
+   * evaluated.
+   */
+  async?: Promise<void>;
+  [ASYNC_MODULE_STATE]?: AsyncModuleState;
 }
 
 interface Module extends BaseModule {}
@@ -184,6 +191,96 @@ function commonJsRequire(sourceModule: Module, id: ModuleId): Exports {
   return module.exports;
 }
 
+interface AsyncModuleState {
+  /**
+   * `true` while the module is synchronously evaluating its imports, i.e. until
+   * it awaits for the first time.
+   */
+  importing: boolean;
+  settled: boolean;
+  /**
+   * The async modules this module is waiting for.
+   */
+  dependencies: Module[];
+}
+
+type AsyncModuleBody = (
+  waitForAsyncDependencies: (ids: ModuleId[]) => Promise<void>
+) => Promise<void>;
+
+/**
+ * Evaluates the body of a module which has a top-level `await` or imports
+ * async modules. The body waits for the async modules it imports before
+ * evaluating the rest of the module, and `module.async` settles once the body
+ * has been evaluated.
+ */
+function asyncModule(module: Module, body: AsyncModuleBody) {
+  const state: AsyncModuleState = {
+    importing: true,
+    settled: false,
+    dependencies: [],
+  };
+  module[ASYNC_MODULE_STATE] = state;
+
+  const promise = body((ids) => waitForAsyncDependencies(module, ids));
+  state.importing = false;
+
+  module.async = promise.then(
+    () => {
+      state.settled = true;
+    },
+    (error) => {
+      state.settled = true;
+      module.error = error;
+      throw error;
+    }
+  );
+}
+
+/**
+ * Waits for the imported async modules which are still evaluating.
+ *
+ * Like with ESM evaluation, modules in an import cycle don't wait for the
+ * modules that are still importing further up in the cycle, so they can't
+ * deadlock.
+ */
+function waitForAsyncDependencies(
+  module: Module,
+  ids: ModuleId[]
+): Promise<void> {
+  const state = module[ASYNC_MODULE_STATE]!;
+  const promises = [];
+  for (const id of ids) {
+    const dependency = moduleCache[id];
+    const dependencyState = dependency?.[ASYNC_MODULE_STATE];
+    if (
+      dependencyState == null ||
+      dependencyState.settled ||
+      dependencyState.importing ||
+      isWaitingFor(dependency, module, new Set())
+    ) {
+      continue;
+    }
+    state.dependencies.push(dependency);
+    promises.push(dependency.async);
+  }
+  return Promise.all(promises).then(() => {});
+}
+
+/**
+ * Returns whether `module` is waiting for `target`, directly or transitively.
+ */
+function isWaitingFor(module: Module, target: Module, visited: Set<Module>) {
+  if (module === target) return true;
+  if (visited.has(module)) return false;
+  visited.add(module);
+  const state = module[ASYNC_MODULE_STATE];
+  if (state == null || state.settled) return false;
+  return state.dependencies.some((dependency) =>
+    isWaitingFor(dependency, target, visited)
+  );
+}
+
 type RequireContextFactory = (map: RequireContextMap) => RequireContext;
 
 function requireContext(
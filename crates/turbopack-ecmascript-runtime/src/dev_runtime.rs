@@ -6,16 +6,18 @@ use turbo_tasks::primitives::OptionStringVc;
 use turbopack_core::{
     code_builder::{CodeBuilder, CodeVc},
     context::AssetContext,
-    environment::{ChunkLoading, EnvironmentVc},
+    environment::{ChunkLoading, ChunkLoadingVc, EnvironmentVc},
 };
 use turbopack_ecmascript::{utils::StringifyJs, StaticEcmascriptCodeVc};
 
 use crate::{asset_context::get_runtime_asset_context, embed_file_path};
 
-/// Returns the code for the development ECMAScript runtime.
+/// Returns the code for the development ECMAScript runtime, which loads async
+/// chunks with `chunk_loading`.
 #[turbo_tasks::function]
 pub async fn get_dev_runtime_code(
     environment: EnvironmentVc,
+    chunk_loading: ChunkLoadingVc,
     chunk_base_path: OptionStringVc,
 ) -> Result<CodeVc> {
     let asset_context = get_runtime_asset_context(environment);
@@ -30,20 +32,7 @@ pub async fn get_dev_runtime_code(
     )
     .code();
 
-    let runtime_backend_code = StaticEcmascriptCodeVc::new(
-        asset_context,
-        match &*asset_context
-            .compile_time_info()
-            .environment()
-            .chunk_loading()
-            .await?
-        {
-            ChunkLoading::None => embed_file_path("dev/runtime/none/runtime-backend-none.ts"),
-            ChunkLoading::NodeJs => embed_file_path("dev/runtime/nodejs/runtime-backend-nodejs.ts"),
-            ChunkLoading::Dom => embed_file_path("dev/runtime/dom/runtime-backend-dom.ts"),
-        },
-    )
-    .code();
+    let runtime_backend_code = get_dev_runtime_backend_code(environment, chunk_loading);
 
     let mut code: CodeBuilder = CodeBuilder::default();
 
@@ -82,3 +71,49 @@ pub async fn get_dev_runtime_code(
 
     Ok(CodeVc::cell(code.build()))
 }
+
+/// Returns the code of the development ECMAScript runtime which is specific to
+/// `chunk_loading`, i.e. the runtime backend and its chunk loader.
+#[turbo_tasks::function]
+pub async fn get_dev_runtime_backend_code(
+    environment: EnvironmentVc,
+    chunk_loading: ChunkLoadingVc,
+) -> Result<CodeVc> {
+    let asset_context = get_runtime_asset_context(environment);
+
+    let chunk_loading = *chunk_loading.await?;
+    let runtime_backend_code = StaticEcmascriptCodeVc::new(
+        asset_context,
+        match chunk_loading {
+            ChunkLoading::None => embed_file_path("dev/runtime/none/runtime-backend-none.ts"),
+            ChunkLoading::NodeRequire => {
+                embed_file_path("dev/runtime/nodejs/runtime-backend-nodejs.ts")
+            }
+            ChunkLoading::DomScript | ChunkLoading::DynamicImport => {
+                embed_file_path("dev/runtime/dom/runtime-backend-dom.ts")
+            }
+            ChunkLoading::ImportScripts => {
+                embed_file_path("dev/runtime/worker/runtime-backend-worker.ts")
+            }
+        },
+    )
+    .code();
+
+    // The DOM backend loads JS chunks with the loader of the strategy.
+    let chunk_loader_code = match chunk_loading {
+        ChunkLoading::DomScript => Some(embed_file_path("dev/runtime/dom/loader-script.ts")),
+        ChunkLoading::DynamicImport => {
+            Some(embed_file_path("dev/runtime/dom/loader-dynamic-import.ts"))
+        }
+        _ => None,
+    }
+    .map(|path| StaticEcmascriptCodeVc::new(asset_context, path).code());
+
+    let mut code: CodeBuilder = CodeBuilder::default();
+    code.push_code(&*runtime_backend_code.await?);
+    if let Some(chunk_loader_code) = chunk_loader_code {
+        code.push_code(&*chunk_loader_code.await?);
+    }
+
+    Ok(CodeVc::cell(code.build()))
+}
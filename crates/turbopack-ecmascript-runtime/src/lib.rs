@@ -7,7 +7,7 @@ pub(crate) mod embed_js;
 pub(crate) mod runtime_type;
 
 pub use build_runtime::get_build_runtime_code;
-pub use dev_runtime::get_dev_runtime_code;
+pub use dev_runtime::{get_dev_runtime_backend_code, get_dev_runtime_code};
 #[cfg(feature = "test")]
 pub use dummy_runtime::get_dummy_runtime_code;
 pub use embed_js::{embed_file, embed_file_path, embed_fs};
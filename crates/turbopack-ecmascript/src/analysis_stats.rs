@@ -0,0 +1,126 @@
+use std::ops::AddAssign;
+
+use anyhow::Result;
+use turbo_tasks::Value;
+use turbopack_core::{
+    asset::AssetsSetVc, chunk::availability_info::AvailabilityInfo, reference::AssetReference,
+};
+
+use crate::{
+    chunk::EcmascriptChunkingContextVc,
+    module_code_generations,
+    references::{
+        cjs::{CjsAssetReferenceVc, CjsRequireAssetReferenceVc},
+        esm::{EsmAssetReferenceVc, EsmAsyncAssetReferenceVc},
+    },
+    EcmascriptModuleAssetVc,
+};
+
+/// A summary of the analysis and code generation of a single module.
+#[turbo_tasks::value(shared)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ModuleAnalysisStats {
+    /// `import` statements and re-exports
+    pub static_imports: usize,
+    /// `import()` expressions
+    pub dynamic_imports: usize,
+    /// `require()` calls
+    pub requires: usize,
+    /// references that couldn't be resolved
+    pub unresolved_references: usize,
+    /// visitors that are applied to the module during code generation
+    pub code_gen_visitors: usize,
+    /// size of the generated code in bytes
+    pub code_size: usize,
+}
+
+impl AddAssign for ModuleAnalysisStats {
+    fn add_assign(&mut self, other: Self) {
+        self.static_imports += other.static_imports;
+        self.dynamic_imports += other.dynamic_imports;
+        self.requires += other.requires;
+        self.unresolved_references += other.unresolved_references;
+        self.code_gen_visitors += other.code_gen_visitors;
+        self.code_size += other.code_size;
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ModuleAnalysisStatsVc {
+    #[turbo_tasks::function]
+    pub async fn new(
+        module: EcmascriptModuleAssetVc,
+        chunking_context: EcmascriptChunkingContextVc,
+    ) -> Result<Self> {
+        let analyzed = module.analyze();
+        let mut stats = ModuleAnalysisStats::default();
+
+        for reference in analyzed.await?.references.await?.iter() {
+            let reference = reference.resolve().await?;
+            if EsmAssetReferenceVc::resolve_from(reference)
+                .await?
+                .is_some()
+            {
+                stats.static_imports += 1;
+            } else if EsmAsyncAssetReferenceVc::resolve_from(reference)
+                .await?
+                .is_some()
+            {
+                stats.dynamic_imports += 1;
+            } else if CjsRequireAssetReferenceVc::resolve_from(reference)
+                .await?
+                .is_some()
+                || CjsAssetReferenceVc::resolve_from(reference)
+                    .await?
+                    .is_some()
+            {
+                stats.requires += 1;
+            }
+            if *reference.resolve_reference().is_unresolveable().await? {
+                stats.unresolved_references += 1;
+            }
+        }
+
+        let availability_info = Value::new(AvailabilityInfo::Untracked);
+        let code_gens =
+            module_code_generations(chunking_context, analyzed, availability_info).await?;
+        stats.code_gen_visitors = code_gens.iter().map(|cg| cg.visitors.len()).sum();
+
+        stats.code_size = module
+            .module_content(chunking_context, availability_info)
+            .await?
+            .inner_code
+            .len();
+
+        Ok(stats.cell())
+    }
+}
+
+/// The sum of the [ModuleAnalysisStats] of multiple modules, e.g. of all
+/// modules in a chunk.
+#[turbo_tasks::value(shared)]
+#[derive(Default)]
+pub struct ModulesStats {
+    /// The number of ecmascript modules that were summarized. Other assets in
+    /// the set are skipped.
+    pub modules: usize,
+    pub total: ModuleAnalysisStats,
+}
+
+#[turbo_tasks::value_impl]
+impl ModulesStatsVc {
+    #[turbo_tasks::function]
+    pub async fn new(
+        modules: AssetsSetVc,
+        chunking_context: EcmascriptChunkingContextVc,
+    ) -> Result<Self> {
+        let mut stats = ModulesStats::default();
+        for module in modules.await?.iter() {
+            if let Some(module) = EcmascriptModuleAssetVc::resolve_from(*module).await? {
+                stats.modules += 1;
+                stats.total += *module.analysis_stats(chunking_context).await?;
+            }
+        }
+        Ok(stats.cell())
+    }
+}
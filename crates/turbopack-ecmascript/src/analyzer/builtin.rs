@@ -26,7 +26,8 @@ pub fn early_replace_builtin(value: &mut JsValue) -> bool {
             | JsValue::Alternatives(_, _)
             | JsValue::Concat(_, _)
             | JsValue::Add(_, _)
-            | JsValue::Not(_, _) => {
+            | JsValue::Not(_, _)
+            | JsValue::TypeOf(_, _) => {
                 value.make_unknown("non-function callee");
                 true
             }
@@ -357,6 +358,20 @@ pub fn replace_builtin(value: &mut JsValue) -> bool {
                         }
                     }
                 }
+                // matching calls on strings like `"./".concat("a", ".js")`
+                JsValue::Constant(ConstantValue::Str(_)) | JsValue::Concat(..) => {
+                    // The String.prototype.concat method, when all arguments are known
+                    if prop.as_str() == Some("concat")
+                        && args
+                            .iter()
+                            .all(|arg| matches!(arg, JsValue::Constant(_) | JsValue::Concat(..)))
+                    {
+                        let mut parts = vec![take(obj)];
+                        parts.extend(take(args));
+                        *value = JsValue::concat(parts);
+                        return true;
+                    }
+                }
                 // matching calls on multiple alternative objects like `(obj1 | obj2).prop(arg1,
                 // arg2, ...)`
                 JsValue::Alternatives(_, alts) => {
@@ -492,6 +507,44 @@ pub fn replace_builtin(value: &mut JsValue) -> bool {
             }
             None => false,
         },
+        // match the typeof operator like `typeof a`
+        // Evaluate typeof when the type of the inner value is known
+        JsValue::TypeOf(_, ref inner) => match type_of(inner) {
+            Some(type_name) => {
+                *value = type_name.into();
+                true
+            }
+            None => false,
+        },
         _ => false,
     }
 }
+
+/// Returns the result of `typeof value`, if the type of the value is known.
+fn type_of(value: &JsValue) -> Option<&'static str> {
+    Some(match value {
+        JsValue::Constant(constant) => match constant {
+            ConstantValue::Undefined => "undefined",
+            ConstantValue::Str(_) => "string",
+            ConstantValue::Num(_) => "number",
+            ConstantValue::True | ConstantValue::False => "boolean",
+            ConstantValue::BigInt(_) => "bigint",
+            ConstantValue::Null | ConstantValue::Regex(..) => "object",
+        },
+        JsValue::Array { .. } | JsValue::Object { .. } => "object",
+        JsValue::Function(..) => "function",
+        JsValue::Concat(..) | JsValue::TypeOf(..) => "string",
+        JsValue::Not(..) | JsValue::Binary(..) => "boolean",
+        JsValue::Alternatives(_, list) => {
+            let mut types = list.iter().map(type_of);
+            let first = types.next()??;
+            for other in types {
+                if other? != first {
+                    return None;
+                }
+            }
+            first
+        }
+        _ => return None,
+    })
+}
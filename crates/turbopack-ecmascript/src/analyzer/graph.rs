@@ -8,7 +8,7 @@ use swc_core::{
     common::{pass::AstNodePath, Mark, Span, Spanned, SyntaxContext},
     ecma::{
         ast::*,
-        atoms::js_word,
+        atoms::{js_word, JsWord},
         visit::{fields::*, VisitAstPath, VisitWithPath, *},
     },
 };
@@ -170,6 +170,36 @@ pub enum Effect {
         span: Span,
         in_try: bool,
     },
+    /// A reference to `new Worker(new URL(..., import.meta.url))` or
+    /// `new SharedWorker(new URL(..., import.meta.url))`. The ast path points
+    /// to the `new URL(...)` expression.
+    Worker {
+        input: JsValue,
+        ast_path: Vec<AstParentKind>,
+        span: Span,
+        in_try: bool,
+    },
+    /// A call to `import.meta.resolve(...)`.
+    ImportMetaResolve {
+        args: Vec<EffectArg>,
+        ast_path: Vec<AstParentKind>,
+        span: Span,
+        in_try: bool,
+    },
+    /// A call to `import.meta.glob(...)`.
+    ImportMetaGlob {
+        args: Vec<EffectArg>,
+        ast_path: Vec<AstParentKind>,
+        span: Span,
+        in_try: bool,
+    },
+    /// A reference to `import.meta.dirname` or `import.meta.filename`.
+    ImportMetaPath {
+        prop: JsWord,
+        ast_path: Vec<AstParentKind>,
+        span: Span,
+        in_try: bool,
+    },
 }
 
 impl Effect {
@@ -206,9 +236,15 @@ impl Effect {
             }
             Effect::ImportedBinding { .. } => {}
             Effect::ImportMeta { .. } => {}
-            Effect::Url { input, .. } => {
+            Effect::Url { input, .. } | Effect::Worker { input, .. } => {
                 input.normalize();
             }
+            Effect::ImportMetaResolve { args, .. } | Effect::ImportMetaGlob { args, .. } => {
+                for arg in args.iter_mut() {
+                    arg.normalize();
+                }
+            }
+            Effect::ImportMetaPath { .. } => {}
         }
     }
 }
@@ -257,6 +293,10 @@ pub fn create_graph(m: &Program, eval_context: &EvalContext) -> VarGraph {
     graph
 }
 
+/// The maximum number of nested conditionals with statically known conditions
+/// which are folded, see [EvalContext::eval].
+const MAX_FOLDED_CONDITIONALS: usize = 16;
+
 pub struct EvalContext {
     pub(crate) unresolved_mark: Mark,
     pub(crate) imports: ImportMap,
@@ -284,6 +324,24 @@ impl EvalContext {
         }
     }
 
+    /// When the condition is known statically, e. g. `true ? "./a" : "./b"`,
+    /// only the selected branch is evaluated. Nested conditionals are folded up
+    /// to [MAX_FOLDED_CONDITIONALS] levels deep, deeper ones are unknown.
+    fn eval_cond(&self, mut e: &CondExpr) -> JsValue {
+        for _ in 0..MAX_FOLDED_CONDITIONALS {
+            let selected = match self.eval(&e.test).is_truthy() {
+                Some(true) => &*e.cons,
+                Some(false) => &*e.alt,
+                None => return JsValue::alternatives(vec![self.eval(&e.cons), self.eval(&e.alt)]),
+            };
+            match unparen(selected) {
+                Expr::Cond(cond) => e = cond,
+                selected => return self.eval(selected),
+            }
+        }
+        JsValue::unknown_empty("conditional nesting limit reached")
+    }
+
     fn eval_tpl(&self, e: &Tpl, raw: bool) -> JsValue {
         debug_assert!(e.quasis.len() == e.exprs.len() + 1);
 
@@ -346,6 +404,16 @@ impl EvalContext {
                 JsValue::logical_not(Box::new(arg))
             }
 
+            Expr::Unary(UnaryExpr {
+                op: op!("typeof"),
+                arg,
+                ..
+            }) => {
+                let arg = self.eval(arg);
+
+                JsValue::type_of(Box::new(arg))
+            }
+
             Expr::Bin(BinExpr {
                 op: op!(bin, "+"),
                 left,
@@ -413,11 +481,7 @@ impl EvalContext {
                 ..
             }) => JsValue::strict_not_equal(self.eval(left), self.eval(right)),
 
-            &Expr::Cond(CondExpr {
-                box ref cons,
-                box ref alt,
-                ..
-            }) => JsValue::alternatives(vec![self.eval(cons), self.eval(alt)]),
+            Expr::Cond(e) => self.eval_cond(e),
 
             Expr::Tpl(e) => self.eval_tpl(e, false),
 
@@ -643,6 +707,53 @@ pub fn is_in_try(ast_path: &AstNodePath<AstParentNodeRef<'_>>) -> bool {
         .unwrap_or(false)
 }
 
+/// Returns whether the expression at the end of the ast path is the first
+/// argument of `new Worker(...)` or `new SharedWorker(...)`.
+fn is_worker_argument(ast_path: &AstNodePath<AstParentNodeRef<'_>>, unresolved_mark: Mark) -> bool {
+    let mut parents = ast_path.iter().rev();
+    matches!(
+        parents.next(),
+        Some(AstParentNodeRef::Expr(_, ExprField::New))
+    ) && matches!(
+        parents.next(),
+        Some(AstParentNodeRef::ExprOrSpread(
+            ExprOrSpread { spread: None, .. },
+            ExprOrSpreadField::Expr
+        ))
+    ) && matches!(
+            parents.next(),
+            Some(AstParentNodeRef::NewExpr(
+                NewExpr {
+                    callee: box Expr::Ident(callee),
+                    ..
+                },
+                NewExprField::Args(0)
+            )) if (&*callee.sym == "Worker" || &*callee.sym == "SharedWorker")
+                && is_unresolved(callee, unresolved_mark)
+        )
+}
+
+fn is_import_meta(expr: &Expr) -> bool {
+    matches!(
+        unparen(expr),
+        Expr::MetaProp(MetaPropExpr {
+            kind: MetaPropKind::ImportMeta,
+            ..
+        })
+    )
+}
+
+fn is_default_prop(prop: &MemberProp) -> bool {
+    match prop {
+        MemberProp::Ident(ident) => &*ident.sym == "default",
+        MemberProp::Computed(ComputedPropName {
+            expr: box Expr::Lit(Lit::Str(str)),
+            ..
+        }) => &*str.value == "default",
+        _ => false,
+    }
+}
+
 impl Analyzer<'_> {
     fn add_value(&mut self, id: Id, value: JsValue) {
         if let Some(prev) = self.data.values.get_mut(&id) {
@@ -883,6 +994,30 @@ impl Analyzer<'_> {
             }
             Callee::Expr(box expr) => {
                 if let Expr::Member(MemberExpr { obj, prop, .. }) = unparen(expr) {
+                    // import.meta.resolve("...")
+                    if is_import_meta(obj) {
+                        if let MemberProp::Ident(prop) = prop {
+                            if &*prop.sym == "resolve" {
+                                self.add_effect(Effect::ImportMetaResolve {
+                                    args,
+                                    ast_path: as_parent_path(ast_path),
+                                    span: n.span(),
+                                    in_try: is_in_try(ast_path),
+                                });
+                                return;
+                            }
+                            // import.meta.glob("...")
+                            if &*prop.sym == "glob" {
+                                self.add_effect(Effect::ImportMetaGlob {
+                                    args,
+                                    ast_path: as_parent_path(ast_path),
+                                    span: n.span(),
+                                    in_try: is_in_try(ast_path),
+                                });
+                                return;
+                            }
+                        }
+                    }
                     let obj_value = self.eval_context.eval(obj);
                     let prop_value = match prop {
                         // TODO avoid clone
@@ -1147,12 +1282,25 @@ impl VisitAstPath for Analyzer<'_> {
                         }) = &*args[1].expr
                         {
                             if &*prop.sym == "url" {
-                                self.add_effect(Effect::Url {
-                                    input: self.eval_context.eval(&args[0].expr),
-                                    ast_path: as_parent_path(ast_path),
-                                    span: new_expr.span(),
-                                    in_try: is_in_try(ast_path),
-                                });
+                                let input = self.eval_context.eval(&args[0].expr);
+                                let span = new_expr.span();
+                                let in_try = is_in_try(ast_path);
+                                // new Worker(new URL("path", import.meta.url))
+                                if is_worker_argument(ast_path, self.eval_context.unresolved_mark) {
+                                    self.add_effect(Effect::Worker {
+                                        input,
+                                        ast_path: as_parent_path(ast_path),
+                                        span,
+                                        in_try,
+                                    });
+                                } else {
+                                    self.add_effect(Effect::Url {
+                                        input,
+                                        ast_path: as_parent_path(ast_path),
+                                        span,
+                                        in_try,
+                                    });
+                                }
                             }
                         }
                     }
@@ -1167,6 +1315,41 @@ impl VisitAstPath for Analyzer<'_> {
         member_expr: &'ast MemberExpr,
         ast_path: &mut AstNodePath<AstParentNodeRef<'r>>,
     ) {
+        // import.meta.dirname / import.meta.filename
+        if is_import_meta(&member_expr.obj) {
+            if let MemberProp::Ident(prop) = &member_expr.prop {
+                if &*prop.sym == "dirname" || &*prop.sym == "filename" {
+                    self.add_effect(Effect::ImportMetaPath {
+                        prop: prop.sym.clone(),
+                        ast_path: as_parent_path(ast_path),
+                        span: member_expr.span(),
+                        in_try: is_in_try(ast_path),
+                    });
+                }
+            }
+        }
+        // `ns.default` of a namespace import references the default export,
+        // which needs the same interop as a default import.
+        if let (Some(AstParentNodeRef::Expr(_, ExprField::Member)), Expr::Ident(obj)) =
+            (ast_path.last(), &*member_expr.obj)
+        {
+            if let Some((esm_reference_index, None)) =
+                self.eval_context.imports.get_binding(&obj.to_id())
+            {
+                if is_default_prop(&member_expr.prop) {
+                    self.add_effect(Effect::ImportedBinding {
+                        esm_reference_index,
+                        export: Some("default".to_string()),
+                        ast_path: as_parent_path(ast_path),
+                        span: member_expr.span(),
+                        in_try: is_in_try(ast_path),
+                    });
+                    self.check_member_expr_for_effects(member_expr, ast_path);
+                    // The namespace object itself is not referenced.
+                    return;
+                }
+            }
+        }
         self.check_member_expr_for_effects(member_expr, ast_path);
         member_expr.visit_children_with_path(self, ast_path);
     }
@@ -9,7 +9,7 @@ use swc_core::ecma::{
 };
 
 use super::{JsValue, ModuleValue};
-use crate::utils::unparen;
+use crate::{tree_shake::find_turbopack_chunk_id_in_asserts, utils::unparen};
 
 #[turbo_tasks::value(serialization = "auto_for_input")]
 #[derive(Default, Debug, Clone, Hash, PartialOrd, Ord)]
@@ -106,6 +106,9 @@ pub(crate) enum ImportedSymbol {
     ModuleEvaluation,
     Symbol(JsWord),
     Namespace,
+    /// An import of another part of the same split module. All bindings of
+    /// such an import refer to the part.
+    Part(u32),
 }
 
 #[derive(Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
@@ -244,15 +247,20 @@ impl Visit for Analyzer<'_> {
 
     fn visit_import_decl(&mut self, import: &ImportDecl) {
         let annotations = take(&mut self.current_annotations);
+        let part = import
+            .asserts
+            .as_deref()
+            .and_then(find_turbopack_chunk_id_in_asserts);
 
         self.ensure_reference(
             import.src.value.clone(),
-            ImportedSymbol::ModuleEvaluation,
+            part.map_or(ImportedSymbol::ModuleEvaluation, ImportedSymbol::Part),
             annotations.clone(),
         );
 
         for s in &import.specifiers {
-            let symbol = get_import_symbol_from_import(s);
+            let symbol =
+                part.map_or_else(|| get_import_symbol_from_import(s), ImportedSymbol::Part);
             let i = self.ensure_reference(import.src.value.clone(), symbol, annotations.clone());
 
             let (local, orig_sym) = match s {
@@ -417,6 +417,8 @@ pub enum JsValue {
     Add(usize, Vec<JsValue>),
     /// Logical negation `!expr`
     Not(usize, Box<JsValue>),
+    /// The type of a value `typeof expr`
+    TypeOf(usize, Box<JsValue>),
     /// Logical operator chain e. g. `expr && expr`
     Logical(usize, LogicalOperator, Vec<JsValue>),
     /// Binary expression e. g. `expr == expr`
@@ -574,6 +576,7 @@ impl Display for JsValue {
                     .join(" + ")
             ),
             JsValue::Not(_, value) => write!(f, "!({})", value),
+            JsValue::TypeOf(_, value) => write!(f, "typeof({})", value),
             JsValue::Logical(_, op, list) => write!(
                 f,
                 "({})",
@@ -691,6 +694,7 @@ impl JsValue {
             JsValue::Concat(..)
             | JsValue::Add(..)
             | JsValue::Not(..)
+            | JsValue::TypeOf(..)
             | JsValue::Logical(..)
             | JsValue::Binary(..)
             | JsValue::Call(..)
@@ -774,6 +778,10 @@ impl JsValue {
         Self::Not(1 + inner.total_nodes(), inner)
     }
 
+    pub fn type_of(inner: Box<JsValue>) -> Self {
+        Self::TypeOf(1 + inner.total_nodes(), inner)
+    }
+
     pub fn array(items: Vec<JsValue>) -> Self {
         Self::Array {
             total_nodes: 1 + total_nodes(&items),
@@ -872,6 +880,7 @@ impl JsValue {
             | JsValue::Concat(c, _)
             | JsValue::Add(c, _)
             | JsValue::Not(c, _)
+            | JsValue::TypeOf(c, _)
             | JsValue::Logical(c, _, _)
             | JsValue::Binary(c, _, _, _)
             | JsValue::Call(c, _, _)
@@ -909,7 +918,7 @@ impl JsValue {
             JsValue::Binary(c, a, _, b) => {
                 *c = 1 + a.total_nodes() + b.total_nodes();
             }
-            JsValue::Not(c, r) => {
+            JsValue::Not(c, r) | JsValue::TypeOf(c, r) => {
                 *c = 1 + r.total_nodes();
             }
 
@@ -988,7 +997,7 @@ impl JsValue {
                     make_max_unknown(list.iter_mut());
                     self.update_total_nodes();
                 }
-                JsValue::Not(_, r) => {
+                JsValue::Not(_, r) | JsValue::TypeOf(_, r) => {
                     r.make_unknown_without_content("node limit reached");
                 }
                 JsValue::Binary(_, a, _, b) => {
@@ -1238,6 +1247,10 @@ impl JsValue {
                 "!({})",
                 value.explain_internal_inner(hints, indent_depth, depth, unknown_depth)
             ),
+            JsValue::TypeOf(_, value) => format!(
+                "typeof({})",
+                value.explain_internal_inner(hints, indent_depth, depth, unknown_depth)
+            ),
             JsValue::Call(_, callee, list) => {
                 format!(
                     "{}({})",
@@ -1681,7 +1694,8 @@ impl JsValue {
             | JsValue::Object { .. }
             | JsValue::WellKnownObject(..)
             | JsValue::WellKnownFunction(..)
-            | JsValue::Function(..) => Some(true),
+            | JsValue::Function(..)
+            | JsValue::TypeOf(..) => Some(true),
             JsValue::Alternatives(_, list) => merge_if_known(list, JsValue::is_truthy),
             JsValue::Not(_, value) => value.is_truthy().map(|x| !x),
             JsValue::Logical(_, op, list) => match op {
@@ -1758,6 +1772,7 @@ impl JsValue {
             | JsValue::WellKnownObject(..)
             | JsValue::WellKnownFunction(..)
             | JsValue::Not(..)
+            | JsValue::TypeOf(..)
             | JsValue::Binary(..)
             | JsValue::Function(..) => Some(false),
             JsValue::Alternatives(_, list) => merge_if_known(list, JsValue::is_nullish),
@@ -1802,6 +1817,8 @@ impl JsValue {
             },
             // Booleans are not empty strings
             JsValue::Not(..) | JsValue::Binary(..) => Some(false),
+            // Type names are not empty strings
+            JsValue::TypeOf(..) => Some(false),
             // Objects are not empty strings
             JsValue::Url(..)
             | JsValue::Array { .. }
@@ -1827,7 +1844,9 @@ impl JsValue {
     /// don't know. Returns Some if we know if or if not the value is a string.
     pub fn is_string(&self) -> Option<bool> {
         match self {
-            JsValue::Constant(ConstantValue::Str(..)) | JsValue::Concat(..) => Some(true),
+            JsValue::Constant(ConstantValue::Str(..))
+            | JsValue::Concat(..)
+            | JsValue::TypeOf(..) => Some(true),
 
             // Objects are not strings
             JsValue::Constant(..)
@@ -2119,7 +2138,7 @@ macro_rules! for_each_children_async {
                 $value.update_total_nodes();
                 ($value, modified)
             }
-            JsValue::Not(_, box value) => {
+            JsValue::Not(_, box value) | JsValue::TypeOf(_, box value) => {
                 let (new_value, modified) = $visit_fn(take(value), $($args),+).await?;
                 *value = new_value;
 
@@ -2318,7 +2337,7 @@ impl JsValue {
                 }
                 modified
             }
-            JsValue::Not(_, value) => {
+            JsValue::Not(_, value) | JsValue::TypeOf(_, value) => {
                 let modified = visitor(value);
                 if modified {
                     self.update_total_nodes();
@@ -2519,7 +2538,7 @@ impl JsValue {
                     visitor(item);
                 }
             }
-            JsValue::Not(_, value) => {
+            JsValue::Not(_, value) | JsValue::TypeOf(_, value) => {
                 visitor(value);
             }
             JsValue::Object { parts, .. } => {
@@ -2799,6 +2818,7 @@ impl JsValue {
                 lc == rc && lo == ro && all_similar(l, r, depth - 1)
             }
             (JsValue::Not(lc, l), JsValue::Not(rc, r)) => lc == rc && l.similar(r, depth - 1),
+            (JsValue::TypeOf(lc, l), JsValue::TypeOf(rc, r)) => lc == rc && l.similar(r, depth - 1),
             (JsValue::Call(lc, lf, la), JsValue::Call(rc, rf, ra)) => {
                 lc == rc && lf.similar(rf, depth - 1) && all_similar(la, ra, depth - 1)
             }
@@ -2877,7 +2897,7 @@ impl JsValue {
             | JsValue::Concat(_, v)
             | JsValue::Add(_, v)
             | JsValue::Logical(_, _, v) => all_similar_hash(v, state, depth - 1),
-            JsValue::Not(_, v) => v.similar_hash(state, depth - 1),
+            JsValue::Not(_, v) | JsValue::TypeOf(_, v) => v.similar_hash(state, depth - 1),
             JsValue::Call(_, a, b) => {
                 a.similar_hash(state, depth - 1);
                 all_similar_hash(b, state, depth - 1);
@@ -3073,6 +3093,83 @@ pub fn parse_require_context(args: &Vec<JsValue>) -> Result<RequireContextOption
     })
 }
 
+#[derive(Debug, Clone)]
+pub struct ImportMetaGlobOptions {
+    /// The glob patterns relative to the module. Patterns starting with `!`
+    /// exclude files.
+    pub patterns: Vec<String>,
+    /// Whether the matching modules are imported statically, instead of by
+    /// functions which import them dynamically.
+    pub eager: bool,
+    /// The export which is imported instead of the namespace.
+    pub import: Option<String>,
+}
+
+/// Parse the arguments passed to an import.meta.glob invocation, validate them
+/// and convert them to the appropriate rust values.
+pub fn parse_import_meta_glob(args: &[JsValue]) -> Result<ImportMetaGlobOptions> {
+    if !(1..=2).contains(&args.len()) {
+        bail!("import.meta.glob() only supports 1-2 arguments");
+    }
+
+    let patterns = match &args[0] {
+        JsValue::Array { items, .. } => items
+            .iter()
+            .map(|item| item.as_str().map(|s| s.to_string()))
+            .collect::<Option<Vec<_>>>(),
+        pattern => pattern.as_str().map(|s| vec![s.to_string()]),
+    };
+    let Some(patterns) = patterns else {
+        bail!(
+            "import.meta.glob(patterns, ...) requires patterns to be a constant string or an \
+             array of constant strings"
+        );
+    };
+    for pattern in &patterns {
+        let pattern = pattern.strip_prefix('!').unwrap_or(pattern);
+        if !pattern.starts_with("./") && !pattern.starts_with("../") {
+            bail!("import.meta.glob() only supports patterns starting with `./` or `../`");
+        }
+    }
+    if !patterns.iter().any(|pattern| !pattern.starts_with('!')) {
+        bail!("import.meta.glob() requires at least one pattern which is not negated");
+    }
+
+    let mut eager = false;
+    let mut import = None;
+    if let Some(options) = args.get(1) {
+        let JsValue::Object { parts, .. } = options else {
+            bail!("import.meta.glob(..., options) requires options to be an object literal");
+        };
+        for part in parts {
+            let ObjectPart::KeyValue(key, value) = part else {
+                bail!("import.meta.glob(..., options) doesn't support spread options");
+            };
+            match key.as_str() {
+                Some("eager") => {
+                    let Some(value) = value.as_bool() else {
+                        bail!("import.meta.glob(..., {{ eager }}) requires a constant boolean");
+                    };
+                    eager = value;
+                }
+                Some("import") => {
+                    let Some(value) = value.as_str() else {
+                        bail!("import.meta.glob(..., {{ import }}) requires a constant string");
+                    };
+                    import = Some(value.to_string());
+                }
+                _ => bail!("import.meta.glob(..., {{ {key}: ... }}) is not supported"),
+            }
+        }
+    }
+
+    Ok(ImportMetaGlobOptions {
+        patterns,
+        eager,
+        import,
+    })
+}
+
 #[turbo_tasks::value(transparent)]
 #[derive(Debug, Clone)]
 pub struct RequireContextValue(IndexMap<String, String>);
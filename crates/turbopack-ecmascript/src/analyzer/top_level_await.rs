@@ -1,19 +1,27 @@
-use swc_core::ecma::{
-    ast::*,
-    visit::{noop_visit_type, Visit, VisitWith},
+use swc_core::{
+    common::Span,
+    ecma::{
+        ast::*,
+        visit::{noop_visit_type, Visit, VisitWith},
+    },
 };
 
 pub(crate) fn has_top_level_await(m: &Program) -> bool {
+    top_level_await_span(m).is_some()
+}
+
+/// The span of the first top level `await` expression.
+pub(crate) fn top_level_await_span(m: &Program) -> Option<Span> {
     let mut visitor = TopLevelAwaitVisitor::default();
 
     m.visit_with(&mut visitor);
 
-    visitor.has_top_level_await
+    visitor.top_level_await
 }
 
 #[derive(Default)]
 struct TopLevelAwaitVisitor {
-    has_top_level_await: bool,
+    top_level_await: Option<Span>,
 }
 
 macro_rules! noop {
@@ -23,8 +31,10 @@ macro_rules! noop {
 }
 
 impl Visit for TopLevelAwaitVisitor {
-    fn visit_await_expr(&mut self, _: &AwaitExpr) {
-        self.has_top_level_await = true;
+    fn visit_await_expr(&mut self, n: &AwaitExpr) {
+        if self.top_level_await.is_none() {
+            self.top_level_await = Some(n.span);
+        }
     }
 
     // prevent non top level items from visiting their children
@@ -41,6 +41,16 @@ pub async fn replace_well_known(
         JsValue::Member(_, box JsValue::WellKnownFunction(kind), box prop) => {
             well_known_function_member(kind, prop)
         }
+        JsValue::TypeOf(_, box JsValue::FreeVar(ref name)) => {
+            let type_name = compile_time_info
+                .environment()
+                .type_of_global(name.to_string())
+                .await?;
+            match &*type_name {
+                Some(type_name) => (type_name.as_str().into(), true),
+                None => (value, false),
+            }
+        }
         _ => (value, false),
     })
 }
@@ -714,6 +724,22 @@ async fn node_process_member(
             .into(),
         Some("cwd") => JsValue::WellKnownFunction(WellKnownFunctionKind::ProcessCwd),
         Some("env") => JsValue::WellKnownObject(WellKnownObjectKind::NodeProcessEnv),
+        Some("browser") => {
+            let environment = compile_time_info.environment();
+            if *environment.is_browser().await? {
+                ConstantValue::True.into()
+            } else if *environment.node_externals().await? {
+                ConstantValue::Undefined.into()
+            } else {
+                JsValue::unknown(
+                    JsValue::member(
+                        Box::new(JsValue::WellKnownObject(WellKnownObjectKind::NodeProcess)),
+                        Box::new(prop),
+                    ),
+                    "process.browser is unknown in this environment",
+                )
+            }
+        }
         _ => JsValue::unknown(
             JsValue::member(
                 Box::new(JsValue::WellKnownObject(WellKnownObjectKind::NodeProcess)),
@@ -1,23 +1,29 @@
+use std::{collections::HashSet, ops::Range};
+
 use anyhow::Result;
-use indexmap::IndexSet;
-use turbo_tasks::Value;
+use indexmap::{IndexMap, IndexSet};
+use turbo_tasks::{TryJoinIterExt, Value, ValueToString};
 use turbopack_core::{
+    asset::AssetVc,
     chunk::{
         availability_info::AvailabilityInfo, chunk_content, chunk_content_split,
-        ChunkContentResult, ChunkVc,
+        ChunkContentResult, ChunkItem, ChunkVc, ChunkingContext, ChunkingContextVc,
     },
     reference::AssetReferenceVc,
 };
 
 use super::{
-    item::EcmascriptChunkItemVc,
+    duplicate_modules::detect_duplicate_modules,
+    item::{EcmascriptChunkItem, EcmascriptChunkItemVc},
     placeable::{EcmascriptChunkPlaceableVc, EcmascriptChunkPlaceablesVc},
-    EcmascriptChunkingContextVc,
+    EcmascriptChunkingContext, EcmascriptChunkingContextVc,
 };
 
 #[turbo_tasks::value]
 pub struct EcmascriptChunkContent {
     pub chunk_items: Vec<EcmascriptChunkItemVc>,
+    /// The assets the `chunk_items` were created from, in the same order.
+    pub chunk_item_assets: Vec<AssetVc>,
     pub chunks: Vec<ChunkVc>,
     pub external_asset_references: Vec<AssetReferenceVc>,
     pub availability_info: AvailabilityInfo,
@@ -27,6 +33,7 @@ impl From<ChunkContentResult<EcmascriptChunkItemVc>> for EcmascriptChunkContent
     fn from(from: ChunkContentResult<EcmascriptChunkItemVc>) -> Self {
         EcmascriptChunkContent {
             chunk_items: from.chunk_items,
+            chunk_item_assets: from.chunk_item_assets,
             chunks: from.chunks,
             external_asset_references: from.external_asset_references,
             availability_info: from.availability_info,
@@ -36,19 +43,38 @@ impl From<ChunkContentResult<EcmascriptChunkItemVc>> for EcmascriptChunkContent
 
 #[turbo_tasks::value_impl]
 impl EcmascriptChunkContentVc {
+    /// Removes the chunk items of `other` from the content. The chunks and
+    /// external references are kept.
     #[turbo_tasks::function]
-    pub fn filter(self, _other: EcmascriptChunkContentVc) -> EcmascriptChunkContentVc {
-        todo!()
+    pub async fn filter(self, other: EcmascriptChunkContentVc) -> Result<EcmascriptChunkContentVc> {
+        let this = self.await?;
+        let other = other.await?;
+        let omitted = other.chunk_items.iter().copied().collect::<HashSet<_>>();
+        let (chunk_items, chunk_item_assets) = this
+            .chunk_items
+            .iter()
+            .copied()
+            .zip(this.chunk_item_assets.iter().copied())
+            .filter(|(item, _)| !omitted.contains(item))
+            .unzip();
+        Ok(EcmascriptChunkContent {
+            chunk_items,
+            chunk_item_assets,
+            chunks: this.chunks.clone(),
+            external_asset_references: this.external_asset_references.clone(),
+            availability_info: this.availability_info,
+        }
+        .cell())
     }
 }
 
 #[turbo_tasks::function]
-pub(crate) fn ecmascript_chunk_content(
+pub(crate) async fn ecmascript_chunk_content(
     context: EcmascriptChunkingContextVc,
     main_entries: EcmascriptChunkPlaceablesVc,
     omit_entries: Option<EcmascriptChunkPlaceablesVc>,
     availability_info: Value<AvailabilityInfo>,
-) -> EcmascriptChunkContentVc {
+) -> Result<EcmascriptChunkContentVc> {
     let mut chunk_content =
         ecmascript_chunk_content_internal(context, main_entries, availability_info);
     if let Some(omit_entries) = omit_entries {
@@ -56,7 +82,10 @@ pub(crate) fn ecmascript_chunk_content(
             ecmascript_chunk_content_internal(context, omit_entries, availability_info);
         chunk_content = chunk_content.filter(omit_chunk_content);
     }
-    chunk_content
+    if *context.detect_duplicate_modules().await? {
+        detect_duplicate_modules(chunk_content).await?;
+    }
+    Ok(chunk_content)
 }
 
 #[turbo_tasks::function]
@@ -76,24 +105,32 @@ async fn ecmascript_chunk_content_internal(
         return Ok(contents.into_iter().next().unwrap());
     }
 
-    let mut all_chunk_items = IndexSet::<EcmascriptChunkItemVc>::new();
+    let mut all_chunk_items = IndexMap::<EcmascriptChunkItemVc, AssetVc>::new();
     let mut all_chunks = IndexSet::<ChunkVc>::new();
     let mut all_external_asset_references = IndexSet::<AssetReferenceVc>::new();
 
     for content in contents {
         let EcmascriptChunkContent {
             chunk_items,
+            chunk_item_assets,
             chunks,
             external_asset_references,
             availability_info: _,
         } = &*content.await?;
-        all_chunk_items.extend(chunk_items.iter().copied());
+        all_chunk_items.extend(
+            chunk_items
+                .iter()
+                .copied()
+                .zip(chunk_item_assets.iter().copied()),
+        );
         all_chunks.extend(chunks.iter().copied());
         all_external_asset_references.extend(external_asset_references.iter().copied());
     }
 
+    let (chunk_items, chunk_item_assets) = all_chunk_items.into_iter().unzip();
     Ok(EcmascriptChunkContent {
-        chunk_items: all_chunk_items.into_iter().collect(),
+        chunk_items,
+        chunk_item_assets,
         chunks: all_chunks.into_iter().collect(),
         external_asset_references: all_external_asset_references.into_iter().collect(),
         availability_info: availability_info.into_value(),
@@ -127,3 +164,181 @@ async fn ecmascript_chunk_content_single_entry(
         .into(),
     ))
 }
+
+#[turbo_tasks::value(transparent)]
+pub struct EcmascriptChunkContents(Vec<EcmascriptChunkContentVc>);
+
+/// Splits the content of a chunk into parts whose estimated size (the length
+/// of the chunk item code) stays under `budget`. Content which fits into the
+/// budget isn't split.
+///
+/// Chunk items are ordered by their ident and packed greedily. The chunking
+/// context only permits items to be in the same part: consecutive items which
+/// it allows to be in the same chunk as the first item of their run, e.g.
+/// modules of the same directory, can share a part, while other items start a
+/// new part. Items are split whenever the budget is exceeded, and an item
+/// which is larger than the budget gets a part on its own.
+///
+/// Every part has the external references of its own chunk items. The
+/// parallel chunks are loaded with the chunk as a whole, so they stay in the
+/// first part.
+#[turbo_tasks::function]
+pub(crate) async fn split_ecmascript_chunk_content(
+    context: EcmascriptChunkingContextVc,
+    content: EcmascriptChunkContentVc,
+    budget: u64,
+) -> Result<EcmascriptChunkContentsVc> {
+    let content_ref = content.await?;
+
+    let mut items = content_ref
+        .chunk_items
+        .iter()
+        .copied()
+        .zip(content_ref.chunk_item_assets.iter().copied())
+        .map(|(item, asset)| async move {
+            let ident = item.asset_ident().to_string().await?;
+            let size = item.content().await?.inner_code.len() as u64;
+            Ok((ident, item, asset, size))
+        })
+        .try_join()
+        .await?;
+    if items.iter().map(|(.., size)| size).sum::<u64>() <= budget {
+        return Ok(EcmascriptChunkContentsVc::cell(vec![content]));
+    }
+    items.sort_by(|(a, ..), (b, ..)| a.cmp(b));
+
+    // Runs of items which are permitted to be in the same part.
+    let context: ChunkingContextVc = context.into();
+    let mut runs: Vec<(AssetVc, Vec<(EcmascriptChunkItemVc, AssetVc, u64)>)> = Vec::new();
+    for (_, item, asset, size) in items {
+        let permitted = match runs.last() {
+            Some(&(first, _)) => {
+                *context.can_be_in_same_chunk(first, asset).await?
+                    && *context.can_be_in_same_chunk(asset, first).await?
+            }
+            None => false,
+        };
+        match runs.last_mut() {
+            Some((_, run)) if permitted => run.push((item, asset, size)),
+            _ => runs.push((asset, vec![(item, asset, size)])),
+        }
+    }
+
+    let mut parts = Vec::new();
+    for (_, run) in runs {
+        let sizes = run.iter().map(|&(.., size)| size).collect::<Vec<_>>();
+        for range in pack_items(&sizes, budget) {
+            parts.push(
+                run[range]
+                    .iter()
+                    .map(|&(item, asset, _)| (item, asset))
+                    .collect::<Vec<_>>(),
+            );
+        }
+    }
+
+    let mut unassigned_references = content_ref
+        .external_asset_references
+        .iter()
+        .copied()
+        .collect::<IndexSet<_>>();
+    let mut part_references = Vec::with_capacity(parts.len());
+    for part in &parts {
+        let mut item_references = HashSet::new();
+        for &(item, _) in part {
+            item_references.extend(item.references().await?.iter().copied());
+        }
+        let references = content_ref
+            .external_asset_references
+            .iter()
+            .copied()
+            .filter(|reference| item_references.contains(reference))
+            .collect::<Vec<_>>();
+        for reference in &references {
+            unassigned_references.remove(reference);
+        }
+        part_references.push(references);
+    }
+    if let Some(references) = part_references.first_mut() {
+        references.extend(unassigned_references);
+    }
+
+    let parts = parts
+        .into_iter()
+        .zip(part_references)
+        .enumerate()
+        .map(|(index, (part, external_asset_references))| {
+            let (chunk_items, chunk_item_assets) = part.into_iter().unzip();
+            EcmascriptChunkContent {
+                chunk_items,
+                chunk_item_assets,
+                chunks: if index == 0 {
+                    content_ref.chunks.clone()
+                } else {
+                    Vec::new()
+                },
+                external_asset_references,
+                availability_info: content_ref.availability_info,
+            }
+            .cell()
+        })
+        .collect::<Vec<_>>();
+
+    Ok(EcmascriptChunkContentsVc::cell(parts))
+}
+
+/// Greedily packs consecutive items into ranges whose summed size stays
+/// under `budget`. An item larger than the budget gets a range on its own.
+fn pack_items(sizes: &[u64], budget: u64) -> Vec<Range<usize>> {
+    let mut ranges = Vec::new();
+    let mut start = 0;
+    let mut current_size = 0;
+    for (index, &size) in sizes.iter().enumerate() {
+        if index > start && current_size + size > budget {
+            ranges.push(start..index);
+            start = index;
+            current_size = 0;
+        }
+        current_size += size;
+    }
+    if start < sizes.len() {
+        ranges.push(start..sizes.len());
+    }
+    ranges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::pack_items;
+
+    /// Asserts that every item is in exactly one range, in order.
+    fn assert_covers(ranges: &[std::ops::Range<usize>], len: usize) {
+        let indices = ranges.iter().flat_map(|range| range.clone());
+        assert!(indices.eq(0..len), "{ranges:?}");
+    }
+
+    #[test]
+    fn packs_under_budget() {
+        let sizes = [30, 30, 30, 30, 30];
+        let ranges = pack_items(&sizes, 100);
+        assert_eq!(ranges, vec![0..3, 3..5]);
+        assert_covers(&ranges, sizes.len());
+    }
+
+    #[test]
+    fn oversized_item_gets_own_range() {
+        let sizes = [10, 500, 10, 10];
+        let ranges = pack_items(&sizes, 100);
+        assert_eq!(ranges, vec![0..1, 1..2, 2..4]);
+        assert_covers(&ranges, sizes.len());
+    }
+
+    #[test]
+    fn everything_fits() {
+        let sizes = [10, 20, 30];
+        let ranges = pack_items(&sizes, 100);
+        assert_eq!(ranges, vec![0..3]);
+        assert_covers(&ranges, sizes.len());
+        assert!(pack_items(&[], 100).is_empty());
+    }
+}
@@ -1,8 +1,12 @@
-use anyhow::Result;
+use anyhow::{bail, Result};
 use turbo_tasks::{primitives::BoolVc, ValueToString};
 use turbopack_core::chunk::{ChunkItem, ChunkingContext, ChunkingContextVc, ModuleId, ModuleIdVc};
 
-use super::item::EcmascriptChunkItemVc;
+use super::{
+    item::EcmascriptChunkItemVc,
+    module_id_map::OptionModuleIdMapVc,
+    module_id_strategy::{ModuleIdStrategy, ModuleIdStrategyVc},
+};
 
 /// [`EcmascriptChunkingContext`] must be implemented by [`ChunkingContext`]
 /// implementors that want to operate on [`EcmascriptChunk`]s.
@@ -14,12 +18,42 @@ pub trait EcmascriptChunkingContext: ChunkingContext {
         BoolVc::cell(false)
     }
 
+    /// Whether chunks should be checked for distinct modules that share the
+    /// same ident. This is a debugging aid, as it stringifies every ident.
+    fn detect_duplicate_modules(&self) -> BoolVc {
+        BoolVc::cell(false)
+    }
+
+    /// The map of numeric module ids. It must assign ids to all chunk items
+    /// of this chunking context. When none is configured, module ids are
+    /// derived from the asset ident.
+    fn module_id_map(&self) -> OptionModuleIdMapVc {
+        OptionModuleIdMapVc::cell(None)
+    }
+
+    /// How module ids are derived from the asset ident when no
+    /// [`module_id_map`](EcmascriptChunkingContext::module_id_map) is
+    /// configured.
+    fn chunk_item_id_strategy(&self) -> ModuleIdStrategyVc {
+        ModuleIdStrategy::default().cell()
+    }
+
     async fn chunk_item_id(&self, chunk_item: EcmascriptChunkItemVc) -> Result<ModuleIdVc> {
+        if let Some(map) = *self.module_id_map().await? {
+            let ident = chunk_item.asset_ident().to_string().await?;
+            let Some(id) = map.await?.get(&ident) else {
+                bail!("the module id map doesn't assign an id to {ident}");
+            };
+            return Ok(ModuleId::Number(id).cell());
+        }
         let layer = self.layer();
         let mut ident = chunk_item.asset_ident();
         if !layer.await?.is_empty() {
             ident = ident.with_modifier(layer)
         }
-        Ok(ModuleId::String(ident.to_string().await?.clone_value()).cell())
+        let ident = ident.to_string().await?;
+        let strategy = *self.chunk_item_id_strategy().await?;
+        let context_path = self.context_path().to_string().await?;
+        Ok(strategy.module_id(&ident, &context_path).await?.cell())
     }
 }
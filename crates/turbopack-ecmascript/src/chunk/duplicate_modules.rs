@@ -0,0 +1,147 @@
+use std::fmt::Write;
+
+use anyhow::Result;
+use indexmap::IndexMap;
+use turbo_tasks::{primitives::StringVc, CompletionVc, TryJoinIterExt, ValueToString};
+use turbo_tasks_fs::FileSystemPathVc;
+use turbopack_core::{
+    chunk::ChunkItem,
+    ident::AssetIdentVc,
+    issue::{Issue, IssueSeverity, IssueSeverityVc, IssueVc},
+};
+
+use super::content::EcmascriptChunkContentVc;
+
+/// Reported when distinct modules in a chunk share the same ident. They would
+/// get the same module id and both be registered at runtime.
+#[turbo_tasks::value(shared)]
+pub struct DuplicateModuleIssue {
+    pub ident: String,
+    /// The idents of all modules that share the ident string.
+    pub modules: Vec<AssetIdentVc>,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for DuplicateModuleIssue {
+    #[turbo_tasks::function]
+    fn severity(&self) -> IssueSeverityVc {
+        IssueSeverity::Warning.into()
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> StringVc {
+        StringVc::cell("chunking".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> StringVc {
+        StringVc::cell(format!(
+            "{} distinct modules have the same ident {}",
+            self.modules.len(),
+            self.ident
+        ))
+    }
+
+    #[turbo_tasks::function]
+    fn context(&self) -> FileSystemPathVc {
+        self.modules[0].path()
+    }
+
+    #[turbo_tasks::function]
+    async fn description(&self) -> Result<StringVc> {
+        let mut description = "The modules will get the same module id and overwrite each other \
+                               at runtime. They were created from these sources:"
+            .to_string();
+        for module in self.modules.iter() {
+            write!(description, "\n- {}", module.path().to_string().await?)?;
+        }
+        Ok(StringVc::cell(description))
+    }
+}
+
+/// Emits a [DuplicateModuleIssue] for every ident that is shared by distinct
+/// chunk items of the chunk content.
+///
+/// This stringifies the ident of every chunk item, so it should only be used
+/// when the chunking context asks for it.
+#[turbo_tasks::function]
+pub(crate) async fn detect_duplicate_modules(
+    content: EcmascriptChunkContentVc,
+) -> Result<CompletionVc> {
+    let content = content.await?;
+    let idents = content
+        .chunk_items
+        .iter()
+        .map(|chunk_item| async move {
+            let ident = chunk_item.asset_ident();
+            Ok((ident.to_string().await?.clone_value(), ident))
+        })
+        .try_join()
+        .await?;
+
+    for (string, modules) in shared_idents(idents) {
+        DuplicateModuleIssue {
+            ident: string,
+            modules,
+        }
+        .cell()
+        .as_issue()
+        .emit();
+    }
+
+    Ok(CompletionVc::new())
+}
+
+/// Groups the items by their ident string and returns the groups with more
+/// than one item, in the order of their first item.
+fn shared_idents<T>(items: impl IntoIterator<Item = (String, T)>) -> Vec<(String, Vec<T>)> {
+    let mut by_ident: IndexMap<String, Vec<T>> = IndexMap::new();
+    for (string, item) in items {
+        by_ident.entry(string).or_default().push(item);
+    }
+    by_ident
+        .into_iter()
+        .filter(|(_, items)| items.len() > 1)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::shared_idents;
+
+    fn items(idents: &[&str]) -> Vec<(String, usize)> {
+        idents
+            .iter()
+            .enumerate()
+            .map(|(index, ident)| (ident.to_string(), index))
+            .collect()
+    }
+
+    #[test]
+    fn reports_only_shared_idents() {
+        let shared = shared_idents(items(&[
+            "[project]/b.js",
+            "[project]/a.js",
+            "[project]/b.js",
+        ]));
+        assert_eq!(shared, vec![("[project]/b.js".to_string(), vec![0, 2])]);
+    }
+
+    #[test]
+    fn keeps_order_of_first_occurrence() {
+        let shared = shared_idents(items(&["c", "a", "a", "c", "b", "a"]));
+        assert_eq!(
+            shared,
+            vec![
+                ("c".to_string(), vec![0, 3]),
+                ("a".to_string(), vec![1, 2, 5])
+            ]
+        );
+    }
+
+    #[test]
+    fn no_duplicates() {
+        assert!(shared_idents(items(&["a", "b", "c"])).is_empty());
+        assert!(shared_idents(items(&[])).is_empty());
+    }
+}
@@ -41,6 +41,7 @@ impl EcmascriptChunkItemContentVc {
     pub async fn new(
         content: EcmascriptModuleContentVc,
         context: EcmascriptChunkingContextVc,
+        async_module: bool,
     ) -> Result<Self> {
         let refresh = *context.has_react_refresh().await?;
         let externals = *context.environment().node_externals().await?;
@@ -53,15 +54,18 @@ impl EcmascriptChunkItemContentVc {
                 EcmascriptChunkItemOptions {
                     refresh,
                     externals,
+                    async_module,
                     ..Default::default()
                 }
             } else {
                 EcmascriptChunkItemOptions {
                     refresh,
                     externals,
+                    async_module,
                     // These things are not available in ESM
                     module: true,
                     exports: true,
+                    require: content.free_require,
                     this: true,
                     ..Default::default()
                 }
@@ -90,6 +94,7 @@ impl EcmascriptChunkItemContentVc {
         ];
         if this.options.externals {
             args.push("x: __turbopack_external_require__");
+            args.push("P: __turbopack_resolve_absolute_path__");
         }
         if this.options.refresh {
             args.push("k: __turbopack_refresh__");
@@ -100,6 +105,12 @@ impl EcmascriptChunkItemContentVc {
         if this.options.exports {
             args.push("e: exports");
         }
+        if this.options.require {
+            args.push("r: require");
+        }
+        if this.options.async_module {
+            args.push("a: __turbopack_async_module__");
+        }
         let mut code = CodeBuilder::default();
         let args = FormatIter(|| args.iter().copied().intersperse(", "));
         if this.options.this {
@@ -107,9 +118,16 @@ impl EcmascriptChunkItemContentVc {
         } else {
             write!(code, "(({{ {} }}) => (() => {{\n\n", args,)?;
         }
+        if this.options.async_module {
+            code += "__turbopack_async_module__(async (__turbopack_wait_for_async_dependencies__) \
+                     => {\n\n";
+        }
 
         let source_map = this.source_map.map(|sm| sm.as_generate_source_map());
         code.push_source(&this.inner_code, source_map);
+        if this.options.async_module {
+            code += "\n});";
+        }
         if this.options.this {
             code += "\n}.call(this) })";
         } else {
@@ -130,10 +148,19 @@ pub struct EcmascriptChunkItemOptions {
     /// Whether this chunk item's module factory should include an `exports`
     /// argument.
     pub exports: bool,
-    /// Whether this chunk item's module factory should include a
-    /// `__turbopack_external_require__` argument.
+    /// Whether this chunk item's module factory should include a `require`
+    /// argument, which is the same as `__turbopack_require__`.
+    pub require: bool,
+    /// Whether this chunk item's module factory should include the
+    /// `__turbopack_external_require__` and
+    /// `__turbopack_resolve_absolute_path__` arguments of the Node.js runtimes.
     pub externals: bool,
     pub this: bool,
+    /// Whether this chunk item is an async module, i.e. it has a top-level
+    /// `await` or imports async modules. Its code is then evaluated in an
+    /// async function passed to `__turbopack_async_module__`, which waits for
+    /// the async modules it imports before the rest of the module is evaluated.
+    pub async_module: bool,
     pub placeholder_for_future_extensions: (),
 }
 
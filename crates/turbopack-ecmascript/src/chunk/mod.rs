@@ -1,23 +1,26 @@
 pub(crate) mod content;
 pub(crate) mod context;
 pub(crate) mod data;
+pub(crate) mod duplicate_modules;
 pub(crate) mod item;
+pub(crate) mod module_id_map;
+pub mod module_id_strategy;
 pub(crate) mod placeable;
 
-use std::fmt::Write;
+use std::{collections::HashSet, fmt::Write};
 
 use anyhow::{anyhow, bail, Result};
 use indexmap::IndexSet;
 use turbo_tasks::{
-    primitives::{StringReadRef, StringVc, UsizeVc},
+    primitives::{StringReadRef, StringVc, StringsVc, UsizeVc},
     TryJoinIterExt, Value, ValueToString, ValueToStringVc,
 };
 use turbo_tasks_fs::FileSystemPathOptionVc;
 use turbopack_core::{
     asset::{Asset, AssetContentVc, AssetVc},
     chunk::{
-        availability_info::AvailabilityInfo, Chunk, ChunkItem, ChunkVc, ChunkingContextVc,
-        ChunksVc, ModuleIdsVc,
+        availability_info::AvailabilityInfo, Chunk, ChunkItem, ChunkVc, ChunkableModuleReference,
+        ChunkingContext, ChunkingContextVc, ChunksVc, ModuleIdsVc,
     },
     ident::{AssetIdent, AssetIdentVc},
     introspect::{
@@ -27,21 +30,37 @@ use turbopack_core::{
     reference::AssetReferencesVc,
 };
 
-use self::content::ecmascript_chunk_content;
+use self::content::{ecmascript_chunk_content, split_ecmascript_chunk_content};
 pub use self::{
-    content::{EcmascriptChunkContent, EcmascriptChunkContentVc},
+    content::{
+        EcmascriptChunkContent, EcmascriptChunkContentVc, EcmascriptChunkContents,
+        EcmascriptChunkContentsVc,
+    },
     context::{EcmascriptChunkingContext, EcmascriptChunkingContextVc},
     data::EcmascriptChunkData,
+    duplicate_modules::{DuplicateModuleIssue, DuplicateModuleIssueVc},
     item::{
         EcmascriptChunkItem, EcmascriptChunkItemContent, EcmascriptChunkItemContentVc,
         EcmascriptChunkItemOptions, EcmascriptChunkItemVc,
     },
+    module_id_map::{
+        module_idents, ModuleIdMap, ModuleIdMapVc, OptionModuleIdMap, OptionModuleIdMapVc,
+    },
+    module_id_strategy::{
+        validate_module_ids, ModuleIdAssignment, ModuleIdAssignmentVc, ModuleIdStrategy,
+        ModuleIdStrategyVc,
+    },
     placeable::{
-        EcmascriptChunkPlaceable, EcmascriptChunkPlaceableVc, EcmascriptChunkPlaceables,
-        EcmascriptChunkPlaceablesVc, EcmascriptExports, EcmascriptExportsVc,
+        EcmascriptAsyncModule, EcmascriptAsyncModuleVc, EcmascriptChunkPlaceable,
+        EcmascriptChunkPlaceableVc, EcmascriptChunkPlaceables, EcmascriptChunkPlaceablesVc,
+        EcmascriptConstantModule, EcmascriptConstantModuleVc, EcmascriptExports,
+        EcmascriptExportsVc,
     },
 };
-use crate::utils::FormatIter;
+use crate::{
+    references::esm::{async_module::is_async_module, base::ReferencedAsset, EsmAssetReferenceVc},
+    utils::FormatIter,
+};
 
 #[turbo_tasks::value]
 pub struct EcmascriptChunk {
@@ -167,6 +186,94 @@ impl EcmascriptChunkVc {
         Ok(ModuleIdsVc::cell(entries))
     }
 
+    /// The idents of the modules of the chunk in the order the runtime
+    /// evaluates them, starting from its entries. The modules statically
+    /// imported by a module are evaluated in order before its body, and an
+    /// import of a module which is already being evaluated is skipped, like
+    /// the runtime does for circular imports. Modules of parent chunks are
+    /// considered evaluated already, and `require()`d modules are evaluated
+    /// when the call runs, so neither is listed.
+    ///
+    /// Modules which have to be evaluated asynchronously because of a
+    /// top-level `await` are suffixed with ` (async)`. They are listed when
+    /// their evaluation starts, but the rest of their body, and the body of
+    /// their importers, only runs when the awaited promises resolve.
+    #[turbo_tasks::function]
+    pub async fn evaluation_order(self) -> Result<StringsVc> {
+        enum Step {
+            Enter(EcmascriptChunkPlaceableVc),
+            Evaluate(EcmascriptChunkPlaceableVc),
+        }
+
+        let this = self.await?;
+        let content = ecmascript_chunk_content(
+            this.context,
+            this.main_entries,
+            this.omit_entries,
+            Value::new(this.availability_info),
+        )
+        .await?;
+        let chunk_assets: HashSet<_> = content
+            .chunk_item_assets
+            .iter()
+            .map(|asset| asset.resolve())
+            .try_join()
+            .await?
+            .into_iter()
+            .collect();
+
+        let mut order = Vec::new();
+        let mut visited = HashSet::new();
+        let mut stack: Vec<_> = this
+            .main_entries
+            .await?
+            .iter()
+            .rev()
+            .map(|&entry| Step::Enter(entry))
+            .collect();
+        while let Some(step) = stack.pop() {
+            match step {
+                Step::Enter(module) => {
+                    let module = module.resolve().await?;
+                    if !visited.insert(module)
+                        || !chunk_assets.contains(&module.as_asset().resolve().await?)
+                    {
+                        continue;
+                    }
+                    stack.push(Step::Evaluate(module));
+                    let mut imports = Vec::new();
+                    for reference in module.references().await?.iter() {
+                        let Some(reference) = EsmAssetReferenceVc::resolve_from(reference).await?
+                        else {
+                            continue;
+                        };
+                        // Inlined modules are not evaluated.
+                        if reference.chunking_type().await?.is_none() {
+                            continue;
+                        }
+                        if let ReferencedAsset::Some(imported) =
+                            &*reference.get_referenced_asset().await?
+                        {
+                            imports.push(Step::Enter(*imported));
+                        }
+                    }
+                    stack.extend(imports.into_iter().rev());
+                }
+                Step::Evaluate(module) => {
+                    let ident = module.ident().to_string().await?;
+                    let async_module =
+                        *is_async_module(module, Value::new(this.availability_info)).await?;
+                    order.push(if async_module {
+                        format!("{ident} (async)")
+                    } else {
+                        ident.to_string()
+                    });
+                }
+            }
+        }
+        Ok(StringsVc::cell(order))
+    }
+
     #[turbo_tasks::function]
     pub async fn compare(
         left: EcmascriptChunkVc,
@@ -286,6 +393,29 @@ impl EcmascriptChunkVc {
         ))
     }
 
+    /// The content of the chunk, split into parts according to the chunk size
+    /// budget of the chunking context. Without a budget, this is the whole
+    /// content.
+    #[turbo_tasks::function]
+    pub async fn content_parts(self) -> Result<EcmascriptChunkContentsVc> {
+        let this = self.await?;
+        let context: ChunkingContextVc = this.context.into();
+        let content = self.chunk_content();
+        Ok(match *context.chunk_size_budget().await? {
+            Some(budget) => split_ecmascript_chunk_content(this.context, content, budget),
+            None => EcmascriptChunkContentsVc::cell(vec![content]),
+        })
+    }
+
+    #[turbo_tasks::function]
+    pub async fn content_part(self, index: usize) -> Result<EcmascriptChunkContentVc> {
+        let parts = self.content_parts().await?;
+        let Some(&part) = parts.get(index) else {
+            bail!("chunk content part {index} does not exist");
+        };
+        Ok(part)
+    }
+
     #[turbo_tasks::function]
     pub async fn main_entries(self) -> Result<EcmascriptChunkPlaceablesVc> {
         let this = self.await?;
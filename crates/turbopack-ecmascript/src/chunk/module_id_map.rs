@@ -0,0 +1,230 @@
+use std::collections::{BTreeMap, HashSet};
+
+use anyhow::{bail, Result};
+use turbo_tasks::{primitives::StringsVc, CompletionVc, TryJoinIterExt, ValueToString};
+use turbo_tasks_fs::{File, FileContent, FileJsonContent, FileSystemPathVc};
+use turbo_tasks_hash::hash_xxh3_hash64;
+use turbopack_core::{
+    asset::{Asset, AssetsVc},
+    reference::all_assets,
+};
+
+/// The size of the id space that hashed module ids are initially mapped into.
+/// Ids only exceed this when there are collisions at the end of the space.
+const MODULE_ID_SPACE: u32 = 100_000;
+
+/// Assigns short numeric module ids to module idents.
+///
+/// Ids are derived from the hash of the ident, so they don't change when
+/// unrelated modules are added or removed. Collisions are resolved by picking
+/// the next free id. Existing assignments never change, which allows to
+/// persist the map and reuse it in later builds for long-term caching.
+#[turbo_tasks::value(shared)]
+#[derive(Debug, Default, Clone)]
+pub struct ModuleIdMap {
+    ids: BTreeMap<String, u32>,
+    used: HashSet<u32>,
+}
+
+impl ModuleIdMap {
+    /// Parses a persisted module id map.
+    pub fn from_json(json: serde_json::Value) -> Result<Self> {
+        let ids: BTreeMap<String, u32> = serde_json::from_value(json)?;
+        let mut used = HashSet::with_capacity(ids.len());
+        for (ident, id) in ids.iter() {
+            if !used.insert(*id) {
+                bail!("module id {id} of {ident} is assigned to multiple modules");
+            }
+        }
+        Ok(Self { ids, used })
+    }
+
+    /// Serializes the module id map so it can be restored with
+    /// [ModuleIdMap::from_json].
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(&self.ids)?)
+    }
+
+    /// Returns the id assigned to the ident, if any.
+    pub fn get(&self, ident: &str) -> Option<u32> {
+        self.ids.get(ident).copied()
+    }
+
+    /// Returns the id of the ident, assigning a new one if needed.
+    pub fn get_or_assign(&mut self, ident: &str) -> u32 {
+        if let Some(id) = self.ids.get(ident) {
+            return *id;
+        }
+        let mut id = (hash_xxh3_hash64(ident) % MODULE_ID_SPACE as u64) as u32;
+        while self.used.contains(&id) {
+            id += 1;
+        }
+        self.used.insert(id);
+        self.ids.insert(ident.to_string(), id);
+        id
+    }
+
+    /// Assigns ids to all idents which don't have one yet. The idents are
+    /// assigned in sorted order, so colliding idents get the same ids
+    /// regardless of the order they are passed in.
+    pub fn assign_all<'a>(&mut self, idents: impl IntoIterator<Item = &'a str>) {
+        let mut idents: Vec<_> = idents.into_iter().collect();
+        idents.sort_unstable();
+        for ident in idents {
+            self.get_or_assign(ident);
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+}
+
+#[turbo_tasks::value(transparent)]
+pub struct OptionModuleIdMap(Option<ModuleIdMapVc>);
+
+#[turbo_tasks::value_impl]
+impl ModuleIdMapVc {
+    /// Creates an empty module id map.
+    #[turbo_tasks::function]
+    pub fn empty() -> Self {
+        ModuleIdMap::default().cell()
+    }
+
+    /// Restores a module id map that was persisted with
+    /// [ModuleIdMapVc::store]. A missing file results in an empty map.
+    #[turbo_tasks::function]
+    pub async fn load(path: FileSystemPathVc) -> Result<Self> {
+        Ok(match &*path.read_json().await? {
+            FileJsonContent::Content(json) => ModuleIdMap::from_json(json.clone())?.cell(),
+            FileJsonContent::NotFound => ModuleIdMap::default().cell(),
+            FileJsonContent::Unparseable(e) => {
+                bail!(
+                    "module id map {} is not valid JSON: {}",
+                    path.await?.path,
+                    e
+                )
+            }
+        })
+    }
+
+    /// Returns a map with the assignments of this map, which also assigns ids
+    /// to the `idents` of all modules of a build, see
+    /// [ModuleIdMap::assign_all].
+    #[turbo_tasks::function]
+    pub async fn with_idents(self, idents: StringsVc) -> Result<Self> {
+        let mut map = self.await?.clone_value();
+        map.assign_all(idents.await?.iter().map(|ident| ident.as_str()));
+        Ok(map.cell())
+    }
+
+    /// Persists the assignments to the given path.
+    #[turbo_tasks::function]
+    pub async fn store(self, path: FileSystemPathVc) -> Result<CompletionVc> {
+        let json = self.await?.to_json()?;
+        Ok(path.write(FileContent::Content(File::from(json)).cell()))
+    }
+}
+
+/// Returns the ident strings of the `entries` and all modules they reference,
+/// e.g. to assign ids to all modules of a build with
+/// [ModuleIdMapVc::with_idents].
+#[turbo_tasks::function]
+pub async fn module_idents(entries: AssetsVc) -> Result<StringsVc> {
+    let assets = entries
+        .await?
+        .iter()
+        .map(|&entry| async move { Ok(all_assets(entry).await?) })
+        .try_join()
+        .await?;
+    let mut idents = assets
+        .iter()
+        .flat_map(|assets| assets.iter())
+        .map(|asset| async move { Ok(asset.ident().to_string().await?.clone_value()) })
+        .try_join()
+        .await?;
+    idents.sort_unstable();
+    idents.dedup();
+    Ok(StringsVc::cell(idents))
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use super::{ModuleIdMap, MODULE_ID_SPACE};
+
+    #[test]
+    fn assigns_stable_short_ids() {
+        let mut map = ModuleIdMap::default();
+        let a = map.get_or_assign("[project]/a.js (ecmascript)");
+        let b = map.get_or_assign("[project]/b.js (ecmascript)");
+        assert_ne!(a, b);
+        assert!(a < MODULE_ID_SPACE);
+        assert!(b < MODULE_ID_SPACE);
+        assert_eq!(map.get_or_assign("[project]/a.js (ecmascript)"), a);
+
+        // Ids are independent of the order of assignment.
+        let mut other = ModuleIdMap::default();
+        assert_eq!(other.get_or_assign("[project]/b.js (ecmascript)"), b);
+        assert_eq!(other.get_or_assign("[project]/a.js (ecmascript)"), a);
+    }
+
+    #[test]
+    fn persistence_round_trip() -> Result<()> {
+        let mut map = ModuleIdMap::default();
+        let a = map.get_or_assign("a");
+        let b = map.get_or_assign("b");
+
+        let json = map.to_json()?;
+        let mut restored = ModuleIdMap::from_json(serde_json::from_str(&json)?)?;
+        assert_eq!(restored, map);
+        assert_eq!(restored.get("a"), Some(a));
+        assert_eq!(restored.get_or_assign("b"), b);
+        assert_eq!(restored.len(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn resolves_collisions_after_restore() -> Result<()> {
+        let mut fresh = ModuleIdMap::default();
+        let hashed = fresh.get_or_assign("new");
+
+        // A persisted map where another module already occupies the id that
+        // "new" hashes to.
+        let mut restored =
+            ModuleIdMap::from_json(serde_json::json!({ "old": hashed, "other": hashed + 1 }))?;
+        let id = restored.get_or_assign("new");
+        assert_eq!(id, hashed + 2);
+        // Existing assignments are unchanged.
+        assert_eq!(restored.get("old"), Some(hashed));
+        assert_eq!(restored.get("other"), Some(hashed + 1));
+        Ok(())
+    }
+
+    #[test]
+    fn assignment_is_independent_of_order() {
+        let idents = ["c", "a", "b", "d", "e"];
+        let mut forward = ModuleIdMap::default();
+        forward.assign_all(idents);
+        let mut backward = ModuleIdMap::default();
+        backward.assign_all(idents.into_iter().rev());
+        assert_eq!(forward, backward);
+        assert_eq!(forward.len(), idents.len());
+
+        // Already assigned idents keep their ids.
+        let a = forward.get("a");
+        forward.assign_all(["a", "f"]);
+        assert_eq!(forward.get("a"), a);
+        assert_eq!(forward.len(), idents.len() + 1);
+    }
+
+    #[test]
+    fn rejects_duplicate_ids() {
+        assert!(ModuleIdMap::from_json(serde_json::json!({ "a": 1, "b": 1 })).is_err());
+    }
+}
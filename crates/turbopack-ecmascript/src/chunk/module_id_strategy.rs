@@ -0,0 +1,309 @@
+use std::collections::{hash_map::Entry, BTreeMap, HashMap};
+
+use anyhow::{bail, Result};
+use turbo_tasks::{primitives::StringsVc, CompletionVc, ValueToString};
+use turbo_tasks_hash::{encode_hex, hash_xxh3_hash64};
+use turbopack_core::chunk::{ChunkItem, ChunksVc, ModuleId};
+
+use super::EcmascriptChunkVc;
+
+/// The number of hex digits of a 64 bit hash.
+const MAX_HASH_LENGTH: usize = 16;
+
+/// How the module ids of chunk items are derived from their idents.
+#[turbo_tasks::value(shared, serialization = "auto_for_input")]
+#[derive(Debug, Default, Clone, Copy, Hash, PartialOrd, Ord)]
+pub enum ModuleIdStrategy {
+    /// The full ident, e.g. `[project]/src/index.js (ecmascript)`. This is
+    /// the most readable, for development.
+    #[default]
+    Ident,
+    /// The first `length` hex digits of the hash of the ident, at least one
+    /// and at most 16. This is the smallest, for production. Distinct modules
+    /// which get the same id fail the build when their chunk group is
+    /// generated.
+    Hashed { length: usize },
+    /// Like [ModuleIdStrategy::Hashed], but ids are lengthened until they are
+    /// unique among the modules of the `assignment`, so they can't collide.
+    /// The assignment must contain the idents of all chunk items.
+    UniqueHashed { assignment: ModuleIdAssignmentVc },
+    /// The ident relative to the context path, e.g. `src/index.js
+    /// (ecmascript)`. The ids only change when the paths within the package
+    /// do, for library builds whose consumers depend on the ids.
+    Named,
+}
+
+impl ModuleIdStrategy {
+    /// Returns the module id of the chunk item with the ident string `ident`.
+    pub async fn module_id(&self, ident: &str, context_path: &str) -> Result<ModuleId> {
+        Ok(match self {
+            ModuleIdStrategy::Ident => ModuleId::String(ident.to_string()),
+            ModuleIdStrategy::Hashed { length } => {
+                let mut hash = hash_ident(ident);
+                hash.truncate((*length).clamp(1, MAX_HASH_LENGTH));
+                ModuleId::String(hash)
+            }
+            ModuleIdStrategy::UniqueHashed { assignment } => {
+                let Some(id) = assignment.await?.get(ident) else {
+                    bail!("no module id has been assigned to {}", ident);
+                };
+                ModuleId::String(id.to_string())
+            }
+            ModuleIdStrategy::Named => {
+                let ident = ident.replace('\\', "/");
+                let context_path = context_path.replace('\\', "/");
+                let relative = ident
+                    .strip_prefix(context_path.trim_end_matches('/'))
+                    .and_then(|relative| relative.strip_prefix('/'));
+                ModuleId::String(relative.unwrap_or(&ident).to_string())
+            }
+        })
+    }
+}
+
+/// The hex encoded hash of a module ident, whose prefixes are hashed module
+/// ids.
+fn hash_ident(ident: &str) -> String {
+    encode_hex(hash_xxh3_hash64(ident))
+}
+
+/// Assigns short module ids to a set of module idents, see
+/// [ModuleIdStrategy::UniqueHashed].
+///
+/// Every id is the shortest prefix of the hash of the ident that is at least
+/// `min_length` digits long and isn't shared with the hash of another ident in
+/// the set. The ids are therefore independent of the order of the idents, and
+/// only change when an ident is added whose hash shares a prefix of at least
+/// `min_length` digits.
+#[turbo_tasks::value]
+#[derive(Debug)]
+pub struct ModuleIdAssignment {
+    ids: BTreeMap<String, String>,
+}
+
+#[turbo_tasks::value_impl]
+impl ModuleIdAssignmentVc {
+    /// Assigns ids to the `idents`, e.g. the
+    /// [module_idents](super::module_idents) of the entries of a build.
+    #[turbo_tasks::function]
+    pub async fn new(idents: StringsVc, min_length: usize) -> Result<Self> {
+        Ok(ModuleIdAssignment::from_idents(
+            idents.await?.iter().map(|ident| ident.as_str()),
+            min_length,
+        )?
+        .cell())
+    }
+}
+
+impl ModuleIdAssignment {
+    /// Assigns ids to the given module idents, see [ModuleIdAssignment].
+    pub fn from_idents<'a>(
+        idents: impl IntoIterator<Item = &'a str>,
+        min_length: usize,
+    ) -> Result<Self> {
+        let mut hashes: Vec<(String, &str)> = idents
+            .into_iter()
+            .map(|ident| (hash_ident(ident), ident))
+            .collect();
+        hashes.sort();
+        hashes.dedup();
+
+        // In sorted order, the longest prefix a hash shares with any other
+        // hash is the one it shares with one of its neighbors.
+        let common_prefix =
+            |a: &str, b: &str| a.bytes().zip(b.bytes()).take_while(|(a, b)| a == b).count();
+        let mut ids = BTreeMap::new();
+        for (i, (hash, ident)) in hashes.iter().enumerate() {
+            let mut length = min_length.clamp(1, MAX_HASH_LENGTH);
+            for neighbor in [i.checked_sub(1), Some(i + 1)].into_iter().flatten() {
+                let Some((neighbor_hash, neighbor_ident)) = hashes.get(neighbor) else {
+                    continue;
+                };
+                if neighbor_hash == hash {
+                    bail!(
+                        "the module idents {} and {} have the same hash {}",
+                        ident,
+                        neighbor_ident,
+                        hash
+                    );
+                }
+                length = length.max(common_prefix(hash, neighbor_hash) + 1);
+            }
+            ids.insert(ident.to_string(), hash[..length].to_string());
+        }
+        Ok(ModuleIdAssignment { ids })
+    }
+
+    /// Returns the id assigned to the ident, if any.
+    pub fn get(&self, ident: &str) -> Option<&str> {
+        self.ids.get(ident).map(|id| id.as_str())
+    }
+
+    /// Iterates over the idents and their ids, ordered by ident.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.ids
+            .iter()
+            .map(|(ident, id)| (ident.as_str(), id.as_str()))
+    }
+}
+
+/// Fails when distinct chunk items in the ecmascript chunks of a chunk group
+/// have the same module id, naming both of them.
+#[turbo_tasks::function]
+pub async fn validate_module_ids(chunks: ChunksVc) -> Result<CompletionVc> {
+    let mut idents = HashMap::new();
+    for &chunk in chunks.await?.iter() {
+        let Some(chunk) = EcmascriptChunkVc::resolve_from(chunk).await? else {
+            continue;
+        };
+        for chunk_item in chunk.chunk_content().await?.chunk_items.iter() {
+            let id = chunk_item.id().await?;
+            let ident = chunk_item.asset_ident().to_string().await?;
+            match idents.entry(id.clone_value()) {
+                Entry::Occupied(entry) => {
+                    if *entry.get() != ident {
+                        bail!(
+                            "module id {} is used by both {} and {}",
+                            entry.key(),
+                            entry.get(),
+                            ident
+                        );
+                    }
+                }
+                Entry::Vacant(entry) => {
+                    entry.insert(ident);
+                }
+            }
+        }
+    }
+    Ok(CompletionVc::new())
+}
+
+#[cfg(test)]
+mod tests {
+    use turbopack_core::chunk::ModuleId;
+
+    use super::{ModuleIdAssignment, ModuleIdStrategy};
+
+    async fn id(strategy: ModuleIdStrategy, ident: &str, context_path: &str) -> String {
+        match strategy.module_id(ident, context_path).await.unwrap() {
+            ModuleId::String(id) => id,
+            ModuleId::Number(id) => panic!("unexpected numeric id {id}"),
+        }
+    }
+
+    fn assigned_ids(idents: &[&str], min_length: usize) -> Vec<(String, String)> {
+        ModuleIdAssignment::from_idents(idents.iter().copied(), min_length)
+            .unwrap()
+            .iter()
+            .map(|(ident, id)| (ident.to_string(), id.to_string()))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn named_ids_are_relative_to_the_context_path() {
+        let ident = "[project]/packages/lib/src/index.js (ecmascript)";
+        assert_eq!(
+            id(ModuleIdStrategy::Named, ident, "[project]/packages/lib").await,
+            "src/index.js (ecmascript)"
+        );
+        assert_eq!(
+            id(
+                ModuleIdStrategy::Named,
+                "[project]\\packages\\lib\\src\\index.js (ecmascript)",
+                "[project]\\packages\\lib"
+            )
+            .await,
+            "src/index.js (ecmascript)"
+        );
+        assert_eq!(
+            id(ModuleIdStrategy::Named, ident, "[project]/").await,
+            "packages/lib/src/index.js (ecmascript)"
+        );
+        // Modules outside of the context path keep their full ident, even when
+        // the context path occurs later in the ident.
+        assert_eq!(
+            id(ModuleIdStrategy::Named, ident, "[project]/packages/app").await,
+            ident
+        );
+        let ident = "[project]/packages/app/index.js (ecmascript) <[project]/packages/lib/a.js>";
+        assert_eq!(
+            id(ModuleIdStrategy::Named, ident, "[project]/packages/lib").await,
+            ident
+        );
+        // Only whole path segments are stripped.
+        assert_eq!(
+            id(
+                ModuleIdStrategy::Named,
+                "[project]/packages/library/index.js (ecmascript)",
+                "[project]/packages/lib"
+            )
+            .await,
+            "[project]/packages/library/index.js (ecmascript)"
+        );
+    }
+
+    #[tokio::test]
+    async fn hashed_ids_have_the_configured_length() {
+        let ident = "[project]/src/index.js (ecmascript)";
+        let short = id(ModuleIdStrategy::Hashed { length: 4 }, ident, "[project]").await;
+        let long = id(ModuleIdStrategy::Hashed { length: 8 }, ident, "[project]").await;
+        assert_eq!(short.len(), 4);
+        assert!(long.starts_with(&short));
+        let empty = id(ModuleIdStrategy::Hashed { length: 0 }, ident, "[project]").await;
+        assert_eq!(empty.len(), 1);
+        assert_eq!(id(ModuleIdStrategy::Ident, ident, "[project]").await, ident);
+    }
+
+    #[test]
+    fn assigned_ids_are_independent_of_order() {
+        let idents = [
+            "[project]/src/a.js (ecmascript)",
+            "[project]/src/b.js (ecmascript)",
+            "[project]/src/c.js (ecmascript)",
+        ];
+        let mut reversed = idents;
+        reversed.reverse();
+        assert_eq!(assigned_ids(&idents, 4), assigned_ids(&reversed, 4));
+        assert!(assigned_ids(&idents, 4).iter().all(|(_, id)| id.len() >= 4));
+
+        // Duplicates don't affect the ids.
+        let duplicated = [idents[0], idents[1], idents[0], idents[2]];
+        assert_eq!(assigned_ids(&duplicated, 4), assigned_ids(&idents, 4));
+    }
+
+    #[test]
+    fn assigned_ids_are_lengthened_on_collision() {
+        // Pick many idents so that some share their first digit.
+        let idents: Vec<String> = (0..64).map(|i| format!("[project]/{i}.js")).collect();
+        let idents: Vec<&str> = idents.iter().map(|ident| ident.as_str()).collect();
+        let assignment = assigned_ids(&idents, 1);
+
+        let mut unique: Vec<&String> = assignment.iter().map(|(_, id)| id).collect();
+        unique.sort();
+        unique.dedup();
+        assert_eq!(unique.len(), idents.len());
+        assert!(assignment.iter().any(|(_, id)| id.len() > 1));
+
+        // Ids of modules that don't collide are the same without the others.
+        for (ident, id) in assignment.iter().filter(|(_, id)| id.len() == 1) {
+            assert_eq!(
+                assigned_ids(&[ident.as_str()], 1),
+                vec![(ident.clone(), id.clone())]
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn assigned_ids_are_prefixes_of_hashed_ids() {
+        let ident = "[project]/src/index.js (ecmascript)";
+        let assignment = ModuleIdAssignment::from_idents([ident], 6).unwrap();
+        let hashed = id(ModuleIdStrategy::Hashed { length: 6 }, ident, "[project]").await;
+        assert_eq!(assignment.get(ident), Some(hashed.as_str()));
+
+        let long = ModuleIdAssignment::from_idents([ident], 100).unwrap();
+        let long = long.get(ident).unwrap();
+        assert_eq!(long.len(), 16);
+        assert!(long.starts_with(&hashed));
+    }
+}
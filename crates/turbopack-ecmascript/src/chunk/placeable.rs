@@ -1,4 +1,5 @@
 use anyhow::Result;
+use turbo_tasks::primitives::{BoolVc, OptionStringVc, StringsVc};
 use turbopack_core::{
     asset::{Asset, AssetVc},
     chunk::{ChunkableModule, ChunkableModuleVc},
@@ -6,7 +7,10 @@ use turbopack_core::{
 };
 
 use super::{item::EcmascriptChunkItemVc, EcmascriptChunkingContextVc};
-use crate::references::esm::EsmExportsVc;
+use crate::references::esm::{
+    export::{ExportNames, ExportNamesVc},
+    EsmExportsVc,
+};
 
 #[turbo_tasks::value_trait]
 pub trait EcmascriptChunkPlaceable: ChunkableModule + Module + Asset {
@@ -14,6 +18,25 @@ pub trait EcmascriptChunkPlaceable: ChunkableModule + Module + Asset {
     fn get_exports(&self) -> EcmascriptExportsVc;
 }
 
+/// A module which exports a constant JSON value, e.g. a JSON file. Small
+/// values are inlined into the modules importing them, see
+/// [crate::EcmascriptOptions::inline_json_bytes].
+#[turbo_tasks::value_trait]
+pub trait EcmascriptConstantModule: EcmascriptChunkPlaceable {
+    /// The JSON text of the exported value, or `None` when importing the
+    /// module can't be replaced by the value.
+    fn inline_json(&self) -> OptionStringVc;
+}
+
+/// A module which is evaluated asynchronously regardless of the modules it
+/// imports, e.g. a WebAssembly module which has to be instantiated before its
+/// exports are available. See
+/// [crate::chunk::EcmascriptChunkItemOptions::async_module].
+#[turbo_tasks::value_trait]
+pub trait EcmascriptAsyncModule: EcmascriptChunkPlaceable {
+    fn is_async(&self) -> BoolVc;
+}
+
 #[turbo_tasks::value(transparent)]
 pub struct EcmascriptChunkPlaceables(Vec<EcmascriptChunkPlaceableVc>);
 
@@ -33,3 +56,44 @@ pub enum EcmascriptExports {
     Value,
     None,
 }
+
+#[turbo_tasks::value_impl]
+impl EcmascriptExportsVc {
+    /// The names of the exports, sorted. The default export is listed as
+    /// `"default"`. Names re-exported by `export *` are included as far as they
+    /// can be determined statically, see [EcmascriptExportsVc::is_incomplete].
+    #[turbo_tasks::function]
+    pub async fn names(self) -> Result<StringsVc> {
+        Ok(StringsVc::cell(self.export_names().await?.names.clone()))
+    }
+
+    /// Whether the module might have exports that are not listed by
+    /// [EcmascriptExportsVc::names], e.g. because it is a CommonJS module or
+    /// re-exports from a module whose exports are only known at runtime.
+    #[turbo_tasks::function]
+    pub async fn is_incomplete(self) -> Result<BoolVc> {
+        Ok(BoolVc::cell(self.export_names().await?.incomplete))
+    }
+
+    #[turbo_tasks::function]
+    async fn export_names(self) -> Result<ExportNamesVc> {
+        Ok(match &*self.await? {
+            EcmascriptExports::EsmExports(exports) => exports.export_names(),
+            EcmascriptExports::Value => ExportNames {
+                names: vec!["default".to_string()],
+                incomplete: false,
+            }
+            .cell(),
+            EcmascriptExports::DynamicNamespace | EcmascriptExports::CommonJs => ExportNames {
+                names: Vec::new(),
+                incomplete: true,
+            }
+            .cell(),
+            EcmascriptExports::None => ExportNames {
+                names: Vec::new(),
+                incomplete: false,
+            }
+            .cell(),
+        })
+    }
+}
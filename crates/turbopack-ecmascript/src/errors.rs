@@ -15,5 +15,9 @@ pub mod failed_to_analyse {
         pub const NODE_PROTOBUF_LOADER: &str = "TP1105";
         pub const AMD_DEFINE: &str = "TP1200";
         pub const NEW_URL_IMPORT_META: &str = "TP1201";
+        pub const IMPORT_META_RESOLVE: &str = "TP1202";
+        pub const IMPORT_META_PATH: &str = "TP1203";
+        pub const NEW_WORKER: &str = "TP1204";
+        pub const IMPORT_META_GLOB: &str = "TP1205";
     }
 }
@@ -0,0 +1,284 @@
+use swc_core::{
+    common::EqIgnoreSpan,
+    ecma::{
+        ast::{
+            CallExpr, Callee, ClassDecl, Decl, Expr, ExprStmt, FnDecl, Ident, Lit, MemberExpr,
+            MemberProp, ModuleDecl, ModuleItem, Pat, Stmt, VarDecl,
+        },
+        visit::{VisitMut, VisitMutWith},
+    },
+    quote,
+};
+
+use crate::{magic_identifier, KeepNames};
+
+/// Re-attaches the original `name` of functions and classes with an
+/// `Object.defineProperty` call, so it is not affected by the bindings being
+/// renamed later on. Declarations that are named by the magic default export
+/// identifier are named `default`, like it is specified for anonymous default
+/// exports.
+pub(crate) struct KeepNamesVisitor {
+    keep_names: KeepNames,
+}
+
+impl KeepNamesVisitor {
+    pub(crate) fn new(keep_names: KeepNames) -> Self {
+        KeepNamesVisitor { keep_names }
+    }
+
+    /// Returns the binding of a declaration whose name has to be kept, and
+    /// whether it is a function declaration, which is hoisted.
+    fn named_binding<'a>(&self, decl: &'a Decl) -> Option<(&'a Ident, bool)> {
+        match decl {
+            Decl::Fn(FnDecl { ident, .. }) if self.keep_names.functions => Some((ident, true)),
+            Decl::Class(ClassDecl { ident, .. }) if self.keep_names.classes => Some((ident, false)),
+            Decl::Var(box VarDecl { decls, .. }) => {
+                // Only single declarators are handled, the inferred name of
+                // the anonymous function or class is the name of the binding.
+                let [declarator] = &decls[..] else {
+                    return None;
+                };
+                let (Pat::Ident(binding), Some(init)) = (&declarator.name, &declarator.init) else {
+                    return None;
+                };
+                let keep = match init.as_ref().unwrap_parens() {
+                    Expr::Fn(fn_expr) => self.keep_names.functions && fn_expr.ident.is_none(),
+                    Expr::Arrow(_) => self.keep_names.functions,
+                    Expr::Class(class_expr) => {
+                        self.keep_names.classes && class_expr.ident.is_none()
+                    }
+                    _ => false,
+                };
+                keep.then_some((&binding.id, false))
+            }
+            _ => None,
+        }
+    }
+
+    /// Inserts the `Object.defineProperty` calls for the declarations in a
+    /// list of statements. Calls for function declarations are inserted at the
+    /// start of the list (after directives), since the functions can be used
+    /// before their declaration. Other calls directly follow the declaration.
+    fn insert_name_definitions<T>(&self, items: &mut Vec<T>, as_decl: impl Fn(&T) -> Option<&Decl>)
+    where
+        T: From<Stmt> + AsStmt + EqIgnoreSpan,
+    {
+        let mut hoisted = Vec::new();
+        let mut new_items = Vec::with_capacity(items.len());
+        for item in items.drain(..) {
+            let definition = as_decl(&item)
+                .and_then(|decl| self.named_binding(decl))
+                .map(|(ident, hoist)| (name_definition(ident), hoist));
+            new_items.push(item);
+            match definition {
+                Some((stmt, true)) => hoisted.push(T::from(stmt)),
+                Some((stmt, false)) => new_items.push(T::from(stmt)),
+                None => {}
+            }
+        }
+
+        // Don't add a definition that is already present, e.g. when the
+        // program has been processed before.
+        let mut result: Vec<T> = Vec::with_capacity(new_items.len() + hoisted.len());
+        let directives = new_items
+            .iter()
+            .take_while(|item| item.as_stmt().map_or(false, is_directive))
+            .count();
+        let mut new_items = new_items.into_iter();
+        result.extend(new_items.by_ref().take(directives));
+        result.extend(hoisted);
+        for item in new_items {
+            let is_definition = item.as_stmt().map_or(false, is_name_definition);
+            if is_definition && result.iter().any(|existing| existing.eq_ignore_span(&item)) {
+                continue;
+            }
+            result.push(item);
+        }
+        *items = result;
+    }
+}
+
+/// Access to the statement of a module item or statement.
+trait AsStmt {
+    fn as_stmt(&self) -> Option<&Stmt>;
+}
+
+impl AsStmt for Stmt {
+    fn as_stmt(&self) -> Option<&Stmt> {
+        Some(self)
+    }
+}
+
+impl AsStmt for ModuleItem {
+    fn as_stmt(&self) -> Option<&Stmt> {
+        match self {
+            ModuleItem::Stmt(stmt) => Some(stmt),
+            ModuleItem::ModuleDecl(_) => None,
+        }
+    }
+}
+
+fn name_definition(ident: &Ident) -> Stmt {
+    let name = if *ident.sym == *magic_identifier::mangle("default export") {
+        "default".to_string()
+    } else {
+        ident.sym.to_string()
+    };
+    quote!(
+        "Object.defineProperty($ident, \"name\", { value: $name, configurable: true });" as Stmt,
+        ident = ident.clone(),
+        name: Expr = name.into(),
+    )
+}
+
+fn is_name_definition(stmt: &Stmt) -> bool {
+    let Stmt::Expr(ExprStmt { box expr, .. }) = stmt else {
+        return false;
+    };
+    let Expr::Call(CallExpr {
+        callee: Callee::Expr(box callee),
+        args,
+        ..
+    }) = expr
+    else {
+        return false;
+    };
+    let Expr::Member(MemberExpr {
+        obj: box Expr::Ident(obj),
+        prop: MemberProp::Ident(prop),
+        ..
+    }) = callee
+    else {
+        return false;
+    };
+    if &*obj.sym != "Object" || &*prop.sym != "defineProperty" {
+        return false;
+    }
+    let [_, second, _] = &args[..] else {
+        return false;
+    };
+    matches!(&*second.expr, Expr::Lit(Lit::Str(str)) if &*str.value == "name")
+}
+
+fn is_directive(stmt: &Stmt) -> bool {
+    matches!(stmt, Stmt::Expr(ExprStmt { box expr, .. }) if matches!(expr, Expr::Lit(Lit::Str(_))))
+}
+
+impl VisitMut for KeepNamesVisitor {
+    fn visit_mut_module_items(&mut self, items: &mut Vec<ModuleItem>) {
+        items.visit_mut_children_with(self);
+        self.insert_name_definitions(items, |item| match item {
+            ModuleItem::Stmt(Stmt::Decl(decl)) => Some(decl),
+            ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(export)) => Some(&export.decl),
+            _ => None,
+        });
+    }
+
+    fn visit_mut_stmts(&mut self, stmts: &mut Vec<Stmt>) {
+        stmts.visit_mut_children_with(self);
+        self.insert_name_definitions(stmts, |stmt| match stmt {
+            Stmt::Decl(decl) => Some(decl),
+            _ => None,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use swc_core::{
+        common::{FileName, SourceMap},
+        ecma::{
+            ast::EsVersion,
+            codegen::{text_writer::JsWriter, Emitter},
+            parser::{parse_file_as_module, Syntax},
+            visit::VisitMutWith,
+        },
+    };
+
+    use super::KeepNamesVisitor;
+    use crate::{magic_identifier, KeepNames};
+
+    fn transform(code: &str, keep_names: KeepNames) -> String {
+        let cm = std::sync::Arc::new(SourceMap::default());
+        let fm = cm.new_source_file(FileName::Anon, code.to_string());
+        let mut module = parse_file_as_module(
+            &fm,
+            Syntax::Es(Default::default()),
+            EsVersion::latest(),
+            None,
+            &mut vec![],
+        )
+        .unwrap();
+        module.visit_mut_with(&mut KeepNamesVisitor::new(keep_names));
+        // Running the visitor again must not add the definitions twice.
+        module.visit_mut_with(&mut KeepNamesVisitor::new(keep_names));
+
+        let mut bytes = Vec::new();
+        let mut emitter = Emitter {
+            cfg: Default::default(),
+            cm: cm.clone(),
+            comments: None,
+            wr: JsWriter::new(cm, "\n", &mut bytes, None),
+        };
+        emitter.emit_module(&module).unwrap();
+        String::from_utf8(bytes).unwrap()
+    }
+
+    const ALL: KeepNames = KeepNames {
+        functions: true,
+        classes: true,
+    };
+
+    #[test]
+    fn functions_are_hoisted() {
+        let code = transform("\"use strict\";\nfoo();\nfunction foo() {}\n", ALL);
+        assert_eq!(
+            code,
+            "\"use strict\";\nObject.defineProperty(foo, \"name\", {\n    value: \"foo\",\n    \
+             configurable: true\n});\nfoo();\nfunction foo() {}\n"
+        );
+    }
+
+    #[test]
+    fn anonymous_initializers() {
+        let code = transform(
+            "const Bar = class {};\nconst baz = (() => {});\nconst named = function other() \
+             {};\nconst value = 1;\n",
+            ALL,
+        );
+        assert_eq!(
+            code,
+            "const Bar = class {\n};\nObject.defineProperty(Bar, \"name\", {\n    value: \
+             \"Bar\",\n    configurable: true\n});\nconst baz = \
+             (()=>{});\nObject.defineProperty(baz, \"name\", {\n    value: \"baz\",\n    \
+             configurable: true\n});\nconst named = function other() {};\nconst value = 1;\n"
+        );
+    }
+
+    #[test]
+    fn default_export() {
+        let code = transform(
+            &format!(
+                "class {} {{}}\n",
+                magic_identifier::mangle("default export")
+            ),
+            KeepNames {
+                functions: false,
+                classes: true,
+            },
+        );
+        assert!(code.contains("value: \"default\""), "{code}");
+    }
+
+    #[test]
+    fn disabled() {
+        let code = transform(
+            "function foo() {}\nclass Bar {}\n",
+            KeepNames {
+                functions: false,
+                classes: false,
+            },
+        );
+        assert_eq!(code, "function foo() {}\nclass Bar {\n}\n");
+    }
+}
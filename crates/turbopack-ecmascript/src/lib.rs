@@ -6,15 +6,18 @@
 #![recursion_limit = "256"]
 #![allow(clippy::too_many_arguments)]
 
+pub mod analysis_stats;
 pub mod analyzer;
 pub mod chunk;
 pub mod chunk_group_files_asset;
 pub mod code_gen;
 mod errors;
+mod keep_names;
 pub mod magic_identifier;
 pub(crate) mod manifest;
 pub mod parse;
 mod path_visitor;
+pub mod post_process;
 pub(crate) mod references;
 pub mod resolve;
 pub(crate) mod special_cases;
@@ -26,45 +29,70 @@ pub mod typescript;
 pub mod utils;
 pub mod webpack;
 
-use anyhow::Result;
+use std::{
+    any::Any,
+    collections::HashMap,
+    fmt::Write,
+    panic::{catch_unwind, AssertUnwindSafe},
+};
+
+use anyhow::{bail, Result};
 use chunk::{
     EcmascriptChunkItem, EcmascriptChunkItemVc, EcmascriptChunkPlaceablesVc, EcmascriptChunkVc,
     EcmascriptChunkingContextVc,
 };
 use code_gen::CodeGenerateableVc;
+use indexmap::IndexSet;
+use keep_names::KeepNamesVisitor;
+pub use manifest::loader_item::{ManifestLoaderItem, ManifestLoaderItemVc};
 use parse::{parse, ParseResult};
 pub use parse::{ParseResultSourceMap, ParseResultSourceMapVc};
-use path_visitor::ApplyVisitors;
-use references::AnalyzeEcmascriptModuleResult;
-pub use references::TURBOPACK_HELPER;
+use path_visitor::{ApplyVisitors, AstPath};
+use post_process::{shift_mappings, CodePostProcessorVc};
+pub use references::{
+    esm::{EsmExport, EsmExports, EsmExportsVc},
+    TURBOPACK_HELPER,
+};
+use references::{AnalyzeEcmascriptModuleResult, AnalyzeEcmascriptModuleResultReadRef};
 pub use static_code::{StaticEcmascriptCode, StaticEcmascriptCodeVc};
 use swc_core::{
-    common::GLOBALS,
+    base::SwcComments,
+    common::{
+        comments::{Comment, Comments},
+        Mark, Spanned, GLOBALS,
+    },
     ecma::{
+        ast::{ModuleItem, Program},
         codegen::{text_writer::JsWriter, Emitter},
         visit::{VisitMutWith, VisitMutWithPath},
     },
 };
 pub use transform::{
+    register_transform_plugin, require_transform_plugin, resolve_transform_plugin,
     CustomTransformer, EcmascriptInputTransform, EcmascriptInputTransformsVc,
     OptionTransformPlugin, OptionTransformPluginVc, TransformContext, TransformPlugin,
-    TransformPluginVc, UnsupportedServerActionIssue,
+    TransformPluginFactory, TransformPluginRegistry, TransformPluginVc, TransformRule,
+    TransformRules, TransformRulesVc, UnsupportedServerActionIssue,
 };
 use turbo_tasks::{
-    primitives::StringVc, trace::TraceRawVcs, RawVc, ReadRef, TryJoinIterExt, Value, ValueToString,
+    primitives::{StringVc, StringsVc},
+    trace::TraceRawVcs,
+    CompletionVc, RawVc, ReadRef, TryJoinIterExt, Value, ValueToString,
 };
-use turbo_tasks_fs::{rope::Rope, FileSystemPathVc};
+use turbo_tasks_fs::{rope::Rope, FileContent, FileSystemPathVc};
 use turbopack_core::{
     asset::{Asset, AssetContentVc, AssetOptionVc, AssetVc},
     chunk::{
         availability_info::AvailabilityInfo, ChunkItem, ChunkItemVc, ChunkVc, ChunkableModule,
-        ChunkableModuleVc, ChunkingContextVc, EvaluatableAsset, EvaluatableAssetVc,
+        ChunkableModuleReferenceVc, ChunkableModuleVc, ChunkingContextVc, EvaluatableAsset,
+        EvaluatableAssetVc,
     },
     compile_time_info::CompileTimeInfoVc,
     context::AssetContextVc,
     ident::AssetIdentVc,
-    module::{Module, ModuleVc},
-    reference::{AssetReferencesReadRef, AssetReferencesVc},
+    issue::{code_gen::CodeGenerationIssue, IssueSeverity, IssueSourceVc, IssueVc},
+    module::{Module, ModuleVc, ModulesVc},
+    reference::{AssetReference, AssetReferencesReadRef, AssetReferencesVc},
     reference_type::InnerAssetsVc,
     resolve::{
         origin::{ResolveOrigin, ResolveOriginVc},
@@ -76,21 +104,28 @@ use turbopack_core::{
 
 pub use self::references::AnalyzeEcmascriptModuleResultVc;
 use self::{
+    analysis_stats::ModuleAnalysisStatsVc,
     chunk::{
-        placeable::EcmascriptExportsReadRef, EcmascriptChunkItemContentVc, EcmascriptExportsVc,
+        placeable::EcmascriptExportsReadRef, EcmascriptChunkItemContentVc, EcmascriptExports,
+        EcmascriptExportsVc,
     },
     code_gen::{
         CodeGen, CodeGenerateableWithAvailabilityInfo, CodeGenerateableWithAvailabilityInfoVc,
-        VisitorFactory,
+        CodeGenerationReadRef, VisitorFactory,
     },
     parse::ParseResultVc,
     tree_shake::asset::EcmascriptModulePartAssetVc,
+    typescript::declaration::{typescript_declaration_references, TypescriptReferencesVc},
 };
 use crate::{
+    analyzer::top_level_await::has_top_level_await,
     chunk::{EcmascriptChunkPlaceable, EcmascriptChunkPlaceableVc},
     code_gen::CodeGenerateable,
-    references::analyze_ecmascript_module,
-    transform::remove_shebang,
+    references::{
+        analyze_ecmascript_module, esm::async_module::is_async_module, size_limit::source_size,
+    },
+    transform::{ensure_use_strict, remove_shebang},
+    utils::is_empty_program,
 };
 
 #[turbo_tasks::value(serialization = "auto_for_input")]
@@ -112,8 +147,113 @@ pub struct EcmascriptOptions {
     pub import_parts: bool,
     /// module is forced to a specific type (happens e. g. for .cjs and .mjs)
     pub specified_module_type: SpecifiedModuleType,
+    /// which comments of the source are kept in the emitted module content
+    pub preserve_comments: CommentsMode,
+    /// which `name`s of functions and classes are kept when their bindings are
+    /// renamed in the emitted module content
+    pub keep_names: KeepNames,
+    /// the module format of the emitted module content
+    pub target_module_format: TargetFormat,
+    /// additional transforms selected by the path of the module
+    pub transform_rules: Option<TransformRulesVc>,
+    /// imports which read a global variable instead of referencing a module
+    pub globals_externals: Option<GlobalsExternalsVc>,
+    /// modules whose source is larger than this number of bytes are not
+    /// evaluated by the analysis, only their import and export declarations
+    /// are handled
+    pub max_analysis_bytes: Option<usize>,
+    /// CommonJS modules whose source is larger than this number of bytes are
+    /// emitted as is, without parsing them for the code generation, when the
+    /// analysis found no references in them
+    pub max_codegen_bytes: Option<usize>,
+    /// interop hazards between CommonJS and EcmaScript modules are reported
+    /// as errors instead of warnings
+    pub strict_interop: bool,
+    /// constant modules, e.g. JSON files, whose value is at most this number
+    /// of bytes are inlined into each module importing them with `import`
+    /// instead of being referenced as a separate chunk item
+    pub inline_json_bytes: Option<usize>,
+    /// a final textual pass over the emitted code of the module
+    pub post_processor: Option<CodePostProcessorVc>,
+    /// what the default imports of the module bind to when the imported
+    /// module is a CommonJS module
+    pub cjs_default_interop: CjsDefaultInterop,
 }
 
+/// Controls which comments are kept in the emitted module content.
+#[turbo_tasks::value(serialization = "auto_for_input")]
+#[derive(PartialOrd, Ord, Hash, Debug, Clone, Copy, Default)]
+pub enum CommentsMode {
+    /// All comments are removed.
+    #[default]
+    None,
+    /// Only comments containing `@license` or `@preserve` are kept, as well as
+    /// comments starting with `!`.
+    LicenseOnly,
+    /// All comments are kept.
+    All,
+}
+
+/// Controls whether the `name` property of functions and classes is kept when
+/// their bindings are renamed, e.g. for default exports or by the hygiene
+/// pass. Some libraries (ORMs, DI containers) rely on
+/// `Function.prototype.name`.
+#[turbo_tasks::value(serialization = "auto_for_input")]
+#[derive(PartialOrd, Ord, Hash, Debug, Clone, Copy, Default)]
+pub struct KeepNames {
+    pub functions: bool,
+    pub classes: bool,
+}
+
+/// The module format of the emitted module content. Some deployment targets,
+/// e.g. older Node.js versions or bundlers consuming the output, can't handle
+/// ESM syntax.
+#[turbo_tasks::value(serialization = "auto_for_input")]
+#[derive(PartialOrd, Ord, Hash, Debug, Clone, Copy, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum TargetFormat {
+    /// ESM syntax is kept. CommonJS sources are not converted.
+    Esm,
+    /// ES modules are emitted as strict CommonJS modules, which are evaluated
+    /// with `module`, `exports` and `this`. Their remaining `import`s and
+    /// `export`s are transformed into `require` calls and `exports`
+    /// assignments. Modules with top-level `await` are kept as ES modules and
+    /// an issue is reported for them, since `require` is synchronous.
+    CommonJs,
+    /// The module format of the source is kept.
+    #[default]
+    Preserve,
+}
+
+/// What a default import binds to when the imported module is a CommonJS
+/// module. Runtimes and bundlers disagree about it, so code which was written
+/// for one of them may see `mod.default.default` or `undefined` in another.
+///
+/// The setting applies to the importing module, so modules with different
+/// settings can import the same CommonJS module in one build.
+#[turbo_tasks::value(serialization = "auto_for_input")]
+#[derive(PartialOrd, Ord, Hash, Debug, Clone, Copy, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum CjsDefaultInterop {
+    /// `module.exports.default` when the module sets the `__esModule` flag,
+    /// otherwise `module.exports`, like Babel and TypeScript's
+    /// `esModuleInterop`.
+    #[default]
+    Babel,
+    /// Always `module.exports`, like Node.js and Jest.
+    Node,
+    /// Only `module.exports.default`, without a synthetic default export. It's
+    /// `undefined` when the module doesn't export a `default` property.
+    EsmStrict,
+}
+
+/// Maps import specifiers to the global variables which provide the modules at
+/// runtime, e.g. `react` to `window.React` when the host page loads React from
+/// a CDN. Default imports and namespace imports read the global itself, named
+/// imports read its properties.
+#[turbo_tasks::value(transparent)]
+pub struct GlobalsExternals(HashMap<String, String>);
+
 #[turbo_tasks::value(serialization = "auto_for_input")]
 #[derive(PartialOrd, Ord, Hash, Debug, Copy, Clone)]
 pub enum EcmascriptModuleAssetType {
@@ -132,12 +272,25 @@ fn modifier() -> StringVc {
     StringVc::cell("ecmascript".to_string())
 }
 
+/// The ident modifier of an [EcmascriptModuleAsset] that was created in the
+/// given layer. An empty layer results in the same modifier as [modifier], so
+/// that idents of modules without a layer stay stable.
+#[turbo_tasks::function]
+async fn layer_modifier(layer: StringVc) -> Result<StringVc> {
+    let layer = layer.await?;
+    if layer.is_empty() {
+        return Ok(modifier());
+    }
+    Ok(StringVc::cell(format!("ecmascript ({})", layer)))
+}
+
 #[derive(PartialEq, Eq, Clone, TraceRawVcs)]
 struct MemoizedSuccessfulAnalysis {
     operation: RawVc,
     references: AssetReferencesReadRef,
     exports: EcmascriptExportsReadRef,
     has_top_level_await: bool,
+    top_level_await: Option<IssueSourceVc>,
 }
 
 pub struct EcmascriptModuleAssetBuilder {
@@ -148,6 +301,8 @@ pub struct EcmascriptModuleAssetBuilder {
     options: EcmascriptOptions,
     compile_time_info: CompileTimeInfoVc,
     inner_assets: Option<InnerAssetsVc>,
+    layer: Option<StringVc>,
+    ident_modifiers: Vec<String>,
     part: Option<ModulePartVc>,
 }
 
@@ -167,29 +322,40 @@ impl EcmascriptModuleAssetBuilder {
         self
     }
 
-    pub fn build(self) -> ModuleVc {
-        let base = if let Some(inner_assets) = self.inner_assets {
-            EcmascriptModuleAssetVc::new_with_inner_assets(
-                self.source,
-                self.context,
-                Value::new(self.ty),
-                self.transforms,
-                Value::new(self.options),
-                self.compile_time_info,
-                inner_assets,
-            )
-        } else {
-            EcmascriptModuleAssetVc::new(
-                self.source,
-                self.context,
-                Value::new(self.ty),
-                self.transforms,
-                Value::new(self.options),
-                self.compile_time_info,
-            )
-        };
+    /// Sets the layer of the module. Modules of the same source in different
+    /// layers get distinct idents.
+    pub fn with_layer(mut self, layer: StringVc) -> Self {
+        self.layer = Some(layer);
+        self
+    }
+
+    /// Adds a modifier to the ident of the module, after the ecmascript one,
+    /// e.g. to distinguish variants of the same module created by a plugin.
+    /// The added modifiers are sorted, so that the ident doesn't depend on the
+    /// order in which they were added.
+    pub fn with_ident_modifier(mut self, modifier: String) -> Self {
+        self.ident_modifiers.push(modifier);
+        self
+    }
+
+    pub fn build(mut self) -> ModuleVc {
+        self.ident_modifiers.sort();
+        self.ident_modifiers.dedup();
+        let ident_modifiers =
+            (!self.ident_modifiers.is_empty()).then(|| StringsVc::cell(self.ident_modifiers));
+        let base = EcmascriptModuleAssetVc::new_with_options(
+            self.source,
+            self.context,
+            Value::new(self.ty),
+            self.transforms,
+            Value::new(self.options),
+            self.compile_time_info,
+            self.inner_assets,
+            self.layer,
+            ident_modifiers,
+        );
         if let Some(part) = self.part {
-            EcmascriptModulePartAssetVc::new(base, part).into()
+            EcmascriptModulePartAssetVc::select_part(base, part).into()
         } else {
             base.into()
         }
@@ -205,9 +371,46 @@ pub struct EcmascriptModuleAsset {
     pub options: EcmascriptOptions,
     pub compile_time_info: CompileTimeInfoVc,
     pub inner_assets: Option<InnerAssetsVc>,
+    pub layer: Option<StringVc>,
+    /// Modifiers which are added to the ident after the ecmascript one.
+    pub ident_modifiers: Option<StringsVc>,
     #[turbo_tasks(debug_ignore)]
     #[serde(skip)]
     last_successful_analysis: turbo_tasks::State<Option<MemoizedSuccessfulAnalysis>>,
+    /// The number of [AnalysisPin]s of the module.
+    #[turbo_tasks(debug_ignore)]
+    #[serde(skip)]
+    analysis_pins: turbo_tasks::State<usize>,
+}
+
+/// Keeps the analysis of an [EcmascriptModuleAsset] active and its result in
+/// memory while it exists, see [EcmascriptModuleAssetVc::pin_analysis].
+pub struct AnalysisPin {
+    module: EcmascriptModuleAssetReadRef,
+    _analysis: Option<MemoizedAnalysis>,
+}
+
+/// The snapshot of an analysis held by an [AnalysisPin].
+struct MemoizedAnalysis {
+    _result: AnalyzeEcmascriptModuleResultReadRef,
+    _references: AssetReferencesReadRef,
+    _exports: EcmascriptExportsReadRef,
+}
+
+impl Drop for AnalysisPin {
+    fn drop(&mut self) {
+        self.module.analysis_pins.update_conditionally(|pins| {
+            *pins -= 1;
+            *pins == 0
+        });
+    }
+}
+
+impl EcmascriptModuleAsset {
+    /// The number of [AnalysisPin]s which currently exist for the module.
+    pub fn analysis_pins(&self) -> usize {
+        *self.analysis_pins.get_untracked()
+    }
 }
 
 /// An optional [EcmascriptModuleAsset]
@@ -230,9 +433,43 @@ impl EcmascriptModuleAssetVc {
             options,
             compile_time_info,
             inner_assets: None,
+            layer: None,
+            ident_modifiers: Vec::new(),
             part: None,
         }
     }
+
+    /// Keeps the analysis of the module active until the returned pin is
+    /// dropped, so that garbage collection doesn't cause repeated
+    /// recomputations of large modules, e.g. in long running watch sessions.
+    /// While pinned, the analysis is connected to a separate task which
+    /// [EcmascriptModuleAssetVc::failsafe_analyze] keeps active, so pinning
+    /// doesn't recompute the analysis or its consumers. The pin holds the
+    /// current result.
+    ///
+    /// Pins are reference counted, so a module can be pinned multiple times.
+    /// Once all pins are dropped, the analysis can be garbage collected again.
+    pub async fn pin_analysis(self) -> Result<AnalysisPin> {
+        let module = self.await?;
+        module.analysis_pins.update_conditionally(|pins| {
+            *pins += 1;
+            *pins == 1
+        });
+        // The pin is created before the analysis is read, so that a failing
+        // analysis doesn't leak the pin count.
+        let mut pin = AnalysisPin {
+            module,
+            _analysis: None,
+        };
+        self.pinned_analysis().await?;
+        let result = self.failsafe_analyze().await?;
+        pin._analysis = Some(MemoizedAnalysis {
+            _references: result.references.await?,
+            _exports: result.exports.await?,
+            _result: result,
+        });
+        Ok(pin)
+    }
 }
 
 #[turbo_tasks::value_impl]
@@ -246,27 +483,32 @@ impl EcmascriptModuleAssetVc {
         options: Value<EcmascriptOptions>,
         compile_time_info: CompileTimeInfoVc,
     ) -> Self {
-        Self::cell(EcmascriptModuleAsset {
+        Self::new_with_options(
             source,
             context,
-            ty: ty.into_value(),
+            ty,
             transforms,
-            options: options.into_value(),
+            options,
             compile_time_info,
-            inner_assets: None,
-            last_successful_analysis: Default::default(),
-        })
+            None,
+            None,
+            None,
+        )
     }
 
+    /// Creates a module with optional inner assets, layer and extra ident
+    /// modifiers, see [EcmascriptModuleAssetBuilder].
     #[turbo_tasks::function]
-    pub fn new_with_inner_assets(
+    pub fn new_with_options(
         source: SourceVc,
         context: AssetContextVc,
         ty: Value<EcmascriptModuleAssetType>,
         transforms: EcmascriptInputTransformsVc,
         options: Value<EcmascriptOptions>,
         compile_time_info: CompileTimeInfoVc,
-        inner_assets: InnerAssetsVc,
+        inner_assets: Option<InnerAssetsVc>,
+        layer: Option<StringVc>,
+        ident_modifiers: Option<StringsVc>,
     ) -> Self {
         Self::cell(EcmascriptModuleAsset {
             source,
@@ -275,8 +517,11 @@ impl EcmascriptModuleAssetVc {
             transforms,
             options: options.into_value(),
             compile_time_info,
-            inner_assets: Some(inner_assets),
+            inner_assets,
+            layer,
+            ident_modifiers,
             last_successful_analysis: Default::default(),
+            analysis_pins: Default::default(),
         })
     }
 
@@ -289,6 +534,17 @@ impl EcmascriptModuleAssetVc {
         EcmascriptChunkVc::new_root_with_entries(context, self_vc.into(), other_entries).into()
     }
 
+    /// The transforms of the module, including the additional transforms of
+    /// the transform rules that match the path of the source.
+    #[turbo_tasks::function]
+    pub async fn transforms(self) -> Result<EcmascriptInputTransformsVc> {
+        let this = self.await?;
+        Ok(match this.options.transform_rules {
+            Some(rules) => rules.apply(this.source.ident().path(), this.transforms),
+            None => this.transforms,
+        })
+    }
+
     #[turbo_tasks::function]
     pub async fn analyze(self) -> Result<AnalyzeEcmascriptModuleResultVc> {
         let this = self.await?;
@@ -296,7 +552,7 @@ impl EcmascriptModuleAssetVc {
             this.source,
             self.as_resolve_origin(),
             Value::new(this.ty),
-            this.transforms,
+            self.transforms(),
             Value::new(this.options),
             this.compile_time_info,
             None,
@@ -306,6 +562,9 @@ impl EcmascriptModuleAssetVc {
     #[turbo_tasks::function]
     pub async fn failsafe_analyze(self) -> Result<AnalyzeEcmascriptModuleResultVc> {
         let this = self.await?;
+        // Connected without reading it, so that pinning or unpinning the module
+        // doesn't recompute this.
+        RawVc::from(self.pinned_analysis()).connect();
         let result = self.analyze();
         let result_value = result.await?;
         if result_value.successful {
@@ -316,12 +575,14 @@ impl EcmascriptModuleAssetVc {
                     references: result_value.references.await?,
                     exports: result_value.exports.await?,
                     has_top_level_await: result_value.has_top_level_await,
+                    top_level_await: result_value.top_level_await,
                 }));
         } else if let Some(MemoizedSuccessfulAnalysis {
             operation,
             references,
             exports,
             has_top_level_await,
+            top_level_await,
         }) = &*this.last_successful_analysis.get()
         {
             // It's important to connect to the last operation here to keep it active, so
@@ -332,6 +593,7 @@ impl EcmascriptModuleAssetVc {
                 exports: ReadRef::cell(exports.clone()),
                 code_generation: result_value.code_generation,
                 has_top_level_await: *has_top_level_await,
+                top_level_await: *top_level_await,
                 successful: false,
             }
             .cell());
@@ -340,10 +602,73 @@ impl EcmascriptModuleAssetVc {
         Ok(ReadRef::cell(result_value))
     }
 
+    /// Keeps the analysis active while the module is pinned, like the last
+    /// successful analysis in [`EcmascriptModuleAssetVc::failsafe_analyze`].
+    #[turbo_tasks::function]
+    async fn pinned_analysis(self) -> Result<CompletionVc> {
+        let this = self.await?;
+        // Read tracked, so that this is recomputed when the module is pinned or
+        // unpinned.
+        if *this.analysis_pins.get() > 0 {
+            RawVc::from(self.analyze()).connect();
+        }
+        Ok(CompletionVc::new())
+    }
+
+    /// Like [`EcmascriptModuleAssetVc::analyze`], but fails when the analysis
+    /// wasn't successful instead of falling back to the last successful
+    /// analysis like [`EcmascriptModuleAssetVc::failsafe_analyze`] does. The
+    /// error contains the path of the module and the issues emitted by the
+    /// analysis.
+    #[turbo_tasks::function]
+    pub async fn analyze_strict(self) -> Result<AnalyzeEcmascriptModuleResultVc> {
+        let result = self.analyze();
+        if result.await?.successful {
+            return Ok(result);
+        }
+        let issues = IssueVc::peek_issues_with_path(result)
+            .await?
+            .await?
+            .get_plain_issues()
+            .await?;
+        let mut message = format!(
+            "analysis of {} failed",
+            self.ident().path().to_string().await?
+        );
+        for issue in issues {
+            write!(
+                message,
+                "\n- {} ({}): {}",
+                issue.severity.as_str(),
+                issue.category,
+                issue.title
+            )?;
+            if !issue.description.is_empty() {
+                write!(message, "\n  {}", issue.description)?;
+            }
+        }
+        bail!(message)
+    }
+
     #[turbo_tasks::function]
     pub async fn parse(self) -> Result<ParseResultVc> {
         let this = self.await?;
-        Ok(parse(this.source, Value::new(this.ty), this.transforms))
+        Ok(parse(this.source, Value::new(this.ty), self.transforms()))
+    }
+
+    /// The type-level references of a TypeScript declaration file: triple-slash
+    /// directives, imports and module augmentations. This doesn't run the full
+    /// analysis of the module. Other modules have no type-level references.
+    #[turbo_tasks::function]
+    pub async fn typescript_declaration_references(self) -> Result<TypescriptReferencesVc> {
+        let this = self.await?;
+        if !matches!(this.ty, EcmascriptModuleAssetType::TypescriptDeclaration) {
+            return Ok(TypescriptReferencesVc::cell(Vec::new()));
+        }
+        Ok(typescript_declaration_references(
+            self.parse(),
+            self.as_resolve_origin(),
+        ))
     }
 
     /// Generates module contents without an analysis pass. This is useful for
@@ -352,11 +677,15 @@ impl EcmascriptModuleAssetVc {
     pub async fn module_content_without_analysis(self) -> Result<EcmascriptModuleContentVc> {
         let this = self.await?;
 
-        let parsed = parse(this.source, Value::new(this.ty), this.transforms);
+        let parsed = parse(this.source, Value::new(this.ty), self.transforms());
 
         Ok(EcmascriptModuleContentVc::new_without_analysis(
             parsed,
             self.ident(),
+            Value::new(this.options.preserve_comments),
+            Value::new(this.options.keep_names),
+            Value::new(this.options.target_module_format),
+            this.options.post_processor,
         ))
     }
 
@@ -367,37 +696,110 @@ impl EcmascriptModuleAssetVc {
         availability_info: Value<AvailabilityInfo>,
     ) -> Result<EcmascriptModuleContentVc> {
         let this = self.await?;
-        if *self.analyze().needs_availability_info().await? {
-            availability_info
-        } else {
-            Value::new(AvailabilityInfo::Untracked)
-        };
+        let analyzed = self.analyze();
 
-        let parsed = parse(this.source, Value::new(this.ty), this.transforms);
+        if let Some(max_size) = this.options.max_codegen_bytes {
+            if source_size(this.source).await? > max_size
+                && is_emitted_as_is(self, analyzed).await?
+            {
+                return Ok(EcmascriptModuleContentVc::new_as_is(
+                    this.source,
+                    this.options.post_processor,
+                ));
+            }
+        }
 
+        let parsed = parse(this.source, Value::new(this.ty), self.transforms());
         Ok(EcmascriptModuleContentVc::new(
             parsed,
             self.ident(),
             chunking_context,
-            self.analyze(),
+            analyzed,
             availability_info,
+            Value::new(this.options.preserve_comments),
+            Value::new(this.options.keep_names),
+            Value::new(this.options.target_module_format),
+            this.options.post_processor,
         ))
     }
+
+    /// The modules which are referenced by this module directly, e.g. by
+    /// imports, `require` calls or `new URL()` expressions, in the order of
+    /// their references. Each module is listed once. References which don't
+    /// reference modules, e.g. to a package.json or a source map, are skipped.
+    ///
+    /// Unlike the available assets of a chunk, this doesn't include the
+    /// modules referenced by the listed modules.
+    #[turbo_tasks::function]
+    pub async fn direct_dependencies(self) -> Result<ModulesVc> {
+        let modules = self
+            .references()
+            .await?
+            .iter()
+            .map(|&reference| async move {
+                let Some(reference) = ChunkableModuleReferenceVc::resolve_from(reference).await?
+                else {
+                    return Ok(Vec::new());
+                };
+                reference
+                    .resolve_reference()
+                    .primary_assets()
+                    .await?
+                    .iter()
+                    .map(|&asset| async move { Ok(ModuleVc::resolve_from(asset).await?) })
+                    .try_join()
+                    .await
+            })
+            .try_join()
+            .await?
+            .into_iter()
+            .flatten()
+            .flatten()
+            .collect::<IndexSet<_>>();
+        Ok(ModulesVc::cell(modules.into_iter().collect()))
+    }
+
+    /// Summarizes the references and the generated code of this module. This
+    /// is meant for performance investigations.
+    #[turbo_tasks::function]
+    pub fn analysis_stats(
+        self,
+        chunking_context: EcmascriptChunkingContextVc,
+    ) -> ModuleAnalysisStatsVc {
+        ModuleAnalysisStatsVc::new(self, chunking_context)
+    }
 }
 
 #[turbo_tasks::value_impl]
 impl Asset for EcmascriptModuleAsset {
     #[turbo_tasks::function]
     async fn ident(&self) -> Result<AssetIdentVc> {
-        if let Some(inner_assets) = self.inner_assets {
+        let modifier = if let Some(layer) = self.layer {
+            layer_modifier(layer)
+        } else {
+            modifier()
+        };
+        if self.inner_assets.is_some() || self.ident_modifiers.is_some() {
             let mut ident = self.source.ident().await?.clone_value();
-            for (name, asset) in inner_assets.await?.iter() {
-                ident.add_asset(StringVc::cell(name.clone()), asset.ident());
+            if let Some(inner_assets) = self.inner_assets {
+                let inner_assets = inner_assets.await?;
+                // Add the inner assets sorted by name, so that the ident doesn't
+                // depend on the order in which they were inserted.
+                let mut inner_assets = inner_assets.iter().collect::<Vec<_>>();
+                inner_assets.sort_by(|(a, _), (b, _)| a.cmp(b));
+                for (name, asset) in inner_assets {
+                    ident.add_asset(StringVc::cell(name.clone()), asset.ident());
+                }
+            }
+            ident.add_modifier(modifier);
+            if let Some(ident_modifiers) = self.ident_modifiers {
+                for ident_modifier in ident_modifiers.await?.iter() {
+                    ident.add_modifier(StringVc::cell(ident_modifier.clone()));
+                }
             }
-            ident.add_modifier(modifier());
             Ok(AssetIdentVc::new(Value::new(ident)))
         } else {
-            Ok(self.source.ident().with_modifier(modifier()))
+            Ok(self.source.ident().with_modifier(modifier))
         }
     }
 
@@ -521,8 +923,43 @@ impl EcmascriptChunkItem for ModuleChunkItem {
     ) -> Result<EcmascriptChunkItemContentVc> {
         let this = self_vc.await?;
         let content = this.module.module_content(this.context, availability_info);
-        Ok(EcmascriptChunkItemContentVc::new(content, this.context))
+        let async_module = *is_async_module(this.module.into(), availability_info).await?;
+        Ok(EcmascriptChunkItemContentVc::new(
+            content,
+            this.context,
+            async_module,
+        ))
+    }
+}
+
+/// Whether `module` can be emitted as is, without parsing it for the code
+/// generation. That's the case for untransformed scripts and CommonJS modules
+/// whose analysis found no references and no code generations.
+async fn is_emitted_as_is(
+    module: EcmascriptModuleAssetVc,
+    analyzed: AnalyzeEcmascriptModuleResultVc,
+) -> Result<bool> {
+    let this = module.await?;
+    if !matches!(this.ty, EcmascriptModuleAssetType::Ecmascript)
+        || !module.transforms().await?.is_empty()
+    {
+        return Ok(false);
+    }
+    let analyzed = analyzed.await?;
+    if matches!(&*analyzed.exports.await?, EcmascriptExports::EsmExports(_))
+        || analyzed.has_top_level_await
+        || !analyzed.references.await?.is_empty()
+        || !analyzed.code_generation.await?.is_empty()
+    {
+        return Ok(false);
     }
+    if let FileContent::Content(file) = &*this.source.content().file_content().await? {
+        // The shebang is removed by the code generation.
+        if file.content().to_str()?.starts_with("#!") {
+            return Ok(false);
+        }
+    }
+    Ok(true)
 }
 
 /// The transformed contents of an Ecmascript module.
@@ -530,12 +967,21 @@ impl EcmascriptChunkItem for ModuleChunkItem {
 pub struct EcmascriptModuleContent {
     pub inner_code: Rope,
     pub source_map: Option<ParseResultSourceMapVc>,
+    /// Whether the emitted code is an ES module. This is the module format of
+    /// the source, unless it was transformed to CommonJS. An empty module is
+    /// ESM unless its specified module type is CommonJS, like in the analysis.
     pub is_esm: bool,
+    /// Whether the code calls the free `require` of CommonJS, which is then
+    /// bound to the module require of the runtime.
+    pub free_require: bool,
 }
 
 #[turbo_tasks::value_impl]
 impl EcmascriptModuleContentVc {
     /// Creates a new [`EcmascriptModuleContentVc`].
+    ///
+    /// When no code generation that depends on the availability info produces
+    /// visitors, the content is shared by all availability infos.
     #[turbo_tasks::function]
     pub async fn new(
         parsed: ParseResultVc,
@@ -543,58 +989,225 @@ impl EcmascriptModuleContentVc {
         context: EcmascriptChunkingContextVc,
         analyzed: AnalyzeEcmascriptModuleResultVc,
         availability_info: Value<AvailabilityInfo>,
+        preserve_comments: Value<CommentsMode>,
+        keep_names: Value<KeepNames>,
+        target_module_format: Value<TargetFormat>,
+        post_processor: Option<CodePostProcessorVc>,
     ) -> Result<Self> {
-        let AnalyzeEcmascriptModuleResult {
-            references,
-            code_generation,
-            ..
-        } = &*analyzed.await?;
-
-        let mut code_gens = Vec::new();
-        for r in references.await?.iter() {
-            let r = r.resolve().await?;
-            if let Some(code_gen) = CodeGenerateableWithAvailabilityInfoVc::resolve_from(r).await? {
+        let availability_code_gens = if *analyzed.needs_availability_info().await? {
+            code_generations(context, analyzed, Some(availability_info)).await?
+        } else {
+            Vec::new()
+        };
+        if availability_code_gens
+            .iter()
+            .all(|cg| cg.visitors.is_empty())
+        {
+            return Ok(Self::new_availability_independent(
+                parsed,
+                ident,
+                context,
+                analyzed,
+                preserve_comments,
+                keep_names,
+                target_module_format,
+                post_processor,
+            ));
+        }
+
+        let mut code_gens = code_generations(context, analyzed, None).await?;
+        code_gens.extend(availability_code_gens);
+        let (visitors, root_visitors) = split_visitors(&code_gens);
+
+        gen_content_with_visitors(
+            parsed,
+            ident,
+            visitors,
+            root_visitors,
+            preserve_comments.into_value(),
+            keep_names.into_value(),
+            target_module_format.into_value(),
+            post_processor,
+            is_empty_esm(analyzed).await?,
+        )
+        .await
+    }
+
+    /// The content of a module whose code generations that depend on the
+    /// availability info don't produce visitors.
+    #[turbo_tasks::function]
+    async fn new_availability_independent(
+        parsed: ParseResultVc,
+        ident: AssetIdentVc,
+        context: EcmascriptChunkingContextVc,
+        analyzed: AnalyzeEcmascriptModuleResultVc,
+        preserve_comments: Value<CommentsMode>,
+        keep_names: Value<KeepNames>,
+        target_module_format: Value<TargetFormat>,
+        post_processor: Option<CodePostProcessorVc>,
+    ) -> Result<Self> {
+        let code_gens = code_generations(context, analyzed, None).await?;
+        let (visitors, root_visitors) = split_visitors(&code_gens);
+
+        gen_content_with_visitors(
+            parsed,
+            ident,
+            visitors,
+            root_visitors,
+            preserve_comments.into_value(),
+            keep_names.into_value(),
+            target_module_format.into_value(),
+            post_processor,
+            is_empty_esm(analyzed).await?,
+        )
+        .await
+    }
+
+    /// Creates a new [`EcmascriptModuleContentVc`] from the unchanged code of
+    /// `source`, without parsing it. The content has no source map, and all
+    /// comments of the source are kept. Raw `require()` calls of the source
+    /// load modules with `__turbopack_require__`.
+    #[turbo_tasks::function]
+    pub async fn new_as_is(
+        source: SourceVc,
+        post_processor: Option<CodePostProcessorVc>,
+    ) -> Result<Self> {
+        let inner_code = match &*source.content().file_content().await? {
+            FileContent::Content(file) => file.content().clone(),
+            FileContent::NotFound => Rope::default(),
+        };
+        let inner_code = match post_processor {
+            Some(post_processor) => post_processor
+                .await?
+                .process(inner_code.to_bytes()?.into_owned())?
+                .code
+                .into(),
+            None => inner_code,
+        };
+        Ok(EcmascriptModuleContent {
+            inner_code,
+            source_map: None,
+            is_esm: false,
+            // The `require` calls of the source aren't rewritten.
+            free_require: true,
+        }
+        .cell())
+    }
+
+    /// Creates a new [`EcmascriptModuleContentVc`] without an analysis pass.
+    /// Without the analysis, an empty module is not ESM.
+    #[turbo_tasks::function]
+    pub async fn new_without_analysis(
+        parsed: ParseResultVc,
+        ident: AssetIdentVc,
+        preserve_comments: Value<CommentsMode>,
+        keep_names: Value<KeepNames>,
+        target_module_format: Value<TargetFormat>,
+        post_processor: Option<CodePostProcessorVc>,
+    ) -> Result<Self> {
+        gen_content_with_visitors(
+            parsed,
+            ident,
+            Vec::new(),
+            Vec::new(),
+            preserve_comments.into_value(),
+            keep_names.into_value(),
+            target_module_format.into_value(),
+            post_processor,
+            false,
+        )
+        .await
+    }
+}
+
+/// Whether the module is ESM when its program is empty, like in the analysis.
+async fn is_empty_esm(analyzed: AnalyzeEcmascriptModuleResultVc) -> Result<bool> {
+    Ok(matches!(
+        &*analyzed.await?.exports.await?,
+        EcmascriptExports::EsmExports(_)
+    ))
+}
+
+/// Visitors for the nodes at the AST paths.
+type PathVisitors<'a> = Vec<(&'a AstPath, &'a dyn VisitorFactory)>;
+
+/// Splits the visitors of the code generations into visitors for AST paths
+/// and visitors for the root of the program.
+fn split_visitors(
+    code_gens: &[CodeGenerationReadRef],
+) -> (PathVisitors<'_>, Vec<&dyn VisitorFactory>) {
+    let mut visitors = Vec::new();
+    let mut root_visitors = Vec::new();
+    for code_gen in code_gens {
+        for (path, visitor) in code_gen.visitors.iter() {
+            if path.is_empty() {
+                root_visitors.push(&**visitor);
+            } else {
+                visitors.push((path, &**visitor));
+            }
+        }
+    }
+    (visitors, root_visitors)
+}
+
+/// Collects the code generations of all references and code generateables of
+/// the analyzed module.
+pub(crate) async fn module_code_generations(
+    context: EcmascriptChunkingContextVc,
+    analyzed: AnalyzeEcmascriptModuleResultVc,
+    availability_info: Value<AvailabilityInfo>,
+) -> Result<Vec<CodeGenerationReadRef>> {
+    let mut code_gens = code_generations(context, analyzed, None).await?;
+    code_gens.extend(code_generations(context, analyzed, Some(availability_info)).await?);
+    Ok(code_gens)
+}
+
+/// Collects the code generations of the references and code generateables of
+/// the analyzed module that depend on the availability info when it's given,
+/// or the ones that don't depend on it otherwise.
+async fn code_generations(
+    context: EcmascriptChunkingContextVc,
+    analyzed: AnalyzeEcmascriptModuleResultVc,
+    availability_info: Option<Value<AvailabilityInfo>>,
+) -> Result<Vec<CodeGenerationReadRef>> {
+    let AnalyzeEcmascriptModuleResult {
+        references,
+        code_generation,
+        ..
+    } = &*analyzed.await?;
+
+    let mut code_gens = Vec::new();
+    for r in references.await?.iter() {
+        let r = r.resolve().await?;
+        if let Some(code_gen) = CodeGenerateableWithAvailabilityInfoVc::resolve_from(r).await? {
+            if let Some(availability_info) = availability_info {
                 code_gens.push(code_gen.code_generation(context, availability_info));
-            } else if let Some(code_gen) = CodeGenerateableVc::resolve_from(r).await? {
+            }
+        } else if let Some(code_gen) = CodeGenerateableVc::resolve_from(r).await? {
+            if availability_info.is_none() {
                 code_gens.push(code_gen.code_generation(context));
             }
         }
-        for c in code_generation.await?.iter() {
-            match c {
-                CodeGen::CodeGenerateable(c) => {
-                    code_gens.push(c.code_generation(context));
-                }
-                CodeGen::CodeGenerateableWithAvailabilityInfo(c) => {
-                    code_gens.push(c.code_generation(context, availability_info));
-                }
+    }
+    for c in code_generation.await?.iter() {
+        match (c, availability_info) {
+            (CodeGen::CodeGenerateable(c), None) => {
+                code_gens.push(c.code_generation(context));
             }
-        }
-        // need to keep that around to allow references into that
-        let code_gens = code_gens.into_iter().try_join().await?;
-        let code_gens = code_gens.iter().map(|cg| &**cg).collect::<Vec<_>>();
-        // TOOD use interval tree with references into "code_gens"
-        let mut visitors = Vec::new();
-        let mut root_visitors = Vec::new();
-        for code_gen in code_gens {
-            for (path, visitor) in code_gen.visitors.iter() {
-                if path.is_empty() {
-                    root_visitors.push(&**visitor);
-                } else {
-                    visitors.push((path, &**visitor));
-                }
+            (CodeGen::CodeGenerateableWithAvailabilityInfo(c), Some(availability_info)) => {
+                code_gens.push(c.code_generation(context, availability_info));
             }
+            _ => {}
         }
-
-        gen_content_with_visitors(parsed, ident, visitors, root_visitors).await
-    }
-
-    /// Creates a new [`EcmascriptModuleContentVc`] without an analysis pass.
-    #[turbo_tasks::function]
-    pub async fn new_without_analysis(parsed: ParseResultVc, ident: AssetIdentVc) -> Result<Self> {
-        gen_content_with_visitors(parsed, ident, Vec::new(), Vec::new()).await
     }
+    code_gens.into_iter().try_join().await
 }
 
+/// Generates the content of the module by applying all `visitors` to the
+/// parsed program in a single pass, since the AST paths of the visitors point
+/// into the unmodified program. `empty_is_esm` is whether the module is ESM
+/// when the parsed program is empty. The emitted code is passed through the
+/// `post_processor`, if any.
 async fn gen_content_with_visitors(
     parsed: ParseResultVc,
     ident: AssetIdentVc,
@@ -603,60 +1216,138 @@ async fn gen_content_with_visitors(
         &dyn VisitorFactory,
     )>,
     root_visitors: Vec<&dyn VisitorFactory>,
+    preserve_comments: CommentsMode,
+    keep_names: KeepNames,
+    target_module_format: TargetFormat,
+    post_processor: Option<CodePostProcessorVc>,
+    empty_is_esm: bool,
 ) -> Result<EcmascriptModuleContentVc> {
     let parsed = parsed.await?;
 
     if let ParseResult::Ok {
-        program,
+        program: parsed_program,
         source_map,
         globals,
         eval_context,
-        ..
+        comments,
     } = &*parsed
     {
-        let mut program = program.clone();
-
-        GLOBALS.set(globals, || {
-            if !visitors.is_empty() {
-                program.visit_mut_with_path(
-                    &mut ApplyVisitors::new(visitors),
-                    &mut Default::default(),
-                );
-            }
-            for visitor in root_visitors {
-                program.visit_mut_with(&mut visitor.create());
+        let is_esm = eval_context.is_esm() || (empty_is_esm && is_empty_program(parsed_program));
+        let mut program = parsed_program.clone();
+
+        let mut to_common_js = matches!(target_module_format, TargetFormat::CommonJs) && is_esm;
+        if to_common_js && has_top_level_await(&program) {
+            to_common_js = false;
+            CodeGenerationIssue {
+                severity: IssueSeverity::Error.cell(),
+                path: ident.path(),
+                title: StringVc::cell(
+                    "Top-level await is not supported when emitting CommonJS".to_string(),
+                ),
+                message: StringVc::cell(format!(
+                    "{} uses top-level await, which can't be emitted as CommonJS since `require` \
+                     is synchronous. It is emitted as an ES module instead.",
+                    ident.to_string().await?
+                )),
             }
-            program.visit_mut_with(&mut swc_core::ecma::transforms::base::hygiene::hygiene());
-            program.visit_mut_with(&mut swc_core::ecma::transforms::base::fixer::fixer(None));
+            .cell()
+            .as_issue()
+            .emit();
+        }
 
-            // we need to remove any shebang before bundling as it's only valid as the first
-            // line in a js file (not in a chunk item wrapped in the runtime)
-            remove_shebang(&mut program);
-        });
+        let post_processor = match post_processor {
+            Some(post_processor) => Some(post_processor.await?),
+            None => None,
+        };
 
-        let mut bytes: Vec<u8> = vec![];
-        // TODO: Insert this as a sourceless segment so that sourcemaps aren't affected.
-        // = format!("/* {} */\n", self.module.path().to_string().await?).into_bytes();
+        // Visitors of transform plugins and post processors may panic. The
+        // panic is isolated to this module, which throws when it's evaluated
+        // instead.
+        let generated = GLOBALS.set(globals, || {
+            catch_unwind(AssertUnwindSafe(|| -> Result<_> {
+                if !visitors.is_empty() {
+                    program.visit_mut_with_path(
+                        &mut ApplyVisitors::new(visitors),
+                        &mut Default::default(),
+                    );
+                }
+                for visitor in root_visitors {
+                    program.visit_mut_with(&mut visitor.create());
+                }
+                // The names are recorded before the hygiene pass renames the bindings.
+                if keep_names.functions || keep_names.classes {
+                    program.visit_mut_with(&mut KeepNamesVisitor::new(keep_names));
+                }
+                if to_common_js {
+                    // The code generation of an analyzed module replaces its imports
+                    // and exports with the runtime's CommonJS compatible helpers. The
+                    // ESM syntax that is left, e.g. when the module was not analyzed,
+                    // is transformed.
+                    let has_module_decls = program.as_module().map_or(false, |module| {
+                        module
+                            .body
+                            .iter()
+                            .any(|item| matches!(item, ModuleItem::ModuleDecl(_)))
+                    });
+                    if has_module_decls {
+                        transform_to_common_js(&mut program, eval_context.unresolved_mark);
+                    }
+                    // ES modules are always strict.
+                    ensure_use_strict(&mut program);
+                }
+                program.visit_mut_with(&mut swc_core::ecma::transforms::base::hygiene::hygiene());
+                program.visit_mut_with(&mut swc_core::ecma::transforms::base::fixer::fixer(None));
 
-        let mut srcmap = vec![];
+                // we need to remove any shebang before bundling as it's only valid as the first
+                // line in a js file (not in a chunk item wrapped in the runtime)
+                remove_shebang(&mut program);
 
-        let mut emitter = Emitter {
-            cfg: swc_core::ecma::codegen::Config {
-                ..Default::default()
-            },
-            cm: source_map.clone(),
-            comments: None,
-            wr: JsWriter::new(source_map.clone(), "\n", &mut bytes, Some(&mut srcmap)),
-        };
+                let mut bytes: Vec<u8> = vec![];
+                // TODO: Insert this as a sourceless segment so that sourcemaps aren't affected.
+                // = format!("/* {} */\n", self.module.path().to_string().await?).into_bytes();
+
+                let mut srcmap = vec![];
+
+                // The emitter takes the comments it emits out of the map, so it gets a
+                // copy to keep the parse result intact.
+                let comments = retained_comments(comments, preserve_comments);
 
-        emitter.emit_program(&program)?;
+                let mut emitter = Emitter {
+                    cfg: swc_core::ecma::codegen::Config {
+                        ..Default::default()
+                    },
+                    cm: source_map.clone(),
+                    comments: comments.as_ref().map(|c| c as &dyn Comments),
+                    wr: JsWriter::new(source_map.clone(), "\n", &mut bytes, Some(&mut srcmap)),
+                };
+
+                emitter.emit_program(&program)?;
+
+                Ok(match &post_processor {
+                    Some(post_processor) => {
+                        let processed = post_processor.process(bytes)?;
+                        (processed.code, shift_mappings(srcmap, processed.line_delta))
+                    }
+                    None => (bytes, srcmap),
+                })
+            }))
+        });
+        let (bytes, srcmap) = match generated {
+            Ok(generated) => generated?,
+            Err(payload) => {
+                let original_source = source_map.span_to_snippet(parsed_program.span()).ok();
+                return codegen_failed_content(ident, panic_message(&*payload), original_source)
+                    .await;
+            }
+        };
 
         let srcmap = ParseResultSourceMap::new(source_map.clone(), srcmap).cell();
 
         Ok(EcmascriptModuleContent {
             inner_code: bytes.into(),
             source_map: Some(srcmap),
-            is_esm: eval_context.is_esm(),
+            is_esm: is_esm && !to_common_js,
+            free_require: false,
         }
         .cell())
     } else {
@@ -669,14 +1360,308 @@ async fn gen_content_with_visitors(
             .into(),
             source_map: None,
             is_esm: false,
+            free_require: false,
         }
         .cell())
     }
 }
 
+/// Transforms the ESM syntax of the program into `require` calls and
+/// `exports` assignments.
+fn transform_to_common_js(program: &mut Program, unresolved_mark: Mark) {
+    use swc_core::ecma::transforms::{
+        base::feature::FeatureFlag,
+        module::{
+            common_js,
+            util::{Config, ImportInterop},
+        },
+    };
+
+    // Explicit type annotation to ensure that we don't duplicate transforms in the
+    // final binary
+    program.visit_mut_with(&mut common_js::<&dyn Comments>(
+        unresolved_mark,
+        Config {
+            allow_top_level_this: true,
+            import_interop: Some(ImportInterop::Swc),
+            ..Default::default()
+        },
+        FeatureFlag::all(),
+        // The comments of the parse result must not be modified.
+        None,
+    ));
+}
+
+/// The content of a module whose code generation panicked, e.g. in a visitor
+/// of a transform plugin. Like the content of a module that can't be parsed, it
+/// throws when it's evaluated. The panic message and the original source are
+/// kept in comments, and the panic is reported as an issue.
+async fn codegen_failed_content(
+    ident: AssetIdentVc,
+    message: String,
+    original_source: Option<String>,
+) -> Result<EcmascriptModuleContentVc> {
+    let path = ident.path().to_string().await?;
+    CodeGenerationIssue {
+        severity: IssueSeverity::Error.cell(),
+        path: ident.path(),
+        title: StringVc::cell("Code generation for module panicked".to_string()),
+        message: StringVc::cell(format!(
+            "Code generation for {} panicked: {}",
+            ident.to_string().await?,
+            message
+        )),
+    }
+    .cell()
+    .as_issue()
+    .emit();
+
+    // Every line is a line comment, so the comments can't be terminated early.
+    let mut code = String::new();
+    let mut comment = |text: &str| -> std::fmt::Result {
+        for line in text.split(['\n', '\r', '\u{2028}', '\u{2029}']) {
+            writeln!(code, "// {line}")?;
+        }
+        Ok(())
+    };
+    comment(&format!("Code generation panicked: {message}"))?;
+    if let Some(original_source) = original_source {
+        comment("Original source:")?;
+        comment(&original_source)?;
+    }
+    write!(
+        code,
+        "const e = new Error(\"Could not generate code for module '{path}'\");\ne.code = \
+         'MODULE_CODEGEN_FAILED';\nthrow e;"
+    )?;
+    Ok(EcmascriptModuleContent {
+        inner_code: code.into(),
+        source_map: None,
+        is_esm: false,
+        free_require: false,
+    }
+    .cell())
+}
+
+/// Returns the message of a panic payload.
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
+
+/// Copies the comments that should be kept according to `mode`.
+fn retained_comments(comments: &SwcComments, mode: CommentsMode) -> Option<SwcComments> {
+    let keep: fn(&Comment) -> bool = match mode {
+        CommentsMode::None => return None,
+        CommentsMode::LicenseOnly => is_license_comment,
+        CommentsMode::All => |_| true,
+    };
+    let retained = SwcComments::default();
+    for (source, target) in [
+        (&comments.leading, &retained.leading),
+        (&comments.trailing, &retained.trailing),
+    ] {
+        for entry in source.iter() {
+            let kept: Vec<_> = entry.value().iter().filter(|c| keep(c)).cloned().collect();
+            if !kept.is_empty() {
+                target.insert(*entry.key(), kept);
+            }
+        }
+    }
+    Some(retained)
+}
+
+/// Whether the comment is a license banner that has to be kept in the output.
+fn is_license_comment(comment: &Comment) -> bool {
+    comment.text.starts_with('!')
+        || comment.text.contains("@license")
+        || comment.text.contains("@preserve")
+}
+
 pub fn register() {
     turbo_tasks::register();
     turbo_tasks_fs::register();
     turbopack_core::register();
     include!(concat!(env!("OUT_DIR"), "/register.rs"));
 }
+
+#[cfg(test)]
+mod tests {
+    use swc_core::ecma::{ast::Program, visit::VisitMut};
+    use turbo_tasks::{TurboTasks, Value};
+    use turbo_tasks_fs::{File, FileSystem, VirtualFileSystemVc};
+    use turbo_tasks_memory::MemoryBackend;
+    use turbopack_core::{
+        asset::AssetContentVc, ident::AssetIdentVc, virtual_source::VirtualSourceVc,
+    };
+
+    use super::{
+        gen_content_with_visitors, parse, CommentsMode, EcmascriptInputTransformsVc,
+        EcmascriptModuleAssetType, KeepNames, TargetFormat,
+    };
+    use crate::{
+        code_gen::VisitorFactory,
+        post_process::{CodePostProcessorVc, EmittedCodePostProcessor, PostProcessedCode},
+    };
+
+    struct PanickingVisitor;
+
+    impl VisitMut for PanickingVisitor {
+        fn visit_mut_program(&mut self, _: &mut Program) {
+            panic!("the plugin is broken");
+        }
+    }
+
+    struct PanickingVisitorFactory;
+
+    impl VisitorFactory for PanickingVisitorFactory {
+        fn create<'a>(&'a self) -> Box<dyn VisitMut + Send + Sync + 'a> {
+            Box::new(PanickingVisitor)
+        }
+    }
+
+    #[tokio::test]
+    async fn codegen_panic_emits_stub() {
+        crate::register();
+
+        let tt = TurboTasks::new(MemoryBackend::default());
+        tt.run_once(async {
+            let path = VirtualFileSystemVc::new().root().join("input.js");
+            let source = VirtualSourceVc::new(
+                path,
+                AssetContentVc::from(File::from("export const a = 1;\n")),
+            );
+            let parsed = parse(
+                source.into(),
+                Value::new(EcmascriptModuleAssetType::Ecmascript),
+                EcmascriptInputTransformsVc::empty(),
+            );
+            let ident = AssetIdentVc::from_path(path);
+            let content = gen_content_with_visitors(
+                parsed,
+                ident,
+                Vec::new(),
+                vec![&PanickingVisitorFactory as &dyn VisitorFactory],
+                CommentsMode::None,
+                KeepNames::default(),
+                TargetFormat::default(),
+                None,
+                false,
+            )
+            .await?
+            .await?;
+            let code = content.inner_code.to_str()?;
+            assert!(
+                code.contains("// Code generation panicked: the plugin is broken\n"),
+                "{code}"
+            );
+            assert!(code.contains("// export const a = 1;\n"), "{code}");
+            assert!(
+                code.ends_with("e.code = 'MODULE_CODEGEN_FAILED';\nthrow e;"),
+                "{code}"
+            );
+            assert!(content.source_map.is_none());
+
+            // The parse result isn't affected by the panic.
+            let content = gen_content_with_visitors(
+                parsed,
+                ident,
+                Vec::new(),
+                Vec::new(),
+                CommentsMode::None,
+                KeepNames::default(),
+                TargetFormat::default(),
+                None,
+                false,
+            )
+            .await?
+            .await?;
+            let code = content.inner_code.to_str()?;
+            assert!(!code.contains("MODULE_CODEGEN_FAILED"), "{code}");
+            assert!(code.contains("export const a = 1;"), "{code}");
+            Ok(())
+        })
+        .await
+        .unwrap();
+    }
+
+    #[derive(Debug)]
+    struct Banner;
+
+    impl EmittedCodePostProcessor for Banner {
+        fn process(&self, code: Vec<u8>) -> anyhow::Result<PostProcessedCode> {
+            let mut banner = b"// banner\n".to_vec();
+            banner.extend(code);
+            Ok(PostProcessedCode {
+                code: banner,
+                line_delta: 1,
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn post_processor() {
+        crate::register();
+
+        let tt = TurboTasks::new(MemoryBackend::default());
+        tt.run_once(async {
+            let path = VirtualFileSystemVc::new().root().join("input.js");
+            let source = VirtualSourceVc::new(
+                path,
+                AssetContentVc::from(File::from("export const a = 1;\n")),
+            );
+            let parsed = parse(
+                source.into(),
+                Value::new(EcmascriptModuleAssetType::Ecmascript),
+                EcmascriptInputTransformsVc::empty(),
+            );
+            let ident = AssetIdentVc::from_path(path);
+            let generate = |post_processor| {
+                gen_content_with_visitors(
+                    parsed,
+                    ident,
+                    Vec::new(),
+                    Vec::new(),
+                    CommentsMode::None,
+                    KeepNames::default(),
+                    TargetFormat::default(),
+                    post_processor,
+                    false,
+                )
+            };
+
+            let content = generate(None).await?.await?;
+            let processed = generate(Some(CodePostProcessorVc::new(Banner)))
+                .await?
+                .await?;
+            assert_eq!(
+                processed.inner_code.to_str()?,
+                format!("// banner\n{}", content.inner_code.to_str()?)
+            );
+
+            // The code moved down by a line, so the first line has no mappings.
+            let source_map = |content: &super::EcmascriptModuleContent| {
+                content
+                    .source_map
+                    .expect("expected a source map")
+                    .to_json_string()
+            };
+            let mappings = |json: &str| -> anyhow::Result<String> {
+                let map: serde_json::Value = serde_json::from_str(json)?;
+                Ok(map["mappings"].as_str().unwrap_or_default().to_string())
+            };
+            let original = mappings(&source_map(&content).await?)?;
+            let shifted = mappings(&source_map(&processed).await?)?;
+            assert!(!original.starts_with(';'), "{original}");
+            assert_eq!(shifted, format!(";{original}"));
+            Ok(())
+        })
+        .await
+        .unwrap();
+    }
+}
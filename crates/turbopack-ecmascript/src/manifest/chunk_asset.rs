@@ -88,8 +88,16 @@ fn manifest_chunk_reference_description() -> StringVc {
 #[turbo_tasks::value_impl]
 impl Asset for ManifestChunkAsset {
     #[turbo_tasks::function]
-    fn ident(&self) -> AssetIdentVc {
-        self.asset.ident().with_modifier(modifier())
+    async fn ident(&self) -> Result<AssetIdentVc> {
+        let ident = self.asset.ident().with_modifier(modifier());
+        // Manifest chunks of the same module with different chunk names load
+        // different chunks.
+        Ok(match &*self.chunking_context.chunk_name().await? {
+            Some(chunk_name) => {
+                ident.with_modifier(StringVc::cell(format!("chunk name {chunk_name}")))
+            }
+            None => ident,
+        })
     }
 
     #[turbo_tasks::function]
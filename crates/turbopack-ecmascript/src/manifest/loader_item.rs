@@ -1,12 +1,13 @@
 use std::io::Write as _;
 
 use anyhow::{anyhow, Result};
-use indoc::writedoc;
-use turbo_tasks::{primitives::StringVc, TryJoinIterExt};
+use indoc::{formatdoc, writedoc};
+use turbo_tasks::{primitives::StringVc, TryJoinIterExt, Value};
 use turbopack_core::{
     asset::Asset,
     chunk::{ChunkDataVc, ChunkItem, ChunkItemVc, ChunkingContext, ChunksDataVc},
     ident::AssetIdentVc,
+    output::OutputAssetsVc,
     reference::{AssetReferencesVc, SingleAssetReferenceVc},
 };
 
@@ -17,6 +18,7 @@ use crate::{
         EcmascriptChunkItemContentVc, EcmascriptChunkItemVc, EcmascriptChunkPlaceable,
         EcmascriptChunkPlaceableVc, EcmascriptChunkingContextVc,
     },
+    references::esm::async_module::is_async_module,
     utils::StringifyJs,
 };
 
@@ -59,6 +61,13 @@ impl ManifestLoaderItemVc {
             chunks,
         ))
     }
+
+    /// The chunks of the chunk group of the dynamically imported module, which
+    /// are loaded by the manifest chunk.
+    #[turbo_tasks::function]
+    pub async fn async_chunk_group(self) -> Result<OutputAssetsVc> {
+        Ok(self.await?.manifest.chunks())
+    }
 }
 
 #[turbo_tasks::function]
@@ -138,6 +147,25 @@ impl EcmascriptChunkItem for ManifestLoaderItem {
             .id()
             .await?;
 
+        // Async modules are still evaluating when they are imported, so the import
+        // has to wait for their evaluation before it resolves.
+        let async_module =
+            *is_async_module(placeable, Value::new(manifest.availability_info)).await?;
+        let import_dynamic = if async_module {
+            formatdoc!(
+                r#"
+                    const namespace = __turbopack_import__({dynamic_id});
+                    return __turbopack_cache__[{dynamic_id}].async.then(() => namespace);
+                "#,
+                dynamic_id = StringifyJs(dynamic_id),
+            )
+        } else {
+            format!(
+                "return __turbopack_import__({dynamic_id});\n",
+                dynamic_id = StringifyJs(dynamic_id),
+            )
+        };
+
         // This is the code that will be executed when the dynamic import is reached.
         // It will load the manifest chunk, which will load all the chunks needed by
         // the dynamic import, and finally we'll be able to import the module we're
@@ -153,7 +181,7 @@ impl EcmascriptChunkItem for ManifestLoaderItem {
                     }}).then((chunks) => {{
                         return Promise.all(chunks.map((chunk) => __turbopack_load__(chunk)));
                     }}).then(() => {{
-                        return __turbopack_import__({dynamic_id});
+                        {import_dynamic}
                     }});
                 }});
             "#,
@@ -164,7 +192,7 @@ impl EcmascriptChunkItem for ManifestLoaderItem {
                     .collect::<Vec<_>>()
             ),
             item_id = StringifyJs(item_id),
-            dynamic_id = StringifyJs(dynamic_id),
+            import_dynamic = import_dynamic.trim_end().replace('\n', "\n        "),
         )?;
 
         Ok(EcmascriptChunkItemContent {
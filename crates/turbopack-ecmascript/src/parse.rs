@@ -103,6 +103,21 @@ impl ParseResultSourceMap {
     }
 }
 
+#[turbo_tasks::value_impl]
+impl ParseResultSourceMapVc {
+    /// Serializes the source map to Source Map v3 JSON, including the
+    /// `sourcesContent`, e.g. to assert on it in tests.
+    #[turbo_tasks::function]
+    pub async fn to_json_string(self) -> Result<StringVc> {
+        let map = self
+            .generate_source_map()
+            .await?
+            .context("expected a source map")?;
+        let json = map.to_rope().await?;
+        Ok(StringVc::cell(json.to_str()?.into_owned()))
+    }
+}
+
 #[turbo_tasks::value_impl]
 impl GenerateSourceMap for ParseResultSourceMap {
     #[turbo_tasks::function]
@@ -415,3 +430,51 @@ impl Issue for ReadSourceIssue {
         StringVc::cell("parse".to_string())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use serde_json::{json, Value};
+    use swc_core::common::{BytePos, FileName, LineCol, SourceMap};
+    use turbo_tasks::TurboTasks;
+    use turbo_tasks_memory::MemoryBackend;
+
+    use super::ParseResultSourceMap;
+
+    #[tokio::test]
+    async fn to_json_string() {
+        crate::register();
+
+        let tt = TurboTasks::new(MemoryBackend::default());
+        tt.run_once(async {
+            let source_map = Arc::new(SourceMap::default());
+            let file = source_map.new_source_file(
+                FileName::Custom("input.js".to_string()),
+                "const a = 1;\n".to_string(),
+            );
+            let mappings = vec![
+                (file.start_pos, LineCol { line: 0, col: 0 }),
+                (file.start_pos + BytePos(6), LineCol { line: 0, col: 2 }),
+            ];
+            let json = ParseResultSourceMap::new(source_map, mappings)
+                .cell()
+                .to_json_string()
+                .await?;
+            let map: Value = serde_json::from_str(&json)?;
+            assert_eq!(
+                map,
+                json!({
+                    "version": 3,
+                    "sources": ["/turbopack/input.js"],
+                    "sourcesContent": ["const a = 1;\n"],
+                    "names": [],
+                    "mappings": "AAAA,EAAM",
+                })
+            );
+            Ok(())
+        })
+        .await
+        .unwrap();
+    }
+}
@@ -0,0 +1,84 @@
+use std::fmt::Debug;
+
+use anyhow::Result;
+use swc_core::common::{BytePos, LineCol};
+
+/// The code returned by an [EmittedCodePostProcessor].
+pub struct PostProcessedCode {
+    pub code: Vec<u8>,
+    /// The number of lines that were inserted before the emitted code, or
+    /// removed from its start when negative. The source map of the module is
+    /// shifted by it.
+    pub line_delta: i32,
+}
+
+/// A final textual pass over the emitted code of every module, before it
+/// becomes the content of the module, e.g. to inject a build banner or to
+/// rewrite a sentinel.
+///
+/// Changes within a line aren't reflected in the source map, only the line
+/// delta which the processor reports. When the processor panics, the module
+/// throws when it's evaluated, like when a code generation visitor panics.
+pub trait EmittedCodePostProcessor: Debug + Send + Sync {
+    fn process(&self, code: Vec<u8>) -> Result<PostProcessedCode>;
+}
+
+#[turbo_tasks::value(
+    transparent,
+    serialization = "none",
+    eq = "manual",
+    into = "new",
+    cell = "new"
+)]
+#[derive(Debug)]
+pub struct CodePostProcessor(#[turbo_tasks(trace_ignore)] Box<dyn EmittedCodePostProcessor>);
+
+impl CodePostProcessorVc {
+    pub fn new(processor: impl EmittedCodePostProcessor + 'static) -> Self {
+        Self::cell(Box::new(processor))
+    }
+}
+
+/// Shifts the generated lines of the source map mappings by `line_delta`.
+/// Mappings which would be moved before the first line are dropped.
+pub(crate) fn shift_mappings(
+    mappings: Vec<(BytePos, LineCol)>,
+    line_delta: i32,
+) -> Vec<(BytePos, LineCol)> {
+    if line_delta == 0 {
+        return mappings;
+    }
+    mappings
+        .into_iter()
+        .filter_map(|(pos, LineCol { line, col })| {
+            let line = u32::try_from(i64::from(line) + i64::from(line_delta)).ok()?;
+            Some((pos, LineCol { line, col }))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use swc_core::common::{BytePos, LineCol};
+
+    use super::shift_mappings;
+
+    fn mapping(pos: u32, line: u32) -> (BytePos, LineCol) {
+        (BytePos(pos), LineCol { line, col: 0 })
+    }
+
+    #[test]
+    fn shifts_mappings() {
+        let mappings = vec![mapping(1, 0), mapping(5, 1), mapping(9, 3)];
+        assert_eq!(shift_mappings(mappings.clone(), 0), mappings);
+        assert_eq!(
+            shift_mappings(mappings.clone(), 2),
+            vec![mapping(1, 2), mapping(5, 3), mapping(9, 5)]
+        );
+        // Mappings of removed lines are dropped.
+        assert_eq!(
+            shift_mappings(mappings, -1),
+            vec![mapping(5, 0), mapping(9, 2)]
+        );
+    }
+}
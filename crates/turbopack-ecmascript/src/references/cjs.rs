@@ -16,7 +16,7 @@ use crate::{
     chunk::EcmascriptChunkingContextVc,
     code_gen::{CodeGenerateable, CodeGenerateableVc, CodeGeneration, CodeGenerationVc},
     create_visitor,
-    references::{util::throw_module_not_found_expr, AstPathVc},
+    references::{interop::lint_require_interop, util::throw_module_not_found_expr, AstPathVc},
     resolve::{cjs_resolve, try_to_severity},
 };
 
@@ -82,6 +82,10 @@ pub struct CjsRequireAssetReference {
     pub path: AstPathVc,
     pub issue_source: IssueSourceVc,
     pub in_try: bool,
+    /// A `.default` member expression on the result of the call.
+    pub default_access: Option<IssueSourceVc>,
+    /// Whether interop hazards are reported as errors.
+    pub strict_interop: bool,
 }
 
 #[turbo_tasks::value_impl]
@@ -93,6 +97,8 @@ impl CjsRequireAssetReferenceVc {
         path: AstPathVc,
         issue_source: IssueSourceVc,
         in_try: bool,
+        default_access: Option<IssueSourceVc>,
+        strict_interop: bool,
     ) -> Self {
         Self::cell(CjsRequireAssetReference {
             origin,
@@ -100,6 +106,8 @@ impl CjsRequireAssetReferenceVc {
             path,
             issue_source,
             in_try,
+            default_access,
+            strict_interop,
         })
     }
 }
@@ -108,12 +116,22 @@ impl CjsRequireAssetReferenceVc {
 impl AssetReference for CjsRequireAssetReference {
     #[turbo_tasks::function]
     fn resolve_reference(&self) -> ResolveResultVc {
-        cjs_resolve(
+        let result = cjs_resolve(
             self.origin,
             self.request,
             OptionIssueSourceVc::some(self.issue_source),
             try_to_severity(self.in_try),
-        )
+        );
+        // The lint is a separate task which emits its issues on its own. It
+        // analyzes the required modules, so the resolution must not wait for
+        // it, as a module which is required in a cycle would wait for itself.
+        let _ = lint_require_interop(
+            result,
+            self.issue_source,
+            self.default_access,
+            self.strict_interop,
+        );
+        result
     }
 }
 
@@ -0,0 +1,232 @@
+use std::{collections::HashMap, iter::once};
+
+use anyhow::Result;
+use indexmap::IndexSet;
+use swc_core::{
+    common::DUMMY_SP,
+    ecma::ast::{ArrayLit, Expr, ExprOrSpread},
+    quote,
+};
+use turbo_tasks::{
+    graph::{AdjacencyMap, GraphTraversal},
+    primitives::BoolVc,
+    Value,
+};
+use turbopack_core::{
+    chunk::{availability_info::AvailabilityInfo, ChunkableModuleReference, ModuleId},
+    resolve::ExternalType,
+};
+
+use super::{
+    base::{insert_after_hoisted_stmts, ReferencedAsset},
+    EsmAssetReferenceVc,
+};
+use crate::{
+    chunk::{
+        EcmascriptAsyncModule, EcmascriptAsyncModuleVc, EcmascriptChunkPlaceable,
+        EcmascriptChunkPlaceableVc, EcmascriptChunkingContextVc,
+    },
+    code_gen::{
+        CodeGenerateableWithAvailabilityInfo, CodeGenerateableWithAvailabilityInfoVc,
+        CodeGeneration, CodeGenerationVc,
+    },
+    create_visitor,
+    tree_shake::asset::EcmascriptModulePartAssetVc,
+    EcmascriptModuleAssetVc,
+};
+
+/// A module of an import graph, see [async_module_graph].
+#[turbo_tasks::value]
+struct AsyncModuleNode {
+    /// Whether the module contains a top-level `await`, imports an ES module
+    /// external or is an [EcmascriptAsyncModule], so it's async regardless of
+    /// its imports.
+    self_async: bool,
+    /// The modules which are statically imported by the module.
+    imports: Vec<EcmascriptChunkPlaceableVc>,
+}
+
+#[turbo_tasks::function]
+async fn async_module_node(module: EcmascriptChunkPlaceableVc) -> Result<AsyncModuleNodeVc> {
+    let mut node = AsyncModuleNode {
+        self_async: false,
+        imports: Vec::new(),
+    };
+    if let Some(module) = EcmascriptAsyncModuleVc::resolve_from(module).await? {
+        node.self_async = *module.is_async().await?;
+        return Ok(node.cell());
+    }
+    let analysis = if let Some(module) = EcmascriptModuleAssetVc::resolve_from(module).await? {
+        module.failsafe_analyze()
+    } else if let Some(part) = EcmascriptModulePartAssetVc::resolve_from(module).await? {
+        part.analyze()
+    } else {
+        return Ok(node.cell());
+    };
+    let analysis = analysis.await?;
+    node.self_async = analysis.has_top_level_await;
+    for reference in analysis.references.await?.iter() {
+        let Some(reference) = EsmAssetReferenceVc::resolve_from(reference).await? else {
+            continue;
+        };
+        match &*reference.get_referenced_asset().await? {
+            ReferencedAsset::Some(imported) => node.imports.push(imported.resolve().await?),
+            // ES module externals are loaded with `import()`, see
+            // [ExternalType::EcmaScriptModule].
+            ReferencedAsset::OriginalReferenceTypeExternal(_, ExternalType::EcmaScriptModule) => {
+                node.self_async = true;
+            }
+            _ => {}
+        }
+    }
+    Ok(node.cell())
+}
+
+/// The modules statically imported by a root module, and which of them have
+/// to be evaluated asynchronously.
+#[turbo_tasks::value]
+struct AsyncModuleGraph {
+    modules: IndexSet<EcmascriptChunkPlaceableVc>,
+    async_modules: IndexSet<EcmascriptChunkPlaceableVc>,
+}
+
+/// Walks the import graph of `root` once and propagates asyncness from the
+/// self async modules to their importers, so every module of the graph is
+/// visited once, however many of them ask whether they are async.
+///
+/// Recursing into the imported modules instead would make the tasks wait on
+/// each other for import cycles.
+#[turbo_tasks::function]
+async fn async_module_graph(root: EcmascriptChunkPlaceableVc) -> Result<AsyncModuleGraphVc> {
+    let graph = AdjacencyMap::new()
+        .skip_duplicates()
+        .visit(once(root.resolve().await?), |&module| async move {
+            Ok(async_module_node(module).await?.imports.clone())
+        })
+        .await
+        .completed()?
+        .into_inner();
+
+    let mut importers: HashMap<_, Vec<_>> = HashMap::new();
+    let mut queue = Vec::new();
+    for &module in graph.iter_nodes() {
+        for &imported in graph.get(&module).into_iter().flatten() {
+            importers.entry(imported).or_default().push(module);
+        }
+        if async_module_node(module).await?.self_async {
+            queue.push(module);
+        }
+    }
+    let mut async_modules = IndexSet::new();
+    while let Some(module) = queue.pop() {
+        if async_modules.insert(module) {
+            queue.extend(importers.get(&module).into_iter().flatten().copied());
+        }
+    }
+
+    Ok(AsyncModuleGraph {
+        modules: graph.iter_nodes().copied().collect(),
+        async_modules,
+    }
+    .cell())
+}
+
+/// Returns whether the module has to be evaluated asynchronously, which is the
+/// case when it contains a top-level `await`, is an [EcmascriptAsyncModule],
+/// imports an ES module external or statically imports a module that has to be
+/// evaluated asynchronously.
+///
+/// The answer is looked up in the import graph of the current availability
+/// root, which is shared by all modules of a chunk group. Modules outside of
+/// it, e.g. ones only reached by `require()`, and untracked availability use
+/// the import graph of the module itself.
+#[turbo_tasks::function]
+pub(crate) async fn is_async_module(
+    module: EcmascriptChunkPlaceableVc,
+    availability_info: Value<AvailabilityInfo>,
+) -> Result<BoolVc> {
+    let module = module.resolve().await?;
+    if let Some(root) = availability_info.current_availability_root() {
+        if let Some(root) = EcmascriptChunkPlaceableVc::resolve_from(root).await? {
+            let graph = async_module_graph(root).await?;
+            if graph.modules.contains(&module) {
+                return Ok(BoolVc::cell(graph.async_modules.contains(&module)));
+            }
+        }
+    }
+    let graph = async_module_graph(module).await?;
+    Ok(BoolVc::cell(graph.async_modules.contains(&module)))
+}
+
+/// Makes an async module wait for the async modules it imports before the rest
+/// of its body is evaluated. The module itself is wrapped by the module
+/// factory, see [crate::chunk::EcmascriptChunkItemOptions::async_module].
+#[turbo_tasks::value]
+pub struct AsyncModule {
+    import_references: Vec<EsmAssetReferenceVc>,
+}
+
+#[turbo_tasks::value_impl]
+impl AsyncModuleVc {
+    #[turbo_tasks::function]
+    pub fn new(import_references: Vec<EsmAssetReferenceVc>) -> Self {
+        Self::cell(AsyncModule { import_references })
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl CodeGenerateableWithAvailabilityInfo for AsyncModule {
+    #[turbo_tasks::function]
+    async fn code_generation(
+        &self,
+        context: EcmascriptChunkingContextVc,
+        availability_info: Value<AvailabilityInfo>,
+    ) -> Result<CodeGenerationVc> {
+        let mut ids = Vec::new();
+        for reference in self.import_references.iter() {
+            // only chunked references are imported
+            if reference.chunking_type().await?.is_none() {
+                continue;
+            }
+            let ReferencedAsset::Some(imported) = &*reference.get_referenced_asset().await? else {
+                continue;
+            };
+            if !*is_async_module(*imported, availability_info).await? {
+                continue;
+            }
+            let id = imported.as_chunk_item(context).id().await?;
+            let id = Expr::Lit(match &*id {
+                ModuleId::String(s) => s.clone().into(),
+                ModuleId::Number(n) => (*n as f64).into(),
+            });
+            if !ids.contains(&id) {
+                ids.push(id);
+            }
+        }
+
+        let mut visitors = Vec::new();
+        if !ids.is_empty() {
+            let ids = Expr::Array(ArrayLit {
+                span: DUMMY_SP,
+                elems: ids
+                    .into_iter()
+                    .map(|id| {
+                        Some(ExprOrSpread {
+                            spread: None,
+                            expr: Box::new(id),
+                        })
+                    })
+                    .collect(),
+            });
+            visitors.push(create_visitor!(visit_mut_program(program: &mut Program) {
+                let stmt = quote!(
+                    "await __turbopack_wait_for_async_dependencies__($ids);" as Stmt,
+                    ids: Expr = ids.clone()
+                );
+                insert_after_hoisted_stmts(program, stmt);
+            }));
+        }
+
+        Ok(CodeGeneration { visitors }.into())
+    }
+}
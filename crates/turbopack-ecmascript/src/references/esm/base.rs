@@ -5,7 +5,10 @@ use swc_core::{
     ecma::ast::{Expr, ExprStmt, Ident, Lit, Module, ModuleItem, Program, Script, Stmt},
     quote,
 };
-use turbo_tasks::{primitives::StringVc, Value, ValueToString, ValueToStringVc};
+use turbo_tasks::{
+    primitives::{OptionStringVc, StringVc},
+    Value, ValueToString, ValueToStringVc,
+};
 use turbopack_core::{
     asset::Asset,
     chunk::{
@@ -16,24 +19,31 @@ use turbopack_core::{
     reference::{AssetReference, AssetReferenceVc},
     reference_type::EcmaScriptModulesReferenceSubType,
     resolve::{
-        origin::ResolveOriginVc, parse::RequestVc, ModulePartVc, PrimaryResolveResult,
-        ResolveResultVc,
+        origin::ResolveOriginVc, parse::RequestVc, ExternalType, ModulePartVc,
+        PrimaryResolveResult, ResolveResultVc,
     },
 };
 
 use crate::{
     analyzer::imports::ImportAnnotations,
-    chunk::{EcmascriptChunkPlaceable, EcmascriptChunkPlaceableVc, EcmascriptChunkingContextVc},
+    chunk::{
+        EcmascriptChunkPlaceable, EcmascriptChunkPlaceableVc, EcmascriptChunkingContextVc,
+        EcmascriptConstantModule, EcmascriptConstantModuleVc,
+    },
     code_gen::{CodeGenerateable, CodeGenerateableVc, CodeGeneration, CodeGenerationVc},
     create_visitor, magic_identifier,
     references::util::{request_to_string, throw_module_not_found_expr},
     resolve::esm_resolve,
+    CjsDefaultInterop,
 };
 
 #[turbo_tasks::value]
 pub enum ReferencedAsset {
     Some(EcmascriptChunkPlaceableVc),
-    OriginalReferenceTypeExternal(String),
+    OriginalReferenceTypeExternal(String, ExternalType),
+    /// The module is provided by a global variable, see
+    /// [crate::GlobalsExternals].
+    Global(String),
     None,
 }
 
@@ -41,10 +51,10 @@ impl ReferencedAsset {
     pub async fn get_ident(&self) -> Result<Option<String>> {
         Ok(match self {
             ReferencedAsset::Some(asset) => Some(Self::get_ident_from_placeable(asset).await?),
-            ReferencedAsset::OriginalReferenceTypeExternal(request) => {
+            ReferencedAsset::OriginalReferenceTypeExternal(request, _) => {
                 Some(magic_identifier::mangle(&format!("external {}", request)))
             }
-            ReferencedAsset::None => None,
+            ReferencedAsset::Global(_) | ReferencedAsset::None => None,
         })
     }
 
@@ -70,15 +80,21 @@ impl ReferencedAssetVc {
             match result {
                 PrimaryResolveResult::OriginalReferenceExternal => {
                     if let Some(request) = request.await?.request() {
-                        return Ok(ReferencedAsset::OriginalReferenceTypeExternal(request).cell());
+                        return Ok(ReferencedAsset::OriginalReferenceTypeExternal(
+                            request,
+                            ExternalType::CommonJs,
+                        )
+                        .cell());
                     } else {
                         return Ok(ReferencedAssetVc::cell(ReferencedAsset::None));
                     }
                 }
-                PrimaryResolveResult::OriginalReferenceTypeExternal(request) => {
-                    return Ok(
-                        ReferencedAsset::OriginalReferenceTypeExternal(request.clone()).cell(),
-                    );
+                PrimaryResolveResult::OriginalReferenceTypeExternal(request, ty) => {
+                    return Ok(ReferencedAsset::OriginalReferenceTypeExternal(
+                        request.clone(),
+                        *ty,
+                    )
+                    .cell());
                 }
                 PrimaryResolveResult::Asset(asset) => {
                     if let Some(placeable) = EcmascriptChunkPlaceableVc::resolve_from(asset).await?
@@ -102,6 +118,19 @@ pub struct EsmAssetReference {
     pub annotations: ImportAnnotations,
 
     pub export_name: Option<ModulePartVc>,
+
+    /// The global variable which provides the module, instead of resolving
+    /// the request.
+    pub global: Option<String>,
+
+    /// Constant modules whose value is at most this number of bytes are
+    /// inlined instead of imported, see
+    /// [crate::EcmascriptOptions::inline_json_bytes].
+    pub inline_json_bytes: Option<usize>,
+
+    /// What a default import of the module binds to when it's a CommonJS
+    /// module, see [EsmBinding](super::EsmBinding).
+    pub cjs_default_interop: CjsDefaultInterop,
 }
 
 impl EsmAssetReference {
@@ -117,8 +146,11 @@ impl EsmAssetReference {
 #[turbo_tasks::value_impl]
 impl EsmAssetReferenceVc {
     #[turbo_tasks::function]
-    pub(super) async fn get_referenced_asset(self) -> Result<ReferencedAssetVc> {
+    pub(crate) async fn get_referenced_asset(self) -> Result<ReferencedAssetVc> {
         let this = self.await?;
+        if let Some(global) = &this.global {
+            return Ok(ReferencedAsset::Global(global.clone()).cell());
+        }
 
         Ok(ReferencedAssetVc::from_resolve_result(
             self.resolve_reference(),
@@ -126,18 +158,71 @@ impl EsmAssetReferenceVc {
         ))
     }
 
+    /// The JSON text of the imported module when it is inlined instead of
+    /// imported. Each importer gets its own copy of the value, so the
+    /// module is not emitted unless it's referenced in another way.
+    #[turbo_tasks::function]
+    pub(crate) async fn inline_json(self) -> Result<OptionStringVc> {
+        let this = self.await?;
+        let Some(max_bytes) = this.inline_json_bytes else {
+            return Ok(OptionStringVc::cell(None));
+        };
+        // An explicit chunking type asks for the module itself.
+        if this.annotations.chunking_type().is_some() {
+            return Ok(OptionStringVc::cell(None));
+        }
+        let ReferencedAsset::Some(asset) = &*self.get_referenced_asset().await? else {
+            return Ok(OptionStringVc::cell(None));
+        };
+        let Some(module) = EcmascriptConstantModuleVc::resolve_from(asset).await? else {
+            return Ok(OptionStringVc::cell(None));
+        };
+        let json = module.inline_json().await?;
+        Ok(OptionStringVc::cell(
+            json.as_ref()
+                .filter(|json| json.len() <= max_bytes)
+                .cloned(),
+        ))
+    }
+
     #[turbo_tasks::function]
     pub fn new(
         origin: ResolveOriginVc,
         request: RequestVc,
         annotations: Value<ImportAnnotations>,
         export_name: Option<ModulePartVc>,
+        inline_json_bytes: Option<usize>,
+        cjs_default_interop: Value<CjsDefaultInterop>,
     ) -> Self {
         Self::cell(EsmAssetReference {
             origin,
             request,
             annotations: annotations.into_value(),
             export_name,
+            global: None,
+            inline_json_bytes,
+            cjs_default_interop: cjs_default_interop.into_value(),
+        })
+    }
+
+    /// Creates an import of `request` which reads the global variable
+    /// `global`. It is not resolved and should not be added as a reference of
+    /// the module.
+    #[turbo_tasks::function]
+    pub fn new_global(
+        origin: ResolveOriginVc,
+        request: RequestVc,
+        annotations: Value<ImportAnnotations>,
+        global: String,
+    ) -> Self {
+        Self::cell(EsmAssetReference {
+            origin,
+            request,
+            annotations: annotations.into_value(),
+            export_name: None,
+            global: Some(global),
+            inline_json_bytes: None,
+            cjs_default_interop: CjsDefaultInterop::default(),
         })
     }
 }
@@ -176,9 +261,14 @@ impl ValueToString for EsmAssetReference {
 #[turbo_tasks::value_impl]
 impl ChunkableModuleReference for EsmAssetReference {
     #[turbo_tasks::function]
-    fn chunking_type(&self) -> Result<ChunkingTypeOptionVc> {
+    async fn chunking_type(self_vc: EsmAssetReferenceVc) -> Result<ChunkingTypeOptionVc> {
+        // Inlined modules don't need to be in the chunk.
+        if self_vc.inline_json().await?.is_some() {
+            return Ok(ChunkingTypeOptionVc::cell(None));
+        }
+        let this = self_vc.await?;
         Ok(ChunkingTypeOptionVc::cell(
-            if let Some(chunking_type) = self.annotations.chunking_type() {
+            if let Some(chunking_type) = this.annotations.chunking_type() {
                 match chunking_type {
                     "parallel" => Some(ChunkingType::Parallel),
                     "isolatedParallel" => Some(ChunkingType::IsolatedParallel),
@@ -221,8 +311,40 @@ impl CodeGenerateable for EsmAssetReference {
             return Ok(CodeGeneration { visitors }.into());
         }
 
-        // only chunked references can be imported
-        if chunking_type.is_some() {
+        if let Some(json) = &*self_vc.inline_json().await? {
+            let referenced_asset = self_vc.get_referenced_asset().await?;
+            if let Some(ident) = referenced_asset.get_ident().await? {
+                let json = json.clone();
+                visitors.push(create_visitor!(visit_mut_program(program: &mut Program) {
+                    // Creates the same namespace object as `__turbopack_import__`
+                    // would for the module.
+                    let stmt = quote!(
+                        "var $name = ((raw) => {
+                            const getters = Object.create(null);
+                            if (typeof raw === \"object\") {
+                                for (const key of Object.getOwnPropertyNames(raw)) {
+                                    getters[key] = () => raw[key];
+                                }
+                            }
+                            getters.default = () => raw;
+                            const ns = {};
+                            Object.defineProperty(ns, \"__esModule\", { value: true });
+                            if (typeof Symbol !== \"undefined\" && Symbol.toStringTag) {
+                                Object.defineProperty(ns, Symbol.toStringTag, { value: \"Module\" });
+                            }
+                            for (const key in getters) {
+                                Object.defineProperty(ns, key, { get: getters[key], enumerable: true });
+                            }
+                            return ns;
+                        })(JSON.parse($json));" as Stmt,
+                        name = Ident::new(ident.clone().into(), DUMMY_SP),
+                        json: Expr = Expr::Lit(json.clone().into())
+                    );
+                    insert_hoisted_stmt(program, stmt);
+                }));
+            }
+        } else if chunking_type.is_some() {
+            // only chunked references can be imported
             let referenced_asset = self_vc.get_referenced_asset().await?;
             if let Some(ident) = referenced_asset.get_ident().await? {
                 match &*referenced_asset {
@@ -240,7 +362,7 @@ impl CodeGenerateable for EsmAssetReference {
                             insert_hoisted_stmt(program, stmt);
                         }));
                     }
-                    ReferencedAsset::OriginalReferenceTypeExternal(request) => {
+                    ReferencedAsset::OriginalReferenceTypeExternal(request, ty) => {
                         if !*context.environment().node_externals().await? {
                             bail!(
                                 "the chunking context does not support Node.js external modules \
@@ -249,17 +371,30 @@ impl CodeGenerateable for EsmAssetReference {
                             );
                         }
                         let request = request.clone();
+                        let ty = *ty;
                         visitors.push(create_visitor!(visit_mut_program(program: &mut Program) {
-                            // TODO Technically this should insert a ESM external, but we don't support that yet
-                            let stmt = quote!(
-                                "var $name = __turbopack_external_require__($id, true);" as Stmt,
-                                name = Ident::new(ident.clone().into(), DUMMY_SP),
-                                id: Expr = Expr::Lit(request.clone().into())
-                            );
+                            let name = Ident::new(ident.clone().into(), DUMMY_SP);
+                            let id = Expr::Lit(request.clone().into());
+                            let stmt = match ty {
+                                ExternalType::CommonJs => quote!(
+                                    "var $name = __turbopack_external_require__($id, true);"
+                                        as Stmt,
+                                    name = name,
+                                    id: Expr = id
+                                ),
+                                // The module is evaluated asynchronously, see
+                                // [super::async_module::is_async_module].
+                                ExternalType::EcmaScriptModule => quote!(
+                                    "var $name = await import($id);" as Stmt,
+                                    name = name,
+                                    id: Expr = id
+                                ),
+                            };
                             insert_hoisted_stmt(program, stmt);
                         }));
                     }
-                    ReferencedAsset::None => {}
+                    // Globals are read where they are used.
+                    ReferencedAsset::Global(_) | ReferencedAsset::None => {}
                 }
             }
         }
@@ -328,3 +463,46 @@ pub(crate) fn insert_hoisted_stmt(program: &mut Program, stmt: Stmt) {
         }
     }
 }
+
+/// Inserts a statement after all hoisted statements, e.g. after all imports.
+pub(crate) fn insert_after_hoisted_stmts(program: &mut Program, stmt: Stmt) {
+    let is_hoisting_location = |stmt: &Stmt| {
+        if let Stmt::Expr(ExprStmt {
+            expr: box Expr::Lit(Lit::Str(s)),
+            ..
+        }) = stmt
+        {
+            &*s.value == *ESM_HOISTING_LOCATION
+        } else {
+            false
+        }
+    };
+    let hoisting_location = || {
+        Stmt::Expr(ExprStmt {
+            expr: Box::new(Expr::Lit(Lit::Str((*ESM_HOISTING_LOCATION).into()))),
+            span: DUMMY_SP,
+        })
+    };
+    match program {
+        Program::Module(Module { body, .. }) => {
+            let pos = body.iter().position(
+                |item| matches!(item, ModuleItem::Stmt(stmt) if is_hoisting_location(stmt)),
+            );
+            if let Some(pos) = pos {
+                body.insert(pos + 1, ModuleItem::Stmt(stmt));
+            } else {
+                body.insert(0, ModuleItem::Stmt(stmt));
+                body.insert(0, ModuleItem::Stmt(hoisting_location()));
+            }
+        }
+        Program::Script(Script { body, .. }) => {
+            let pos = body.iter().position(is_hoisting_location);
+            if let Some(pos) = pos {
+                body.insert(pos + 1, stmt);
+            } else {
+                body.insert(0, stmt);
+                body.insert(0, hoisting_location());
+            }
+        }
+    }
+}
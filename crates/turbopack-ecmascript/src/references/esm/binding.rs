@@ -8,14 +8,20 @@ use swc_core::{
         },
         visit::fields::PropField,
     },
+    quote, quote_expr,
+};
+use turbopack_core::{
+    chunk::{ChunkableModuleReference, ModuleId},
+    resolve::ExternalType,
 };
 
-use super::EsmAssetReferenceVc;
+use super::{base::ReferencedAsset, global::global_binding_expr, EsmAssetReferenceVc};
 use crate::{
-    chunk::EcmascriptChunkingContextVc,
+    chunk::{EcmascriptChunkPlaceable, EcmascriptChunkingContextVc, EcmascriptExports},
     code_gen::{CodeGenerateable, CodeGenerateableVc, CodeGeneration, CodeGenerationVc},
     create_visitor,
     references::AstPathVc,
+    CjsDefaultInterop,
 };
 
 #[turbo_tasks::value(shared)]
@@ -48,7 +54,7 @@ impl CodeGenerateable for EsmBinding {
     #[turbo_tasks::function]
     async fn code_generation(
         self_vc: EsmBindingVc,
-        _context: EcmascriptChunkingContextVc,
+        context: EcmascriptChunkingContextVc,
     ) -> Result<CodeGenerationVc> {
         let this = self_vc.await?;
         let mut visitors = Vec::new();
@@ -74,7 +80,25 @@ impl CodeGenerateable for EsmBinding {
         }
 
         let mut ast_path = this.ast_path.await?.clone_value();
-        let imported_module = imported_module.await?.get_ident().await?;
+        let imported_module = imported_module.await?;
+        let imported = match &*imported_module {
+            ReferencedAsset::Global(global) => {
+                Some(global_binding_expr(global, this.export.as_deref()))
+            }
+            _ if this.export.as_deref() == Some("default") => {
+                match cjs_default_expr(this.reference, &imported_module, context).await? {
+                    Some(expr) => Some(expr),
+                    None => imported_module
+                        .get_ident()
+                        .await?
+                        .map(|ident| make_expr(&ident, Some("default"))),
+                }
+            }
+            imported_module => imported_module
+                .get_ident()
+                .await?
+                .map(|ident| make_expr(&ident, this.export.as_deref())),
+        };
 
         loop {
             match ast_path.last() {
@@ -86,8 +110,8 @@ impl CodeGenerateable for EsmBinding {
                         create_visitor!(exact ast_path, visit_mut_prop(prop: &mut Prop) {
                             if let Prop::Shorthand(ident) = prop {
                                 // TODO: Merge with the above condition when https://rust-lang.github.io/rfcs/2497-if-let-chains.html lands.
-                                if let Some(imported_ident) = imported_module.as_deref() {
-                                    *prop = Prop::KeyValue(KeyValueProp { key: PropName::Ident(ident.clone()), value: Box::new(make_expr(imported_ident, this.export.as_deref()))});
+                                if let Some(imported) = &imported {
+                                    *prop = Prop::KeyValue(KeyValueProp { key: PropName::Ident(ident.clone()), value: Box::new(imported.clone())});
                                 }
                             }
                         }),
@@ -99,8 +123,8 @@ impl CodeGenerateable for EsmBinding {
                     ast_path.pop();
                     visitors.push(
                         create_visitor!(exact ast_path, visit_mut_expr(expr: &mut Expr) {
-                            if let Some(ident) = imported_module.as_deref() {
-                                *expr = make_expr(ident, this.export.as_deref());
+                            if let Some(imported) = &imported {
+                                *expr = imported.clone();
                             }
                             // If there's no identifier for the imported module,
                             // resolution failed and will insert code that throws
@@ -119,3 +143,54 @@ impl CodeGenerateable for EsmBinding {
         Ok(CodeGeneration { visitors }.into())
     }
 }
+
+/// The expression for the default export of `imported_module`, as read by a
+/// default import, `ns.default` or `export { default } from`, when it's a
+/// CommonJS module and the importer doesn't use the
+/// [CjsDefaultInterop::Babel] interop, which is what the namespace object of
+/// `__turbopack_import__` provides. The module exports are read with
+/// `__turbopack_require__` instead, which doesn't apply any interop.
+pub(super) async fn cjs_default_expr(
+    reference: EsmAssetReferenceVc,
+    imported_module: &ReferencedAsset,
+    context: EcmascriptChunkingContextVc,
+) -> Result<Option<Expr>> {
+    let interop = reference.await?.cjs_default_interop;
+    if matches!(interop, CjsDefaultInterop::Babel) {
+        return Ok(None);
+    }
+    let exports = match imported_module {
+        ReferencedAsset::Some(asset) => {
+            if !matches!(*asset.get_exports().await?, EcmascriptExports::CommonJs)
+                || reference.chunking_type().await?.is_none()
+            {
+                return Ok(None);
+            }
+            let id = asset.as_chunk_item(context).id().await?;
+            quote_expr!(
+                "__turbopack_require__($id)",
+                id: Expr = Expr::Lit(match &*id {
+                    ModuleId::String(s) => s.clone().into(),
+                    ModuleId::Number(n) => (*n as f64).into(),
+                })
+            )
+        }
+        ReferencedAsset::OriginalReferenceTypeExternal(request, ExternalType::CommonJs) => {
+            quote_expr!(
+                "__turbopack_external_require__($id)",
+                id: Expr = Expr::Lit(request.clone().into())
+            )
+        }
+        // The namespace of an imported ES module has a real default export.
+        ReferencedAsset::OriginalReferenceTypeExternal(_, ExternalType::EcmaScriptModule)
+        | ReferencedAsset::Global(_)
+        | ReferencedAsset::None => return Ok(None),
+    };
+    Ok(Some(match interop {
+        CjsDefaultInterop::Node => *exports,
+        CjsDefaultInterop::EsmStrict => {
+            quote!("$exports.default" as Expr, exports: Expr = *exports)
+        }
+        CjsDefaultInterop::Babel => unreachable!("handled above"),
+    }))
+}
@@ -1,13 +1,26 @@
+use std::collections::HashMap;
+
 use anyhow::Result;
+use lazy_static::lazy_static;
+use regex::Regex;
 use swc_core::{
-    ecma::ast::{Callee, ExprOrSpread},
+    base::SwcComments,
+    common::{Span, Spanned},
+    ecma::{
+        ast::{CallExpr, Callee, ExprOrSpread, Program},
+        visit::{Visit, VisitWith},
+    },
     quote_expr,
 };
-use turbo_tasks::{primitives::StringVc, Value, ValueToString, ValueToStringVc};
+use turbo_tasks::{
+    primitives::{OptionStringVc, StringVc},
+    Value, ValueToString, ValueToStringVc,
+};
 use turbopack_core::{
     chunk::{
-        availability_info::AvailabilityInfo, ChunkableModuleReference, ChunkableModuleReferenceVc,
-        ChunkingType, ChunkingTypeOptionVc,
+        availability_info::AvailabilityInfo, sanitize_chunk_name, ChunkableModuleReference,
+        ChunkableModuleReferenceVc, ChunkingContext, ChunkingContextVc, ChunkingType,
+        ChunkingTypeOptionVc,
     },
     issue::{IssueSourceVc, OptionIssueSourceVc},
     reference::{AssetReference, AssetReferenceVc},
@@ -35,6 +48,8 @@ pub struct EsmAsyncAssetReference {
     pub path: AstPathVc,
     pub issue_source: IssueSourceVc,
     pub in_try: bool,
+    /// The name of the async chunk group, given by a magic comment.
+    pub chunk_name: Option<String>,
 }
 
 #[turbo_tasks::value_impl]
@@ -46,6 +61,7 @@ impl EsmAsyncAssetReferenceVc {
         path: AstPathVc,
         issue_source: IssueSourceVc,
         in_try: bool,
+        chunk_name: Option<String>,
     ) -> Self {
         Self::cell(EsmAsyncAssetReference {
             origin,
@@ -53,6 +69,7 @@ impl EsmAsyncAssetReferenceVc {
             path,
             issue_source,
             in_try,
+            chunk_name,
         })
     }
 }
@@ -88,6 +105,11 @@ impl ChunkableModuleReference for EsmAsyncAssetReference {
     fn chunking_type(&self) -> ChunkingTypeOptionVc {
         ChunkingTypeOptionVc::cell(Some(ChunkingType::Async))
     }
+
+    #[turbo_tasks::function]
+    fn chunk_name(&self) -> OptionStringVc {
+        OptionStringVc::cell(self.chunk_name.clone())
+    }
 }
 
 #[turbo_tasks::value_impl]
@@ -98,10 +120,14 @@ impl CodeGenerateableWithAvailabilityInfo for EsmAsyncAssetReference {
         context: EcmascriptChunkingContextVc,
         availability_info: Value<AvailabilityInfo>,
     ) -> Result<CodeGenerationVc> {
+        // The same chunking context is used for the async chunk group when the
+        // chunk content is collected, so the same manifest loader item is referenced.
+        let context = ChunkingContextVc::from(context)
+            .with_chunk_name(self.chunk_name.as_deref().unwrap_or_default());
         let pm = PatternMappingVc::resolve_request(
             self.request,
             self.origin,
-            context.into(),
+            context,
             esm_resolve(
                 self.origin,
                 self.request,
@@ -192,3 +218,56 @@ impl CodeGenerateableWithAvailabilityInfo for EsmAsyncAssetReference {
         .into())
     }
 }
+
+/// Returns the chunk names of the `import()` expressions in `program` by the
+/// span of the call. A chunk name is given by a `webpackChunkName` or
+/// `turbopackChunkName` magic comment in front of the request, e.g.
+/// `import(/* webpackChunkName: "settings" */ "./settings")`. Names which are
+/// invalid after sanitizing are ignored.
+pub(crate) fn import_chunk_names(
+    program: &Program,
+    comments: &SwcComments,
+) -> HashMap<Span, String> {
+    let mut visitor = ImportChunkNames {
+        comments,
+        chunk_names: HashMap::new(),
+    };
+    // Most modules don't contain magic comments, so they aren't visited.
+    if comments
+        .leading
+        .iter()
+        .any(|entry| entry.value().iter().any(|c| c.text.contains("ChunkName")))
+    {
+        program.visit_with(&mut visitor);
+    }
+    visitor.chunk_names
+}
+
+struct ImportChunkNames<'a> {
+    comments: &'a SwcComments,
+    chunk_names: HashMap<Span, String>,
+}
+
+impl Visit for ImportChunkNames<'_> {
+    fn visit_call_expr(&mut self, call: &CallExpr) {
+        if let (Callee::Import(_), Some(arg)) = (&call.callee, call.args.first()) {
+            if let Some(comments) = self.comments.leading.get(&arg.expr.span_lo()) {
+                if let Some(name) = comments.iter().find_map(|c| chunk_name(&c.text)) {
+                    self.chunk_names.insert(call.span, name);
+                }
+            }
+        }
+        call.visit_children_with(self);
+    }
+}
+
+/// Parses the sanitized chunk name of a magic comment.
+fn chunk_name(comment: &str) -> Option<String> {
+    lazy_static! {
+        static ref CHUNK_NAME: Regex =
+            Regex::new(r#"(?:webpack|turbopack)ChunkName\s*:\s*(?:"([^"]*)"|'([^']*)')"#).unwrap();
+    }
+    let captures = CHUNK_NAME.captures(comment)?;
+    let name = captures.get(1).or_else(|| captures.get(2))?;
+    sanitize_chunk_name(name.as_str())
+}
@@ -1,6 +1,6 @@
 use std::{
     borrow::Cow,
-    collections::{BTreeMap, HashSet},
+    collections::{BTreeMap, BTreeSet, HashSet},
 };
 
 use anyhow::Result;
@@ -19,7 +19,12 @@ use turbopack_core::{
     issue::{analyze::AnalyzeIssue, IssueSeverity},
 };
 
-use super::{base::ReferencedAsset, EsmAssetReferenceVc};
+use super::{
+    base::ReferencedAsset,
+    binding::cjs_default_expr,
+    global::{global_binding_expr, global_expr},
+    EsmAssetReferenceVc,
+};
 use crate::{
     chunk::{
         EcmascriptChunkPlaceable, EcmascriptChunkPlaceableVc, EcmascriptChunkingContextVc,
@@ -138,12 +143,47 @@ pub struct EsmExports {
     pub star_exports: Vec<EsmAssetReferenceVc>,
 }
 
+/// The export names of a module, see
+/// [crate::chunk::EcmascriptExportsVc::names].
+#[turbo_tasks::value(shared)]
+pub(crate) struct ExportNames {
+    pub names: Vec<String>,
+    /// Whether the module might have exports that are not listed in `names`.
+    pub incomplete: bool,
+}
+
+#[turbo_tasks::value_impl]
+impl EsmExportsVc {
+    /// Lists the names of the exports, including those re-exported by
+    /// `export *` as far as they can be determined statically.
+    #[turbo_tasks::function]
+    pub(crate) async fn export_names(self) -> Result<ExportNamesVc> {
+        let this = self.await?;
+        let mut names: BTreeSet<String> = this.exports.keys().cloned().collect();
+        let mut incomplete = false;
+        for esm_ref in this.star_exports.iter() {
+            if let ReferencedAsset::Some(asset) = &*esm_ref.get_referenced_asset().await? {
+                let export_info = expand_star_exports(*asset).await?;
+                names.extend(export_info.star_exports.iter().cloned());
+                incomplete |= export_info.has_dynamic_exports;
+            } else {
+                incomplete = true;
+            }
+        }
+        Ok(ExportNames {
+            names: names.into_iter().collect(),
+            incomplete,
+        }
+        .cell())
+    }
+}
+
 #[turbo_tasks::value_impl]
 impl CodeGenerateable for EsmExports {
     #[turbo_tasks::function]
     async fn code_generation(
         self_vc: EsmExportsVc,
-        _context: EcmascriptChunkingContextVc,
+        context: EcmascriptChunkingContextVc,
     ) -> Result<CodeGenerationVc> {
         let this = self_vc.await?;
         let mut visitors = Vec::new();
@@ -157,7 +197,14 @@ impl CodeGenerateable for EsmExports {
         let mut dynamic_exports = Vec::<Box<Expr>>::new();
 
         for esm_ref in this.star_exports.iter() {
-            if let ReferencedAsset::Some(asset) = &*esm_ref.get_referenced_asset().await? {
+            let referenced_asset = esm_ref.get_referenced_asset().await?;
+            if let ReferencedAsset::Global(global) = &*referenced_asset {
+                // The exports of a global can only be enumerated at runtime.
+                dynamic_exports.push(quote_expr!(
+                    "__turbopack_dynamic__($arg)",
+                    arg: Expr = global_expr(global)
+                ));
+            } else if let ReferencedAsset::Some(asset) = &*referenced_asset {
                 let export_info = expand_star_exports(*asset).await?;
                 let export_names = &export_info.star_exports;
                 for export in export_names.iter() {
@@ -190,32 +237,56 @@ impl CodeGenerateable for EsmExports {
                 )),
                 EsmExport::ImportedBinding(esm_ref, name) => {
                     let referenced_asset = esm_ref.get_referenced_asset().await?;
-                    referenced_asset.get_ident().await?.map(|ident| {
-                        quote!(
+                    let cjs_default = if name == "default" {
+                        cjs_default_expr(*esm_ref, &referenced_asset, context).await?
+                    } else {
+                        None
+                    };
+                    if let ReferencedAsset::Global(global) = &*referenced_asset {
+                        Some(quote!(
+                            "(() => $expr)" as Expr,
+                            expr: Expr = global_binding_expr(global, Some(name))
+                        ))
+                    } else if let Some(cjs_default) = cjs_default {
+                        Some(quote!(
                             "(() => $expr)" as Expr,
-                            expr: Expr = Expr::Member(MemberExpr {
-                                span: DUMMY_SP,
-                                obj: Box::new(Expr::Ident(Ident::new(ident.into(), DUMMY_SP))),
-                                prop: MemberProp::Computed(ComputedPropName {
+                            expr: Expr = cjs_default
+                        ))
+                    } else {
+                        referenced_asset.get_ident().await?.map(|ident| {
+                            quote!(
+                                "(() => $expr)" as Expr,
+                                expr: Expr = Expr::Member(MemberExpr {
                                     span: DUMMY_SP,
-                                    expr: Box::new(Expr::Lit(Lit::Str(Str {
+                                    obj: Box::new(Expr::Ident(Ident::new(ident.into(), DUMMY_SP))),
+                                    prop: MemberProp::Computed(ComputedPropName {
                                         span: DUMMY_SP,
-                                        value: (name as &str).into(),
-                                        raw: None,
-                                    })))
+                                        expr: Box::new(Expr::Lit(Lit::Str(Str {
+                                            span: DUMMY_SP,
+                                            value: (name as &str).into(),
+                                            raw: None,
+                                        })))
+                                    })
                                 })
-                            })
-                        )
-                    })
+                            )
+                        })
+                    }
                 }
                 EsmExport::ImportedNamespace(esm_ref) => {
                     let referenced_asset = esm_ref.get_referenced_asset().await?;
-                    referenced_asset.get_ident().await?.map(|ident| {
-                        quote!(
-                            "(() => $imported)" as Expr,
-                            imported = Ident::new(ident.into(), DUMMY_SP)
-                        )
-                    })
+                    if let ReferencedAsset::Global(global) = &*referenced_asset {
+                        Some(quote!(
+                            "(() => $expr)" as Expr,
+                            expr: Expr = global_expr(global)
+                        ))
+                    } else {
+                        referenced_asset.get_ident().await?.map(|ident| {
+                            quote!(
+                                "(() => $imported)" as Expr,
+                                imported = Ident::new(ident.into(), DUMMY_SP)
+                            )
+                        })
+                    }
                 }
             };
             if let Some(expr) = expr {
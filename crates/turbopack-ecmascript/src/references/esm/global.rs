@@ -0,0 +1,128 @@
+use anyhow::Result;
+use swc_core::{
+    common::DUMMY_SP,
+    ecma::ast::{
+        Callee, ComputedPropName, Expr, ExprOrSpread, Ident, Lit, MemberExpr, MemberProp, Str,
+    },
+    quote_expr,
+};
+
+use crate::{
+    chunk::EcmascriptChunkingContextVc,
+    code_gen::{CodeGenerateable, CodeGenerateableVc, CodeGeneration, CodeGenerationVc},
+    create_visitor,
+    references::AstPathVc,
+};
+
+/// Returns the expression which reads the global `global`, e.g. `window.React`.
+pub(crate) fn global_expr(global: &str) -> Expr {
+    let mut parts = global.split('.');
+    let mut expr = Expr::Ident(Ident::new(
+        parts.next().unwrap_or_default().into(),
+        DUMMY_SP,
+    ));
+    for part in parts {
+        expr = Expr::Member(MemberExpr {
+            span: DUMMY_SP,
+            obj: Box::new(expr),
+            prop: MemberProp::Ident(Ident::new(part.into(), DUMMY_SP)),
+        });
+    }
+    expr
+}
+
+/// Returns the expression which reads the binding `export` of the module
+/// provided by the global `global`. The default export and the namespace
+/// (`None`) are the global itself. Other exports are read from its properties,
+/// which can't be checked at build time.
+pub(crate) fn global_binding_expr(global: &str, export: Option<&str>) -> Expr {
+    match export {
+        None | Some("default") => global_expr(global),
+        Some(export) => Expr::Member(MemberExpr {
+            span: DUMMY_SP,
+            obj: Box::new(global_expr(global)),
+            prop: MemberProp::Computed(ComputedPropName {
+                span: DUMMY_SP,
+                expr: Box::new(Expr::Lit(Lit::Str(Str {
+                    span: DUMMY_SP,
+                    value: export.into(),
+                    raw: None,
+                }))),
+            }),
+        }),
+    }
+}
+
+/// An `import()` of a module which is provided by a global. It resolves to the
+/// global, without loading anything.
+#[turbo_tasks::value]
+pub struct EsmGlobalImport {
+    global: String,
+    path: AstPathVc,
+}
+
+#[turbo_tasks::value_impl]
+impl EsmGlobalImportVc {
+    #[turbo_tasks::function]
+    pub fn new(global: String, path: AstPathVc) -> Self {
+        Self::cell(EsmGlobalImport { global, path })
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl CodeGenerateable for EsmGlobalImport {
+    #[turbo_tasks::function]
+    async fn code_generation(
+        &self,
+        _context: EcmascriptChunkingContextVc,
+    ) -> Result<CodeGenerationVc> {
+        let global = global_expr(&self.global);
+        let visitors = [
+            create_visitor!(exact &self.path.await?, visit_mut_call_expr(call_expr: &mut CallExpr) {
+                call_expr.callee = Callee::Expr(quote_expr!("Promise.resolve"));
+                call_expr.args = vec![ExprOrSpread {
+                    spread: None,
+                    expr: Box::new(global.clone()),
+                }];
+            }),
+        ]
+        .into();
+
+        Ok(CodeGeneration { visitors }.cell())
+    }
+}
+
+/// A `require()` of a module which is provided by a global. It's replaced by
+/// the global itself, like the default import.
+#[turbo_tasks::value]
+pub struct GlobalRequire {
+    global: String,
+    path: AstPathVc,
+}
+
+#[turbo_tasks::value_impl]
+impl GlobalRequireVc {
+    #[turbo_tasks::function]
+    pub fn new(global: String, path: AstPathVc) -> Self {
+        Self::cell(GlobalRequire { global, path })
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl CodeGenerateable for GlobalRequire {
+    #[turbo_tasks::function]
+    async fn code_generation(
+        &self,
+        _context: EcmascriptChunkingContextVc,
+    ) -> Result<CodeGenerationVc> {
+        let global = global_expr(&self.global);
+        let visitors = [
+            create_visitor!(&self.path.await?, visit_mut_expr(expr: &mut Expr) {
+                *expr = global.clone();
+            }),
+        ]
+        .into();
+
+        Ok(CodeGeneration { visitors }.cell())
+    }
+}
@@ -7,6 +7,7 @@ use swc_core::{
     quote,
 };
 use turbo_tasks_fs::FileSystemPathVc;
+use turbopack_core::chunk::ChunkingContext;
 
 use crate::{
     chunk::EcmascriptChunkingContextVc,
@@ -39,9 +40,11 @@ impl CodeGenerateable for ImportMetaBinding {
     #[turbo_tasks::function]
     async fn code_generation(
         &self,
-        _context: EcmascriptChunkingContextVc,
+        context: EcmascriptChunkingContextVc,
     ) -> Result<CodeGenerationVc> {
-        let path = as_abs_path(self.path).await?.as_str().map_or_else(
+        let abs_path = as_abs_path(self.path).await?;
+        let abs_path = abs_path.as_str();
+        let url = abs_path.map_or_else(
             || {
                 quote!(
                     "(() => { throw new Error('could not convert import.meta.url to filepath') })()"
@@ -51,13 +54,54 @@ impl CodeGenerateable for ImportMetaBinding {
             |path| format!("file://{}", encode_path(path)).into(),
         );
 
-        let visitor = create_visitor!(visit_mut_program(program: &mut Program) {
-            let meta = quote!(
-                "const $name = { url: $path };" as Stmt,
+        // `import.meta.dirname` and `import.meta.filename` only make sense when the
+        // output runs on the file system. In other environments, accessing them is
+        // reported during analysis and throws at runtime.
+        let meta = if *context.environment().node_externals().await? {
+            let (dirname, filename): (Expr, Expr) = match abs_path {
+                Some(path) => {
+                    let dirname = path.rsplit_once('/').map_or("/", |(dirname, _)| {
+                        if dirname.is_empty() {
+                            "/"
+                        } else {
+                            dirname
+                        }
+                    });
+                    (dirname.into(), path.into())
+                }
+                None => {
+                    let throw = quote!(
+                        "(() => { throw new Error('could not convert import.meta to filepath') })()"
+                            as Expr
+                    );
+                    (throw.clone(), throw)
+                }
+            };
+            quote!(
+                "const $name = { url: $url, dirname: $dirname, filename: $filename };" as Stmt,
+                name = meta_ident(),
+                url: Expr = url,
+                dirname: Expr = dirname,
+                filename: Expr = filename,
+            )
+        } else {
+            quote!(
+                "const $name = {
+                    url: $url,
+                    get dirname() {
+                        throw new Error('import.meta.dirname is not available in this environment');
+                    },
+                    get filename() {
+                        throw new Error('import.meta.filename is not available in this environment');
+                    },
+                };" as Stmt,
                 name = meta_ident(),
-                path: Expr = path.clone(),
-            );
-            insert_hoisted_stmt(program, meta);
+                url: Expr = url,
+            )
+        };
+
+        let visitor = create_visitor!(visit_mut_program(program: &mut Program) {
+            insert_hoisted_stmt(program, meta.clone());
         });
 
         Ok(CodeGeneration {
@@ -136,7 +180,7 @@ fn encode_path(path: &'_ str) -> Cow<'_, str> {
     Cow::Owned(encoded)
 }
 
-fn meta_ident() -> Ident {
+pub(super) fn meta_ident() -> Ident {
     Ident::new(magic_identifier::mangle("import.meta").into(), DUMMY_SP)
 }
 
@@ -1,18 +1,25 @@
+pub(crate) mod async_module;
 pub(crate) mod base;
 pub(crate) mod binding;
 pub(crate) mod dynamic;
 pub(crate) mod export;
+pub(crate) mod global;
 pub(crate) mod meta;
 pub(crate) mod module_id;
 pub(crate) mod module_item;
 pub(crate) mod url;
 
 pub use self::{
+    async_module::{AsyncModule, AsyncModuleVc},
     base::{EsmAssetReference, EsmAssetReferenceVc},
     binding::{EsmBinding, EsmBindingVc},
     dynamic::{EsmAsyncAssetReference, EsmAsyncAssetReferenceVc},
-    export::{EsmExports, EsmExportsVc},
+    export::{EsmExport, EsmExports, EsmExportsVc},
+    global::{EsmGlobalImport, EsmGlobalImportVc, GlobalRequire, GlobalRequireVc},
     meta::{ImportMetaBinding, ImportMetaBindingVc, ImportMetaRef, ImportMetaRefVc},
     module_item::{EsmModuleItem, EsmModuleItemVc},
-    url::{UrlAssetReference, UrlAssetReferenceVc},
+    url::{
+        ImportMetaResolveAssetReference, ImportMetaResolveAssetReferenceVc, UrlAssetReference,
+        UrlAssetReferenceVc,
+    },
 };
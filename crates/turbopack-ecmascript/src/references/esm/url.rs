@@ -24,7 +24,7 @@ use crate::{
     chunk::{EcmascriptChunkPlaceable, EcmascriptChunkingContextVc},
     code_gen::{CodeGenerateable, CodeGenerateableVc, CodeGeneration, CodeGenerationVc},
     create_visitor,
-    references::AstPathVc,
+    references::{esm::meta::meta_ident, AstPathVc},
     resolve::{try_to_severity, url_resolve},
     utils::module_id_to_lit,
 };
@@ -121,38 +121,7 @@ impl CodeGenerateable for UrlAssetReference {
 
         let referenced_asset = self_vc.get_referenced_asset().await?;
 
-        // For rendering environments (CSR and SSR), we rewrite the `import.meta.url` to
-        // be a location.origin because it allows us to access files from the root of
-        // the dev server. It's important that this be rewritten for SSR as well, so
-        // that the client's hydration matches exactly.
-        //
-        // In a non-rendering env, the `import.meta.url` is already the correct `file://` URL
-        // to load files.
-        let rewrite = match &*this.rendering.await? {
-            Rendering::None => {
-                CodeGenerationIssue {
-                    severity: IssueSeverity::Error.into(),
-                    title: StringVc::cell(
-                        "new URL(…) not implemented for this environment".to_string(),
-                    ),
-                    message: StringVc::cell(
-                        "new URL(…) is only currently supported for rendering environments like \
-                         Client-Side or Server-Side Rendering."
-                            .to_string(),
-                    ),
-                    path: this.origin.origin_path(),
-                }
-                .cell()
-                .as_issue()
-                .emit();
-                None
-            }
-            Rendering::Client => Some(quote!("location.origin" as Expr)),
-            Rendering::Server(server_addr) => {
-                let location = server_addr.await?.to_string()?;
-                Some(location.into())
-            }
-        };
+        let rewrite = url_base_rewrite(this.rendering, this.origin, "new URL(…)").await?;
 
         let ast_path = this.ast_path.await?;
 
@@ -181,7 +150,7 @@ impl CodeGenerateable for UrlAssetReference {
                     }),
                 );
             }
-            ReferencedAsset::OriginalReferenceTypeExternal(request) => {
+            ReferencedAsset::OriginalReferenceTypeExternal(request, _) => {
                 let request = request.to_string();
                 visitors.push(
                     create_visitor!(ast_path, visit_mut_expr(new_expr: &mut Expr) {
@@ -199,7 +168,191 @@ impl CodeGenerateable for UrlAssetReference {
                     }),
                 );
             }
-            ReferencedAsset::None => {}
+            ReferencedAsset::Global(_) | ReferencedAsset::None => {}
+        }
+
+        Ok(CodeGeneration { visitors }.into())
+    }
+}
+
+/// Computes the base that a `new URL(…)` (or equivalent) should be resolved
+/// against in the given rendering environment.
+///
+/// For rendering environments (CSR and SSR), we rewrite the `import.meta.url`
+/// to be a location.origin because it allows us to access files from the root
+/// of the dev server. It's important that this be rewritten for SSR as well, so
+/// that the client's hydration matches exactly.
+///
+/// In a non-rendering env, the `import.meta.url` is already the correct
+/// `file://` URL to load files, so no rewrite is returned and an issue is
+/// emitted instead.
+pub(crate) async fn url_base_rewrite(
+    rendering: RenderingVc,
+    origin: ResolveOriginVc,
+    feature: &str,
+) -> Result<Option<Expr>> {
+    Ok(match &*rendering.await? {
+        Rendering::None => {
+            CodeGenerationIssue {
+                severity: IssueSeverity::Error.into(),
+                title: StringVc::cell(format!("{feature} not implemented for this environment")),
+                message: StringVc::cell(format!(
+                    "{feature} is only currently supported for rendering environments like \
+                     Client-Side or Server-Side Rendering."
+                )),
+                path: origin.origin_path(),
+            }
+            .cell()
+            .as_issue()
+            .emit();
+            None
+        }
+        Rendering::Client => Some(quote!("location.origin" as Expr)),
+        Rendering::Server(server_addr) => {
+            let location = server_addr.await?.to_string()?;
+            Some(location.into())
+        }
+    })
+}
+
+/// Import meta resolve references are injected during code analysis when we
+/// find a (staticly analyzable) `import.meta.resolve("path")`.
+///
+/// It's responsible for rewriting the call into the URL of the referenced
+/// file, making sure that file is emitted.
+#[turbo_tasks::value]
+pub struct ImportMetaResolveAssetReference {
+    origin: ResolveOriginVc,
+    request: RequestVc,
+    rendering: RenderingVc,
+    ast_path: AstPathVc,
+    issue_source: IssueSourceVc,
+    in_try: bool,
+}
+
+#[turbo_tasks::value_impl]
+impl ImportMetaResolveAssetReferenceVc {
+    #[turbo_tasks::function]
+    pub fn new(
+        origin: ResolveOriginVc,
+        request: RequestVc,
+        rendering: RenderingVc,
+        ast_path: AstPathVc,
+        issue_source: IssueSourceVc,
+        in_try: bool,
+    ) -> Self {
+        ImportMetaResolveAssetReference {
+            origin,
+            request,
+            rendering,
+            ast_path,
+            issue_source,
+            in_try,
+        }
+        .cell()
+    }
+
+    #[turbo_tasks::function]
+    pub(super) async fn get_referenced_asset(self) -> Result<ReferencedAssetVc> {
+        let this = self.await?;
+        Ok(ReferencedAssetVc::from_resolve_result(
+            self.resolve_reference(),
+            this.request,
+        ))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl AssetReference for ImportMetaResolveAssetReference {
+    #[turbo_tasks::function]
+    async fn resolve_reference(&self) -> ResolveResultVc {
+        url_resolve(
+            self.origin,
+            self.request,
+            Value::new(UrlReferenceSubType::EcmaScriptNewUrl),
+            self.issue_source,
+            try_to_severity(self.in_try),
+        )
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ValueToString for ImportMetaResolveAssetReference {
+    #[turbo_tasks::function]
+    async fn to_string(&self) -> Result<StringVc> {
+        Ok(StringVc::cell(format!(
+            "import.meta.resolve({})",
+            self.request.to_string().await?,
+        )))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ChunkableModuleReference for ImportMetaResolveAssetReference {
+    #[turbo_tasks::function]
+    fn chunking_type(&self) -> ChunkingTypeOptionVc {
+        ChunkingTypeOptionVc::cell(Some(ChunkingType::PlacedOrParallel))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl CodeGenerateable for ImportMetaResolveAssetReference {
+    #[turbo_tasks::function]
+    async fn code_generation(
+        self_vc: ImportMetaResolveAssetReferenceVc,
+        context: EcmascriptChunkingContextVc,
+    ) -> Result<CodeGenerationVc> {
+        let this = self_vc.await?;
+        let mut visitors = vec![];
+
+        let referenced_asset = self_vc.get_referenced_asset().await?;
+        let ast_path = this.ast_path.await?;
+
+        match &*referenced_asset {
+            ReferencedAsset::Some(asset) => {
+                // We rewrite the call into a URL built from a require() of the chunk
+                // item, which exports the static asset path to the linked file.
+                let id = asset.as_chunk_item(context).id().await?;
+
+                if *context.environment().node_externals().await? {
+                    // The path is relative to the root of the output file system.
+                    // It's resolved relative to the output root at runtime, since
+                    // the output can be moved after it's built.
+                    let output_root = context.output_root().await?;
+                    let fs_root = context.output_root().root().await?;
+                    let fs_root = output_root
+                        .get_relative_path_to(&fs_root)
+                        .unwrap_or_else(|| ".".to_string());
+                    visitors.push(create_visitor!(ast_path, visit_mut_expr(expr: &mut Expr) {
+                        *expr = quote!(
+                            "__turbopack_external_require__(\"url\").pathToFileURL(
+                                __turbopack_resolve_absolute_path__($root + __turbopack_require__($id))
+                            ).href" as Expr,
+                            root: Expr = fs_root.as_str().into(),
+                            id: Expr = module_id_to_lit(&id),
+                        );
+                    }));
+                } else {
+                    let base =
+                        url_base_rewrite(this.rendering, this.origin, "import.meta.resolve(…)")
+                            .await?
+                            .unwrap_or_else(|| quote!("$meta.url" as Expr, meta = meta_ident()));
+                    visitors.push(create_visitor!(ast_path, visit_mut_expr(expr: &mut Expr) {
+                        *expr = quote!(
+                            "new URL(__turbopack_require__($id), $base).href" as Expr,
+                            id: Expr = module_id_to_lit(&id),
+                            base: Expr = base.clone(),
+                        );
+                    }));
+                }
+            }
+            ReferencedAsset::OriginalReferenceTypeExternal(request, _) => {
+                let request = request.to_string();
+                visitors.push(create_visitor!(ast_path, visit_mut_expr(expr: &mut Expr) {
+                    *expr = request.as_str().into();
+                }));
+            }
+            ReferencedAsset::Global(_) | ReferencedAsset::None => {}
         }
 
         Ok(CodeGeneration { visitors }.into())
@@ -0,0 +1,312 @@
+use anyhow::{bail, Result};
+use indexmap::IndexMap;
+use swc_core::{
+    common::DUMMY_SP,
+    ecma::ast::{Expr, KeyValueProp, Lit, ObjectLit, Prop, PropName, PropOrSpread},
+    quote_expr,
+};
+use turbo_tasks::{primitives::StringVc, Value, ValueToString, ValueToStringVc};
+use turbo_tasks_fs::{glob::GlobVc, DirectoryEntry, FileSystemPathVc, ReadGlobResultVc};
+use turbopack_core::{
+    chunk::{
+        availability_info::AvailabilityInfo, ChunkableModuleReference, ChunkableModuleReferenceVc,
+        ChunkingType, ChunkingTypeOptionVc,
+    },
+    issue::{IssueSourceVc, OptionIssueSourceVc},
+    reference::{AssetReference, AssetReferenceVc},
+    reference_type::EcmaScriptModulesReferenceSubType,
+    resolve::{origin::ResolveOriginVc, parse::RequestVc, ResolveResultVc},
+};
+
+use crate::{
+    chunk::EcmascriptChunkingContextVc,
+    code_gen::{
+        CodeGenerateableWithAvailabilityInfo, CodeGenerateableWithAvailabilityInfoVc,
+        CodeGeneration, CodeGenerationVc,
+    },
+    create_visitor,
+    references::{
+        pattern_mapping::{PatternMapping, PatternMappingVc, ResolveType},
+        AstPathVc,
+    },
+    resolve::{esm_resolve, try_to_severity},
+};
+
+/// The files matching the patterns of an `import.meta.glob(..)` call, by
+/// their path relative to the module, e.g. `./routes/index.tsx`.
+#[turbo_tasks::value(transparent)]
+pub struct ImportMetaGlobMap(IndexMap<String, FileSystemPathVc>);
+
+#[turbo_tasks::value_impl]
+impl ImportMetaGlobMapVc {
+    /// Reads the files matching `patterns` relative to `dir`. Files matching
+    /// a pattern starting with `!` are excluded.
+    #[turbo_tasks::function]
+    pub(crate) async fn generate(dir: FileSystemPathVc, patterns: Vec<String>) -> Result<Self> {
+        let mut map = IndexMap::new();
+        let mut excluded = Vec::new();
+
+        for pattern in &patterns {
+            if let Some(pattern) = pattern.strip_prefix('!') {
+                excluded.push(GlobVc::new(pattern).await?);
+                continue;
+            }
+            let (prefix, glob) = split_pattern(pattern);
+            let Some(base) = *dir.try_join(prefix).await? else {
+                bail!("the pattern `{pattern}` leaves the filesystem root");
+            };
+            let result = base.read_glob(GlobVc::new(glob), false);
+            collect_files(prefix, result, &mut map).await?;
+        }
+
+        map.retain(|key, _| !excluded.iter().any(|glob| glob.execute(key)));
+        map.sort_keys();
+
+        Ok(Self::cell(map))
+    }
+}
+
+/// Splits a pattern into the leading `./` and `../` segments and the glob,
+/// e.g. `../icons/*.svg` into `../` and `icons/*.svg`.
+fn split_pattern(pattern: &str) -> (&str, &str) {
+    let mut glob = pattern;
+    loop {
+        if let Some(rest) = glob.strip_prefix("./") {
+            glob = rest;
+        } else if let Some(rest) = glob.strip_prefix("../") {
+            glob = rest;
+        } else {
+            break;
+        }
+    }
+    let prefix = &pattern[..pattern.len() - glob.len()];
+    // `./../` is the same as `../`.
+    match prefix.strip_prefix("./") {
+        Some(rest) if !rest.is_empty() => (rest, glob),
+        _ => (prefix, glob),
+    }
+}
+
+/// Adds the files of a glob result to `map`, keyed by their path prefixed
+/// with `prefix`.
+async fn collect_files(
+    prefix: &str,
+    result: ReadGlobResultVc,
+    map: &mut IndexMap<String, FileSystemPathVc>,
+) -> Result<()> {
+    let mut queue = vec![result];
+    while let Some(result) = queue.pop() {
+        let result = result.await?;
+        for (path, entry) in result.results.iter() {
+            if let DirectoryEntry::File(file) = entry {
+                map.insert(format!("{prefix}{path}"), *file);
+            }
+        }
+        queue.extend(result.inner.values().copied());
+    }
+    Ok(())
+}
+
+/// A reference to a module matching an `import.meta.glob(..)` call. Lazily
+/// imported modules are placed in their own chunk group, like `import()`.
+#[turbo_tasks::value]
+#[derive(Hash, Debug)]
+pub struct ImportMetaGlobAssetReference {
+    pub origin: ResolveOriginVc,
+    pub request: RequestVc,
+    pub eager: bool,
+    pub issue_source: IssueSourceVc,
+    pub in_try: bool,
+}
+
+#[turbo_tasks::value_impl]
+impl ImportMetaGlobAssetReferenceVc {
+    #[turbo_tasks::function]
+    pub fn new(
+        origin: ResolveOriginVc,
+        request: RequestVc,
+        eager: bool,
+        issue_source: IssueSourceVc,
+        in_try: bool,
+    ) -> Self {
+        Self::cell(ImportMetaGlobAssetReference {
+            origin,
+            request,
+            eager,
+            issue_source,
+            in_try,
+        })
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl AssetReference for ImportMetaGlobAssetReference {
+    #[turbo_tasks::function]
+    fn resolve_reference(&self) -> ResolveResultVc {
+        esm_resolve(
+            self.origin,
+            self.request,
+            Default::default(),
+            OptionIssueSourceVc::some(self.issue_source),
+            try_to_severity(self.in_try),
+        )
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ValueToString for ImportMetaGlobAssetReference {
+    #[turbo_tasks::function]
+    async fn to_string(&self) -> Result<StringVc> {
+        Ok(StringVc::cell(format!(
+            "import.meta.glob {}",
+            self.request.to_string().await?,
+        )))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ChunkableModuleReference for ImportMetaGlobAssetReference {
+    #[turbo_tasks::function]
+    fn chunking_type(&self) -> ChunkingTypeOptionVc {
+        ChunkingTypeOptionVc::cell(Some(if self.eager {
+            ChunkingType::default()
+        } else {
+            ChunkingType::Async
+        }))
+    }
+}
+
+/// Replaces an `import.meta.glob(..)` call with an object which maps the keys
+/// of the matching modules to the modules (eager) or to functions which import
+/// them (lazy).
+#[turbo_tasks::value]
+pub struct ImportMetaGlob {
+    origin: ResolveOriginVc,
+    map: ImportMetaGlobMapVc,
+    eager: bool,
+    import: Option<String>,
+    path: AstPathVc,
+    issue_source: IssueSourceVc,
+    in_try: bool,
+}
+
+#[turbo_tasks::value_impl]
+impl ImportMetaGlobVc {
+    #[turbo_tasks::function]
+    pub fn new(
+        origin: ResolveOriginVc,
+        map: ImportMetaGlobMapVc,
+        eager: bool,
+        import: Option<String>,
+        path: AstPathVc,
+        issue_source: IssueSourceVc,
+        in_try: bool,
+    ) -> Self {
+        Self::cell(ImportMetaGlob {
+            origin,
+            map,
+            eager,
+            import,
+            path,
+            issue_source,
+            in_try,
+        })
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl CodeGenerateableWithAvailabilityInfo for ImportMetaGlob {
+    #[turbo_tasks::function]
+    async fn code_generation(
+        &self,
+        context: EcmascriptChunkingContextVc,
+        availability_info: Value<AvailabilityInfo>,
+    ) -> Result<CodeGenerationVc> {
+        let resolve_type = if self.eager {
+            ResolveType::Cjs
+        } else {
+            ResolveType::EsmAsync(availability_info.into_value())
+        };
+
+        let mut object = ObjectLit {
+            span: DUMMY_SP,
+            props: vec![],
+        };
+        for key in self.map.await?.keys() {
+            let request = RequestVc::parse(Value::new(key.clone().into()));
+            let pm = PatternMappingVc::resolve_request(
+                request,
+                self.origin,
+                context.into(),
+                esm_resolve(
+                    self.origin,
+                    request,
+                    Value::new(EcmaScriptModulesReferenceSubType::Undefined),
+                    OptionIssueSourceVc::some(self.issue_source),
+                    try_to_severity(self.in_try),
+                ),
+                Value::new(resolve_type),
+            )
+            .await?;
+
+            let value = if self.eager {
+                let module = match &*pm {
+                    PatternMapping::Single(_) => {
+                        quote_expr!("__turbopack_import__($id)", id: Expr = pm.create())
+                    }
+                    _ => Box::new(pm.create()),
+                };
+                match &self.import {
+                    Some(import) => quote_expr!(
+                        "$module[$import]",
+                        module: Expr = *module,
+                        import: Expr = Expr::Lit(Lit::Str(import.as_str().into())),
+                    ),
+                    None => module,
+                }
+            } else {
+                let promise = match &*pm {
+                    PatternMapping::SingleLoader(_) => quote_expr!(
+                        "__turbopack_require__($loader)(__turbopack_import__)",
+                        loader: Expr = pm.create()
+                    ),
+                    PatternMapping::Single(_) => quote_expr!(
+                        "Promise.resolve().then(() => __turbopack_import__($id))",
+                        id: Expr = pm.create()
+                    ),
+                    _ => quote_expr!(
+                        "Promise.resolve().then(() => $module)",
+                        module: Expr = pm.create()
+                    ),
+                };
+                match &self.import {
+                    Some(import) => quote_expr!(
+                        "() => $promise.then((module) => module[$import])",
+                        promise: Expr = *promise,
+                        import: Expr = Expr::Lit(Lit::Str(import.as_str().into())),
+                    ),
+                    None => quote_expr!("() => $promise", promise: Expr = *promise),
+                }
+            };
+
+            object
+                .props
+                .push(PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+                    key: PropName::Str(key.as_str().into()),
+                    value,
+                }))));
+        }
+
+        let path = &self.path.await?;
+        let visitor = create_visitor!(path, visit_mut_expr(expr: &mut Expr) {
+            if let Expr::Call(_) = expr {
+                *expr = Expr::Object(object.clone());
+            }
+        });
+
+        Ok(CodeGeneration {
+            visitors: vec![visitor],
+        }
+        .into())
+    }
+}
@@ -0,0 +1,347 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+use swc_core::{
+    common::{Mark, Span, Spanned},
+    ecma::{
+        ast::*,
+        visit::{Visit, VisitWith},
+    },
+};
+use turbo_tasks::{primitives::StringVc, CompletionVc};
+use turbo_tasks_fs::FileSystemPathVc;
+use turbopack_core::{
+    asset::Asset,
+    issue::{
+        Issue, IssueSeverity, IssueSeverityVc, IssueSourceVc, IssueVc, IssuesVc,
+        OptionIssueSourceVc,
+    },
+    resolve::ResolveResultVc,
+};
+
+use super::is_turbopack_helper_import;
+use crate::{chunk::EcmascriptExports, EcmascriptModuleAssetVc};
+
+/// A hazard of the interop between CommonJS and EcmaScript modules, which
+/// works in some runtimes and bundlers, but not in others.
+#[turbo_tasks::value(serialization = "auto_for_input")]
+#[derive(Debug, Clone, Copy, PartialOrd, Ord, Hash)]
+pub enum InteropHazard {
+    /// `module.exports` or `exports` is assigned in a module which also uses
+    /// import or export declarations.
+    EsmWithCjsExports,
+    /// An EcmaScript module with a top level `await` is `require()`d.
+    RequireOfAsyncEsm,
+    /// `.default` is accessed on the result of a `require()` of an EcmaScript
+    /// module.
+    DefaultOfRequiredEsm,
+}
+
+/// Reported for an [InteropHazard]. It's a warning, or an error when
+/// [EcmascriptOptions::strict_interop](crate::EcmascriptOptions::strict_interop)
+/// is set.
+#[turbo_tasks::value(shared)]
+pub struct InteropIssue {
+    pub hazard: InteropHazard,
+    pub path: FileSystemPathVc,
+    pub strict: bool,
+    pub source: IssueSourceVc,
+    /// The other side of the conflict, e.g. the import or export declaration
+    /// of a module which assigns `module.exports`.
+    pub related: Option<IssueSourceVc>,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for InteropIssue {
+    #[turbo_tasks::function]
+    fn context(&self) -> FileSystemPathVc {
+        self.path
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> StringVc {
+        StringVc::cell(
+            match self.hazard {
+                InteropHazard::EsmWithCjsExports => {
+                    "CommonJS exports are assigned in an EcmaScript module"
+                }
+                InteropHazard::RequireOfAsyncEsm => {
+                    "An EcmaScript module with a top level await is required"
+                }
+                InteropHazard::DefaultOfRequiredEsm => {
+                    "`.default` is accessed on a required EcmaScript module"
+                }
+            }
+            .to_string(),
+        )
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> StringVc {
+        StringVc::cell(
+            match self.hazard {
+                InteropHazard::EsmWithCjsExports => {
+                    "The module uses import or export declarations, so it's an EcmaScript module \
+                     and its exports are the exports of the declarations. Assignments to \
+                     `module.exports` or `exports` are not exports of the module, and importers \
+                     won't see them.\nUse either EcmaScript export declarations or CommonJS \
+                     exports in one module."
+                }
+                InteropHazard::RequireOfAsyncEsm => {
+                    "`require()` returns synchronously, but the required module only finishes \
+                     evaluating after its top level await. The result may be a promise or an \
+                     incompletely evaluated module, depending on the runtime.\nUse `import` or \
+                     `import()` to load the module instead."
+                }
+                InteropHazard::DefaultOfRequiredEsm => {
+                    "`require()` of an EcmaScript module returns its namespace object, so \
+                     `.default` is its default export. Runtimes and bundlers which apply CommonJS \
+                     interop return the default export itself instead, so `.default` may be \
+                     undefined there.\nUse `import` to load the default export of the module \
+                     instead."
+                }
+            }
+            .to_string(),
+        )
+    }
+
+    #[turbo_tasks::function]
+    fn severity(&self) -> IssueSeverityVc {
+        if self.strict {
+            IssueSeverity::Error.cell()
+        } else {
+            IssueSeverity::Warning.cell()
+        }
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> StringVc {
+        StringVc::cell("interop".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn source(&self) -> OptionIssueSourceVc {
+        OptionIssueSourceVc::some(self.source)
+    }
+
+    #[turbo_tasks::function]
+    fn sub_issues(&self) -> IssuesVc {
+        IssuesVc::cell(
+            self.related
+                .map(|source| {
+                    InteropRelatedIssue {
+                        hazard: self.hazard,
+                        source,
+                    }
+                    .cell()
+                    .into()
+                })
+                .into_iter()
+                .collect(),
+        )
+    }
+}
+
+/// Points at the other side of the conflict of an [InteropIssue].
+#[turbo_tasks::value(shared)]
+pub struct InteropRelatedIssue {
+    pub hazard: InteropHazard,
+    pub source: IssueSourceVc,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for InteropRelatedIssue {
+    #[turbo_tasks::function]
+    async fn context(&self) -> Result<FileSystemPathVc> {
+        Ok(self.source.await?.source.ident().path())
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> StringVc {
+        StringVc::cell(
+            match self.hazard {
+                InteropHazard::EsmWithCjsExports => "The EcmaScript syntax is used here",
+                InteropHazard::RequireOfAsyncEsm => "The top level await is here",
+                InteropHazard::DefaultOfRequiredEsm => "The module is required here",
+            }
+            .to_string(),
+        )
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> StringVc {
+        StringVc::empty()
+    }
+
+    #[turbo_tasks::function]
+    fn severity(&self) -> IssueSeverityVc {
+        IssueSeverity::Note.cell()
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> StringVc {
+        StringVc::cell("interop".to_string())
+    }
+
+    #[turbo_tasks::function]
+    fn source(&self) -> OptionIssueSourceVc {
+        OptionIssueSourceVc::some(self.source)
+    }
+}
+
+/// Reports the hazards of requiring the modules of `result`, which are
+/// detected from their analysis. `default_access` is the `.default` member
+/// expression on the result of the `require()` call, if any.
+#[turbo_tasks::function]
+pub(crate) async fn lint_require_interop(
+    result: ResolveResultVc,
+    issue_source: IssueSourceVc,
+    default_access: Option<IssueSourceVc>,
+    strict: bool,
+) -> Result<CompletionVc> {
+    let path = issue_source.await?.source.ident().path();
+    for &asset in result.primary_assets().await?.iter() {
+        let Some(module) = EcmascriptModuleAssetVc::resolve_from(asset).await? else {
+            continue;
+        };
+        let analysis = module.analyze().await?;
+        if !matches!(&*analysis.exports.await?, EcmascriptExports::EsmExports(_)) {
+            continue;
+        }
+        if analysis.has_top_level_await {
+            InteropIssue {
+                hazard: InteropHazard::RequireOfAsyncEsm,
+                path,
+                strict,
+                source: issue_source,
+                related: analysis.top_level_await,
+            }
+            .cell()
+            .as_issue()
+            .emit();
+        }
+        if let Some(default_access) = default_access {
+            InteropIssue {
+                hazard: InteropHazard::DefaultOfRequiredEsm,
+                path,
+                strict,
+                source: default_access,
+                related: Some(issue_source),
+            }
+            .cell()
+            .as_issue()
+            .emit();
+        }
+    }
+    Ok(CompletionVc::new())
+}
+
+/// The span of the first CommonJS export assignment and the span of the first
+/// import or export declaration of a module which contains both.
+pub(crate) fn esm_with_cjs_exports(
+    program: &Program,
+    unresolved_mark: Mark,
+) -> Option<(Span, Span)> {
+    let Program::Module(module) = program else {
+        return None;
+    };
+    let esm_span = module
+        .body
+        .iter()
+        .find_map(|item| match item.as_module_decl()? {
+            ModuleDecl::Import(import) if is_turbopack_helper_import(import) => None,
+            decl => Some(decl.span()),
+        })?;
+
+    let mut visitor = CjsExportAssignment {
+        unresolved_mark,
+        span: None,
+    };
+    program.visit_with(&mut visitor);
+    Some((visitor.span?, esm_span))
+}
+
+struct CjsExportAssignment {
+    unresolved_mark: Mark,
+    span: Option<Span>,
+}
+
+impl Visit for CjsExportAssignment {
+    fn visit_assign_expr(&mut self, assign: &AssignExpr) {
+        if self.span.is_some() {
+            return;
+        }
+        let target = match &assign.left {
+            PatOrExpr::Expr(expr) => Some(&**expr),
+            PatOrExpr::Pat(pat) => pat.as_expr().map(|expr| &**expr),
+        };
+        if target.map_or(false, |target| {
+            is_cjs_exports_target(target, self.unresolved_mark)
+        }) {
+            self.span = Some(assign.span);
+            return;
+        }
+        assign.visit_children_with(self);
+    }
+}
+
+/// Matches `module.exports`, `module.exports.foo` and `exports.foo`, where
+/// `module` and `exports` are the free variables.
+fn is_cjs_exports_target(expr: &Expr, unresolved_mark: Mark) -> bool {
+    let Expr::Member(member) = expr else {
+        return false;
+    };
+    match &*member.obj {
+        Expr::Ident(obj) if is_free(obj, "exports", unresolved_mark) => true,
+        Expr::Ident(obj) if is_free(obj, "module", unresolved_mark) => {
+            matches!(&member.prop, MemberProp::Ident(prop) if &*prop.sym == "exports")
+        }
+        obj @ Expr::Member(_) => is_cjs_exports_target(obj, unresolved_mark),
+        _ => false,
+    }
+}
+
+/// The spans of `.default` member expressions by the span of the `require()`
+/// call they are accessed on, e.g. `require("./esm").default`.
+pub(crate) fn required_default_accesses(
+    program: &Program,
+    unresolved_mark: Mark,
+) -> HashMap<Span, Span> {
+    let mut visitor = RequiredDefaultAccesses {
+        unresolved_mark,
+        accesses: HashMap::new(),
+    };
+    program.visit_with(&mut visitor);
+    visitor.accesses
+}
+
+struct RequiredDefaultAccesses {
+    unresolved_mark: Mark,
+    accesses: HashMap<Span, Span>,
+}
+
+impl Visit for RequiredDefaultAccesses {
+    fn visit_member_expr(&mut self, member: &MemberExpr) {
+        let is_default = match &member.prop {
+            MemberProp::Ident(prop) => &*prop.sym == "default",
+            MemberProp::Computed(prop) => {
+                matches!(&*prop.expr, Expr::Lit(Lit::Str(str)) if &*str.value == "default")
+            }
+            MemberProp::PrivateName(_) => false,
+        };
+        if let (true, Expr::Call(call)) = (is_default, &*member.obj) {
+            if matches!(
+                &call.callee,
+                Callee::Expr(callee)
+                    if matches!(&**callee, Expr::Ident(ident) if is_free(ident, "require", self.unresolved_mark))
+            ) {
+                self.accesses.insert(call.span, member.span);
+            }
+        }
+        member.visit_children_with(self);
+    }
+}
+
+fn is_free(ident: &Ident, name: &str, unresolved_mark: Mark) -> bool {
+    &*ident.sym == name && ident.span.ctxt.outer() == unresolved_mark
+}
@@ -3,14 +3,18 @@ pub mod cjs;
 pub mod constant_condition;
 pub mod constant_value;
 pub mod esm;
+pub mod import_meta_glob;
+pub mod interop;
 pub mod node;
 pub mod pattern_mapping;
 pub mod raw;
 pub mod require_context;
+pub mod size_limit;
 pub mod type_issue;
 pub mod typescript;
 pub mod unreachable;
 pub mod util;
+pub mod worker;
 
 use std::{
     borrow::Cow,
@@ -24,7 +28,7 @@ use std::{
 use anyhow::Result;
 use constant_condition::{ConstantConditionValue, ConstantConditionVc};
 use constant_value::ConstantValueVc;
-use indexmap::IndexSet;
+use indexmap::{IndexMap, IndexSet};
 use lazy_static::lazy_static;
 use parking_lot::Mutex;
 use regex::Regex;
@@ -46,7 +50,7 @@ use swc_core::{
 };
 use turbo_tasks::{
     primitives::{BoolVc, RegexVc},
-    TryJoinIterExt, Value,
+    TryJoinIterExt, Value, ValueToString,
 };
 use turbo_tasks_fs::{FileJsonContent, FileSystemPathVc};
 use turbopack_core::{
@@ -76,14 +80,18 @@ use self::{
     },
     cjs::CjsAssetReferenceVc,
     esm::{
-        export::EsmExport, EsmAssetReferenceVc, EsmAsyncAssetReferenceVc, EsmExports,
-        EsmModuleItemVc, ImportMetaBindingVc, ImportMetaRefVc, UrlAssetReferenceVc,
+        dynamic::import_chunk_names, export::EsmExport, AsyncModuleVc, EsmAssetReferenceVc,
+        EsmAsyncAssetReferenceVc, EsmExports, EsmGlobalImportVc, EsmModuleItemVc, GlobalRequireVc,
+        ImportMetaBindingVc, ImportMetaRefVc, ImportMetaResolveAssetReferenceVc,
+        UrlAssetReferenceVc,
     },
     node::{DirAssetReferenceVc, PackageJsonReferenceVc},
     raw::FileSourceReferenceVc,
+    size_limit::{source_size, AnalysisSizeLimitIssue},
     typescript::{
         TsConfigReferenceVc, TsReferencePathAssetReferenceVc, TsReferenceTypeAssetReferenceVc,
     },
+    worker::WorkerReferenceVc,
 };
 use super::{
     analyzer::{
@@ -97,7 +105,7 @@ use super::{
     parse::{parse, ParseResult},
     resolve::{apply_cjs_specific_options, cjs_resolve},
     special_cases::special_cases,
-    utils::js_value_to_pattern,
+    utils::{is_empty_program, js_value_to_pattern},
     webpack::{
         parse::{webpack_runtime, WebpackRuntime, WebpackRuntimeVc},
         WebpackChunkAssetReference, WebpackEntryAssetReference, WebpackRuntimeAssetReference,
@@ -109,8 +117,8 @@ use crate::{
         builtin::early_replace_builtin,
         graph::{ConditionalKind, EffectArg, EvalContext, VarGraph},
         imports::{ImportedSymbol, Reexport},
-        parse_require_context,
-        top_level_await::has_top_level_await,
+        parse_import_meta_glob, parse_require_context,
+        top_level_await::top_level_await_span,
         ModuleValue, RequireContextValueVc,
     },
     chunk::{EcmascriptExports, EcmascriptExportsVc},
@@ -123,13 +131,19 @@ use crate::{
             CjsRequireAssetReferenceVc, CjsRequireCacheAccess, CjsRequireResolveAssetReferenceVc,
         },
         esm::{module_id::EsmModuleIdAssetReferenceVc, EsmBindingVc, EsmExportsVc},
+        import_meta_glob::{ImportMetaGlobAssetReferenceVc, ImportMetaGlobMapVc, ImportMetaGlobVc},
+        interop::{esm_with_cjs_exports, required_default_accesses, InteropHazard, InteropIssue},
         require_context::{RequireContextAssetReferenceVc, RequireContextMapVc},
         type_issue::SpecifiedModuleTypeIssue,
     },
     resolve::try_to_severity,
     tree_shake::{part_of_module, split},
-    typescript::resolve::tsconfig,
-    EcmascriptInputTransformsVc, EcmascriptOptions, SpecifiedModuleType, SpecifiedModuleTypeVc,
+    typescript::{
+        declaration::{triple_slash_directives, TripleSlashDirective},
+        resolve::tsconfig,
+    },
+    CjsDefaultInterop, EcmascriptInputTransformsVc, EcmascriptOptions, GlobalsExternalsReadRef,
+    SpecifiedModuleType, SpecifiedModuleTypeVc,
 };
 
 #[turbo_tasks::value(shared)]
@@ -138,6 +152,8 @@ pub struct AnalyzeEcmascriptModuleResult {
     pub code_generation: CodeGenerateablesVc,
     pub exports: EcmascriptExportsVc,
     pub has_top_level_await: bool,
+    /// The location of the first top level `await`.
+    pub top_level_await: Option<IssueSourceVc>,
     /// `true` when the analysis was successful.
     pub successful: bool,
 }
@@ -166,6 +182,80 @@ impl AnalyzeEcmascriptModuleResultVc {
         }
         Ok(BoolVc::cell(false))
     }
+
+    /// Compares the references of this analysis with the references of a
+    /// `prev`ious analysis of the module. References are compared by their
+    /// description and the idents of the assets they resolve to, so a
+    /// reference which was recreated by a new analysis but is logically the
+    /// same is not reported as changed.
+    #[turbo_tasks::function]
+    pub async fn references_diff(
+        self,
+        prev: AnalyzeEcmascriptModuleResultVc,
+    ) -> Result<ReferencesDiffVc> {
+        let current = keyed_references(self.await?.references).await?;
+        let prev = keyed_references(prev.await?.references).await?;
+        let added = current
+            .iter()
+            .filter(|(key, _)| !prev.contains_key(*key))
+            .map(|(_, reference)| *reference)
+            .collect();
+        let removed = prev
+            .iter()
+            .filter(|(key, _)| !current.contains_key(*key))
+            .map(|(_, reference)| *reference)
+            .collect();
+        Ok(ReferencesDiff {
+            added: AssetReferencesVc::cell(added),
+            removed: AssetReferencesVc::cell(removed),
+        }
+        .cell())
+    }
+}
+
+/// The references which a module gained and lost between two analyses, see
+/// [AnalyzeEcmascriptModuleResultVc::references_diff].
+#[turbo_tasks::value(shared)]
+pub struct ReferencesDiff {
+    pub added: AssetReferencesVc,
+    pub removed: AssetReferencesVc,
+}
+
+#[turbo_tasks::value_impl]
+impl ReferencesDiffVc {
+    /// `true` when the references of the module didn't change.
+    #[turbo_tasks::function]
+    pub async fn is_empty(self) -> Result<BoolVc> {
+        let this = self.await?;
+        Ok(BoolVc::cell(
+            this.added.await?.is_empty() && this.removed.await?.is_empty(),
+        ))
+    }
+}
+
+/// Keys the references by their description and the idents of the assets
+/// they resolve to.
+async fn keyed_references(
+    references: AssetReferencesVc,
+) -> Result<IndexMap<(String, Vec<String>), AssetReferenceVc>> {
+    references
+        .await?
+        .iter()
+        .map(|&reference| async move {
+            let targets = reference
+                .resolve_reference()
+                .primary_assets()
+                .await?
+                .iter()
+                .map(|asset| async move { Ok(asset.ident().to_string().await?.to_string()) })
+                .try_join()
+                .await?;
+            let description = reference.to_string().await?.to_string();
+            Ok(((description, targets), reference))
+        })
+        .try_join()
+        .await
+        .map(|keyed| keyed.into_iter().collect())
 }
 
 /// A temporary analysis result builder to pass around, to be turned into an
@@ -175,6 +265,7 @@ pub(crate) struct AnalyzeEcmascriptModuleResultBuilder {
     code_gens: Vec<CodeGen>,
     exports: EcmascriptExports,
     has_top_level_await: bool,
+    top_level_await: Option<IssueSourceVc>,
     successful: bool,
 }
 
@@ -185,6 +276,7 @@ impl AnalyzeEcmascriptModuleResultBuilder {
             code_gens: Vec::new(),
             exports: EcmascriptExports::None,
             has_top_level_await: false,
+            top_level_await: None,
             successful: false,
         }
     }
@@ -207,7 +299,6 @@ impl AnalyzeEcmascriptModuleResultBuilder {
     }
 
     /// Adds a codegen to the analysis result.
-    #[allow(dead_code)]
     pub fn add_code_gen_with_availability_info<C>(&mut self, code_gen: C)
     where
         C: Into<CodeGenerateableWithAvailabilityInfoVc>,
@@ -228,6 +319,11 @@ impl AnalyzeEcmascriptModuleResultBuilder {
         self.has_top_level_await = top_level_await;
     }
 
+    /// Sets the location of the first top level await of the analysed module.
+    pub fn set_top_level_await_source(&mut self, source: Option<IssueSourceVc>) {
+        self.top_level_await = source;
+    }
+
     /// Sets whether the analysis was successful.
     pub fn set_successful(&mut self, successful: bool) {
         self.successful = successful;
@@ -256,6 +352,7 @@ impl AnalyzeEcmascriptModuleResultBuilder {
                 code_generation: CodeGenerateablesVc::cell(self.code_gens),
                 exports: self.exports.into(),
                 has_top_level_await: self.has_top_level_await,
+                top_level_await: self.top_level_await,
                 successful: self.successful,
             },
         ))
@@ -295,6 +392,15 @@ struct AnalysisState<'a> {
     // the object allocation.
     first_import_meta: bool,
     import_parts: bool,
+    globals_externals: Option<GlobalsExternalsReadRef>,
+    /// The names of the async chunk groups of `import()` calls by the span of
+    /// the call.
+    import_chunk_names: HashMap<Span, String>,
+    /// The spans of `.default` member expressions on `require()` calls by the
+    /// span of the call.
+    required_default_accesses: HashMap<Span, Span>,
+    strict_interop: bool,
+    cjs_default_interop: CjsDefaultInterop,
 }
 
 impl<'a> AnalysisState<'a> {
@@ -390,29 +496,13 @@ pub(crate) async fn analyze_ecmascript_module(
     let pos = program.span().lo;
     if analyze_types {
         if let Some(comments) = comments.leading.get(&pos) {
-            for comment in comments.iter() {
-                if let CommentKind::Line = comment.kind {
-                    lazy_static! {
-                        static ref REFERENCE_PATH: Regex =
-                            Regex::new(r#"^/\s*<reference\s*path\s*=\s*["'](.+)["']\s*/>\s*$"#)
-                                .unwrap();
-                        static ref REFERENCE_TYPES: Regex =
-                            Regex::new(r#"^/\s*<reference\s*types\s*=\s*["'](.+)["']\s*/>\s*$"#)
-                                .unwrap();
+            for directive in triple_slash_directives(&comments) {
+                match directive {
+                    TripleSlashDirective::Path(path) => {
+                        analysis.add_reference(TsReferencePathAssetReferenceVc::new(origin, path));
                     }
-                    let text = &comment.text;
-                    if let Some(m) = REFERENCE_PATH.captures(text) {
-                        let path = &m[1];
-                        analysis.add_reference(TsReferencePathAssetReferenceVc::new(
-                            origin,
-                            path.to_string(),
-                        ));
-                    } else if let Some(m) = REFERENCE_TYPES.captures(text) {
-                        let types = &m[1];
-                        analysis.add_reference(TsReferenceTypeAssetReferenceVc::new(
-                            origin,
-                            types.to_string(),
-                        ));
+                    TripleSlashDirective::Types(types) => {
+                        analysis.add_reference(TsReferenceTypeAssetReferenceVc::new(origin, types));
                     }
                 }
             }
@@ -452,28 +542,78 @@ pub(crate) async fn analyze_ecmascript_module(
         }),
     );
 
-    let has_top_level_await =
-        set_handler_and_globals(&handler, globals, || has_top_level_await(program));
+    let top_level_await =
+        set_handler_and_globals(&handler, globals, || top_level_await_span(program));
+
+    analysis.set_top_level_await(top_level_await.is_some());
+    analysis.set_top_level_await_source(top_level_await.map(|span| issue_source(source, span)));
+
+    // The import and export declarations are collected by the parser, so they
+    // are handled even when the evaluation of the code is skipped for large
+    // modules.
+    let skip_evaluation = match options.max_analysis_bytes {
+        Some(max_size) => {
+            let size = source_size(source).await?;
+            if size > max_size {
+                AnalysisSizeLimitIssue {
+                    path: source.ident().path(),
+                    size,
+                    max_size,
+                }
+                .cell()
+                .as_issue()
+                .emit();
+            }
+            size > max_size
+        }
+        None => false,
+    };
 
-    analysis.set_top_level_await(has_top_level_await);
+    let mut var_graph = if skip_evaluation {
+        VarGraph {
+            values: HashMap::new(),
+            effects: Vec::new(),
+        }
+    } else {
+        set_handler_and_globals(&handler, globals, || create_graph(program, eval_context))
+    };
 
-    let mut var_graph =
-        set_handler_and_globals(&handler, globals, || create_graph(program, eval_context));
+    let globals_externals = match options.globals_externals {
+        Some(globals_externals) => Some(globals_externals.await?),
+        None => None,
+    };
+    let global_for = |request: &str| {
+        globals_externals
+            .as_ref()
+            .and_then(|globals_externals| globals_externals.get(request))
+            .cloned()
+    };
 
     for r in eval_context.imports.references() {
+        let request = RequestVc::parse(Value::new(r.module_path.to_string().into()));
+        if let Some(global) = global_for(&r.module_path) {
+            import_references.push(EsmAssetReferenceVc::new_global(
+                origin,
+                request,
+                Value::new(r.annotations.clone()),
+                global,
+            ));
+            continue;
+        }
         let r = EsmAssetReferenceVc::new(
             origin,
-            RequestVc::parse(Value::new(r.module_path.to_string().into())),
+            request,
             Value::new(r.annotations.clone()),
-            if options.import_parts {
-                match &r.imported_symbol {
-                    ImportedSymbol::ModuleEvaluation => Some(ModulePartVc::module_evaluation()),
-                    ImportedSymbol::Symbol(name) => Some(ModulePartVc::export(name.to_string())),
-                    ImportedSymbol::Namespace => None,
-                }
-            } else {
-                None
+            match &r.imported_symbol {
+                // Parts of a split module always import each other by part.
+                ImportedSymbol::Part(part_id) => Some(ModulePartVc::internal(*part_id)),
+                _ if !options.import_parts => None,
+                ImportedSymbol::ModuleEvaluation => Some(ModulePartVc::module_evaluation()),
+                ImportedSymbol::Symbol(name) => Some(ModulePartVc::export(name.to_string())),
+                ImportedSymbol::Namespace => None,
             },
+            options.inline_json_bytes,
+            Value::new(options.cjs_default_interop),
         );
         import_references.push(r);
     }
@@ -484,9 +624,16 @@ pub(crate) async fn analyze_ecmascript_module(
         *r = r.resolve().await?;
     }
     for r in import_references.iter() {
+        // Imports of globals don't reference a module.
+        if r.await?.global.is_some() {
+            continue;
+        }
         // `add_reference` will avoid adding duplicate references
         analysis.add_reference(*r);
     }
+    if !import_references.is_empty() {
+        analysis.add_code_gen_with_availability_info(AsyncModuleVc::new(import_references.clone()));
+    }
 
     let (webpack_runtime, webpack_entry, webpack_chunks, esm_exports, esm_star_exports) =
         set_handler_and_globals(&handler, globals, || {
@@ -589,6 +736,19 @@ pub(crate) async fn analyze_ecmascript_module(
         analysis.add_code_gen(esm_exports);
 
         EcmascriptExports::EsmExports(esm_exports)
+    } else if is_empty_program(program) {
+        // An empty module is ESM, unless it's specified to be CommonJS.
+        if matches!(specified_type, SpecifiedModuleType::CommonJs) {
+            EcmascriptExports::CommonJs
+        } else {
+            EcmascriptExports::EsmExports(
+                EsmExports {
+                    exports: Default::default(),
+                    star_exports: Default::default(),
+                }
+                .cell(),
+            )
+        }
     } else if matches!(specified_type, SpecifiedModuleType::EcmaScript) {
         match detect_dynamic_export(program) {
             DetectedDynamicExportType::CommonJs => {
@@ -636,7 +796,26 @@ pub(crate) async fn analyze_ecmascript_module(
 
     analysis.set_exports(exports);
 
+    let esm_with_cjs_exports = set_handler_and_globals(&handler, globals, || {
+        esm_with_cjs_exports(program, eval_context.unresolved_mark)
+    });
+    if let Some((cjs_span, esm_span)) = esm_with_cjs_exports {
+        InteropIssue {
+            hazard: InteropHazard::EsmWithCjsExports,
+            path: source.ident().path(),
+            strict: options.strict_interop,
+            source: issue_source(source, cjs_span),
+            related: Some(issue_source(source, esm_span)),
+        }
+        .cell()
+        .as_issue()
+        .emit();
+    }
+
     let effects = take(&mut var_graph.effects);
+    let required_default_accesses = set_handler_and_globals(&handler, globals, || {
+        required_default_accesses(program, eval_context.unresolved_mark)
+    });
 
     let mut analysis_state = AnalysisState {
         handler: &handler,
@@ -647,6 +826,11 @@ pub(crate) async fn analyze_ecmascript_module(
         fun_args_values: Mutex::new(HashMap::<u32, Vec<JsValue>>::new()),
         first_import_meta: true,
         import_parts: options.import_parts,
+        globals_externals: globals_externals.clone(),
+        import_chunk_names: import_chunk_names(program, comments),
+        required_default_accesses,
+        strict_interop: options.strict_interop,
+        cjs_default_interop: options.cjs_default_interop,
     };
 
     enum Action {
@@ -937,13 +1121,18 @@ pub(crate) async fn analyze_ecmascript_module(
             } => {
                 let pat = js_value_to_pattern(&input);
                 if !pat.has_constant_parts() {
+                    // Without any constant parts the URL could point to any file, so the
+                    // expression is left untouched instead of referencing all of them.
                     handler.span_warn_with_code(
                         span,
-                        &format!("new URL({input}, import.meta.url) is very dynamic"),
+                        &format!(
+                            "new URL({input}, import.meta.url) is not statically analyse-able"
+                        ),
                         DiagnosticId::Lint(
                             errors::failed_to_analyse::ecmascript::NEW_URL_IMPORT_META.to_string(),
                         ),
-                    )
+                    );
+                    continue;
                 }
                 analysis.add_reference(UrlAssetReferenceVc::new(
                     origin,
@@ -958,6 +1147,187 @@ pub(crate) async fn analyze_ecmascript_module(
                     in_try,
                 ));
             }
+            Effect::Worker {
+                input,
+                ast_path,
+                span,
+                in_try,
+            } => {
+                // A worker is bundled as its own chunk group, so it has to be a single
+                // statically known file.
+                let Some(request) = js_value_to_pattern(&input).into_string() else {
+                    handler.span_warn_with_code(
+                        span,
+                        &format!(
+                            "new Worker(new URL({input}, import.meta.url)) is not statically \
+                             analyse-able"
+                        ),
+                        DiagnosticId::Lint(
+                            errors::failed_to_analyse::ecmascript::NEW_WORKER.to_string(),
+                        ),
+                    );
+                    continue;
+                };
+                analysis.add_reference(WorkerReferenceVc::new(
+                    origin,
+                    RequestVc::parse(Value::new(request.into())),
+                    compile_time_info.environment().rendering(),
+                    AstPathVc::cell(ast_path),
+                    issue_source(source, span),
+                    in_try,
+                ));
+            }
+            Effect::ImportMetaResolve {
+                args,
+                ast_path,
+                span,
+                in_try,
+            } => {
+                let args = args
+                    .into_iter()
+                    .map(|arg| {
+                        let analysis_state = &analysis_state;
+                        let add_effects = &add_effects;
+                        async move {
+                            let value = match arg {
+                                EffectArg::Value(value) => value,
+                                EffectArg::Closure(value, block) => {
+                                    add_effects(block.effects);
+                                    value
+                                }
+                                EffectArg::Spread => {
+                                    JsValue::unknown_empty("spread is not supported yet")
+                                }
+                            };
+                            analysis_state.link_value(value, in_try).await
+                        }
+                    })
+                    .try_join()
+                    .await?;
+                if args.len() == 1 {
+                    let pat = js_value_to_pattern(&args[0]);
+                    if !pat.has_constant_parts() {
+                        let (args, hints) = JsValue::explain_args(&args, 10, 2);
+                        handler.span_warn_with_code(
+                            span,
+                            &format!("import.meta.resolve({args}) is very dynamic{hints}",),
+                            DiagnosticId::Lint(
+                                errors::failed_to_analyse::ecmascript::IMPORT_META_RESOLVE
+                                    .to_string(),
+                            ),
+                        )
+                    }
+                    analysis.add_reference(ImportMetaResolveAssetReferenceVc::new(
+                        origin,
+                        RequestVc::parse(Value::new(pat)),
+                        compile_time_info.environment().rendering(),
+                        AstPathVc::cell(ast_path),
+                        issue_source(source, span),
+                        in_try,
+                    ));
+                } else {
+                    let (args, hints) = JsValue::explain_args(&args, 10, 2);
+                    handler.span_warn_with_code(
+                        span,
+                        &format!(
+                            "import.meta.resolve({args}) is not statically analyse-able{hints}",
+                        ),
+                        DiagnosticId::Lint(
+                            errors::failed_to_analyse::ecmascript::IMPORT_META_RESOLVE.to_string(),
+                        ),
+                    )
+                }
+            }
+            Effect::ImportMetaGlob {
+                args,
+                ast_path,
+                span,
+                in_try,
+            } => {
+                let args = args
+                    .into_iter()
+                    .map(|arg| {
+                        let analysis_state = &analysis_state;
+                        let add_effects = &add_effects;
+                        async move {
+                            let value = match arg {
+                                EffectArg::Value(value) => value,
+                                EffectArg::Closure(value, block) => {
+                                    add_effects(block.effects);
+                                    value
+                                }
+                                EffectArg::Spread => {
+                                    JsValue::unknown_empty("spread is not supported yet")
+                                }
+                            };
+                            analysis_state.link_value(value, in_try).await
+                        }
+                    })
+                    .try_join()
+                    .await?;
+                let options = match parse_import_meta_glob(&args) {
+                    Ok(options) => options,
+                    Err(err) => {
+                        let (args, hints) = JsValue::explain_args(&args, 10, 2);
+                        handler.span_err_with_code(
+                            span,
+                            &format!(
+                                "import.meta.glob({args}) is not statically analyse-able: \
+                                 {}{hints}",
+                                PrettyPrintError(&err)
+                            ),
+                            DiagnosticId::Error(
+                                errors::failed_to_analyse::ecmascript::IMPORT_META_GLOB.to_string(),
+                            ),
+                        );
+                        continue;
+                    }
+                };
+
+                // Reading the matching files makes the module depend on the
+                // directories, so it's analyzed again when files are added or
+                // removed.
+                let map =
+                    ImportMetaGlobMapVc::generate(origin.origin_path().parent(), options.patterns);
+                let issue_source = issue_source(source, span);
+                for key in map.await?.keys() {
+                    analysis.add_reference(ImportMetaGlobAssetReferenceVc::new(
+                        origin,
+                        RequestVc::parse(Value::new(key.clone().into())),
+                        options.eager,
+                        issue_source,
+                        in_try,
+                    ));
+                }
+                analysis.add_code_gen_with_availability_info(ImportMetaGlobVc::new(
+                    origin,
+                    map,
+                    options.eager,
+                    options.import,
+                    AstPathVc::cell(ast_path),
+                    issue_source,
+                    in_try,
+                ));
+            }
+            Effect::ImportMetaPath {
+                prop,
+                ast_path: _,
+                span,
+                in_try: _,
+            } => {
+                if !*compile_time_info.environment().node_externals().await? {
+                    handler.span_err_with_code(
+                        span,
+                        &format!(
+                            "import.meta.{prop} is only available when targeting Node.js, it will \
+                             throw at runtime"
+                        ),
+                        DiagnosticId::Error(
+                            errors::failed_to_analyse::ecmascript::IMPORT_META_PATH.to_string(),
+                        ),
+                    )
+                }
+            }
         }
     }
 
@@ -1061,12 +1431,26 @@ async fn handle_call<G: Fn(Vec<Effect>) + Send + Sync>(
                         ),
                     )
                 }
+                let global = match (&pat, &state.globals_externals) {
+                    (Pattern::Constant(request), Some(globals_externals)) => {
+                        globals_externals.get(request)
+                    }
+                    _ => None,
+                };
+                if let Some(global) = global {
+                    analysis.add_code_gen(EsmGlobalImportVc::new(
+                        global.clone(),
+                        AstPathVc::cell(ast_path.to_vec()),
+                    ));
+                    return Ok(());
+                }
                 analysis.add_reference(EsmAsyncAssetReferenceVc::new(
                     origin,
                     RequestVc::parse(Value::new(pat)),
                     AstPathVc::cell(ast_path.to_vec()),
                     issue_source(source, span),
                     in_try,
+                    state.import_chunk_names.get(&span).cloned(),
                 ));
                 return Ok(());
             }
@@ -1093,12 +1477,30 @@ async fn handle_call<G: Fn(Vec<Effect>) + Send + Sync>(
                         ),
                     )
                 }
+                let global = match (&pat, &state.globals_externals) {
+                    (Pattern::Constant(request), Some(globals_externals)) => {
+                        globals_externals.get(request)
+                    }
+                    _ => None,
+                };
+                if let Some(global) = global {
+                    analysis.add_code_gen(GlobalRequireVc::new(
+                        global.clone(),
+                        AstPathVc::cell(ast_path.to_vec()),
+                    ));
+                    return Ok(());
+                }
                 analysis.add_reference(CjsRequireAssetReferenceVc::new(
                     origin,
                     RequestVc::parse(Value::new(pat)),
                     AstPathVc::cell(ast_path.to_vec()),
                     issue_source(source, span),
                     in_try,
+                    state
+                        .required_default_accesses
+                        .get(&span)
+                        .map(|&default_span| issue_source(source, default_span)),
+                    state.strict_interop,
                 ));
                 return Ok(());
             }
@@ -1734,6 +2136,8 @@ async fn handle_free_var_reference(
                             .map(|export| ModulePartVc::export(export.to_string()))
                     })
                     .flatten(),
+                None,
+                Value::new(state.cjs_default_interop),
             )
             .resolve()
             .await?;
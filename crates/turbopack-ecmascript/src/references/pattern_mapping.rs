@@ -142,7 +142,7 @@ impl PatternMappingVc {
             Some(PrimaryResolveResult::OriginalReferenceExternal) => {
                 return Ok(PatternMapping::OriginalReferenceExternal.cell())
             }
-            Some(PrimaryResolveResult::OriginalReferenceTypeExternal(s)) => {
+            Some(PrimaryResolveResult::OriginalReferenceTypeExternal(s, _)) => {
                 return Ok(PatternMapping::OriginalReferenceTypeExternal(s.clone()).cell())
             }
             Some(PrimaryResolveResult::Ignore) => return Ok(PatternMapping::Ignored.cell()),
@@ -0,0 +1,64 @@
+use anyhow::Result;
+use turbo_tasks::primitives::StringVc;
+use turbo_tasks_fs::{FileContent, FileSystemPathVc};
+use turbopack_core::{
+    asset::Asset,
+    issue::{Issue, IssueSeverity, IssueSeverityVc, IssueVc},
+    source::SourceVc,
+};
+
+/// The size of the content of `source` in bytes, 0 when it doesn't exist.
+pub(crate) async fn source_size(source: SourceVc) -> Result<usize> {
+    Ok(match &*source.content().file_content().await? {
+        FileContent::Content(file) => file.content().len(),
+        FileContent::NotFound => 0,
+    })
+}
+
+/// Reported when the analysis of a module was limited because its source
+/// exceeds
+/// [EcmascriptOptions::max_analysis_bytes](crate::EcmascriptOptions::max_analysis_bytes).
+#[turbo_tasks::value(shared)]
+pub struct AnalysisSizeLimitIssue {
+    pub path: FileSystemPathVc,
+    pub size: usize,
+    pub max_size: usize,
+}
+
+#[turbo_tasks::value_impl]
+impl Issue for AnalysisSizeLimitIssue {
+    #[turbo_tasks::function]
+    fn context(&self) -> FileSystemPathVc {
+        self.path
+    }
+
+    #[turbo_tasks::function]
+    fn title(&self) -> StringVc {
+        StringVc::cell(format!(
+            "Module is too large to be analyzed ({} bytes, the limit is {} bytes)",
+            self.size, self.max_size
+        ))
+    }
+
+    #[turbo_tasks::function]
+    fn description(&self) -> StringVc {
+        StringVc::cell(
+            "Only the import and export declarations of the module were analyzed. References \
+             which are found by evaluating the code, like `require()` calls, `import()` \
+             expressions and `new URL()` expressions, are not followed, and the referenced \
+             modules are not included in the bundle.\nIncrease the `max_analysis_bytes` limit if \
+             the module contains such references."
+                .to_string(),
+        )
+    }
+
+    #[turbo_tasks::function]
+    fn severity(&self) -> IssueSeverityVc {
+        IssueSeverity::Warning.cell()
+    }
+
+    #[turbo_tasks::function]
+    fn category(&self) -> StringVc {
+        StringVc::cell("analyze".to_string())
+    }
+}
@@ -0,0 +1,374 @@
+use anyhow::{bail, Result};
+use swc_core::{
+    ecma::ast::{Expr, ExprOrSpread, NewExpr},
+    quote,
+};
+use turbo_tasks::{primitives::StringVc, Value, ValueToString, ValueToStringVc};
+use turbo_tasks_fs::File;
+use turbopack_core::{
+    asset::{Asset, AssetContentVc, AssetVc},
+    chunk::{
+        availability_info::AvailabilityInfo, ChunkItem, ChunkItemVc, ChunkVc, ChunkableModule,
+        ChunkableModuleReference, ChunkableModuleReferenceVc, ChunkableModuleVc, ChunkingContext,
+        ChunkingContextVc, ChunkingType, ChunkingTypeOptionVc, EvaluatableAssetVc,
+        EvaluatableAssetsVc,
+    },
+    environment::RenderingVc,
+    ident::AssetIdentVc,
+    issue::{IssueSourceVc, OptionIssueSourceVc},
+    module::{Module, ModuleVc},
+    output::OutputAssetsVc,
+    reference::{AssetReference, AssetReferenceVc, AssetReferencesVc, SingleAssetReferenceVc},
+    reference_type::EcmaScriptModulesReferenceSubType,
+    resolve::{origin::ResolveOriginVc, parse::RequestVc, ResolveResultVc},
+};
+
+use super::esm::{
+    base::{ReferencedAsset, ReferencedAssetVc},
+    url::url_base_rewrite,
+};
+use crate::{
+    chunk::{
+        EcmascriptChunkItem, EcmascriptChunkItemContent, EcmascriptChunkItemContentVc,
+        EcmascriptChunkItemVc, EcmascriptChunkPlaceable, EcmascriptChunkPlaceableVc,
+        EcmascriptChunkVc, EcmascriptChunkingContextVc, EcmascriptExports, EcmascriptExportsVc,
+    },
+    code_gen::{CodeGenerateable, CodeGenerateableVc, CodeGeneration, CodeGenerationVc},
+    create_visitor,
+    references::AstPathVc,
+    resolve::{esm_resolve, try_to_severity},
+    utils::{module_id_to_lit, StringifyJs},
+};
+
+/// Worker references are injected during code analysis when we find a
+/// (staticly analyzable) `new Worker(new URL("path", import.meta.url))` or
+/// `new SharedWorker(new URL("path", import.meta.url))`.
+///
+/// The worker entry is bundled as its own evaluated chunk group, and the
+/// `URL` constructor's arguments are rewritten to point to the entry chunk of
+/// that group.
+#[turbo_tasks::value]
+pub struct WorkerReference {
+    origin: ResolveOriginVc,
+    request: RequestVc,
+    rendering: RenderingVc,
+    ast_path: AstPathVc,
+    issue_source: IssueSourceVc,
+    in_try: bool,
+}
+
+#[turbo_tasks::value_impl]
+impl WorkerReferenceVc {
+    #[turbo_tasks::function]
+    pub fn new(
+        origin: ResolveOriginVc,
+        request: RequestVc,
+        rendering: RenderingVc,
+        ast_path: AstPathVc,
+        issue_source: IssueSourceVc,
+        in_try: bool,
+    ) -> Self {
+        WorkerReference {
+            origin,
+            request,
+            rendering,
+            ast_path,
+            issue_source,
+            in_try,
+        }
+        .cell()
+    }
+
+    #[turbo_tasks::function]
+    async fn get_referenced_asset(self) -> Result<ReferencedAssetVc> {
+        let this = self.await?;
+        Ok(ReferencedAssetVc::from_resolve_result(
+            self.resolve_reference(),
+            this.request,
+        ))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl AssetReference for WorkerReference {
+    #[turbo_tasks::function]
+    async fn resolve_reference(&self) -> Result<ResolveResultVc> {
+        let result = esm_resolve(
+            self.origin,
+            self.request,
+            Value::new(EcmaScriptModulesReferenceSubType::Undefined),
+            OptionIssueSourceVc::some(self.issue_source),
+            try_to_severity(self.in_try),
+        );
+        Ok(result
+            .await?
+            .map(
+                |asset| async move {
+                    Ok(
+                        if let Some(entry) = EvaluatableAssetVc::resolve_from(asset).await? {
+                            WorkerAssetVc::new(entry).into()
+                        } else {
+                            asset
+                        },
+                    )
+                },
+                |reference| async move { Ok(reference) },
+            )
+            .await?
+            .cell())
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ValueToString for WorkerReference {
+    #[turbo_tasks::function]
+    async fn to_string(&self) -> Result<StringVc> {
+        Ok(StringVc::cell(format!(
+            "new Worker(new URL({}))",
+            self.request.to_string().await?,
+        )))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ChunkableModuleReference for WorkerReference {
+    #[turbo_tasks::function]
+    fn chunking_type(&self) -> ChunkingTypeOptionVc {
+        ChunkingTypeOptionVc::cell(Some(ChunkingType::PlacedOrParallel))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl CodeGenerateable for WorkerReference {
+    #[turbo_tasks::function]
+    async fn code_generation(
+        self_vc: WorkerReferenceVc,
+        context: EcmascriptChunkingContextVc,
+    ) -> Result<CodeGenerationVc> {
+        let this = self_vc.await?;
+        let mut visitors = vec![];
+
+        let referenced_asset = self_vc.get_referenced_asset().await?;
+
+        let rewrite = url_base_rewrite(this.rendering, this.origin, "new Worker(…)").await?;
+
+        let ast_path = this.ast_path.await?;
+
+        let first_arg = match &*referenced_asset {
+            // The worker asset exports the URL path of the worker's entry chunk.
+            ReferencedAsset::Some(asset) => {
+                let id = asset.as_chunk_item(context).id().await?;
+                Some(quote!(
+                    "__turbopack_require__($id)" as Expr,
+                    id: Expr = module_id_to_lit(&id),
+                ))
+            }
+            ReferencedAsset::OriginalReferenceTypeExternal(request, _) => {
+                Some(request.to_string().as_str().into())
+            }
+            ReferencedAsset::Global(_) | ReferencedAsset::None => None,
+        };
+
+        if let Some(first_arg) = first_arg {
+            visitors.push(
+                create_visitor!(ast_path, visit_mut_expr(new_expr: &mut Expr) {
+                    if let Expr::New(NewExpr { args: Some(args), .. }) = new_expr {
+                        if let Some(ExprOrSpread { box expr, spread: None }) = args.get_mut(0) {
+                            *expr = first_arg.clone();
+                        }
+
+                        if let Some(rewrite) = &rewrite {
+                            if let Some(ExprOrSpread { box expr, spread: None }) = args.get_mut(1) {
+                                *expr = rewrite.clone();
+                            }
+                        }
+                    }
+                }),
+            );
+        }
+
+        Ok(CodeGeneration { visitors }.into())
+    }
+}
+
+#[turbo_tasks::function]
+fn modifier() -> StringVc {
+    StringVc::cell("worker".to_string())
+}
+
+#[turbo_tasks::function]
+fn worker_entry_description() -> StringVc {
+    StringVc::cell("worker entry".to_string())
+}
+
+#[turbo_tasks::function]
+fn worker_chunk_reference_description() -> StringVc {
+    StringVc::cell("worker chunk".to_string())
+}
+
+/// An asset that exports the URL path of the entry chunk of a worker. The
+/// worker entry is put into its own evaluated chunk group by the chunking
+/// context of the importing module.
+#[turbo_tasks::value]
+pub struct WorkerAsset {
+    entry: EvaluatableAssetVc,
+}
+
+#[turbo_tasks::value_impl]
+impl WorkerAssetVc {
+    #[turbo_tasks::function]
+    pub fn new(entry: EvaluatableAssetVc) -> Self {
+        WorkerAsset { entry }.cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Asset for WorkerAsset {
+    #[turbo_tasks::function]
+    fn ident(&self) -> AssetIdentVc {
+        self.entry.ident().with_modifier(modifier())
+    }
+
+    #[turbo_tasks::function]
+    fn content(&self) -> AssetContentVc {
+        AssetContentVc::from(File::from("// Chunking only content".to_string()))
+    }
+
+    #[turbo_tasks::function]
+    fn references(&self) -> AssetReferencesVc {
+        AssetReferencesVc::cell(vec![SingleAssetReferenceVc::new(
+            self.entry.into(),
+            worker_entry_description(),
+        )
+        .into()])
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Module for WorkerAsset {}
+
+#[turbo_tasks::value_impl]
+impl ChunkableModule for WorkerAsset {
+    #[turbo_tasks::function]
+    fn as_chunk(
+        self_vc: WorkerAssetVc,
+        context: ChunkingContextVc,
+        availability_info: Value<AvailabilityInfo>,
+    ) -> ChunkVc {
+        EcmascriptChunkVc::new(
+            context,
+            self_vc.as_ecmascript_chunk_placeable(),
+            availability_info,
+        )
+        .into()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl EcmascriptChunkPlaceable for WorkerAsset {
+    #[turbo_tasks::function]
+    fn as_chunk_item(
+        self_vc: WorkerAssetVc,
+        chunking_context: EcmascriptChunkingContextVc,
+    ) -> EcmascriptChunkItemVc {
+        WorkerChunkItem {
+            chunking_context,
+            inner: self_vc,
+        }
+        .cell()
+        .into()
+    }
+
+    #[turbo_tasks::function]
+    fn get_exports(&self) -> EcmascriptExportsVc {
+        EcmascriptExports::Value.cell()
+    }
+}
+
+#[turbo_tasks::value]
+struct WorkerChunkItem {
+    chunking_context: EcmascriptChunkingContextVc,
+    inner: WorkerAssetVc,
+}
+
+#[turbo_tasks::value_impl]
+impl WorkerChunkItemVc {
+    /// The evaluated chunk group of the worker. The last chunk of the group
+    /// evaluates the worker entry. The chunks are evaluated in the
+    /// environment of a web worker, so the runtime loads their chunks with
+    /// `importScripts`.
+    #[turbo_tasks::function]
+    async fn chunks(self) -> Result<OutputAssetsVc> {
+        let this = self.await?;
+        let entry = this.inner.await?.entry;
+        let chunking_context = this
+            .chunking_context
+            .with_environment(this.chunking_context.environment().web_worker());
+        Ok(chunking_context.evaluated_chunk_group(
+            entry.as_root_chunk(chunking_context),
+            EvaluatableAssetsVc::one(entry),
+        ))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl EcmascriptChunkItem for WorkerChunkItem {
+    #[turbo_tasks::function]
+    fn chunking_context(&self) -> EcmascriptChunkingContextVc {
+        self.chunking_context
+    }
+
+    #[turbo_tasks::function]
+    async fn content(self_vc: WorkerChunkItemVc) -> Result<EcmascriptChunkItemContentVc> {
+        let chunks = self_vc.chunks().await?;
+        let Some(entry_chunk) = chunks.last() else {
+            bail!("the chunk group of a worker must not be empty");
+        };
+        // Like the chunks loaded by the runtime, the entry chunk is loaded from
+        // the chunk base path.
+        let this = self_vc.await?;
+        let output_root = this.chunking_context.output_root().await?;
+        let entry_chunk_path = entry_chunk.ident().path().await?;
+        let Some(path) = output_root.get_path_to(&entry_chunk_path) else {
+            bail!(
+                "the worker entry chunk {} is not in the output root {}",
+                entry_chunk_path.to_string(),
+                output_root.to_string()
+            );
+        };
+        let chunk_base_path = this.chunking_context.chunk_base_path().await?;
+        let chunk_base_path = chunk_base_path.as_deref().unwrap_or_default();
+        Ok(EcmascriptChunkItemContent {
+            inner_code: format!(
+                "__turbopack_export_value__({url});\n",
+                url = StringifyJs(&format_args!("{chunk_base_path}{path}"))
+            )
+            .into(),
+            ..Default::default()
+        }
+        .cell())
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ChunkItem for WorkerChunkItem {
+    #[turbo_tasks::function]
+    fn asset_ident(&self) -> AssetIdentVc {
+        self.inner.ident()
+    }
+
+    #[turbo_tasks::function]
+    async fn references(self_vc: WorkerChunkItemVc) -> Result<AssetReferencesVc> {
+        Ok(AssetReferencesVc::cell(
+            self_vc
+                .chunks()
+                .await?
+                .iter()
+                .map(|&chunk| {
+                    SingleAssetReferenceVc::new(chunk.into(), worker_chunk_reference_description())
+                        .into()
+                })
+                .collect(),
+        ))
+    }
+}
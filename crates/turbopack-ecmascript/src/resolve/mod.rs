@@ -1,4 +1,5 @@
 pub mod node_native_binding;
+pub(crate) mod uri;
 
 use anyhow::Result;
 use turbo_tasks::Value;
@@ -126,6 +127,19 @@ async fn specific_resolve(
     issue_source: OptionIssueSourceVc,
     issue_severity: IssueSeverityVc,
 ) -> Result<ResolveResultVc> {
+    if let Some(result) = uri::resolve_uri_request(
+        origin,
+        request,
+        options,
+        reference_type.clone(),
+        issue_source,
+        issue_severity,
+    )
+    .await?
+    {
+        return Ok(result);
+    }
+
     let result = origin.resolve_asset(request, options, reference_type.clone());
 
     handle_resolve_error(
@@ -0,0 +1,310 @@
+use anyhow::{anyhow, bail, Result};
+use base64::Engine;
+use turbo_tasks::Value;
+use turbo_tasks_fs::{util::sys_to_unix, DiskFileSystemVc, File, FileContent, FileSystemPathVc};
+use turbo_tasks_hash::hash_xxh3_hash64;
+use turbopack_core::{
+    asset::AssetContent,
+    context::AssetContext,
+    issue::{resolve::ResolvingIssue, IssueSeverityVc, OptionIssueSourceVc},
+    reference_type::ReferenceType,
+    resolve::{
+        handle_resolve_error,
+        options::ResolveOptionsVc,
+        origin::{ResolveOrigin, ResolveOriginVc},
+        parse::{Request, RequestVc},
+        pattern::Pattern,
+        ResolveResult, ResolveResultVc,
+    },
+    source::SourceVc,
+    virtual_source::VirtualSourceVc,
+};
+
+/// The decoded payload of a `data:` URL.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct DataUrl {
+    pub mime_type: String,
+    pub data: Vec<u8>,
+}
+
+/// Parses the part of a `data:` URL after the protocol, e.g.
+/// `text/javascript;base64,ZXhwb3J0IGRlZmF1bHQgMQ==`.
+pub(crate) fn parse_data_url(remainder: &str) -> Result<DataUrl> {
+    let Some((meta, payload)) = remainder.split_once(',') else {
+        bail!("a data URL must contain a `,` separating the MIME type and the data");
+    };
+    let mut params = meta.split(';');
+    let mime_type = params
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+    let is_base64 = params.any(|param| param.trim().eq_ignore_ascii_case("base64"));
+
+    let payload = urlencoding::decode_binary(payload.as_bytes());
+    let data = if is_base64 {
+        base64::engine::general_purpose::STANDARD
+            .decode(&*payload)
+            .map_err(|err| anyhow!("the base64 data is invalid: {err}"))?
+    } else {
+        payload.into_owned()
+    };
+
+    Ok(DataUrl { mime_type, data })
+}
+
+/// The file extension of modules created from `data:` URLs with the given
+/// MIME type, which determines how they are processed.
+fn data_url_extension(mime_type: &str) -> Option<&'static str> {
+    match mime_type {
+        "text/javascript" | "application/javascript" => Some("js"),
+        "application/json" => Some("json"),
+        _ => None,
+    }
+}
+
+/// Parses a `data:` URL which can be imported, or returns the message of the
+/// issue explaining why it can't be imported.
+fn importable_data_url(remainder: &str) -> Result<DataUrl, String> {
+    match parse_data_url(remainder) {
+        Ok(data_url) if data_url_extension(&data_url.mime_type).is_some() => Ok(data_url),
+        Ok(data_url) => Err(format!(
+            "The MIME type `{}` is not supported. Only JavaScript and JSON data URLs can be \
+             imported.",
+            data_url.mime_type
+        )),
+        Err(err) => Err(format!("The data URL is invalid: {err}")),
+    }
+}
+
+/// Converts a `file:` URL into an absolute path with `/` as separator, e.g.
+/// `/home/user/file.js` or `C:/Users/user/file.js`.
+pub(crate) fn file_url_to_path(url: &str) -> Option<String> {
+    let url = url::Url::parse(url).ok()?;
+    if url.scheme() != "file" || !matches!(url.host_str(), None | Some("" | "localhost")) {
+        return None;
+    }
+    let mut path = String::new();
+    for (i, segment) in url.path_segments()?.enumerate() {
+        let segment = urlencoding::decode(segment).ok()?;
+        let is_drive_letter = i == 0
+            && segment.len() == 2
+            && segment.as_bytes()[0].is_ascii_alphabetic()
+            && matches!(segment.as_bytes()[1], b':' | b'|');
+        if is_drive_letter {
+            path.push(segment.as_bytes()[0].to_ascii_uppercase() as char);
+            path.push(':');
+        } else {
+            path.push('/');
+            path.push_str(&segment);
+        }
+    }
+    Some(path)
+}
+
+/// Creates the source of a `data:` URL. The ident only depends on the URL, so
+/// the same URL always results in the same module, regardless of where it was
+/// imported from.
+#[turbo_tasks::function]
+async fn data_url_source(root: FileSystemPathVc, url: String) -> Result<SourceVc> {
+    let Some(remainder) = url.strip_prefix("data:") else {
+        bail!("{url} is not a data URL");
+    };
+    let data_url = parse_data_url(remainder)?;
+    let Some(extension) = data_url_extension(&data_url.mime_type) else {
+        bail!("unsupported MIME type {}", data_url.mime_type);
+    };
+    let path = root.join(&format!(
+        "__data_url__/{:016x}.{extension}",
+        hash_xxh3_hash64(&url)
+    ));
+    Ok(VirtualSourceVc::new(
+        path,
+        AssetContent::File(FileContent::Content(File::from(data_url.data)).cell()).cell(),
+    )
+    .into())
+}
+
+/// Resolves `data:` and `file:` requests. Returns `None` for all other
+/// requests, which go through the regular resolving.
+pub(crate) async fn resolve_uri_request(
+    origin: ResolveOriginVc,
+    request: RequestVc,
+    options: ResolveOptionsVc,
+    reference_type: Value<ReferenceType>,
+    issue_source: OptionIssueSourceVc,
+    issue_severity: IssueSeverityVc,
+) -> Result<Option<ResolveResultVc>> {
+    let Request::Uri {
+        protocol,
+        remainder,
+    } = &*request.await?
+    else {
+        return Ok(None);
+    };
+    let url = format!("{protocol}{remainder}");
+
+    let error = match protocol.as_str() {
+        "data:" => match importable_data_url(remainder) {
+            Ok(_) => {
+                let source = data_url_source(origin.origin_path().root(), url);
+                let module = origin.context().process(source, reference_type);
+                return Ok(Some(ResolveResult::asset(module.into()).cell()));
+            }
+            Err(error) => error,
+        },
+        "file:" => match file_url_to_path(&url) {
+            Some(path) => match project_path(origin.origin_path(), &path).await? {
+                Some(path) => {
+                    let file_name = path.await?.file_name().to_string();
+                    let request =
+                        RequestVc::parse(Value::new(Pattern::Constant(format!("./{file_name}"))));
+                    let result = origin.context().resolve_asset(
+                        path,
+                        request,
+                        options,
+                        reference_type.clone(),
+                    );
+                    return Ok(Some(
+                        handle_resolve_error(
+                            result,
+                            reference_type,
+                            origin.origin_path(),
+                            request,
+                            options,
+                            issue_source,
+                            issue_severity,
+                        )
+                        .await?,
+                    ));
+                }
+                None => format!("The file {path} is outside of the project."),
+            },
+            None => "The file URL can't be converted to a path.".to_string(),
+        },
+        _ => return Ok(None),
+    };
+
+    ResolvingIssue {
+        severity: issue_severity,
+        request_type: format!("{} import: `{url}`", &protocol[..protocol.len() - 1]),
+        request,
+        context: origin.origin_path(),
+        resolve_options: options,
+        error_message: Some(error),
+        source: issue_source,
+    }
+    .cell()
+    .as_issue()
+    .emit();
+    Ok(Some(ResolveResult::unresolveable().cell()))
+}
+
+/// Maps an absolute path on disk to a path in the file system of `origin`.
+async fn project_path(origin: FileSystemPathVc, path: &str) -> Result<Option<FileSystemPathVc>> {
+    let Some(disk_fs) = DiskFileSystemVc::resolve_from(origin.fs()).await? else {
+        return Ok(None);
+    };
+    let disk_fs = disk_fs.await?;
+    let root = sys_to_unix(&disk_fs.root);
+    let Some(relative) = path.strip_prefix(root.trim_end_matches('/')) else {
+        return Ok(None);
+    };
+    let Some(relative) = relative.strip_prefix('/') else {
+        return Ok(None);
+    };
+    Ok(Some(origin.root().join(relative)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{file_url_to_path, importable_data_url, parse_data_url, DataUrl};
+
+    #[test]
+    fn data_url_url_encoded() {
+        assert_eq!(
+            parse_data_url("text/javascript,export%20default%20%221%2C2%22").unwrap(),
+            DataUrl {
+                mime_type: "text/javascript".to_string(),
+                data: b"export default \"1,2\"".to_vec(),
+            }
+        );
+        assert_eq!(
+            parse_data_url("Application/JSON;charset=utf-8,{\"a\":1}").unwrap(),
+            DataUrl {
+                mime_type: "application/json".to_string(),
+                data: b"{\"a\":1}".to_vec(),
+            }
+        );
+    }
+
+    #[test]
+    fn data_url_base64() {
+        assert_eq!(
+            parse_data_url("text/javascript;base64,ZXhwb3J0IGRlZmF1bHQgMQ==").unwrap(),
+            DataUrl {
+                mime_type: "text/javascript".to_string(),
+                data: b"export default 1".to_vec(),
+            }
+        );
+        assert!(parse_data_url("text/javascript;base64,not base64!").is_err());
+        assert!(parse_data_url("text/javascript").is_err());
+    }
+
+    #[test]
+    fn file_url() {
+        assert_eq!(
+            file_url_to_path("file:///home/user/my%20project/index.js").as_deref(),
+            Some("/home/user/my project/index.js")
+        );
+        assert_eq!(
+            file_url_to_path("file://localhost/home/user/index.js").as_deref(),
+            Some("/home/user/index.js")
+        );
+        assert_eq!(
+            file_url_to_path("file:///c:/Users/user/My%20Documents/index.js").as_deref(),
+            Some("C:/Users/user/My Documents/index.js")
+        );
+        assert_eq!(file_url_to_path("file://server/share/index.js"), None);
+    }
+
+    #[test]
+    fn file_url_encoding() {
+        // Spaces which aren't percent-encoded are encoded by the URL parser.
+        assert_eq!(
+            file_url_to_path("file:///home/user/my project/index.js").as_deref(),
+            Some("/home/user/my project/index.js")
+        );
+        assert_eq!(
+            file_url_to_path("file:///home/user/%C3%A9t%C3%A9/50%25%20%23off.js").as_deref(),
+            Some("/home/user/été/50% #off.js")
+        );
+        // The query and fragment aren't part of the path.
+        assert_eq!(
+            file_url_to_path("file:///home/user/index.js?query#fragment").as_deref(),
+            Some("/home/user/index.js")
+        );
+        // Percent-encoded bytes which aren't UTF-8 can't be part of a path.
+        assert_eq!(file_url_to_path("file:///home/user/%FF.js"), None);
+        assert_eq!(file_url_to_path("https://example.com/index.js"), None);
+    }
+
+    #[test]
+    fn unsupported_mime_type() {
+        assert!(importable_data_url("text/javascript,export default 1").is_ok());
+        assert!(importable_data_url("application/json;base64,e30=").is_ok());
+        assert_eq!(
+            importable_data_url("image/png;base64,iVBORw0KGgo=").unwrap_err(),
+            "The MIME type `image/png` is not supported. Only JavaScript and JSON data URLs can \
+             be imported."
+        );
+        assert_eq!(
+            importable_data_url("text/css,body{}").unwrap_err(),
+            "The MIME type `text/css` is not supported. Only JavaScript and JSON data URLs can be \
+             imported."
+        );
+        assert!(importable_data_url("text/javascript")
+            .unwrap_err()
+            .starts_with("The data URL is invalid: "));
+    }
+}
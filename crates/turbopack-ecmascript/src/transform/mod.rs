@@ -1,12 +1,15 @@
+pub mod registry;
+pub mod rules;
+
 use std::{fmt::Debug, hash::Hash, sync::Arc};
 
 use anyhow::Result;
 use async_trait::async_trait;
 use swc_core::{
     base::SwcComments,
-    common::{chain, comments::Comments, util::take::Take, Mark, SourceMap},
+    common::{chain, comments::Comments, util::take::Take, Mark, SourceMap, DUMMY_SP},
     ecma::{
-        ast::{Module, ModuleItem, Program, Script},
+        ast::{Expr, ExprStmt, Lit, Module, ModuleItem, Program, Script, Stmt},
         preset_env::{self, Targets},
         transforms::{
             base::{feature::FeatureFlag, helpers::inject_helpers, Assumptions},
@@ -22,6 +25,14 @@ use turbopack_core::{
     issue::{Issue, IssueSeverity, IssueSeverityVc, IssueVc},
 };
 
+pub use self::{
+    registry::{
+        register_transform_plugin, require_transform_plugin, resolve_transform_plugin,
+        TransformPluginFactory, TransformPluginRegistry,
+    },
+    rules::{TransformRule, TransformRules, TransformRulesVc},
+};
+
 #[turbo_tasks::value(serialization = "auto_for_input")]
 #[derive(Debug, Clone, PartialOrd, Ord, Hash)]
 pub enum EcmascriptInputTransform {
@@ -239,9 +250,16 @@ impl EcmascriptInputTransform {
                 // TODO(WEB-1213)
                 use_define_for_class_fields: _use_define_for_class_fields,
             } => {
-                use swc_core::ecma::transforms::typescript::strip_with_config;
+                use swc_core::ecma::transforms::typescript::strip_with_jsx;
                 let config = Default::default();
-                program.visit_mut_with(&mut strip_with_config(config, top_level_mark));
+                // The strip runs before the React transform, so it needs to know the JSX
+                // pragmas of the file, e.g. `/** @jsx h */`, to keep their imports.
+                program.visit_mut_with(&mut strip_with_jsx::<&dyn Comments>(
+                    source_map.clone(),
+                    config,
+                    &comments,
+                    top_level_mark,
+                ));
             }
             EcmascriptInputTransform::Decorators {
                 is_legacy,
@@ -282,6 +300,37 @@ pub fn remove_shebang(program: &mut Program) {
     }
 }
 
+/// Inserts a `"use strict"` directive at the start of the program, unless it
+/// already has one. A module which is emitted as CommonJS keeps the strict mode
+/// of its ESM source this way.
+pub fn ensure_use_strict(program: &mut Program) {
+    let is_use_strict = |stmt: Option<&Stmt>| {
+        matches!(
+            stmt,
+            Some(Stmt::Expr(ExprStmt { box expr, .. }))
+                if matches!(expr, Expr::Lit(Lit::Str(str)) if &*str.value == "use strict")
+        )
+    };
+    let use_strict = || {
+        Stmt::Expr(ExprStmt {
+            span: DUMMY_SP,
+            expr: Box::new(Expr::Lit(Lit::Str("use strict".into()))),
+        })
+    };
+    match program {
+        Program::Module(m) => {
+            if !is_use_strict(m.body.first().and_then(|item| item.as_stmt())) {
+                m.body.insert(0, ModuleItem::Stmt(use_strict()));
+            }
+        }
+        Program::Script(s) => {
+            if !is_use_strict(s.body.first()) {
+                s.body.insert(0, use_strict());
+            }
+        }
+    }
+}
+
 #[turbo_tasks::value(shared)]
 pub struct UnsupportedServerActionIssue {
     pub context: FileSystemPathVc,
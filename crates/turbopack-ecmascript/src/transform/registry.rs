@@ -0,0 +1,157 @@
+use std::{collections::BTreeMap, sync::RwLock};
+
+use anyhow::{bail, Result};
+use lazy_static::lazy_static;
+
+use super::{CustomTransformer, OptionTransformPluginVc, TransformPluginVc};
+
+/// Creates a new instance of a transform plugin.
+pub type TransformPluginFactory =
+    Box<dyn Fn() -> Box<dyn CustomTransformer + Send + Sync> + Send + Sync>;
+
+/// Maps names to factories of transform plugins, so that configuration can
+/// refer to transforms by name, e.g. `"transforms": ["my-plugin"]`.
+#[derive(Default)]
+pub struct TransformPluginRegistry {
+    factories: BTreeMap<String, TransformPluginFactory>,
+}
+
+impl TransformPluginRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers the factory of a transform plugin under `name`. Replaces a
+    /// factory that was registered under the same name before.
+    pub fn register(
+        &mut self,
+        name: impl Into<String>,
+        factory: impl Fn() -> Box<dyn CustomTransformer + Send + Sync> + Send + Sync + 'static,
+    ) {
+        self.factories.insert(name.into(), Box::new(factory));
+    }
+
+    /// Returns true when a transform plugin is registered under `name`.
+    pub fn contains(&self, name: &str) -> bool {
+        self.factories.contains_key(name)
+    }
+
+    /// Returns the names of the registered transform plugins in alphabetical
+    /// order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.factories.keys().map(|name| name.as_str())
+    }
+
+    /// Creates an instance of the transform plugin registered under `name`.
+    pub fn create(&self, name: &str) -> Option<Box<dyn CustomTransformer + Send + Sync>> {
+        self.factories.get(name).map(|factory| factory())
+    }
+}
+
+lazy_static! {
+    static ref TRANSFORM_PLUGINS: RwLock<TransformPluginRegistry> = Default::default();
+}
+
+/// Registers the factory of a transform plugin under `name` in the global
+/// registry that is used by [resolve_transform_plugin].
+///
+/// The registry is not tracked by turbo-tasks, plugins need to be registered
+/// at startup before any module is processed.
+pub fn register_transform_plugin(
+    name: impl Into<String>,
+    factory: impl Fn() -> Box<dyn CustomTransformer + Send + Sync> + Send + Sync + 'static,
+) {
+    TRANSFORM_PLUGINS.write().unwrap().register(name, factory);
+}
+
+/// Looks up the transform plugin registered under `name`. Returns `None` for
+/// unknown names.
+///
+/// Every name resolves to the same plugin instance, so that modules
+/// transformed with it can share their cached results.
+#[turbo_tasks::function]
+pub fn resolve_transform_plugin(name: String) -> OptionTransformPluginVc {
+    OptionTransformPluginVc::cell(
+        TRANSFORM_PLUGINS
+            .read()
+            .unwrap()
+            .create(&name)
+            .map(TransformPluginVc::cell),
+    )
+}
+
+/// Like [resolve_transform_plugin], but fails for unknown names.
+pub async fn require_transform_plugin(name: String) -> Result<TransformPluginVc> {
+    if let Some(plugin) = *resolve_transform_plugin(name.clone()).await? {
+        return Ok(plugin);
+    }
+    let registry = TRANSFORM_PLUGINS.read().unwrap();
+    let names = registry.names().collect::<Vec<_>>();
+    if names.is_empty() {
+        bail!("unknown transform plugin \"{name}\", no transform plugins are registered");
+    }
+    bail!(
+        "unknown transform plugin \"{name}\", registered transform plugins are: {}",
+        names.join(", ")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use async_trait::async_trait;
+    use swc_core::ecma::ast::Program;
+    use turbo_tasks::TurboTasks;
+    use turbo_tasks_memory::MemoryBackend;
+
+    use super::{register_transform_plugin, require_transform_plugin, resolve_transform_plugin};
+    use crate::transform::{CustomTransformer, TransformContext};
+
+    #[derive(Debug)]
+    struct Noop(&'static str);
+
+    #[async_trait]
+    impl CustomTransformer for Noop {
+        async fn transform(
+            &self,
+            _program: &mut Program,
+            _ctx: &TransformContext<'_>,
+        ) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn resolves_registered_plugins() {
+        crate::register();
+        register_transform_plugin("noop", || Box::new(Noop("noop")));
+
+        let tt = TurboTasks::new(MemoryBackend::default());
+        tt.run_once(async {
+            let plugin = resolve_transform_plugin("noop".to_string()).await?;
+            let plugin = plugin.expect("noop is registered");
+            assert_eq!(format!("{:?}", &*plugin.await?), "Noop(\"noop\")");
+            // The same instance is returned every time.
+            let again = resolve_transform_plugin("noop".to_string()).await?;
+            assert_eq!(Some(plugin), *again);
+            assert_eq!(require_transform_plugin("noop".to_string()).await?, plugin);
+
+            assert!(resolve_transform_plugin("missing".to_string())
+                .await?
+                .is_none());
+            let error = require_transform_plugin("missing".to_string())
+                .await
+                .unwrap_err();
+            assert!(
+                error
+                    .to_string()
+                    .contains("unknown transform plugin \"missing\"")
+                    && error.to_string().contains("noop"),
+                "{error}"
+            );
+            Ok(())
+        })
+        .await
+        .unwrap();
+    }
+}
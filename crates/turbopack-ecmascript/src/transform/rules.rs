@@ -0,0 +1,130 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use turbo_tasks::trace::TraceRawVcs;
+use turbo_tasks_fs::{glob::GlobVc, FileSystemPathVc};
+
+use super::EcmascriptInputTransformsVc;
+
+/// Additional transforms for the modules whose path matches `glob`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs)]
+pub struct TransformRule {
+    pub glob: GlobVc,
+    pub transforms: EcmascriptInputTransformsVc,
+}
+
+impl TransformRule {
+    pub fn new(glob: &str, transforms: EcmascriptInputTransformsVc) -> Self {
+        TransformRule {
+            glob: GlobVc::new(glob),
+            transforms,
+        }
+    }
+}
+
+/// Selects additional transforms for a module by its path, e.g. to run a
+/// transform only on `**/*.macro.js`. The first matching rule is used, and
+/// `default` when none matches.
+#[turbo_tasks::value(shared)]
+#[derive(Clone, Debug, Default)]
+pub struct TransformRules {
+    pub rules: Vec<TransformRule>,
+    pub default: Option<EcmascriptInputTransformsVc>,
+}
+
+#[turbo_tasks::value_impl]
+impl TransformRulesVc {
+    #[turbo_tasks::function]
+    pub fn empty() -> Self {
+        TransformRules::default().cell()
+    }
+
+    /// Returns `transforms` followed by the additional transforms for the
+    /// module at `path`. Returns `transforms` itself when there are none.
+    #[turbo_tasks::function]
+    pub async fn apply(
+        self,
+        path: FileSystemPathVc,
+        transforms: EcmascriptInputTransformsVc,
+    ) -> Result<EcmascriptInputTransformsVc> {
+        let this = self.await?;
+        let path = path.await?;
+        let mut additional = this.default;
+        for rule in this.rules.iter() {
+            if rule.glob.await?.execute(&path.path) {
+                additional = Some(rule.transforms);
+                break;
+            }
+        }
+        Ok(match additional {
+            Some(additional) => transforms.extend(additional),
+            None => transforms,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use turbo_tasks::TurboTasks;
+    use turbo_tasks_fs::{FileSystem, VirtualFileSystemVc};
+    use turbo_tasks_memory::MemoryBackend;
+
+    use super::{TransformRule, TransformRules, TransformRulesVc};
+    use crate::transform::{EcmascriptInputTransform, EcmascriptInputTransformsVc};
+
+    #[tokio::test]
+    async fn first_matching_rule_wins() {
+        crate::register();
+
+        let tt = TurboTasks::new(MemoryBackend::default());
+        tt.run_once(async {
+            let root = VirtualFileSystemVc::new().root();
+            let base = EcmascriptInputTransformsVc::cell(vec![EcmascriptInputTransform::CommonJs]);
+            let macros =
+                EcmascriptInputTransformsVc::cell(vec![EcmascriptInputTransform::TypeScript {
+                    use_define_for_class_fields: false,
+                }]);
+            let rules = |default| {
+                TransformRules {
+                    rules: vec![
+                        TransformRule::new("**/*.macro.js", macros),
+                        TransformRule::new("src/**", EcmascriptInputTransformsVc::empty()),
+                    ],
+                    default,
+                }
+                .cell()
+            };
+            let transforms =
+                |rules: TransformRulesVc, path: &str| rules.apply(root.join(path), base);
+
+            // Non-matching files keep their transforms.
+            let rules_without_default = rules(None);
+            let other = transforms(rules_without_default, "lib/other.js");
+            assert_eq!(other.resolve().await?, base.resolve().await?);
+
+            // The first matching rule is used, even when a later one matches too.
+            let macro_transforms = transforms(rules_without_default, "src/a.macro.js").await?;
+            assert_eq!(
+                *macro_transforms,
+                vec![
+                    EcmascriptInputTransform::CommonJs,
+                    EcmascriptInputTransform::TypeScript {
+                        use_define_for_class_fields: false
+                    },
+                ]
+            );
+            let src_transforms = transforms(rules_without_default, "src/a.js").await?;
+            assert_eq!(*src_transforms, vec![EcmascriptInputTransform::CommonJs]);
+
+            // The default rule is used when no rule matches.
+            let rules_with_default = rules(Some(macros));
+            let other = transforms(rules_with_default, "lib/other.js").await?;
+            assert_eq!(*other, *macro_transforms);
+            let src_transforms = transforms(rules_with_default, "src/a.js").await?;
+            assert_eq!(*src_transforms, vec![EcmascriptInputTransform::CommonJs]);
+
+            Ok(())
+        })
+        .await
+        .unwrap();
+    }
+}
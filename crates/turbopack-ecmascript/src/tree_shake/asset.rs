@@ -1,5 +1,5 @@
-use anyhow::{bail, Context, Result};
-use turbo_tasks::{primitives::StringVc, Value};
+use anyhow::{Context, Result};
+use turbo_tasks::Value;
 use turbopack_core::{
     asset::{Asset, AssetContentVc, AssetVc},
     chunk::{
@@ -8,7 +8,7 @@ use turbopack_core::{
     },
     ident::AssetIdentVc,
     module::{Module, ModuleVc},
-    reference::{AssetReferencesVc, SingleAssetReferenceVc},
+    reference::AssetReferencesVc,
     resolve::ModulePartVc,
 };
 
@@ -44,6 +44,23 @@ impl EcmascriptModulePartAssetVc {
         }
         .cell()
     }
+
+    /// Like [EcmascriptModulePartAssetVc::new], but refers to the part by its
+    /// internal id, so that all [ModulePart]s pointing to the same part of
+    /// the module result in the same asset.
+    #[turbo_tasks::function]
+    pub async fn select_part(module: EcmascriptModuleAssetVc, part: ModulePartVc) -> Result<Self> {
+        let split_result = split_module(module).await?;
+
+        Ok(if matches!(&*split_result, SplitResult::Ok { .. }) {
+            let part_id = get_part_id(&split_result, part)
+                .await
+                .with_context(|| format!("part {:?} is not found in the module", part))?;
+            Self::new(module, ModulePartVc::internal(part_id))
+        } else {
+            Self::new(module, part)
+        })
+    }
 }
 
 #[turbo_tasks::value_impl]
@@ -56,48 +73,11 @@ impl Asset for EcmascriptModulePartAsset {
     }
 
     #[turbo_tasks::function]
-    async fn references(&self) -> Result<AssetReferencesVc> {
-        let split_data = split_module(self.full_module).await?;
-
-        let deps = match &*split_data {
-            SplitResult::Ok { deps, .. } => deps,
-            _ => {
-                bail!("failed to split module")
-            }
-        };
-
-        let part_id = get_part_id(&split_data, self.part)
-            .await
-            .with_context(|| format!("part {:?} is not found in the module", self.part))?;
-
-        let deps = match deps.get(&part_id) {
-            Some(v) => v,
-            None => bail!("part {:?} is not found in the module", part_id),
-        };
-
-        let mut assets = deps
-            .iter()
-            .map(|&part_id| {
-                Ok(SingleAssetReferenceVc::new(
-                    EcmascriptModulePartAssetVc::new(
-                        self.full_module,
-                        ModulePartVc::internal(part_id),
-                    )
-                    .as_asset(),
-                    StringVc::cell("ecmascript module part".to_string()),
-                )
-                .as_asset_reference())
-            })
-            .collect::<Result<Vec<_>>>()?;
-
-        let external = analyze(self.full_module, self.part)
-            .await?
-            .references
-            .await?;
-
-        assets.extend(external.iter().cloned());
-
-        Ok(AssetReferencesVc::cell(assets))
+    async fn references(self_vc: EcmascriptModulePartAssetVc) -> Result<AssetReferencesVc> {
+        // The code of the part imports the parts it depends on, so the analysis
+        // references exactly these parts and the external references of the
+        // part.
+        Ok(self_vc.analyze().await?.references)
     }
 
     #[turbo_tasks::function]
@@ -124,7 +104,7 @@ impl EcmascriptChunkPlaceable for EcmascriptModulePartAsset {
     }
 
     #[turbo_tasks::function]
-    async fn get_exports(self_vc: EcmascriptModuleAssetVc) -> Result<EcmascriptExportsVc> {
+    async fn get_exports(self_vc: EcmascriptModulePartAssetVc) -> Result<EcmascriptExportsVc> {
         Ok(self_vc.analyze().await?.exports)
     }
 }
@@ -152,7 +132,7 @@ impl ChunkableModule for EcmascriptModulePartAsset {
 #[turbo_tasks::value_impl]
 impl EcmascriptModulePartAssetVc {
     #[turbo_tasks::function]
-    pub(super) async fn analyze(self) -> Result<AnalyzeEcmascriptModuleResultVc> {
+    pub(crate) async fn analyze(self) -> Result<AnalyzeEcmascriptModuleResultVc> {
         let this = self.await?;
 
         Ok(analyze(this.full_module, this.part))
@@ -170,7 +150,7 @@ async fn analyze(
         module.source,
         full_module.as_resolve_origin(),
         Value::new(module.ty),
-        module.transforms,
+        full_module.transforms(),
         Value::new(module.options),
         module.compile_time_info,
         Some(part),
@@ -13,6 +13,7 @@ use crate::{
         EcmascriptChunkItem, EcmascriptChunkItemContentVc, EcmascriptChunkItemVc,
         EcmascriptChunkingContextVc,
     },
+    references::esm::async_module::is_async_module,
     EcmascriptModuleContentVc,
 };
 
@@ -47,6 +48,7 @@ impl EcmascriptChunkItem for EcmascriptModulePartChunkItem {
         let module = this.module.await?;
         let split_data = split_module(module.full_module);
         let parsed = part_of_module(split_data, module.part);
+        let options = module.full_module.await?.options;
 
         let content = EcmascriptModuleContentVc::new(
             parsed,
@@ -54,9 +56,18 @@ impl EcmascriptChunkItem for EcmascriptModulePartChunkItem {
             this.context,
             this.module.analyze(),
             availability_info,
+            Value::new(options.preserve_comments),
+            Value::new(options.keep_names),
+            Value::new(options.target_module_format),
+            options.post_processor,
         );
 
-        Ok(EcmascriptChunkItemContentVc::new(content, this.context))
+        let async_module = *is_async_module(this.module.into(), availability_info).await?;
+        Ok(EcmascriptChunkItemContentVc::new(
+            content,
+            this.context,
+            async_module,
+        ))
     }
 
     #[turbo_tasks::function]
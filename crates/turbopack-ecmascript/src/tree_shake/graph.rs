@@ -186,9 +186,6 @@ pub(super) enum Mode {
 
 pub(super) struct SplitModuleResult {
     pub entrypoints: FxHashMap<Key, u32>,
-
-    /// Dependency between parts.
-    pub part_deps: FxHashMap<u32, Vec<u32>>,
     pub modules: Vec<Module>,
 }
 
@@ -227,7 +224,6 @@ impl DepGraph {
     ) -> SplitModuleResult {
         let groups = self.finalize(data);
         let mut exports = FxHashMap::default();
-        let mut part_deps = FxHashMap::<_, Vec<_>>::default();
 
         let mut modules = vec![];
 
@@ -290,8 +286,6 @@ impl DepGraph {
                     }
                 }
 
-                part_deps.entry(ix as u32).or_default().push(dep);
-
                 chunk
                     .body
                     .push(ModuleItem::ModuleDecl(ModuleDecl::Import(ImportDecl {
@@ -349,7 +343,6 @@ impl DepGraph {
 
         SplitModuleResult {
             entrypoints: exports,
-            part_deps,
             modules,
         }
     }
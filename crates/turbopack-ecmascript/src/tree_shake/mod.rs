@@ -5,6 +5,7 @@ use swc_core::ecma::ast::{Id, Module, Program};
 use turbo_tasks_fs::FileSystemPathVc;
 use turbopack_core::resolve::{origin::ResolveOrigin, ModulePart, ModulePartVc};
 
+pub(crate) use self::graph::find_turbopack_chunk_id_in_asserts;
 use self::graph::{DepGraph, ItemData, ItemId, ItemIdGroupKind, Mode, SplitModuleResult};
 use crate::{
     analyzer::graph::EvalContext,
@@ -280,9 +281,6 @@ pub(crate) enum SplitResult {
 
         #[turbo_tasks(debug_ignore, trace_ignore)]
         modules: Vec<ParseResultVc>,
-
-        #[turbo_tasks(debug_ignore, trace_ignore)]
-        deps: FxHashMap<u32, Vec<u32>>,
     },
     Unparseable,
     NotFound,
@@ -322,7 +320,6 @@ pub(super) async fn split(path: FileSystemPathVc, parsed: ParseResultVc) -> Resu
 
             let SplitModuleResult {
                 entrypoints,
-                part_deps,
                 modules,
             } = dep_graph.split_module(&format!("./{filename}").into(), &items);
 
@@ -344,7 +341,6 @@ pub(super) async fn split(path: FileSystemPathVc, parsed: ParseResultVc) -> Resu
 
             Ok(SplitResult::Ok {
                 entrypoints,
-                deps: part_deps,
                 modules,
             }
             .cell())
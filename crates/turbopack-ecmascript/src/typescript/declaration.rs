@@ -0,0 +1,213 @@
+use anyhow::Result;
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use swc_core::{
+    common::{
+        comments::{Comment, CommentKind},
+        Spanned,
+    },
+    ecma::{
+        ast::{
+            Decl, ModuleDecl, ModuleItem, Program, Stmt, TsImportType, TsModuleDecl, TsModuleName,
+            TsModuleRef,
+        },
+        visit::{Visit, VisitWith},
+    },
+};
+use turbo_tasks::{debug::ValueDebugFormat, trace::TraceRawVcs, TryJoinIterExt, Value};
+use turbopack_core::{
+    asset::AssetVc,
+    reference::AssetReference,
+    resolve::{origin::ResolveOriginVc, parse::RequestVc, ResolveResultVc},
+};
+
+use super::resolve::type_resolve;
+use crate::{
+    parse::{ParseResult, ParseResultVc},
+    references::typescript::{TsReferencePathAssetReferenceVc, TsReferenceTypeAssetReferenceVc},
+};
+
+/// A triple-slash directive at the start of a TypeScript file.
+pub(crate) enum TripleSlashDirective {
+    /// `/// <reference path="..." />`
+    Path(String),
+    /// `/// <reference types="..." />`
+    Types(String),
+}
+
+/// Extracts the triple-slash directives from the comments at the start of a
+/// file.
+pub(crate) fn triple_slash_directives(comments: &[Comment]) -> Vec<TripleSlashDirective> {
+    lazy_static! {
+        static ref REFERENCE_PATH: Regex =
+            Regex::new(r#"^/\s*<reference\s*path\s*=\s*["'](.+)["']\s*/>\s*$"#).unwrap();
+        static ref REFERENCE_TYPES: Regex =
+            Regex::new(r#"^/\s*<reference\s*types\s*=\s*["'](.+)["']\s*/>\s*$"#).unwrap();
+    }
+    comments
+        .iter()
+        .filter(|comment| comment.kind == CommentKind::Line)
+        .filter_map(|comment| {
+            let text = &comment.text;
+            if let Some(m) = REFERENCE_PATH.captures(text) {
+                Some(TripleSlashDirective::Path(m[1].to_string()))
+            } else {
+                REFERENCE_TYPES
+                    .captures(text)
+                    .map(|m| TripleSlashDirective::Types(m[1].to_string()))
+            }
+        })
+        .collect()
+}
+
+/// How a TypeScript declaration file references another file.
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs, ValueDebugFormat,
+)]
+pub enum TypescriptReferenceKind {
+    /// `/// <reference path="..." />`
+    ReferencePath,
+    /// `/// <reference types="..." />`
+    ReferenceTypes,
+    /// `import`, `import type`, `export ... from`, `import x = require(...)`
+    /// and `import("...")` types.
+    Import,
+    /// `declare module "..." { ... }` in a module, which augments the
+    /// declarations of another module.
+    ModuleAugmentation,
+}
+
+/// A type-level reference of a TypeScript declaration file.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, TraceRawVcs, ValueDebugFormat)]
+pub struct TypescriptReference {
+    pub kind: TypescriptReferenceKind,
+    /// The request as written in the source.
+    pub request: String,
+    /// The asset the request resolves to, if it could be resolved.
+    pub target: Option<AssetVc>,
+}
+
+#[turbo_tasks::value(transparent)]
+pub struct TypescriptReferences(Vec<TypescriptReference>);
+
+/// Extracts the type-level references of a TypeScript declaration file
+/// without running the full ECMAScript analysis.
+#[turbo_tasks::function]
+pub(crate) async fn typescript_declaration_references(
+    parsed: ParseResultVc,
+    origin: ResolveOriginVc,
+) -> Result<TypescriptReferencesVc> {
+    let ParseResult::Ok {
+        program, comments, ..
+    } = &*parsed.await?
+    else {
+        return Ok(TypescriptReferencesVc::cell(Vec::new()));
+    };
+
+    let mut requests = Vec::new();
+    if let Some(comments) = comments.leading.get(&program.span().lo) {
+        for directive in triple_slash_directives(&comments) {
+            requests.push(match directive {
+                TripleSlashDirective::Path(path) => (TypescriptReferenceKind::ReferencePath, path),
+                TripleSlashDirective::Types(types) => {
+                    (TypescriptReferenceKind::ReferenceTypes, types)
+                }
+            });
+        }
+    }
+    let mut visitor = DeclarationReferencesVisitor::default();
+    program.visit_with(&mut visitor);
+    requests.extend(visitor.requests);
+
+    let references = requests
+        .into_iter()
+        .map(|(kind, request)| async move {
+            let result: ResolveResultVc = match kind {
+                TypescriptReferenceKind::ReferencePath => {
+                    TsReferencePathAssetReferenceVc::new(origin, request.clone())
+                        .resolve_reference()
+                }
+                TypescriptReferenceKind::ReferenceTypes => {
+                    TsReferenceTypeAssetReferenceVc::new(origin, request.clone())
+                        .resolve_reference()
+                }
+                TypescriptReferenceKind::Import | TypescriptReferenceKind::ModuleAugmentation => {
+                    type_resolve(origin, RequestVc::parse(Value::new(request.clone().into())))
+                }
+            };
+            Ok(TypescriptReference {
+                kind,
+                target: *result.first_asset().await?,
+                request,
+            })
+        })
+        .try_join()
+        .await?;
+
+    Ok(TypescriptReferencesVc::cell(references))
+}
+
+/// Collects the module requests of imports and module augmentations.
+#[derive(Default)]
+struct DeclarationReferencesVisitor {
+    requests: Vec<(TypescriptReferenceKind, String)>,
+}
+
+impl Visit for DeclarationReferencesVisitor {
+    fn visit_program(&mut self, program: &Program) {
+        let Program::Module(module) = program else {
+            // Declarations in scripts are global, `declare module` declares an
+            // ambient module instead of augmenting one.
+            program.visit_children_with(self);
+            return;
+        };
+        let is_module = module
+            .body
+            .iter()
+            .any(|item| matches!(item, ModuleItem::ModuleDecl(_)));
+        for item in &module.body {
+            match item {
+                ModuleItem::ModuleDecl(ModuleDecl::Import(import)) => {
+                    self.push_import(&import.src.value);
+                }
+                ModuleItem::ModuleDecl(ModuleDecl::ExportAll(export)) => {
+                    self.push_import(&export.src.value);
+                }
+                ModuleItem::ModuleDecl(ModuleDecl::ExportNamed(export)) => {
+                    if let Some(src) = &export.src {
+                        self.push_import(&src.value);
+                    }
+                }
+                ModuleItem::ModuleDecl(ModuleDecl::TsImportEquals(import)) => {
+                    if let TsModuleRef::TsExternalModuleRef(module_ref) = &import.module_ref {
+                        self.push_import(&module_ref.expr.value);
+                    }
+                }
+                ModuleItem::Stmt(Stmt::Decl(Decl::TsModule(box TsModuleDecl {
+                    id: TsModuleName::Str(name),
+                    ..
+                }))) if is_module => {
+                    self.requests.push((
+                        TypescriptReferenceKind::ModuleAugmentation,
+                        name.value.to_string(),
+                    ));
+                }
+                _ => {}
+            }
+            item.visit_with(self);
+        }
+    }
+
+    fn visit_ts_import_type(&mut self, import: &TsImportType) {
+        self.push_import(&import.arg.value);
+        import.visit_children_with(self);
+    }
+}
+
+impl DeclarationReferencesVisitor {
+    fn push_import(&mut self, request: &str) {
+        self.requests
+            .push((TypescriptReferenceKind::Import, request.to_string()));
+    }
+}
@@ -413,7 +413,13 @@ async fn apply_typescript_types_options(
     resolve_options: ResolveOptionsVc,
 ) -> Result<ResolveOptionsVc> {
     let mut resolve_options = resolve_options.await?.clone_value();
-    resolve_options.extensions = vec![".tsx".to_string(), ".ts".to_string(), ".d.ts".to_string()];
+    resolve_options.extensions = vec![
+        ".tsx".to_string(),
+        ".ts".to_string(),
+        ".d.ts".to_string(),
+        ".d.mts".to_string(),
+        ".d.cts".to_string(),
+    ];
     resolve_options.into_package = resolve_options
         .into_package
         .drain(..)
@@ -1,7 +1,7 @@
 use serde::Serialize;
 use swc_core::{
     common::DUMMY_SP,
-    ecma::ast::{Expr, Lit, Str},
+    ecma::ast::{Expr, Lit, Program, Str},
 };
 use turbopack_core::{chunk::ModuleId, resolve::pattern::Pattern};
 
@@ -17,6 +17,16 @@ pub fn unparen(expr: &Expr) -> &Expr {
     expr
 }
 
+/// Whether `program` has no statements, e.g. when the source is empty or only
+/// contains comments. The syntax of such a module doesn't tell whether it's ESM
+/// or CommonJS.
+pub fn is_empty_program(program: &Program) -> bool {
+    match program {
+        Program::Module(module) => module.body.is_empty(),
+        Program::Script(script) => script.body.is_empty(),
+    }
+}
+
 pub fn js_value_to_pattern(value: &JsValue) -> Pattern {
     let mut result = match value {
         JsValue::Constant(v) => Pattern::Constant(match v {
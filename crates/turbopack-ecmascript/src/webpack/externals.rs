@@ -0,0 +1,110 @@
+use std::collections::HashMap;
+
+use anyhow::{bail, Result};
+use indexmap::IndexMap;
+use turbopack_core::resolve::{
+    options::{ImportMap, ImportMapVc, ImportMapping},
+    ExternalType, PrimaryResolveResult, ResolveResult,
+};
+
+use crate::GlobalsExternalsVc;
+
+/// How an external is provided at runtime, see webpack's `externalsType`.
+#[turbo_tasks::value(serialization = "auto_for_input")]
+#[derive(PartialOrd, Ord, Hash, Debug, Clone, Copy)]
+pub enum WebpackExternalType {
+    /// The module is loaded with `require` at runtime.
+    CommonJs,
+    /// The module is read from a property of the global object, e.g.
+    /// `jQuery`. Nested properties are separated by `.`.
+    Global,
+    /// The module is loaded with `import` at runtime, so the modules which
+    /// import it are evaluated asynchronously.
+    Module,
+}
+
+/// A request which is left unbundled and mapped to an external.
+#[turbo_tasks::value(shared)]
+#[derive(Hash, Debug, Clone)]
+pub struct WebpackExternal {
+    pub ty: WebpackExternalType,
+    /// The request which is loaded at runtime, or the global for
+    /// [WebpackExternalType::Global].
+    pub request: String,
+}
+
+impl WebpackExternal {
+    /// Parses the value of a webpack `externals` entry, e.g. `"jQuery"` or
+    /// `"commonjs react"`. Values without a type use `default_type`, like
+    /// webpack's `externalsType`. `var`, `window` and `self` externals are
+    /// [WebpackExternalType::Global] externals.
+    pub fn parse(value: &str, default_type: WebpackExternalType) -> Result<Self> {
+        let (ty, request) = match value.split_once(' ') {
+            Some((ty, request)) => {
+                let ty = match ty {
+                    "commonjs" | "commonjs2" | "node-commonjs" => WebpackExternalType::CommonJs,
+                    "global" | "var" | "window" | "self" => WebpackExternalType::Global,
+                    "module" | "import" => WebpackExternalType::Module,
+                    _ => bail!("unsupported webpack external type `{ty}` in `{value}`"),
+                };
+                (ty, request.trim())
+            }
+            None => (default_type, value),
+        };
+        if request.is_empty() {
+            bail!("the webpack external `{value}` has no request");
+        }
+        Ok(WebpackExternal {
+            ty,
+            request: request.to_string(),
+        })
+    }
+}
+
+/// Maps requests to externals, like webpack's `externals` config. Requests
+/// are matched exactly and are never bundled, even when they can be
+/// resolved.
+///
+/// The externals are provided by the existing mechanisms: `commonjs` and
+/// `module` externals by the [import map](WebpackExternalsVc::import_map) of
+/// the resolve options, `global` externals by the
+/// [GlobalsExternals](WebpackExternalsVc::globals_externals) of the module
+/// options.
+#[turbo_tasks::value(transparent)]
+pub struct WebpackExternals(IndexMap<String, WebpackExternal>);
+
+#[turbo_tasks::value_impl]
+impl WebpackExternalsVc {
+    /// An import map which maps the requests of the `commonjs` and `module`
+    /// externals to externals. It should be used as primary import map, so
+    /// that it takes precedence over resolving.
+    #[turbo_tasks::function]
+    pub async fn import_map(self) -> Result<ImportMapVc> {
+        let mut import_map = ImportMap::empty();
+        for (request, external) in self.await?.iter() {
+            let ty = match external.ty {
+                WebpackExternalType::CommonJs => ExternalType::CommonJs,
+                WebpackExternalType::Module => ExternalType::EcmaScriptModule,
+                WebpackExternalType::Global => continue,
+            };
+            let result = ResolveResult::primary(
+                PrimaryResolveResult::OriginalReferenceTypeExternal(external.request.clone(), ty),
+            );
+            import_map.insert_exact_alias(request, ImportMapping::Direct(result.cell()).cell());
+        }
+        Ok(import_map.cell())
+    }
+
+    /// The `global` externals, which are read from the global object where
+    /// they are imported or required.
+    #[turbo_tasks::function]
+    pub async fn globals_externals(self) -> Result<GlobalsExternalsVc> {
+        let globals_externals: HashMap<_, _> = self
+            .await?
+            .iter()
+            .filter(|(_, external)| external.ty == WebpackExternalType::Global)
+            .map(|(request, external)| (request.clone(), external.request.clone()))
+            .collect();
+        Ok(GlobalsExternalsVc::cell(globals_externals))
+    }
+}
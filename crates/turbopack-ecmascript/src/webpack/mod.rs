@@ -22,6 +22,7 @@ use self::{
 use super::resolve::apply_cjs_specific_options;
 use crate::EcmascriptInputTransformsVc;
 
+pub mod externals;
 pub mod parse;
 pub(crate) mod references;
 
@@ -0,0 +1,3 @@
+import(`./locales/${"en"}.json`);
+require("./" + "config" + ".js");
+require(true ? "./a.js" : "./b.js");
@@ -0,0 +1,5 @@
+const template = `./locales/${"en"}.json`;
+const binary = "./" + "config" + ".js";
+const ternary = true ? "./a.js" : "./b.js";
+const comparison = "en" === "de" ? "./de.js" : "./en.js";
+const concatenated = "./".concat("config", ".js");
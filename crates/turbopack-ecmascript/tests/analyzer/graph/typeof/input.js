@@ -0,0 +1,11 @@
+const a = typeof "str";
+const b = typeof 1;
+const c = typeof (x ? 1 : 2);
+const d = typeof (x ? 1 : "str");
+const e = typeof typeof x;
+const f = typeof window === "undefined";
+const g = typeof {} === "object" ? "yes" : "no";
+
+if (typeof document !== "undefined") {
+  require("client");
+}
@@ -0,0 +1,6 @@
+const worker = new Worker(new URL("./worker.js", import.meta.url));
+const shared = new SharedWorker(new URL("./shared-worker.js", import.meta.url), {
+  type: "module",
+});
+const dynamic = new Worker(new URL(`./workers/${name}.js`, import.meta.url));
+const url = new URL("./asset.txt", import.meta.url);
@@ -10,7 +10,10 @@
 use std::fmt::Write;
 
 use anyhow::{bail, Error, Result};
-use turbo_tasks::{primitives::StringVc, Value, ValueToString};
+use turbo_tasks::{
+    primitives::{OptionStringVc, StringVc},
+    Value, ValueToString,
+};
 use turbo_tasks_fs::{FileContent, FileJsonContent};
 use turbopack_core::{
     asset::{Asset, AssetContentVc, AssetVc},
@@ -26,7 +29,8 @@ use turbopack_core::{
 use turbopack_ecmascript::chunk::{
     EcmascriptChunkItem, EcmascriptChunkItemContent, EcmascriptChunkItemContentVc,
     EcmascriptChunkItemVc, EcmascriptChunkPlaceable, EcmascriptChunkPlaceableVc, EcmascriptChunkVc,
-    EcmascriptChunkingContextVc, EcmascriptExports, EcmascriptExportsVc,
+    EcmascriptChunkingContextVc, EcmascriptConstantModule, EcmascriptConstantModuleVc,
+    EcmascriptExports, EcmascriptExportsVc,
 };
 
 #[turbo_tasks::function]
@@ -100,6 +104,24 @@ impl EcmascriptChunkPlaceable for JsonModuleAsset {
     }
 }
 
+#[turbo_tasks::value_impl]
+impl EcmascriptConstantModule for JsonModuleAsset {
+    #[turbo_tasks::function]
+    async fn inline_json(&self) -> Result<OptionStringVc> {
+        let data = self.source.content().file_content().parse_json().await?;
+        Ok(OptionStringVc::cell(match &*data {
+            // Importing `null` throws, and an `__esModule` key changes the
+            // default export, so these are left to the runtime.
+            FileJsonContent::Content(data)
+                if !data.is_null() && data.get("__esModule").is_none() =>
+            {
+                Some(data.to_string())
+            }
+            _ => None,
+        }))
+    }
+}
+
 #[turbo_tasks::value]
 struct JsonChunkItem {
     module: JsonModuleAssetVc,
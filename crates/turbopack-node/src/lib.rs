@@ -22,6 +22,7 @@ use turbopack_core::{
         ChunkableModule, ChunkingContext, ChunkingContextVc, EvaluatableAssetVc,
         EvaluatableAssetsVc,
     },
+    output::write_output_asset,
     reference::primary_referenced_assets,
     source_map::GenerateSourceMapVc,
     virtual_source::VirtualSourceVc,
@@ -55,7 +56,7 @@ async fn emit(
             .strongly_consistent()
             .await?
             .iter()
-            .map(|a| a.content().write(a.ident().path()))
+            .map(|a| write_output_asset(*a))
             .collect(),
     )
     .completed())
@@ -4,10 +4,12 @@ mod util;
 
 use std::{
     collections::HashMap,
+    fs,
     path::{Path, PathBuf},
 };
 
 use anyhow::{Context, Result};
+use serde::Deserialize;
 use turbo_tasks::{debug::ValueDebug, CompletionVc, NothingVc, TryJoinIterExt, TurboTasks, Value};
 use turbo_tasks_bytes::stream::SingleValue;
 use turbo_tasks_env::CommandLineProcessEnvVc;
@@ -18,6 +20,10 @@ use turbo_tasks_fs::{
 use turbo_tasks_memory::MemoryBackend;
 use turbopack::{
     condition::ContextCondition,
+    ecmascript::{
+        webpack::externals::{WebpackExternal, WebpackExternalType, WebpackExternalsVc},
+        CjsDefaultInterop, GlobalsExternalsVc, KeepNames, TargetFormat,
+    },
     module_options::{ModuleOptionsContext, TypescriptTransformOptionsVc},
     resolve_options_context::ResolveOptionsContext,
     transition::TransitionsByNameVc,
@@ -57,6 +63,32 @@ struct JsResult {
     jest_result: JestRunResult,
 }
 
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ExecutionOptions {
+    #[serde(default)]
+    keep_names: KeepNames,
+    /// Maps import specifiers to the globals which provide them.
+    #[serde(default)]
+    globals_externals: HashMap<String, String>,
+    /// Webpack `externals` config, e.g. `{ "jquery": "jQuery" }`.
+    #[serde(default)]
+    webpack_externals: HashMap<String, String>,
+    /// Inlines imported JSON files up to this number of bytes.
+    #[serde(default)]
+    inline_json_bytes: Option<usize>,
+    /// What default imports of CommonJS modules bind to, outside of
+    /// node_modules.
+    #[serde(default)]
+    cjs_default_interop: CjsDefaultInterop,
+    /// The module format modules are emitted in, outside of node_modules.
+    #[serde(default)]
+    target_module_format: TargetFormat,
+    /// Loads the runtime from its own chunk, before the evaluated chunk group.
+    #[serde(default)]
+    separate_runtime_chunk: bool,
+}
+
 enum IssueSnapshotMode {
     Snapshots,
     NoSnapshots,
@@ -173,6 +205,11 @@ async fn run_test(resource: &str) -> Result<RunTestResultVc> {
         resource_path.to_str().unwrap()
     );
 
+    let options_file = fs::read_to_string(resource_path.join("options.json"));
+    let options = match options_file {
+        Err(_) => ExecutionOptions::default(),
+        Ok(options_str) => parse_json_with_source_context(&options_str).unwrap(),
+    };
     let root_fs = DiskFileSystemVc::new("workspace".to_string(), REPO_ROOT.clone());
     let project_fs = DiskFileSystemVc::new("project".to_string(), REPO_ROOT.clone());
     let project_root = project_fs.root();
@@ -194,6 +231,35 @@ async fn run_test(resource: &str) -> Result<RunTestResultVc> {
         NodeJsEnvironment::default().into(),
     )));
 
+    let webpack_externals = if options.webpack_externals.is_empty() {
+        None
+    } else {
+        let mut externals = options
+            .webpack_externals
+            .iter()
+            .map(|(request, value)| {
+                Ok((
+                    request.clone(),
+                    WebpackExternal::parse(value, WebpackExternalType::Global)?,
+                ))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        externals.sort_by(|(a, _), (b, _)| a.cmp(b));
+        Some(WebpackExternalsVc::cell(externals.into_iter().collect()))
+    };
+    // `global` webpack externals are read from the global object like the
+    // globals externals.
+    let mut globals_externals = options.globals_externals;
+    if let Some(webpack_externals) = webpack_externals {
+        globals_externals.extend(
+            webpack_externals
+                .globals_externals()
+                .await?
+                .iter()
+                .map(|(request, global)| (request.clone(), global.clone())),
+        );
+    }
+
     let compile_time_info = CompileTimeInfo::builder(env)
         .defines(
             compile_time_defines!(
@@ -210,6 +276,12 @@ async fn run_test(resource: &str) -> Result<RunTestResultVc> {
         ModuleOptionsContext {
             enable_typescript_transform: Some(TypescriptTransformOptionsVc::default()),
             preset_env_versions: Some(env),
+            keep_names: options.keep_names,
+            ecmascript_globals_externals: (!globals_externals.is_empty())
+                .then(|| GlobalsExternalsVc::cell(globals_externals)),
+            ecmascript_inline_json_bytes: options.inline_json_bytes,
+            ecmascript_cjs_default_interop: options.cjs_default_interop,
+            target_module_format: options.target_module_format,
             rules: vec![(
                 ContextCondition::InDirectory("node_modules".to_string()),
                 ModuleOptionsContext {
@@ -224,6 +296,7 @@ async fn run_test(resource: &str) -> Result<RunTestResultVc> {
             enable_typescript: true,
             enable_node_modules: Some(project_root),
             custom_conditions: vec!["development".to_string()],
+            webpack_externals,
             rules: vec![(
                 ContextCondition::InDirectory("node_modules".to_string()),
                 ResolveOptionsContext {
@@ -246,6 +319,7 @@ async fn run_test(resource: &str) -> Result<RunTestResultVc> {
         static_root_path,
         env,
     )
+    .separate_runtime_chunk(options.separate_runtime_chunk)
     .build();
 
     let jest_entry_asset = process_path_to_asset(jest_entry_path, context);
@@ -0,0 +1,8 @@
+import { log } from "./cycle-log.js";
+import "./cycle-b.js";
+
+log.push("cycle-a start");
+await Promise.resolve();
+log.push("cycle-a end");
+
+export { log };
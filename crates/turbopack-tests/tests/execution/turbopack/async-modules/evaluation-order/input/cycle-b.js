@@ -0,0 +1,4 @@
+import { log } from "./cycle-log.js";
+import "./cycle-a.js";
+
+log.push("cycle-b");
@@ -0,0 +1,6 @@
+import { log } from "./log.js";
+import { middle } from "./middle.js";
+
+log.push(`entry ${middle}`);
+
+export { log };
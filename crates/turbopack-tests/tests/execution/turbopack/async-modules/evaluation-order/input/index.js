@@ -0,0 +1,14 @@
+it("should evaluate async modules before the modules importing them", async () => {
+  const { log } = await import("./entry.js");
+  expect(log).toEqual([
+    "leaf start",
+    "leaf end",
+    "middle leaf",
+    "entry middle",
+  ]);
+});
+
+it("should not deadlock on cycles with async modules", async () => {
+  const { log } = await import("./cycle-a.js");
+  expect(log).toEqual(["cycle-b", "cycle-a start", "cycle-a end"]);
+});
@@ -0,0 +1,7 @@
+import { log } from "./log.js";
+
+log.push("leaf start");
+await new Promise((resolve) => setTimeout(resolve, 10));
+log.push("leaf end");
+
+export const leaf = "leaf";
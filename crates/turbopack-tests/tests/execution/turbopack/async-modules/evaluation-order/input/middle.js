@@ -0,0 +1,6 @@
+import { log } from "./log.js";
+import { leaf } from "./leaf.js";
+
+log.push(`middle ${leaf}`);
+
+export const middle = "middle";
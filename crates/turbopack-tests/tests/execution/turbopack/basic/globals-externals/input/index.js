@@ -0,0 +1,27 @@
+import "./stub.js";
+import React, { useState, missing } from "react";
+import * as ReactNamespace from "react";
+import { useStateReexport, version } from "./reexport.js";
+
+it("reads the default import from the global", () => {
+  expect(React).toBe(globalThis.TestReact);
+});
+
+it("reads named imports from properties of the global", () => {
+  expect(useState()).toBe("useState");
+  expect(missing).toBeUndefined();
+});
+
+it("reads the namespace import from the global", () => {
+  expect(ReactNamespace).toBe(globalThis.TestReact);
+  expect(ReactNamespace.version).toBe("18.0.0");
+});
+
+it("re-exports bindings of the global", () => {
+  expect(useStateReexport()).toBe("useState");
+  expect(version).toBe("18.0.0");
+});
+
+it("resolves dynamic imports to the global", async () => {
+  await expect(import("react")).resolves.toBe(globalThis.TestReact);
+});
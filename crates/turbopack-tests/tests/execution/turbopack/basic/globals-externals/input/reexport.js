@@ -0,0 +1,2 @@
+export { useState as useStateReexport } from "react";
+export * from "react";
@@ -0,0 +1,6 @@
+globalThis.TestReact = {
+  version: "18.0.0",
+  useState() {
+    return "useState";
+  },
+};
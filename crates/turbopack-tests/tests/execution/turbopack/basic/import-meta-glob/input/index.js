@@ -0,0 +1,51 @@
+it("imports matching modules lazily", async () => {
+  const modules = import.meta.glob("./routes/*.js");
+  expect(Object.keys(modules)).toEqual([
+    "./routes/about.js",
+    "./routes/home.js",
+    "./routes/secret.js",
+  ]);
+  expect(typeof modules["./routes/home.js"]).toBe("function");
+  const home = await modules["./routes/home.js"]();
+  expect(home.default).toBe("home");
+  expect(home.title).toBe("Home");
+});
+
+it("imports matching modules eagerly", () => {
+  const modules = import.meta.glob("./routes/**/*.js", { eager: true });
+  expect(Object.keys(modules)).toEqual([
+    "./routes/about.js",
+    "./routes/home.js",
+    "./routes/nested/page.js",
+    "./routes/secret.js",
+  ]);
+  expect(modules["./routes/nested/page.js"].default).toBe("nested");
+});
+
+it("imports a single export of the matching modules", async () => {
+  const eager = import.meta.glob("./routes/*.js", {
+    eager: true,
+    import: "title",
+  });
+  expect(eager["./routes/about.js"]).toBe("About");
+
+  const lazy = import.meta.glob("./routes/*.js", { import: "default" });
+  await expect(lazy["./routes/about.js"]()).resolves.toBe("about");
+});
+
+it("excludes files matching negated patterns", () => {
+  const modules = import.meta.glob(["./routes/*.js", "!./routes/secret.js"], {
+    eager: true,
+  });
+  expect(Object.keys(modules)).toEqual(["./routes/about.js", "./routes/home.js"]);
+});
+
+it("supports patterns relative to a parent directory", () => {
+  const modules = import.meta.glob("../input/shared/*.js", { eager: true });
+  expect(Object.keys(modules)).toEqual(["../input/shared/util.js"]);
+  expect(modules["../input/shared/util.js"].default).toBe("shared");
+});
+
+it("returns an empty object when no files match", () => {
+  expect(import.meta.glob("./empty/*.js")).toEqual({});
+});
@@ -0,0 +1,2 @@
+export default "about";
+export const title = "About";
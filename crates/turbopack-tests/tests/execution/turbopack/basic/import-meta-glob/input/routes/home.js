@@ -0,0 +1,2 @@
+export default "home";
+export const title = "Home";
@@ -0,0 +1,2 @@
+export default "secret";
+export const title = "Secret";
@@ -0,0 +1,8 @@
+import { readFileSync } from "fs";
+import { fileURLToPath } from "url";
+
+it("should resolve to the file URL of the emitted asset", () => {
+  const url = import.meta.resolve("./asset.txt");
+  expect(url).toMatch(/^file:\/\/\//);
+  expect(readFileSync(fileURLToPath(url), "utf-8")).toBe("hello world!\n");
+});
@@ -0,0 +1,30 @@
+import flags, { beta, default as flagsDefault } from "./flags.json";
+import * as flagsNs from "./flags.json";
+import locales from "./locales.json";
+import { readFlags } from "./reader.js";
+
+it("should inline the default export of small JSON files", () => {
+  expect(flags).toEqual({ beta: true, default: "not the default export" });
+  expect(flagsDefault).toBe(flags);
+});
+
+it("should inline the named exports of small JSON files", () => {
+  expect(beta).toBe(true);
+});
+
+it("should create the same namespace as importing the module", async () => {
+  const imported = await import("./flags.json");
+  expect(Object.keys(flagsNs)).toEqual(Object.keys(imported));
+  expect(flagsNs.default).toEqual(imported.default);
+  expect(flagsNs.beta).toBe(imported.beta);
+  expect(flagsNs.__esModule).toBe(true);
+});
+
+it("should inline the value into each importer", () => {
+  expect(readFlags()).toEqual(flags);
+});
+
+it("should import JSON files larger than the limit", () => {
+  expect(locales.length).toBe(3);
+  expect(locales[0].name).toBe("English");
+});
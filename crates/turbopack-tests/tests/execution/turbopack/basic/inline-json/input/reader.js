@@ -0,0 +1,5 @@
+import flags from "./flags.json";
+
+export function readFlags() {
+  return flags;
+}
@@ -0,0 +1,39 @@
+import DefaultClass from "./default-class.js";
+import defaultFunction from "./default-function.js";
+import DefaultClassExpression from "./default-class-expression.js";
+
+const Bar = class {};
+const baz = () => {};
+
+function hoisted() {
+  return hoisted.name;
+}
+
+it("keeps the names of anonymous default exports", () => {
+  expect(DefaultClass.name).toBe("default");
+  expect(defaultFunction.name).toBe("default");
+  expect(DefaultClassExpression.name).toBe("default");
+});
+
+it("keeps the names of anonymous class and function expressions", () => {
+  expect(Bar.name).toBe("Bar");
+  expect(baz.name).toBe("baz");
+});
+
+it("keeps the names of functions used before their declaration", () => {
+  expect(hoisted()).toBe("hoisted");
+});
+
+const calls = [];
+function track(target, key, value) {
+  calls.push([target, key, value]);
+}
+
+it("doesn't drop repeated calls which look like name definitions", () => {
+  track(Bar, "name", 1);
+  track(Bar, "name", 1);
+  expect(calls).toEqual([
+    [Bar, "name", 1],
+    [Bar, "name", 1],
+  ]);
+});
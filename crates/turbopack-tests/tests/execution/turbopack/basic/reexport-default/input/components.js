@@ -0,0 +1,3 @@
+export { default } from "./button.js";
+export { default as Card } from "./card.js";
+export * from "./icon.js";
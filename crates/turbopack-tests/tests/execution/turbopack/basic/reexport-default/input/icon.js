@@ -0,0 +1,3 @@
+export const Icon = "icon";
+
+export default "icon default";
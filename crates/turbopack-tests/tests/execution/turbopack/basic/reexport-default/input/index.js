@@ -0,0 +1,15 @@
+import Button, { Card, Icon } from "./components.js";
+
+it("should re-export the default export under its own name", () => {
+  expect(Button()).toBe("button");
+});
+
+it("should re-export the default export under another name", () => {
+  expect(Card()).toBe("card");
+});
+
+it("should not re-export the default export with export *", async () => {
+  const components = await import("./components.js");
+  expect(Icon).toBe("icon");
+  expect(components.default).toBe(Button);
+});
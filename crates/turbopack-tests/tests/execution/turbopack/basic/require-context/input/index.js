@@ -0,0 +1,22 @@
+it("requires the matching modules of a directory", () => {
+  const context = require.context("./icons", false, /\.js$/);
+  expect(context.keys()).toEqual(["./add.js", "./remove.js"]);
+  expect(context("./add.js")).toBe("add");
+  expect(context("./remove.js")).toBe("remove");
+});
+
+it("requires the matching modules of subdirectories", () => {
+  const context = require.context("./icons", true, /\.js$/);
+  expect(context.keys()).toEqual(["./add.js", "./remove.js", "./nested/star.js"]);
+  expect(context("./nested/star.js")).toBe("nested");
+});
+
+it("throws for modules which are not in the context", () => {
+  const context = require.context("./icons", false, /\.js$/);
+  expect(() => context("./README.txt")).toThrow();
+});
+
+it("has no keys when no files match", () => {
+  const context = require.context("./empty", true, /\.js$/);
+  expect(context.keys()).toEqual([]);
+});
@@ -0,0 +1,10 @@
+import { value } from "./value.js";
+
+it("should evaluate modules with the runtime loaded from its own chunk", () => {
+  expect(value).toBe(42);
+});
+
+it("should load async chunks with the runtime loaded from its own chunk", async () => {
+  const { lazyValue } = await import("./lazy.js");
+  expect(lazyValue).toBe("lazy");
+});
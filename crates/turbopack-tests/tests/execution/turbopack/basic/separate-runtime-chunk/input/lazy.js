@@ -0,0 +1 @@
+export const lazyValue = "lazy";
@@ -0,0 +1,29 @@
+import "./stub.js";
+import $ from "jquery";
+import { value } from "nested-global";
+import * as commonjsPath from "commonjs-path";
+import * as modulePath from "module-path";
+import { join } from "module-path";
+
+it("reads global externals from the global object", () => {
+  expect($).toBe(globalThis.TestJQuery);
+  expect($("div")).toBe("jquery div");
+  expect(require("jquery")).toBe(globalThis.TestJQuery);
+});
+
+it("reads nested properties of the global object", () => {
+  expect(value).toBe("nested");
+});
+
+it("requires commonjs externals at runtime", () => {
+  expect(commonjsPath.join("a", "b")).toBe("a/b");
+  expect(require("commonjs-path").join).toBe(commonjsPath.join);
+});
+
+it("imports module externals at runtime", () => {
+  expect(join).toBe(commonjsPath.join);
+  // The namespace of a real ES module can't be extended, unlike the interop
+  // namespace of a required module.
+  expect(Object.isExtensible(modulePath)).toBe(false);
+  expect(Object.isExtensible(commonjsPath)).toBe(true);
+});
@@ -0,0 +1,8 @@
+globalThis.TestJQuery = function $(selector) {
+  return `jquery ${selector}`;
+};
+globalThis.TestGlobals = {
+  nested: {
+    value: "nested",
+  },
+};
@@ -0,0 +1,30 @@
+import flagged from "cjs-fixture/flagged.js";
+import plain from "cjs-fixture/plain.js";
+import defaultProperty from "cjs-fixture/default-property.js";
+import { flagged as flaggedInNodeModules } from "cjs-fixture/esm-importer.js";
+import * as flaggedNamespace from "cjs-fixture/flagged.js";
+import * as plainNamespace from "cjs-fixture/plain.js";
+import * as reexported from "./reexport.js";
+
+it("should respect the __esModule flag", () => {
+  expect(flagged).toBe("default export");
+});
+
+it("should have module.exports as default export without the __esModule flag", () => {
+  expect(plain()).toBe("plain");
+  expect(defaultProperty).toMatchObject({
+    default: "default property",
+    named: "named",
+  });
+});
+
+it("should use the default interop in node_modules", () => {
+  expect(flaggedInNodeModules).toBe("default export");
+});
+
+it("should use the interop for namespace access and re-exports", () => {
+  expect(flaggedNamespace.default).toBe("default export");
+  expect(plainNamespace["default"]()).toBe("plain");
+  expect(reexported.flagged).toBe("default export");
+  expect(reexported.plain()).toBe("plain");
+});
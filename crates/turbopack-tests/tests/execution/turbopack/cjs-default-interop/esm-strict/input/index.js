@@ -0,0 +1,27 @@
+import flagged from "cjs-fixture/flagged.js";
+import plain from "cjs-fixture/plain.js";
+import defaultProperty from "cjs-fixture/default-property.js";
+import { flagged as flaggedInNodeModules } from "cjs-fixture/esm-importer.js";
+import * as flaggedNamespace from "cjs-fixture/flagged.js";
+import * as plainNamespace from "cjs-fixture/plain.js";
+import * as reexported from "./reexport.js";
+
+it("should have the default property as default export", () => {
+  expect(flagged).toBe("default export");
+  expect(defaultProperty).toBe("default property");
+});
+
+it("should not have a synthetic default export", () => {
+  expect(plain).toBeUndefined();
+});
+
+it("should use the default interop in node_modules", () => {
+  expect(flaggedInNodeModules).toBe("default export");
+});
+
+it("should use the interop for namespace access and re-exports", () => {
+  expect(flaggedNamespace.default).toBe("default export");
+  expect(plainNamespace["default"]).toBeUndefined();
+  expect(reexported.flagged).toBe("default export");
+  expect(reexported.plain).toBeUndefined();
+});
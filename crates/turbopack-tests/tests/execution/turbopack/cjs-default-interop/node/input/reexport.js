@@ -0,0 +1,3 @@
+// Re-exports of the default export use the same interop as default imports.
+export { default as flagged } from "cjs-fixture/flagged.js";
+export { default as plain } from "cjs-fixture/plain.js";
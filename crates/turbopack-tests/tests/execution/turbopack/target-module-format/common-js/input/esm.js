@@ -0,0 +1,13 @@
+export let counter = 0;
+
+export function increment() {
+  counter++;
+}
+
+export function isStrict() {
+  return this === undefined;
+}
+
+export const topLevelThis = this;
+
+export default "default export";
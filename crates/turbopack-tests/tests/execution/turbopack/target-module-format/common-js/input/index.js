@@ -0,0 +1,24 @@
+import value, { counter, increment, isStrict } from "./esm.js";
+import * as ns from "./esm.js";
+
+it("should keep the bindings of ES modules", () => {
+  expect(value).toBe("default export");
+  expect(counter).toBe(0);
+  increment();
+  expect(counter).toBe(1);
+  expect(ns.counter).toBe(1);
+});
+
+it("should require ES modules with the __esModule flag", () => {
+  const esm = require("./esm.js");
+  expect(esm.__esModule).toBe(true);
+  expect(esm.default).toBe("default export");
+});
+
+it("should keep ES modules strict", () => {
+  expect(isStrict()).toBe(true);
+});
+
+it("should evaluate ES modules with the exports as this", () => {
+  expect(ns.topLevelThis).toBe(ns);
+});
@@ -20,11 +20,15 @@ use turbo_tasks_fs::{
 use turbo_tasks_memory::MemoryBackend;
 use turbopack::{
     condition::ContextCondition,
-    ecmascript::{EcmascriptModuleAssetVc, TransformPluginVc},
+    ecmascript::{
+        post_process::{CodePostProcessorVc, EmittedCodePostProcessor, PostProcessedCode},
+        CommentsMode, EcmascriptModuleAssetVc, TransformPluginVc,
+    },
     module_options::{
         CustomEcmascriptTransformPlugins, CustomEcmascriptTransformPluginsVc, JsxTransformOptions,
-        JsxTransformOptionsVc, ModuleOptionsContext,
+        JsxTransformOptionsVc, ModuleOptionsContext, TypescriptTransformOptionsVc,
     },
+    resolve::ExternalsConfig,
     resolve_options_context::ResolveOptionsContext,
     transition::TransitionsByNameVc,
     ModuleAssetContextVc,
@@ -84,6 +88,36 @@ struct SnapshotOptions {
     runtime_type: RuntimeType,
     #[serde(default)]
     environment: Environment,
+    #[serde(default)]
+    preserve_comments: CommentsMode,
+    #[serde(default)]
+    externals: Option<ExternalsOptions>,
+    #[serde(default)]
+    post_processor: Option<PostProcessor>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ExternalsOptions {
+    #[serde(default)]
+    prefixes: Vec<String>,
+    #[serde(default)]
+    node_builtins: bool,
+}
+
+#[derive(Debug, Deserialize)]
+enum PostProcessor {
+    /// Panics like a broken transform plugin.
+    Panic,
+}
+
+#[derive(Debug)]
+struct PanickingPostProcessor;
+
+impl EmittedCodePostProcessor for PanickingPostProcessor {
+    fn process(&self, _code: Vec<u8>) -> Result<PostProcessedCode> {
+        panic!("the plugin is broken")
+    }
 }
 
 #[derive(Debug, Deserialize, Default)]
@@ -108,6 +142,9 @@ impl Default for SnapshotOptions {
             runtime: Default::default(),
             runtime_type: default_runtime_type(),
             environment: Default::default(),
+            preserve_comments: Default::default(),
+            externals: None,
+            post_processor: None,
         }
     }
 }
@@ -255,6 +292,7 @@ async fn run_test(resource: &str) -> Result<FileSystemPathVc> {
                 development: true,
                 ..Default::default()
             })),
+            enable_typescript_transform: Some(TypescriptTransformOptionsVc::default()),
             preset_env_versions: Some(env),
             rules: vec![(
                 ContextCondition::InDirectory("node_modules".to_string()),
@@ -264,6 +302,12 @@ async fn run_test(resource: &str) -> Result<FileSystemPathVc> {
                 .cell(),
             )],
             custom_ecma_transform_plugins,
+            preserve_comments: options.preserve_comments,
+            ecmascript_post_processor: options.post_processor.map(|post_processor| {
+                match post_processor {
+                    PostProcessor::Panic => CodePostProcessorVc::new(PanickingPostProcessor),
+                }
+            }),
             ..Default::default()
         }
         .into(),
@@ -272,6 +316,13 @@ async fn run_test(resource: &str) -> Result<FileSystemPathVc> {
             enable_react: true,
             enable_node_modules: Some(project_root),
             custom_conditions: vec!["development".to_string()],
+            externals: options.externals.map(|externals| {
+                ExternalsConfig {
+                    prefixes: externals.prefixes,
+                    node_builtins: externals.node_builtins,
+                }
+                .cell()
+            }),
             rules: vec![(
                 ContextCondition::InDirectory("node_modules".to_string()),
                 ResolveOptionsContext {
@@ -0,0 +1,12 @@
+(globalThis.TURBOPACK = globalThis.TURBOPACK || []).push(["output/crates_turbopack-tests_tests_snapshot_basic_codegen_panic_input_index_b53fce.js", {
+
+"[project]/crates/turbopack-tests/tests/snapshot/basic/codegen_panic/input/index.js (ecmascript)": (function({ r: __turbopack_require__, f: __turbopack_require_context__, i: __turbopack_import__, s: __turbopack_esm__, v: __turbopack_export_value__, n: __turbopack_export_namespace__, c: __turbopack_cache__, l: __turbopack_load__, j: __turbopack_dynamic__, g: global, __dirname, k: __turbopack_refresh__, m: module, e: exports }) { !function() {
+
+// Code generation panicked: the plugin is broken
+// Original source:
+// console.log("hello");
+const e = new Error("Could not generate code for module '[project]/crates/turbopack-tests/tests/snapshot/basic/codegen_panic/input/index.js'");
+e.code = 'MODULE_CODEGEN_FAILED';
+throw e;
+}.call(this) }),
+}]);
\ No newline at end of file
@@ -0,0 +1,6 @@
+(globalThis.TURBOPACK = globalThis.TURBOPACK || []).push([
+    "output/crates_turbopack-tests_tests_snapshot_basic_codegen_panic_input_index_d475fd.js",
+    {},
+    {"otherChunks":[{"path":"output/crates_turbopack-tests_tests_snapshot_basic_codegen_panic_input_index_b53fce.js","included":["[project]/crates/turbopack-tests/tests/snapshot/basic/codegen_panic/input/index.js (ecmascript)"]}],"runtimeModuleIds":["[project]/crates/turbopack-tests/tests/snapshot/basic/codegen_panic/input/index.js (ecmascript)"]}
+]);
+// Dummy runtime
\ No newline at end of file
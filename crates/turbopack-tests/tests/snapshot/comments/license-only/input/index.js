@@ -0,0 +1,9 @@
+/*!
+ * banner v1.0.0
+ * Released under the MIT License.
+ */
+
+import { add } from "./lib.js";
+
+// this comment is removed
+console.log(add(1, 2));
@@ -0,0 +1,10 @@
+/**
+ * @license lib
+ * Copyright (c) Example Authors
+ */
+
+/* regular block comment */
+export function add(a, b) {
+  /* @preserve keep this */
+  return a + b;
+}
@@ -0,0 +1,29 @@
+(globalThis.TURBOPACK = globalThis.TURBOPACK || []).push(["output/crates_turbopack-tests_tests_snapshot_comments_license-only_input_index_b53fce.js", {
+
+"[project]/crates/turbopack-tests/tests/snapshot/comments/license-only/input/lib.js (ecmascript)": (({ r: __turbopack_require__, f: __turbopack_require_context__, i: __turbopack_import__, s: __turbopack_esm__, v: __turbopack_export_value__, n: __turbopack_export_namespace__, c: __turbopack_cache__, l: __turbopack_load__, j: __turbopack_dynamic__, g: global, __dirname, k: __turbopack_refresh__ }) => (() => {
+
+/**
+ * @license lib
+ * Copyright (c) Example Authors
+ */ __turbopack_esm__({
+    "add": ()=>add
+});
+function add(a, b) {
+    /* @preserve keep this */ return a + b;
+}
+
+})()),
+"[project]/crates/turbopack-tests/tests/snapshot/comments/license-only/input/index.js (ecmascript)": (({ r: __turbopack_require__, f: __turbopack_require_context__, i: __turbopack_import__, s: __turbopack_esm__, v: __turbopack_export_value__, n: __turbopack_export_namespace__, c: __turbopack_cache__, l: __turbopack_load__, j: __turbopack_dynamic__, g: global, __dirname, k: __turbopack_refresh__ }) => (() => {
+
+/*!
+ * banner v1.0.0
+ * Released under the MIT License.
+ */ var __TURBOPACK__imported__module__$5b$project$5d2f$crates$2f$turbopack$2d$tests$2f$tests$2f$snapshot$2f$comments$2f$license$2d$only$2f$input$2f$lib$2e$js__$28$ecmascript$29$__ = __turbopack_import__("[project]/crates/turbopack-tests/tests/snapshot/comments/license-only/input/lib.js (ecmascript)");
+"__TURBOPACK__ecmascript__hoisting__location__";
+;
+console.log(__TURBOPACK__imported__module__$5b$project$5d2f$crates$2f$turbopack$2d$tests$2f$tests$2f$snapshot$2f$comments$2f$license$2d$only$2f$input$2f$lib$2e$js__$28$ecmascript$29$__["add"](1, 2));
+
+})()),
+}]);
+
+//# sourceMappingURL=crates_turbopack-tests_tests_snapshot_comments_license-only_input_index_b53fce.js.map
\ No newline at end of file
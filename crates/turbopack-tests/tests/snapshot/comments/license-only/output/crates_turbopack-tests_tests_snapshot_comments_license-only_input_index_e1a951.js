@@ -0,0 +1,6 @@
+(globalThis.TURBOPACK = globalThis.TURBOPACK || []).push([
+    "output/crates_turbopack-tests_tests_snapshot_comments_license-only_input_index_e1a951.js",
+    {},
+    {"otherChunks":[{"path":"output/crates_turbopack-tests_tests_snapshot_comments_license-only_input_index_b53fce.js","included":["[project]/crates/turbopack-tests/tests/snapshot/comments/license-only/input/index.js (ecmascript)"]}],"runtimeModuleIds":["[project]/crates/turbopack-tests/tests/snapshot/comments/license-only/input/index.js (ecmascript)"]}
+]);
+// Dummy runtime
\ No newline at end of file
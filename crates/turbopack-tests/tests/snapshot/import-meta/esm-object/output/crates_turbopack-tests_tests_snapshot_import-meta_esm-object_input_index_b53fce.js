@@ -3,7 +3,13 @@
 "[project]/crates/turbopack-tests/tests/snapshot/import-meta/esm-object/input/mod.mjs (ecmascript)": (function({ r: __turbopack_require__, f: __turbopack_require_context__, i: __turbopack_import__, s: __turbopack_esm__, v: __turbopack_export_value__, n: __turbopack_export_namespace__, c: __turbopack_cache__, l: __turbopack_load__, j: __turbopack_dynamic__, g: global, __dirname, k: __turbopack_refresh__, m: module, e: exports }) { !function() {
 
 const __TURBOPACK__import$2e$meta__ = {
-    url: "file:///ROOT/crates/turbopack-tests/tests/snapshot/import-meta/esm-object/input/mod.mjs"
+    url: "file:///ROOT/crates/turbopack-tests/tests/snapshot/import-meta/esm-object/input/mod.mjs",
+    get dirname () {
+        throw new Error('import.meta.dirname is not available in this environment');
+    },
+    get filename () {
+        throw new Error('import.meta.filename is not available in this environment');
+    }
 };
 "__TURBOPACK__ecmascript__hoisting__location__";
 console.log(__TURBOPACK__import$2e$meta__);
@@ -0,0 +1 @@
+import './mod.mjs';
@@ -0,0 +1,2 @@
+console.log(import.meta.dirname);
+console.log(import.meta.filename);
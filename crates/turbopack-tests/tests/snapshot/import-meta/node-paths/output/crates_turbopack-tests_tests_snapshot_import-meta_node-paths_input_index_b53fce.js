@@ -0,0 +1,24 @@
+(globalThis.TURBOPACK = globalThis.TURBOPACK || []).push(["output/crates_turbopack-tests_tests_snapshot_import-meta_node-paths_input_index_b53fce.js", {
+
+"[project]/crates/turbopack-tests/tests/snapshot/import-meta/node-paths/input/mod.mjs (ecmascript)": (function({ r: __turbopack_require__, f: __turbopack_require_context__, i: __turbopack_import__, s: __turbopack_esm__, v: __turbopack_export_value__, n: __turbopack_export_namespace__, c: __turbopack_cache__, l: __turbopack_load__, j: __turbopack_dynamic__, g: global, __dirname, x: __turbopack_external_require__, P: __turbopack_resolve_absolute_path__, k: __turbopack_refresh__, m: module, e: exports }) { !function() {
+
+const __TURBOPACK__import$2e$meta__ = {
+    url: "file:///ROOT/crates/turbopack-tests/tests/snapshot/import-meta/node-paths/input/mod.mjs",
+    dirname: "/ROOT/crates/turbopack-tests/tests/snapshot/import-meta/node-paths/input",
+    filename: "/ROOT/crates/turbopack-tests/tests/snapshot/import-meta/node-paths/input/mod.mjs"
+};
+"__TURBOPACK__ecmascript__hoisting__location__";
+console.log(__TURBOPACK__import$2e$meta__.dirname);
+console.log(__TURBOPACK__import$2e$meta__.filename);
+
+}.call(this) }),
+"[project]/crates/turbopack-tests/tests/snapshot/import-meta/node-paths/input/index.js (ecmascript)": (function({ r: __turbopack_require__, f: __turbopack_require_context__, i: __turbopack_import__, s: __turbopack_esm__, v: __turbopack_export_value__, n: __turbopack_export_namespace__, c: __turbopack_cache__, l: __turbopack_load__, j: __turbopack_dynamic__, g: global, __dirname, x: __turbopack_external_require__, P: __turbopack_resolve_absolute_path__, k: __turbopack_refresh__, m: module, e: exports }) { !function() {
+
+var __TURBOPACK__imported__module__$5b$project$5d2f$crates$2f$turbopack$2d$tests$2f$tests$2f$snapshot$2f$import$2d$meta$2f$node$2d$paths$2f$input$2f$mod$2e$mjs__$28$ecmascript$29$__ = __turbopack_import__("[project]/crates/turbopack-tests/tests/snapshot/import-meta/node-paths/input/mod.mjs (ecmascript)");
+"__TURBOPACK__ecmascript__hoisting__location__";
+;
+
+}.call(this) }),
+}]);
+
+//# sourceMappingURL=crates_turbopack-tests_tests_snapshot_import-meta_node-paths_input_index_b53fce.js.map
\ No newline at end of file
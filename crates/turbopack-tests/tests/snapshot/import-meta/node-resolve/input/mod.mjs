@@ -0,0 +1,3 @@
+const assetUrl = import.meta.resolve('./asset.txt');
+
+console.log(assetUrl);
@@ -0,0 +1,28 @@
+(globalThis.TURBOPACK = globalThis.TURBOPACK || []).push(["output/79fb1_turbopack-tests_tests_snapshot_import-meta_node-resolve_input_index_b53fce.js", {
+
+"[project]/crates/turbopack-tests/tests/snapshot/import-meta/node-resolve/input/asset.txt (static)": (({ r: __turbopack_require__, f: __turbopack_require_context__, i: __turbopack_import__, s: __turbopack_esm__, v: __turbopack_export_value__, n: __turbopack_export_namespace__, c: __turbopack_cache__, l: __turbopack_load__, j: __turbopack_dynamic__, g: global, __dirname, x: __turbopack_external_require__, P: __turbopack_resolve_absolute_path__ }) => (() => {
+
+__turbopack_export_value__("/crates/turbopack-tests/tests/snapshot/import-meta/node-resolve/static/asset.05254cf2.txt");
+})()),
+"[project]/crates/turbopack-tests/tests/snapshot/import-meta/node-resolve/input/mod.mjs (ecmascript)": (function({ r: __turbopack_require__, f: __turbopack_require_context__, i: __turbopack_import__, s: __turbopack_esm__, v: __turbopack_export_value__, n: __turbopack_export_namespace__, c: __turbopack_cache__, l: __turbopack_load__, j: __turbopack_dynamic__, g: global, __dirname, x: __turbopack_external_require__, P: __turbopack_resolve_absolute_path__, k: __turbopack_refresh__, m: module, e: exports }) { !function() {
+
+const __TURBOPACK__import$2e$meta__ = {
+    url: "file:///ROOT/crates/turbopack-tests/tests/snapshot/import-meta/node-resolve/input/mod.mjs",
+    dirname: "/ROOT/crates/turbopack-tests/tests/snapshot/import-meta/node-resolve/input",
+    filename: "/ROOT/crates/turbopack-tests/tests/snapshot/import-meta/node-resolve/input/mod.mjs"
+};
+"__TURBOPACK__ecmascript__hoisting__location__";
+const assetUrl = __turbopack_external_require__("url").pathToFileURL(__turbopack_resolve_absolute_path__("../../../../../.." + __turbopack_require__("[project]/crates/turbopack-tests/tests/snapshot/import-meta/node-resolve/input/asset.txt (static)"))).href;
+console.log(assetUrl);
+
+}.call(this) }),
+"[project]/crates/turbopack-tests/tests/snapshot/import-meta/node-resolve/input/index.js (ecmascript)": (function({ r: __turbopack_require__, f: __turbopack_require_context__, i: __turbopack_import__, s: __turbopack_esm__, v: __turbopack_export_value__, n: __turbopack_export_namespace__, c: __turbopack_cache__, l: __turbopack_load__, j: __turbopack_dynamic__, g: global, __dirname, x: __turbopack_external_require__, P: __turbopack_resolve_absolute_path__, k: __turbopack_refresh__, m: module, e: exports }) { !function() {
+
+var __TURBOPACK__imported__module__$5b$project$5d2f$crates$2f$turbopack$2d$tests$2f$tests$2f$snapshot$2f$import$2d$meta$2f$node$2d$resolve$2f$input$2f$mod$2e$mjs__$28$ecmascript$29$__ = __turbopack_import__("[project]/crates/turbopack-tests/tests/snapshot/import-meta/node-resolve/input/mod.mjs (ecmascript)");
+"__TURBOPACK__ecmascript__hoisting__location__";
+;
+
+}.call(this) }),
+}]);
+
+//# sourceMappingURL=79fb1_turbopack-tests_tests_snapshot_import-meta_node-resolve_input_index_b53fce.js.map
\ No newline at end of file
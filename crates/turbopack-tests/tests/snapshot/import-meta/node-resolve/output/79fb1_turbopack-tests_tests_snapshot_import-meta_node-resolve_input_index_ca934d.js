@@ -0,0 +1,6 @@
+(globalThis.TURBOPACK = globalThis.TURBOPACK || []).push([
+    "output/79fb1_turbopack-tests_tests_snapshot_import-meta_node-resolve_input_index_ca934d.js",
+    {},
+    {"otherChunks":[{"path":"output/79fb1_turbopack-tests_tests_snapshot_import-meta_node-resolve_input_index_b53fce.js","included":["[project]/crates/turbopack-tests/tests/snapshot/import-meta/node-resolve/input/index.js (ecmascript)"]}],"runtimeModuleIds":["[project]/crates/turbopack-tests/tests/snapshot/import-meta/node-resolve/input/index.js (ecmascript)"]}
+]);
+// Dummy runtime
\ No newline at end of file
@@ -0,0 +1,6 @@
+const assetUrl = import.meta.resolve('./asset.txt');
+
+console.log(assetUrl);
+fetch(assetUrl)
+  .then(res => res.text())
+  .then(console.log);
@@ -0,0 +1,7 @@
+const name = globalThis.assetName;
+const dynamicUrl = new URL(name, import.meta.url);
+const assetUrl = new URL("./asset.txt", import.meta.url);
+
+console.log(dynamicUrl, assetUrl);
+
+export { assetUrl };
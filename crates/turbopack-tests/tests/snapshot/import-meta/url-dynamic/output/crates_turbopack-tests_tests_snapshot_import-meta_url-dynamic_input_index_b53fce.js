@@ -0,0 +1,31 @@
+(globalThis.TURBOPACK = globalThis.TURBOPACK || []).push(["output/crates_turbopack-tests_tests_snapshot_import-meta_url-dynamic_input_index_b53fce.js", {
+
+"[project]/crates/turbopack-tests/tests/snapshot/import-meta/url-dynamic/input/asset.txt (static)": (({ r: __turbopack_require__, f: __turbopack_require_context__, i: __turbopack_import__, s: __turbopack_esm__, v: __turbopack_export_value__, n: __turbopack_export_namespace__, c: __turbopack_cache__, l: __turbopack_load__, j: __turbopack_dynamic__, g: global, __dirname }) => (() => {
+
+__turbopack_export_value__("/crates/turbopack-tests/tests/snapshot/import-meta/url-dynamic/static/asset.05254cf2.txt");
+})()),
+"[project]/crates/turbopack-tests/tests/snapshot/import-meta/url-dynamic/input/index.js (ecmascript)": (({ r: __turbopack_require__, f: __turbopack_require_context__, i: __turbopack_import__, s: __turbopack_esm__, v: __turbopack_export_value__, n: __turbopack_export_namespace__, c: __turbopack_cache__, l: __turbopack_load__, j: __turbopack_dynamic__, g: global, __dirname, k: __turbopack_refresh__ }) => (() => {
+
+const __TURBOPACK__import$2e$meta__ = {
+    url: "file:///ROOT/crates/turbopack-tests/tests/snapshot/import-meta/url-dynamic/input/index.js",
+    get dirname () {
+        throw new Error('import.meta.dirname is not available in this environment');
+    },
+    get filename () {
+        throw new Error('import.meta.filename is not available in this environment');
+    }
+};
+"__TURBOPACK__ecmascript__hoisting__location__";
+__turbopack_esm__({
+    "assetUrl": ()=>assetUrl
+});
+const name = globalThis.assetName;
+const dynamicUrl = new URL(name, __TURBOPACK__import$2e$meta__.url);
+const assetUrl = new URL(__turbopack_require__("[project]/crates/turbopack-tests/tests/snapshot/import-meta/url-dynamic/input/asset.txt (static)"), location.origin);
+console.log(dynamicUrl, assetUrl);
+;
+
+})()),
+}]);
+
+//# sourceMappingURL=crates_turbopack-tests_tests_snapshot_import-meta_url-dynamic_input_index_b53fce.js.map
\ No newline at end of file
@@ -0,0 +1,5 @@
+import value from "data:text/javascript,export default 'url-encoded'";
+import base64 from "data:text/javascript;base64,ZXhwb3J0IGRlZmF1bHQgImJhc2U2NCI7";
+import json from "data:application/json,{\"name\":\"data-url\"}";
+
+console.log(value, base64, json.name);
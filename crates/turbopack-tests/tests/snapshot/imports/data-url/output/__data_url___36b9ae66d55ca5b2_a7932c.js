@@ -0,0 +1,13 @@
+(globalThis.TURBOPACK = globalThis.TURBOPACK || []).push(["output/__data_url___36b9ae66d55ca5b2_a7932c.js", {
+
+"[project]/__data_url__/36b9ae66d55ca5b2.js (ecmascript)": (({ r: __turbopack_require__, f: __turbopack_require_context__, i: __turbopack_import__, s: __turbopack_esm__, v: __turbopack_export_value__, n: __turbopack_export_namespace__, c: __turbopack_cache__, l: __turbopack_load__, j: __turbopack_dynamic__, g: global, __dirname, k: __turbopack_refresh__ }) => (() => {
+
+__turbopack_esm__({
+    "default": ()=>__TURBOPACK__default__export__
+});
+const __TURBOPACK__default__export__ = 'url-encoded';
+
+})()),
+}]);
+
+//# sourceMappingURL=__data_url___36b9ae66d55ca5b2_a7932c.js.map
\ No newline at end of file
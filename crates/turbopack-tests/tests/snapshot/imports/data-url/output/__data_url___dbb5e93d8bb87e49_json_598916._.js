@@ -0,0 +1,7 @@
+(globalThis.TURBOPACK = globalThis.TURBOPACK || []).push(["output/__data_url___dbb5e93d8bb87e49_json_598916._.js", {
+
+"[project]/__data_url__/dbb5e93d8bb87e49.json (json)": (({ r: __turbopack_require__, f: __turbopack_require_context__, i: __turbopack_import__, s: __turbopack_esm__, v: __turbopack_export_value__, n: __turbopack_export_namespace__, c: __turbopack_cache__, l: __turbopack_load__, j: __turbopack_dynamic__, g: global, __dirname }) => (() => {
+
+__turbopack_export_value__(JSON.parse("{\"name\":\"data-url\"}"));
+})()),
+}]);
\ No newline at end of file
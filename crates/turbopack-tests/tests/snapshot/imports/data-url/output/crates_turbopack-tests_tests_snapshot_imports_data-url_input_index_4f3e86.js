@@ -0,0 +1,6 @@
+(globalThis.TURBOPACK = globalThis.TURBOPACK || []).push([
+    "output/crates_turbopack-tests_tests_snapshot_imports_data-url_input_index_4f3e86.js",
+    {},
+    {"otherChunks":[{"path":"output/__data_url___36b9ae66d55ca5b2_a7932c.js","included":["[project]/__data_url__/36b9ae66d55ca5b2.js (ecmascript)"]},{"path":"output/__data_url___aab379042a2c4997_ac3b21.js","included":["[project]/__data_url__/aab379042a2c4997.js (ecmascript)"]},{"path":"output/__data_url___dbb5e93d8bb87e49_json_598916._.js","included":["[project]/__data_url__/dbb5e93d8bb87e49.json (json)"]},{"path":"output/crates_turbopack-tests_tests_snapshot_imports_data-url_input_index_b53fce.js","included":["[project]/crates/turbopack-tests/tests/snapshot/imports/data-url/input/index.js (ecmascript)"]}],"runtimeModuleIds":["[project]/crates/turbopack-tests/tests/snapshot/imports/data-url/input/index.js (ecmascript)"]}
+]);
+// Dummy runtime
\ No newline at end of file
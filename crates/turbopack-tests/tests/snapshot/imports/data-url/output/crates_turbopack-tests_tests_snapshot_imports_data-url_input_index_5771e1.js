@@ -0,0 +1,14 @@
+(globalThis.TURBOPACK = globalThis.TURBOPACK || []).push([
+    "output/crates_turbopack-tests_tests_snapshot_imports_data-url_input_index_5771e1.js",
+    {},
+]);
+(globalThis.TURBOPACK_CHUNK_LISTS = globalThis.TURBOPACK_CHUNK_LISTS || []).push({
+  "path": "output/crates_turbopack-tests_tests_snapshot_imports_data-url_input_index_5771e1.js",
+  "chunks": [
+    "output/__data_url___36b9ae66d55ca5b2_a7932c.js",
+    "output/__data_url___aab379042a2c4997_ac3b21.js",
+    "output/__data_url___dbb5e93d8bb87e49_json_598916._.js",
+    "output/crates_turbopack-tests_tests_snapshot_imports_data-url_input_index_b53fce.js"
+  ],
+  "source": "entry"
+});
\ No newline at end of file
@@ -0,0 +1,17 @@
+(globalThis.TURBOPACK = globalThis.TURBOPACK || []).push(["output/crates_turbopack-tests_tests_snapshot_imports_data-url_input_index_b53fce.js", {
+
+"[project]/crates/turbopack-tests/tests/snapshot/imports/data-url/input/index.js (ecmascript)": (({ r: __turbopack_require__, f: __turbopack_require_context__, i: __turbopack_import__, s: __turbopack_esm__, v: __turbopack_export_value__, n: __turbopack_export_namespace__, c: __turbopack_cache__, l: __turbopack_load__, j: __turbopack_dynamic__, g: global, __dirname, k: __turbopack_refresh__ }) => (() => {
+
+var __TURBOPACK__imported__module__$5b$project$5d2f$_$5f$data_url_$5f2f$36b9ae66d55ca5b2$2e$js__$28$ecmascript$29$__ = __turbopack_import__("[project]/__data_url__/36b9ae66d55ca5b2.js (ecmascript)");
+var __TURBOPACK__imported__module__$5b$project$5d2f$_$5f$data_url_$5f2f$aab379042a2c4997$2e$js__$28$ecmascript$29$__ = __turbopack_import__("[project]/__data_url__/aab379042a2c4997.js (ecmascript)");
+var __TURBOPACK__imported__module__$5b$project$5d2f$_$5f$data_url_$5f2f$dbb5e93d8bb87e49$2e$json__$28$json$29$__ = __turbopack_import__("[project]/__data_url__/dbb5e93d8bb87e49.json (json)");
+"__TURBOPACK__ecmascript__hoisting__location__";
+;
+;
+;
+console.log(__TURBOPACK__imported__module__$5b$project$5d2f$_$5f$data_url_$5f2f$36b9ae66d55ca5b2$2e$js__$28$ecmascript$29$__["default"], __TURBOPACK__imported__module__$5b$project$5d2f$_$5f$data_url_$5f2f$aab379042a2c4997$2e$js__$28$ecmascript$29$__["default"], __TURBOPACK__imported__module__$5b$project$5d2f$_$5f$data_url_$5f2f$dbb5e93d8bb87e49$2e$json__$28$json$29$__["default"].name);
+
+})()),
+}]);
+
+//# sourceMappingURL=crates_turbopack-tests_tests_snapshot_imports_data-url_input_index_b53fce.js.map
\ No newline at end of file
@@ -0,0 +1,6 @@
+import(/* webpackChunkName: "Settings Page" */ "./settings.js").then(console.log);
+// Chunks with the same name don't collide.
+import(/* turbopackChunkName: "shared" */ "./a.js").then(console.log);
+import(/* turbopackChunkName: 'shared' */ "./b.js").then(console.log);
+// Invalid names are ignored.
+import(/* webpackChunkName: "@@@" */ "./invalid.js").then(console.log);
@@ -0,0 +1 @@
+export const invalid = "invalid";
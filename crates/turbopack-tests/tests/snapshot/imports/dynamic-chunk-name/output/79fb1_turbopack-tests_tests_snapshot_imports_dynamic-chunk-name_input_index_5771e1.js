@@ -0,0 +1,11 @@
+(globalThis.TURBOPACK = globalThis.TURBOPACK || []).push([
+    "output/79fb1_turbopack-tests_tests_snapshot_imports_dynamic-chunk-name_input_index_5771e1.js",
+    {},
+]);
+(globalThis.TURBOPACK_CHUNK_LISTS = globalThis.TURBOPACK_CHUNK_LISTS || []).push({
+  "path": "output/79fb1_turbopack-tests_tests_snapshot_imports_dynamic-chunk-name_input_index_5771e1.js",
+  "chunks": [
+    "output/79fb1_turbopack-tests_tests_snapshot_imports_dynamic-chunk-name_input_index_b53fce.js"
+  ],
+  "source": "entry"
+});
\ No newline at end of file
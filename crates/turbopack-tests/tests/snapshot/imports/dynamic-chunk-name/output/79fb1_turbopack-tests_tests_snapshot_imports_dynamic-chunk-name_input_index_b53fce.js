@@ -0,0 +1,65 @@
+(globalThis.TURBOPACK = globalThis.TURBOPACK || []).push(["output/79fb1_turbopack-tests_tests_snapshot_imports_dynamic-chunk-name_input_index_b53fce.js", {
+
+"[project]/crates/turbopack-tests/tests/snapshot/imports/dynamic-chunk-name/input/invalid.js (ecmascript, manifest chunk, loader)": (({ r: __turbopack_require__, f: __turbopack_require_context__, i: __turbopack_import__, s: __turbopack_esm__, v: __turbopack_export_value__, n: __turbopack_export_namespace__, c: __turbopack_cache__, l: __turbopack_load__, j: __turbopack_dynamic__, g: global, __dirname }) => (() => {
+
+__turbopack_export_value__((__turbopack_import__) => {
+    return Promise.all([{"path":"output/79fb1_turbopack-tests_tests_snapshot_imports_dynamic-chunk-name_input_invalid_c0a7a5.js","included":["[project]/crates/turbopack-tests/tests/snapshot/imports/dynamic-chunk-name/input/invalid.js (ecmascript, manifest chunk)"]},"output/79fb1_turbopack-tests_tests_snapshot_imports_dynamic-chunk-name_input_invalid_5c3f0f.js"].map((chunk) => __turbopack_load__(chunk))).then(() => {
+        return __turbopack_require__("[project]/crates/turbopack-tests/tests/snapshot/imports/dynamic-chunk-name/input/invalid.js (ecmascript, manifest chunk)");
+    }).then((chunks) => {
+        return Promise.all(chunks.map((chunk) => __turbopack_load__(chunk)));
+    }).then(() => {
+        return __turbopack_import__("[project]/crates/turbopack-tests/tests/snapshot/imports/dynamic-chunk-name/input/invalid.js (ecmascript)");
+    });
+});
+
+})()),
+"[project]/crates/turbopack-tests/tests/snapshot/imports/dynamic-chunk-name/input/b.js (ecmascript, manifest chunk, chunk name shared, loader)": (({ r: __turbopack_require__, f: __turbopack_require_context__, i: __turbopack_import__, s: __turbopack_esm__, v: __turbopack_export_value__, n: __turbopack_export_namespace__, c: __turbopack_cache__, l: __turbopack_load__, j: __turbopack_dynamic__, g: global, __dirname }) => (() => {
+
+__turbopack_export_value__((__turbopack_import__) => {
+    return Promise.all([{"path":"output/shared_374f2696.js","included":["[project]/crates/turbopack-tests/tests/snapshot/imports/dynamic-chunk-name/input/b.js (ecmascript, manifest chunk, chunk name shared)"]},"output/shared_73fd33fb.js"].map((chunk) => __turbopack_load__(chunk))).then(() => {
+        return __turbopack_require__("[project]/crates/turbopack-tests/tests/snapshot/imports/dynamic-chunk-name/input/b.js (ecmascript, manifest chunk, chunk name shared)");
+    }).then((chunks) => {
+        return Promise.all(chunks.map((chunk) => __turbopack_load__(chunk)));
+    }).then(() => {
+        return __turbopack_import__("[project]/crates/turbopack-tests/tests/snapshot/imports/dynamic-chunk-name/input/b.js (ecmascript)");
+    });
+});
+
+})()),
+"[project]/crates/turbopack-tests/tests/snapshot/imports/dynamic-chunk-name/input/a.js (ecmascript, manifest chunk, chunk name shared, loader)": (({ r: __turbopack_require__, f: __turbopack_require_context__, i: __turbopack_import__, s: __turbopack_esm__, v: __turbopack_export_value__, n: __turbopack_export_namespace__, c: __turbopack_cache__, l: __turbopack_load__, j: __turbopack_dynamic__, g: global, __dirname }) => (() => {
+
+__turbopack_export_value__((__turbopack_import__) => {
+    return Promise.all([{"path":"output/shared_3e2e148b.js","included":["[project]/crates/turbopack-tests/tests/snapshot/imports/dynamic-chunk-name/input/a.js (ecmascript, manifest chunk, chunk name shared)"]},"output/shared_2bc6575d.js"].map((chunk) => __turbopack_load__(chunk))).then(() => {
+        return __turbopack_require__("[project]/crates/turbopack-tests/tests/snapshot/imports/dynamic-chunk-name/input/a.js (ecmascript, manifest chunk, chunk name shared)");
+    }).then((chunks) => {
+        return Promise.all(chunks.map((chunk) => __turbopack_load__(chunk)));
+    }).then(() => {
+        return __turbopack_import__("[project]/crates/turbopack-tests/tests/snapshot/imports/dynamic-chunk-name/input/a.js (ecmascript)");
+    });
+});
+
+})()),
+"[project]/crates/turbopack-tests/tests/snapshot/imports/dynamic-chunk-name/input/settings.js (ecmascript, manifest chunk, chunk name settings-page, loader)": (({ r: __turbopack_require__, f: __turbopack_require_context__, i: __turbopack_import__, s: __turbopack_esm__, v: __turbopack_export_value__, n: __turbopack_export_namespace__, c: __turbopack_cache__, l: __turbopack_load__, j: __turbopack_dynamic__, g: global, __dirname }) => (() => {
+
+__turbopack_export_value__((__turbopack_import__) => {
+    return Promise.all([{"path":"output/settings-page_9481c3fb.js","included":["[project]/crates/turbopack-tests/tests/snapshot/imports/dynamic-chunk-name/input/settings.js (ecmascript, manifest chunk, chunk name settings-page)"]},"output/settings-page_77b264f7.js"].map((chunk) => __turbopack_load__(chunk))).then(() => {
+        return __turbopack_require__("[project]/crates/turbopack-tests/tests/snapshot/imports/dynamic-chunk-name/input/settings.js (ecmascript, manifest chunk, chunk name settings-page)");
+    }).then((chunks) => {
+        return Promise.all(chunks.map((chunk) => __turbopack_load__(chunk)));
+    }).then(() => {
+        return __turbopack_import__("[project]/crates/turbopack-tests/tests/snapshot/imports/dynamic-chunk-name/input/settings.js (ecmascript)");
+    });
+});
+
+})()),
+"[project]/crates/turbopack-tests/tests/snapshot/imports/dynamic-chunk-name/input/index.js (ecmascript)": (function({ r: __turbopack_require__, f: __turbopack_require_context__, i: __turbopack_import__, s: __turbopack_esm__, v: __turbopack_export_value__, n: __turbopack_export_namespace__, c: __turbopack_cache__, l: __turbopack_load__, j: __turbopack_dynamic__, g: global, __dirname, k: __turbopack_refresh__, m: module, e: exports }) { !function() {
+
+__turbopack_require__("[project]/crates/turbopack-tests/tests/snapshot/imports/dynamic-chunk-name/input/settings.js (ecmascript, manifest chunk, chunk name settings-page, loader)")(__turbopack_import__).then(console.log);
+__turbopack_require__("[project]/crates/turbopack-tests/tests/snapshot/imports/dynamic-chunk-name/input/a.js (ecmascript, manifest chunk, chunk name shared, loader)")(__turbopack_import__).then(console.log);
+__turbopack_require__("[project]/crates/turbopack-tests/tests/snapshot/imports/dynamic-chunk-name/input/b.js (ecmascript, manifest chunk, chunk name shared, loader)")(__turbopack_import__).then(console.log);
+__turbopack_require__("[project]/crates/turbopack-tests/tests/snapshot/imports/dynamic-chunk-name/input/invalid.js (ecmascript, manifest chunk, loader)")(__turbopack_import__).then(console.log);
+
+}.call(this) }),
+}]);
+
+//# sourceMappingURL=79fb1_turbopack-tests_tests_snapshot_imports_dynamic-chunk-name_input_index_b53fce.js.map
\ No newline at end of file
@@ -0,0 +1,11 @@
+(globalThis.TURBOPACK = globalThis.TURBOPACK || []).push([
+    "output/79fb1_turbopack-tests_tests_snapshot_imports_dynamic-chunk-name_input_invalid_5c3f0f.js",
+    {},
+]);
+(globalThis.TURBOPACK_CHUNK_LISTS = globalThis.TURBOPACK_CHUNK_LISTS || []).push({
+  "path": "output/79fb1_turbopack-tests_tests_snapshot_imports_dynamic-chunk-name_input_invalid_5c3f0f.js",
+  "chunks": [
+    "output/79fb1_turbopack-tests_tests_snapshot_imports_dynamic-chunk-name_input_invalid_c0a7a5.js"
+  ],
+  "source": "dynamic"
+});
\ No newline at end of file
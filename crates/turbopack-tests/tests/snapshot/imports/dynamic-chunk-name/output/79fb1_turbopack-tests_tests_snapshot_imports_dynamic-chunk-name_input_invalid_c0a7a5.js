@@ -0,0 +1,16 @@
+(globalThis.TURBOPACK = globalThis.TURBOPACK || []).push(["output/79fb1_turbopack-tests_tests_snapshot_imports_dynamic-chunk-name_input_invalid_c0a7a5.js", {
+
+"[project]/crates/turbopack-tests/tests/snapshot/imports/dynamic-chunk-name/input/invalid.js (ecmascript, manifest chunk)": (({ r: __turbopack_require__, f: __turbopack_require_context__, i: __turbopack_import__, s: __turbopack_esm__, v: __turbopack_export_value__, n: __turbopack_export_namespace__, c: __turbopack_cache__, l: __turbopack_load__, j: __turbopack_dynamic__, g: global, __dirname }) => (() => {
+
+__turbopack_export_value__([
+  {
+    "path": "output/79fb1_turbopack-tests_tests_snapshot_imports_dynamic-chunk-name_input_invalid_dc4064.js",
+    "included": [
+      "[project]/crates/turbopack-tests/tests/snapshot/imports/dynamic-chunk-name/input/invalid.js (ecmascript)"
+    ]
+  },
+  "output/79fb1_turbopack-tests_tests_snapshot_imports_dynamic-chunk-name_input_invalid_610323.js"
+]);
+
+})()),
+}]);
\ No newline at end of file
@@ -0,0 +1,11 @@
+(globalThis.TURBOPACK = globalThis.TURBOPACK || []).push([
+    "output/settings-page_4869d888.js",
+    {},
+]);
+(globalThis.TURBOPACK_CHUNK_LISTS = globalThis.TURBOPACK_CHUNK_LISTS || []).push({
+  "path": "output/settings-page_4869d888.js",
+  "chunks": [
+    "output/settings-page_fccaa762.js"
+  ],
+  "source": "dynamic"
+});
\ No newline at end of file
@@ -0,0 +1,11 @@
+(globalThis.TURBOPACK = globalThis.TURBOPACK || []).push([
+    "output/settings-page_77b264f7.js",
+    {},
+]);
+(globalThis.TURBOPACK_CHUNK_LISTS = globalThis.TURBOPACK_CHUNK_LISTS || []).push({
+  "path": "output/settings-page_77b264f7.js",
+  "chunks": [
+    "output/settings-page_9481c3fb.js"
+  ],
+  "source": "dynamic"
+});
\ No newline at end of file
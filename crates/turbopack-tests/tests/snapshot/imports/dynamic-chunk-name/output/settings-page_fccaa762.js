@@ -0,0 +1,13 @@
+(globalThis.TURBOPACK = globalThis.TURBOPACK || []).push(["output/settings-page_fccaa762.js", {
+
+"[project]/crates/turbopack-tests/tests/snapshot/imports/dynamic-chunk-name/input/settings.js (ecmascript)": (({ r: __turbopack_require__, f: __turbopack_require_context__, i: __turbopack_import__, s: __turbopack_esm__, v: __turbopack_export_value__, n: __turbopack_export_namespace__, c: __turbopack_cache__, l: __turbopack_load__, j: __turbopack_dynamic__, g: global, __dirname, k: __turbopack_refresh__ }) => (() => {
+
+__turbopack_esm__({
+    "settings": ()=>settings
+});
+const settings = "settings";
+
+})()),
+}]);
+
+//# sourceMappingURL=settings-page_fccaa762.js.map
\ No newline at end of file
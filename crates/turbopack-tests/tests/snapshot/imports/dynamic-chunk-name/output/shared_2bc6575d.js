@@ -0,0 +1,11 @@
+(globalThis.TURBOPACK = globalThis.TURBOPACK || []).push([
+    "output/shared_2bc6575d.js",
+    {},
+]);
+(globalThis.TURBOPACK_CHUNK_LISTS = globalThis.TURBOPACK_CHUNK_LISTS || []).push({
+  "path": "output/shared_2bc6575d.js",
+  "chunks": [
+    "output/shared_3e2e148b.js"
+  ],
+  "source": "dynamic"
+});
\ No newline at end of file
@@ -0,0 +1,16 @@
+(globalThis.TURBOPACK = globalThis.TURBOPACK || []).push(["output/shared_3e2e148b.js", {
+
+"[project]/crates/turbopack-tests/tests/snapshot/imports/dynamic-chunk-name/input/a.js (ecmascript, manifest chunk, chunk name shared)": (({ r: __turbopack_require__, f: __turbopack_require_context__, i: __turbopack_import__, s: __turbopack_esm__, v: __turbopack_export_value__, n: __turbopack_export_namespace__, c: __turbopack_cache__, l: __turbopack_load__, j: __turbopack_dynamic__, g: global, __dirname }) => (() => {
+
+__turbopack_export_value__([
+  {
+    "path": "output/shared_7c3536d8.js",
+    "included": [
+      "[project]/crates/turbopack-tests/tests/snapshot/imports/dynamic-chunk-name/input/a.js (ecmascript)"
+    ]
+  },
+  "output/shared_df0d2a16.js"
+]);
+
+})()),
+}]);
\ No newline at end of file
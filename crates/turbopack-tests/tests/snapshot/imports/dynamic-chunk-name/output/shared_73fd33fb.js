@@ -0,0 +1,11 @@
+(globalThis.TURBOPACK = globalThis.TURBOPACK || []).push([
+    "output/shared_73fd33fb.js",
+    {},
+]);
+(globalThis.TURBOPACK_CHUNK_LISTS = globalThis.TURBOPACK_CHUNK_LISTS || []).push({
+  "path": "output/shared_73fd33fb.js",
+  "chunks": [
+    "output/shared_374f2696.js"
+  ],
+  "source": "dynamic"
+});
\ No newline at end of file
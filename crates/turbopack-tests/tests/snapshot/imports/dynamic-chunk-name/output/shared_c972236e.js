@@ -0,0 +1,11 @@
+(globalThis.TURBOPACK = globalThis.TURBOPACK || []).push([
+    "output/shared_c972236e.js",
+    {},
+]);
+(globalThis.TURBOPACK_CHUNK_LISTS = globalThis.TURBOPACK_CHUNK_LISTS || []).push({
+  "path": "output/shared_c972236e.js",
+  "chunks": [
+    "output/shared_40b17db9.js"
+  ],
+  "source": "dynamic"
+});
\ No newline at end of file
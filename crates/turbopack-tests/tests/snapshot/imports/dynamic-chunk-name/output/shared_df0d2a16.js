@@ -0,0 +1,11 @@
+(globalThis.TURBOPACK = globalThis.TURBOPACK || []).push([
+    "output/shared_df0d2a16.js",
+    {},
+]);
+(globalThis.TURBOPACK_CHUNK_LISTS = globalThis.TURBOPACK_CHUNK_LISTS || []).push({
+  "path": "output/shared_df0d2a16.js",
+  "chunks": [
+    "output/shared_7c3536d8.js"
+  ],
+  "source": "dynamic"
+});
\ No newline at end of file
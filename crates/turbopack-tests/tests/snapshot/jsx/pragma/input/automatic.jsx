@@ -0,0 +1,3 @@
+export default function Automatic() {
+  return <div>automatic</div>;
+}
@@ -0,0 +1,13 @@
+/** @jsxRuntime classic */
+/** @jsx h */
+/** @jsxFrag Fragment */
+
+import { h, Fragment } from "preact";
+
+export default function Classic(): unknown {
+  return (
+    <>
+      <div>classic</div>
+    </>
+  );
+}
@@ -0,0 +1,5 @@
+/** @jsxImportSource preact */
+
+export default function ImportSource() {
+  return <div>import source</div>;
+}
@@ -0,0 +1,5 @@
+import Automatic from "./automatic.jsx";
+import ImportSource from "./import-source.jsx";
+import Classic from "./classic.tsx";
+
+console.log(Automatic, ImportSource, Classic);
@@ -0,0 +1,11 @@
+(globalThis.TURBOPACK = globalThis.TURBOPACK || []).push(["output/c9662_preact_jsx-dev-runtime_f1be1c.js", {
+
+"[project]/crates/turbopack-tests/tests/snapshot/node_modules/preact/jsx-dev-runtime.js (ecmascript)": (function({ r: __turbopack_require__, f: __turbopack_require_context__, i: __turbopack_import__, s: __turbopack_esm__, v: __turbopack_export_value__, n: __turbopack_export_namespace__, c: __turbopack_cache__, l: __turbopack_load__, j: __turbopack_dynamic__, g: global, __dirname, k: __turbopack_refresh__, m: module, e: exports }) { !function() {
+
+"purposefully empty stub";
+"preact/jsx-dev-runtime.js";
+
+}.call(this) }),
+}]);
+
+//# sourceMappingURL=c9662_preact_jsx-dev-runtime_f1be1c.js.map
\ No newline at end of file
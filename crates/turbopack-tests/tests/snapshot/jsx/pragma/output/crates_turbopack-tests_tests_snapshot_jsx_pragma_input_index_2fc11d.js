@@ -0,0 +1,6 @@
+(globalThis.TURBOPACK = globalThis.TURBOPACK || []).push([
+    "output/crates_turbopack-tests_tests_snapshot_jsx_pragma_input_index_2fc11d.js",
+    {},
+    {"otherChunks":[{"path":"output/c9662_react_jsx-dev-runtime_899933.js","included":["[project]/crates/turbopack-tests/tests/snapshot/node_modules/react/jsx-dev-runtime.js (ecmascript)"]},{"path":"output/c9662_preact_jsx-dev-runtime_f1be1c.js","included":["[project]/crates/turbopack-tests/tests/snapshot/node_modules/preact/jsx-dev-runtime.js (ecmascript)"]},{"path":"output/c9662_preact_index_e930b2.js","included":["[project]/crates/turbopack-tests/tests/snapshot/node_modules/preact/index.js (ecmascript)"]},{"path":"output/crates_turbopack-tests_tests_snapshot_jsx_pragma_input_index_b53fce.js","included":["[project]/crates/turbopack-tests/tests/snapshot/jsx/pragma/input/index.js (ecmascript)"]}],"runtimeModuleIds":["[project]/crates/turbopack-tests/tests/snapshot/jsx/pragma/input/index.js (ecmascript)"]}
+]);
+// Dummy runtime
\ No newline at end of file
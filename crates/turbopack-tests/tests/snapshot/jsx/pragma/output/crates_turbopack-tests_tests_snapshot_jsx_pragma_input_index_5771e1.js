@@ -0,0 +1,14 @@
+(globalThis.TURBOPACK = globalThis.TURBOPACK || []).push([
+    "output/crates_turbopack-tests_tests_snapshot_jsx_pragma_input_index_5771e1.js",
+    {},
+]);
+(globalThis.TURBOPACK_CHUNK_LISTS = globalThis.TURBOPACK_CHUNK_LISTS || []).push({
+  "path": "output/crates_turbopack-tests_tests_snapshot_jsx_pragma_input_index_5771e1.js",
+  "chunks": [
+    "output/c9662_react_jsx-dev-runtime_899933.js",
+    "output/c9662_preact_jsx-dev-runtime_f1be1c.js",
+    "output/c9662_preact_index_e930b2.js",
+    "output/crates_turbopack-tests_tests_snapshot_jsx_pragma_input_index_b53fce.js"
+  ],
+  "source": "entry"
+});
\ No newline at end of file
@@ -0,0 +1,75 @@
+(globalThis.TURBOPACK = globalThis.TURBOPACK || []).push(["output/crates_turbopack-tests_tests_snapshot_jsx_pragma_input_index_b53fce.js", {
+
+"[project]/crates/turbopack-tests/tests/snapshot/jsx/pragma/input/classic.tsx (ecmascript)": (({ r: __turbopack_require__, f: __turbopack_require_context__, i: __turbopack_import__, s: __turbopack_esm__, v: __turbopack_export_value__, n: __turbopack_export_namespace__, c: __turbopack_cache__, l: __turbopack_load__, j: __turbopack_dynamic__, g: global, __dirname, k: __turbopack_refresh__ }) => (() => {
+
+__turbopack_esm__({
+    "default": ()=>Classic
+});
+var __TURBOPACK__imported__module__$5b$project$5d2f$crates$2f$turbopack$2d$tests$2f$tests$2f$snapshot$2f$node_modules$2f$preact$2f$index$2e$js__$28$ecmascript$29$__ = __turbopack_import__("[project]/crates/turbopack-tests/tests/snapshot/node_modules/preact/index.js (ecmascript)");
+"__TURBOPACK__ecmascript__hoisting__location__";
+;
+function Classic() {
+    return __TURBOPACK__imported__module__$5b$project$5d2f$crates$2f$turbopack$2d$tests$2f$tests$2f$snapshot$2f$node_modules$2f$preact$2f$index$2e$js__$28$ecmascript$29$__["h"](__TURBOPACK__imported__module__$5b$project$5d2f$crates$2f$turbopack$2d$tests$2f$tests$2f$snapshot$2f$node_modules$2f$preact$2f$index$2e$js__$28$ecmascript$29$__["Fragment"], null, __TURBOPACK__imported__module__$5b$project$5d2f$crates$2f$turbopack$2d$tests$2f$tests$2f$snapshot$2f$node_modules$2f$preact$2f$index$2e$js__$28$ecmascript$29$__["h"]("div", {
+        __source: {
+            fileName: "<[project]/crates/turbopack-tests/tests/snapshot/jsx/pragma/input/classic.tsx>",
+            lineNumber: 10,
+            columnNumber: 7
+        },
+        __self: this
+    }, "classic"));
+}
+
+})()),
+"[project]/crates/turbopack-tests/tests/snapshot/jsx/pragma/input/import-source.jsx (ecmascript)": (({ r: __turbopack_require__, f: __turbopack_require_context__, i: __turbopack_import__, s: __turbopack_esm__, v: __turbopack_export_value__, n: __turbopack_export_namespace__, c: __turbopack_cache__, l: __turbopack_load__, j: __turbopack_dynamic__, g: global, __dirname, k: __turbopack_refresh__ }) => (() => {
+
+__turbopack_esm__({
+    "default": ()=>ImportSource
+});
+var __TURBOPACK__imported__module__$5b$project$5d2f$crates$2f$turbopack$2d$tests$2f$tests$2f$snapshot$2f$node_modules$2f$preact$2f$jsx$2d$dev$2d$runtime$2e$js__$28$ecmascript$29$__ = __turbopack_import__("[project]/crates/turbopack-tests/tests/snapshot/node_modules/preact/jsx-dev-runtime.js (ecmascript)");
+"__TURBOPACK__ecmascript__hoisting__location__";
+;
+function ImportSource() {
+    return __TURBOPACK__imported__module__$5b$project$5d2f$crates$2f$turbopack$2d$tests$2f$tests$2f$snapshot$2f$node_modules$2f$preact$2f$jsx$2d$dev$2d$runtime$2e$js__$28$ecmascript$29$__["jsxDEV"]("div", {
+        children: "import source"
+    }, void 0, false, {
+        fileName: "<[project]/crates/turbopack-tests/tests/snapshot/jsx/pragma/input/import-source.jsx>",
+        lineNumber: 4,
+        columnNumber: 10
+    }, this);
+}
+
+})()),
+"[project]/crates/turbopack-tests/tests/snapshot/jsx/pragma/input/automatic.jsx (ecmascript)": (({ r: __turbopack_require__, f: __turbopack_require_context__, i: __turbopack_import__, s: __turbopack_esm__, v: __turbopack_export_value__, n: __turbopack_export_namespace__, c: __turbopack_cache__, l: __turbopack_load__, j: __turbopack_dynamic__, g: global, __dirname, k: __turbopack_refresh__ }) => (() => {
+
+__turbopack_esm__({
+    "default": ()=>Automatic
+});
+var __TURBOPACK__imported__module__$5b$project$5d2f$crates$2f$turbopack$2d$tests$2f$tests$2f$snapshot$2f$node_modules$2f$react$2f$jsx$2d$dev$2d$runtime$2e$js__$28$ecmascript$29$__ = __turbopack_import__("[project]/crates/turbopack-tests/tests/snapshot/node_modules/react/jsx-dev-runtime.js (ecmascript)");
+"__TURBOPACK__ecmascript__hoisting__location__";
+;
+function Automatic() {
+    return __TURBOPACK__imported__module__$5b$project$5d2f$crates$2f$turbopack$2d$tests$2f$tests$2f$snapshot$2f$node_modules$2f$react$2f$jsx$2d$dev$2d$runtime$2e$js__$28$ecmascript$29$__["jsxDEV"]("div", {
+        children: "automatic"
+    }, void 0, false, {
+        fileName: "<[project]/crates/turbopack-tests/tests/snapshot/jsx/pragma/input/automatic.jsx>",
+        lineNumber: 2,
+        columnNumber: 10
+    }, this);
+}
+
+})()),
+"[project]/crates/turbopack-tests/tests/snapshot/jsx/pragma/input/index.js (ecmascript)": (({ r: __turbopack_require__, f: __turbopack_require_context__, i: __turbopack_import__, s: __turbopack_esm__, v: __turbopack_export_value__, n: __turbopack_export_namespace__, c: __turbopack_cache__, l: __turbopack_load__, j: __turbopack_dynamic__, g: global, __dirname, k: __turbopack_refresh__ }) => (() => {
+
+var __TURBOPACK__imported__module__$5b$project$5d2f$crates$2f$turbopack$2d$tests$2f$tests$2f$snapshot$2f$jsx$2f$pragma$2f$input$2f$automatic$2e$jsx__$28$ecmascript$29$__ = __turbopack_import__("[project]/crates/turbopack-tests/tests/snapshot/jsx/pragma/input/automatic.jsx (ecmascript)");
+var __TURBOPACK__imported__module__$5b$project$5d2f$crates$2f$turbopack$2d$tests$2f$tests$2f$snapshot$2f$jsx$2f$pragma$2f$input$2f$import$2d$source$2e$jsx__$28$ecmascript$29$__ = __turbopack_import__("[project]/crates/turbopack-tests/tests/snapshot/jsx/pragma/input/import-source.jsx (ecmascript)");
+var __TURBOPACK__imported__module__$5b$project$5d2f$crates$2f$turbopack$2d$tests$2f$tests$2f$snapshot$2f$jsx$2f$pragma$2f$input$2f$classic$2e$tsx__$28$ecmascript$29$__ = __turbopack_import__("[project]/crates/turbopack-tests/tests/snapshot/jsx/pragma/input/classic.tsx (ecmascript)");
+"__TURBOPACK__ecmascript__hoisting__location__";
+;
+;
+;
+console.log(__TURBOPACK__imported__module__$5b$project$5d2f$crates$2f$turbopack$2d$tests$2f$tests$2f$snapshot$2f$jsx$2f$pragma$2f$input$2f$automatic$2e$jsx__$28$ecmascript$29$__["default"], __TURBOPACK__imported__module__$5b$project$5d2f$crates$2f$turbopack$2d$tests$2f$tests$2f$snapshot$2f$jsx$2f$pragma$2f$input$2f$import$2d$source$2e$jsx__$28$ecmascript$29$__["default"], __TURBOPACK__imported__module__$5b$project$5d2f$crates$2f$turbopack$2d$tests$2f$tests$2f$snapshot$2f$jsx$2f$pragma$2f$input$2f$classic$2e$tsx__$28$ecmascript$29$__["default"]);
+
+})()),
+}]);
+
+//# sourceMappingURL=crates_turbopack-tests_tests_snapshot_jsx_pragma_input_index_b53fce.js.map
\ No newline at end of file
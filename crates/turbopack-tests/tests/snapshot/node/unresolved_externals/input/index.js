@@ -0,0 +1,6 @@
+import path from "node:path";
+import { S3 } from "@aws-sdk/client-s3";
+
+const fs = require("fs");
+
+console.log(path, S3, fs);
@@ -0,0 +1,6 @@
+(globalThis.TURBOPACK = globalThis.TURBOPACK || []).push([
+    "output/79fb1_turbopack-tests_tests_snapshot_node_unresolved_externals_input_index_186b14.js",
+    {},
+    {"otherChunks":[{"path":"output/79fb1_turbopack-tests_tests_snapshot_node_unresolved_externals_input_index_b53fce.js","included":["[project]/crates/turbopack-tests/tests/snapshot/node/unresolved_externals/input/index.js (ecmascript)"]}],"runtimeModuleIds":["[project]/crates/turbopack-tests/tests/snapshot/node/unresolved_externals/input/index.js (ecmascript)"]}
+]);
+// Dummy runtime
\ No newline at end of file
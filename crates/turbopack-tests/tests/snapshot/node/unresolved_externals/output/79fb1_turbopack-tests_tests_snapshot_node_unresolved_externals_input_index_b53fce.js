@@ -0,0 +1,16 @@
+(globalThis.TURBOPACK = globalThis.TURBOPACK || []).push(["output/79fb1_turbopack-tests_tests_snapshot_node_unresolved_externals_input_index_b53fce.js", {
+
+"[project]/crates/turbopack-tests/tests/snapshot/node/unresolved_externals/input/index.js (ecmascript)": (({ r: __turbopack_require__, f: __turbopack_require_context__, i: __turbopack_import__, s: __turbopack_esm__, v: __turbopack_export_value__, n: __turbopack_export_namespace__, c: __turbopack_cache__, l: __turbopack_load__, j: __turbopack_dynamic__, g: global, __dirname, x: __turbopack_external_require__, P: __turbopack_resolve_absolute_path__, k: __turbopack_refresh__ }) => (() => {
+
+var __TURBOPACK__external__node$3a$path__ = __turbopack_external_require__("node:path", true);
+var __TURBOPACK__external__$40$aws$2d$sdk$2f$client$2d$s3__ = __turbopack_external_require__("@aws-sdk/client-s3", true);
+"__TURBOPACK__ecmascript__hoisting__location__";
+;
+;
+const fs = __turbopack_external_require__("fs");
+console.log(__TURBOPACK__external__node$3a$path__["default"], __TURBOPACK__external__$40$aws$2d$sdk$2f$client$2d$s3__["S3"], fs);
+
+})()),
+}]);
+
+//# sourceMappingURL=79fb1_turbopack-tests_tests_snapshot_node_unresolved_externals_input_index_b53fce.js.map
\ No newline at end of file
@@ -1,6 +1,7 @@
 const RUNTIME_PUBLIC_PATH = "output/[turbopack]_runtime.js";
 ;
 const REEXPORTED_OBJECTS = Symbol("reexported objects");
+const ASYNC_MODULE_STATE = Symbol("async module state");
 ;
 ;
 ;
@@ -97,6 +98,46 @@ function commonJsRequire(sourceModule, id) {
     if (module.error) throw module.error;
     return module.exports;
 }
+;
+function asyncModule(module, body) {
+    const state = {
+        importing: true,
+        settled: false,
+        dependencies: []
+    };
+    module[ASYNC_MODULE_STATE] = state;
+    const promise = body((ids)=>waitForAsyncDependencies(module, ids));
+    state.importing = false;
+    module.async = promise.then(()=>{
+        state.settled = true;
+    }, (error)=>{
+        state.settled = true;
+        module.error = error;
+        throw error;
+    });
+}
+function waitForAsyncDependencies(module, ids) {
+    const state = module[ASYNC_MODULE_STATE];
+    const promises = [];
+    for (const id of ids){
+        const dependency = moduleCache[id];
+        const dependencyState = dependency?.[ASYNC_MODULE_STATE];
+        if (dependencyState == null || dependencyState.settled || dependencyState.importing || isWaitingFor(dependency, module, new Set())) {
+            continue;
+        }
+        state.dependencies.push(dependency);
+        promises.push(dependency.async);
+    }
+    return Promise.all(promises).then(()=>{});
+}
+function isWaitingFor(module, target, visited) {
+    if (module === target) return true;
+    if (visited.has(module)) return false;
+    visited.add(module);
+    const state = module[ASYNC_MODULE_STATE];
+    if (state == null || state.settled) return false;
+    return state.dependencies.some((dependency)=>isWaitingFor(dependency, target, visited));
+}
 function requireContext(sourceModule, map) {
     function requireContext(id) {
         const entry = map[id];
@@ -131,6 +172,12 @@ var SourceType;
 const path = require("path");
 const relativePathToRuntimeRoot = path.relative(RUNTIME_PUBLIC_PATH, ".");
 const RUNTIME_ROOT = path.resolve(__filename, relativePathToRuntimeRoot);
+function resolveAbsolutePath(modulePath) {
+    if (modulePath) {
+        return path.join(RUNTIME_ROOT, modulePath);
+    }
+    return RUNTIME_ROOT;
+}
 const moduleFactories = Object.create(null);
 const moduleCache = Object.create(null);
 function commonJsRequireContext(entry, sourceModule) {
@@ -217,12 +264,14 @@ function instantiateModule(id, source) {
             e: module1.exports,
             r: commonJsRequire.bind(null, module1),
             x: externalRequire,
+            P: resolveAbsolutePath,
             f: requireContext.bind(null, module1),
             i: esmImport.bind(null, module1),
             s: esm.bind(null, module1.exports),
             j: dynamicExport.bind(null, module1),
             v: exportValue.bind(null, module1),
             n: exportNamespace.bind(null, module1),
+            a: asyncModule.bind(null, module1),
             m: module1,
             c: moduleCache,
             l: loadChunkAsync.bind(null, {
@@ -11,6 +11,7 @@ if (!Array.isArray(globalThis.TURBOPACK)) {
 const CHUNK_BASE_PATH = "";
 ;
 const REEXPORTED_OBJECTS = Symbol("reexported objects");
+const ASYNC_MODULE_STATE = Symbol("async module state");
 ;
 ;
 ;
@@ -107,6 +108,46 @@ function commonJsRequire(sourceModule, id) {
     if (module.error) throw module.error;
     return module.exports;
 }
+;
+function asyncModule(module, body) {
+    const state = {
+        importing: true,
+        settled: false,
+        dependencies: []
+    };
+    module[ASYNC_MODULE_STATE] = state;
+    const promise = body((ids)=>waitForAsyncDependencies(module, ids));
+    state.importing = false;
+    module.async = promise.then(()=>{
+        state.settled = true;
+    }, (error)=>{
+        state.settled = true;
+        module.error = error;
+        throw error;
+    });
+}
+function waitForAsyncDependencies(module, ids) {
+    const state = module[ASYNC_MODULE_STATE];
+    const promises = [];
+    for (const id of ids){
+        const dependency = moduleCache[id];
+        const dependencyState = dependency?.[ASYNC_MODULE_STATE];
+        if (dependencyState == null || dependencyState.settled || dependencyState.importing || isWaitingFor(dependency, module, new Set())) {
+            continue;
+        }
+        state.dependencies.push(dependency);
+        promises.push(dependency.async);
+    }
+    return Promise.all(promises).then(()=>{});
+}
+function isWaitingFor(module, target, visited) {
+    if (module === target) return true;
+    if (visited.has(module)) return false;
+    visited.add(module);
+    const state = module[ASYNC_MODULE_STATE];
+    if (state == null || state.settled) return false;
+    return state.dependencies.some((dependency)=>isWaitingFor(dependency, target, visited));
+}
 function requireContext(sourceModule, map) {
     function requireContext(id) {
         const entry = map[id];
@@ -283,6 +324,7 @@ function instantiateModule(id, source) {
                 j: dynamicExport.bind(null, module),
                 v: exportValue.bind(null, module),
                 n: exportNamespace.bind(null, module),
+                a: asyncModule.bind(null, module),
                 m: module,
                 c: moduleCache,
                 l: loadChunk.bind(null, {
@@ -935,6 +977,7 @@ globalThis.TURBOPACK_CHUNK_LISTS = {
     }
 };
 let BACKEND;
+let loadScriptChunk;
 function augmentContext(context1) {
     return context1;
 }
@@ -1061,12 +1104,9 @@ function commonJsRequireContext(entry1, sourceModule1) {
             };
             document.body.appendChild(link1);
         } else if (chunkPath1.endsWith(".js")) {
-            const script1 = document.createElement("script");
-            script1.src = chunkUrl1;
-            script1.onerror = ()=>{
+            loadScriptChunk(chunkUrl1, ()=>{
                 resolver1.reject();
-            };
-            document.body.appendChild(script1);
+            });
         } else {
             throw new Error(`can't infer type of chunk from path ${chunkPath1}`);
         }
@@ -1078,6 +1118,12 @@ function _eval({ code, url, map }) {
     if (map) code += `\n//# sourceMappingURL=${map}`;
     return eval(code);
 }
+loadScriptChunk = (chunkUrl, onError)=>{
+    const script = document.createElement("script");
+    script.src = chunkUrl;
+    script.onerror = onError;
+    document.body.appendChild(script);
+};
 const chunksToRegister = globalThis.TURBOPACK;
 globalThis.TURBOPACK = { push: registerChunk };
 chunksToRegister.forEach(registerChunk);
@@ -0,0 +1,8 @@
+const worker = new Worker(new URL("./worker.js", import.meta.url));
+worker.postMessage("ping");
+
+const shared = new SharedWorker(new URL("./shared-worker.js", import.meta.url));
+shared.port.postMessage("ping");
+
+const name = Math.random() > 0.5 ? "worker" : "shared-worker";
+new Worker(new URL(`./${name}.js`, import.meta.url));
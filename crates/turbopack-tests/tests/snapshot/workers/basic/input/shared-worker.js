@@ -0,0 +1,7 @@
+self.addEventListener("connect", (event) => {
+  const port = event.ports[0];
+  port.addEventListener("message", (event) => {
+    port.postMessage(event.data);
+  });
+  port.start();
+});
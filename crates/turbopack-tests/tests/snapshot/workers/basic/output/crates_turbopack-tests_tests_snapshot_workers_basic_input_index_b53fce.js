@@ -0,0 +1,35 @@
+(globalThis.TURBOPACK = globalThis.TURBOPACK || []).push(["output/crates_turbopack-tests_tests_snapshot_workers_basic_input_index_b53fce.js", {
+
+"[project]/crates/turbopack-tests/tests/snapshot/workers/basic/input/shared-worker.js (ecmascript, worker)": (({ r: __turbopack_require__, f: __turbopack_require_context__, i: __turbopack_import__, s: __turbopack_esm__, v: __turbopack_export_value__, n: __turbopack_export_namespace__, c: __turbopack_cache__, l: __turbopack_load__, j: __turbopack_dynamic__, g: global, __dirname }) => (() => {
+
+__turbopack_export_value__("output/crates_turbopack-tests_tests_snapshot_workers_basic_input_shared-worker_76575a.js");
+
+})()),
+"[project]/crates/turbopack-tests/tests/snapshot/workers/basic/input/worker.js (ecmascript, worker)": (({ r: __turbopack_require__, f: __turbopack_require_context__, i: __turbopack_import__, s: __turbopack_esm__, v: __turbopack_export_value__, n: __turbopack_export_namespace__, c: __turbopack_cache__, l: __turbopack_load__, j: __turbopack_dynamic__, g: global, __dirname }) => (() => {
+
+__turbopack_export_value__("output/crates_turbopack-tests_tests_snapshot_workers_basic_input_worker_e14c08.js");
+
+})()),
+"[project]/crates/turbopack-tests/tests/snapshot/workers/basic/input/index.js (ecmascript)": (function({ r: __turbopack_require__, f: __turbopack_require_context__, i: __turbopack_import__, s: __turbopack_esm__, v: __turbopack_export_value__, n: __turbopack_export_namespace__, c: __turbopack_cache__, l: __turbopack_load__, j: __turbopack_dynamic__, g: global, __dirname, k: __turbopack_refresh__, m: module, e: exports }) { !function() {
+
+const __TURBOPACK__import$2e$meta__ = {
+    url: "file:///ROOT/crates/turbopack-tests/tests/snapshot/workers/basic/input/index.js",
+    get dirname () {
+        throw new Error('import.meta.dirname is not available in this environment');
+    },
+    get filename () {
+        throw new Error('import.meta.filename is not available in this environment');
+    }
+};
+"__TURBOPACK__ecmascript__hoisting__location__";
+const worker = new Worker(new URL(__turbopack_require__("[project]/crates/turbopack-tests/tests/snapshot/workers/basic/input/worker.js (ecmascript, worker)"), location.origin));
+worker.postMessage("ping");
+const shared = new SharedWorker(new URL(__turbopack_require__("[project]/crates/turbopack-tests/tests/snapshot/workers/basic/input/shared-worker.js (ecmascript, worker)"), location.origin));
+shared.port.postMessage("ping");
+const name = Math.random() > 0.5 ? "worker" : "shared-worker";
+new Worker(new URL(`./${name}.js`, __TURBOPACK__import$2e$meta__.url));
+
+}.call(this) }),
+}]);
+
+//# sourceMappingURL=crates_turbopack-tests_tests_snapshot_workers_basic_input_index_b53fce.js.map
\ No newline at end of file
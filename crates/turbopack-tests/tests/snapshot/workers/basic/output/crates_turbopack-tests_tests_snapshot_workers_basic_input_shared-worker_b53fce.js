@@ -0,0 +1,16 @@
+(globalThis.TURBOPACK = globalThis.TURBOPACK || []).push(["output/crates_turbopack-tests_tests_snapshot_workers_basic_input_shared-worker_b53fce.js", {
+
+"[project]/crates/turbopack-tests/tests/snapshot/workers/basic/input/shared-worker.js (ecmascript)": (function({ r: __turbopack_require__, f: __turbopack_require_context__, i: __turbopack_import__, s: __turbopack_esm__, v: __turbopack_export_value__, n: __turbopack_export_namespace__, c: __turbopack_cache__, l: __turbopack_load__, j: __turbopack_dynamic__, g: global, __dirname, k: __turbopack_refresh__, m: module, e: exports }) { !function() {
+
+self.addEventListener("connect", (event)=>{
+    const port = event.ports[0];
+    port.addEventListener("message", (event)=>{
+        port.postMessage(event.data);
+    });
+    port.start();
+});
+
+}.call(this) }),
+}]);
+
+//# sourceMappingURL=crates_turbopack-tests_tests_snapshot_workers_basic_input_shared-worker_b53fce.js.map
\ No newline at end of file
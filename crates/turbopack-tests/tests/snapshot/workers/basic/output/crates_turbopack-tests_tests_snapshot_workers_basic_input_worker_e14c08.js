@@ -0,0 +1,6 @@
+(globalThis.TURBOPACK = globalThis.TURBOPACK || []).push([
+    "output/crates_turbopack-tests_tests_snapshot_workers_basic_input_worker_e14c08.js",
+    {},
+    {"otherChunks":[{"path":"output/crates_turbopack-tests_tests_snapshot_workers_basic_input_worker_b53fce.js","included":["[project]/crates/turbopack-tests/tests/snapshot/workers/basic/input/worker.js (ecmascript)"]}],"runtimeModuleIds":["[project]/crates/turbopack-tests/tests/snapshot/workers/basic/input/worker.js (ecmascript)"]}
+]);
+// Dummy runtime
\ No newline at end of file
@@ -0,0 +1,233 @@
+use anyhow::{bail, Context, Result};
+use turbo_tasks_fs::FileContent;
+use turbopack_core::{
+    asset::{Asset, AssetContent},
+    source::SourceVc,
+};
+
+const MAGIC: &[u8] = b"\0asm";
+const VERSION: &[u8] = &[1, 0, 0, 0];
+
+const IMPORT_SECTION: u8 = 2;
+const EXPORT_SECTION: u8 = 7;
+
+/// The imports and exports of a WebAssembly module, as declared in its import
+/// and export sections.
+#[turbo_tasks::value]
+#[derive(Debug, Default)]
+pub struct WebAssemblyAnalysis {
+    /// The `(module, name)` pairs of the imports.
+    pub imports: Vec<(String, String)>,
+    /// The names of the exports.
+    pub exports: Vec<String>,
+}
+
+/// Parses the import and export sections of a WebAssembly module.
+#[turbo_tasks::function]
+pub async fn analyze(source: SourceVc) -> Result<WebAssemblyAnalysisVc> {
+    let content = source.content().await?;
+    let AssetContent::File(file) = &*content else {
+        bail!("WebAssembly module must be a file");
+    };
+    let FileContent::Content(file) = &*file.await? else {
+        bail!("WebAssembly module not found");
+    };
+    let analysis =
+        parse(&file.content().to_bytes()?).context("Unable to parse the WebAssembly module")?;
+    Ok(analysis.cell())
+}
+
+fn parse(bytes: &[u8]) -> Result<WebAssemblyAnalysis> {
+    let mut reader = Reader { bytes, pos: 0 };
+    if reader.bytes(MAGIC.len())? != MAGIC {
+        bail!("missing the WebAssembly magic number");
+    }
+    if reader.bytes(VERSION.len())? != VERSION {
+        bail!("unsupported WebAssembly binary version");
+    }
+
+    let mut analysis = WebAssemblyAnalysis::default();
+    while !reader.is_empty() {
+        let id = reader.byte()?;
+        let size = reader.u32()? as usize;
+        let mut section = Reader {
+            bytes: reader.bytes(size)?,
+            pos: 0,
+        };
+        match id {
+            IMPORT_SECTION => {
+                for _ in 0..section.u32()? {
+                    let module = section.name()?;
+                    let name = section.name()?;
+                    analysis.imports.push((module, name));
+                    section.import_desc()?;
+                }
+            }
+            EXPORT_SECTION => {
+                for _ in 0..section.u32()? {
+                    analysis.exports.push(section.name()?);
+                    // The kind and index of the export.
+                    section.byte()?;
+                    section.u32()?;
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(analysis)
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn is_empty(&self) -> bool {
+        self.pos >= self.bytes.len()
+    }
+
+    fn byte(&mut self) -> Result<u8> {
+        let byte = *self
+            .bytes
+            .get(self.pos)
+            .context("unexpected end of the WebAssembly module")?;
+        self.pos += 1;
+        Ok(byte)
+    }
+
+    fn bytes(&mut self, len: usize) -> Result<&'a [u8]> {
+        let bytes = self
+            .pos
+            .checked_add(len)
+            .and_then(|end| self.bytes.get(self.pos..end))
+            .context("unexpected end of the WebAssembly module")?;
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    /// Reads an unsigned LEB128 encoded integer.
+    fn u32(&mut self) -> Result<u32> {
+        let mut result = 0u32;
+        for shift in (0..32).step_by(7) {
+            let byte = self.byte()?;
+            result |= ((byte & 0x7f) as u32) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(result);
+            }
+        }
+        bail!("invalid LEB128 encoded integer in the WebAssembly module")
+    }
+
+    fn name(&mut self) -> Result<String> {
+        let len = self.u32()? as usize;
+        Ok(std::str::from_utf8(self.bytes(len)?)
+            .context("invalid name in the WebAssembly module")?
+            .to_string())
+    }
+
+    fn limits(&mut self) -> Result<()> {
+        let flags = self.byte()?;
+        self.u32()?;
+        if flags & 1 != 0 {
+            self.u32()?;
+        }
+        Ok(())
+    }
+
+    /// Skips the description of an import.
+    fn import_desc(&mut self) -> Result<()> {
+        match self.byte()? {
+            // function: type index
+            0x00 => {
+                self.u32()?;
+            }
+            // table: reference type and limits
+            0x01 => {
+                self.byte()?;
+                self.limits()?;
+            }
+            // memory: limits
+            0x02 => self.limits()?,
+            // global: value type and mutability
+            0x03 => {
+                self.byte()?;
+                self.byte()?;
+            }
+            // tag: attribute and type index
+            0x04 => {
+                self.byte()?;
+                self.u32()?;
+            }
+            kind => bail!("unknown import kind {kind:#x} in the WebAssembly module"),
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HEADER: &[u8] = b"\0asm\x01\0\0\0";
+
+    fn module(sections: &[&[u8]]) -> Vec<u8> {
+        let mut bytes = HEADER.to_vec();
+        for section in sections {
+            bytes.extend_from_slice(section);
+        }
+        bytes
+    }
+
+    #[test]
+    fn exports() {
+        let analysis = parse(&module(&[
+            // type section: (i32, i32) -> i32
+            &[0x01, 0x07, 0x01, 0x60, 0x02, 0x7f, 0x7f, 0x01, 0x7f],
+            // function section
+            &[0x03, 0x02, 0x01, 0x00],
+            // export section: "add" (func 0), "mem" (memory 0)
+            &[
+                0x07, 0x0d, 0x02, 0x03, b'a', b'd', b'd', 0x00, 0x00, 0x03, b'm', b'e', b'm', 0x02,
+                0x00,
+            ],
+            // code section: local.get 0, local.get 1, i32.add
+            &[
+                0x0a, 0x09, 0x01, 0x07, 0x00, 0x20, 0x00, 0x20, 0x01, 0x6a, 0x0b,
+            ],
+        ]))
+        .unwrap();
+        assert!(analysis.imports.is_empty());
+        assert_eq!(analysis.exports, vec!["add", "mem"]);
+    }
+
+    #[test]
+    fn imports() {
+        let analysis = parse(&module(&[
+            // import section: "env" "memory" (memory, min 1, max 2),
+            // "./math.js" "log" (func 0)
+            &[
+                0x02, 0x20, 0x02, 0x03, b'e', b'n', b'v', 0x06, b'm', b'e', b'm', b'o', b'r', b'y',
+                0x02, 0x01, 0x01, 0x02, 0x09, b'.', b'/', b'm', b'a', b't', b'h', b'.', b'j', b's',
+                0x03, b'l', b'o', b'g', 0x00, 0x00,
+            ],
+        ]))
+        .unwrap();
+        assert_eq!(
+            analysis.imports,
+            vec![
+                ("env".to_string(), "memory".to_string()),
+                ("./math.js".to_string(), "log".to_string()),
+            ]
+        );
+        assert!(analysis.exports.is_empty());
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(parse(b"\0asm").is_err());
+        assert!(parse(b"not wasm").is_err());
+        // The export section is longer than the module.
+        assert!(parse(&module(&[&[0x07, 0x10, 0x01]])).is_err());
+    }
+}
@@ -0,0 +1,304 @@
+//! WebAssembly support for turbopack.
+//!
+//! WebAssembly modules are copied to the output folder.
+//!
+//! When imported from ES modules, they produce an async module which
+//! instantiates the WebAssembly module and exports its exports, following the
+//! ESM integration proposal. The WebAssembly module is fetched in browsers and
+//! read from disk in Node.js.
+
+#![feature(min_specialization)]
+
+pub mod analysis;
+
+use std::fmt::Write;
+
+use anyhow::{anyhow, bail, Result};
+use turbo_tasks::{
+    primitives::{BoolVc, StringVc},
+    Value, ValueToString,
+};
+use turbo_tasks_fs::FileContent;
+use turbopack_core::{
+    asset::{Asset, AssetContent, AssetContentVc},
+    chunk::{
+        availability_info::AvailabilityInfo, ChunkItem, ChunkItemVc, ChunkVc, ChunkableModule,
+        ChunkableModuleVc, ChunkingContext, ChunkingContextVc,
+    },
+    context::AssetContextVc,
+    ident::AssetIdentVc,
+    module::{Module, ModuleVc},
+    output::{OutputAsset, OutputAssetVc},
+    reference::{AssetReferencesVc, SingleAssetReferenceVc},
+    source::SourceVc,
+};
+use turbopack_ecmascript::{
+    chunk::{
+        EcmascriptAsyncModule, EcmascriptAsyncModuleVc, EcmascriptChunkItem,
+        EcmascriptChunkItemContent, EcmascriptChunkItemContentVc, EcmascriptChunkItemOptions,
+        EcmascriptChunkItemVc, EcmascriptChunkPlaceable, EcmascriptChunkPlaceableVc,
+        EcmascriptChunkVc, EcmascriptChunkingContextVc, EcmascriptExports, EcmascriptExportsVc,
+    },
+    utils::StringifyJs,
+    EsmExport, EsmExports,
+};
+
+use self::analysis::{analyze, WebAssemblyAnalysisVc};
+
+#[turbo_tasks::function]
+fn modifier() -> StringVc {
+    StringVc::cell("wasm".to_string())
+}
+
+#[turbo_tasks::value]
+#[derive(Clone)]
+pub struct WebAssemblyModuleAsset {
+    pub source: SourceVc,
+    pub context: AssetContextVc,
+}
+
+#[turbo_tasks::value_impl]
+impl WebAssemblyModuleAssetVc {
+    #[turbo_tasks::function]
+    pub fn new(source: SourceVc, context: AssetContextVc) -> Self {
+        Self::cell(WebAssemblyModuleAsset { source, context })
+    }
+
+    #[turbo_tasks::function]
+    pub async fn analyze(self) -> Result<WebAssemblyAnalysisVc> {
+        Ok(analyze(self.await?.source))
+    }
+
+    #[turbo_tasks::function]
+    async fn wasm_asset(
+        self_vc: WebAssemblyModuleAssetVc,
+        context: ChunkingContextVc,
+    ) -> Result<WebAssemblyAssetVc> {
+        Ok(WebAssemblyAssetVc::cell(WebAssemblyAsset {
+            context,
+            source: self_vc.await?.source,
+        }))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Asset for WebAssemblyModuleAsset {
+    #[turbo_tasks::function]
+    fn ident(&self) -> AssetIdentVc {
+        self.source.ident().with_modifier(modifier())
+    }
+
+    #[turbo_tasks::function]
+    fn content(&self) -> AssetContentVc {
+        self.source.content()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Module for WebAssemblyModuleAsset {}
+
+#[turbo_tasks::value_impl]
+impl ChunkableModule for WebAssemblyModuleAsset {
+    #[turbo_tasks::function]
+    fn as_chunk(
+        self_vc: WebAssemblyModuleAssetVc,
+        context: ChunkingContextVc,
+        availability_info: Value<AvailabilityInfo>,
+    ) -> ChunkVc {
+        EcmascriptChunkVc::new(
+            context,
+            self_vc.as_ecmascript_chunk_placeable(),
+            availability_info,
+        )
+        .into()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl EcmascriptChunkPlaceable for WebAssemblyModuleAsset {
+    #[turbo_tasks::function]
+    fn as_chunk_item(
+        self_vc: WebAssemblyModuleAssetVc,
+        context: EcmascriptChunkingContextVc,
+    ) -> EcmascriptChunkItemVc {
+        ModuleChunkItemVc::cell(ModuleChunkItem {
+            module: self_vc,
+            context,
+            wasm_asset: self_vc.wasm_asset(context.into()),
+        })
+        .into()
+    }
+
+    #[turbo_tasks::function]
+    async fn get_exports(self_vc: WebAssemblyModuleAssetVc) -> Result<EcmascriptExportsVc> {
+        let analysis = self_vc.analyze().await?;
+        let exports = analysis
+            .exports
+            .iter()
+            .map(|name| (name.clone(), EsmExport::LocalBinding(name.clone())))
+            .collect();
+        Ok(EcmascriptExports::EsmExports(
+            EsmExports {
+                exports,
+                star_exports: Vec::new(),
+            }
+            .cell(),
+        )
+        .cell())
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl EcmascriptAsyncModule for WebAssemblyModuleAsset {
+    #[turbo_tasks::function]
+    fn is_async(&self) -> BoolVc {
+        // The module is compiled and instantiated asynchronously.
+        BoolVc::cell(true)
+    }
+}
+
+#[turbo_tasks::value]
+struct WebAssemblyAsset {
+    context: ChunkingContextVc,
+    source: SourceVc,
+}
+
+#[turbo_tasks::value_impl]
+impl OutputAsset for WebAssemblyAsset {}
+
+#[turbo_tasks::value_impl]
+impl Asset for WebAssemblyAsset {
+    #[turbo_tasks::function]
+    async fn ident(&self) -> Result<AssetIdentVc> {
+        let content = self.source.content();
+        let content_hash = if let AssetContent::File(file) = &*content.await? {
+            if let FileContent::Content(file) = &*file.await? {
+                turbo_tasks_hash::hash_xxh3_hash64(file.content())
+            } else {
+                return Err(anyhow!("WebAssemblyAsset::path: not found"));
+            }
+        } else {
+            return Err(anyhow!("WebAssemblyAsset::path: unsupported file content"));
+        };
+        let content_hash_b16 = turbo_tasks_hash::encode_hex(content_hash);
+        let asset_path = self
+            .context
+            .asset_path(&content_hash_b16, self.source.ident());
+        Ok(AssetIdentVc::from_path(asset_path))
+    }
+
+    #[turbo_tasks::function]
+    fn content(&self) -> AssetContentVc {
+        self.source.content()
+    }
+}
+
+#[turbo_tasks::value]
+struct ModuleChunkItem {
+    module: WebAssemblyModuleAssetVc,
+    context: EcmascriptChunkingContextVc,
+    wasm_asset: WebAssemblyAssetVc,
+}
+
+#[turbo_tasks::value_impl]
+impl ChunkItem for ModuleChunkItem {
+    #[turbo_tasks::function]
+    fn asset_ident(&self) -> AssetIdentVc {
+        self.module.ident()
+    }
+
+    #[turbo_tasks::function]
+    async fn references(&self) -> Result<AssetReferencesVc> {
+        Ok(AssetReferencesVc::cell(vec![SingleAssetReferenceVc::new(
+            self.wasm_asset.into(),
+            StringVc::cell(format!(
+                "wasm(url) {}",
+                self.wasm_asset.ident().to_string().await?
+            )),
+        )
+        .into()]))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl EcmascriptChunkItem for ModuleChunkItem {
+    #[turbo_tasks::function]
+    fn chunking_context(&self) -> EcmascriptChunkingContextVc {
+        self.context
+    }
+
+    #[turbo_tasks::function]
+    async fn content(&self) -> Result<EcmascriptChunkItemContentVc> {
+        let analysis = self.module.analyze().await?;
+        if let Some((module, name)) = analysis.imports.first() {
+            bail!(
+                "WebAssembly modules which import JavaScript are not supported yet: {} imports {} \
+                 from {}",
+                self.module.ident().to_string().await?,
+                StringifyJs(name),
+                StringifyJs(module)
+            );
+        }
+
+        let wasm_path = self.wasm_asset.ident().path();
+        let node = *self.context.environment().node_externals().await?;
+
+        let mut code = String::new();
+        writeln!(code, "let wasmExports;")?;
+        writeln!(code, "__turbopack_esm__({{")?;
+        for name in analysis.exports.iter() {
+            let name = StringifyJs(name);
+            writeln!(code, "    {name}: () => wasmExports[{name}],")?;
+        }
+        writeln!(code, "}});")?;
+        if node {
+            // The chunks of a chunking context are all placed in the same
+            // directory, so the asset is located relative to the chunk.
+            let chunk_dir = self
+                .context
+                .chunk_path(self.module.ident(), ".js")
+                .parent()
+                .await?;
+            let Some(relative_path) = chunk_dir.get_relative_path_to(&wasm_path.await?) else {
+                bail!(
+                    "WebAssembly module {} is emitted outside of the output root",
+                    self.module.ident().to_string().await?
+                );
+            };
+            writeln!(
+                code,
+                "const path = __turbopack_external_require__(\"path\");\nconst buffer = await \
+                 __turbopack_external_require__(\"fs/promises\").readFile(path.join(path.\
+                 dirname(__filename), {}));\nwasmExports = (await WebAssembly.instantiate(buffer, \
+                 {{}})).instance.exports;",
+                StringifyJs(&relative_path)
+            )?;
+        } else {
+            writeln!(
+                code,
+                "wasmExports = (await WebAssembly.instantiateStreaming(fetch({}), \
+                 {{}})).instance.exports;",
+                StringifyJs(&format_args!("/{}", &*wasm_path.await?))
+            )?;
+        }
+
+        Ok(EcmascriptChunkItemContent {
+            inner_code: code.into(),
+            options: EcmascriptChunkItemOptions {
+                externals: node,
+                async_module: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+        .into())
+    }
+}
+
+pub fn register() {
+    turbo_tasks::register();
+    turbo_tasks_fs::register();
+    turbopack_core::register();
+    turbopack_ecmascript::register();
+    include!(concat!(env!("OUT_DIR"), "/register.rs"));
+}
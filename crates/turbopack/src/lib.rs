@@ -34,6 +34,7 @@ use turbopack_core::{
     ident::AssetIdentVc,
     issue::{Issue, IssueVc},
     module::ModuleVc,
+    output::write_output_asset,
     raw_module::RawModuleVc,
     reference::all_referenced_assets,
     reference_type::{EcmaScriptModulesReferenceSubType, InnerAssetsVc, ReferenceType},
@@ -53,6 +54,8 @@ pub mod module_options;
 pub mod rebase;
 pub mod resolve;
 pub mod resolve_options_context;
+#[cfg(test)]
+mod tests;
 pub mod transition;
 pub(crate) mod unsupported_sass;
 
@@ -61,6 +64,7 @@ pub use turbopack_ecmascript as ecmascript;
 use turbopack_json::JsonModuleAssetVc;
 use turbopack_mdx::MdxModuleAssetVc;
 use turbopack_static::StaticModuleAssetVc;
+use turbopack_wasm::WebAssemblyModuleAssetVc;
 
 use self::{
     module_options::CustomModuleType,
@@ -158,6 +162,10 @@ async fn apply_module_type(
                 builder = builder.with_inner_assets(inner_assets);
             }
 
+            if let Some(layer) = &context.module_options_context().await?.layer {
+                builder = builder.with_layer(StringVc::cell(layer.clone()));
+            }
+
             if options.split_into_parts {
                 if let Some(part) = part {
                     builder = builder.with_part(part);
@@ -174,6 +182,7 @@ async fn apply_module_type(
             CssModuleAssetVc::new(source, context.into(), *transforms, *ty).into()
         }
         ModuleType::Static => StaticModuleAssetVc::new(source, context.into()).into(),
+        ModuleType::WebAssembly => WebAssemblyModuleAssetVc::new(source, context.into()).into(),
         ModuleType::Mdx {
             transforms,
             options,
@@ -534,7 +543,7 @@ async fn emit_aggregated_assets(
 
 #[turbo_tasks::function]
 pub async fn emit_asset(asset: AssetVc) -> CompletionVc {
-    asset.content().write(asset.ident().path())
+    write_output_asset(asset)
 }
 
 #[turbo_tasks::function]
@@ -623,5 +632,6 @@ pub fn register() {
     turbopack_mdx::register();
     turbopack_json::register();
     turbopack_static::register();
+    turbopack_wasm::register();
     include!(concat!(env!("OUT_DIR"), "/register.rs"));
 }
@@ -66,6 +66,17 @@ impl ModuleOptionsVc {
             enable_jsx,
             enable_types,
             enable_tree_shaking,
+            preserve_comments,
+            keep_names,
+            target_module_format,
+            ecmascript_transform_rules,
+            ecmascript_globals_externals,
+            ecmascript_max_analysis_bytes,
+            ecmascript_max_codegen_bytes,
+            ecmascript_strict_interop,
+            ecmascript_inline_json_bytes,
+            ecmascript_post_processor,
+            ecmascript_cjs_default_interop,
             ref enable_typescript_transform,
             ref decorators,
             enable_mdx,
@@ -131,6 +142,17 @@ impl ModuleOptionsVc {
         let ecmascript_options = EcmascriptOptions {
             split_into_parts: enable_tree_shaking,
             import_parts: enable_tree_shaking,
+            preserve_comments,
+            keep_names,
+            target_module_format,
+            transform_rules: ecmascript_transform_rules,
+            globals_externals: ecmascript_globals_externals,
+            max_analysis_bytes: ecmascript_max_analysis_bytes,
+            max_codegen_bytes: ecmascript_max_codegen_bytes,
+            strict_interop: ecmascript_strict_interop,
+            inline_json_bytes: ecmascript_inline_json_bytes,
+            post_processor: ecmascript_post_processor,
+            cjs_default_interop: ecmascript_cjs_default_interop,
             ..Default::default()
         };
 
@@ -225,6 +247,10 @@ impl ModuleOptionsVc {
                 ModuleRuleCondition::ResourcePathEndsWith(".json".to_string()),
                 vec![ModuleRuleEffect::ModuleType(ModuleType::Json)],
             ),
+            ModuleRule::new(
+                ModuleRuleCondition::ResourcePathEndsWith(".wasm".to_string()),
+                vec![ModuleRuleEffect::ModuleType(ModuleType::WebAssembly)],
+            ),
             ModuleRule::new_all(
                 ModuleRuleCondition::any(vec![
                     ModuleRuleCondition::ResourcePathEndsWith(".js".to_string()),
@@ -319,7 +345,11 @@ impl ModuleOptionsVc {
                 }],
             ),
             ModuleRule::new(
-                ModuleRuleCondition::ResourcePathEndsWith(".d.ts".to_string()),
+                ModuleRuleCondition::any(vec![
+                    ModuleRuleCondition::ResourcePathEndsWith(".d.ts".to_string()),
+                    ModuleRuleCondition::ResourcePathEndsWith(".d.mts".to_string()),
+                    ModuleRuleCondition::ResourcePathEndsWith(".d.cts".to_string()),
+                ]),
                 vec![ModuleRuleEffect::ModuleType(
                     ModuleType::TypescriptDeclaration {
                         transforms: vendor_transforms,
@@ -567,3 +597,209 @@ impl ModuleOptionsVc {
         Ok(ModuleOptionsVc::cell(ModuleOptions { rules }))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use turbo_tasks::TurboTasks;
+    use turbo_tasks_fs::FileSystemPathVc;
+    use turbo_tasks_memory::MemoryBackend;
+    use turbopack_core::{context::AssetContextVc, issue::IssueVc};
+
+    use crate::{
+        ecmascript::{
+            chunk::EcmascriptExports, EcmascriptInputTransform, EcmascriptInputTransformsVc,
+            EcmascriptModuleAssetVc, TargetFormat, TransformRule, TransformRules,
+        },
+        module_options::ModuleOptionsContext,
+        register,
+        resolve_options_context::ResolveOptionsContext,
+        tests::{ecmascript_module, node_asset_context, project_root, write_files},
+    };
+
+    /// Returns the emitted code of `file`, whether it is an ES module and the
+    /// titles of the issues reported for it.
+    async fn module_content(
+        root: String,
+        file: &'static str,
+        target_module_format: TargetFormat,
+    ) -> Result<(String, bool, Vec<String>)> {
+        let context = node_asset_context(
+            ModuleOptionsContext {
+                target_module_format,
+                ..Default::default()
+            }
+            .cell(),
+            ResolveOptionsContext::default().cell(),
+        );
+        let module = ecmascript_module(context, project_root(root).join(file)).await?;
+
+        let content = module.module_content_without_analysis();
+        let issues = IssueVc::peek_issues_with_path(content)
+            .await?
+            .await?
+            .get_plain_issues()
+            .await?
+            .iter()
+            .map(|issue| issue.title.clone())
+            .collect();
+        let content = content.await?;
+        Ok((
+            content.inner_code.to_str()?.into_owned(),
+            content.is_esm,
+            issues,
+        ))
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn target_module_format() {
+        register();
+
+        let dir = write_files(&[
+            (
+                "esm.js",
+                "import { b } from './b.js';\nexport const a = b;\nexport default function f() \
+                 {}\n",
+            ),
+            ("tla.js", "export const a = await Promise.resolve(1);\n"),
+        ]);
+
+        let tt = TurboTasks::new(MemoryBackend::default());
+        let root = dir.path().to_string_lossy().to_string();
+
+        let (code, is_esm, _) = tt
+            .run_once(module_content(
+                root.clone(),
+                "esm.js",
+                TargetFormat::Preserve,
+            ))
+            .await
+            .unwrap();
+        assert!(is_esm);
+        assert!(code.contains("import { b } from"), "{code}");
+
+        let (code, is_esm, issues) = tt
+            .run_once(module_content(
+                root.clone(),
+                "esm.js",
+                TargetFormat::CommonJs,
+            ))
+            .await
+            .unwrap();
+        assert!(!is_esm);
+        assert!(issues.is_empty(), "{issues:?}");
+        assert!(code.starts_with("\"use strict\";"), "{code}");
+        assert!(code.contains("require(\"./b.js\")"), "{code}");
+        assert!(code.contains("exports"), "{code}");
+        assert!(!code.contains("import "), "{code}");
+        assert!(!code.contains("export "), "{code}");
+
+        let (_, is_esm, _) = tt
+            .run_once(module_content(
+                root.clone(),
+                "tla.js",
+                TargetFormat::Preserve,
+            ))
+            .await
+            .unwrap();
+        assert!(is_esm);
+
+        // Top-level await can't be emitted as CommonJS, so the module is kept
+        // as an ES module and an issue is reported.
+        let (code, is_esm, issues) = tt
+            .run_once(module_content(root, "tla.js", TargetFormat::CommonJs))
+            .await
+            .unwrap();
+        assert!(is_esm);
+        assert!(code.contains("await Promise.resolve(1)"), "{code}");
+        assert_eq!(
+            issues,
+            ["Top-level await is not supported when emitting CommonJS"]
+        );
+    }
+
+    async fn module(
+        context: AssetContextVc,
+        root: FileSystemPathVc,
+        file: &str,
+    ) -> Result<EcmascriptModuleAssetVc> {
+        ecmascript_module(context, root.join(file)).await
+    }
+
+    /// Returns whether `file` is analyzed as CommonJS, and the last of its
+    /// transforms.
+    async fn analyze(
+        context: AssetContextVc,
+        root: FileSystemPathVc,
+        file: &'static str,
+    ) -> Result<(bool, Option<EcmascriptInputTransform>)> {
+        let module = module(context, root, file).await?;
+        let exports = module.analyze().await?.exports.await?;
+        let transforms = module.transforms().await?;
+        Ok((
+            matches!(&*exports, EcmascriptExports::CommonJs),
+            transforms.last().cloned(),
+        ))
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn transform_rules_select_transforms_by_path() {
+        register();
+
+        let dir = write_files(
+            &["index.macro.js", "index.js"].map(|file| (file, "export const a = 1;\n")),
+        );
+        let root = dir.path().to_string_lossy().to_string();
+
+        let tt = TurboTasks::new(MemoryBackend::default());
+        tt.run_once(async move {
+            let root = project_root(root);
+
+            let context = |ecmascript_transform_rules| {
+                node_asset_context(
+                    ModuleOptionsContext {
+                        ecmascript_transform_rules,
+                        ..Default::default()
+                    }
+                    .cell(),
+                    ResolveOptionsContext::default().cell(),
+                )
+            };
+
+            let without_rules = context(None);
+            let (commonjs, last_transform) = analyze(without_rules, root, "index.js").await?;
+            assert!(!commonjs);
+
+            let rules = TransformRules {
+                rules: vec![TransformRule::new(
+                    "**/*.macro.js",
+                    EcmascriptInputTransformsVc::cell(vec![EcmascriptInputTransform::CommonJs]),
+                )],
+                default: None,
+            }
+            .cell();
+            let with_rules = context(Some(rules));
+
+            // The transform only runs on the matching file, before the analysis.
+            let (commonjs, macro_transform) = analyze(with_rules, root, "index.macro.js").await?;
+            assert!(commonjs);
+            assert_eq!(macro_transform, Some(EcmascriptInputTransform::CommonJs));
+
+            // Files which don't match are processed exactly as without rules.
+            assert_eq!(
+                analyze(with_rules, root, "index.js").await?,
+                (false, last_transform)
+            );
+            let module_without_rules = module(without_rules, root, "index.js").await?;
+            let module_with_rules = module(with_rules, root, "index.js").await?;
+            assert_eq!(
+                *module_with_rules.transforms().await?,
+                *module_without_rules.transforms().await?
+            );
+
+            Ok(())
+        })
+        .await
+        .unwrap();
+    }
+}
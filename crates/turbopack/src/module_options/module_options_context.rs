@@ -2,7 +2,10 @@ use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 use turbo_tasks::trace::TraceRawVcs;
 use turbopack_core::{environment::EnvironmentVc, resolve::options::ImportMappingVc};
-use turbopack_ecmascript::TransformPluginVc;
+use turbopack_ecmascript::{
+    post_process::CodePostProcessorVc, CjsDefaultInterop, CommentsMode, GlobalsExternalsVc,
+    KeepNames, TargetFormat, TransformPluginVc, TransformRulesVc,
+};
 use turbopack_node::{
     execution_context::ExecutionContextVc, transforms::webpack::WebpackLoaderItemsVc,
 };
@@ -176,6 +179,50 @@ pub struct ModuleOptionsContext {
     pub rules: Vec<(ContextCondition, ModuleOptionsContextVc)>,
     pub placeholder_for_future_extensions: (),
     pub enable_tree_shaking: bool,
+    /// Which comments are kept in the emitted JavaScript. License comments
+    /// are often required to be kept for legal reasons.
+    pub preserve_comments: CommentsMode,
+    /// Whether the `name` of functions and classes is kept when their bindings
+    /// are renamed in the emitted JavaScript.
+    pub keep_names: KeepNames,
+    /// The module format of the emitted JavaScript.
+    pub target_module_format: TargetFormat,
+    /// Additional ecmascript transforms selected by the path of the module,
+    /// e.g. a transform which only runs on `**/*.macro.js`.
+    pub ecmascript_transform_rules: Option<TransformRulesVc>,
+    /// Imports which read a global variable instead of bundling the module,
+    /// e.g. `react` from `window.React` for widgets embedded in pages which
+    /// already load React.
+    pub ecmascript_globals_externals: Option<GlobalsExternalsVc>,
+    /// JavaScript modules larger than this number of bytes, e.g. generated data
+    /// files, are not evaluated by the analysis. Only their import and export
+    /// declarations are followed, and a warning is reported.
+    pub ecmascript_max_analysis_bytes: Option<usize>,
+    /// CommonJS modules larger than this number of bytes are emitted without
+    /// running the code generation when their analysis found no references.
+    pub ecmascript_max_codegen_bytes: Option<usize>,
+    /// Reports hazards of the interop between CommonJS and EcmaScript modules,
+    /// e.g. assigning `module.exports` in a module with `export`s, as errors
+    /// instead of warnings.
+    pub ecmascript_strict_interop: bool,
+    /// JSON files whose value is at most this number of bytes are inlined
+    /// into each JavaScript module importing them with `import`, e.g. small
+    /// feature flag or locale files. A copy of the value is emitted for every
+    /// importer, so this trades bundle size for fewer module factories.
+    pub ecmascript_inline_json_bytes: Option<usize>,
+    /// A final textual pass over the emitted code of every JavaScript module,
+    /// e.g. to inject a build banner.
+    pub ecmascript_post_processor: Option<CodePostProcessorVc>,
+    /// What default imports of CommonJS modules bind to in JavaScript modules,
+    /// e.g. `module.exports` for code which was written for Node.js or Jest.
+    /// Use [ModuleOptionsContext::rules] to apply a different setting to some
+    /// directories.
+    pub ecmascript_cjs_default_interop: CjsDefaultInterop,
+    /// The layer of the JavaScript modules created in this context, e.g.
+    /// `server` and `client` when the same sources are compiled for both. It
+    /// is part of the idents of the modules, so modules of different layers
+    /// don't share their analysis and content.
+    pub layer: Option<String>,
 }
 
 #[turbo_tasks::value_impl]
@@ -124,5 +124,6 @@ pub enum ModuleType {
         transforms: CssInputTransformsVc,
     },
     Static,
+    WebAssembly,
     Custom(CustomModuleTypeVc),
 }
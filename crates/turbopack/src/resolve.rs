@@ -3,10 +3,13 @@ use turbo_tasks_fs::{FileSystem, FileSystemPathVc};
 use turbopack_core::resolve::{
     find_context_file,
     options::{
-        ConditionValue, ImportMap, ImportMapping, ResolutionConditions, ResolveInPackage,
-        ResolveIntoPackage, ResolveModules, ResolveOptions, ResolveOptionsVc,
+        ConditionValue, ImportMap, ImportMapResult, ImportMapResultVc, ImportMapVc, ImportMapping,
+        ImportMappingReplacement, ImportMappingReplacementVc, ImportMappingVc,
+        ResolutionConditions, ResolveInPackage, ResolveIntoPackage, ResolveModules, ResolveOptions,
+        ResolveOptionsVc,
     },
-    AliasMap, AliasPattern, FindContextFileResult,
+    parse::RequestVc,
+    AliasMap, AliasPattern, FindContextFileResult, PrimaryResolveResult, ResolveResult,
 };
 use turbopack_ecmascript::typescript::resolve::{
     apply_tsconfig_resolve_options, tsconfig, tsconfig_resolve_options,
@@ -68,6 +71,77 @@ const NODE_EXTERNALS: [&str; 51] = [
     "pnpapi",
 ];
 
+/// Requests which are left as `require` or `import` at runtime when they can't
+/// be resolved, e.g. for packages which are installed next to a Node.js server
+/// bundle.
+#[turbo_tasks::value(shared)]
+#[derive(Clone, Debug, Default)]
+pub struct ExternalsConfig {
+    /// Requests starting with one of these prefixes are external, e.g.
+    /// `@aws-sdk/`.
+    pub prefixes: Vec<String>,
+    /// Well-known Node.js modules and all requests with the `node:` prefix
+    /// are external. Builtins which are only available with the prefix, e.g.
+    /// `node:test`, don't need to be well-known.
+    pub node_builtins: bool,
+}
+
+impl ExternalsConfig {
+    /// Returns whether `request` is external.
+    pub fn is_external(&self, request: &str) -> bool {
+        if self.node_builtins && (request.starts_with("node:") || NODE_EXTERNALS.contains(&request))
+        {
+            return true;
+        }
+        self.prefixes
+            .iter()
+            .any(|prefix| request.starts_with(prefix.as_str()))
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ExternalsConfigVc {
+    /// An import map which maps every external request to an external. It is
+    /// used as fallback import map, so that requests which resolve are still
+    /// bundled.
+    #[turbo_tasks::function]
+    pub fn import_map(self) -> ImportMapVc {
+        let mut map = AliasMap::new();
+        map.insert(
+            AliasPattern::wildcard("", ""),
+            ImportMapping::Dynamic(self.into()).into(),
+        );
+        ImportMap::new(map).cell()
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ImportMappingReplacement for ExternalsConfig {
+    #[turbo_tasks::function]
+    fn replace(self_vc: ExternalsConfigVc, _capture: &str) -> ImportMappingVc {
+        ImportMapping::Dynamic(self_vc.into()).into()
+    }
+
+    #[turbo_tasks::function]
+    async fn result(
+        &self,
+        _context: FileSystemPathVc,
+        request: RequestVc,
+    ) -> Result<ImportMapResultVc> {
+        let Some(request) = request.await?.request() else {
+            return Ok(ImportMapResult::NoEntry.into());
+        };
+        Ok(if self.is_external(&request) {
+            ImportMapResult::Result(
+                ResolveResult::primary(PrimaryResolveResult::OriginalReferenceExternal).into(),
+            )
+            .into()
+        } else {
+            ImportMapResult::NoEntry.into()
+        })
+    }
+}
+
 #[turbo_tasks::function]
 async fn base_resolve_options(
     context: FileSystemPathVc,
@@ -229,6 +303,33 @@ async fn base_resolve_options(
     .into())
 }
 
+/// Returns the import maps of the directories that contain `context`, ordered
+/// from the outermost to the innermost directory. Import maps of the same
+/// directory keep their order.
+async fn scoped_import_maps(
+    context: FileSystemPathVc,
+    scoped_import_maps: &[(FileSystemPathVc, ImportMapVc)],
+) -> Result<Vec<ImportMapVc>> {
+    if scoped_import_maps.is_empty() {
+        return Ok(Vec::new());
+    }
+    let context = context.await?;
+    let mut import_maps = Vec::new();
+    for (directory, import_map) in scoped_import_maps {
+        let directory = directory.await?;
+        if context.is_inside_or_equal(&directory) {
+            import_maps.push((directory.path.len(), *import_map));
+        }
+    }
+    // The sort is stable, so the order of the list decides between import
+    // maps of the same directory.
+    import_maps.sort_by_key(|(depth, _)| *depth);
+    Ok(import_maps
+        .into_iter()
+        .map(|(_, import_map)| import_map)
+        .collect())
+}
+
 #[turbo_tasks::function]
 pub async fn resolve_options(
     context: FileSystemPathVc,
@@ -258,12 +359,26 @@ pub async fn resolve_options(
         resolve_options
     };
 
+    // Webpack externals take precedence over resolving, but not over
+    // `options_context.import_map`.
+    let resolve_options = options_context_value
+        .webpack_externals
+        .map(|externals| resolve_options.with_extended_import_map(externals.import_map()))
+        .unwrap_or(resolve_options);
     // Make sure to always apply `options_context.import_map` last, so it properly
     // overwrites any other mappings.
     let resolve_options = options_context_value
         .import_map
         .map(|import_map| resolve_options.with_extended_import_map(import_map))
         .unwrap_or(resolve_options);
+    // Only the scoped import maps are more specific, the innermost one is
+    // applied last.
+    let resolve_options = scoped_import_maps(context, &options_context_value.scoped_import_maps)
+        .await?
+        .into_iter()
+        .fold(resolve_options, |resolve_options, import_map| {
+            resolve_options.with_extended_import_map(import_map)
+        });
     // And the same for the fallback_import_map
     let resolve_options = options_context_value
         .fallback_import_map
@@ -271,6 +386,261 @@ pub async fn resolve_options(
             resolve_options.with_extended_fallback_import_map(fallback_import_map)
         })
         .unwrap_or(resolve_options);
+    // Externals only apply when nothing else resolves a request.
+    let resolve_options = options_context_value
+        .externals
+        .map(|externals| resolve_options.with_extended_fallback_import_map(externals.import_map()))
+        .unwrap_or(resolve_options);
 
     Ok(resolve_options)
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::Path, time::Duration};
+
+    use anyhow::{Context, Result};
+    use turbo_tasks::TurboTasks;
+    use turbo_tasks_fs::{DiskFileSystemVc, FileSystem, FileSystemPathVc};
+    use turbo_tasks_memory::MemoryBackend;
+    use turbopack_core::{
+        asset::Asset,
+        context::AssetContextVc,
+        reference::{all_assets, AssetReference},
+        resolve::options::{ImportMap, ImportMapVc, ImportMapping},
+    };
+
+    use super::ExternalsConfig;
+    use crate::{
+        module_options::ModuleOptionsContext,
+        register,
+        resolve_options_context::ResolveOptionsContext,
+        tests::{node_asset_context, process, project_root, write_files},
+    };
+
+    /// Returns the paths of all assets in the module graph of `index.js`.
+    async fn module_graph(root: String) -> Result<Vec<String>> {
+        let fs = DiskFileSystemVc::new("project".to_string(), root);
+        fs.await?.start_watching()?;
+        let root = fs.root();
+
+        let context = node_asset_context(
+            ModuleOptionsContext::default().cell(),
+            ResolveOptionsContext {
+                enable_node_modules: Some(root),
+                ..Default::default()
+            }
+            .cell(),
+        );
+        let module = process(context, root.join("index.js"));
+
+        let root = root.await?;
+        let mut paths = Vec::new();
+        for asset in all_assets(module.into()).await?.iter() {
+            let path = asset.ident().path().await?;
+            if let Some(path) = root.get_path_to(&path) {
+                paths.push(path.to_string());
+            }
+        }
+        paths.sort();
+        Ok(paths)
+    }
+
+    fn write_exports(dir: &Path, target: &str) {
+        fs::write(
+            dir.join("node_modules/pkg/package.json"),
+            format!(r#"{{ "name": "pkg", "exports": {{ ".": "{target}" }} }}"#),
+        )
+        .unwrap();
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn exports_field_change_updates_module_graph() {
+        register();
+
+        let dir = write_files(&[
+            ("index.js", "import \"pkg\";\n"),
+            ("node_modules/pkg/a.js", "export default 'a';\n"),
+            ("node_modules/pkg/b.js", "export default 'b';\n"),
+        ]);
+        write_exports(dir.path(), "./a.js");
+
+        let tt = TurboTasks::new(MemoryBackend::default());
+        let root = dir.path().to_string_lossy().to_string();
+
+        let graph = tt.run_once(module_graph(root.clone())).await.unwrap();
+        assert!(graph.contains(&"node_modules/pkg/a.js".to_string()));
+        assert!(graph.contains(&"node_modules/pkg/package.json".to_string()));
+        assert!(!graph.contains(&"node_modules/pkg/b.js".to_string()));
+
+        // Only the package.json changes, the importer is not touched.
+        write_exports(dir.path(), "./b.js");
+
+        let mut graph = Vec::new();
+        for _ in 0..50 {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            graph = tt.run_once(module_graph(root.clone())).await.unwrap();
+            if graph.contains(&"node_modules/pkg/b.js".to_string()) {
+                break;
+            }
+        }
+        assert!(graph.contains(&"node_modules/pkg/b.js".to_string()));
+        assert!(!graph.contains(&"node_modules/pkg/a.js".to_string()));
+    }
+
+    #[test]
+    fn node_builtins_are_external() {
+        let externals = ExternalsConfig {
+            prefixes: vec!["@aws-sdk/".to_string()],
+            node_builtins: true,
+        };
+
+        assert!(externals.is_external("fs"));
+        assert!(externals.is_external("node:fs"));
+        // Builtins which only exist with the prefix are external as well.
+        assert!(externals.is_external("node:test"));
+        assert!(externals.is_external("node:sqlite"));
+        assert!(!externals.is_external("test"));
+        assert!(externals.is_external("@aws-sdk/client-s3"));
+        assert!(!externals.is_external("react"));
+
+        let externals = ExternalsConfig {
+            prefixes: vec![],
+            node_builtins: false,
+        };
+        assert!(!externals.is_external("fs"));
+        assert!(!externals.is_external("node:test"));
+    }
+
+    /// An import map with an `@/` alias for the files in `directory`.
+    fn src_alias(directory: FileSystemPathVc) -> ImportMap {
+        let mut import_map = ImportMap::default();
+        import_map.insert_wildcard_alias(
+            "@/",
+            ImportMapping::PrimaryAlternative("./*".to_string(), Some(directory)).cell(),
+        );
+        import_map
+    }
+
+    /// Returns the paths of the modules that the module at `file` references,
+    /// relative to `root`.
+    async fn dependencies(
+        context: AssetContextVc,
+        root: FileSystemPathVc,
+        file: &str,
+    ) -> Result<Vec<String>> {
+        let module = process(context, root.join(file));
+        let root = root.await?;
+        let mut dependencies = Vec::new();
+        for reference in module.references().await?.iter() {
+            for asset in reference.resolve_reference().primary_assets().await?.iter() {
+                let path = asset.ident().path().await?;
+                dependencies.push(
+                    root.get_path_to(&path)
+                        .context("expected the dependency to be in the root directory")?
+                        .to_string(),
+                );
+            }
+        }
+        Ok(dependencies)
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn scoped_import_maps() {
+        register();
+
+        let dir = write_files(&[
+            ("index.js", "import \"@/util.js\";\n"),
+            ("shared/util.js", "export const scope = \"shared\";\n"),
+            (
+                "apps/web/index.js",
+                "import \"@/util.js\";\nimport \"ui\";\n",
+            ),
+            ("apps/web/src/util.js", "export const scope = \"web\";\n"),
+            ("apps/admin/index.js", "import \"@/util.js\";\n"),
+            ("apps/admin/config.js", "export const config = \"admin\";\n"),
+            (
+                "apps/admin/src/util.js",
+                "export const scope = \"admin\";\n",
+            ),
+            (
+                "apps/admin/nested/index.js",
+                "import \"@/util.js\";\nimport \"~config\";\n",
+            ),
+            (
+                "apps/admin/nested/lib/util.js",
+                "export const scope = \"nested\";\n",
+            ),
+            (
+                "node_modules/web-ui/package.json",
+                "{ \"name\": \"web-ui\", \"main\": \"index.js\" }\n",
+            ),
+            (
+                "node_modules/web-ui/index.js",
+                "export const ui = \"web-ui\";\n",
+            ),
+        ]);
+        let root = dir.path().to_string_lossy().to_string();
+
+        let tt = TurboTasks::new(MemoryBackend::default());
+        tt.run_once(async move {
+            let root = project_root(root);
+            let web = root.join("apps/web");
+            let admin = root.join("apps/admin");
+            let nested = admin.join("nested");
+
+            // The target of an alias can be a package.
+            let mut web_import_map = src_alias(web.join("src"));
+            web_import_map.insert_exact_alias(
+                "ui",
+                ImportMapping::PrimaryAlternative("web-ui".to_string(), None).cell(),
+            );
+            let mut admin_import_map = src_alias(admin.join("src"));
+            admin_import_map.insert_exact_alias(
+                "~config",
+                ImportMapping::PrimaryAlternative("./config.js".to_string(), Some(admin)).cell(),
+            );
+
+            // The order of the list doesn't matter for nested directories.
+            let scoped_import_maps: Vec<(FileSystemPathVc, ImportMapVc)> = vec![
+                (nested, src_alias(nested.join("lib")).cell()),
+                (root, src_alias(root.join("shared")).cell()),
+                (web, web_import_map.cell()),
+                (admin, admin_import_map.cell()),
+            ];
+
+            let context = node_asset_context(
+                ModuleOptionsContext::default().cell(),
+                ResolveOptionsContext {
+                    enable_node_modules: Some(root),
+                    scoped_import_maps,
+                    ..Default::default()
+                }
+                .cell(),
+            );
+
+            assert_eq!(
+                dependencies(context, root, "index.js").await?,
+                vec!["shared/util.js"]
+            );
+            assert_eq!(
+                dependencies(context, root, "apps/web/index.js").await?,
+                vec!["apps/web/src/util.js", "node_modules/web-ui/index.js"]
+            );
+            assert_eq!(
+                dependencies(context, root, "apps/admin/index.js").await?,
+                vec!["apps/admin/src/util.js"]
+            );
+            // The innermost directory takes precedence, but the aliases of the
+            // outer directories which it doesn't override still apply.
+            assert_eq!(
+                dependencies(context, root, "apps/admin/nested/index.js").await?,
+                vec!["apps/admin/nested/lib/util.js", "apps/admin/config.js"]
+            );
+
+            Ok(())
+        })
+        .await
+        .unwrap();
+    }
+}
@@ -7,8 +7,9 @@ use turbopack_core::{
         plugin::ResolvePluginVc,
     },
 };
+use turbopack_ecmascript::webpack::externals::WebpackExternalsVc;
 
-use crate::condition::ContextCondition;
+use crate::{condition::ContextCondition, resolve::ExternalsConfigVc};
 
 #[turbo_tasks::value(shared)]
 #[derive(Default, Clone)]
@@ -47,6 +48,16 @@ pub struct ResolveOptionsContext {
     /// precedence over any other (e.g. tsconfig.json `compilerOptions.paths`).
     pub import_map: Option<ImportMapVc>,
     #[serde(default)]
+    /// Import maps which only apply to requests from within a directory, e.g.
+    /// an `@/` alias which refers to the `src` directory of each app of a
+    /// monorepo.
+    ///
+    /// The import maps of all directories containing the requesting module
+    /// are applied after `import_map`, the one of the innermost directory
+    /// last, so its mappings take precedence. Import maps of the same
+    /// directory are applied in the order of the list.
+    pub scoped_import_maps: Vec<(FileSystemPathVc, ImportMapVc)>,
+    #[serde(default)]
     /// An import map to fall back to when a request could not be resolved.
     ///
     /// If set, this import map will be applied to
@@ -54,6 +65,21 @@ pub struct ResolveOptionsContext {
     /// any mapping defined within will take precedence over any other.
     pub fallback_import_map: Option<ImportMapVc>,
     #[serde(default)]
+    /// Requests which are left as `require` or `import` at runtime when they
+    /// can't be resolved, instead of failing.
+    ///
+    /// It is applied after `fallback_import_map`.
+    pub externals: Option<ExternalsConfigVc>,
+    #[serde(default)]
+    /// Requests which are never bundled and are mapped to an external at
+    /// runtime instead, like webpack's `externals` config. Only the `commonjs`
+    /// and `module` externals are resolved, the `global` externals need to be
+    /// passed to `ModuleOptionsContext::ecmascript_globals_externals`, see
+    /// `WebpackExternalsVc::globals_externals`.
+    ///
+    /// It is applied before `import_map`.
+    pub webpack_externals: Option<WebpackExternalsVc>,
+    #[serde(default)]
     /// An additional resolved map to use after modules have been resolved.
     pub resolved_map: Option<ResolvedMapVc>,
     #[serde(default)]
@@ -0,0 +1,1227 @@
+use std::{collections::HashMap, fs, path::Path};
+
+use anyhow::{bail, Context, Result};
+use indexmap::IndexMap;
+use tempfile::TempDir;
+use turbo_tasks::{RawVc, TryJoinIterExt, TurboTasks, Value, ValueToString};
+use turbo_tasks_fs::{DiskFileSystemVc, File, FileSystem, FileSystemPathVc};
+use turbo_tasks_memory::MemoryBackend;
+use turbopack_core::{
+    asset::{Asset, AssetVc},
+    chunk::{
+        available_assets::AvailableAssetsVc, evaluatable_assets_from_requests, EvaluatableAssetsVc,
+    },
+    compile_time_info::CompileTimeInfoVc,
+    composite_output::CompositeOutputAssetVc,
+    context::{AssetContext, AssetContextVc},
+    emit::{emit_assets, EmitOptions, EmitSummary, EMIT_MANIFEST},
+    environment::{BrowserEnvironment, EnvironmentVc, ExecutionEnvironment, NodeJsEnvironment},
+    file_source::FileSourceVc,
+    issue::{IssueSeverity, IssueVc, PlainIssue, PlainIssueSource},
+    output::OutputAssetsVc,
+    raw_output::RawOutputVc,
+    reference::{AssetReference, AssetReferencesVc},
+    reference_type::{InnerAssetsVc, ReferenceType},
+    resolve::{origin::PlainResolveOriginVc, parse::RequestVc},
+    virtual_source::VirtualSourceVc,
+};
+
+use crate::{
+    ecmascript::{
+        chunk::{EcmascriptChunkPlaceable, EcmascriptChunkPlaceableVc},
+        typescript::declaration::{TypescriptReference, TypescriptReferenceKind},
+        AnalyzeEcmascriptModuleResultVc, EcmascriptInputTransformsVc, EcmascriptModuleAssetVc,
+        EcmascriptOptions,
+    },
+    emit_asset,
+    module_options::{ModuleOptionsContext, ModuleOptionsContextVc},
+    register,
+    resolve_options_context::{ResolveOptionsContext, ResolveOptionsContextVc},
+    transition::TransitionsByNameVc,
+    ModuleAssetContextVc,
+};
+
+/// Writes `files`, given as paths and contents, to a new temporary directory.
+pub(crate) fn write_files(files: &[(&str, &str)]) -> TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    for (file, content) in files {
+        let path = dir.path().join(file);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+    dir
+}
+
+/// The root of a disk file system named `project` which is rooted at `root`.
+pub(crate) fn project_root(root: String) -> FileSystemPathVc {
+    DiskFileSystemVc::new("project".to_string(), root).root()
+}
+
+/// An asset context for `environment` without transitions.
+pub(crate) fn asset_context(
+    environment: ExecutionEnvironment,
+    module_options_context: ModuleOptionsContextVc,
+    resolve_options_context: ResolveOptionsContextVc,
+) -> AssetContextVc {
+    ModuleAssetContextVc::new(
+        TransitionsByNameVc::cell(HashMap::new()),
+        CompileTimeInfoVc::new(EnvironmentVc::new(Value::new(environment))),
+        module_options_context,
+        resolve_options_context,
+    )
+    .into()
+}
+
+/// An asset context for Node.js lambdas without transitions.
+pub(crate) fn node_asset_context(
+    module_options_context: ModuleOptionsContextVc,
+    resolve_options_context: ResolveOptionsContextVc,
+) -> AssetContextVc {
+    asset_context(
+        ExecutionEnvironment::NodeJsLambda(NodeJsEnvironment::default().into()),
+        module_options_context,
+        resolve_options_context,
+    )
+}
+
+/// Processes the file at `path` as an asset which isn't referenced by another
+/// one.
+pub(crate) fn process(context: AssetContextVc, path: FileSystemPathVc) -> AssetVc {
+    context.process(
+        FileSourceVc::new(path).into(),
+        Value::new(ReferenceType::Undefined),
+    )
+}
+
+/// Processes the file at `path` like [process] and expects an ecmascript
+/// module.
+pub(crate) async fn ecmascript_module(
+    context: AssetContextVc,
+    path: FileSystemPathVc,
+) -> Result<EcmascriptModuleAssetVc> {
+    EcmascriptModuleAssetVc::resolve_from(process(context, path))
+        .await?
+        .context("expected an ecmascript module")
+}
+
+/// Returns the ident of `index.js` processed in a context with the given
+/// layer.
+async fn ident_in_layer(root: String, layer: Option<&'static str>) -> Result<String> {
+    let context = node_asset_context(
+        ModuleOptionsContext {
+            layer: layer.map(|layer| layer.to_string()),
+            ..Default::default()
+        }
+        .cell(),
+        ResolveOptionsContext::default().cell(),
+    );
+    let module = process(context, project_root(root).join("index.js"));
+    Ok(module.ident().to_string().await?.clone_value())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn layer_is_part_of_the_ident() {
+    register();
+
+    let dir = write_files(&[("index.js", "export default 1;\n")]);
+    let root = dir.path().to_string_lossy().to_string();
+
+    let tt = TurboTasks::new(MemoryBackend::default());
+
+    let mut idents = Vec::new();
+    for layer in [None, Some("server"), Some("client")] {
+        idents.push(
+            tt.run_once(ident_in_layer(root.clone(), layer))
+                .await
+                .unwrap(),
+        );
+    }
+
+    // Modules without a layer keep the plain modifier.
+    assert!(
+        idents[0].ends_with("index.js (ecmascript)"),
+        "{}",
+        idents[0]
+    );
+    assert!(
+        idents[1].ends_with("index.js (ecmascript (server))"),
+        "{}",
+        idents[1]
+    );
+    assert!(
+        idents[2].ends_with("index.js (ecmascript (client))"),
+        "{}",
+        idents[2]
+    );
+}
+
+/// Returns the export names of `file` and whether they are incomplete.
+async fn export_names(root: String, file: &'static str) -> Result<(Vec<String>, bool)> {
+    let context = node_asset_context(
+        ModuleOptionsContext::default().cell(),
+        ResolveOptionsContext::default().cell(),
+    );
+    let module = process(context, project_root(root).join(file));
+    let exports = EcmascriptChunkPlaceableVc::resolve_from(module)
+        .await?
+        .context("expected an ecmascript module")?
+        .get_exports();
+
+    Ok((
+        exports.names().await?.clone_value(),
+        *exports.is_incomplete().await?,
+    ))
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn export_names_of_modules() {
+    register();
+
+    let dir = write_files(&[
+        (
+            "esm.js",
+            "export const a = 1;\nexport default class {}\nexport * from './star.js';\n",
+        ),
+        (
+            "star.js",
+            "export function b() {}\nexport default 1;\nexport * from './nested.js';\n",
+        ),
+        ("nested.js", "export let c;\n"),
+        (
+            "dynamic.js",
+            "export const a = 1;\nexport * from './cjs.js';\n",
+        ),
+        ("cjs.js", "exports.d = 1;\n"),
+        (
+            "unresolved.js",
+            "export const a = 1;\nexport * from './missing.js';\n",
+        ),
+    ]);
+
+    let tt = TurboTasks::new(MemoryBackend::default());
+    let root = dir.path().to_string_lossy().to_string();
+
+    let (names, incomplete) = tt
+        .run_once(export_names(root.clone(), "esm.js"))
+        .await
+        .unwrap();
+    assert_eq!(names, vec!["a", "b", "c", "default"]);
+    assert!(!incomplete);
+
+    let (names, incomplete) = tt
+        .run_once(export_names(root.clone(), "dynamic.js"))
+        .await
+        .unwrap();
+    assert_eq!(names, vec!["a"]);
+    assert!(incomplete);
+
+    let (names, incomplete) = tt
+        .run_once(export_names(root.clone(), "unresolved.js"))
+        .await
+        .unwrap();
+    assert_eq!(names, vec!["a"]);
+    assert!(incomplete);
+
+    let (names, incomplete) = tt.run_once(export_names(root, "cjs.js")).await.unwrap();
+    assert!(names.is_empty());
+    assert!(incomplete);
+}
+
+/// Returns the type-level references of `file` as kind, request and the path
+/// of the target relative to `root`.
+async fn declaration_references(
+    root: String,
+    file: &'static str,
+) -> Result<Vec<(TypescriptReferenceKind, String, Option<String>)>> {
+    let root = project_root(root);
+
+    let context = node_asset_context(
+        ModuleOptionsContext::default().cell(),
+        ResolveOptionsContext {
+            enable_node_modules: Some(root),
+            ..Default::default()
+        }
+        .cell(),
+    );
+    let module = ecmascript_module(context, root.join(file)).await?;
+
+    let root = root.await?;
+    let mut references = Vec::new();
+    for TypescriptReference {
+        kind,
+        request,
+        target,
+    } in module.typescript_declaration_references().await?.iter()
+    {
+        let target = match target {
+            Some(target) => Some(
+                root.get_path_to(&*target.ident().path().await?)
+                    .context("target is outside of the project")?
+                    .to_string(),
+            ),
+            None => None,
+        };
+        references.push((*kind, request.clone(), target));
+    }
+    Ok(references)
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn typescript_declaration_references() {
+    register();
+
+    let dir = write_files(&[
+        (
+            "index.d.mts",
+            r#"/// <reference path="./globals.d.ts" />
+/// <reference types="pkg" />
+
+import type { Foo } from "./foo";
+import { Bar } from "./bar";
+export * from "./foo";
+
+export type Baz = import("./baz").Baz;
+
+export declare function make(foo: Foo, bar: Bar): Baz;
+
+declare module "pkg" {
+  interface Options {
+    extra: boolean;
+  }
+}
+
+declare module "missing" {
+  export const value: number;
+}
+"#,
+        ),
+        ("globals.d.ts", "declare const VERSION: string;\n"),
+        ("foo.d.mts", "export interface Foo { foo: string; }\n"),
+        ("bar.d.cts", "export interface Bar { bar: number; }\n"),
+        ("baz.d.ts", "export type Baz = { baz: boolean };\n"),
+        (
+            "ambient.d.ts",
+            "declare module \"ambient\" {\n  export const value: number;\n}\n",
+        ),
+        (
+            "module.ts",
+            "import type { Foo } from \"./foo\";\n\nexport const foo: Foo = { foo: \"foo\" };\n",
+        ),
+        (
+            "node_modules/pkg/package.json",
+            r#"{ "name": "pkg", "types": "index.d.ts" }"#,
+        ),
+        (
+            "node_modules/pkg/index.d.ts",
+            "export interface Options {\n  name: string;\n}\n",
+        ),
+    ]);
+
+    let tt = TurboTasks::new(MemoryBackend::default());
+    let root = dir.path().to_string_lossy().to_string();
+
+    let references = tt
+        .run_once(declaration_references(root.clone(), "index.d.mts"))
+        .await
+        .unwrap();
+    let reference = |kind, request: &str, target: Option<&str>| {
+        (kind, request.to_string(), target.map(|t| t.to_string()))
+    };
+    assert_eq!(
+        references,
+        vec![
+            reference(
+                TypescriptReferenceKind::ReferencePath,
+                "./globals.d.ts",
+                Some("globals.d.ts")
+            ),
+            reference(
+                TypescriptReferenceKind::ReferenceTypes,
+                "pkg",
+                Some("node_modules/pkg/index.d.ts")
+            ),
+            reference(TypescriptReferenceKind::Import, "./foo", Some("foo.d.mts")),
+            reference(TypescriptReferenceKind::Import, "./bar", Some("bar.d.cts")),
+            reference(TypescriptReferenceKind::Import, "./foo", Some("foo.d.mts")),
+            reference(TypescriptReferenceKind::Import, "./baz", Some("baz.d.ts")),
+            reference(
+                TypescriptReferenceKind::ModuleAugmentation,
+                "pkg",
+                Some("node_modules/pkg/index.d.ts")
+            ),
+            reference(TypescriptReferenceKind::ModuleAugmentation, "missing", None),
+        ]
+    );
+
+    // `declare module` in a script declares an ambient module.
+    let references = tt
+        .run_once(declaration_references(root.clone(), "ambient.d.ts"))
+        .await
+        .unwrap();
+    assert!(references.is_empty(), "{references:?}");
+
+    // Other modules have no type-level references.
+    let references = tt
+        .run_once(declaration_references(root, "module.ts"))
+        .await
+        .unwrap();
+    assert!(references.is_empty(), "{references:?}");
+}
+
+/// Returns the ident of `index.js` with the inner assets `names` inserted in
+/// the given order. Each name refers to the module `<name>.js`.
+async fn ident_with_inner_assets(root: String, names: Vec<&'static str>) -> Result<String> {
+    let root = project_root(root);
+    let context = node_asset_context(
+        ModuleOptionsContext::default().cell(),
+        ResolveOptionsContext::default().cell(),
+    );
+
+    let mut inner_assets = IndexMap::new();
+    for name in names {
+        let asset = process(context, root.join(&format!("{name}.js")));
+        inner_assets.insert(name.to_uppercase(), asset.into());
+    }
+
+    let source = FileSourceVc::new(root.join("index.js"));
+    let module = context.process(
+        source.into(),
+        Value::new(ReferenceType::Internal(InnerAssetsVc::cell(inner_assets))),
+    );
+    Ok(module.ident().to_string().await?.clone_value())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn inner_assets_ident_is_deterministic() {
+    register();
+
+    let dir = write_files(
+        &["index.js", "a.js", "b.js", "c.js"].map(|file| (file, "export default 1;\n")),
+    );
+    let root = dir.path().to_string_lossy().to_string();
+
+    let tt = TurboTasks::new(MemoryBackend::default());
+
+    let mut idents = Vec::new();
+    for names in [
+        vec!["a", "b", "c"],
+        vec!["c", "a", "b"],
+        vec!["b", "c", "a"],
+    ] {
+        idents.push(
+            tt.run_once(ident_with_inner_assets(root.clone(), names))
+                .await
+                .unwrap(),
+        );
+    }
+
+    let ident = &idents[0];
+    for other in &idents[1..] {
+        assert_eq!(ident, other);
+    }
+    let a = ident.find("/(A)/").unwrap();
+    let b = ident.find("/(B)/").unwrap();
+    let c = ident.find("/(C)/").unwrap();
+    assert!(a < b && b < c, "{ident}");
+}
+
+/// Runs the strict analysis of `file` in `root` and returns whether it was
+/// successful.
+async fn analyze_strict(root: String, file: &'static str) -> Result<bool> {
+    let context = node_asset_context(
+        ModuleOptionsContext::default().cell(),
+        ResolveOptionsContext::default().cell(),
+    );
+    let module = ecmascript_module(context, project_root(root).join(file)).await?;
+    Ok(module.analyze_strict().await?.successful)
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn analyze_strict_surfaces_failures() {
+    register();
+
+    let dir = write_files(&[
+        ("valid.js", "export default 1;\n"),
+        ("invalid.js", "export default {;\n"),
+    ]);
+    let root = dir.path().to_string_lossy().to_string();
+
+    let tt = TurboTasks::new(MemoryBackend::default());
+
+    let successful = tt
+        .run_once(analyze_strict(root.clone(), "valid.js"))
+        .await
+        .unwrap();
+    assert!(successful);
+
+    let error = tt
+        .run_once(analyze_strict(root, "invalid.js"))
+        .await
+        .unwrap_err();
+    let message = format!("{error:?}");
+    assert!(
+        message.contains("analysis of [project]/invalid.js failed"),
+        "{message}"
+    );
+    assert!(message.contains("- error (parse):"), "{message}");
+}
+
+const ENVIRONMENT_BRANCHES: &str = r#"
+if (typeof window === "undefined") {
+  require("fs");
+}
+if (typeof document !== "object") {
+  require("./server-typeof-document.js");
+}
+if (!process.browser) {
+  require("./server-process-browser.js");
+}
+if (typeof window !== "undefined") {
+  require("./client-typeof-window.js");
+}
+const isServer = typeof window === "undefined";
+if (isServer) {
+  require("./server-variable.js");
+}
+function shadowed(window) {
+  if (typeof window === "undefined") {
+    require("./shadowed.js");
+  }
+}
+"#;
+
+/// Returns the requests of the references of `index.js` analyzed for
+/// `environment`.
+async fn references(root: FileSystemPathVc, environment: ExecutionEnvironment) -> Result<String> {
+    let context = asset_context(
+        environment,
+        ModuleOptionsContext::default().cell(),
+        ResolveOptionsContext::default().cell(),
+    );
+    let module = ecmascript_module(context, root.join("index.js")).await?;
+    let mut references = Vec::new();
+    for reference in module.analyze().await?.references.await?.iter() {
+        references.push(reference.to_string().await?.clone_value());
+    }
+    Ok(references.join("\n"))
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn environment_branches() {
+    register();
+
+    let dir = write_files(&[("index.js", ENVIRONMENT_BRANCHES)]);
+    let root = dir.path().to_string_lossy().to_string();
+
+    let tt = TurboTasks::new(MemoryBackend::default());
+    tt.run_once(async move {
+        let root = project_root(root);
+
+        let browser = references(
+            root,
+            ExecutionEnvironment::Browser(
+                BrowserEnvironment {
+                    dom: true,
+                    web_worker: false,
+                    service_worker: false,
+                    browserslist_query: "last 1 Chrome versions".to_string(),
+                }
+                .into(),
+            ),
+        )
+        .await?;
+        for server_only in [
+            "require module \"fs\"",
+            "server-typeof-document",
+            "server-process-browser",
+            "server-variable",
+        ] {
+            assert!(!browser.contains(server_only), "{server_only}:\n{browser}");
+        }
+        assert!(browser.contains("client-typeof-window"), "{browser}");
+        // `window` is a parameter there, not the global.
+        assert!(browser.contains("shadowed"), "{browser}");
+
+        let node = references(
+            root,
+            ExecutionEnvironment::NodeJsLambda(NodeJsEnvironment::default().into()),
+        )
+        .await?;
+        for server_only in [
+            "require module \"fs\"",
+            "server-typeof-document",
+            "server-process-browser",
+            "server-variable",
+        ] {
+            assert!(node.contains(server_only), "{server_only}:\n{node}");
+        }
+        assert!(!node.contains("client-typeof-window"), "{node}");
+        assert!(node.contains("shadowed"), "{node}");
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
+
+/// Emits a chunk with its source map as one asset, and returns its ident.
+async fn emit_chunk(root: String, map_name: &'static str) -> Result<String> {
+    let root = project_root(root);
+
+    let asset = CompositeOutputAssetVc::new(vec![
+        (root.join("chunk.js"), File::from("chunk();").into()),
+        (root.join(map_name), File::from("{}").into()),
+    ])?;
+    emit_asset(asset.into()).await?;
+    Ok(asset.ident().to_string().await?.clone_value())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn composite_output_writes_all_files() {
+    register();
+
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path().to_string_lossy().to_string();
+
+    let tt = TurboTasks::new(MemoryBackend::default());
+    let ident = tt
+        .run_once(emit_chunk(root.clone(), "chunk.js.map"))
+        .await
+        .unwrap();
+    assert_eq!(
+        fs::read_to_string(dir.path().join("chunk.js")).unwrap(),
+        "chunk();"
+    );
+    assert_eq!(
+        fs::read_to_string(dir.path().join("chunk.js.map")).unwrap(),
+        "{}"
+    );
+
+    // The ident is the one of the primary file, with a hash of all paths.
+    assert!(ident.starts_with("[project]/chunk.js "), "{ident}");
+    let other_ident = tt.run_once(emit_chunk(root, "other.js.map")).await.unwrap();
+    assert_ne!(ident, other_ident);
+}
+
+/// Returns the paths of the direct dependencies of the module at `path`,
+/// relative to `root`, and the number of its references.
+async fn direct_dependencies(
+    context: AssetContextVc,
+    root: FileSystemPathVc,
+    path: FileSystemPathVc,
+) -> Result<(Vec<String>, usize)> {
+    let module = ecmascript_module(context, path).await?;
+
+    let root = root.await?;
+    let mut paths = Vec::new();
+    for dependency in module.direct_dependencies().await?.iter() {
+        let path = dependency.ident().path().await?;
+        paths.push(
+            root.get_path_to(&path)
+                .with_context(|| format!("{} is outside of the root", path.path))?
+                .to_string(),
+        );
+    }
+    Ok((paths, module.references().await?.len()))
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn direct_dependencies_in_source_order() {
+    register();
+
+    let dir = write_files(&[
+        (
+            "index.js",
+            r#"import { a } from "./a";
+import "./a";
+
+const { b } = require("./b");
+
+import("./c").then(({ c }) => console.log(a, b, c));
+
+//# sourceMappingURL=index.js.map
+"#,
+        ),
+        (
+            "index.js.map",
+            r#"{"version":3,"sources":["index.js"],"names":[],"mappings":""}"#,
+        ),
+        (
+            "a.js",
+            "import { b } from \"./b\";\n\nexport const a = \"a\" + b;\n",
+        ),
+        ("b.js", "exports.b = \"b\";\n"),
+        ("c.js", "export const c = \"c\";\n"),
+    ]);
+    let root = dir.path().to_string_lossy().to_string();
+
+    let tt = TurboTasks::new(MemoryBackend::default());
+    tt.run_once(async move {
+        let root = project_root(root);
+        let context = node_asset_context(
+            ModuleOptionsContext::default().cell(),
+            ResolveOptionsContext::default().cell(),
+        );
+
+        // `a` is imported twice, and the source map isn't a module.
+        let (dependencies, references) =
+            direct_dependencies(context, root, root.join("index.js")).await?;
+        assert_eq!(dependencies, ["a.js", "b.js", "c.js"]);
+        assert!(references > dependencies.len());
+
+        // `a` only imports `b`, and `b` imports nothing.
+        let (dependencies, _) = direct_dependencies(context, root, root.join("a.js")).await?;
+        assert_eq!(dependencies, ["b.js"]);
+        let (dependencies, _) = direct_dependencies(context, root, root.join("b.js")).await?;
+        assert!(dependencies.is_empty());
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
+
+/// An interop issue, with the positions of its source and of the related
+/// source as `line:column-line:column`. Lines are 0-based, columns are offset
+/// by one, as swc's byte positions start at 1.
+#[derive(Debug, PartialEq)]
+struct Interop {
+    title: String,
+    severity: IssueSeverity,
+    source: String,
+    related: Option<String>,
+}
+
+fn span(source: &PlainIssueSource) -> String {
+    format!(
+        "{}:{}-{}:{}",
+        source.start.line, source.start.column, source.end.line, source.end.column
+    )
+}
+
+impl From<&PlainIssue> for Interop {
+    fn from(issue: &PlainIssue) -> Self {
+        Interop {
+            title: issue.title.clone(),
+            severity: issue.severity,
+            source: issue.source.as_deref().map(span).unwrap_or_default(),
+            related: issue
+                .sub_issues
+                .first()
+                .and_then(|related| related.source.as_deref().map(span)),
+        }
+    }
+}
+
+/// The interop issues reported by the task of `source` and the tasks it
+/// spawned, which are awaited to complete.
+async fn interop_issues(source: impl Into<RawVc>) -> Result<Vec<Interop>> {
+    Ok(IssueVc::peek_issues_with_path(source.into())
+        .await?
+        .strongly_consistent()
+        .await?
+        .get_plain_issues()
+        .await?
+        .iter()
+        .filter(|issue| issue.category == "interop")
+        .map(|issue| Interop::from(&**issue))
+        .collect())
+}
+
+fn interop_context(strict_interop: bool) -> AssetContextVc {
+    node_asset_context(
+        ModuleOptionsContext {
+            ecmascript_strict_interop: strict_interop,
+            ..Default::default()
+        }
+        .cell(),
+        ResolveOptionsContext::default().cell(),
+    )
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn interop() {
+    register();
+
+    // The positions of the issues depend on the exact contents.
+    let dir = write_files(&[
+        (
+            "mixed.js",
+            "import dep from \"./esm.js\";\n\nmodule.exports = dep;\n",
+        ),
+        (
+            "require.js",
+            r#"const async = require("./async.js");
+const esm = require("./esm.js").default;
+
+module.exports = { async, esm };
+"#,
+        ),
+        (
+            "async.js",
+            "export const value = await Promise.resolve(\"async\");\n",
+        ),
+        ("esm.js", "export default \"esm\";\n"),
+    ]);
+    let root = dir.path().to_string_lossy().to_string();
+
+    let tt = TurboTasks::new(MemoryBackend::default());
+    tt.run_once(async move {
+        let root = project_root(root);
+
+        // `module.exports` is assigned below the import declaration.
+        let mixed = ecmascript_module(interop_context(false), root.join("mixed.js")).await?;
+        assert_eq!(
+            interop_issues(mixed.analyze()).await?,
+            [Interop {
+                title: "CommonJS exports are assigned in an EcmaScript module".to_string(),
+                severity: IssueSeverity::Warning,
+                source: "2:1-2:21".to_string(),
+                related: Some("0:1-1:0".to_string()),
+            }]
+        );
+
+        // Strict mode makes it an error.
+        let mixed = ecmascript_module(interop_context(true), root.join("mixed.js")).await?;
+        let issues = interop_issues(mixed.analyze()).await?;
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].severity, IssueSeverity::Error);
+
+        // The hazards of a `require()` are reported by a task spawned by its
+        // resolution, which doesn't wait for it.
+        let require = ecmascript_module(interop_context(false), root.join("require.js")).await?;
+        assert!(interop_issues(require.analyze()).await?.is_empty());
+        let mut issues = Vec::new();
+        for reference in require.references().await?.iter() {
+            issues.extend(interop_issues(reference.resolve_reference()).await?);
+        }
+        assert_eq!(
+            issues,
+            [
+                Interop {
+                    title: "An EcmaScript module with a top level await is required".to_string(),
+                    severity: IssueSeverity::Warning,
+                    source: "0:15-0:36".to_string(),
+                    related: Some("0:22-0:52".to_string()),
+                },
+                Interop {
+                    title: "`.default` is accessed on a required EcmaScript module".to_string(),
+                    severity: IssueSeverity::Warning,
+                    source: "1:13-1:40".to_string(),
+                    related: Some("1:13-1:32".to_string()),
+                },
+            ]
+        );
+
+        // An EcmaScript module without exports assignments isn't reported.
+        let esm = ecmascript_module(interop_context(false), root.join("esm.js")).await?;
+        assert!(interop_issues(esm.analyze()).await?.is_empty());
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
+
+/// Emits files with the given paths and contents to `root`. Every call uses
+/// new turbo tasks, like a new build.
+async fn emit(root: &Path, files: &[(&str, &str)], clean: bool) -> Result<EmitSummary> {
+    let root = root.to_string_lossy().to_string();
+    let files: Vec<(String, String)> = files
+        .iter()
+        .map(|&(path, content)| (path.to_string(), content.to_string()))
+        .collect();
+    let tt = TurboTasks::new(MemoryBackend::default());
+    tt.run_once(async move {
+        let root = DiskFileSystemVc::new("output".to_string(), root).root();
+        let assets = files
+            .into_iter()
+            .map(|(path, content)| {
+                let source = VirtualSourceVc::new(root.join(&path), File::from(content).into());
+                RawOutputVc::new(source.into()).into()
+            })
+            .collect();
+        emit_assets(OutputAssetsVc::cell(assets), root, EmitOptions { clean }).await
+    })
+    .await
+}
+
+fn summary(written: usize, skipped: usize, removed: usize) -> EmitSummary {
+    EmitSummary {
+        written,
+        skipped,
+        removed,
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn emit_assets_writes_skips_and_cleans() {
+    register();
+
+    let dir = write_files(&[("user.txt", "not emitted")]);
+    let root = dir.path();
+
+    let files = [("a.js", "a();"), ("dir/b.js", "b();")];
+    assert_eq!(emit(root, &files, true).await.unwrap(), summary(2, 0, 0));
+    assert_eq!(fs::read_to_string(root.join("a.js")).unwrap(), "a();");
+    assert_eq!(fs::read_to_string(root.join("dir/b.js")).unwrap(), "b();");
+    assert!(root.join(EMIT_MANIFEST).exists());
+
+    // Unchanged files aren't written again.
+    assert_eq!(emit(root, &files, true).await.unwrap(), summary(0, 2, 0));
+
+    // Without `clean`, files which aren't emitted anymore are kept, and still
+    // removed by a later call with `clean`.
+    assert_eq!(
+        emit(root, &[("a.js", "a();")], false).await.unwrap(),
+        summary(0, 1, 0)
+    );
+    assert!(root.join("dir/b.js").exists());
+    assert_eq!(
+        emit(root, &[("a.js", "a(1);")], true).await.unwrap(),
+        summary(1, 0, 1)
+    );
+    assert_eq!(fs::read_to_string(root.join("a.js")).unwrap(), "a(1);");
+    assert!(!root.join("dir/b.js").exists());
+
+    // Files which weren't emitted, or were changed since, are never removed.
+    fs::write(root.join("a.js"), "edited").unwrap();
+    assert_eq!(emit(root, &[], true).await.unwrap(), summary(0, 0, 0));
+    assert_eq!(fs::read_to_string(root.join("a.js")).unwrap(), "edited");
+    assert_eq!(
+        fs::read_to_string(root.join("user.txt")).unwrap(),
+        "not emitted"
+    );
+
+    // No temporary files are left behind.
+    let mut entries = fs::read_dir(root)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+        .collect::<Vec<_>>();
+    entries.sort();
+    assert_eq!(entries, [EMIT_MANIFEST, "a.js", "dir", "user.txt"]);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn emit_assets_rejects_case_collisions() {
+    register();
+
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path();
+
+    let err = emit(root, &[("Chunk.js", "a();"), ("chunk.js", "b();")], true)
+        .await
+        .unwrap_err();
+    assert!(
+        format!("{err:#}").contains("Chunk.js and chunk.js can't both be emitted"),
+        "{err:#}"
+    );
+    assert_eq!(fs::read_dir(root).unwrap().count(), 0);
+}
+
+/// Pins the analysis of `file` in `root` twice, and returns the number of pins
+/// after each pin and after dropping each of them.
+async fn pin_analysis(root: String, file: &'static str) -> Result<Vec<usize>> {
+    let context = node_asset_context(
+        ModuleOptionsContext::default().cell(),
+        ResolveOptionsContext::default().cell(),
+    );
+    let module = ecmascript_module(context, project_root(root).join(file)).await?;
+
+    let mut pins = vec![module.await?.analysis_pins()];
+    let first = module.pin_analysis().await?;
+    pins.push(module.await?.analysis_pins());
+    let second = module.pin_analysis().await?;
+    pins.push(module.await?.analysis_pins());
+    drop(first);
+    pins.push(module.await?.analysis_pins());
+    drop(second);
+    pins.push(module.await?.analysis_pins());
+    Ok(pins)
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn pin_analysis_is_reference_counted() {
+    register();
+
+    let dir = write_files(&[
+        ("valid.js", "export default 1;\n"),
+        ("invalid.js", "export default {;\n"),
+    ]);
+    let root = dir.path().to_string_lossy().to_string();
+
+    let tt = TurboTasks::new(MemoryBackend::default());
+
+    // A module can be pinned even when its analysis fails.
+    for file in ["valid.js", "invalid.js"] {
+        let pins = tt.run_once(pin_analysis(root.clone(), file)).await.unwrap();
+        assert_eq!(pins, [0, 1, 2, 1, 0], "{file}");
+    }
+}
+
+fn evaluatable_assets(root: FileSystemPathVc, requests: &[&str]) -> EvaluatableAssetsVc {
+    let context = node_asset_context(
+        ModuleOptionsContext::default().cell(),
+        ResolveOptionsContext::default().cell(),
+    );
+    let requests = requests
+        .iter()
+        .map(|request| RequestVc::parse(Value::new(request.to_string().into())))
+        .collect();
+    evaluatable_assets_from_requests(
+        PlainResolveOriginVc::new(context, root.join("_")).into(),
+        context,
+        requests,
+    )
+}
+
+/// Returns the file names of the evaluatable assets.
+async fn file_names(assets: EvaluatableAssetsVc) -> Result<Vec<String>> {
+    let mut names = Vec::new();
+    for asset in assets.await?.iter() {
+        names.push(asset.ident().path().await?.file_name().to_string());
+    }
+    Ok(names)
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn evaluatable_assets_from_requests_test() {
+    register();
+
+    let dir = write_files(&[
+        ("a.js", "console.log(\"a\");\n"),
+        ("b.js", "console.log(\"b\");\n"),
+        ("data.json", "{ \"data\": true }\n"),
+    ]);
+    let root = dir.path().to_string_lossy().to_string();
+
+    let tt = TurboTasks::new(MemoryBackend::default());
+    tt.run_once(async move {
+        let root = project_root(root);
+
+        // Duplicates are removed, keeping the order of the first request.
+        let assets = evaluatable_assets(root, &["./b.js", "./a.js", "./b.js"]);
+        assert_eq!(file_names(assets).await?, vec!["b.js", "a.js"]);
+        let assets = evaluatable_assets(root, &["./a.js", "./b.js"]);
+        assert_eq!(file_names(assets).await?, vec!["a.js", "b.js"]);
+
+        // The error names the request and what it resolved to.
+        let Err(err) = evaluatable_assets(root, &["./a.js", "./data.json"]).await else {
+            bail!("expected data.json not to be evaluatable");
+        };
+        let message = format!("{err:?}");
+        assert!(message.contains("cannot be evaluated"), "{message}");
+        assert!(message.contains("[project]/data.json"), "{message}");
+
+        let Err(err) = evaluatable_assets(root, &["./missing.js"]).await else {
+            bail!("expected missing.js not to resolve");
+        };
+        let message = format!("{err:?}");
+        assert!(
+            message.contains("could not be resolved") && message.contains("missing.js"),
+            "{message}"
+        );
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn available_assets_roots() {
+    register();
+
+    let dir = write_files(&[
+        ("a.js", "import \"./shared.js\";\n\nconsole.log(\"a\");\n"),
+        ("b.js", "console.log(\"b\");\n"),
+        ("c.js", "console.log(\"c\");\n"),
+        ("shared.js", "console.log(\"shared\");\n"),
+    ]);
+    let root = dir.path().to_string_lossy().to_string();
+
+    let tt = TurboTasks::new(MemoryBackend::default());
+    tt.run_once(async move {
+        let root = project_root(root);
+        let context = node_asset_context(
+            ModuleOptionsContext::default().cell(),
+            ResolveOptionsContext::default().cell(),
+        );
+
+        let a = process(context, root.join("a.js")).resolve().await?;
+        let b = process(context, root.join("b.js")).resolve().await?;
+        let c = process(context, root.join("c.js")).resolve().await?;
+        let shared = process(context, root.join("shared.js")).resolve().await?;
+
+        let available = AvailableAssetsVc::new(vec![a]);
+        assert_eq!(*available.all_roots().await?, vec![a]);
+
+        // `a` and `shared`, which is imported by `a`, are already available
+        // and don't become roots of the inner links.
+        let inner = available.with_roots(vec![b, a]).with_roots(vec![shared, c]);
+        assert_eq!(*inner.all_roots().await?, vec![a, b, c]);
+
+        // Batched queries answer the same as individual ones.
+        let assets = vec![a, b, c, shared];
+        assert_eq!(
+            *available.includes_all(assets.clone()).await?,
+            vec![true, false, false, true]
+        );
+        for available_assets in [available, inner] {
+            let included = assets
+                .iter()
+                .map(|&asset| async move { Ok(*available_assets.includes(asset).await?) })
+                .try_join()
+                .await?;
+            assert_eq!(
+                *available_assets.includes_all(assets.clone()).await?,
+                included
+            );
+        }
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
+
+/// Builds the module at `path` with the given extra ident modifiers and
+/// returns its ident.
+async fn ident_with_modifiers(path: FileSystemPathVc, modifiers: &[&str]) -> Result<String> {
+    let environment = EnvironmentVc::new(Value::new(ExecutionEnvironment::NodeJsLambda(
+        NodeJsEnvironment::default().into(),
+    )));
+    let context = node_asset_context(
+        ModuleOptionsContext::default().cell(),
+        ResolveOptionsContext::default().cell(),
+    );
+    let mut builder = EcmascriptModuleAssetVc::builder(
+        FileSourceVc::new(path).into(),
+        context,
+        EcmascriptInputTransformsVc::empty(),
+        EcmascriptOptions::default(),
+        CompileTimeInfoVc::new(environment),
+    );
+    for modifier in modifiers {
+        builder = builder.with_ident_modifier(modifier.to_string());
+    }
+    Ok(builder.build().ident().to_string().await?.to_string())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn ident_modifier() {
+    register();
+
+    let dir = write_files(&[("index.js", "export const flavor = \"default\";\n")]);
+    let root = dir.path().to_string_lossy().to_string();
+
+    let tt = TurboTasks::new(MemoryBackend::default());
+    tt.run_once(async move {
+        let path = project_root(root).join("index.js");
+
+        let default = ident_with_modifiers(path, &[]).await?;
+        assert!(default.ends_with("index.js (ecmascript)"), "{default}");
+
+        // The extra modifiers follow the ecmascript one, in a fixed order.
+        let variant = ident_with_modifiers(path, &["ssr", "rsc"]).await?;
+        assert!(
+            variant.ends_with("index.js (ecmascript, rsc, ssr)"),
+            "{variant}"
+        );
+        assert_eq!(ident_with_modifiers(path, &["rsc", "ssr"]).await?, variant);
+        assert_eq!(
+            ident_with_modifiers(path, &["rsc", "ssr", "rsc"]).await?,
+            variant
+        );
+
+        // Variants of the same module don't share an ident.
+        assert_ne!(
+            ident_with_modifiers(path, &["rsc"]).await?,
+            ident_with_modifiers(path, &["ssr"]).await?
+        );
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
+
+async fn analyze(
+    context: AssetContextVc,
+    path: FileSystemPathVc,
+) -> Result<AnalyzeEcmascriptModuleResultVc> {
+    Ok(ecmascript_module(context, path).await?.analyze())
+}
+
+async fn descriptions(references: AssetReferencesVc) -> Result<Vec<String>> {
+    references
+        .await?
+        .iter()
+        .map(|reference| async move { Ok(reference.to_string().await?.to_string()) })
+        .try_join()
+        .await
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn references_diff() {
+    register();
+
+    let dir = write_files(&[
+        (
+            "before.js",
+            "import a from \"./a.js\";\nimport b from \"./b.js\";\n\nconsole.log(a, b);\n",
+        ),
+        (
+            "unchanged.js",
+            r#"import a from "./a.js";
+import b from "./b.js";
+
+console.log(b, a);
+console.log("unrelated change");
+"#,
+        ),
+        (
+            "after.js",
+            "import b from \"./b.js\";\nimport c from \"./c.js\";\n\nconsole.log(b, c);\n",
+        ),
+        ("a.js", "export default \"a\";\n"),
+        ("b.js", "export default \"b\";\n"),
+        ("c.js", "export default \"c\";\n"),
+    ]);
+    let root = dir.path().to_string_lossy().to_string();
+
+    let tt = TurboTasks::new(MemoryBackend::default());
+    tt.run_once(async move {
+        let root = project_root(root);
+        let context = node_asset_context(
+            ModuleOptionsContext::default().cell(),
+            ResolveOptionsContext::default().cell(),
+        );
+
+        let before = analyze(context, root.join("before.js")).await?;
+        let unchanged = analyze(context, root.join("unchanged.js")).await?;
+        let after = analyze(context, root.join("after.js")).await?;
+
+        // The references of another module are different cells, but they are
+        // logically equal.
+        assert!(*before.references_diff(before).is_empty().await?);
+        assert!(*unchanged.references_diff(before).is_empty().await?);
+
+        let diff = after.references_diff(before).await?;
+        let added = descriptions(diff.added).await?;
+        let removed = descriptions(diff.removed).await?;
+        assert_eq!(added.len(), 1);
+        assert!(added[0].contains("./c.js"), "{added:?}");
+        assert_eq!(removed.len(), 1);
+        assert!(removed[0].contains("./a.js"), "{removed:?}");
+
+        // The diff is symmetric.
+        let reverse = before.references_diff(after).await?;
+        assert_eq!(descriptions(reverse.added).await?, removed);
+        assert_eq!(descriptions(reverse.removed).await?, added);
+
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
@@ -7,8 +7,11 @@ use std::env;
 
 use lazy_static::lazy_static;
 use regex::Regex;
-pub use reqwest::Response;
-use reqwest::{Method, RequestBuilder};
+use reqwest::{header::AUTHORIZATION, Method, RequestBuilder};
+pub use reqwest::{
+    header::{HeaderMap, HeaderName, HeaderValue},
+    Response,
+};
 use serde::{Deserialize, Serialize};
 use url::Url;
 
@@ -201,6 +204,34 @@ impl APIClient {
         request_builder
     }
 
+    /// Adds caller provided headers, e.g. for tracing, to the request.
+    /// `Authorization` is skipped, it can only be set from the token.
+    fn add_extra_headers(
+        mut request_builder: RequestBuilder,
+        extra_headers: &HeaderMap,
+    ) -> RequestBuilder {
+        for (name, value) in extra_headers {
+            if name != AUTHORIZATION {
+                request_builder = request_builder.header(name, value);
+            }
+        }
+
+        request_builder
+    }
+
+    /// Appends the names of the extra headers to the headers requested in a
+    /// preflight request.
+    fn preflight_request_headers(request_headers: &str, extra_headers: &HeaderMap) -> String {
+        let mut request_headers = request_headers.to_string();
+        for name in extra_headers.keys() {
+            if name != AUTHORIZATION {
+                request_headers.push_str(", ");
+                request_headers.push_str(name.as_str());
+            }
+        }
+        request_headers
+    }
+
     pub async fn get_caching_status(
         &self,
         token: &str,
@@ -263,13 +294,18 @@ impl APIClient {
         })
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub async fn put_artifact(
         &self,
         hash: &str,
-        artifact_body: &[u8],
+        artifact_body: impl Fn() -> reqwest::Body,
+        artifact_size: u64,
         duration: u32,
         tag: Option<&str>,
         token: &str,
+        team_id: &str,
+        team_slug: Option<&str>,
+        extra_headers: &HeaderMap,
     ) -> Result<()> {
         let mut request_url = self.make_url(&format!("/v8/artifacts/{}", hash));
         let mut allow_auth = true;
@@ -280,7 +316,11 @@ impl APIClient {
                     token,
                     &request_url,
                     "PUT",
-                    "Authorization, Content-Type, User-Agent, x-artifact-duration, x-artifact-tag",
+                    &Self::preflight_request_headers(
+                        "Authorization, Content-Type, User-Agent, x-artifact-duration, \
+                         x-artifact-tag",
+                        extra_headers,
+                    ),
                 )
                 .await?;
 
@@ -288,25 +328,34 @@ impl APIClient {
             request_url = preflight_response.location.to_string();
         }
 
-        let mut request_builder = self
-            .client
-            .put(&request_url)
-            .header("Content-Type", "application/octet-stream")
-            .header("x-artifact-duration", duration.to_string())
-            .header("User-Agent", self.user_agent.clone())
-            .body(artifact_body.to_vec());
-
-        // TODO: Add CI header
+        // The body may be a stream, which can only be sent once, so every
+        // attempt builds its own request.
+        let build_request = || {
+            let mut request_builder = self
+                .client
+                .put(&request_url)
+                .header("Content-Type", "application/octet-stream")
+                .header("Content-Length", artifact_size)
+                .header("x-artifact-duration", duration.to_string())
+                .header("User-Agent", self.user_agent.clone())
+                .body(artifact_body());
+
+            // TODO: Add CI header
+
+            if allow_auth {
+                request_builder =
+                    request_builder.header("Authorization", format!("Bearer {}", token));
+            }
 
-        if allow_auth {
-            request_builder = request_builder.header("Authorization", format!("Bearer {}", token));
-        }
+            if let Some(tag) = tag {
+                request_builder = request_builder.header("x-artifact-tag", tag);
+            }
 
-        if let Some(tag) = tag {
-            request_builder = request_builder.header("x-artifact-tag", tag);
-        }
+            request_builder = Self::add_team_params(request_builder, team_id, team_slug);
+            Self::add_extra_headers(request_builder, extra_headers)
+        };
 
-        retry::make_retryable_request(request_builder)
+        retry::make_retryable_request_with(build_request)
             .await?
             .error_for_status()?;
 
@@ -320,9 +369,18 @@ impl APIClient {
         team_id: &str,
         team_slug: Option<&str>,
         use_preflight: bool,
+        extra_headers: &HeaderMap,
     ) -> Result<Response> {
-        self.get_artifact(hash, token, team_id, team_slug, use_preflight, Method::GET)
-            .await
+        self.get_artifact(
+            hash,
+            token,
+            team_id,
+            team_slug,
+            use_preflight,
+            extra_headers,
+            Method::GET,
+        )
+        .await
     }
 
     pub async fn artifact_exists(
@@ -332,11 +390,21 @@ impl APIClient {
         team_id: &str,
         team_slug: Option<&str>,
         use_preflight: bool,
+        extra_headers: &HeaderMap,
     ) -> Result<Response> {
-        self.get_artifact(hash, token, team_id, team_slug, use_preflight, Method::HEAD)
-            .await
+        self.get_artifact(
+            hash,
+            token,
+            team_id,
+            team_slug,
+            use_preflight,
+            extra_headers,
+            Method::HEAD,
+        )
+        .await
     }
 
+    #[allow(clippy::too_many_arguments)]
     async fn get_artifact(
         &self,
         hash: &str,
@@ -344,6 +412,7 @@ impl APIClient {
         team_id: &str,
         team_slug: Option<&str>,
         use_preflight: bool,
+        extra_headers: &HeaderMap,
         method: Method,
     ) -> Result<Response> {
         let mut request_url = self.make_url(&format!("/v8/artifacts/{}", hash));
@@ -351,7 +420,12 @@ impl APIClient {
 
         if use_preflight {
             let preflight_response = self
-                .do_preflight(token, &request_url, "GET", "Authorization, User-Agent")
+                .do_preflight(
+                    token,
+                    &request_url,
+                    "GET",
+                    &Self::preflight_request_headers("Authorization, User-Agent", extra_headers),
+                )
                 .await?;
 
             allow_auth = preflight_response.allow_authorization_header;
@@ -368,6 +442,7 @@ impl APIClient {
         }
 
         request_builder = Self::add_team_params(request_builder, team_id, team_slug);
+        request_builder = Self::add_extra_headers(request_builder, extra_headers);
 
         let response = retry::make_retryable_request(request_builder)
             .await?
@@ -459,7 +534,7 @@ mod test {
     use anyhow::Result;
     use vercel_api_mock::start_test_server;
 
-    use crate::APIClient;
+    use crate::{APIClient, HeaderMap};
 
     #[tokio::test]
     async fn test_do_preflight() -> Result<()> {
@@ -518,4 +593,34 @@ mod test {
         handle.abort();
         Ok(())
     }
+
+    #[test]
+    fn test_extra_headers_cannot_override_authorization() -> Result<()> {
+        let client = APIClient::new("http://localhost", 200, "2.0.0", true)?;
+
+        let mut extra_headers = HeaderMap::new();
+        extra_headers.insert("Authorization", "Bearer other".parse()?);
+        extra_headers.insert("traceparent", "trace".parse()?);
+
+        let request_builder = client
+            .client
+            .get(client.make_url("/v8/artifacts/hash"))
+            .header("Authorization", "Bearer token");
+        let request = APIClient::add_extra_headers(request_builder, &extra_headers).build()?;
+
+        let authorization = request
+            .headers()
+            .get_all("Authorization")
+            .iter()
+            .collect::<Vec<_>>();
+        assert_eq!(authorization, ["Bearer token"]);
+        assert_eq!(request.headers().get("traceparent").unwrap(), "trace");
+
+        assert_eq!(
+            APIClient::preflight_request_headers("Authorization, User-Agent", &extra_headers),
+            "Authorization, User-Agent, traceparent"
+        );
+
+        Ok(())
+    }
 }
@@ -15,15 +15,23 @@ const RETRY_MAX: u32 = 2;
 ///
 /// * `request_builder`: The request builder with everything, i.e. headers and
 ///   body already set. NOTE: This must be cloneable, so no streams are allowed.
+///   Use `make_retryable_request_with` for streamed bodies.
 ///
 /// returns: Result<Response, Error>
 pub(crate) async fn make_retryable_request(
     request_builder: RequestBuilder,
+) -> Result<Response, Error> {
+    make_retryable_request_with(|| request_builder.try_clone().expect("cannot clone request")).await
+}
+
+/// Like `make_retryable_request`, but builds a new request for every attempt,
+/// so the body may be a stream, which can only be sent once.
+pub(crate) async fn make_retryable_request_with(
+    build_request: impl Fn() -> RequestBuilder,
 ) -> Result<Response, Error> {
     let mut last_error = None;
     for retry_count in 0..RETRY_MAX {
-        let builder = request_builder.try_clone().expect("cannot clone request");
-        match builder.send().await {
+        match build_request().send().await {
             Ok(value) => return Ok(value),
             Err(err) => {
                 if !should_retry_request(&err) {
@@ -1,15 +1,21 @@
 use std::{
     backtrace::Backtrace,
+    collections::HashSet,
     fs,
-    fs::OpenOptions,
-    io::{BufWriter, Read, Write},
+    fs::{File, OpenOptions},
+    io::{BufWriter, Read, Seek, SeekFrom, Write},
     path::Path,
 };
 
 use tar::{EntryType, Header};
-use turbopath::{AbsoluteSystemPath, AnchoredSystemPath, RelativeUnixPathBuf};
+use turbopath::{
+    AbsoluteSystemPath, AnchoredSystemPath, AnchoredSystemPathBuf, RelativeUnixPathBuf,
+};
 
-use crate::CacheError;
+use crate::{
+    cache_archive::metadata::{ArtifactMetadata, ARTIFACT_METADATA_PATH},
+    CacheError,
+};
 
 pub struct CacheWriter<'a> {
     builder: tar::Builder<Box<dyn Write + 'a>>,
@@ -30,17 +36,32 @@ impl<'a> CacheWriter<'a> {
         Ok(self.builder.finish()?)
     }
 
+    /// Creates a new artifact, starting with its [`ArtifactMetadata`] entry.
+    ///
+    /// Versions of turbo from before the metadata entry was introduced don't
+    /// skip it, and restore it as `.turbo-artifact.json` into the outputs of
+    /// the task. Artifacts written by this version shouldn't be shared with
+    /// them through a remote cache until they are upgraded.
     pub fn from_writer(writer: impl Write + 'a, use_compression: bool) -> Result<Self, CacheError> {
-        if use_compression {
+        let mut cache_writer = if use_compression {
             let zw = zstd::Encoder::new(writer, 0)?.auto_finish();
-            Ok(CacheWriter {
+            CacheWriter {
                 builder: tar::Builder::new(Box::new(zw)),
-            })
+            }
         } else {
-            Ok(CacheWriter {
+            CacheWriter {
                 builder: tar::Builder::new(Box::new(writer)),
-            })
-        }
+            }
+        };
+        cache_writer.add_metadata()?;
+        Ok(cache_writer)
+    }
+
+    // Writes the metadata entry, which has to be the first entry of a new
+    // artifact.
+    fn add_metadata(&mut self) -> Result<(), CacheError> {
+        let (mut header, body) = ArtifactMetadata::default().to_entry()?;
+        self.append_data(&mut header, ARTIFACT_METADATA_PATH, &body[..])
     }
 
     // Makes a new CacheArchive at the specified path
@@ -57,30 +78,202 @@ impl<'a> CacheWriter<'a> {
 
         let is_compressed = path.extension() == Some("zst");
 
-        if is_compressed {
-            let zw = zstd::Encoder::new(file_buffer, 0)?.auto_finish();
+        Self::from_writer(file_buffer, is_compressed)
+    }
 
-            Ok(CacheWriter {
-                builder: tar::Builder::new(Box::new(zw)),
+    /// Opens an existing uncompressed artifact to add more files to it.
+    ///
+    /// The artifact must end with the tar terminator (at least two zeroed
+    /// blocks) after its last entry. The writer is positioned before the
+    /// terminator, which is written again by [`CacheWriter::finish`].
+    ///
+    /// Compressed artifacts can't be appended to in place, since the
+    /// terminator is part of the compressed stream. Use
+    /// [`CacheWriter::append_files`] for them instead.
+    pub fn open_existing(path: &AbsoluteSystemPath) -> Result<Self, CacheError> {
+        if path.extension() == Some("zst") {
+            return Err(CacheError::AppendToCompressedArtifact(
+                path.to_string(),
+                Backtrace::capture(),
+            ));
+        }
+
+        let mut options = OpenOptions::new();
+        options.read(true).write(true);
+        let mut file = path.open_with_options(options)?;
+
+        let end = Self::validate_terminator(&mut file)?;
+        file.set_len(end)?;
+        file.seek(SeekFrom::Start(end))?;
+
+        let file_buffer = BufWriter::with_capacity(2usize.pow(20), file);
+        Ok(CacheWriter {
+            builder: tar::Builder::new(Box::new(file_buffer)),
+        })
+    }
+
+    // Returns the offset of the end of the last entry, checking that only
+    // the zeroed blocks of the terminator follow it.
+    fn validate_terminator(file: &mut File) -> Result<u64, CacheError> {
+        let mut end = 0;
+        for entry in tar::Archive::new(&*file).entries()? {
+            let entry = entry?;
+            // The data of an entry is padded to a full block.
+            end = entry.raw_file_position() + (entry.size() + 511) / 512 * 512;
+        }
+
+        let mut trailer = Vec::new();
+        file.seek(SeekFrom::Start(end))?;
+        file.read_to_end(&mut trailer)?;
+        if trailer.len() < 1024 || trailer.len() % 512 != 0 || trailer.iter().any(|&b| b != 0) {
+            return Err(CacheError::MalformedTar(Backtrace::capture()));
+        }
+
+        Ok(end)
+    }
+
+    /// Adds `files` to the existing artifact at `path`. If a file is already
+    /// in the artifact, the added entry replaces it.
+    ///
+    /// Uncompressed artifacts are appended to in place when none of the files
+    /// are already in them. Otherwise, e.g. for compressed artifacts which
+    /// can't be appended to in place, the artifact is rewritten by streaming
+    /// its entries, without the replaced ones, into a new artifact with the
+    /// same compression, followed by `files`. The new artifact then replaces
+    /// the original one.
+    pub fn append_files(
+        path: &AbsoluteSystemPath,
+        anchor: &AbsoluteSystemPath,
+        files: &[AnchoredSystemPathBuf],
+    ) -> Result<(), CacheError> {
+        let is_compressed = path.extension() == Some("zst");
+        let appended = files
+            .iter()
+            .map(|file| {
+                let file = RelativeUnixPathBuf::new(file.as_str())?;
+                Ok(tar_path_key(file.as_str()).to_string())
             })
+            .collect::<Result<HashSet<_>, CacheError>>()?;
+
+        if !is_compressed && !Self::contains_any(path, &appended)? {
+            let mut writer = Self::open_existing(path)?;
+            for file in files {
+                writer.add_file(anchor, file)?;
+            }
+            return writer.finish();
+        }
+
+        let file_name = path
+            .as_path()
+            .file_name()
+            .ok_or_else(|| CacheError::InvalidFilePath(path.to_string(), Backtrace::capture()))?;
+        let temp_path = path
+            .parent()
+            .ok_or_else(|| CacheError::InvalidFilePath(path.to_string(), Backtrace::capture()))?
+            .join_component(&format!("{file_name}.tmp"));
+
+        let result = Self::rewrite(path, &temp_path, is_compressed, &appended, anchor, files);
+        if result.is_err() {
+            let _ = temp_path.remove_file();
+            return result;
+        }
+        fs::rename(&temp_path, path)?;
+
+        Ok(())
+    }
+
+    // Returns whether the uncompressed artifact at `path` contains any of
+    // the `paths`.
+    fn contains_any(
+        path: &AbsoluteSystemPath,
+        paths: &HashSet<String>,
+    ) -> Result<bool, CacheError> {
+        for entry in tar::Archive::new(path.open()?).entries()? {
+            let entry = entry?;
+            if paths.contains(tar_path_key(&entry.path()?.to_string_lossy())) {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    // Writes the entries of the artifact at `path` that aren't `replaced`,
+    // followed by `files`, to a new artifact at `temp_path`.
+    fn rewrite(
+        path: &AbsoluteSystemPath,
+        temp_path: &AbsoluteSystemPath,
+        is_compressed: bool,
+        replaced: &HashSet<String>,
+        anchor: &AbsoluteSystemPath,
+        files: &[AnchoredSystemPathBuf],
+    ) -> Result<(), CacheError> {
+        let original = path.open()?;
+        let reader: Box<dyn Read> = if is_compressed {
+            Box::new(zstd::Decoder::new(original)?)
         } else {
-            Ok(CacheWriter {
-                builder: tar::Builder::new(Box::new(file_buffer)),
-            })
+            Box::new(original)
+        };
+        let mut archive = tar::Archive::new(reader);
+
+        let mut options = OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        let file = temp_path.open_with_options(options)?;
+        let file_buffer = BufWriter::with_capacity(2usize.pow(20), file);
+        let mut writer = Self::from_writer(file_buffer, is_compressed)?;
+
+        for entry in archive.entries()? {
+            let mut entry = entry?;
+            let entry_path = entry.path()?.into_owned();
+            // The new artifact already starts with its own metadata.
+            if entry_path == Path::new(ARTIFACT_METADATA_PATH)
+                || replaced.contains(tar_path_key(&entry_path.to_string_lossy()))
+            {
+                continue;
+            }
+            let mut header = entry.header().clone();
+            writer.append_data(&mut header, entry_path, &mut entry)?;
+        }
+        for file in files {
+            writer.add_file(anchor, file)?;
         }
+
+        writer.finish()
     }
 
-    // Adds a user-cached item to the tar
+    // Adds a user-cached item to the tar, along with its mode
     pub(crate) fn add_file(
         &mut self,
         anchor: &AbsoluteSystemPath,
         file_path: &AnchoredSystemPath,
+    ) -> Result<(), CacheError> {
+        self.add_file_with(anchor, file_path, false)
+    }
+
+    // Adds a user-cached item to the tar like `add_file`, but symlinks are
+    // followed, so the item the symlink points to is added in its place.
+    pub(crate) fn add_file_following_symlinks(
+        &mut self,
+        anchor: &AbsoluteSystemPath,
+        file_path: &AnchoredSystemPath,
+    ) -> Result<(), CacheError> {
+        self.add_file_with(anchor, file_path, true)
+    }
+
+    fn add_file_with(
+        &mut self,
+        anchor: &AbsoluteSystemPath,
+        file_path: &AnchoredSystemPath,
+        follow_symlinks: bool,
     ) -> Result<(), CacheError> {
         // Resolve the fully-qualified path to the file to read it.
         let source_path = anchor.resolve(file_path);
 
         // Grab the file info to construct the header.
-        let file_info = source_path.symlink_metadata()?;
+        let file_info = if follow_symlinks {
+            fs::metadata(source_path.as_path())?
+        } else {
+            source_path.symlink_metadata()?
+        };
 
         // Normalize the path within the cache
         let mut file_path = RelativeUnixPathBuf::new(file_path.as_str())?;
@@ -145,6 +338,12 @@ impl<'a> CacheWriter<'a> {
     }
 }
 
+// Directories are stored with a trailing slash in the tar, so it's removed
+// to compare the paths of entries.
+fn tar_path_key(path: &str) -> &str {
+    path.trim_end_matches('/')
+}
+
 #[cfg(test)]
 mod tests {
     use std::path::PathBuf;
@@ -242,9 +441,9 @@ mod tests {
            file_type: FileType::File,
          }
       ],
-      "bf0b4bf722f8d845dce7627606ab8af30bb6454d7c0379219e4c036a484960fe78e3d98e29ca0bac9b69b858d446b89d2d691c524e2884389032be799b6699f6",
-      "bf0b4bf722f8d845dce7627606ab8af30bb6454d7c0379219e4c036a484960fe78e3d98e29ca0bac9b69b858d446b89d2d691c524e2884389032be799b6699f6",
-      "4f1357753cceec5df1c8a36110ce256f3e8c5c1f62cab3283013b6266d6e97b3884711ccdd45462a4607bee7ac7a8e414d0acea4672a9f0306bcf364281edc2f",
+      "5e67fb00a943ce03e5c5e1936cec41238701c4632418eeb87463337e9766e80d59801e8712047e9f20dc11f10113692de7fc87d3eb67391b8cacae4e39fbed5d",
+      "5e67fb00a943ce03e5c5e1936cec41238701c4632418eeb87463337e9766e80d59801e8712047e9f20dc11f10113692de7fc87d3eb67391b8cacae4e39fbed5d",
+      "f75ff6035c4b634a31c52f5b77e86117906f70f4bf791a4499b000bc9a577789184b7805d3e35b6d975e4f6d44e71bcb5a6c8041f19bfa5eeb8a6fc6288f2145",
       None
     )]
     #[test_case(
@@ -270,9 +469,9 @@ mod tests {
                 file_type: FileType::File,
             }
         ],
-        "2e6febdd2e8180f91f481ae58510e4afd3f071e66b7b64d82616ebb2d2d560b9a8a814e41f723cdaa5faec90405818421d590fcf8e617df0aabaa6fc61427d4f",
-        "0ece16efdb0b7e2a087e622ed52f29f21a4c080d77c31c4ed940b57dcdcb1f60b910d15232c0a2747325c22dadbfd069f15de969626dc49746be2d4b9b22e239",
-        "2e8ad9651964faa76082306dc95bff86fa0db821681e7a8acb982244ce0a9375417e867c3a9cb82f70bc6f03c7fb085e402712d3e9f27b980d5a0c22e086f4e2",
+        "a73f2ecb901f9ae41613ce57933fef51dae961e121e8aeb6ee257f467a11e58e2b68f427c1e3d2ca330f910a6bfea6956ad3f21004fdd0bd154d98209930177d",
+        "b27a5fdac9972976346423f59997f879fa4fe5a3b279fb2f200f3a4f7b03a6c2a44896e37b33993eb67538fa90ac9de6783656bd66134d8f6f9af32fa6cc748d",
+        "9654dc6095bd872b8b76c652105fd8860e196137879a4048407cf4b01722db633714389a76890bea8c27190995c64f68439bad120492495bc11be779420d072b",
         None
     )]
     #[test_case(
@@ -288,9 +487,9 @@ mod tests {
                 file_type: FileType::File,
             },
         ],
-        "027346e0349f948c0a2e7e9badb67d27fcc8ff4d5eacff1e5dd6a09c23a54d6793bf7ef1f25c9ed6b8c74f49d86d7b87478b7a00e24ea72e2ed2cadc0286c761",
-        "027346e0349f948c0a2e7e9badb67d27fcc8ff4d5eacff1e5dd6a09c23a54d6793bf7ef1f25c9ed6b8c74f49d86d7b87478b7a00e24ea72e2ed2cadc0286c761",
-        "1a2b32fe2b252ec622e5a15af21b274d702faa623d09c6fc51a44e7562cc84ac8b8c368d98d284dfb6666680ee252b071d5fbff44564a952ebaa12fe6f389e68",
+        "459670a5bdd88811aadcc28f8a3b9b90106cb82ca9ddb2e63400c8951b0d0dbe07f70e874c52d5978c88c8d20f1be6d80caa6fa5b4bfe792fe0f377e3d0dbd75",
+        "459670a5bdd88811aadcc28f8a3b9b90106cb82ca9ddb2e63400c8951b0d0dbe07f70e874c52d5978c88c8d20f1be6d80caa6fa5b4bfe792fe0f377e3d0dbd75",
+        "9ca403cabb1000a60125828e06d78406a4f1536effa53df35b2795adf83dc792a2d5013402f1479a61db5a7a2b64ccaae1ad5a88334ab794de82dde50fabb187",
         None
     )]
     #[test_case(
@@ -301,9 +500,9 @@ mod tests {
                 file_type: FileType::Symlink { linkname: "two".to_string() },
             },
         ],
-        "40ce0d42109bb5e5a6b1d4ba9087a317b4c1c6c51822a57c9cb983f878b0ff765637c05fadd4bac32c8dd2b496c2a24825b183d9720b0cdd5b33f9248b692cc1",
-        "c113763393a9fb498cc676e1fe4843206cda665afe2144829fe7434da9e81f0cf6d11386fa79877d3c514d108f9696740256af952b57d32216fbed2eb2fb049d",
-        "fe692a000551a60da6cc303a9552a16d7ed5c462e33153a96824e96596da6d642fc671448f06f34e9685a13fe5bbb4220f59db73a856626b8a0962916a8f5ea3",
+        "22b4fe1c568a83d350128f68b4b33f4e11e14859eb89ec54f98c6cc05e6bed83b5f693d756abd748be160a980173bcf622e8907c05f61eb477f573f60af23fdb",
+        "b70e811c2d28eecc3d959580f8da026765aa6cc145697671ffb97386bf4e318b1bbfaaea853aed3a63e0770be65a21c2d89e150c81faa1bf737282368cabe8a2",
+        "0d9a327e008672eada6f9b5a9c8fdb27ba35c0e6964b85ae59eb4d27f6fe98baaeec3f5a1bfab6b3e53bf6c02a2f0c8ddb00fa5b26603e51de0f9d9e79b757d3",
         None
     )]
     #[test_case(
@@ -417,6 +616,135 @@ mod tests {
         Ok(())
     }
 
+    fn create_archive(
+        archive_path: &AbsoluteSystemPath,
+        anchor: &AbsoluteSystemPath,
+        files: &[&AnchoredSystemPathBuf],
+    ) -> Result<()> {
+        let mut archive = CacheWriter::create(archive_path)?;
+        for file in files {
+            archive.add_file(anchor, file)?;
+        }
+        archive.finish()?;
+        Ok(())
+    }
+
+    fn read_restored(anchor: &AbsoluteSystemPath, file: &AnchoredSystemPathBuf) -> Result<String> {
+        Ok(fs::read_to_string(anchor.resolve(file).as_path())?)
+    }
+
+    #[test_case(false ; "uncompressed")]
+    #[test_case(true ; "compressed")]
+    fn test_append_files(compressed: bool) -> Result<()> {
+        let input_dir = tempdir()?;
+        let archive_dir = tempdir()?;
+        let output_dir = tempdir()?;
+        let input = AbsoluteSystemPathBuf::try_from(input_dir.path())?;
+        let output = AbsoluteSystemPathBuf::try_from(output_dir.path())?;
+        let archive_path =
+            AbsoluteSystemPathBuf::try_from(archive_dir.path().join(if compressed {
+                "out.tar.zst"
+            } else {
+                "out.tar"
+            }))?;
+
+        let built = AnchoredSystemPathBuf::from_raw("dist/built.js")?;
+        let log = AnchoredSystemPathBuf::from_raw("build.log")?;
+        let processed = AnchoredSystemPathBuf::from_raw("dist/processed.js")?;
+
+        input.resolve(&built).ensure_dir()?;
+        input.resolve(&built).create_with_contents("built")?;
+        input.resolve(&log).create_with_contents("build")?;
+        create_archive(&archive_path, &input, &[&built, &log])?;
+
+        // The second phase replaces the log and adds a file.
+        input
+            .resolve(&log)
+            .create_with_contents("build\npost-process")?;
+        input
+            .resolve(&processed)
+            .create_with_contents("processed")?;
+        CacheWriter::append_files(&archive_path, &input, &[log.clone(), processed.clone()])?;
+
+        let mut restored = CacheReader::open(&archive_path)?.restore(&output)?;
+        restored.sort();
+        let mut expected = vec![built.clone(), log.clone(), processed.clone()];
+        expected.sort();
+        assert_eq!(restored, expected);
+
+        assert_eq!(read_restored(&output, &built)?, "built");
+        assert_eq!(read_restored(&output, &log)?, "build\npost-process");
+        assert_eq!(read_restored(&output, &processed)?, "processed");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_open_existing() -> Result<()> {
+        let input_dir = tempdir()?;
+        let archive_dir = tempdir()?;
+        let output_dir = tempdir()?;
+        let input = AbsoluteSystemPathBuf::try_from(input_dir.path())?;
+        let output = AbsoluteSystemPathBuf::try_from(output_dir.path())?;
+        let archive_path = AbsoluteSystemPathBuf::try_from(archive_dir.path().join("out.tar"))?;
+
+        let first = AnchoredSystemPathBuf::from_raw("first.txt")?;
+        let second = AnchoredSystemPathBuf::from_raw("second.txt")?;
+        input.resolve(&first).create_with_contents("first")?;
+        input.resolve(&second).create_with_contents("second")?;
+        create_archive(&archive_path, &input, &[&first])?;
+        let original_len = fs::metadata(archive_path.as_path())?.len();
+
+        let mut archive = CacheWriter::open_existing(&archive_path)?;
+        archive.add_file(&input, &second)?;
+        archive.finish()?;
+        // The new entry is written over the original terminator: a header
+        // block and a data block.
+        assert_eq!(
+            fs::metadata(archive_path.as_path())?.len(),
+            original_len + 1024
+        );
+
+        let restored = CacheReader::open(&archive_path)?.restore(&output)?;
+        assert_eq!(restored, vec![first.clone(), second.clone()]);
+        assert_eq!(read_restored(&output, &first)?, "first");
+        assert_eq!(read_restored(&output, &second)?, "second");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_open_existing_invalid() -> Result<()> {
+        let input_dir = tempdir()?;
+        let archive_dir = tempdir()?;
+        let input = AbsoluteSystemPathBuf::try_from(input_dir.path())?;
+        let file = AnchoredSystemPathBuf::from_raw("file.txt")?;
+        input.resolve(&file).create_with_contents("contents")?;
+
+        let compressed_path =
+            AbsoluteSystemPathBuf::try_from(archive_dir.path().join("out.tar.zst"))?;
+        create_archive(&compressed_path, &input, &[&file])?;
+        assert!(matches!(
+            CacheWriter::open_existing(&compressed_path),
+            Err(CacheError::AppendToCompressedArtifact(..))
+        ));
+
+        // An archive without its terminator.
+        let truncated_path = AbsoluteSystemPathBuf::try_from(archive_dir.path().join("out.tar"))?;
+        create_archive(&truncated_path, &input, &[&file])?;
+        let len = fs::metadata(truncated_path.as_path())?.len();
+        fs::OpenOptions::new()
+            .write(true)
+            .open(truncated_path.as_path())?
+            .set_len(len - 1024)?;
+        assert!(matches!(
+            CacheWriter::open_existing(&truncated_path),
+            Err(CacheError::MalformedTar(..))
+        ));
+
+        Ok(())
+    }
+
     #[test]
     fn test_compression() -> Result<()> {
         let mut buffer = Vec::new();
@@ -0,0 +1,118 @@
+use std::{backtrace::Backtrace, io::Read, path::Path};
+
+use serde::{Deserialize, Serialize};
+use tar::{EntryType, Header};
+
+use crate::CacheError;
+
+/// The path of the metadata entry which is written as first entry of every
+/// artifact. Artifacts written before it was introduced don't have it. It's
+/// never restored, neither by [`super::CacheReader`] nor by the Go
+/// `cacheitem` reader, which need to be kept in sync.
+pub const ARTIFACT_METADATA_PATH: &str = ".turbo-artifact.json";
+
+/// The version of the artifact layout written by [`super::CacheWriter`].
+/// Bump it whenever the layout changes.
+pub const ARTIFACT_SCHEMA_VERSION: u32 = 1;
+
+/// The oldest reader version which can restore the artifacts written by
+/// [`super::CacheWriter`]. Only bump it for changes that older readers would
+/// restore incorrectly, additive changes keep it.
+pub const MIN_READER_VERSION: u32 = 1;
+
+/// The newest reader version this build implements. Artifacts which require a
+/// newer reader aren't restored. It's independent of
+/// [`ARTIFACT_SCHEMA_VERSION`], so a build can read a newer layout before it
+/// writes it.
+pub const SUPPORTED_READER_VERSION: u32 = 1;
+
+/// The versions of an artifact, stored in its [`ARTIFACT_METADATA_PATH`]
+/// entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArtifactMetadata {
+    /// The version of the layout the artifact was written with.
+    pub schema_version: u32,
+    /// The oldest reader version which can restore the artifact.
+    pub min_reader_version: u32,
+}
+
+/// The formats of the archive which this reader can decode. The format is
+/// selected by the first entry of the archive, see
+/// [`ArchiveVersion::of_first_entry`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveVersion {
+    /// Artifacts written before the metadata entry was introduced. All of
+    /// their entries belong to the artifact, even one at
+    /// [`ARTIFACT_METADATA_PATH`].
+    V0,
+    /// Artifacts starting with an [`ArtifactMetadata`] entry, which only
+    /// describes the artifact and isn't restored.
+    V1,
+}
+
+impl ArchiveVersion {
+    /// Selects the format of an archive by its first entry, reading the
+    /// metadata if the entry holds it. Returns whether it did, in which case
+    /// the entry isn't part of the artifact.
+    pub(crate) fn of_first_entry(
+        path: &Path,
+        body: impl Read,
+    ) -> Result<(ArchiveVersion, bool), CacheError> {
+        if path != Path::new(ARTIFACT_METADATA_PATH) {
+            return Ok((ArchiveVersion::V0, false));
+        }
+        let version = ArtifactMetadata::from_reader(body)?.archive_version()?;
+        Ok((version, true))
+    }
+}
+
+impl Default for ArtifactMetadata {
+    fn default() -> Self {
+        ArtifactMetadata {
+            schema_version: ARTIFACT_SCHEMA_VERSION,
+            min_reader_version: MIN_READER_VERSION,
+        }
+    }
+}
+
+impl ArtifactMetadata {
+    /// Reads the metadata from the body of its entry.
+    pub(crate) fn from_reader(reader: impl Read) -> Result<Self, CacheError> {
+        serde_json::from_reader(reader).map_err(|_| CacheError::MalformedTar(Backtrace::capture()))
+    }
+
+    /// Selects the format the archive is decoded with. Artifacts written by a
+    /// newer version are restored as long as they don't require a newer
+    /// reader, otherwise this fails with
+    /// [`CacheError::UnsupportedArtifactVersion`].
+    pub(crate) fn archive_version(&self) -> Result<ArchiveVersion, CacheError> {
+        if self.min_reader_version > SUPPORTED_READER_VERSION {
+            return Err(CacheError::UnsupportedArtifactVersion {
+                found: self.min_reader_version,
+                supported: SUPPORTED_READER_VERSION,
+                backtrace: Backtrace::capture(),
+            });
+        }
+        Ok(ArchiveVersion::V1)
+    }
+
+    /// The header and body of the metadata entry. The header is
+    /// deterministic, like the headers of the files.
+    pub(crate) fn to_entry(self) -> Result<(Header, Vec<u8>), CacheError> {
+        let body = serde_json::to_vec(&self)
+            .map_err(|e| CacheError::IO(e.into(), Backtrace::capture()))?;
+
+        let mut header = Header::new_gnu();
+        header.set_entry_type(EntryType::Regular);
+        header.set_mode(0o644);
+        header.set_size(body.len() as u64);
+        header.set_uid(0);
+        header.set_gid(0);
+        header.as_gnu_mut().unwrap().set_atime(0);
+        header.set_mtime(0);
+        header.as_gnu_mut().unwrap().set_ctime(0);
+
+        Ok((header, body))
+    }
+}
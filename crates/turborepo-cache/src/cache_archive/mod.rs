@@ -1,9 +1,16 @@
 #![allow(dead_code)]
 mod create;
+mod metadata;
 mod restore;
 mod restore_directory;
 mod restore_regular;
 mod restore_symlink;
+mod walk_dir;
 
 pub use create::CacheWriter;
-pub use restore::CacheReader;
+pub use metadata::{
+    ArchiveVersion, ArtifactMetadata, ARTIFACT_METADATA_PATH, ARTIFACT_SCHEMA_VERSION,
+    MIN_READER_VERSION, SUPPORTED_READER_VERSION,
+};
+pub use restore::{ArtifactLimit, CacheReader, PartialRestore, RestoreLimits};
+pub use walk_dir::DirWalker;
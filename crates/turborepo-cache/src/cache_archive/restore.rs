@@ -1,4 +1,4 @@
-use std::{backtrace::Backtrace, collections::HashMap, io::Read};
+use std::{backtrace::Backtrace, collections::HashMap, fmt, fs, io::Read, path::Path};
 
 use petgraph::graph::DiGraph;
 use sha2::{Digest, Sha512};
@@ -7,6 +7,7 @@ use turbopath::{AbsoluteSystemPath, AbsoluteSystemPathBuf, AnchoredSystemPathBuf
 
 use crate::{
     cache_archive::{
+        metadata::{ArchiveVersion, ARTIFACT_METADATA_PATH},
         restore_directory::{restore_directory, CachedDirTree},
         restore_regular::restore_regular,
         restore_symlink::{
@@ -16,8 +17,163 @@ use crate::{
     CacheError,
 };
 
+/// Limits that are enforced while restoring an artifact, so that a malicious
+/// or corrupted artifact can't fill up the disk. `None` disables a limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RestoreLimits {
+    /// The maximum number of entries in the archive.
+    pub max_entries: Option<u64>,
+    /// The maximum number of bytes extracted from the archive in total.
+    pub max_total_size: Option<u64>,
+    /// The maximum size of a single file in the archive.
+    pub max_file_size: Option<u64>,
+}
+
+impl RestoreLimits {
+    /// Limits that don't restrict the restore at all.
+    pub const fn unlimited() -> Self {
+        RestoreLimits {
+            max_entries: None,
+            max_total_size: None,
+            max_file_size: None,
+        }
+    }
+}
+
+impl Default for RestoreLimits {
+    fn default() -> Self {
+        RestoreLimits {
+            max_entries: Some(1_000_000),
+            max_total_size: Some(10 * 1024 * 1024 * 1024),
+            max_file_size: Some(4 * 1024 * 1024 * 1024),
+        }
+    }
+}
+
+/// A limit of [`RestoreLimits`] that was exceeded, along with its value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArtifactLimit {
+    MaxEntries(u64),
+    MaxTotalSize(u64),
+    MaxFileSize(u64),
+}
+
+impl fmt::Display for ArtifactLimit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ArtifactLimit::MaxEntries(max) => write!(f, "maximum number of entries ({max})"),
+            ArtifactLimit::MaxTotalSize(max) => write!(f, "maximum total size ({max} bytes)"),
+            ArtifactLimit::MaxFileSize(max) => write!(f, "maximum file size ({max} bytes)"),
+        }
+    }
+}
+
+/// Tracks the usage of [`RestoreLimits`] during a single restore.
+struct LimitTracker {
+    limits: RestoreLimits,
+    entries: u64,
+    total_size: u64,
+}
+
+impl LimitTracker {
+    fn new(limits: RestoreLimits) -> Self {
+        LimitTracker {
+            limits,
+            entries: 0,
+            total_size: 0,
+        }
+    }
+
+    // The size in the header is checked before anything is written. The tar
+    // reader never yields more bytes than that for an entry, so this also
+    // guards against archives that decompress to much more than they claim.
+    fn check<T: Read>(&mut self, entry: &Entry<'_, T>) -> Result<(), CacheError> {
+        self.entries += 1;
+        if let Some(max) = self.limits.max_entries {
+            if self.entries > max {
+                return Err(limit_exceeded(ArtifactLimit::MaxEntries(max), self.entries));
+            }
+        }
+
+        let size = entry.header().size()?;
+        if let Some(max) = self.limits.max_file_size {
+            if size > max {
+                return Err(limit_exceeded(ArtifactLimit::MaxFileSize(max), size));
+            }
+        }
+
+        self.total_size = self.total_size.saturating_add(size);
+        if let Some(max) = self.limits.max_total_size {
+            if self.total_size > max {
+                return Err(limit_exceeded(
+                    ArtifactLimit::MaxTotalSize(max),
+                    self.total_size,
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn limit_exceeded(limit: ArtifactLimit, observed: u64) -> CacheError {
+    CacheError::ArtifactLimitExceeded {
+        limit,
+        observed,
+        backtrace: Backtrace::capture(),
+    }
+}
+
+/// The result of [`CacheReader::restore_paths`].
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct PartialRestore {
+    /// The restored entries, relative to the anchor.
+    pub restored: Vec<AnchoredSystemPathBuf>,
+    /// The requested paths which no entry of the archive matched.
+    pub missing: Vec<AnchoredSystemPathBuf>,
+}
+
+/// The requested paths of a partial restore, along with whether an entry
+/// matched them.
+struct PathFilter<'a> {
+    paths: &'a [AnchoredSystemPathBuf],
+    matched: Vec<bool>,
+}
+
+impl<'a> PathFilter<'a> {
+    fn new(paths: &'a [AnchoredSystemPathBuf]) -> Self {
+        PathFilter {
+            paths,
+            matched: vec![false; paths.len()],
+        }
+    }
+
+    /// Whether the entry at `path` is requested, either by its own path or
+    /// by the path of a directory containing it.
+    fn matches(&mut self, path: &Path) -> bool {
+        let mut matches = false;
+        for (requested, matched) in self.paths.iter().zip(self.matched.iter_mut()) {
+            if path.starts_with(requested.as_path()) {
+                *matched = true;
+                matches = true;
+            }
+        }
+        matches
+    }
+
+    fn missing(&self) -> Vec<AnchoredSystemPathBuf> {
+        self.paths
+            .iter()
+            .zip(&self.matched)
+            .filter(|(_, matched)| !**matched)
+            .map(|(path, _)| path.clone())
+            .collect()
+    }
+}
+
 pub struct CacheReader<'a> {
     reader: Box<dyn Read + 'a>,
+    limits: RestoreLimits,
 }
 
 impl<'a> CacheReader<'a> {
@@ -28,7 +184,10 @@ impl<'a> CacheReader<'a> {
             Box::new(reader)
         };
 
-        Ok(CacheReader { reader })
+        Ok(CacheReader {
+            reader,
+            limits: RestoreLimits::default(),
+        })
     }
 
     pub fn open(path: &AbsoluteSystemPathBuf) -> Result<Self, CacheError> {
@@ -41,7 +200,16 @@ impl<'a> CacheReader<'a> {
             Box::new(file)
         };
 
-        Ok(CacheReader { reader })
+        Ok(CacheReader {
+            reader,
+            limits: RestoreLimits::default(),
+        })
+    }
+
+    /// Sets the limits that are enforced by [`CacheReader::restore`].
+    pub fn with_limits(mut self, limits: RestoreLimits) -> Self {
+        self.limits = limits;
+        self
     }
 
     pub fn get_sha(mut self) -> Result<Vec<u8>, CacheError> {
@@ -58,9 +226,41 @@ impl<'a> CacheReader<'a> {
         Ok(context.finalize().to_vec())
     }
 
+    /// Restores the archive into `anchor`. The first entry of the archive
+    /// selects its [`ArchiveVersion`]: archives which start with an
+    /// [`ArtifactMetadata`](super::ArtifactMetadata) entry are rejected with
+    /// [`CacheError::UnsupportedArtifactVersion`] when they require a newer
+    /// reader, archives without it are restored as is. An archive with an
+    /// entry outside of `anchor` is rejected with
+    /// [`CacheError::PathEscapesRoot`].
     pub fn restore(
         &mut self,
         anchor: &AbsoluteSystemPath,
+    ) -> Result<Vec<AnchoredSystemPathBuf>, CacheError> {
+        self.restore_filtered(anchor, None)
+    }
+
+    /// Like [`CacheReader::restore`], but only restores the entries at one of
+    /// `paths`, or inside of a directory at one of them, e.g. `dist` restores
+    /// everything under `dist/`. The requested paths which no entry matched
+    /// are returned as missing.
+    pub fn restore_paths(
+        &mut self,
+        anchor: &AbsoluteSystemPath,
+        paths: &[AnchoredSystemPathBuf],
+    ) -> Result<PartialRestore, CacheError> {
+        let mut filter = PathFilter::new(paths);
+        let restored = self.restore_filtered(anchor, Some(&mut filter))?;
+        Ok(PartialRestore {
+            restored,
+            missing: filter.missing(),
+        })
+    }
+
+    fn restore_filtered(
+        &mut self,
+        anchor: &AbsoluteSystemPath,
+        filter: Option<&mut PathFilter>,
     ) -> Result<Vec<AnchoredSystemPathBuf>, CacheError> {
         let mut restored = Vec::new();
         anchor.create_dir_all()?;
@@ -81,9 +281,27 @@ impl<'a> CacheReader<'a> {
         // shared prefix.
         let dir_cache = CachedDirTree::new(anchor.to_owned());
         let mut tr = tar::Archive::new(&mut self.reader);
-
-        Self::restore_entries(&mut tr, &mut restored, dir_cache, anchor)?;
-        Ok(restored)
+        let mut limits = LimitTracker::new(self.limits);
+
+        match Self::restore_entries(
+            &mut tr,
+            &mut restored,
+            dir_cache,
+            anchor,
+            &mut limits,
+            filter,
+        ) {
+            Err(
+                e @ (CacheError::ArtifactLimitExceeded { .. }
+                | CacheError::UnsupportedArtifactVersion { .. }
+                | CacheError::PathEscapesRoot(..)),
+            ) => {
+                remove_restored(anchor, &restored);
+                Err(e)
+            }
+            Err(e) => Err(e),
+            Ok(()) => Ok(restored),
+        }
     }
 
     fn restore_entries<T: Read>(
@@ -91,13 +309,60 @@ impl<'a> CacheReader<'a> {
         restored: &mut Vec<AnchoredSystemPathBuf>,
         mut dir_cache: CachedDirTree,
         anchor: &AbsoluteSystemPath,
+        limits: &mut LimitTracker,
+        mut filter: Option<&mut PathFilter>,
     ) -> Result<(), CacheError> {
         // On first attempt to restore it's possible that a link target doesn't exist.
         // Save them and topologically sort them.
         let mut symlinks = Vec::new();
+        let mut version = None;
+        let root = path_clean::clean(anchor.as_std_path());
 
         for entry in tr.entries()? {
             let mut entry = entry?;
+            // Every entry is checked, including the ones which aren't
+            // restored. Paths are resolved lexically, symlinks are checked
+            // when they are restored.
+            let path = entry.path()?;
+            if !path_clean::clean(root.join(&path)).starts_with(&root) {
+                return Err(CacheError::PathEscapesRoot(
+                    path.display().to_string(),
+                    Backtrace::capture(),
+                ));
+            }
+            let version = match version {
+                Some(version) => version,
+                None => {
+                    // The metadata isn't restored, it only selects how the
+                    // remaining entries are decoded.
+                    let path = entry.path()?.into_owned();
+                    let (first_version, is_metadata) =
+                        ArchiveVersion::of_first_entry(&path, &mut entry)?;
+                    version = Some(first_version);
+                    if is_metadata {
+                        continue;
+                    }
+                    first_version
+                }
+            };
+            match version {
+                ArchiveVersion::V0 => {}
+                // The metadata path is reserved in these archives, so no
+                // entry at it is restored.
+                ArchiveVersion::V1 => {
+                    if entry.path()?.as_ref() == Path::new(ARTIFACT_METADATA_PATH) {
+                        continue;
+                    }
+                }
+            }
+            // Entries which aren't requested are skipped without counting
+            // towards the limits, as nothing is written for them.
+            if let Some(filter) = filter.as_deref_mut() {
+                if !filter.matches(&entry.path()?) {
+                    continue;
+                }
+            }
+            limits.check(&entry)?;
             match restore_entry(&mut dir_cache, anchor, &mut entry) {
                 Err(CacheError::LinkTargetDoesNotExist(_, _)) => {
                     symlinks.push(entry);
@@ -164,6 +429,24 @@ impl<'a> CacheReader<'a> {
     }
 }
 
+/// Removes the files and directories of an aborted restore. Directories are
+/// only removed if they are empty, as they might have existed before.
+fn remove_restored(anchor: &AbsoluteSystemPath, restored: &[AnchoredSystemPathBuf]) {
+    for path in restored.iter().rev() {
+        let path = anchor.resolve(path);
+        let Ok(metadata) = path.symlink_metadata() else {
+            continue;
+        };
+        // Errors are ignored, as the restore already failed and there is
+        // nothing better to do with the remaining files.
+        if metadata.is_dir() {
+            let _ = fs::remove_dir(path.as_path());
+        } else {
+            let _ = path.remove_file();
+        }
+    }
+}
+
 fn restore_entry<T: Read>(
     dir_cache: &mut CachedDirTree,
     anchor: &AbsoluteSystemPath,
@@ -193,7 +476,18 @@ mod tests {
     use tracing::debug;
     use turbopath::{AbsoluteSystemPath, AbsoluteSystemPathBuf, AnchoredSystemPathBuf};
 
-    use crate::cache_archive::{restore::CacheReader, restore_symlink::canonicalize_linkname};
+    use crate::{
+        cache_archive::{
+            create::CacheWriter,
+            metadata::{
+                ArchiveVersion, ARTIFACT_METADATA_PATH, ARTIFACT_SCHEMA_VERSION,
+                SUPPORTED_READER_VERSION,
+            },
+            restore::{ArtifactLimit, CacheReader, RestoreLimits},
+            restore_symlink::canonicalize_linkname,
+        },
+        CacheError,
+    };
 
     // Expected output of the cache
     #[derive(Debug)]
@@ -349,7 +643,7 @@ mod tests {
             assert!(result.is_err());
             assert_eq!(
                 result.unwrap_err().to_string(),
-                "Invalid file path: path is malformed: ../escape"
+                "artifact entry ../escape escapes the root of the restore"
             );
         }
 
@@ -914,6 +1208,249 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_restore_limits() -> Result<()> {
+        let input_files = vec![
+            TarFile::Directory {
+                path: AnchoredSystemPathBuf::from_raw("dir/").unwrap(),
+            },
+            TarFile::File {
+                body: b"hello".to_vec(),
+                path: AnchoredSystemPathBuf::from_raw("dir/small").unwrap(),
+            },
+            // Compresses to a few bytes but expands to 1MiB
+            TarFile::File {
+                body: vec![0; 1024 * 1024],
+                path: AnchoredSystemPathBuf::from_raw("dir/bomb").unwrap(),
+            },
+        ];
+
+        let cases = [
+            (
+                RestoreLimits {
+                    max_total_size: Some(1024),
+                    ..RestoreLimits::unlimited()
+                },
+                ArtifactLimit::MaxTotalSize(1024),
+                1024 * 1024 + 5,
+            ),
+            (
+                RestoreLimits {
+                    max_file_size: Some(1024),
+                    ..RestoreLimits::unlimited()
+                },
+                ArtifactLimit::MaxFileSize(1024),
+                1024 * 1024,
+            ),
+            (
+                RestoreLimits {
+                    max_entries: Some(2),
+                    ..RestoreLimits::unlimited()
+                },
+                ArtifactLimit::MaxEntries(2),
+                3,
+            ),
+        ];
+
+        for (limits, expected_limit, expected_observed) in cases {
+            let input_dir = tempdir()?;
+            let archive_path = compress_tar(&generate_tar(&input_dir, &input_files)?)?;
+            assert!(fs::metadata(archive_path.as_path())?.len() < 1024);
+
+            let output_dir = tempdir()?;
+            let output_dir_path = output_dir.path().to_string_lossy();
+            let anchor = AbsoluteSystemPath::new(&output_dir_path)?;
+
+            let mut cache_reader = CacheReader::open(&archive_path)?.with_limits(limits);
+            match cache_reader.restore(anchor) {
+                Err(CacheError::ArtifactLimitExceeded {
+                    limit, observed, ..
+                }) => {
+                    assert_eq!(limit, expected_limit);
+                    assert_eq!(observed, expected_observed);
+                }
+                result => panic!("expected limit to be exceeded, received {:?}", result),
+            }
+
+            // Everything written by the aborted restore is removed again.
+            assert_eq!(fs::read_dir(output_dir.path())?.count(), 0);
+        }
+
+        // Without limits the same archive restores fine.
+        let input_dir = tempdir()?;
+        let archive_path = compress_tar(&generate_tar(&input_dir, &input_files)?)?;
+        let output_dir = tempdir()?;
+        let output_dir_path = output_dir.path().to_string_lossy();
+        let anchor = AbsoluteSystemPath::new(&output_dir_path)?;
+        let mut cache_reader =
+            CacheReader::open(&archive_path)?.with_limits(RestoreLimits::unlimited());
+        assert_eq!(cache_reader.restore(anchor)?.len(), 3);
+
+        Ok(())
+    }
+
+    fn metadata_file(schema_version: u32, min_reader_version: u32) -> TarFile {
+        TarFile::File {
+            body: format!(
+                r#"{{"schemaVersion":{schema_version},"minReaderVersion":{min_reader_version}}}"#
+            )
+            .into_bytes(),
+            path: AnchoredSystemPathBuf::from_raw(ARTIFACT_METADATA_PATH).unwrap(),
+        }
+    }
+
+    #[test_case(Some((ARTIFACT_SCHEMA_VERSION, SUPPORTED_READER_VERSION)), None ; "same version")]
+    #[test_case(Some((ARTIFACT_SCHEMA_VERSION + 1, SUPPORTED_READER_VERSION)), None ; "newer compatible version")]
+    #[test_case(Some((ARTIFACT_SCHEMA_VERSION + 2, SUPPORTED_READER_VERSION + 1)), Some(SUPPORTED_READER_VERSION + 1) ; "incompatible version")]
+    #[test_case(None, None ; "legacy artifact")]
+    fn test_artifact_version(
+        metadata: Option<(u32, u32)>,
+        expected_unsupported: Option<u32>,
+    ) -> Result<()> {
+        let file = AnchoredSystemPathBuf::from_raw("dist/index.js")?;
+        let mut input_files = Vec::new();
+        if let Some((schema_version, min_reader_version)) = metadata {
+            input_files.push(metadata_file(schema_version, min_reader_version));
+        }
+        input_files.push(TarFile::Directory {
+            path: AnchoredSystemPathBuf::from_raw("dist/")?,
+        });
+        input_files.push(TarFile::File {
+            body: b"Yi Yi".to_vec(),
+            path: file.clone(),
+        });
+
+        let input_dir = tempdir()?;
+        let archive_path = generate_tar(&input_dir, &input_files)?;
+        let output_dir = tempdir()?;
+        let output_dir_path = output_dir.path().to_string_lossy();
+        let anchor = AbsoluteSystemPath::new(&output_dir_path)?;
+
+        let result = CacheReader::open(&archive_path)?.restore(anchor);
+        match expected_unsupported {
+            Some(expected_found) => {
+                match result {
+                    Err(CacheError::UnsupportedArtifactVersion {
+                        found, supported, ..
+                    }) => {
+                        assert_eq!(found, expected_found);
+                        assert_eq!(supported, SUPPORTED_READER_VERSION);
+                    }
+                    result => panic!("expected unsupported version, received {:?}", result),
+                }
+                assert_eq!(fs::read_dir(output_dir.path())?.count(), 0);
+            }
+            None => {
+                let restored = result?;
+                // The metadata itself is never restored.
+                assert_eq!(restored.len(), 2);
+                assert!(restored.contains(&file));
+                assert!(!anchor.join_component(ARTIFACT_METADATA_PATH).exists());
+                assert_eq!(fs::read(anchor.resolve(&file))?, b"Yi Yi");
+            }
+        }
+
+        Ok(())
+    }
+
+    // Writes the files at `anchor` to an archive of the given version, the
+    // way the clients of that version did.
+    fn write_archive(
+        version: ArchiveVersion,
+        archive_path: &AbsoluteSystemPath,
+        anchor: &AbsoluteSystemPath,
+        files: &[AnchoredSystemPathBuf],
+    ) -> Result<()> {
+        match version {
+            ArchiveVersion::V0 => {
+                let mut tar_writer = tar::Builder::new(File::create(archive_path.as_path())?);
+                for file in files {
+                    let mut header = Header::new_gnu();
+                    let contents = fs::read(anchor.resolve(file).as_path())?;
+                    header.set_size(contents.len() as u64);
+                    header.set_entry_type(tar::EntryType::Regular);
+                    header.set_mode(0o644);
+                    tar_writer.append_data(&mut header, file.as_str(), &contents[..])?;
+                }
+                tar_writer.into_inner()?;
+            }
+            ArchiveVersion::V1 => {
+                let mut writer =
+                    CacheWriter::from_writer(File::create(archive_path.as_path())?, false)?;
+                for file in files {
+                    writer.add_file(anchor, file)?;
+                }
+                writer.finish()?;
+            }
+        }
+        Ok(())
+    }
+
+    #[test_case(ArchiveVersion::V0 ; "v0")]
+    #[test_case(ArchiveVersion::V1 ; "v1")]
+    fn test_archive_version_round_trip(version: ArchiveVersion) -> Result<()> {
+        let input_dir = tempdir()?;
+        let archive_dir = tempdir()?;
+        let output_dir = tempdir()?;
+        let input = AbsoluteSystemPathBuf::try_from(input_dir.path())?;
+        let output = AbsoluteSystemPathBuf::try_from(output_dir.path())?;
+        let archive_path = AbsoluteSystemPathBuf::try_from(archive_dir.path().join("out.tar"))?;
+
+        let index = AnchoredSystemPathBuf::from_raw("index.js")?;
+        let log = AnchoredSystemPathBuf::from_raw("build.log")?;
+        input.resolve(&index).create_with_contents("Yi Yi")?;
+        input
+            .resolve(&log)
+            .create_with_contents("A Brighter Summer Day")?;
+        let files = vec![index.clone(), log.clone()];
+        write_archive(version, &archive_path, &input, &files)?;
+
+        let mut restored = CacheReader::open(&archive_path)?.restore(&output)?;
+        restored.sort();
+        let mut expected = files;
+        expected.sort();
+        assert_eq!(restored, expected);
+        assert_eq!(fs::read(output.resolve(&index).as_path())?, b"Yi Yi");
+        assert_eq!(
+            fs::read(output.resolve(&log).as_path())?,
+            b"A Brighter Summer Day"
+        );
+        assert!(!output.join_component(ARTIFACT_METADATA_PATH).exists());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_legacy_archive_with_metadata_path() -> Result<()> {
+        let input_dir = tempdir()?;
+        let archive_dir = tempdir()?;
+        let output_dir = tempdir()?;
+        let input = AbsoluteSystemPathBuf::try_from(input_dir.path())?;
+        let output = AbsoluteSystemPathBuf::try_from(output_dir.path())?;
+        let archive_path = AbsoluteSystemPathBuf::try_from(archive_dir.path().join("out.tar"))?;
+
+        // Legacy archives don't start with metadata, so a file at the
+        // metadata path is part of the artifact, even when it isn't valid
+        // metadata.
+        let index = AnchoredSystemPathBuf::from_raw("index.js")?;
+        let metadata = AnchoredSystemPathBuf::from_raw(ARTIFACT_METADATA_PATH)?;
+        input.resolve(&index).create_with_contents("Yi Yi")?;
+        input
+            .resolve(&metadata)
+            .create_with_contents("not metadata")?;
+        let files = vec![index.clone(), metadata.clone()];
+        write_archive(ArchiveVersion::V0, &archive_path, &input, &files)?;
+
+        let restored = CacheReader::open(&archive_path)?.restore(&output)?;
+        assert_eq!(restored, files);
+        assert_eq!(
+            fs::read(output.resolve(&metadata).as_path())?,
+            b"not metadata"
+        );
+
+        Ok(())
+    }
+
     #[test_case(Path::new("source").try_into()?, Path::new("target"), "/Users/test/target", "C:\\Users\\test\\target" ; "hello world")]
     #[test_case(Path::new("child/source").try_into()?, Path::new("../sibling/target"), "/Users/test/sibling/target", "C:\\Users\\test\\sibling\\target" ; "Unix path subdirectory traversal")]
     #[test_case(Path::new("child/source").try_into()?, Path::new("..\\sibling\\target"), "/Users/test/child/..\\sibling\\target", "C:\\Users\\test\\sibling\\target" ; "Windows path subdirectory traversal")]
@@ -25,16 +25,28 @@ pub fn restore_regular(
     let mut open_options = OpenOptions::new();
     open_options.write(true).truncate(true).create(true);
 
+    let mode = header.mode()?;
     #[cfg(unix)]
     {
         use std::os::unix::fs::OpenOptionsExt;
-        open_options.mode(header.mode()?);
+        open_options.mode(mode);
     }
 
     println!("resolved path: {}", resolved_path);
     let mut file = open_options.open(resolved_path.as_path())?;
     io::copy(entry, &mut file)?;
 
+    // The mode passed when opening is masked by the umask and ignored for
+    // files which already exist, so it's applied explicitly. Windows has no
+    // equivalent of the mode, so the recorded one is ignored there.
+    #[cfg(unix)]
+    {
+        use std::{fs::Permissions, os::unix::fs::PermissionsExt};
+        file.set_permissions(Permissions::from_mode(mode & 0o777))?;
+    }
+    #[cfg(windows)]
+    let _ = mode;
+
     Ok(processed_name)
 }
 
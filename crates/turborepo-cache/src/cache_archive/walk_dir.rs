@@ -0,0 +1,241 @@
+use std::{backtrace::Backtrace, fs, path::PathBuf};
+
+use turbopath::{AbsoluteSystemPath, AnchoredSystemPath, AnchoredSystemPathBuf};
+
+use crate::CacheError;
+
+type IgnoreFn<'a> = Box<dyn Fn(&AnchoredSystemPath) -> bool + Send + Sync + 'a>;
+
+/// Collects the contents of a directory, so they can be added to a cache
+/// archive.
+///
+/// Entries are produced depth-first with the entries of every directory
+/// sorted by name, which keeps artifacts reproducible and matches the order
+/// the restore fast path expects.
+pub struct DirWalker<'a> {
+    include_symlinks: bool,
+    follow_symlinks: bool,
+    ignore: Option<IgnoreFn<'a>>,
+}
+
+impl<'a> Default for DirWalker<'a> {
+    fn default() -> Self {
+        DirWalker {
+            include_symlinks: true,
+            follow_symlinks: false,
+            ignore: None,
+        }
+    }
+}
+
+impl<'a> DirWalker<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether symlinks are added to the artifact. Unless they are
+    /// followed, symlinks are stored as links and never traversed, so
+    /// enabling this can't escape the directory. Defaults to `true`.
+    pub fn include_symlinks(mut self, include_symlinks: bool) -> Self {
+        self.include_symlinks = include_symlinks;
+        self
+    }
+
+    /// Whether symlinks are followed. Links to directories are traversed like
+    /// directories, and links to files are added as the files they point to,
+    /// see [`DirWalker::follows_symlinks`]. Links to a directory that is
+    /// already being traversed are skipped. Defaults to `false`.
+    pub fn follow_symlinks(mut self, follow_symlinks: bool) -> Self {
+        self.follow_symlinks = follow_symlinks;
+        self
+    }
+
+    /// Whether the walked paths have to be added with their symlinks
+    /// followed, because they can contain symlinks.
+    pub fn follows_symlinks(&self) -> bool {
+        self.follow_symlinks
+    }
+
+    /// Skips every entry for which `ignore` returns `true`. Ignored
+    /// directories are not traversed.
+    pub fn ignore(
+        mut self,
+        ignore: impl Fn(&AnchoredSystemPath) -> bool + Send + Sync + 'a,
+    ) -> Self {
+        self.ignore = Some(Box::new(ignore));
+        self
+    }
+
+    /// Returns `dir` and all entries below it, anchored at `anchor`.
+    pub fn walk(
+        &self,
+        anchor: &AbsoluteSystemPath,
+        dir: &AnchoredSystemPath,
+    ) -> Result<Vec<AnchoredSystemPathBuf>, CacheError> {
+        let mut files = Vec::new();
+        self.walk_into(anchor, dir.to_owned(), &mut files, &mut Vec::new())?;
+        Ok(files)
+    }
+
+    fn walk_into(
+        &self,
+        anchor: &AbsoluteSystemPath,
+        dir: AnchoredSystemPathBuf,
+        files: &mut Vec<AnchoredSystemPathBuf>,
+        ancestors: &mut Vec<PathBuf>,
+    ) -> Result<(), CacheError> {
+        let dir_path = anchor.resolve(&dir);
+        if self.follow_symlinks {
+            let real_path = fs::canonicalize(dir_path.as_path())?;
+            if ancestors.contains(&real_path) {
+                return Ok(());
+            }
+            ancestors.push(real_path);
+        }
+
+        let mut names = fs::read_dir(dir_path.as_path())?
+            .map(|entry| {
+                entry?.file_name().into_string().map_err(|name| {
+                    CacheError::InvalidFilePath(
+                        name.to_string_lossy().into_owned(),
+                        Backtrace::capture(),
+                    )
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        names.sort();
+
+        if !dir.as_str().is_empty() {
+            files.push(dir.clone());
+        }
+
+        for name in names {
+            let mut path = dir.clone();
+            path.push(&name);
+            if self.ignore.as_ref().map_or(false, |ignore| ignore(&path)) {
+                continue;
+            }
+
+            let source_path = anchor.resolve(&path);
+            let mut file_type = source_path.symlink_metadata()?.file_type();
+            if file_type.is_symlink() {
+                if !self.include_symlinks {
+                    continue;
+                }
+                if self.follow_symlinks {
+                    file_type = fs::metadata(source_path.as_path())?.file_type();
+                }
+            }
+            if file_type.is_dir() {
+                self.walk_into(anchor, path, files, ancestors)?;
+            } else {
+                files.push(path);
+            }
+        }
+
+        if self.follow_symlinks {
+            ancestors.pop();
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use anyhow::Result;
+    use tempfile::tempdir;
+    use turbopath::{AbsoluteSystemPath, AnchoredSystemPath, AnchoredSystemPathBuf};
+
+    use super::DirWalker;
+
+    fn paths(items: &[&str]) -> Vec<AnchoredSystemPathBuf> {
+        items
+            .iter()
+            .map(|item| AnchoredSystemPathBuf::from_raw(item).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_walk_dir() -> Result<()> {
+        let root = tempdir()?;
+        let root_path = root.path().to_string_lossy();
+        let anchor = AbsoluteSystemPath::new(&root_path)?;
+
+        // Created out of order to make sure the output is sorted
+        fs::create_dir_all(root.path().join("dist/b"))?;
+        fs::create_dir_all(root.path().join("dist/a"))?;
+        fs::write(root.path().join("dist/b/file.js"), "b")?;
+        fs::write(root.path().join("dist/a/file.js"), "a")?;
+        fs::write(root.path().join("dist/index.js.map"), "map")?;
+        fs::write(root.path().join("dist/index.js"), "index")?;
+        fs::write(root.path().join("outside.js"), "outside")?;
+        #[cfg(unix)]
+        std::os::unix::fs::symlink("a", root.path().join("dist/link"))?;
+
+        let dist = AnchoredSystemPath::new("dist")?;
+
+        let files = DirWalker::new().walk(anchor, dist)?;
+        #[cfg(unix)]
+        assert_eq!(
+            files,
+            paths(&[
+                "dist",
+                "dist/a",
+                "dist/a/file.js",
+                "dist/b",
+                "dist/b/file.js",
+                "dist/index.js",
+                "dist/index.js.map",
+                "dist/link",
+            ])
+        );
+
+        let files = DirWalker::new()
+            .include_symlinks(false)
+            .ignore(|path| path.as_str().ends_with(".map") || path.as_str().ends_with('b'))
+            .walk(anchor, dist)?;
+        assert_eq!(
+            files,
+            paths(&["dist", "dist/a", "dist/a/file.js", "dist/index.js"])
+        );
+
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_walk_dir_following_symlinks() -> Result<()> {
+        use std::os::unix::fs::symlink;
+
+        let root = tempdir()?;
+        let root_path = root.path().to_string_lossy();
+        let anchor = AbsoluteSystemPath::new(&root_path)?;
+
+        fs::create_dir_all(root.path().join("dist/a"))?;
+        fs::create_dir_all(root.path().join("shared"))?;
+        fs::write(root.path().join("dist/a/file.js"), "a")?;
+        fs::write(root.path().join("shared/file.js"), "shared")?;
+        symlink("../shared", root.path().join("dist/shared"))?;
+        symlink("../shared/file.js", root.path().join("dist/file.js"))?;
+        // Links back to a directory that is being traversed are skipped.
+        symlink("..", root.path().join("dist/a/parent"))?;
+
+        let dist = AnchoredSystemPath::new("dist")?;
+        let files = DirWalker::new().follow_symlinks(true).walk(anchor, dist)?;
+        assert_eq!(
+            files,
+            paths(&[
+                "dist",
+                "dist/a",
+                "dist/a/file.js",
+                "dist/file.js",
+                "dist/shared",
+                "dist/shared/file.js",
+            ])
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,251 @@
+//! A mock of the endpoints of the GitHub Actions cache service.
+
+use std::{
+    net::{SocketAddr, TcpListener},
+    sync::{Arc, Mutex},
+};
+
+use axum::{
+    body::Bytes,
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use serde::Deserialize;
+use serde_json::json;
+use tokio::task::JoinHandle;
+
+#[derive(Debug, Default)]
+pub struct MockEntry {
+    pub key: String,
+    pub version: String,
+    pub data: Vec<u8>,
+    /// The inclusive byte ranges of the uploaded chunks.
+    pub chunks: Vec<(usize, usize)>,
+    pub committed: bool,
+}
+
+#[derive(Debug, Default)]
+pub struct MockState {
+    entries: Vec<MockEntry>,
+}
+
+impl MockState {
+    /// Returns the entry with the given key.
+    pub fn entry(&self, key: &str) -> Option<&MockEntry> {
+        self.entries.iter().find(|entry| entry.key == key)
+    }
+
+    /// Reserves an entry and returns its id, or `None` when there is one for
+    /// the key and version already.
+    pub fn reserve(&mut self, key: &str, version: &str) -> Option<usize> {
+        if self
+            .entries
+            .iter()
+            .any(|entry| entry.key == key && entry.version == version)
+        {
+            return None;
+        }
+        self.entries.push(MockEntry {
+            key: key.to_string(),
+            version: version.to_string(),
+            ..Default::default()
+        });
+        Some(self.entries.len())
+    }
+
+    fn entry_mut(&mut self, id: usize) -> Option<&mut MockEntry> {
+        id.checked_sub(1)
+            .and_then(|index| self.entries.get_mut(index))
+    }
+}
+
+#[derive(Clone)]
+struct AppState {
+    token: String,
+    base_url: String,
+    state: Arc<Mutex<MockState>>,
+}
+
+impl AppState {
+    fn authorize(&self, headers: &HeaderMap) -> Result<(), Response> {
+        let expected = format!("Bearer {}", self.token);
+        match headers.get(header::AUTHORIZATION) {
+            Some(value) if value.as_bytes() == expected.as_bytes() => Ok(()),
+            _ => Err(StatusCode::UNAUTHORIZED.into_response()),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct LookupQuery {
+    keys: String,
+    version: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ReserveBody {
+    key: String,
+    version: String,
+    cache_size: Option<u64>,
+}
+
+#[derive(Deserialize)]
+struct CommitBody {
+    size: u64,
+}
+
+/// Starts the mock on the given port. Only requests with `token` are
+/// accepted. Returns the state of the mock, so tests can inspect and
+/// prepare the entries.
+pub async fn start_test_server(port: u16, token: &str) -> (JoinHandle<()>, Arc<Mutex<MockState>>) {
+    let state = Arc::new(Mutex::new(MockState::default()));
+    let app_state = AppState {
+        token: token.to_string(),
+        base_url: format!("http://localhost:{port}"),
+        state: state.clone(),
+    };
+
+    let app = Router::new()
+        .route("/_apis/artifactcache/cache", get(lookup))
+        .route("/_apis/artifactcache/caches", post(reserve))
+        .route(
+            "/_apis/artifactcache/caches/:id",
+            post(commit).patch(upload),
+        )
+        .route("/download/:id", get(download))
+        .with_state(app_state);
+
+    // Bind before returning, so requests can be made right away.
+    let listener = TcpListener::bind(SocketAddr::from(([127, 0, 0, 1], port))).unwrap();
+    let server = axum::Server::from_tcp(listener)
+        .unwrap()
+        .serve(app.into_make_service());
+    let handle = tokio::spawn(async move {
+        server.await.unwrap();
+    });
+    (handle, state)
+}
+
+async fn lookup(
+    State(app): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<LookupQuery>,
+) -> Response {
+    if let Err(response) = app.authorize(&headers) {
+        return response;
+    }
+    let state = app.state.lock().unwrap();
+    // Only the first key is used, restore keys aren't supported.
+    let key = query.keys.split(',').next().unwrap_or_default();
+    let entry =
+        state.entries.iter().enumerate().find(|(_, entry)| {
+            entry.committed && entry.key == key && entry.version == query.version
+        });
+    match entry {
+        Some((index, entry)) => Json(json!({
+            "cacheKey": entry.key,
+            "scope": "refs/heads/main",
+            "archiveLocation": format!("{}/download/{}", app.base_url, index + 1),
+        }))
+        .into_response(),
+        None => StatusCode::NO_CONTENT.into_response(),
+    }
+}
+
+async fn reserve(
+    State(app): State<AppState>,
+    headers: HeaderMap,
+    Json(body): Json<ReserveBody>,
+) -> Response {
+    if let Err(response) = app.authorize(&headers) {
+        return response;
+    }
+    if body.cache_size.is_none() {
+        return StatusCode::BAD_REQUEST.into_response();
+    }
+    match app.state.lock().unwrap().reserve(&body.key, &body.version) {
+        Some(id) => (StatusCode::CREATED, Json(json!({ "cacheId": id }))).into_response(),
+        None => (
+            StatusCode::CONFLICT,
+            Json(json!({
+                "message": format!("Cache already exists. Key: {}", body.key),
+                "typeKey": "ArtifactCacheAlreadyExistsException",
+            })),
+        )
+            .into_response(),
+    }
+}
+
+async fn upload(
+    State(app): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<usize>,
+    body: Bytes,
+) -> Response {
+    if let Err(response) = app.authorize(&headers) {
+        return response;
+    }
+    let Some((start, end)) = headers
+        .get(header::CONTENT_RANGE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(parse_content_range)
+    else {
+        return StatusCode::BAD_REQUEST.into_response();
+    };
+    if end + 1 - start != body.len() {
+        return StatusCode::BAD_REQUEST.into_response();
+    }
+    let mut state = app.state.lock().unwrap();
+    let Some(entry) = state.entry_mut(id) else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    if entry.committed {
+        return StatusCode::CONFLICT.into_response();
+    }
+    if entry.data.len() <= end {
+        entry.data.resize(end + 1, 0);
+    }
+    entry.data[start..=end].copy_from_slice(&body);
+    entry.chunks.push((start, end));
+    StatusCode::NO_CONTENT.into_response()
+}
+
+async fn commit(
+    State(app): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<usize>,
+    Json(body): Json<CommitBody>,
+) -> Response {
+    if let Err(response) = app.authorize(&headers) {
+        return response;
+    }
+    let mut state = app.state.lock().unwrap();
+    let Some(entry) = state.entry_mut(id) else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    if entry.data.len() as u64 != body.size {
+        return StatusCode::BAD_REQUEST.into_response();
+    }
+    entry.committed = true;
+    StatusCode::NO_CONTENT.into_response()
+}
+
+async fn download(State(app): State<AppState>, Path(id): Path<usize>) -> Response {
+    let mut state = app.state.lock().unwrap();
+    match state.entry_mut(id) {
+        Some(entry) if entry.committed => entry.data.clone().into_response(),
+        _ => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+/// Parses `bytes <start>-<end>/*`.
+fn parse_content_range(value: &str) -> Option<(usize, usize)> {
+    let range = value.strip_prefix("bytes ")?.strip_suffix("/*")?;
+    let (start, end) = range.split_once('-')?;
+    let (start, end) = (start.parse().ok()?, end.parse().ok()?);
+    (start <= end).then_some((start, end))
+}
@@ -0,0 +1,468 @@
+//! A cache backend that stores artifacts in the GitHub Actions cache service.
+//!
+//! An upload reserves a cache entry for the key and version, uploads the
+//! artifact in chunks with a `Content-Range` each, and commits the entry with
+//! its final size. Downloads look up the entry by key and version and fetch
+//! the artifact from the returned archive location.
+
+#[cfg(test)]
+mod mock;
+
+use std::backtrace::Backtrace;
+
+use reqwest::{header, Client, RequestBuilder, StatusCode};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use turbopath::{AbsoluteSystemPath, AbsoluteSystemPathBuf, AnchoredSystemPathBuf};
+
+use crate::{
+    cache_archive::RestoreLimits,
+    http::HttpCache,
+    progress::{ProgressReporter, ThrottledProgress},
+    CacheError, CacheResponse, CacheSource, PutResponse,
+};
+
+/// The API version of the cache service.
+const ACCEPT: &str = "application/json;api-version=6.0-preview.1";
+/// The prefix of the keys of the cache entries, unless configured otherwise.
+const DEFAULT_KEY_PREFIX: &str = "turbo-";
+/// The maximum size of a chunk of an upload.
+pub const MAX_CHUNK_SIZE: usize = 32 * 1024 * 1024;
+/// The maximum size of a cache entry.
+pub const MAX_ENTRY_SIZE: u64 = 10 * 1024 * 1024 * 1024;
+/// Distinguishes the entries of turbo from other entries with the same key.
+/// Needs to change when the format of the artifacts changes.
+const VERSION_SALT: &str = "turborepo-cache|tar|zstd";
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ReserveCacheRequest<'a> {
+    key: &'a str,
+    version: &'a str,
+    cache_size: u64,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ReserveCacheResponse {
+    cache_id: u64,
+}
+
+#[derive(Debug, Serialize)]
+struct CommitCacheRequest {
+    size: u64,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CacheEntry {
+    archive_location: String,
+}
+
+/// Stores artifacts in the GitHub Actions cache. The cache url and the token
+/// are provided by the Actions runtime as `ACTIONS_CACHE_URL` and
+/// `ACTIONS_RUNTIME_TOKEN`.
+///
+/// The cache service doesn't store metadata of the entries, so the
+/// [`CacheResponse`]s report no time saved.
+pub struct GithubActionsCache {
+    client: Client,
+    cache_url: String,
+    token: String,
+    key_prefix: String,
+    version: String,
+    chunk_size: usize,
+    repo_root: AbsoluteSystemPathBuf,
+    restore_limits: RestoreLimits,
+}
+
+impl GithubActionsCache {
+    pub fn new(
+        cache_url: impl Into<String>,
+        token: impl Into<String>,
+        repo_root: AbsoluteSystemPathBuf,
+    ) -> Self {
+        let mut cache_url = cache_url.into();
+        if !cache_url.ends_with('/') {
+            cache_url.push('/');
+        }
+        GithubActionsCache {
+            client: Client::new(),
+            cache_url,
+            token: token.into(),
+            key_prefix: DEFAULT_KEY_PREFIX.to_string(),
+            version: hex::encode(Sha256::digest(VERSION_SALT)),
+            chunk_size: MAX_CHUNK_SIZE,
+            repo_root,
+            restore_limits: RestoreLimits::default(),
+        }
+    }
+
+    /// Sets the prefix of the keys of the cache entries, e.g. to separate the
+    /// entries of different workflows.
+    pub fn with_key_prefix(mut self, key_prefix: impl Into<String>) -> Self {
+        self.key_prefix = key_prefix.into();
+        self
+    }
+
+    /// Sets the size of the chunks of an upload. It's capped at
+    /// [`MAX_CHUNK_SIZE`].
+    pub fn with_chunk_size(mut self, chunk_size: usize) -> Self {
+        self.chunk_size = chunk_size.clamp(1, MAX_CHUNK_SIZE);
+        self
+    }
+
+    /// Sets the limits that are enforced when restoring retrieved artifacts.
+    /// Use [`RestoreLimits::unlimited`] to disable them.
+    pub fn with_restore_limits(mut self, restore_limits: RestoreLimits) -> Self {
+        self.restore_limits = restore_limits;
+        self
+    }
+
+    /// The key of the cache entry of the artifact with the given hash.
+    pub fn key(&self, hash: &str) -> String {
+        format!("{}{}", self.key_prefix, hash)
+    }
+
+    /// Uploads the given files. Fails with [`CacheError::CacheEntryReserved`]
+    /// when another job is already uploading the artifact. The progress is
+    /// reported as the chunks are uploaded, its total is the size of the
+    /// artifact.
+    pub async fn put(
+        &self,
+        anchor: &AbsoluteSystemPath,
+        hash: &str,
+        files: Vec<AnchoredSystemPathBuf>,
+        progress: Option<&dyn ProgressReporter>,
+    ) -> Result<PutResponse, CacheError> {
+        let mut artifact_body = Vec::new();
+        HttpCache::write(&mut artifact_body, anchor, files)?;
+
+        let size = artifact_body.len() as u64;
+        if size > MAX_ENTRY_SIZE {
+            return Err(CacheError::ArtifactTooLarge(
+                size,
+                MAX_ENTRY_SIZE,
+                Backtrace::capture(),
+            ));
+        }
+
+        let key = self.key(hash);
+        let cache_id = self.reserve(&key, size).await?;
+        let mut progress = ThrottledProgress::new(progress, Some(size));
+        for (index, chunk) in artifact_body.chunks(self.chunk_size).enumerate() {
+            let start = index * self.chunk_size;
+            let end = start + chunk.len() - 1;
+            self.request(
+                self.client
+                    .patch(self.url(&format!("caches/{cache_id}")))
+                    .header(header::CONTENT_TYPE, "application/octet-stream")
+                    .header(header::CONTENT_RANGE, format!("bytes {start}-{end}/*"))
+                    .body(chunk.to_vec()),
+            )
+            .await?;
+            progress.advance(chunk.len() as u64);
+        }
+        self.request(
+            self.client
+                .post(self.url(&format!("caches/{cache_id}")))
+                .json(&CommitCacheRequest { size }),
+        )
+        .await?;
+        progress.finish();
+
+        Ok(PutResponse {
+            bytes_transferred: size,
+        })
+    }
+
+    /// Checks whether the artifact is in the cache. Returns `None` when it
+    /// isn't, while a failing request, e.g. due to an invalid token, is an
+    /// error.
+    pub async fn exists(&self, hash: &str) -> Result<Option<CacheResponse>, CacheError> {
+        if self.lookup(hash).await?.is_none() {
+            return Ok(None);
+        }
+
+        Ok(Some(CacheResponse {
+            source: CacheSource::Remote,
+            time_saved: 0,
+            // Only the entry is transferred.
+            bytes_transferred: 0,
+        }))
+    }
+
+    /// Downloads and restores an artifact. Returns `None` when it isn't in the
+    /// cache, without writing any files.
+    pub async fn retrieve(
+        &self,
+        hash: &str,
+        progress: Option<&dyn ProgressReporter>,
+    ) -> Result<Option<(CacheResponse, Vec<AnchoredSystemPathBuf>)>, CacheError> {
+        let Some(entry) = self.lookup(hash).await? else {
+            return Ok(None);
+        };
+        // The archive location is a pre-signed url, it doesn't accept the token.
+        let response = self
+            .client
+            .get(&entry.archive_location)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(request_error)?;
+        let body = HttpCache::read_body(response, progress).await?;
+        let files = HttpCache::restore_tar(&self.repo_root, &body, self.restore_limits)?;
+
+        Ok(Some((
+            CacheResponse {
+                source: CacheSource::Remote,
+                time_saved: 0,
+                bytes_transferred: body.len() as u64,
+            },
+            files,
+        )))
+    }
+
+    /// Reserves a cache entry for the upload and returns its id.
+    async fn reserve(&self, key: &str, size: u64) -> Result<u64, CacheError> {
+        let response = self
+            .authorized(self.client.post(self.url("caches")))
+            .json(&ReserveCacheRequest {
+                key,
+                version: &self.version,
+                cache_size: size,
+            })
+            .send()
+            .await
+            .map_err(request_error)?;
+        if response.status() == StatusCode::CONFLICT {
+            return Err(CacheError::CacheEntryReserved(
+                key.to_string(),
+                Backtrace::capture(),
+            ));
+        }
+        let response: ReserveCacheResponse = response
+            .error_for_status()
+            .map_err(request_error)?
+            .json()
+            .await
+            .map_err(request_error)?;
+        Ok(response.cache_id)
+    }
+
+    /// Looks up the committed cache entry of the artifact, returns `None` for
+    /// `204 No Content`.
+    async fn lookup(&self, hash: &str) -> Result<Option<CacheEntry>, CacheError> {
+        let key = self.key(hash);
+        let response = self
+            .authorized(self.client.get(self.url("cache")))
+            .query(&[("keys", key.as_str()), ("version", self.version.as_str())])
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(request_error)?;
+        if response.status() == StatusCode::NO_CONTENT {
+            return Ok(None);
+        }
+        response.json().await.map(Some).map_err(request_error)
+    }
+
+    async fn request(&self, request_builder: RequestBuilder) -> Result<(), CacheError> {
+        self.authorized(request_builder)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(request_error)?;
+        Ok(())
+    }
+
+    fn authorized(&self, request_builder: RequestBuilder) -> RequestBuilder {
+        request_builder
+            .header(header::ACCEPT, ACCEPT)
+            .bearer_auth(&self.token)
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}_apis/artifactcache/{}", self.cache_url, path)
+    }
+}
+
+fn request_error(error: reqwest::Error) -> CacheError {
+    turborepo_api_client::Error::ReqwestError(error).into()
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Mutex;
+
+    use anyhow::Result;
+    use tempfile::tempdir;
+    use turbopath::{AbsoluteSystemPathBuf, AnchoredSystemPathBuf};
+
+    use super::{mock::start_test_server, GithubActionsCache};
+    use crate::{CacheError, CacheSource};
+
+    const TOKEN: &str = "actions_runtime_token";
+
+    #[tokio::test]
+    async fn test_round_trip() -> Result<()> {
+        let port = port_scanner::request_open_port().unwrap();
+        let (handle, state) = start_test_server(port, TOKEN).await;
+
+        let repo_root = tempdir()?;
+        let repo_root_path = AbsoluteSystemPathBuf::try_from(repo_root.path())?;
+        let file = AnchoredSystemPathBuf::from_raw("package.json")?;
+        // Random-ish contents, so the compressed artifact spans several chunks.
+        let contents = (0..20_000u32)
+            .map(|i| (i.wrapping_mul(2_654_435_761) >> 7).to_string())
+            .collect::<String>();
+        std::fs::write(repo_root_path.resolve(&file), &contents)?;
+
+        let cache = GithubActionsCache::new(
+            format!("http://localhost:{port}"),
+            TOKEN,
+            repo_root_path.to_owned(),
+        )
+        .with_key_prefix("ci-")
+        .with_chunk_size(16 * 1024);
+
+        let reports = Mutex::new(Vec::new());
+        let reporter = |transferred, total| reports.lock().unwrap().push((transferred, total));
+        let put_response = cache
+            .put(
+                &repo_root_path,
+                "Tokyo Story",
+                vec![file.clone()],
+                Some(&reporter),
+            )
+            .await?;
+        assert!(put_response.bytes_transferred > 32 * 1024);
+        // The first report follows the first chunk.
+        let size = put_response.bytes_transferred;
+        let reports = reports.into_inner().unwrap();
+        assert_eq!(reports.first(), Some(&(16 * 1024, Some(size))));
+        assert_eq!(reports.last(), Some(&(size, Some(size))));
+
+        {
+            let state = state.lock().unwrap();
+            let entry = state.entry("ci-Tokyo Story").unwrap();
+            assert!(entry.committed);
+            assert_eq!(entry.data.len() as u64, put_response.bytes_transferred);
+            assert!(entry.chunks.len() > 2, "{:?}", entry.chunks);
+            assert!(entry
+                .chunks
+                .iter()
+                .all(|&(start, end)| end - start < 16 * 1024));
+        }
+
+        let cache_response = cache.exists("Tokyo Story").await?.unwrap();
+        assert_eq!(cache_response.source, CacheSource::Remote);
+
+        std::fs::remove_file(repo_root_path.resolve(&file))?;
+        let reports = Mutex::new(Vec::new());
+        let reporter = |transferred, total| reports.lock().unwrap().push((transferred, total));
+        let (cache_response, files) = cache
+            .retrieve("Tokyo Story", Some(&reporter))
+            .await?
+            .unwrap();
+        assert_eq!(files, vec![file.clone()]);
+        assert_eq!(
+            cache_response.bytes_transferred,
+            put_response.bytes_transferred
+        );
+        assert_eq!(
+            reports.into_inner().unwrap().last(),
+            Some(&(
+                put_response.bytes_transferred,
+                Some(put_response.bytes_transferred)
+            ))
+        );
+        assert_eq!(
+            std::fs::read_to_string(repo_root_path.resolve(&file))?,
+            contents
+        );
+
+        handle.abort();
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_cache_miss() -> Result<()> {
+        let port = port_scanner::request_open_port().unwrap();
+        let (handle, _) = start_test_server(port, TOKEN).await;
+
+        let repo_root = tempdir()?;
+        let repo_root_path = AbsoluteSystemPathBuf::try_from(repo_root.path())?;
+        let cache = GithubActionsCache::new(
+            format!("http://localhost:{port}/"),
+            TOKEN,
+            repo_root_path.to_owned(),
+        );
+
+        assert_eq!(cache.exists("Late Spring").await?, None);
+        assert_eq!(cache.retrieve("Late Spring", None).await?, None);
+        assert_eq!(std::fs::read_dir(repo_root.path())?.count(), 0);
+
+        // Entries of other versions are not used.
+        let file = AnchoredSystemPathBuf::from_raw("package.json")?;
+        std::fs::write(repo_root_path.resolve(&file), "Floating Weeds")?;
+        cache
+            .put(&repo_root_path, "Late Spring", vec![file], None)
+            .await?;
+        let mut other_version = GithubActionsCache::new(
+            format!("http://localhost:{port}/"),
+            TOKEN,
+            repo_root_path.to_owned(),
+        );
+        other_version.version = "other".to_string();
+        assert_eq!(other_version.exists("Late Spring").await?, None);
+
+        // Requests without the token are rejected.
+        let unauthorized = GithubActionsCache::new(
+            format!("http://localhost:{port}/"),
+            "wrong token",
+            repo_root_path.to_owned(),
+        );
+        assert!(matches!(
+            unauthorized.exists("Late Spring").await,
+            Err(CacheError::ApiClientError(..))
+        ));
+
+        handle.abort();
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_reserve_conflict() -> Result<()> {
+        let port = port_scanner::request_open_port().unwrap();
+        let (handle, state) = start_test_server(port, TOKEN).await;
+
+        let repo_root = tempdir()?;
+        let repo_root_path = AbsoluteSystemPathBuf::try_from(repo_root.path())?;
+        let file = AnchoredSystemPathBuf::from_raw("package.json")?;
+        std::fs::write(repo_root_path.resolve(&file), "Equinox Flower")?;
+        let cache = GithubActionsCache::new(
+            format!("http://localhost:{port}"),
+            TOKEN,
+            repo_root_path.to_owned(),
+        );
+
+        // Another job reserved the entry and is still uploading.
+        state
+            .lock()
+            .unwrap()
+            .reserve("turbo-Good Morning", &cache.version);
+
+        assert!(matches!(
+            cache
+                .put(&repo_root_path, "Good Morning", vec![file], None)
+                .await,
+            Err(CacheError::CacheEntryReserved(key, _)) if key == "turbo-Good Morning"
+        ));
+        // The entry is not committed yet.
+        assert_eq!(cache.exists("Good Morning").await?, None);
+
+        handle.abort();
+        Ok(())
+    }
+}
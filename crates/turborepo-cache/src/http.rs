@@ -1,18 +1,141 @@
 use std::{backtrace::Backtrace, io::Write};
 
-use turbopath::{AbsoluteSystemPath, AbsoluteSystemPathBuf, AnchoredSystemPathBuf};
-use turborepo_api_client::{APIClient, Response};
+use reqwest::StatusCode;
+use sha2::{Digest, Sha512};
+use tracing::debug;
+use turbopath::{
+    AbsoluteSystemPath, AbsoluteSystemPathBuf, AnchoredSystemPath, AnchoredSystemPathBuf,
+};
+use turborepo_api_client::{APIClient, HeaderMap, HeaderName, HeaderValue, Response};
 
 use crate::{
-    cache_archive::{CacheReader, CacheWriter},
+    cache_archive::{CacheReader, CacheWriter, DirWalker, PartialRestore, RestoreLimits},
+    progress::{ProgressBody, ProgressReporter, ThrottledProgress},
     signature_authentication::ArtifactSignatureAuthenticator,
-    CacheError, CacheResponse, CacheSource,
+    CacheError, CacheResponse, CacheSource, PutResponse,
 };
 
+/// The headers that extra headers can't override: the ones the API client
+/// sets itself, and headers named like the `teamId` and `teamSlug` query
+/// parameters, which scope a request to a team.
+const RESERVED_HEADERS: &[&str] = &[
+    "authorization",
+    "content-type",
+    "content-length",
+    "teamid",
+    "teamslug",
+];
+
+/// The prefix of the artifact metadata headers, e.g. `x-artifact-duration` or
+/// `x-artifact-tag`, which are reserved as well.
+const RESERVED_HEADER_PREFIX: &str = "x-artifact-";
+
+/// Parses the value of the `x-artifact-duration` header, failing with
+/// [`CacheError::InvalidDuration`] unless it is a valid `u32`.
+pub fn parse_duration(duration: &HeaderValue) -> Result<u32, CacheError> {
+    duration
+        .to_str()
+        .ok()
+        .and_then(|duration| duration.parse().ok())
+        .ok_or_else(|| CacheError::InvalidDuration(Backtrace::capture()))
+}
+
+/// Parses the value of the `x-artifact-duration` header. The duration is only
+/// reported as the time saved, so instead of failing, negative durations are
+/// clamped to 0, durations which don't fit into a `u32` to `u32::MAX`, and
+/// malformed ones are treated as 0.
+pub fn parse_duration_lenient(duration: &HeaderValue) -> u32 {
+    if let Ok(parsed) = parse_duration(duration) {
+        return parsed;
+    }
+    let coerced = duration
+        .to_str()
+        .ok()
+        .and_then(|duration| clamp_duration(duration.trim()))
+        .unwrap_or(0);
+    debug!("coerced x-artifact-duration {:?} to {}", duration, coerced);
+    coerced
+}
+
+/// Clamps integers outside of the range of a `u32`. Returns `None` for
+/// anything else.
+fn clamp_duration(duration: &str) -> Option<u32> {
+    let (negative, digits) = match duration.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, duration.strip_prefix('+').unwrap_or(duration)),
+    };
+    if digits.is_empty() || !digits.bytes().all(|byte| byte.is_ascii_digit()) {
+        return None;
+    }
+    if negative {
+        return Some(0);
+    }
+    // Only overflows fail to parse at this point.
+    Some(digits.parse().unwrap_or(u32::MAX))
+}
+
+/// Authentication, team scoping and extra headers for the requests to the
+/// remote cache. Built once per run and passed to every operation.
+#[derive(Debug, Clone, Default)]
+pub struct CacheRequestContext {
+    token: String,
+    team_id: String,
+    team_slug: Option<String>,
+    use_preflight: bool,
+    headers: HeaderMap,
+}
+
+impl CacheRequestContext {
+    pub fn new(token: impl Into<String>) -> Self {
+        Self {
+            token: token.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Scopes the requests to the given team.
+    pub fn with_team(mut self, team_id: impl Into<String>, team_slug: Option<String>) -> Self {
+        self.team_id = team_id.into();
+        self.team_slug = team_slug;
+        self
+    }
+
+    /// Sets whether to send a preflight request before retrieving artifacts.
+    /// Uploads use the preflight setting of the API client.
+    pub fn with_preflight(mut self, use_preflight: bool) -> Self {
+        self.use_preflight = use_preflight;
+        self
+    }
+
+    /// Adds a header that is sent with every request, e.g. `traceparent`.
+    /// Fails if the name or value is not a valid header, or if the header is
+    /// reserved, e.g. because it's used for authentication or artifact
+    /// metadata.
+    pub fn with_header(mut self, name: &str, value: &str) -> Result<Self, CacheError> {
+        let header_name = HeaderName::from_bytes(name.as_bytes())
+            .map_err(|_| CacheError::InvalidHeaderName(name.to_string(), Backtrace::capture()))?;
+        if RESERVED_HEADERS.contains(&header_name.as_str())
+            || header_name.as_str().starts_with(RESERVED_HEADER_PREFIX)
+        {
+            return Err(CacheError::ReservedHeader(
+                name.to_string(),
+                Backtrace::capture(),
+            ));
+        }
+        let header_value = HeaderValue::from_str(value)
+            .map_err(|_| CacheError::InvalidHeaderValue(name.to_string(), Backtrace::capture()))?;
+        self.headers.insert(header_name, header_value);
+        Ok(self)
+    }
+}
+
 pub struct HttpCache {
     client: APIClient,
     signer_verifier: Option<ArtifactSignatureAuthenticator>,
     repo_root: AbsoluteSystemPathBuf,
+    restore_limits: RestoreLimits,
+    lenient_versions: bool,
+    strict_durations: bool,
 }
 
 impl HttpCache {
@@ -25,9 +148,36 @@ impl HttpCache {
             client,
             signer_verifier,
             repo_root,
+            restore_limits: RestoreLimits::default(),
+            lenient_versions: false,
+            strict_durations: false,
         }
     }
 
+    /// Sets the limits that are enforced when restoring retrieved artifacts.
+    /// Use [`RestoreLimits::unlimited`] to disable them.
+    pub fn with_restore_limits(mut self, restore_limits: RestoreLimits) -> Self {
+        self.restore_limits = restore_limits;
+        self
+    }
+
+    /// In lenient mode, artifacts which require a newer version of turbo to
+    /// be restored are treated as cache misses instead of failing with
+    /// [`CacheError::UnsupportedArtifactVersion`].
+    pub fn with_lenient_versions(mut self, lenient_versions: bool) -> Self {
+        self.lenient_versions = lenient_versions;
+        self
+    }
+
+    /// By default, invalid `x-artifact-duration` headers are coerced, see
+    /// [`parse_duration_lenient`]. In strict mode, they fail the request with
+    /// [`CacheError::InvalidDuration`] instead.
+    pub fn with_strict_durations(mut self, strict_durations: bool) -> Self {
+        self.strict_durations = strict_durations;
+        self
+    }
+
+    /// Uploads the given files. See [`HttpCache::put_with_context`].
     pub async fn put(
         &self,
         anchor: &AbsoluteSystemPath,
@@ -35,25 +185,112 @@ impl HttpCache {
         files: Vec<AnchoredSystemPathBuf>,
         duration: u32,
         token: &str,
-    ) -> Result<(), CacheError> {
+        progress: Option<&dyn ProgressReporter>,
+    ) -> Result<PutResponse, CacheError> {
+        let context = CacheRequestContext::new(token);
+        self.put_with_context(anchor, hash, files, duration, &context, progress)
+            .await
+    }
+
+    /// Uploads the given files. The progress is reported while the artifact
+    /// body is sent, its total is the size of the body. The final report is
+    /// sent after the upload has finished. Returns the size of the uploaded
+    /// body.
+    pub async fn put_with_context(
+        &self,
+        anchor: &AbsoluteSystemPath,
+        hash: &str,
+        files: Vec<AnchoredSystemPathBuf>,
+        duration: u32,
+        context: &CacheRequestContext,
+        progress: Option<&dyn ProgressReporter>,
+    ) -> Result<PutResponse, CacheError> {
         let mut artifact_body = Vec::new();
-        self.write(&mut artifact_body, anchor, files).await?;
+        Self::write(&mut artifact_body, anchor, files)?;
+        self.upload(hash, artifact_body, duration, context, progress)
+            .await
+    }
 
+    async fn upload(
+        &self,
+        hash: &str,
+        artifact_body: Vec<u8>,
+        duration: u32,
+        context: &CacheRequestContext,
+        progress: Option<&dyn ProgressReporter>,
+    ) -> Result<PutResponse, CacheError> {
         let tag = self
             .signer_verifier
             .as_ref()
             .map(|signer| signer.generate_tag(hash.as_bytes(), &artifact_body))
             .transpose()?;
 
-        self.client
-            .put_artifact(hash, &artifact_body, duration, tag.as_deref(), token)
-            .await?;
+        let body = ProgressBody::new(artifact_body);
+        body.report(
+            progress,
+            self.client.put_artifact(
+                hash,
+                || body.stream(),
+                body.len(),
+                duration,
+                tag.as_deref(),
+                &context.token,
+                &context.team_id,
+                context.team_slug.as_deref(),
+                &context.headers,
+            ),
+        )
+        .await?;
 
-        Ok(())
+        Ok(PutResponse {
+            bytes_transferred: body.len(),
+        })
     }
 
-    async fn write(
+    /// Uploads `dir` and everything below it, as collected by `walker`.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn put_dir(
         &self,
+        anchor: &AbsoluteSystemPath,
+        dir: &AnchoredSystemPath,
+        walker: &DirWalker<'_>,
+        hash: &str,
+        duration: u32,
+        token: &str,
+        progress: Option<&dyn ProgressReporter>,
+    ) -> Result<PutResponse, CacheError> {
+        let context = CacheRequestContext::new(token);
+        self.put_dir_with_context(anchor, dir, walker, hash, duration, &context, progress)
+            .await
+    }
+
+    /// Uploads `dir` and everything below it, as collected by `walker`, with
+    /// the given request context.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn put_dir_with_context(
+        &self,
+        anchor: &AbsoluteSystemPath,
+        dir: &AnchoredSystemPath,
+        walker: &DirWalker<'_>,
+        hash: &str,
+        duration: u32,
+        context: &CacheRequestContext,
+        progress: Option<&dyn ProgressReporter>,
+    ) -> Result<PutResponse, CacheError> {
+        let files = walker.walk(anchor, dir)?;
+        if !walker.follows_symlinks() {
+            return self
+                .put_with_context(anchor, hash, files, duration, context, progress)
+                .await;
+        }
+
+        let mut artifact_body = Vec::new();
+        Self::write_following_symlinks(&mut artifact_body, anchor, files)?;
+        self.upload(hash, artifact_body, duration, context, progress)
+            .await
+    }
+
+    pub(crate) fn write(
         writer: impl Write,
         anchor: &AbsoluteSystemPath,
         files: Vec<AnchoredSystemPathBuf>,
@@ -66,6 +303,21 @@ impl HttpCache {
         Ok(())
     }
 
+    /// Like [`HttpCache::write`], but symlinks are followed, so the items they
+    /// point to are added in their place.
+    fn write_following_symlinks(
+        writer: impl Write,
+        anchor: &AbsoluteSystemPath,
+        files: Vec<AnchoredSystemPathBuf>,
+    ) -> Result<(), CacheError> {
+        let mut cache_archive = CacheWriter::from_writer(writer, true)?;
+        for file in files {
+            cache_archive.add_file_following_symlinks(anchor, &file)?;
+        }
+
+        Ok(())
+    }
+
     pub async fn exists(
         &self,
         hash: &str,
@@ -73,34 +325,76 @@ impl HttpCache {
         team_id: &str,
         team_slug: Option<&str>,
         use_preflight: bool,
-    ) -> Result<CacheResponse, CacheError> {
+    ) -> Result<Option<CacheResponse>, CacheError> {
+        let context = CacheRequestContext::new(token)
+            .with_team(team_id, team_slug.map(|slug| slug.to_string()))
+            .with_preflight(use_preflight);
+        self.exists_with_context(hash, &context).await
+    }
+
+    /// Checks whether an artifact exists. Returns `None` when it is not
+    /// cached, while a failing request, e.g. due to missing permissions, is an
+    /// error.
+    pub async fn exists_with_context(
+        &self,
+        hash: &str,
+        context: &CacheRequestContext,
+    ) -> Result<Option<CacheResponse>, CacheError> {
         let response = self
             .client
-            .artifact_exists(hash, token, team_id, team_slug, use_preflight)
-            .await?;
+            .artifact_exists(
+                hash,
+                &context.token,
+                &context.team_id,
+                context.team_slug.as_deref(),
+                context.use_preflight,
+                &context.headers,
+            )
+            .await;
+        let Some(response) = Self::artifact_response(response)? else {
+            return Ok(None);
+        };
 
-        let duration = Self::get_duration_from_response(&response)?;
+        let duration = self.get_duration_from_response(&response)?;
 
-        Ok(CacheResponse {
+        Ok(Some(CacheResponse {
             source: CacheSource::Remote,
             time_saved: duration,
-        })
+            // Only the headers are transferred.
+            bytes_transferred: 0,
+        }))
     }
 
-    fn get_duration_from_response(response: &Response) -> Result<u32, CacheError> {
-        if let Some(duration_value) = response.headers().get("x-artifact-duration") {
-            let duration = duration_value
-                .to_str()
-                .map_err(|_| CacheError::InvalidDuration(Backtrace::capture()))?;
+    /// Returns `None` for responses which signal that an artifact is not
+    /// cached, i.e. `404 Not Found` and `204 No Content`.
+    fn artifact_response(
+        response: turborepo_api_client::Result<Response>,
+    ) -> Result<Option<Response>, CacheError> {
+        match response {
+            Ok(response) if response.status() == StatusCode::NO_CONTENT => Ok(None),
+            Ok(response) => Ok(Some(response)),
+            Err(turborepo_api_client::Error::ReqwestError(err))
+                if err.status() == Some(StatusCode::NOT_FOUND) =>
+            {
+                Ok(None)
+            }
+            Err(err) => Err(err.into()),
+        }
+    }
 
-            duration
-                .parse::<u32>()
-                .map_err(|_| CacheError::InvalidDuration(Backtrace::capture()))
+    fn get_duration_from_response(&self, response: &Response) -> Result<u32, CacheError> {
+        let Some(duration) = response.headers().get("x-artifact-duration") else {
+            return Ok(0);
+        };
+        if self.strict_durations {
+            parse_duration(duration)
         } else {
-            Ok(0)
+            Ok(parse_duration_lenient(duration))
         }
     }
 
+    /// Downloads and restores an artifact. See
+    /// [`HttpCache::retrieve_with_context`].
     pub async fn retrieve(
         &self,
         hash: &str,
@@ -108,13 +402,145 @@ impl HttpCache {
         team_id: &str,
         team_slug: Option<&str>,
         use_preflight: bool,
-    ) -> Result<(CacheResponse, Vec<AnchoredSystemPathBuf>), CacheError> {
+        progress: Option<&dyn ProgressReporter>,
+    ) -> Result<Option<(CacheResponse, Vec<AnchoredSystemPathBuf>)>, CacheError> {
+        let context = CacheRequestContext::new(token)
+            .with_team(team_id, team_slug.map(|slug| slug.to_string()))
+            .with_preflight(use_preflight);
+        self.retrieve_with_context(hash, &context, progress).await
+    }
+
+    /// Downloads and restores an artifact. The total reported to `progress`
+    /// is taken from the `Content-Length` of the response, when present.
+    /// Returns `None` when the artifact is not cached, without writing any
+    /// files.
+    ///
+    /// The artifact is only restored once it has been downloaded completely
+    /// and verified, and the restore doesn't yield to the runtime. Dropping
+    /// the returned future therefore stops the transfer at the next chunk and
+    /// never leaves a partially restored artifact behind. See
+    /// [`HttpCache::retrieve_cancellable`] to cancel with a signal instead.
+    pub async fn retrieve_with_context(
+        &self,
+        hash: &str,
+        context: &CacheRequestContext,
+        progress: Option<&dyn ProgressReporter>,
+    ) -> Result<Option<(CacheResponse, Vec<AnchoredSystemPathBuf>)>, CacheError> {
+        self.retrieve_into(&self.repo_root, hash, context, progress)
+            .await
+    }
+
+    /// Like [`HttpCache::retrieve_with_context`], but stops the download as
+    /// soon as `cancelled` completes, e.g. when the user hits Ctrl-C, and
+    /// returns [`CacheError::Cancelled`].
+    ///
+    /// Cancellation is all-or-nothing: it is only observed while the artifact
+    /// is downloaded, before any file has been written. Once the download has
+    /// finished the artifact is restored completely, even if `cancelled`
+    /// completes in the meantime.
+    pub async fn retrieve_cancellable(
+        &self,
+        hash: &str,
+        context: &CacheRequestContext,
+        progress: Option<&dyn ProgressReporter>,
+        cancelled: impl std::future::Future<Output = ()>,
+    ) -> Result<Option<(CacheResponse, Vec<AnchoredSystemPathBuf>)>, CacheError> {
+        let download = tokio::select! {
+            biased;
+            _ = cancelled => {
+                return Err(CacheError::Cancelled(hash.to_string(), Backtrace::capture()));
+            }
+            download = self.download_artifact(hash, context, progress) => download?,
+        };
+        let Some((response, body)) = download else {
+            return Ok(None);
+        };
+        let files = self.restore_or_miss(Self::restore_tar(
+            &self.repo_root,
+            &body,
+            self.restore_limits,
+        ))?;
+        Ok(files.map(|files| (response, files)))
+    }
+
+    /// Downloads an artifact and restores it into `root` instead of the repo
+    /// root, e.g. into a scratch directory to compare it with the working
+    /// tree. The returned paths are relative to `root`.
+    pub async fn retrieve_into(
+        &self,
+        root: &AbsoluteSystemPath,
+        hash: &str,
+        context: &CacheRequestContext,
+        progress: Option<&dyn ProgressReporter>,
+    ) -> Result<Option<(CacheResponse, Vec<AnchoredSystemPathBuf>)>, CacheError> {
+        let Some((response, body)) = self.download_artifact(hash, context, progress).await? else {
+            return Ok(None);
+        };
+        let files = self.restore_or_miss(Self::restore_tar(root, &body, self.restore_limits))?;
+        Ok(files.map(|files| (response, files)))
+    }
+
+    /// Downloads an artifact, but only restores the files at `paths`, or
+    /// inside of a directory at one of them, e.g. `dist` restores everything
+    /// under `dist/`. The requested paths which aren't in the artifact are
+    /// returned as missing. Returns `None` when the artifact is not cached.
+    ///
+    /// The whole artifact is downloaded and checked like for
+    /// [`HttpCache::retrieve_with_context`].
+    pub async fn retrieve_files(
+        &self,
+        hash: &str,
+        paths: &[AnchoredSystemPathBuf],
+        context: &CacheRequestContext,
+        progress: Option<&dyn ProgressReporter>,
+    ) -> Result<Option<(CacheResponse, PartialRestore)>, CacheError> {
+        let Some((response, body)) = self.download_artifact(hash, context, progress).await? else {
+            return Ok(None);
+        };
+        let restore = self.restore_or_miss(Self::restore_tar_paths(
+            &self.repo_root,
+            &body,
+            paths,
+            self.restore_limits,
+        ))?;
+        Ok(restore.map(|restore| (response, restore)))
+    }
+
+    /// Turns an artifact which requires a newer reader into a cache miss in
+    /// lenient mode. Nothing has been written for it, as the version is
+    /// checked before the first file is restored.
+    fn restore_or_miss<T>(&self, restore: Result<T, CacheError>) -> Result<Option<T>, CacheError> {
+        match restore {
+            Ok(restored) => Ok(Some(restored)),
+            Err(CacheError::UnsupportedArtifactVersion { .. }) if self.lenient_versions => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Downloads an artifact and verifies its signature, when signing is
+    /// enabled. Returns `None` when the artifact is not cached.
+    async fn download_artifact(
+        &self,
+        hash: &str,
+        context: &CacheRequestContext,
+        progress: Option<&dyn ProgressReporter>,
+    ) -> Result<Option<(CacheResponse, Vec<u8>)>, CacheError> {
         let response = self
             .client
-            .fetch_artifact(hash, token, team_id, team_slug, use_preflight)
-            .await?;
+            .fetch_artifact(
+                hash,
+                &context.token,
+                &context.team_id,
+                context.team_slug.as_deref(),
+                context.use_preflight,
+                &context.headers,
+            )
+            .await;
+        let Some(response) = Self::artifact_response(response)? else {
+            return Ok(None);
+        };
 
-        let duration = Self::get_duration_from_response(&response)?;
+        let duration = self.get_duration_from_response(&response)?;
 
         let body = if let Some(signer_verifier) = &self.signer_verifier {
             let expected_tag = response
@@ -127,12 +553,7 @@ impl HttpCache {
                 .map_err(|_| CacheError::InvalidTag(Backtrace::capture()))?
                 .to_string();
 
-            let body = response.bytes().await.map_err(|e| {
-                CacheError::ApiClientError(
-                    Box::new(turborepo_api_client::Error::ReqwestError(e)),
-                    Backtrace::capture(),
-                )
-            })?;
+            let body = Self::read_body(response, progress).await?;
             let is_valid = signer_verifier.validate(hash.as_bytes(), &body, &expected_tag)?;
 
             if !is_valid {
@@ -141,44 +562,115 @@ impl HttpCache {
 
             body
         } else {
-            response.bytes().await.map_err(|e| {
-                CacheError::ApiClientError(
-                    Box::new(turborepo_api_client::Error::ReqwestError(e)),
-                    Backtrace::capture(),
-                )
-            })?
+            Self::read_body(response, progress).await?
         };
 
-        let files = Self::restore_tar(&self.repo_root, &body)?;
-
-        Ok((
+        Ok(Some((
             CacheResponse {
                 source: CacheSource::Remote,
                 time_saved: duration,
+                bytes_transferred: body.len() as u64,
             },
-            files,
-        ))
+            body,
+        )))
     }
 
+    /// Reads the body of the response chunk by chunk, reporting the progress.
+    pub(crate) async fn read_body(
+        mut response: Response,
+        progress: Option<&dyn ProgressReporter>,
+    ) -> Result<Vec<u8>, CacheError> {
+        let total_bytes = response.content_length();
+        let mut progress = ThrottledProgress::new(progress, total_bytes);
+        let mut body = Vec::with_capacity(total_bytes.unwrap_or_default() as usize);
+        while let Some(chunk) = response.chunk().await.map_err(|e| {
+            CacheError::ApiClientError(
+                Box::new(turborepo_api_client::Error::ReqwestError(e)),
+                Backtrace::capture(),
+            )
+        })? {
+            body.extend_from_slice(&chunk);
+            progress.advance(chunk.len() as u64);
+        }
+        progress.finish();
+        Ok(body)
+    }
+
+    /// Restores an artifact into `root`. The artifact may come from an
+    /// untrusted cache, so all of its paths are checked while it is restored,
+    /// and an artifact with an entry outside of `root` is rejected with
+    /// [`CacheError::PathEscapesRoot`] after removing what was restored.
     pub(crate) fn restore_tar(
         root: &AbsoluteSystemPath,
         body: &[u8],
+        limits: RestoreLimits,
     ) -> Result<Vec<AnchoredSystemPathBuf>, CacheError> {
-        let mut cache_reader = CacheReader::from_reader(body, true)?;
+        let mut cache_reader = CacheReader::from_reader(body, true)?.with_limits(limits);
         cache_reader.restore(root)
     }
+
+    /// Like [`HttpCache::restore_tar`], but only restores the entries at or
+    /// below `paths`. All paths of the artifact are still checked.
+    pub(crate) fn restore_tar_paths(
+        root: &AbsoluteSystemPath,
+        body: &[u8],
+        paths: &[AnchoredSystemPathBuf],
+        limits: RestoreLimits,
+    ) -> Result<PartialRestore, CacheError> {
+        let mut cache_reader = CacheReader::from_reader(body, true)?.with_limits(limits);
+        cache_reader.restore_paths(root, paths)
+    }
+}
+
+/// Derives a cache key from a set of files and a salt, so that independent
+/// producers and consumers of the cache agree on the key for the same inputs.
+///
+/// The key is the hex encoded SHA-512 (the same digest used for cache
+/// archives) of the salt and the file paths. Paths are normalized to use `/`
+/// as separator and are sorted, so the key does not depend on the platform or
+/// on the order of `files`.
+pub fn compute_artifact_key(files: &[AnchoredSystemPathBuf], salt: &str) -> String {
+    let mut paths = files
+        .iter()
+        .map(|file| {
+            file.components()
+                .map(|component| component.as_str())
+                .collect::<Vec<_>>()
+                .join("/")
+        })
+        .collect::<Vec<_>>();
+    paths.sort();
+    paths.dedup();
+
+    let mut hasher = Sha512::new();
+    hasher.update(salt.as_bytes());
+    for path in paths {
+        // Separate entries so that e.g. ["ab"] and ["a", "b"] hash differently.
+        hasher.update([0]);
+        hasher.update(path.as_bytes());
+    }
+    hex::encode(hasher.finalize())
 }
 
 #[cfg(test)]
 mod test {
+    use std::sync::Mutex;
+
     use anyhow::Result;
     use tempfile::tempdir;
     use test_case::test_case;
-    use turbopath::{AbsoluteSystemPathBuf, AnchoredSystemPathBuf};
-    use turborepo_api_client::APIClient;
-    use vercel_api_mock::start_test_server;
+    use turbopath::{AbsoluteSystemPathBuf, AnchoredSystemPath, AnchoredSystemPathBuf};
+    use turborepo_api_client::{APIClient, HeaderValue};
+    use vercel_api_mock::{start_test_server, FORBIDDEN_ARTIFACT_HASH};
 
-    use crate::{http::HttpCache, CacheSource};
+    use crate::{
+        cache_archive::{DirWalker, RestoreLimits},
+        http::{
+            compute_artifact_key, parse_duration, parse_duration_lenient, CacheRequestContext,
+            HttpCache,
+        },
+        CacheError, CacheSource,
+    };
 
     struct TestFile {
         path: AnchoredSystemPathBuf,
@@ -233,23 +725,33 @@ mod test {
 
         let cache = HttpCache::new(api_client, None, repo_root_path.to_owned());
 
-        cache
+        let put_response = cache
             .put(
                 &repo_root_path,
                 hash,
                 files.iter().map(|f| f.path.clone()).collect(),
                 duration,
                 "",
+                None,
             )
             .await?;
+        assert!(put_response.bytes_transferred > 0);
 
-        let cache_response = cache.exists(hash, "", "", None, false).await?;
+        let cache_response = cache.exists(hash, "", "", None, false).await?.unwrap();
 
         assert_eq!(cache_response.time_saved, duration);
         assert_eq!(cache_response.source, CacheSource::Remote);
+        assert_eq!(cache_response.bytes_transferred, 0);
 
-        let (cache_response, received_files) = cache.retrieve(hash, "", "", None, false).await?;
+        let (cache_response, received_files) = cache
+            .retrieve(hash, "", "", None, false, None)
+            .await?
+            .unwrap();
         assert_eq!(cache_response.time_saved, duration);
+        assert_eq!(
+            cache_response.bytes_transferred,
+            put_response.bytes_transferred
+        );
 
         for (test_file, received_file) in files.iter().zip(received_files) {
             assert_eq!(received_file, test_file.path);
@@ -260,4 +762,549 @@ mod test {
         handle.abort();
         Ok(())
     }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_round_trip_preserves_mode() -> Result<()> {
+        use std::{fs, os::unix::fs::PermissionsExt};
+
+        let port = port_scanner::request_open_port().unwrap();
+        let handle = tokio::spawn(start_test_server(port));
+
+        let repo_root = tempdir()?;
+        let repo_root_path = AbsoluteSystemPathBuf::try_from(repo_root.path())?;
+        let file = AnchoredSystemPathBuf::from_raw("build.sh")?;
+        let file_path = repo_root_path.resolve(&file);
+        fs::write(&file_path, "#!/bin/sh\necho Playtime\n")?;
+        fs::set_permissions(&file_path, fs::Permissions::from_mode(0o755))?;
+
+        let api_client = APIClient::new(format!("http://localhost:{}", port), 200, "2.0.0", true)?;
+        let cache = HttpCache::new(api_client, None, repo_root_path.to_owned());
+        cache
+            .put(
+                &repo_root_path,
+                "Playtime",
+                vec![file.clone()],
+                10,
+                "",
+                None,
+            )
+            .await?;
+
+        // The existing file isn't executable, so the mode has to be restored.
+        fs::set_permissions(&file_path, fs::Permissions::from_mode(0o644))?;
+        cache
+            .retrieve("Playtime", "", "", None, false, None)
+            .await?
+            .unwrap();
+        let mode = fs::metadata(&file_path)?.permissions().mode();
+        assert_eq!(mode & 0o777, 0o755);
+
+        handle.abort();
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_put_dir_following_symlinks() -> Result<()> {
+        use std::{fs, os::unix::fs::symlink};
+
+        let port = port_scanner::request_open_port().unwrap();
+        let handle = tokio::spawn(start_test_server(port));
+
+        let repo_root = tempdir()?;
+        let repo_root_path = AbsoluteSystemPathBuf::try_from(repo_root.path())?;
+        fs::create_dir_all(repo_root.path().join("dist"))?;
+        fs::create_dir_all(repo_root.path().join("shared"))?;
+        fs::write(repo_root.path().join("shared/file.js"), "Petite Maman")?;
+        symlink("../shared", repo_root.path().join("dist/shared"))?;
+        symlink("../shared/file.js", repo_root.path().join("dist/file.js"))?;
+
+        let api_client = APIClient::new(format!("http://localhost:{}", port), 200, "2.0.0", true)?;
+        let cache = HttpCache::new(api_client, None, repo_root_path.to_owned());
+        cache
+            .put_dir(
+                &repo_root_path,
+                AnchoredSystemPath::new("dist")?,
+                &DirWalker::new().follow_symlinks(true),
+                "Petite Maman",
+                10,
+                "",
+                None,
+            )
+            .await?;
+
+        // The links are restored as the directory and file they point to.
+        fs::remove_dir_all(repo_root.path().join("dist"))?;
+        fs::remove_dir_all(repo_root.path().join("shared"))?;
+        cache
+            .retrieve("Petite Maman", "", "", None, false, None)
+            .await?
+            .unwrap();
+        let shared = fs::symlink_metadata(repo_root.path().join("dist/shared"))?;
+        assert!(shared.is_dir());
+        let file = fs::symlink_metadata(repo_root.path().join("dist/file.js"))?;
+        assert!(file.is_file());
+        for path in ["dist/shared/file.js", "dist/file.js"] {
+            assert_eq!(
+                fs::read_to_string(repo_root.path().join(path))?,
+                "Petite Maman"
+            );
+        }
+
+        handle.abort();
+        Ok(())
+    }
+
+    fn assert_progress(reports: Vec<(u64, Option<u64>)>) -> u64 {
+        assert!(!reports.is_empty());
+        for window in reports.windows(2) {
+            assert!(window[0].0 <= window[1].0, "{reports:?}");
+        }
+        let &(transferred, total) = reports.last().unwrap();
+        assert_eq!(total, Some(transferred));
+        transferred
+    }
+
+    #[tokio::test]
+    async fn test_progress() -> Result<()> {
+        let port = port_scanner::request_open_port().unwrap();
+        let handle = tokio::spawn(start_test_server(port));
+
+        let repo_root = tempdir()?;
+        let repo_root_path = AbsoluteSystemPathBuf::try_from(repo_root.path())?;
+        let file = AnchoredSystemPathBuf::from_raw("package.json")?;
+        std::fs::write(repo_root_path.resolve(&file), "Paris, Texas".repeat(1000))?;
+
+        let api_client = APIClient::new(format!("http://localhost:{}", port), 200, "2.0.0", true)?;
+        let cache = HttpCache::new(api_client, None, repo_root_path.to_owned());
+
+        let put_reports = Mutex::new(Vec::new());
+        let reporter = |transferred, total| put_reports.lock().unwrap().push((transferred, total));
+        let put_response = cache
+            .put(
+                &repo_root_path,
+                "Wings of Desire",
+                vec![file.clone()],
+                10,
+                "",
+                Some(&reporter),
+            )
+            .await?;
+        let uploaded = assert_progress(put_reports.into_inner().unwrap());
+        assert_eq!(put_response.bytes_transferred, uploaded);
+
+        let retrieve_reports = Mutex::new(Vec::new());
+        let reporter =
+            |transferred, total| retrieve_reports.lock().unwrap().push((transferred, total));
+        let (cache_response, _) = cache
+            .retrieve("Wings of Desire", "", "", None, false, Some(&reporter))
+            .await?
+            .unwrap();
+        let downloaded = assert_progress(retrieve_reports.into_inner().unwrap());
+        assert_eq!(downloaded, uploaded);
+        assert_eq!(cache_response.bytes_transferred, downloaded);
+
+        handle.abort();
+        Ok(())
+    }
+
+    const TRACEPARENT: &str = "00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01";
+
+    #[tokio::test]
+    async fn test_request_context() -> Result<()> {
+        let port = port_scanner::request_open_port().unwrap();
+        let handle = tokio::spawn(start_test_server(port));
+
+        let repo_root = tempdir()?;
+        let repo_root_path = AbsoluteSystemPathBuf::try_from(repo_root.path())?;
+        let file = AnchoredSystemPathBuf::from_raw("package.json")?;
+        std::fs::write(repo_root_path.resolve(&file), "Stalker")?;
+
+        let base_url = format!("http://localhost:{}", port);
+        let api_client = APIClient::new(&base_url, 200, "2.0.0", true)?;
+        let cache = HttpCache::new(api_client, None, repo_root_path.to_owned());
+
+        let context = CacheRequestContext::new("")
+            .with_team("team_tarkovsky", Some("tarkovsky".to_string()))
+            .with_header("traceparent", TRACEPARENT)?
+            .with_header("x-turbo-interactive", "false")?;
+
+        cache
+            .put_with_context(
+                &repo_root_path,
+                "Solaris",
+                vec![file.clone()],
+                42,
+                &context,
+                None,
+            )
+            .await?;
+        let cache_response = cache
+            .exists_with_context("Solaris", &context)
+            .await?
+            .unwrap();
+        assert_eq!(cache_response.time_saved, 42);
+        let (cache_response, files) = cache
+            .retrieve_with_context("Solaris", &context, None)
+            .await?
+            .unwrap();
+        assert_eq!(cache_response.time_saved, 42);
+        assert_eq!(files, vec![file]);
+
+        // The mock server reports the `traceparent` it received for the upload
+        // and echoes the one of the current request.
+        let api_client = APIClient::new(&base_url, 200, "2.0.0", true)?;
+        let response = api_client
+            .artifact_exists("Solaris", "", "", None, false, &context.headers)
+            .await?;
+        assert_eq!(
+            response.headers().get("x-artifact-traceparent").unwrap(),
+            TRACEPARENT
+        );
+        assert_eq!(
+            response.headers().get("traceresponse").unwrap(),
+            TRACEPARENT
+        );
+        let response = api_client
+            .fetch_artifact("Solaris", "", "", None, false, &context.headers)
+            .await?;
+        assert_eq!(
+            response.headers().get("traceresponse").unwrap(),
+            TRACEPARENT
+        );
+
+        handle.abort();
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_retrieve_into() -> Result<()> {
+        let port = port_scanner::request_open_port().unwrap();
+        let handle = tokio::spawn(start_test_server(port));
+
+        let repo_root = tempdir()?;
+        let repo_root_path = AbsoluteSystemPathBuf::try_from(repo_root.path())?;
+        let file = AnchoredSystemPathBuf::from_raw(
+            ["dist", "index.js"].join(std::path::MAIN_SEPARATOR_STR),
+        )?;
+        let repo_file_path = repo_root_path.resolve(&file);
+        std::fs::create_dir_all(repo_file_path.parent().unwrap())?;
+        std::fs::write(&repo_file_path, "Chungking Express")?;
+
+        let api_client = APIClient::new(format!("http://localhost:{}", port), 200, "2.0.0", true)?;
+        let cache = HttpCache::new(api_client, None, repo_root_path.to_owned());
+        let context = CacheRequestContext::new("");
+        cache
+            .put_with_context(
+                &repo_root_path,
+                "In the Mood for Love",
+                vec![file.clone()],
+                10,
+                &context,
+                None,
+            )
+            .await?;
+
+        std::fs::write(&repo_file_path, "Fallen Angels")?;
+
+        let scratch = tempdir()?;
+        let scratch_path = AbsoluteSystemPathBuf::try_from(scratch.path())?;
+        let (cache_response, files) = cache
+            .retrieve_into(&scratch_path, "In the Mood for Love", &context, None)
+            .await?
+            .unwrap();
+        assert_eq!(cache_response.time_saved, 10);
+        assert_eq!(files, vec![file.clone()]);
+        assert_eq!(
+            std::fs::read_to_string(scratch_path.resolve(&file))?,
+            "Chungking Express"
+        );
+        // The working tree is left untouched.
+        assert_eq!(std::fs::read_to_string(&repo_file_path)?, "Fallen Angels");
+
+        handle.abort();
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_retrieve_files() -> Result<()> {
+        let port = port_scanner::request_open_port().unwrap();
+        let handle = tokio::spawn(start_test_server(port));
+
+        let repo_root = tempdir()?;
+        let repo_root_path = AbsoluteSystemPathBuf::try_from(repo_root.path())?;
+        let path = |components: &[&str]| {
+            AnchoredSystemPathBuf::from_raw(components.join(std::path::MAIN_SEPARATOR_STR))
+        };
+        let manifest = path(&["dist", "manifest.json"])?;
+        let index = path(&["dist", "index.js"])?;
+        let package_json = path(&["package.json"])?;
+        let files = [
+            (&manifest, "Stalker"),
+            (&index, "Solaris"),
+            (&package_json, "Mirror"),
+        ];
+        for (file, contents) in files {
+            let file_path = repo_root_path.resolve(file);
+            std::fs::create_dir_all(file_path.parent().unwrap())?;
+            std::fs::write(file_path, contents)?;
+        }
+
+        let api_client = APIClient::new(format!("http://localhost:{}", port), 200, "2.0.0", true)?;
+        let cache = HttpCache::new(api_client, None, repo_root_path.to_owned());
+        let context = CacheRequestContext::new("");
+        cache
+            .put_with_context(
+                &repo_root_path,
+                "Andrei Rublev",
+                files.iter().map(|(file, _)| (*file).clone()).collect(),
+                10,
+                &context,
+                None,
+            )
+            .await?;
+        for (file, _) in files {
+            std::fs::remove_file(repo_root_path.resolve(file))?;
+        }
+
+        let missing = path(&["docs"])?;
+        let (cache_response, restore) = cache
+            .retrieve_files(
+                "Andrei Rublev",
+                &[manifest.clone(), missing.clone()],
+                &context,
+                None,
+            )
+            .await?
+            .unwrap();
+        assert_eq!(cache_response.time_saved, 10);
+        assert_eq!(restore.restored, vec![manifest.clone()]);
+        assert_eq!(restore.missing, vec![missing]);
+        assert_eq!(
+            std::fs::read_to_string(repo_root_path.resolve(&manifest))?,
+            "Stalker"
+        );
+        assert!(!repo_root_path.resolve(&index).exists());
+        assert!(!repo_root_path.resolve(&package_json).exists());
+
+        // A directory restores everything under it.
+        let (_, restore) = cache
+            .retrieve_files("Andrei Rublev", &[path(&["dist"])?], &context, None)
+            .await?
+            .unwrap();
+        assert!(restore.restored.contains(&index));
+        assert!(restore.missing.is_empty());
+        assert_eq!(
+            std::fs::read_to_string(repo_root_path.resolve(&index))?,
+            "Solaris"
+        );
+        assert!(!repo_root_path.resolve(&package_json).exists());
+
+        handle.abort();
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_cache_miss() -> Result<()> {
+        let port = port_scanner::request_open_port().unwrap();
+        let handle = tokio::spawn(start_test_server(port));
+
+        let repo_root = tempdir()?;
+        let repo_root_path = AbsoluteSystemPathBuf::try_from(repo_root.path())?;
+
+        let api_client = APIClient::new(format!("http://localhost:{}", port), 200, "2.0.0", true)?;
+        let cache = HttpCache::new(api_client, None, repo_root_path.to_owned());
+
+        assert_eq!(cache.exists("Ikiru", "", "", None, false).await?, None);
+        assert_eq!(
+            cache.retrieve("Ikiru", "", "", None, false, None).await?,
+            None
+        );
+        assert_eq!(std::fs::read_dir(&repo_root_path)?.count(), 0);
+
+        // Missing permissions are an error rather than a miss.
+        assert!(matches!(
+            cache
+                .exists(FORBIDDEN_ARTIFACT_HASH, "", "", None, false)
+                .await,
+            Err(CacheError::ApiClientError(..))
+        ));
+        assert!(matches!(
+            cache
+                .retrieve(FORBIDDEN_ARTIFACT_HASH, "", "", None, false, None)
+                .await,
+            Err(CacheError::ApiClientError(..))
+        ));
+
+        handle.abort();
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_retrieve_cancellable() -> Result<()> {
+        let port = port_scanner::request_open_port().unwrap();
+        let handle = tokio::spawn(start_test_server(port));
+
+        let repo_root = tempdir()?;
+        let repo_root_path = AbsoluteSystemPathBuf::try_from(repo_root.path())?;
+        let file = AnchoredSystemPathBuf::from_raw("package.json")?;
+        let file_path = repo_root_path.resolve(&file);
+        std::fs::write(&file_path, "Rashomon")?;
+
+        let api_client = APIClient::new(format!("http://localhost:{}", port), 200, "2.0.0", true)?;
+        let cache = HttpCache::new(api_client, None, repo_root_path.to_owned());
+        let context = CacheRequestContext::new("");
+        cache
+            .put_with_context(
+                &repo_root_path,
+                "Ran",
+                vec![file.clone()],
+                10,
+                &context,
+                None,
+            )
+            .await?;
+        std::fs::remove_file(&file_path)?;
+
+        // An already cancelled retrieve doesn't write anything.
+        assert!(matches!(
+            cache
+                .retrieve_cancellable("Ran", &context, None, std::future::ready(()))
+                .await,
+            Err(CacheError::Cancelled(..))
+        ));
+        assert!(!file_path.exists());
+
+        let (_, files) = cache
+            .retrieve_cancellable("Ran", &context, None, std::future::pending())
+            .await?
+            .unwrap();
+        assert_eq!(files, vec![file]);
+        assert_eq!(std::fs::read_to_string(&file_path)?, "Rashomon");
+
+        handle.abort();
+        Ok(())
+    }
+
+    #[test_case(b"58", Some(58), 58 ; "valid")]
+    #[test_case(b"0", Some(0), 0 ; "zero")]
+    #[test_case(b"4294967295", Some(u32::MAX), u32::MAX ; "max")]
+    #[test_case(b"4294967296", None, u32::MAX ; "overflow")]
+    #[test_case(b"99999999999999999999999999999999999999999", None, u32::MAX ; "huge")]
+    #[test_case(b"-1", None, 0 ; "negative")]
+    #[test_case(b"-99999999999999999999999999999999999999999", None, 0 ; "huge negative")]
+    #[test_case(b" 12 ", None, 12 ; "whitespace")]
+    #[test_case(b"+12", Some(12), 12 ; "plus sign")]
+    #[test_case(b"", None, 0 ; "empty")]
+    #[test_case(b"-", None, 0 ; "only sign")]
+    #[test_case(b"twelve", None, 0 ; "not a number")]
+    #[test_case(b"12ms", None, 0 ; "unit")]
+    #[test_case(b"\xff\xfe", None, 0 ; "not utf8")]
+    fn test_parse_duration(duration: &[u8], strict: Option<u32>, lenient: u32) {
+        let duration = HeaderValue::from_bytes(duration).unwrap();
+        assert_eq!(parse_duration(&duration).ok(), strict);
+        assert_eq!(parse_duration_lenient(&duration), lenient);
+    }
+
+    #[test]
+    fn test_request_context_rejects_invalid_headers() {
+        assert!(matches!(
+            CacheRequestContext::new("token").with_header("trace parent", TRACEPARENT),
+            Err(CacheError::InvalidHeaderName(..))
+        ));
+        assert!(matches!(
+            CacheRequestContext::new("token").with_header("traceparent", "line\nbreak"),
+            Err(CacheError::InvalidHeaderValue(..))
+        ));
+        for name in [
+            "Authorization",
+            "Content-Type",
+            "content-length",
+            "x-artifact-tag",
+            "X-Artifact-Duration",
+            "teamId",
+            "TeamSlug",
+        ] {
+            assert!(
+                matches!(
+                    CacheRequestContext::new("token").with_header(name, "value"),
+                    Err(CacheError::ReservedHeader(..))
+                ),
+                "{name} must be reserved"
+            );
+        }
+        assert!(CacheRequestContext::new("token")
+            .with_header("x-artifact", "value")
+            .is_ok());
+    }
+
+    #[test]
+    fn test_compute_artifact_key() -> Result<()> {
+        let a = AnchoredSystemPathBuf::from_raw("package.json")?;
+        let b = AnchoredSystemPathBuf::from_raw(
+            ["src", "main.js"].join(std::path::MAIN_SEPARATOR_STR),
+        )?;
+
+        let key = compute_artifact_key(&[a.clone(), b.clone()], "salt");
+        assert_eq!(key.len(), 128);
+        // Independent of the order of the files
+        assert_eq!(key, compute_artifact_key(&[b.clone(), a.clone()], "salt"));
+        // Depends on the salt and the files
+        assert_ne!(key, compute_artifact_key(&[a.clone(), b.clone()], "pepper"));
+        assert_ne!(key, compute_artifact_key(&[a.clone()], "salt"));
+        // Separators are normalized, so this is the same on every platform
+        assert_eq!(
+            key,
+            "37800b35c92ec8ff18685aead121a8e4a6818dd7e60b594568fead2efc6b18dfffbd123938dd7ba9299688fffeba94d1d8f8773feb165377661979e63487150d"
+        );
+
+        Ok(())
+    }
+
+    /// A compressed artifact with the given regular files. The paths are
+    /// written as they are, the tar builder would reject `..` components.
+    fn crafted_artifact(files: &[(&str, &str)]) -> Result<Vec<u8>> {
+        let mut builder = tar::Builder::new(Vec::new());
+        for (path, contents) in files {
+            let mut header = tar::Header::new_gnu();
+            header.as_old_mut().name[..path.len()].copy_from_slice(path.as_bytes());
+            header.set_entry_type(tar::EntryType::Regular);
+            header.set_size(contents.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder.append(&header, contents.as_bytes())?;
+        }
+        Ok(zstd::encode_all(&builder.into_inner()?[..], 0)?)
+    }
+
+    #[test]
+    fn test_restore_tar_rejects_escaping_paths() -> Result<()> {
+        let dir = tempdir()?;
+        let dir_path = AbsoluteSystemPathBuf::try_from(dir.path())?;
+        let root = dir_path.join_component("repo");
+
+        for escaping_path in ["../evil", "nested/../../evil", "/evil"] {
+            let artifact = crafted_artifact(&[("ok.txt", "fine"), (escaping_path, "evil")])?;
+            let result = HttpCache::restore_tar(&root, &artifact, RestoreLimits::default());
+            assert!(
+                matches!(&result, Err(CacheError::PathEscapesRoot(path, _)) if path == escaping_path),
+                "{escaping_path}: {result:?}"
+            );
+            // Nothing is left behind, not even the entries before the
+            // escaping one.
+            assert!(!dir_path.join_component("evil").exists());
+            assert!(!root.join_component("ok.txt").exists());
+        }
+
+        // The paths of a regular artifact pass the check.
+        let artifact = crafted_artifact(&[("ok.txt", "fine")])?;
+        HttpCache::restore_tar(&root, &artifact, RestoreLimits::default())?;
+        assert_eq!(
+            std::fs::read_to_string(root.join_component("ok.txt"))?,
+            "fine"
+        );
+
+        Ok(())
+    }
 }
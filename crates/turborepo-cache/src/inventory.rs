@@ -0,0 +1,539 @@
+//! Lists and removes the artifacts of a local cache directory, e.g. to prune
+//! it.
+//!
+//! An artifact `<hash>` is stored as the archive `<hash>.tar.zst` and its
+//! metadata `<hash>-meta.json`, which records the hash and the time saved by
+//! restoring the artifact.
+
+use std::{
+    backtrace::Backtrace,
+    fs::Metadata,
+    io::{self, ErrorKind},
+    sync::atomic::{AtomicUsize, Ordering},
+    time::{Duration, SystemTime},
+};
+
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+use turbopath::{AbsoluteSystemPath, AbsoluteSystemPathBuf};
+
+use crate::CacheError;
+
+const ARCHIVE_SUFFIX: &str = ".tar.zst";
+const METADATA_SUFFIX: &str = "-meta.json";
+/// Archives are renamed to a temporary file before they are deleted, and
+/// metadata is written to one before it's renamed.
+const TEMP_SUFFIX: &str = ".tmp";
+
+/// Makes the names of temporary files unique within the process.
+static TEMP_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// A locally stored artifact.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArtifactInfo {
+    pub hash: String,
+    /// The size of the archive and its metadata in bytes.
+    pub size: u64,
+    /// When the archive was created. Falls back to when it was last modified
+    /// on platforms that don't record the creation time.
+    pub created: SystemTime,
+    /// When the archive was last read. Falls back to when it was last
+    /// modified on platforms or file systems that don't record access times.
+    pub last_accessed: SystemTime,
+    /// The time saved by restoring the artifact in milliseconds.
+    pub duration: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CacheMetadata {
+    hash: String,
+    duration: u32,
+}
+
+/// The artifacts of a local cache directory.
+#[derive(Debug)]
+pub struct CacheInventory {
+    cache_dir: AbsoluteSystemPathBuf,
+    artifacts: Vec<ArtifactInfo>,
+}
+
+impl CacheInventory {
+    /// Lists the artifacts in `cache_dir`, ordered by hash. A missing
+    /// directory has no artifacts.
+    ///
+    /// Files which don't belong to an artifact are skipped. Metadata which
+    /// can't be read is rewritten from the archive, with an unknown duration.
+    /// Artifacts which are removed concurrently are skipped.
+    pub fn scan(cache_dir: &AbsoluteSystemPath) -> Result<Self, CacheError> {
+        let entries = match std::fs::read_dir(cache_dir.as_std_path()) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == ErrorKind::NotFound => {
+                return Ok(Self {
+                    cache_dir: cache_dir.to_owned(),
+                    artifacts: Vec::new(),
+                })
+            }
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut artifacts = Vec::new();
+        for entry in entries {
+            let entry = entry?;
+            let file_name = entry.file_name();
+            let Some(file_name) = file_name.to_str() else {
+                debug!("skipping {:?} in the cache directory", entry.path());
+                continue;
+            };
+            if let Some(hash) = file_name.strip_suffix(ARCHIVE_SUFFIX) {
+                if !is_valid_hash(hash) {
+                    debug!("skipping {} in the cache directory", file_name);
+                    continue;
+                }
+                if let Some(artifact) = Self::stat_artifact(cache_dir, hash)? {
+                    artifacts.push(artifact);
+                }
+            } else if file_name.ends_with(METADATA_SUFFIX) || file_name.ends_with(TEMP_SUFFIX) {
+                // Metadata is read with its archive, and temporary files
+                // don't belong to an artifact yet or anymore.
+            } else {
+                debug!("skipping {} in the cache directory", file_name);
+            }
+        }
+        artifacts.sort_by(|a, b| a.hash.cmp(&b.hash));
+
+        Ok(Self {
+            cache_dir: cache_dir.to_owned(),
+            artifacts,
+        })
+    }
+
+    /// Returns the artifact, or `None` if it has been removed in the meantime.
+    fn stat_artifact(
+        cache_dir: &AbsoluteSystemPath,
+        hash: &str,
+    ) -> Result<Option<ArtifactInfo>, CacheError> {
+        let archive = cache_dir.join_component(&format!("{hash}{ARCHIVE_SUFFIX}"));
+        let Some(archive_metadata) = stat(&archive)? else {
+            return Ok(None);
+        };
+
+        let metadata_path = cache_dir.join_component(&format!("{hash}{METADATA_SUFFIX}"));
+        let duration = match read_metadata(&metadata_path) {
+            Ok(metadata) if metadata.hash == hash => metadata.duration,
+            _ => {
+                debug!("rewriting the corrupted metadata of artifact {}", hash);
+                write_metadata(
+                    cache_dir,
+                    &metadata_path,
+                    &CacheMetadata {
+                        hash: hash.to_string(),
+                        duration: 0,
+                    },
+                )?;
+                // The artifact could have been removed in the meantime, which
+                // renames the archive before it removes the metadata.
+                if stat(&archive)?.is_none() {
+                    ignore_not_found(metadata_path.remove_file())?;
+                    return Ok(None);
+                }
+                0
+            }
+        };
+        let metadata_size = stat(&metadata_path)?.map_or(0, |metadata| metadata.len());
+
+        let modified = archive_metadata.modified()?;
+        Ok(Some(ArtifactInfo {
+            hash: hash.to_string(),
+            size: archive_metadata.len() + metadata_size,
+            created: archive_metadata.created().unwrap_or(modified),
+            last_accessed: archive_metadata.accessed().unwrap_or(modified),
+            duration,
+        }))
+    }
+
+    /// The artifacts, ordered by hash.
+    pub fn artifacts(&self) -> &[ArtifactInfo] {
+        &self.artifacts
+    }
+
+    /// The total size of the artifacts in bytes.
+    pub fn size(&self) -> u64 {
+        self.artifacts.iter().map(|artifact| artifact.size).sum()
+    }
+
+    /// Selects the artifacts which haven't been accessed for longer than
+    /// `age`.
+    pub fn older_than(&self, age: Duration) -> Vec<&ArtifactInfo> {
+        let now = SystemTime::now();
+        self.artifacts
+            .iter()
+            .filter(|artifact| {
+                matches!(
+                    now.duration_since(artifact.last_accessed),
+                    Ok(elapsed) if elapsed > age
+                )
+            })
+            .collect()
+    }
+
+    /// Selects the largest artifacts, until the remaining artifacts take up
+    /// at most `limit_bytes`.
+    pub fn largest_first(&self, limit_bytes: u64) -> Vec<&ArtifactInfo> {
+        let mut artifacts: Vec<_> = self.artifacts.iter().collect();
+        artifacts.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.hash.cmp(&b.hash)));
+
+        let mut size = self.size();
+        artifacts
+            .into_iter()
+            .take_while(|artifact| {
+                if size <= limit_bytes {
+                    return false;
+                }
+                size -= artifact.size;
+                true
+            })
+            .collect()
+    }
+
+    /// Removes the artifacts with the given hashes and returns the hashes of
+    /// the artifacts which were removed. Hashes which aren't in the cache
+    /// directory are ignored.
+    ///
+    /// The archive is renamed before it's deleted, so concurrent restores
+    /// either read the whole archive or don't find it.
+    pub fn remove<'a>(
+        &mut self,
+        hashes: impl IntoIterator<Item = &'a str>,
+    ) -> Result<Vec<String>, CacheError> {
+        let mut removed = Vec::new();
+        for hash in hashes {
+            if !is_valid_hash(hash) {
+                return Err(CacheError::InvalidHash(
+                    hash.to_string(),
+                    Backtrace::capture(),
+                ));
+            }
+            if self.remove_artifact(hash)? {
+                removed.push(hash.to_string());
+            }
+        }
+        self.artifacts
+            .retain(|artifact| !removed.contains(&artifact.hash));
+        Ok(removed)
+    }
+
+    fn remove_artifact(&self, hash: &str) -> Result<bool, CacheError> {
+        let archive = self
+            .cache_dir
+            .join_component(&format!("{hash}{ARCHIVE_SUFFIX}"));
+        let removing = temp_path(&self.cache_dir, hash);
+        match std::fs::rename(archive.as_std_path(), removing.as_std_path()) {
+            Ok(()) => {}
+            // Another process removed the artifact first.
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(false),
+            Err(e) => return Err(e.into()),
+        }
+
+        let metadata = self
+            .cache_dir
+            .join_component(&format!("{hash}{METADATA_SUFFIX}"));
+        ignore_not_found(metadata.remove_file())?;
+        ignore_not_found(removing.remove_file())?;
+        Ok(true)
+    }
+}
+
+/// Hashes are used as file names, so they must not contain path separators.
+fn is_valid_hash(hash: &str) -> bool {
+    !hash.is_empty()
+        && hash
+            .bytes()
+            .all(|byte| byte.is_ascii_alphanumeric() || byte == b'-' || byte == b'_')
+}
+
+/// Returns a unique path for a temporary file of the artifact.
+fn temp_path(cache_dir: &AbsoluteSystemPath, hash: &str) -> AbsoluteSystemPathBuf {
+    cache_dir.join_component(&format!(
+        "{hash}.{}-{}{TEMP_SUFFIX}",
+        std::process::id(),
+        TEMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+    ))
+}
+
+fn stat(path: &AbsoluteSystemPath) -> Result<Option<Metadata>, CacheError> {
+    match std::fs::metadata(path.as_std_path()) {
+        Ok(metadata) => Ok(Some(metadata)),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn ignore_not_found(result: io::Result<()>) -> io::Result<()> {
+    match result {
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(()),
+        result => result,
+    }
+}
+
+fn read_metadata(path: &AbsoluteSystemPath) -> Result<CacheMetadata, CacheError> {
+    let contents = std::fs::read(path.as_std_path())?;
+    serde_json::from_slice(&contents).map_err(|e| CacheError::IO(e.into(), Backtrace::capture()))
+}
+
+/// Writes the metadata to a temporary file first, so it's never read
+/// partially.
+fn write_metadata(
+    cache_dir: &AbsoluteSystemPath,
+    path: &AbsoluteSystemPath,
+    metadata: &CacheMetadata,
+) -> Result<(), CacheError> {
+    let temp = temp_path(cache_dir, &metadata.hash);
+    let contents =
+        serde_json::to_vec(metadata).map_err(|e| CacheError::IO(e.into(), Backtrace::capture()))?;
+    std::fs::write(temp.as_std_path(), contents)?;
+    std::fs::rename(temp.as_std_path(), path.as_std_path())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc,
+        },
+        thread,
+        time::Duration,
+    };
+
+    use anyhow::Result;
+    use tempfile::tempdir;
+    use turbopath::{AbsoluteSystemPath, AbsoluteSystemPathBuf};
+
+    use super::{CacheInventory, CacheMetadata};
+    use crate::CacheError;
+
+    fn put(cache_dir: &AbsoluteSystemPath, hash: &str, size: usize, duration: u32) -> Result<()> {
+        std::fs::write(
+            cache_dir
+                .join_component(&format!("{hash}.tar.zst"))
+                .as_std_path(),
+            vec![b'x'; size],
+        )?;
+        std::fs::write(
+            cache_dir
+                .join_component(&format!("{hash}-meta.json"))
+                .as_std_path(),
+            serde_json::to_vec(&CacheMetadata {
+                hash: hash.to_string(),
+                duration,
+            })?,
+        )?;
+        Ok(())
+    }
+
+    fn hashes(inventory: &CacheInventory) -> Vec<&str> {
+        inventory
+            .artifacts()
+            .iter()
+            .map(|artifact| artifact.hash.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_scan_skips_foreign_files() -> Result<()> {
+        let dir = tempdir()?;
+        let cache_dir = AbsoluteSystemPathBuf::try_from(dir.path())?;
+        put(&cache_dir, "b2", 20, 200)?;
+        put(&cache_dir, "a1", 10, 100)?;
+        std::fs::write(cache_dir.join_component("README.md").as_std_path(), "hi")?;
+        std::fs::write(
+            cache_dir.join_component("not a hash.tar.zst").as_std_path(),
+            "x",
+        )?;
+        std::fs::write(
+            cache_dir.join_component("orphan-meta.json").as_std_path(),
+            "{}",
+        )?;
+        cache_dir.join_component("nested").create_dir_all()?;
+
+        let inventory = CacheInventory::scan(&cache_dir)?;
+        assert_eq!(hashes(&inventory), vec!["a1", "b2"]);
+        let a1 = &inventory.artifacts()[0];
+        assert_eq!(a1.duration, 100);
+        let meta_size =
+            std::fs::metadata(cache_dir.join_component("a1-meta.json").as_std_path())?.len();
+        assert_eq!(a1.size, 10 + meta_size);
+
+        // A missing cache directory has no artifacts.
+        let missing = CacheInventory::scan(&cache_dir.join_component("missing"))?;
+        assert!(missing.artifacts().is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_scan_recovers_corrupted_metadata() -> Result<()> {
+        let dir = tempdir()?;
+        let cache_dir = AbsoluteSystemPathBuf::try_from(dir.path())?;
+        put(&cache_dir, "garbage", 10, 100)?;
+        put(&cache_dir, "mismatch", 10, 100)?;
+        put(&cache_dir, "missing", 10, 100)?;
+        put(&cache_dir, "valid", 10, 100)?;
+        std::fs::write(
+            cache_dir.join_component("garbage-meta.json").as_std_path(),
+            "{\"hash\": \"gar",
+        )?;
+        std::fs::write(
+            cache_dir.join_component("mismatch-meta.json").as_std_path(),
+            "{\"hash\": \"other\", \"duration\": 100}",
+        )?;
+        cache_dir
+            .join_component("missing-meta.json")
+            .remove_file()?;
+
+        let inventory = CacheInventory::scan(&cache_dir)?;
+        assert_eq!(
+            hashes(&inventory),
+            vec!["garbage", "mismatch", "missing", "valid"]
+        );
+        for artifact in inventory.artifacts() {
+            let expected = if artifact.hash == "valid" { 100 } else { 0 };
+            assert_eq!(artifact.duration, expected, "{}", artifact.hash);
+
+            // The metadata has been rewritten.
+            let metadata: CacheMetadata = serde_json::from_slice(&std::fs::read(
+                cache_dir
+                    .join_component(&format!("{}-meta.json", artifact.hash))
+                    .as_std_path(),
+            )?)?;
+            assert_eq!(metadata.hash, artifact.hash);
+            assert_eq!(metadata.duration, expected);
+        }
+
+        // Scanning again finds the same artifacts without leftovers.
+        assert_eq!(
+            CacheInventory::scan(&cache_dir)?.artifacts(),
+            inventory.artifacts()
+        );
+        assert_eq!(std::fs::read_dir(dir.path())?.count(), 8);
+        Ok(())
+    }
+
+    #[test]
+    fn test_selection() -> Result<()> {
+        let dir = tempdir()?;
+        let cache_dir = AbsoluteSystemPathBuf::try_from(dir.path())?;
+        put(&cache_dir, "small", 100, 0)?;
+        put(&cache_dir, "medium", 200, 0)?;
+        put(&cache_dir, "large", 300, 0)?;
+        let inventory = CacheInventory::scan(&cache_dir)?;
+        let size_of = |hash: &str| {
+            inventory
+                .artifacts()
+                .iter()
+                .find(|artifact| artifact.hash == hash)
+                .unwrap()
+                .size
+        };
+
+        let selected = |limit_bytes| {
+            inventory
+                .largest_first(limit_bytes)
+                .into_iter()
+                .map(|artifact| artifact.hash.as_str())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(selected(inventory.size()), Vec::<&str>::new());
+        assert_eq!(selected(inventory.size() - 1), vec!["large"]);
+        assert_eq!(selected(size_of("small")), vec!["large", "medium"]);
+        assert_eq!(selected(0), vec!["large", "medium", "small"]);
+
+        thread::sleep(Duration::from_millis(10));
+        assert_eq!(inventory.older_than(Duration::ZERO).len(), 3);
+        assert!(inventory
+            .older_than(Duration::from_secs(60 * 60))
+            .is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_remove() -> Result<()> {
+        let dir = tempdir()?;
+        let cache_dir = AbsoluteSystemPathBuf::try_from(dir.path())?;
+        put(&cache_dir, "a", 10, 0)?;
+        put(&cache_dir, "b", 10, 0)?;
+
+        let mut inventory = CacheInventory::scan(&cache_dir)?;
+        let removed = inventory.remove(["a", "unknown"])?;
+        assert_eq!(removed, vec!["a"]);
+        assert_eq!(hashes(&inventory), vec!["b"]);
+        assert_eq!(hashes(&CacheInventory::scan(&cache_dir)?), vec!["b"]);
+        assert!(!cache_dir.join_component("a-meta.json").exists());
+
+        // Removing it again is a no-op.
+        assert!(inventory.remove(["a"])?.is_empty());
+
+        // Hashes can't point outside of the cache directory.
+        assert!(matches!(
+            inventory.remove(["../b"]),
+            Err(CacheError::InvalidHash(..))
+        ));
+        assert_eq!(std::fs::read_dir(dir.path())?.count(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_concurrent_scan_and_remove() -> Result<()> {
+        let dir = tempdir()?;
+        let cache_dir = AbsoluteSystemPathBuf::try_from(dir.path())?;
+        let hashes: Vec<String> = (0..200).map(|i| format!("{i:016x}")).collect();
+        for hash in hashes.iter() {
+            put(&cache_dir, hash, 1000, 10)?;
+        }
+
+        let done = Arc::new(AtomicBool::new(false));
+        let scanner = {
+            let cache_dir = cache_dir.clone();
+            let done = done.clone();
+            thread::spawn(move || -> Result<()> {
+                while !done.load(Ordering::SeqCst) {
+                    for artifact in CacheInventory::scan(&cache_dir)?.artifacts() {
+                        assert_eq!(artifact.duration, 10);
+                    }
+                }
+                Ok(())
+            })
+        };
+        let restorer = {
+            let cache_dir = cache_dir.clone();
+            let done = done.clone();
+            let hashes = hashes.clone();
+            thread::spawn(move || -> Result<()> {
+                while !done.load(Ordering::SeqCst) {
+                    for hash in hashes.iter() {
+                        let archive = cache_dir.join_component(&format!("{hash}.tar.zst"));
+                        // An archive is either complete or missing.
+                        match std::fs::read(archive.as_std_path()) {
+                            Ok(contents) => assert_eq!(contents.len(), 1000),
+                            Err(e) => assert_eq!(e.kind(), std::io::ErrorKind::NotFound),
+                        }
+                    }
+                }
+                Ok(())
+            })
+        };
+
+        let mut inventory = CacheInventory::scan(&cache_dir)?;
+        for chunk in hashes.chunks(10) {
+            let removed = inventory.remove(chunk.iter().map(|hash| hash.as_str()))?;
+            assert_eq!(removed, chunk);
+        }
+        done.store(true, Ordering::SeqCst);
+        scanner.join().unwrap()?;
+        restorer.join().unwrap()?;
+
+        assert!(CacheInventory::scan(&cache_dir)?.artifacts().is_empty());
+        assert_eq!(std::fs::read_dir(dir.path())?.count(), 0);
+        Ok(())
+    }
+}
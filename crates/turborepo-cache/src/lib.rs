@@ -3,14 +3,18 @@
 #![deny(clippy::all)]
 
 pub mod cache_archive;
+pub mod github_actions;
 pub mod http;
+pub mod inventory;
+pub mod progress;
+pub mod s3;
 pub mod signature_authentication;
 
 use std::{backtrace, backtrace::Backtrace};
 
 use thiserror::Error;
 
-use crate::signature_authentication::SignatureError;
+use crate::{cache_archive::ArtifactLimit, signature_authentication::SignatureError};
 
 #[derive(Debug, Error)]
 pub enum CacheError {
@@ -45,12 +49,59 @@ pub enum CacheError {
     // way to display it nicely.
     #[error("attempted to create unsupported file type")]
     CreateUnsupportedFileType(#[backtrace] Backtrace),
+    #[error("cannot append to compressed artifact {0} in place")]
+    AppendToCompressedArtifact(String, #[backtrace] Backtrace),
     #[error("tar file is malformed")]
     MalformedTar(#[backtrace] Backtrace),
     #[error("file name is not Windows-safe: {0}")]
     WindowsUnsafeName(String, #[backtrace] Backtrace),
     #[error("tar attempts to write outside of directory: {0}")]
     LinkOutsideOfDirectory(String, #[backtrace] Backtrace),
+    #[error("invalid header name: {0}")]
+    InvalidHeaderName(String, #[backtrace] Backtrace),
+    #[error("invalid value for header {0}")]
+    InvalidHeaderValue(String, #[backtrace] Backtrace),
+    #[error("header {0} is reserved and cannot be set as an extra header")]
+    ReservedHeader(String, #[backtrace] Backtrace),
+    #[error("artifact exceeds the {limit}, found {observed}")]
+    ArtifactLimitExceeded {
+        limit: ArtifactLimit,
+        observed: u64,
+        #[backtrace]
+        backtrace: Backtrace,
+    },
+    #[error("cache entry {0} is already being uploaded by another job")]
+    CacheEntryReserved(String, #[backtrace] Backtrace),
+    #[error("artifact of {0} bytes exceeds the maximum cache entry size of {1} bytes")]
+    ArtifactTooLarge(u64, u64, #[backtrace] Backtrace),
+    #[error("artifact entry {0} escapes the root of the restore")]
+    PathEscapesRoot(String, #[backtrace] Backtrace),
+    #[error("invalid endpoint url: {0}")]
+    InvalidEndpoint(String, #[backtrace] Backtrace),
+    #[error(
+        "archive requires version {found} to be read, this version of turbo supports up to \
+         version {supported}"
+    )]
+    UnsupportedArchiveVersion {
+        found: u32,
+        supported: u32,
+        #[backtrace]
+        backtrace: Backtrace,
+    },
+    #[error(
+        "artifact requires reader version {found}, this version of turbo supports up to version \
+         {supported}"
+    )]
+    UnsupportedArtifactVersion {
+        found: u32,
+        supported: u32,
+        #[backtrace]
+        backtrace: Backtrace,
+    },
+    #[error("retrieving artifact {0} was cancelled")]
+    Cancelled(String, #[backtrace] Backtrace),
+    #[error("invalid artifact hash: {0}")]
+    InvalidHash(String, #[backtrace] Backtrace),
 }
 
 impl From<turborepo_api_client::Error> for CacheError {
@@ -69,4 +120,27 @@ pub enum CacheSource {
 pub struct CacheResponse {
     source: CacheSource,
     time_saved: u32,
+    /// The number of bytes of the artifact body that were transferred over the
+    /// wire, i.e. the size of the compressed artifact.
+    bytes_transferred: u64,
+}
+
+impl CacheResponse {
+    pub fn bytes_transferred(&self) -> u64 {
+        self.bytes_transferred
+    }
+}
+
+/// The result of uploading an artifact.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PutResponse {
+    /// The number of bytes of the artifact body that were transferred over the
+    /// wire, i.e. the size of the compressed artifact.
+    bytes_transferred: u64,
+}
+
+impl PutResponse {
+    pub fn bytes_transferred(&self) -> u64 {
+        self.bytes_transferred
+    }
 }
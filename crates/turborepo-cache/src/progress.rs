@@ -0,0 +1,234 @@
+use std::{
+    convert::Infallible,
+    future::Future,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use bytes::Bytes;
+use futures::{stream, Stream, StreamExt};
+use tokio::sync::watch;
+
+/// Receives the progress of a cache transfer, e.g. to render a progress bar.
+///
+/// It is called with the number of bytes transferred so far and the total
+/// number of bytes, when it is known.
+pub trait ProgressReporter: Send + Sync {
+    fn report(&self, transferred_bytes: u64, total_bytes: Option<u64>);
+}
+
+impl<F> ProgressReporter for F
+where
+    F: Fn(u64, Option<u64>) + Send + Sync,
+{
+    fn report(&self, transferred_bytes: u64, total_bytes: Option<u64>) {
+        self(transferred_bytes, total_bytes)
+    }
+}
+
+/// Reports are sent at most this often...
+const REPORT_INTERVAL: Duration = Duration::from_millis(100);
+/// ...and only once at least this fraction of the known total has been
+/// transferred since the last report.
+const REPORT_FRACTION: u64 = 100;
+
+/// The size of the chunks an upload body is streamed in.
+const UPLOAD_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Forwards progress to a [ProgressReporter] at a throttled rate.
+pub(crate) struct ThrottledProgress<'a> {
+    reporter: Option<&'a dyn ProgressReporter>,
+    total_bytes: Option<u64>,
+    transferred_bytes: u64,
+    last_reported_bytes: u64,
+    last_reported_at: Option<Instant>,
+}
+
+impl<'a> ThrottledProgress<'a> {
+    pub(crate) fn new(
+        reporter: Option<&'a dyn ProgressReporter>,
+        total_bytes: Option<u64>,
+    ) -> Self {
+        ThrottledProgress {
+            reporter,
+            total_bytes,
+            transferred_bytes: 0,
+            last_reported_bytes: 0,
+            last_reported_at: None,
+        }
+    }
+
+    /// Records that `bytes` more bytes have been transferred.
+    pub(crate) fn advance(&mut self, bytes: u64) {
+        self.advance_to(self.transferred_bytes + bytes);
+    }
+
+    /// Records that `transferred_bytes` bytes have been transferred so far.
+    /// This can be less than before when a transfer is retried.
+    pub(crate) fn advance_to(&mut self, transferred_bytes: u64) {
+        self.transferred_bytes = transferred_bytes;
+        let Some(reporter) = self.reporter else {
+            return;
+        };
+        let now = Instant::now();
+        let interval_elapsed = self
+            .last_reported_at
+            .map_or(true, |last| now.duration_since(last) >= REPORT_INTERVAL);
+        let new_bytes = self
+            .transferred_bytes
+            .saturating_sub(self.last_reported_bytes);
+        let enough_transferred = match self.total_bytes {
+            Some(total) => new_bytes * REPORT_FRACTION >= total,
+            None => new_bytes > 0,
+        };
+        if interval_elapsed && enough_transferred {
+            reporter.report(self.transferred_bytes, self.total_bytes);
+            self.last_reported_bytes = self.transferred_bytes;
+            self.last_reported_at = Some(now);
+        }
+    }
+
+    /// Reports the final progress. The total is the number of transferred
+    /// bytes when it wasn't known, since the transfer is complete. Consumes
+    /// the progress, so nothing is reported afterwards.
+    pub(crate) fn finish(self) {
+        if let Some(reporter) = self.reporter {
+            reporter.report(
+                self.transferred_bytes,
+                self.total_bytes.or(Some(self.transferred_bytes)),
+            );
+        }
+    }
+}
+
+/// The body of an upload. The bytes are streamed to the HTTP client, and
+/// recorded as progress when the client reads them, see
+/// [ProgressBody::report].
+pub(crate) struct ProgressBody {
+    bytes: Bytes,
+    sent_bytes: Arc<watch::Sender<u64>>,
+}
+
+impl ProgressBody {
+    pub(crate) fn new(bytes: Vec<u8>) -> Self {
+        ProgressBody {
+            bytes: bytes.into(),
+            sent_bytes: Arc::new(watch::channel(0).0),
+        }
+    }
+
+    pub(crate) fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// The size of the body, which is sent as its `Content-Length`.
+    pub(crate) fn len(&self) -> u64 {
+        self.bytes.len() as u64
+    }
+
+    /// A streamed body for one attempt of the upload. A stream can only be
+    /// sent once, so every attempt needs its own body, which starts from the
+    /// beginning.
+    pub(crate) fn stream(&self) -> reqwest::Body {
+        reqwest::Body::wrap_stream(self.chunks())
+    }
+
+    fn chunks(&self) -> impl Stream<Item = Result<Bytes, Infallible>> + Send + Sync + 'static {
+        let bytes = self.bytes.clone();
+        let sent_bytes = self.sent_bytes.clone();
+        sent_bytes.send_replace(0);
+        stream::iter((0..bytes.len()).step_by(UPLOAD_CHUNK_SIZE)).map(move |start| {
+            let end = (start + UPLOAD_CHUNK_SIZE).min(bytes.len());
+            sent_bytes.send_replace(end as u64);
+            Ok(bytes.slice(start..end))
+        })
+    }
+
+    /// Runs `upload`, which sends the streams of this body, and reports the
+    /// bytes read by the HTTP client to `progress`. The total is the size of
+    /// the body. The final report is sent once the upload has succeeded.
+    pub(crate) async fn report<T, E>(
+        &self,
+        progress: Option<&dyn ProgressReporter>,
+        upload: impl Future<Output = Result<T, E>>,
+    ) -> Result<T, E> {
+        let mut progress = ThrottledProgress::new(progress, Some(self.len()));
+        let mut sent_bytes = self.sent_bytes.subscribe();
+        tokio::pin!(upload);
+        loop {
+            tokio::select! {
+                result = &mut upload => {
+                    let result = result?;
+                    progress.advance_to(*sent_bytes.borrow());
+                    progress.finish();
+                    return Ok(result);
+                }
+                Ok(()) = sent_bytes.changed() => {
+                    progress.advance_to(*sent_bytes.borrow_and_update());
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{convert::Infallible, sync::Mutex};
+
+    use futures::StreamExt;
+
+    use super::{ProgressBody, ThrottledProgress, REPORT_INTERVAL, UPLOAD_CHUNK_SIZE};
+
+    #[test]
+    fn test_throttled_by_time_and_bytes() {
+        let reports = Mutex::new(Vec::new());
+        let reporter = |transferred, total| reports.lock().unwrap().push((transferred, total));
+        let mut progress = ThrottledProgress::new(Some(&reporter), Some(1000));
+        // Less than 1% of the total isn't reported, the first 1% is.
+        progress.advance(5);
+        progress.advance(5);
+        // Even 10% more isn't reported before the interval has elapsed.
+        progress.advance(100);
+        std::thread::sleep(REPORT_INTERVAL);
+        progress.advance(1);
+        progress.finish();
+
+        // The final report keeps the known total.
+        let reports = reports.into_inner().unwrap();
+        assert_eq!(
+            reports,
+            [(10, Some(1000)), (111, Some(1000)), (111, Some(1000))]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_progress_body() {
+        let reports = Mutex::new(Vec::new());
+        let reporter = |transferred, total| reports.lock().unwrap().push((transferred, total));
+        let size = 3 * UPLOAD_CHUNK_SIZE + 1;
+        let body = ProgressBody::new(vec![0; size]);
+        let received = body
+            .report(Some(&reporter), async {
+                let mut received = 0;
+                let mut chunks = body.chunks();
+                while let Some(chunk) = chunks.next().await {
+                    received += chunk?.len();
+                    // Gives the progress of every chunk a chance to be reported.
+                    tokio::time::sleep(2 * REPORT_INTERVAL).await;
+                }
+                Ok::<_, Infallible>(received)
+            })
+            .await
+            .unwrap();
+        assert_eq!(received, size);
+
+        // The progress follows the bytes read from the body.
+        let reports = reports.into_inner().unwrap();
+        let total = Some(size as u64);
+        assert_eq!(reports.first(), Some(&(UPLOAD_CHUNK_SIZE as u64, total)));
+        assert_eq!(reports.last(), Some(&(size as u64, total)));
+        for window in reports.windows(2) {
+            assert!(window[0].0 <= window[1].0, "{reports:?}");
+        }
+    }
+}
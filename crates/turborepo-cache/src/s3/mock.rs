@@ -0,0 +1,239 @@
+//! A mock of the subset of S3 used by the cache: path-style `PUT`, `HEAD` and
+//! `GET` of objects, authenticated with AWS Signature Version 4.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    net::{SocketAddr, TcpListener},
+    sync::{Arc, Mutex},
+};
+
+use axum::{
+    body::Bytes,
+    extract::State,
+    http::{header, HeaderMap, Method, StatusCode, Uri},
+    response::{IntoResponse, Response},
+    Router,
+};
+use tokio::task::JoinHandle;
+
+use super::sigv4;
+
+#[derive(Debug, Default, Clone)]
+pub struct MockObject {
+    pub data: Vec<u8>,
+    /// The `x-amz-meta-*` headers of the upload.
+    pub metadata: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Default)]
+pub struct MockState {
+    /// The objects of the buckets by their encoded keys.
+    buckets: HashMap<String, HashMap<String, MockObject>>,
+}
+
+impl MockState {
+    /// Returns the object with the given encoded key.
+    pub fn object(&self, bucket: &str, key: &str) -> Option<&MockObject> {
+        self.buckets.get(bucket)?.get(key)
+    }
+
+    pub fn object_mut(&mut self, bucket: &str, key: &str) -> Option<&mut MockObject> {
+        self.buckets.get_mut(bucket)?.get_mut(key)
+    }
+}
+
+#[derive(Clone)]
+struct AppState {
+    access_key_id: String,
+    secret_access_key: String,
+    region: String,
+    state: Arc<Mutex<MockState>>,
+}
+
+impl AppState {
+    /// Checks the signature of the request, like S3 does.
+    fn authorize(
+        &self,
+        method: &Method,
+        uri: &Uri,
+        headers: &HeaderMap,
+        body: &[u8],
+    ) -> Result<(), Response> {
+        let Some((credential, signed_headers, signature)) = headers
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(parse_authorization)
+        else {
+            return Err(error(StatusCode::FORBIDDEN, "AccessDenied"));
+        };
+        let Some((access_key_id, scope)) = credential.split_once('/') else {
+            return Err(error(
+                StatusCode::BAD_REQUEST,
+                "AuthorizationHeaderMalformed",
+            ));
+        };
+        if access_key_id != self.access_key_id {
+            return Err(error(StatusCode::FORBIDDEN, "InvalidAccessKeyId"));
+        }
+
+        let mut signed = BTreeMap::new();
+        for name in signed_headers.split(';') {
+            let Some(value) = headers.get(name).and_then(|value| value.to_str().ok()) else {
+                return Err(error(StatusCode::FORBIDDEN, "SignatureDoesNotMatch"));
+            };
+            signed.insert(name.to_string(), value.to_string());
+        }
+        let (Some(amz_date), Some(payload_hash)) =
+            (signed.get("x-amz-date"), signed.get("x-amz-content-sha256"))
+        else {
+            return Err(error(
+                StatusCode::BAD_REQUEST,
+                "AuthorizationHeaderMalformed",
+            ));
+        };
+        if !signed.contains_key("host") || scope != sigv4::credential_scope(amz_date, &self.region)
+        {
+            return Err(error(
+                StatusCode::BAD_REQUEST,
+                "AuthorizationHeaderMalformed",
+            ));
+        }
+        if *payload_hash != sigv4::payload_hash(body) {
+            return Err(error(StatusCode::BAD_REQUEST, "XAmzContentSHA256Mismatch"));
+        }
+
+        let canonical_request =
+            sigv4::canonical_request(method.as_str(), uri.path(), &signed, payload_hash);
+        let expected = sigv4::signature(
+            &self.secret_access_key,
+            amz_date,
+            &self.region,
+            &canonical_request,
+        );
+        if signature != expected {
+            return Err(error(StatusCode::FORBIDDEN, "SignatureDoesNotMatch"));
+        }
+        Ok(())
+    }
+}
+
+/// Starts the mock on the given port with an empty bucket. Only requests
+/// signed with the given credentials for `region` are accepted. Returns the
+/// state of the mock, so tests can inspect and prepare the objects.
+pub async fn start_test_server(
+    port: u16,
+    bucket: &str,
+    region: &str,
+    (access_key_id, secret_access_key): (&str, &str),
+) -> (JoinHandle<()>, Arc<Mutex<MockState>>) {
+    let mut mock_state = MockState::default();
+    mock_state
+        .buckets
+        .insert(bucket.to_string(), HashMap::new());
+    let state = Arc::new(Mutex::new(mock_state));
+    let app_state = AppState {
+        access_key_id: access_key_id.to_string(),
+        secret_access_key: secret_access_key.to_string(),
+        region: region.to_string(),
+        state: state.clone(),
+    };
+
+    let app = Router::new().fallback(object).with_state(app_state);
+
+    // Bind before returning, so requests can be made right away.
+    let listener = TcpListener::bind(SocketAddr::from(([127, 0, 0, 1], port))).unwrap();
+    let server = axum::Server::from_tcp(listener)
+        .unwrap()
+        .serve(app.into_make_service());
+    let handle = tokio::spawn(async move {
+        server.await.unwrap();
+    });
+    (handle, state)
+}
+
+async fn object(
+    State(app): State<AppState>,
+    method: Method,
+    uri: Uri,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    if let Err(response) = app.authorize(&method, &uri, &headers, &body) {
+        return response;
+    }
+    let Some((bucket, key)) = uri
+        .path()
+        .strip_prefix('/')
+        .and_then(|path| path.split_once('/'))
+        .filter(|(_, key)| !key.is_empty())
+    else {
+        return error(StatusCode::BAD_REQUEST, "InvalidRequest");
+    };
+
+    let mut state = app.state.lock().unwrap();
+    let Some(objects) = state.buckets.get_mut(bucket) else {
+        return error(StatusCode::NOT_FOUND, "NoSuchBucket");
+    };
+    match method {
+        Method::PUT => {
+            let metadata = headers
+                .iter()
+                .filter(|(name, _)| name.as_str().starts_with("x-amz-meta-"))
+                .filter_map(|(name, value)| {
+                    Some((name.to_string(), value.to_str().ok()?.to_string()))
+                })
+                .collect();
+            objects.insert(
+                key.to_string(),
+                MockObject {
+                    data: body.to_vec(),
+                    metadata,
+                },
+            );
+            StatusCode::OK.into_response()
+        }
+        Method::GET | Method::HEAD => {
+            let Some(object) = objects.get(key) else {
+                return error(StatusCode::NOT_FOUND, "NoSuchKey");
+            };
+            let mut response_headers = HeaderMap::new();
+            for (name, value) in &object.metadata {
+                response_headers.insert(
+                    header::HeaderName::try_from(name.as_str()).unwrap(),
+                    value.parse().unwrap(),
+                );
+            }
+            // The body of the response to `HEAD` is dropped by the server.
+            (response_headers, object.data.clone()).into_response()
+        }
+        _ => error(StatusCode::METHOD_NOT_ALLOWED, "MethodNotAllowed"),
+    }
+}
+
+/// An S3 error response with the given code.
+fn error(status: StatusCode, code: &str) -> Response {
+    (
+        status,
+        [(header::CONTENT_TYPE, "application/xml")],
+        format!("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<Error><Code>{code}</Code></Error>"),
+    )
+        .into_response()
+}
+
+/// Parses `AWS4-HMAC-SHA256 Credential=<credential>,
+/// SignedHeaders=<headers>, Signature=<signature>`.
+fn parse_authorization(value: &str) -> Option<(&str, &str, &str)> {
+    let parameters = value.strip_prefix(sigv4::ALGORITHM)?.trim_start();
+    let mut credential = None;
+    let mut signed_headers = None;
+    let mut signature = None;
+    for parameter in parameters.split(',') {
+        match parameter.trim().split_once('=')? {
+            ("Credential", value) => credential = Some(value),
+            ("SignedHeaders", value) => signed_headers = Some(value),
+            ("Signature", value) => signature = Some(value),
+            _ => return None,
+        }
+    }
+    Some((credential?, signed_headers?, signature?))
+}
@@ -0,0 +1,717 @@
+//! A cache backend that stores artifacts in an S3-compatible object storage,
+//! e.g. AWS S3 or MinIO.
+//!
+//! Artifacts are stored as objects at `<prefix><hash>` in a bucket, and are
+//! uploaded, checked and downloaded with plain `PUT`, `HEAD` and `GET`
+//! requests, signed with AWS Signature Version 4. The duration of the task
+//! and the tag of the artifact are stored as user metadata of the object.
+
+#[cfg(test)]
+mod mock;
+mod sigv4;
+
+use std::{backtrace::Backtrace, collections::BTreeMap};
+
+use chrono::Utc;
+use reqwest::{
+    header::{HeaderMap, CONTENT_LENGTH},
+    Client, Method, Response, StatusCode, Url,
+};
+use turbopath::{AbsoluteSystemPath, AbsoluteSystemPathBuf, AnchoredSystemPathBuf};
+
+use crate::{
+    cache_archive::RestoreLimits,
+    http::HttpCache,
+    progress::{ProgressBody, ProgressReporter},
+    signature_authentication::ArtifactSignatureAuthenticator,
+    CacheError, CacheResponse, CacheSource, PutResponse,
+};
+
+/// The metadata header with the duration of the task, in milliseconds.
+const DURATION_HEADER: &str = "x-amz-meta-duration";
+/// The metadata header with the tag of a signed artifact.
+const TAG_HEADER: &str = "x-amz-meta-artifact-tag";
+
+/// The credentials of the requests to the object storage.
+#[derive(Clone)]
+pub struct S3Credentials {
+    access_key_id: String,
+    secret_access_key: String,
+    session_token: Option<String>,
+}
+
+impl S3Credentials {
+    pub fn new(access_key_id: impl Into<String>, secret_access_key: impl Into<String>) -> Self {
+        Self {
+            access_key_id: access_key_id.into(),
+            secret_access_key: secret_access_key.into(),
+            session_token: None,
+        }
+    }
+
+    /// Sets the session token of temporary credentials, which is sent as
+    /// `x-amz-security-token`.
+    pub fn with_session_token(mut self, session_token: impl Into<String>) -> Self {
+        self.session_token = Some(session_token.into());
+        self
+    }
+}
+
+/// Stores artifacts in a bucket of an S3-compatible object storage.
+///
+/// With path-style addressing the objects are requested at
+/// `<endpoint>/<bucket>/<key>`, which most self-hosted storages expect.
+/// Otherwise the bucket is a subdomain of the endpoint, i.e.
+/// `<bucket>.<endpoint host>/<key>`, which AWS S3 expects.
+pub struct S3Cache {
+    client: Client,
+    endpoint: Url,
+    bucket: String,
+    region: String,
+    credentials: S3Credentials,
+    path_style: bool,
+    prefix: String,
+    signer_verifier: Option<ArtifactSignatureAuthenticator>,
+    repo_root: AbsoluteSystemPathBuf,
+    restore_limits: RestoreLimits,
+}
+
+impl S3Cache {
+    /// Creates a cache for the bucket at `endpoint`, e.g.
+    /// `https://s3.us-east-1.amazonaws.com`. Fails when `endpoint` is not a
+    /// valid url.
+    pub fn new(
+        endpoint: &str,
+        bucket: impl Into<String>,
+        region: impl Into<String>,
+        credentials: S3Credentials,
+        path_style: bool,
+        signer_verifier: Option<ArtifactSignatureAuthenticator>,
+        repo_root: AbsoluteSystemPathBuf,
+    ) -> Result<Self, CacheError> {
+        let endpoint = Url::parse(endpoint)
+            .ok()
+            .filter(|url| url.has_host())
+            .ok_or_else(|| {
+                CacheError::InvalidEndpoint(endpoint.to_string(), Backtrace::capture())
+            })?;
+        Ok(S3Cache {
+            client: Client::new(),
+            endpoint,
+            bucket: bucket.into(),
+            region: region.into(),
+            credentials,
+            path_style,
+            prefix: String::new(),
+            signer_verifier,
+            repo_root,
+            restore_limits: RestoreLimits::default(),
+        })
+    }
+
+    /// Sets the prefix of the keys of the objects, e.g. `turbo/` to store the
+    /// artifacts in a "directory" of a shared bucket.
+    pub fn with_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    /// Sets the limits that are enforced when restoring retrieved artifacts.
+    /// Use [`RestoreLimits::unlimited`] to disable them.
+    pub fn with_restore_limits(mut self, restore_limits: RestoreLimits) -> Self {
+        self.restore_limits = restore_limits;
+        self
+    }
+
+    /// The key of the object of the artifact with the given hash.
+    pub fn key(&self, hash: &str) -> String {
+        format!("{}{}", self.prefix, hash)
+    }
+
+    /// Uploads the given files. The duration and, when signature
+    /// authentication is enabled, the tag of the artifact are stored as
+    /// metadata of the object. The progress is reported while the artifact
+    /// body is sent, its total is the size of the body.
+    pub async fn put(
+        &self,
+        anchor: &AbsoluteSystemPath,
+        hash: &str,
+        files: Vec<AnchoredSystemPathBuf>,
+        duration: u32,
+        progress: Option<&dyn ProgressReporter>,
+    ) -> Result<PutResponse, CacheError> {
+        let mut artifact_body = Vec::new();
+        HttpCache::write(&mut artifact_body, anchor, files)?;
+
+        let mut metadata = BTreeMap::from([(DURATION_HEADER.to_string(), duration.to_string())]);
+        if let Some(signer) = &self.signer_verifier {
+            let tag = signer.generate_tag(hash.as_bytes(), &artifact_body)?;
+            metadata.insert(TAG_HEADER.to_string(), tag);
+        }
+
+        let body = ProgressBody::new(artifact_body);
+        body.report(progress, async {
+            self.send(Method::PUT, hash, metadata, Some(&body))
+                .await?
+                .error_for_status()
+                .map_err(request_error)
+        })
+        .await?;
+
+        Ok(PutResponse {
+            bytes_transferred: body.len(),
+        })
+    }
+
+    /// Checks whether the artifact is in the cache. Returns `None` when it
+    /// isn't, while a failing request, e.g. due to invalid credentials, is an
+    /// error.
+    pub async fn exists(&self, hash: &str) -> Result<Option<CacheResponse>, CacheError> {
+        let Some(response) = self.object_response(Method::HEAD, hash).await? else {
+            return Ok(None);
+        };
+
+        Ok(Some(CacheResponse {
+            source: CacheSource::Remote,
+            time_saved: duration(response.headers())?,
+            // Only the headers are transferred.
+            bytes_transferred: 0,
+        }))
+    }
+
+    /// Downloads and restores an artifact. Returns `None` when it isn't in the
+    /// cache, without writing any files.
+    pub async fn retrieve(
+        &self,
+        hash: &str,
+        progress: Option<&dyn ProgressReporter>,
+    ) -> Result<Option<(CacheResponse, Vec<AnchoredSystemPathBuf>)>, CacheError> {
+        let Some(response) = self.object_response(Method::GET, hash).await? else {
+            return Ok(None);
+        };
+
+        let time_saved = duration(response.headers())?;
+        let expected_tag = response
+            .headers()
+            .get(TAG_HEADER)
+            .map(|tag| {
+                tag.to_str()
+                    .map(|tag| tag.to_string())
+                    .map_err(|_| CacheError::InvalidTag(Backtrace::capture()))
+            })
+            .transpose()?;
+        let body = HttpCache::read_body(response, progress).await?;
+
+        if let Some(signer_verifier) = &self.signer_verifier {
+            let expected_tag =
+                expected_tag.ok_or(CacheError::ArtifactTagMissing(Backtrace::capture()))?;
+            if !signer_verifier.validate(hash.as_bytes(), &body, &expected_tag)? {
+                return Err(CacheError::InvalidTag(Backtrace::capture()));
+            }
+        }
+
+        let files = HttpCache::restore_tar(&self.repo_root, &body, self.restore_limits)?;
+
+        Ok(Some((
+            CacheResponse {
+                source: CacheSource::Remote,
+                time_saved,
+                bytes_transferred: body.len() as u64,
+            },
+            files,
+        )))
+    }
+
+    /// Requests the object of the artifact, returns `None` for `404 Not
+    /// Found`.
+    async fn object_response(
+        &self,
+        method: Method,
+        hash: &str,
+    ) -> Result<Option<Response>, CacheError> {
+        let response = self.send(method, hash, BTreeMap::new(), None).await?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        response.error_for_status().map(Some).map_err(request_error)
+    }
+
+    /// Sends a signed request for the object of the artifact, with the given
+    /// `x-amz-meta-*` headers and an optional body.
+    async fn send(
+        &self,
+        method: Method,
+        hash: &str,
+        metadata: BTreeMap<String, String>,
+        body: Option<&ProgressBody>,
+    ) -> Result<Response, CacheError> {
+        let (url, uri) = self.object_url(hash);
+        let payload_hash = sigv4::payload_hash(body.map_or(&[][..], |body| body.bytes()));
+
+        let mut headers = metadata;
+        headers.insert("x-amz-content-sha256".to_string(), payload_hash.clone());
+        headers.insert(
+            "x-amz-date".to_string(),
+            Utc::now().format("%Y%m%dT%H%M%SZ").to_string(),
+        );
+        if let Some(session_token) = &self.credentials.session_token {
+            headers.insert("x-amz-security-token".to_string(), session_token.clone());
+        }
+        let authorization = {
+            // The `Host` header is set by the client from the url.
+            let mut signed_headers = headers.clone();
+            signed_headers.insert("host".to_string(), host(&url));
+            sigv4::authorization(
+                &self.credentials.access_key_id,
+                &self.credentials.secret_access_key,
+                &self.region,
+                method.as_str(),
+                &uri,
+                &signed_headers,
+                &payload_hash,
+            )
+        };
+
+        let mut request = self
+            .client
+            .request(method, url)
+            .header("authorization", authorization);
+        for (name, value) in headers {
+            request = request.header(name, value);
+        }
+        if let Some(body) = body {
+            // A streamed body has no length of its own.
+            request = request
+                .header(CONTENT_LENGTH, body.len())
+                .body(body.stream());
+        }
+        request.send().await.map_err(request_error)
+    }
+
+    /// The url of the object of the artifact, and its encoded path, which is
+    /// the canonical uri of the signature.
+    fn object_url(&self, hash: &str) -> (Url, String) {
+        let key = sigv4::uri_encode(&self.key(hash), false);
+        let mut url = self.endpoint.clone();
+        let base_path = url.path().trim_end_matches('/').to_string();
+        let path = if self.path_style {
+            format!(
+                "{base_path}/{}/{key}",
+                sigv4::uri_encode(&self.bucket, true)
+            )
+        } else {
+            let host = format!("{}.{}", self.bucket, url.host_str().unwrap_or_default());
+            // The endpoint has a host, so it can be changed.
+            let _ = url.set_host(Some(&host));
+            format!("{base_path}/{key}")
+        };
+        url.set_path(&path);
+        // The path is encoded already, the url keeps it as it is.
+        let uri = url.path().to_string();
+        (url, uri)
+    }
+}
+
+/// The `Host` header of a request to `url`, which includes the port unless
+/// it's the default port of the scheme.
+fn host(url: &Url) -> String {
+    let host = url.host_str().unwrap_or_default();
+    match url.port() {
+        Some(port) => format!("{host}:{port}"),
+        None => host.to_string(),
+    }
+}
+
+/// The duration stored in the metadata of the object, 0 when it's missing.
+fn duration(headers: &HeaderMap) -> Result<u32, CacheError> {
+    let Some(duration) = headers.get(DURATION_HEADER) else {
+        return Ok(0);
+    };
+    duration
+        .to_str()
+        .ok()
+        .and_then(|duration| duration.parse().ok())
+        .ok_or_else(|| CacheError::InvalidDuration(Backtrace::capture()))
+}
+
+fn request_error(error: reqwest::Error) -> CacheError {
+    turborepo_api_client::Error::ReqwestError(error).into()
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Mutex;
+
+    use anyhow::Result;
+    use reqwest::StatusCode;
+    use tempfile::tempdir;
+    use turbopath::{AbsoluteSystemPathBuf, AnchoredSystemPathBuf};
+
+    use super::{mock::start_test_server, S3Cache, S3Credentials};
+    use crate::{
+        signature_authentication::{ArtifactSignatureAuthenticator, VerificationKey},
+        CacheError, CacheSource,
+    };
+
+    const BUCKET: &str = "turbo-cache";
+    const REGION: &str = "eu-central-1";
+    const ACCESS_KEY_ID: &str = "AKIAMOCK";
+    const SECRET_ACCESS_KEY: &str = "mock/secret";
+
+    fn cache(
+        port: u16,
+        secret_access_key: &str,
+        signer_verifier: Option<ArtifactSignatureAuthenticator>,
+        repo_root: &AbsoluteSystemPathBuf,
+    ) -> Result<S3Cache> {
+        Ok(S3Cache::new(
+            &format!("http://localhost:{port}"),
+            BUCKET,
+            REGION,
+            S3Credentials::new(ACCESS_KEY_ID, secret_access_key),
+            true,
+            signer_verifier,
+            repo_root.to_owned(),
+        )?)
+    }
+
+    fn assert_status(result: Result<impl std::fmt::Debug, CacheError>, status: StatusCode) {
+        match result {
+            Err(CacheError::ApiClientError(error, _)) => match *error {
+                turborepo_api_client::Error::ReqwestError(error) => {
+                    assert_eq!(error.status(), Some(status))
+                }
+                error => panic!("expected a request error, got {error:?}"),
+            },
+            result => panic!("expected a request error, got {result:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_round_trip() -> Result<()> {
+        let port = port_scanner::request_open_port().unwrap();
+        let (handle, state) =
+            start_test_server(port, BUCKET, REGION, (ACCESS_KEY_ID, SECRET_ACCESS_KEY)).await;
+
+        let repo_root = tempdir()?;
+        let repo_root_path = AbsoluteSystemPathBuf::try_from(repo_root.path())?;
+        let file = AnchoredSystemPathBuf::from_raw("package.json")?;
+        std::fs::write(repo_root_path.resolve(&file), "Ugetsu")?;
+
+        let cache =
+            cache(port, SECRET_ACCESS_KEY, None, &repo_root_path)?.with_prefix("ci/artifacts/");
+        assert_eq!(
+            cache.key("Sansho the Bailiff"),
+            "ci/artifacts/Sansho the Bailiff"
+        );
+
+        let reports = Mutex::new(Vec::new());
+        let reporter = |transferred, total| reports.lock().unwrap().push((transferred, total));
+        let put_response = cache
+            .put(
+                &repo_root_path,
+                "Sansho the Bailiff",
+                vec![file.clone()],
+                1954,
+                Some(&reporter),
+            )
+            .await?;
+        // The total of the upload progress is known from the start.
+        let size = Some(put_response.bytes_transferred);
+        let reports = reports.into_inner().unwrap();
+        assert!(reports.iter().all(|&(_, total)| total == size));
+        assert_eq!(
+            reports.last(),
+            Some(&(put_response.bytes_transferred, size))
+        );
+        {
+            let state = state.lock().unwrap();
+            let object = state
+                .object(BUCKET, "ci/artifacts/Sansho%20the%20Bailiff")
+                .unwrap();
+            assert_eq!(object.data.len() as u64, put_response.bytes_transferred);
+            assert_eq!(object.metadata["x-amz-meta-duration"], "1954");
+            assert!(!object.metadata.contains_key("x-amz-meta-artifact-tag"));
+        }
+
+        let cache_response = cache.exists("Sansho the Bailiff").await?.unwrap();
+        assert_eq!(cache_response.source, CacheSource::Remote);
+        assert_eq!(cache_response.time_saved, 1954);
+        assert_eq!(cache_response.bytes_transferred, 0);
+
+        std::fs::remove_file(repo_root_path.resolve(&file))?;
+        let (cache_response, files) = cache.retrieve("Sansho the Bailiff", None).await?.unwrap();
+        assert_eq!(cache_response.time_saved, 1954);
+        assert_eq!(
+            cache_response.bytes_transferred,
+            put_response.bytes_transferred
+        );
+        assert_eq!(files, vec![file.clone()]);
+        assert_eq!(
+            std::fs::read_to_string(repo_root_path.resolve(&file))?,
+            "Ugetsu"
+        );
+
+        handle.abort();
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_cache_miss() -> Result<()> {
+        let port = port_scanner::request_open_port().unwrap();
+        let (handle, _) =
+            start_test_server(port, BUCKET, REGION, (ACCESS_KEY_ID, SECRET_ACCESS_KEY)).await;
+
+        let repo_root = tempdir()?;
+        let repo_root_path = AbsoluteSystemPathBuf::try_from(repo_root.path())?;
+        let cache = cache(port, SECRET_ACCESS_KEY, None, &repo_root_path)?;
+
+        assert_eq!(cache.exists("Street of Shame").await?, None);
+        assert_eq!(cache.retrieve("Street of Shame", None).await?, None);
+        assert_eq!(std::fs::read_dir(repo_root.path())?.count(), 0);
+
+        handle.abort();
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_bad_signature() -> Result<()> {
+        let port = port_scanner::request_open_port().unwrap();
+        let (handle, state) =
+            start_test_server(port, BUCKET, REGION, (ACCESS_KEY_ID, SECRET_ACCESS_KEY)).await;
+
+        let repo_root = tempdir()?;
+        let repo_root_path = AbsoluteSystemPathBuf::try_from(repo_root.path())?;
+        let file = AnchoredSystemPathBuf::from_raw("package.json")?;
+        std::fs::write(repo_root_path.resolve(&file), "The Life of Oharu")?;
+
+        let cache = cache(port, "wrong secret", None, &repo_root_path)?;
+        assert_status(
+            cache
+                .put(&repo_root_path, "Oharu", vec![file], 10, None)
+                .await,
+            StatusCode::FORBIDDEN,
+        );
+        assert!(state.lock().unwrap().object(BUCKET, "Oharu").is_none());
+        // A missing object isn't reported before the signature is checked.
+        assert_status(cache.exists("Oharu").await, StatusCode::FORBIDDEN);
+        assert_status(cache.retrieve("Oharu", None).await, StatusCode::FORBIDDEN);
+
+        handle.abort();
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_signature_authentication() -> Result<()> {
+        let port = port_scanner::request_open_port().unwrap();
+        let (handle, state) =
+            start_test_server(port, BUCKET, REGION, (ACCESS_KEY_ID, SECRET_ACCESS_KEY)).await;
+
+        let repo_root = tempdir()?;
+        let repo_root_path = AbsoluteSystemPathBuf::try_from(repo_root.path())?;
+        let file = AnchoredSystemPathBuf::from_raw("package.json")?;
+        std::fs::write(repo_root_path.resolve(&file), "Chikamatsu Monogatari")?;
+
+        let signer_verifier = || {
+            Some(ArtifactSignatureAuthenticator::new(
+                b"team_mizoguchi".to_vec(),
+                Some(b"signature secret".to_vec()),
+            ))
+        };
+        let cache = cache(port, SECRET_ACCESS_KEY, signer_verifier(), &repo_root_path)?;
+        cache
+            .put(&repo_root_path, "Crucified Lovers", vec![file], 10, None)
+            .await?;
+        assert!(state
+            .lock()
+            .unwrap()
+            .object(BUCKET, "Crucified%20Lovers")
+            .unwrap()
+            .metadata
+            .contains_key("x-amz-meta-artifact-tag"));
+        assert!(cache.retrieve("Crucified Lovers", None).await?.is_some());
+
+        // A tampered artifact is rejected.
+        let original = state
+            .lock()
+            .unwrap()
+            .object(BUCKET, "Crucified%20Lovers")
+            .cloned()
+            .unwrap();
+        state
+            .lock()
+            .unwrap()
+            .object_mut(BUCKET, "Crucified%20Lovers")
+            .unwrap()
+            .data
+            .push(0);
+        assert!(matches!(
+            cache.retrieve("Crucified Lovers", None).await,
+            Err(CacheError::InvalidTag(..))
+        ));
+
+        // So is an artifact without a tag.
+        {
+            let mut state = state.lock().unwrap();
+            let object = state.object_mut(BUCKET, "Crucified%20Lovers").unwrap();
+            *object = original;
+            object.metadata.remove("x-amz-meta-artifact-tag");
+        }
+        assert!(matches!(
+            cache.retrieve("Crucified Lovers", None).await,
+            Err(CacheError::ArtifactTagMissing(..))
+        ));
+
+        handle.abort();
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_signature_key_rotation() -> Result<()> {
+        let port = port_scanner::request_open_port().unwrap();
+        let (handle, state) =
+            start_test_server(port, BUCKET, REGION, (ACCESS_KEY_ID, SECRET_ACCESS_KEY)).await;
+
+        let repo_root = tempdir()?;
+        let repo_root_path = AbsoluteSystemPathBuf::try_from(repo_root.path())?;
+        let file = AnchoredSystemPathBuf::from_raw("package.json")?;
+        std::fs::write(repo_root_path.resolve(&file), "Sansho the Bailiff")?;
+
+        let signer_verifier = |secret: &[u8], key_id: &str| {
+            ArtifactSignatureAuthenticator::new(b"team_mizoguchi".to_vec(), Some(secret.to_vec()))
+                .with_key_id(key_id)
+        };
+        let old_cache = cache(
+            port,
+            SECRET_ACCESS_KEY,
+            Some(signer_verifier(b"old secret", "v1")),
+            &repo_root_path,
+        )?;
+        old_cache
+            .put(&repo_root_path, "Ugetsu", vec![file.clone()], 10, None)
+            .await?;
+
+        // During the rotation, artifacts signed with the old key are restored,
+        // and new artifacts are signed with the new key.
+        let rotating_cache = cache(
+            port,
+            SECRET_ACCESS_KEY,
+            Some(
+                signer_verifier(b"new secret", "v2").with_verification_key(VerificationKey {
+                    id: Some("v1".to_string()),
+                    secret: b"old secret".to_vec(),
+                }),
+            ),
+            &repo_root_path,
+        )?;
+        assert!(rotating_cache.retrieve("Ugetsu", None).await?.is_some());
+        rotating_cache
+            .put(&repo_root_path, "Street of Shame", vec![file], 10, None)
+            .await?;
+        let tag = state
+            .lock()
+            .unwrap()
+            .object(BUCKET, "Street%20of%20Shame")
+            .unwrap()
+            .metadata
+            .get("x-amz-meta-artifact-tag")
+            .cloned()
+            .unwrap();
+        assert!(tag.starts_with("v2:"), "{tag}");
+        assert!(matches!(
+            old_cache.retrieve("Street of Shame", None).await,
+            Err(CacheError::InvalidTag(..))
+        ));
+
+        // A tampered artifact doesn't match any of the keys.
+        state
+            .lock()
+            .unwrap()
+            .object_mut(BUCKET, "Ugetsu")
+            .unwrap()
+            .data
+            .push(0);
+        assert!(matches!(
+            rotating_cache.retrieve("Ugetsu", None).await,
+            Err(CacheError::InvalidTag(..))
+        ));
+
+        // Once the old key is removed, its artifacts are rejected.
+        state
+            .lock()
+            .unwrap()
+            .object_mut(BUCKET, "Ugetsu")
+            .unwrap()
+            .data
+            .pop();
+        let new_cache = cache(
+            port,
+            SECRET_ACCESS_KEY,
+            Some(signer_verifier(b"new secret", "v2")),
+            &repo_root_path,
+        )?;
+        assert!(matches!(
+            new_cache.retrieve("Ugetsu", None).await,
+            Err(CacheError::InvalidTag(..))
+        ));
+        assert!(new_cache.retrieve("Street of Shame", None).await?.is_some());
+
+        handle.abort();
+        Ok(())
+    }
+
+    #[test]
+    fn test_object_url() -> Result<()> {
+        let repo_root = tempdir()?;
+        let repo_root_path = AbsoluteSystemPathBuf::try_from(repo_root.path())?;
+        let credentials = S3Credentials::new(ACCESS_KEY_ID, SECRET_ACCESS_KEY);
+
+        let virtual_hosted = S3Cache::new(
+            "https://s3.eu-central-1.amazonaws.com",
+            BUCKET,
+            REGION,
+            credentials.clone(),
+            false,
+            None,
+            repo_root_path.clone(),
+        )?
+        .with_prefix("turbo/");
+        let (url, uri) = virtual_hosted.object_url("a b");
+        assert_eq!(
+            url.as_str(),
+            "https://turbo-cache.s3.eu-central-1.amazonaws.com/turbo/a%20b"
+        );
+        assert_eq!(uri, "/turbo/a%20b");
+
+        let path_style = S3Cache::new(
+            "http://minio.internal:9000/storage/",
+            BUCKET,
+            REGION,
+            credentials.clone(),
+            true,
+            None,
+            repo_root_path.clone(),
+        )?;
+        let (url, uri) = path_style.object_url("a b");
+        assert_eq!(
+            url.as_str(),
+            "http://minio.internal:9000/storage/turbo-cache/a%20b"
+        );
+        assert_eq!(uri, "/storage/turbo-cache/a%20b");
+        assert_eq!(super::host(&url), "minio.internal:9000");
+
+        assert!(matches!(
+            S3Cache::new(
+                "minio.internal",
+                BUCKET,
+                REGION,
+                credentials,
+                true,
+                None,
+                repo_root_path,
+            ),
+            Err(CacheError::InvalidEndpoint(..))
+        ));
+        Ok(())
+    }
+}
@@ -23,11 +23,29 @@ pub enum SignatureError {
     Hmac(#[from] hmac::digest::InvalidLength),
 }
 
+/// Separates the key id from the signature in tags, e.g. `2024-01:<base64>`.
+/// It doesn't occur in base64.
+const KEY_ID_SEPARATOR: char = ':';
+
 #[derive(Debug)]
 pub struct ArtifactSignatureAuthenticator {
     team_id: Vec<u8>,
     // An override for testing purposes (to avoid env var race conditions)
     secret_key_override: Option<Vec<u8>>,
+    // The id of the secret key, embedded into the tags it generates.
+    key_id: Option<String>,
+    // Keys which are only used to validate tags, e.g. the previous secret key
+    // while it's rotated.
+    verification_keys: Vec<VerificationKey>,
+}
+
+/// A key which is accepted when validating tags, but not used to generate
+/// them.
+#[derive(Debug, Clone)]
+pub struct VerificationKey {
+    /// The id which tags generated with the key embed, if any.
+    pub id: Option<String>,
+    pub secret: Vec<u8>,
 }
 
 impl ArtifactSignatureAuthenticator {
@@ -35,9 +53,25 @@ impl ArtifactSignatureAuthenticator {
         Self {
             team_id,
             secret_key_override,
+            key_id: None,
+            verification_keys: Vec::new(),
         }
     }
 
+    /// Embeds `key_id` into the generated tags, so validation can pick the
+    /// matching key directly once the secret key is rotated.
+    pub fn with_key_id(mut self, key_id: impl Into<String>) -> Self {
+        self.key_id = Some(key_id.into());
+        self
+    }
+
+    /// Also accepts tags generated with `key` when validating. Verification
+    /// keys are tried in the order they are added, after the secret key.
+    pub fn with_verification_key(mut self, key: VerificationKey) -> Self {
+        self.verification_keys.push(key);
+        self
+    }
+
     // Gets secret key from either secret key override or environment variable.
     // HMAC_SHA256 has no key length limit, although it's generally recommended
     // to keep key length under 64 bytes since anything longer is hashed using
@@ -60,7 +94,15 @@ impl ArtifactSignatureAuthenticator {
     }
 
     fn get_tag_generator(&self, hash: &[u8]) -> Result<HmacSha256, SignatureError> {
-        let mut mac = HmacSha256::new_from_slice(&self.secret_key()?)?;
+        self.get_tag_generator_with_key(hash, &self.secret_key()?)
+    }
+
+    fn get_tag_generator_with_key(
+        &self,
+        hash: &[u8],
+        secret_key: &[u8],
+    ) -> Result<HmacSha256, SignatureError> {
+        let mut mac = HmacSha256::new_from_slice(secret_key)?;
         let metadata = self.construct_metadata(hash)?;
 
         mac.update(&metadata);
@@ -85,26 +127,87 @@ impl ArtifactSignatureAuthenticator {
         hash: &[u8],
         artifact_body: &[u8],
     ) -> Result<String, SignatureError> {
-        let mut hmac_ctx = self.get_tag_generator(hash)?;
+        let mut hasher = self.start(hash)?;
+        hasher.update(artifact_body);
+        Ok(hasher.finalize())
+    }
 
-        hmac_ctx.update(artifact_body);
-        let hmac_output = hmac_ctx.finalize();
-        Ok(BASE64_STANDARD.encode(hmac_output.into_bytes()))
+    /// Starts generating the tag of an artifact whose body is passed in parts,
+    /// e.g. while it's streamed. The finalized tag is the same as the tag
+    /// generated by `generate_tag` for the concatenated parts.
+    pub fn start(&self, hash: &[u8]) -> Result<TagHasher, SignatureError> {
+        Ok(TagHasher {
+            mac: self.get_tag_generator(hash)?,
+            key_id: self.key_id.clone(),
+        })
     }
 
+    // Validates the tag against the secret key and the verification keys. When
+    // the tag embeds the id of one of them, only that key is tried.
     pub fn validate(
         &self,
         hash: &[u8],
         artifact_body: &[u8],
         expected_tag: &str,
     ) -> Result<bool, SignatureError> {
-        let mut mac = HmacSha256::new_from_slice(&self.secret_key()?)?;
-        let message = self.construct_metadata(hash)?;
-        mac.update(&message);
-        mac.update(artifact_body);
-
+        let (key_id, expected_tag) = match expected_tag.split_once(KEY_ID_SEPARATOR) {
+            Some((key_id, tag)) => (Some(key_id), tag),
+            None => (None, expected_tag),
+        };
         let expected_bytes = BASE64_STANDARD.decode(expected_tag)?;
-        Ok(mac.verify_slice(&expected_bytes).is_ok())
+
+        for secret_key in self.candidate_keys(key_id)? {
+            let mut mac = self.get_tag_generator_with_key(hash, &secret_key)?;
+            mac.update(artifact_body);
+            if mac.verify_slice(&expected_bytes).is_ok() {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    // The keys to validate a tag with. A key id that isn't configured, e.g.
+    // because the tag was generated with a newer key, falls back to all keys.
+    fn candidate_keys(&self, key_id: Option<&str>) -> Result<Vec<Vec<u8>>, SignatureError> {
+        let secret_key = self.secret_key()?;
+        if let Some(key_id) = key_id {
+            if self.key_id.as_deref() == Some(key_id) {
+                return Ok(vec![secret_key]);
+            }
+            if let Some(key) = self
+                .verification_keys
+                .iter()
+                .find(|key| key.id.as_deref() == Some(key_id))
+            {
+                return Ok(vec![key.secret.clone()]);
+            }
+        }
+        Ok(std::iter::once(secret_key)
+            .chain(self.verification_keys.iter().map(|key| key.secret.clone()))
+            .collect())
+    }
+}
+
+/// Generates the tag of an artifact from the parts of its body, see
+/// [`ArtifactSignatureAuthenticator::start`].
+pub struct TagHasher {
+    mac: HmacSha256,
+    key_id: Option<String>,
+}
+
+impl TagHasher {
+    pub fn update(&mut self, bytes: &[u8]) {
+        self.mac.update(bytes);
+    }
+
+    /// Returns the base64 encoded tag, prefixed with the key id if there is
+    /// one.
+    pub fn finalize(self) -> String {
+        let tag = BASE64_STANDARD.encode(self.mac.finalize().into_bytes());
+        match self.key_id {
+            Some(key_id) => format!("{key_id}{KEY_ID_SEPARATOR}{tag}"),
+            None => tag,
+        }
     }
 }
 
@@ -240,12 +343,120 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_incremental_tag() -> Result<()> {
+        let signature =
+            ArtifactSignatureAuthenticator::new(b"team".to_vec(), Some(b"secret".to_vec()));
+        let hash = b"d5b7e4688f";
+        // A few megabytes of non-repeating bytes.
+        let artifact_body: Vec<u8> = (0..5_000_000u32)
+            .map(|i| (i.wrapping_mul(2_654_435_761) >> 24) as u8)
+            .collect();
+        let tag = signature.generate_tag(hash, &artifact_body)?;
+
+        for chunk_size in [1, 1000, 65_536, 1_048_576, artifact_body.len()] {
+            let mut hasher = signature.start(hash)?;
+            for chunk in artifact_body.chunks(chunk_size) {
+                hasher.update(chunk);
+            }
+            assert_eq!(hasher.finalize(), tag, "chunk size {chunk_size}");
+        }
+
+        // Empty parts don't change the tag.
+        let mut hasher = signature.start(hash)?;
+        hasher.update(&[]);
+        hasher.update(&artifact_body);
+        hasher.update(&[]);
+        assert_eq!(hasher.finalize(), tag);
+
+        assert!(signature.validate(hash, &artifact_body, &tag)?);
+        Ok(())
+    }
+
+    fn authenticator(secret_key: &[u8]) -> ArtifactSignatureAuthenticator {
+        ArtifactSignatureAuthenticator::new(b"team".to_vec(), Some(secret_key.to_vec()))
+    }
+
+    #[test]
+    fn test_key_rotation() -> Result<()> {
+        let hash = b"d5b7e4688f";
+        let artifact_body = b"artifact body";
+        let old_tag = authenticator(b"old secret").generate_tag(hash, artifact_body)?;
+
+        // During the rotation, tags of the old key are still accepted, but new
+        // tags are generated with the new key.
+        let rotating = authenticator(b"new secret").with_verification_key(VerificationKey {
+            id: None,
+            secret: b"old secret".to_vec(),
+        });
+        assert!(rotating.validate(hash, artifact_body, &old_tag)?);
+        let new_tag = rotating.generate_tag(hash, artifact_body)?;
+        assert_ne!(new_tag, old_tag);
+        assert!(rotating.validate(hash, artifact_body, &new_tag)?);
+
+        // Once the old key is removed, its tags are rejected.
+        assert!(!authenticator(b"new secret").validate(hash, artifact_body, &old_tag)?);
+
+        // A tampered body doesn't match any of the keys.
+        assert!(!rotating.validate(hash, b"tampered body", &old_tag)?);
+        assert!(!rotating.validate(hash, b"tampered body", &new_tag)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_key_id() -> Result<()> {
+        let hash = b"d5b7e4688f";
+        let artifact_body = b"artifact body";
+        let old = authenticator(b"old secret").with_key_id("v1");
+        let old_tag = old.generate_tag(hash, artifact_body)?;
+        assert!(old_tag.starts_with("v1:"), "{old_tag}");
+        assert!(old.validate(hash, artifact_body, &old_tag)?);
+
+        let rotating = authenticator(b"new secret")
+            .with_key_id("v2")
+            .with_verification_key(VerificationKey {
+                id: Some("v0".to_string()),
+                secret: b"older secret".to_vec(),
+            })
+            .with_verification_key(VerificationKey {
+                id: Some("v1".to_string()),
+                secret: b"old secret".to_vec(),
+            });
+        let new_tag = rotating.generate_tag(hash, artifact_body)?;
+        assert!(new_tag.starts_with("v2:"), "{new_tag}");
+
+        // The key id picks the key directly.
+        assert_eq!(
+            rotating.candidate_keys(Some("v1"))?,
+            vec![b"old secret".to_vec()]
+        );
+        assert_eq!(
+            rotating.candidate_keys(Some("v2"))?,
+            vec![b"new secret".to_vec()]
+        );
+        assert!(rotating.validate(hash, artifact_body, &old_tag)?);
+        assert!(rotating.validate(hash, artifact_body, &new_tag)?);
+
+        // Unknown key ids fall back to trying every key.
+        assert_eq!(rotating.candidate_keys(Some("v3"))?.len(), 3);
+        let untagged = old_tag.strip_prefix("v1:").unwrap();
+        assert!(rotating.validate(hash, artifact_body, untagged)?);
+        assert!(rotating.validate(hash, artifact_body, &format!("v3:{untagged}"))?);
+
+        // A tag with the id of another key is rejected.
+        assert!(!rotating.validate(hash, artifact_body, &format!("v0:{untagged}"))?);
+        assert!(!rotating.validate(hash, b"tampered body", &old_tag)?);
+
+        // The incremental tag embeds the key id as well.
+        let mut hasher = rotating.start(hash)?;
+        hasher.update(artifact_body);
+        assert_eq!(hasher.finalize(), new_tag);
+        Ok(())
+    }
+
     fn test_signature(test_case: TestCase) -> Result<()> {
         env::set_var("TURBO_REMOTE_CACHE_SIGNATURE_KEY", test_case.secret_key);
-        let signature = ArtifactSignatureAuthenticator {
-            team_id: test_case.team_id.to_vec(),
-            secret_key_override: None,
-        };
+        let signature = ArtifactSignatureAuthenticator::new(test_case.team_id.to_vec(), None);
 
         let hash = test_case.artifact_hash;
         let artifact_body = &test_case.artifact_body;
@@ -33,10 +33,15 @@ pub const EXPECTED_SPACE_NAME: &str = "expected_space_name";
 pub const EXPECTED_SSO_TEAM_ID: &str = "expected_sso_team_id";
 pub const EXPECTED_SSO_TEAM_SLUG: &str = "expected_sso_team_slug";
 
+/// Reading the artifact with this hash is forbidden.
+pub const FORBIDDEN_ARTIFACT_HASH: &str = "forbidden_artifact_hash";
+
 pub async fn start_test_server(port: u16) -> Result<()> {
     let get_durations_ref = Arc::new(Mutex::new(HashMap::new()));
     let head_durations_ref = get_durations_ref.clone();
     let put_durations_ref = get_durations_ref.clone();
+    let put_traceparents_ref = Arc::new(Mutex::new(HashMap::new()));
+    let head_traceparents_ref = put_traceparents_ref.clone();
     let put_tempdir_ref = Arc::new(tempfile::tempdir()?);
     let get_tempdir_ref = put_tempdir_ref.clone();
 
@@ -119,6 +124,13 @@ pub async fn start_test_server(port: u16) -> Result<()> {
                     let mut durations_map = put_durations_ref.lock().await;
                     durations_map.insert(hash.clone(), duration);
 
+                    if let Some(traceparent) = headers.get("traceparent") {
+                        put_traceparents_ref
+                            .lock()
+                            .await
+                            .insert(hash.clone(), traceparent.clone());
+                    }
+
                     while let Some(item) = body.next().await {
                         let chunk = item.unwrap();
                         file.write_all(&chunk).unwrap();
@@ -130,44 +142,59 @@ pub async fn start_test_server(port: u16) -> Result<()> {
         )
         .route(
             "/v8/artifacts/:hash",
-            get(|Path(hash): Path<String>| async move {
-                let root_path = get_tempdir_ref.path();
-                let file_path = root_path.join(&hash);
-                let buffer = std::fs::read(file_path).unwrap();
-                let duration = get_durations_ref
-                    .lock()
-                    .await
-                    .get(&hash)
-                    .cloned()
-                    .unwrap_or(0);
-                let mut headers = HeaderMap::new();
+            get(
+                |Path(hash): Path<String>, request_headers: HeaderMap| async move {
+                    if hash == FORBIDDEN_ARTIFACT_HASH {
+                        return Err(StatusCode::FORBIDDEN);
+                    }
+                    let root_path = get_tempdir_ref.path();
+                    let file_path = root_path.join(&hash);
+                    let Ok(buffer) = std::fs::read(file_path) else {
+                        return Err(StatusCode::NOT_FOUND);
+                    };
+                    let duration = get_durations_ref
+                        .lock()
+                        .await
+                        .get(&hash)
+                        .cloned()
+                        .unwrap_or(0);
+                    let mut headers = HeaderMap::new();
 
-                headers.insert(
-                    "x-artifact-duration",
-                    HeaderValue::from_str(&duration.to_string()).unwrap(),
-                );
+                    headers.insert(
+                        "x-artifact-duration",
+                        HeaderValue::from_str(&duration.to_string()).unwrap(),
+                    );
+                    echo_traceparent(&request_headers, &mut headers);
 
-                (headers, buffer)
-            }),
+                    Ok((headers, buffer))
+                },
+            ),
         )
         .route(
             "/v8/artifacts/:hash",
-            head(|Path(hash): Path<String>| async move {
-                let duration = head_durations_ref
-                    .lock()
-                    .await
-                    .get(&hash)
-                    .cloned()
-                    .unwrap_or(0);
-                let mut headers = HeaderMap::new();
+            head(
+                |Path(hash): Path<String>, request_headers: HeaderMap| async move {
+                    if hash == FORBIDDEN_ARTIFACT_HASH {
+                        return Err(StatusCode::FORBIDDEN);
+                    }
+                    let Some(duration) = head_durations_ref.lock().await.get(&hash).cloned() else {
+                        return Err(StatusCode::NOT_FOUND);
+                    };
+                    let mut headers = HeaderMap::new();
 
-                headers.insert(
-                    "x-artifact-duration",
-                    HeaderValue::from_str(&duration.to_string()).unwrap(),
-                );
+                    headers.insert(
+                        "x-artifact-duration",
+                        HeaderValue::from_str(&duration.to_string()).unwrap(),
+                    );
+                    echo_traceparent(&request_headers, &mut headers);
+                    // Lets tests check the headers that the artifact was uploaded with
+                    if let Some(traceparent) = head_traceparents_ref.lock().await.get(&hash) {
+                        headers.insert("x-artifact-traceparent", traceparent.clone());
+                    }
 
-                headers
-            }),
+                    Ok(headers)
+                },
+            ),
         )
         .route(
             "/preflight/absolute-location",
@@ -223,3 +250,11 @@ pub async fn start_test_server(port: u16) -> Result<()> {
 
     Ok(())
 }
+
+/// Returns the `traceparent` of the request as `traceresponse`, so tests can
+/// check that it was sent.
+fn echo_traceparent(request_headers: &HeaderMap, headers: &mut HeaderMap) {
+    if let Some(traceparent) = request_headers.get("traceparent") {
+        headers.insert("traceresponse", traceparent.clone());
+    }
+}